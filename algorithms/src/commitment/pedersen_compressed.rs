@@ -54,6 +54,41 @@ impl<G: Group + ProjectiveCurve, S: PedersenSize> CommitmentScheme for PedersenC
         Ok(affine.to_x_coordinate())
     }
 
+    /// Returns the affine x-coordinates of the commitments, sharing the window tables of the parameters
+    /// across the inputs and normalizing the commitments with a single batch inversion.
+    fn batch_commit(
+        &self,
+        inputs: &[Vec<u8>],
+        randomness: &[Self::Randomness],
+    ) -> Result<Vec<Self::Output>, CommitmentError> {
+        if inputs.len() != randomness.len() {
+            return Err(CommitmentError::Message(format!(
+                "expected randomness for {} inputs, found {}",
+                inputs.len(),
+                randomness.len()
+            )));
+        }
+
+        let commitment = PedersenCommitment::<G, S> {
+            parameters: self.parameters.clone(),
+        };
+
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for (input, randomness) in inputs.iter().zip(randomness) {
+            outputs.push(commitment.commit(input, randomness)?);
+        }
+        G::batch_normalization(&mut outputs);
+
+        Ok(outputs
+            .into_iter()
+            .map(|output| {
+                let affine = output.into_affine();
+                debug_assert!(affine.is_in_correct_subgroup_assuming_on_curve());
+                affine.to_x_coordinate()
+            })
+            .collect())
+    }
+
     fn parameters(&self) -> &Self::Parameters {
         &self.parameters
     }
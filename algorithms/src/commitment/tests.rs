@@ -22,10 +22,11 @@ use snarkos_curves::edwards_bls12::EdwardsProjective;
 use snarkos_models::algorithms::CommitmentScheme;
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
+    rand::UniformRand,
     to_bytes,
 };
 
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -58,3 +59,33 @@ fn pedersen_commitment_parameters_serialization() {
 fn pedersen_compressed_commitment_parameters_serialization() {
     commitment_parameters_serialization::<PedersenCompressedCommitment<EdwardsProjective, Size>>();
 }
+
+fn commitment_batch_commit<C: CommitmentScheme>() {
+    let rng = &mut XorShiftRng::seed_from_u64(1231275789u64);
+
+    let commitment = C::setup(rng);
+
+    let inputs: Vec<Vec<u8>> = (0..5)
+        .map(|_| (0..Size::WINDOW_SIZE).map(|_| rng.gen()).collect())
+        .collect();
+    let randomness: Vec<C::Randomness> = (0..5).map(|_| C::Randomness::rand(rng)).collect();
+
+    let commitments = commitment.batch_commit(&inputs, &randomness).unwrap();
+    assert_eq!(commitments.len(), inputs.len());
+    for ((input, randomness), batch_commitment) in inputs.iter().zip(&randomness).zip(&commitments) {
+        assert_eq!(&commitment.commit(input, randomness).unwrap(), batch_commitment);
+    }
+
+    assert!(commitment.batch_commit(&inputs, &randomness[1..]).is_err());
+    assert!(commitment.batch_commit(&[], &[]).unwrap().is_empty());
+}
+
+#[test]
+fn pedersen_commitment_batch_commit() {
+    commitment_batch_commit::<PedersenCommitment<EdwardsProjective, Size>>();
+}
+
+#[test]
+fn pedersen_compressed_commitment_batch_commit() {
+    commitment_batch_commit::<PedersenCompressedCommitment<EdwardsProjective, Size>>();
+}
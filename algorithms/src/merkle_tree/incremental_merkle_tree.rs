@@ -0,0 +1,425 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::merkle_tree::{MerklePath, MerkleTreeDigest};
+use snarkos_errors::algorithms::MerkleError;
+use snarkos_models::algorithms::{MerkleParameters, CRH};
+use snarkos_utilities::ToBytes;
+
+/// A Merkle tree that appends and removes leaves in O(log n) hashes.
+///
+/// The tree has the same root and paths as a `MerkleTree` built from the same leaves.
+/// Only the nodes covering at least one leaf are stored. The rightmost node of each level
+/// forms the frontier of the tree, which is the only part rehashed when leaves are appended.
+#[derive(Clone)]
+pub struct IncrementalMerkleTree<P: MerkleParameters> {
+    /// The computed root of the full Merkle tree.
+    root: MerkleTreeDigest<P>,
+
+    /// The hashes of the nodes covering at least one leaf, level by level from the hashed leaves up.
+    levels: Vec<Vec<MerkleTreeDigest<P>>>,
+
+    /// The hash of an empty subtree at each level.
+    empty_hashes: Vec<MerkleTreeDigest<P>>,
+
+    /// The Merkle tree parameters (e.g. the hash function).
+    parameters: P,
+}
+
+impl<P: MerkleParameters> IncrementalMerkleTree<P> {
+    pub const DEPTH: u8 = P::DEPTH as u8;
+
+    pub fn new<L: ToBytes>(parameters: P, leaves: &[L]) -> Result<Self, MerkleError> {
+        let mut tree = Self::from_levels(parameters, vec![vec![]])?;
+        tree.append(leaves)?;
+
+        Ok(tree)
+    }
+
+    /// Returns a tree from previously computed levels, such as the ones returned by `levels`.
+    pub fn from_levels(parameters: P, levels: Vec<Vec<MerkleTreeDigest<P>>>) -> Result<Self, MerkleError> {
+        let num_leaves = levels.first().map(|leaves| leaves.len()).unwrap_or(0);
+        let depth = tree_depth(num_leaves);
+
+        if depth > Self::DEPTH as usize {
+            return Err(MerkleError::InvalidTreeDepth(depth, Self::DEPTH as usize));
+        }
+
+        // Check that every level covers the leaves.
+        if levels.len() != depth + 1
+            || levels
+                .iter()
+                .enumerate()
+                .any(|(level, nodes)| nodes.len() != level_size(num_leaves, level))
+        {
+            return Err(MerkleError::Message("invalid Merkle tree levels".into()));
+        }
+
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        let mut empty_hashes = Vec::with_capacity(Self::DEPTH as usize + 1);
+        empty_hashes.push(parameters.hash_empty()?);
+        for level in 0..Self::DEPTH as usize {
+            let empty_hash = parameters.hash_inner_node(&empty_hashes[level], &empty_hashes[level], &mut buffer)?;
+            empty_hashes.push(empty_hash);
+        }
+
+        let mut tree = Self {
+            root: empty_hashes[0].clone(),
+            levels,
+            empty_hashes,
+            parameters,
+        };
+        tree.root = tree.compute_root(&mut buffer)?;
+
+        Ok(tree)
+    }
+
+    #[inline]
+    pub fn root(&self) -> <P::H as CRH>::Output {
+        self.root.clone()
+    }
+
+    /// Returns the number of leaves in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns true if there are no leaves in the tree.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn hashed_leaves(&self) -> Vec<<P::H as CRH>::Output> {
+        self.levels[0].clone()
+    }
+
+    /// Returns the hashes of the nodes covering at least one leaf, level by level from the hashed leaves up.
+    #[inline]
+    pub fn levels(&self) -> &[Vec<MerkleTreeDigest<P>>] {
+        &self.levels
+    }
+
+    /// Returns the hash of the node at a given index in a given level, if it covers at least one leaf.
+    #[inline]
+    pub fn node(&self, level: usize, index: usize) -> Option<&MerkleTreeDigest<P>> {
+        self.levels.get(level).and_then(|nodes| nodes.get(index))
+    }
+
+    /// Appends leaves to the tree, and returns the (level, index) positions of the nodes that were updated.
+    pub fn append<L: ToBytes>(&mut self, leaves: &[L]) -> Result<Vec<(usize, usize)>, MerkleError> {
+        if leaves.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let first_leaf_index = self.len();
+        let num_leaves = first_leaf_index + leaves.len();
+        let depth = tree_depth(num_leaves);
+
+        if depth > Self::DEPTH as usize {
+            return Err(MerkleError::InvalidTreeDepth(depth, Self::DEPTH as usize));
+        }
+
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        for leaf in leaves {
+            let leaf_hash = self.parameters.hash_leaf(leaf, &mut buffer)?;
+            self.levels[0].push(leaf_hash);
+        }
+
+        let mut updated_nodes: Vec<(usize, usize)> = (first_leaf_index..num_leaves).map(|index| (0, index)).collect();
+
+        // Only the nodes on the right of the first new leaf change.
+        for level in 1..=depth {
+            let first_index = first_leaf_index >> level;
+            self.update_level(level, first_index, &mut buffer)?;
+            updated_nodes.extend((first_index..self.levels[level].len()).map(|index| (level, index)));
+        }
+
+        self.root = self.compute_root(&mut buffer)?;
+
+        Ok(updated_nodes)
+    }
+
+    /// Removes the leaves after the given number of leaves, and returns the (level, index) positions
+    /// of the nodes that were updated. The positions of the removed nodes are past the end of each level.
+    pub fn truncate(&mut self, num_leaves: usize) -> Result<Vec<(usize, usize)>, MerkleError> {
+        if num_leaves >= self.len() {
+            return Ok(vec![]);
+        }
+
+        let depth = tree_depth(num_leaves);
+
+        self.levels.truncate(depth + 1);
+        self.levels[0].truncate(num_leaves);
+
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        // Only the rightmost node of each level changes.
+        let mut updated_nodes = Vec::with_capacity(depth);
+        for level in 1..=depth {
+            let last_index = (num_leaves - 1) >> level;
+            self.update_level(level, last_index, &mut buffer)?;
+            updated_nodes.push((level, last_index));
+        }
+
+        self.root = self.compute_root(&mut buffer)?;
+
+        Ok(updated_nodes)
+    }
+
+    pub fn generate_proof<L: ToBytes>(&self, index: usize, leaf: &L) -> Result<MerklePath<P>, MerkleError> {
+        let prove_time = start_timer!(|| "IncrementalMerkleTree::generate_proof");
+
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        let leaf_hash = self.parameters.hash_leaf(leaf, &mut buffer)?;
+
+        // Check that the given index corresponds to the correct leaf.
+        if self.levels[0].get(index) != Some(&leaf_hash) {
+            return Err(MerkleError::IncorrectLeafIndex(index));
+        }
+
+        // Iterate from the leaf up to the top of the tree, storing all intermediate hash values.
+        let depth = self.levels.len() - 1;
+        let mut path = Vec::with_capacity(Self::DEPTH as usize);
+        for level in 0..depth {
+            let node_index = index >> level;
+            let node_hash = self.levels[level][node_index].clone();
+            let sibling_hash = self.levels[level]
+                .get(node_index ^ 1)
+                .unwrap_or(&self.empty_hashes[level])
+                .clone();
+
+            if node_index % 2 == 0 {
+                path.push((node_hash, sibling_hash));
+            } else {
+                path.push((sibling_hash, node_hash));
+            }
+        }
+
+        // Pad the path with empty siblings up to the root.
+        if depth < Self::DEPTH as usize {
+            let empty_hash = &self.empty_hashes[0];
+            let mut current_hash = self.levels[depth][0].clone();
+            path.push((current_hash.clone(), empty_hash.clone()));
+
+            for _ in (depth + 1)..Self::DEPTH as usize {
+                current_hash = self
+                    .parameters
+                    .hash_inner_node(&current_hash, empty_hash, &mut buffer)?;
+                path.push((current_hash.clone(), empty_hash.clone()));
+            }
+        }
+        end_timer!(prove_time);
+
+        Ok(MerklePath {
+            parameters: self.parameters.clone(),
+            path,
+        })
+    }
+
+    /// Returns the root of the tree as it was when it held the given number of leaves.
+    pub fn historical_root(&self, num_leaves: usize) -> Result<MerkleTreeDigest<P>, MerkleError> {
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        let frontier = self.historical_frontier(num_leaves, &mut buffer)?;
+        let top_hash = frontier.last().unwrap_or(&self.empty_hashes[0]).clone();
+
+        self.pad_root(top_hash, tree_depth(num_leaves), &mut buffer)
+    }
+
+    /// Returns the path of a leaf to the root of the tree as it was when it held the given number of leaves.
+    ///
+    /// The nodes covering only the first leaves never change as leaves are appended, so only the
+    /// rightmost node of each level, which forms the frontier of the past tree, is recomputed.
+    pub fn generate_historical_proof<L: ToBytes>(
+        &self,
+        num_leaves: usize,
+        index: usize,
+        leaf: &L,
+    ) -> Result<MerklePath<P>, MerkleError> {
+        let prove_time = start_timer!(|| "IncrementalMerkleTree::generate_historical_proof");
+
+        let hash_input_size_in_bytes = (P::H::INPUT_SIZE_BITS / 8) * 2;
+        let mut buffer = vec![0u8; hash_input_size_in_bytes];
+
+        let leaf_hash = self.parameters.hash_leaf(leaf, &mut buffer)?;
+
+        // Check that the given index corresponds to the correct leaf in the past tree.
+        if index >= num_leaves || self.levels[0].get(index) != Some(&leaf_hash) {
+            return Err(MerkleError::IncorrectLeafIndex(index));
+        }
+
+        let frontier = self.historical_frontier(num_leaves, &mut buffer)?;
+
+        // Iterate from the leaf up to the top of the past tree, storing all intermediate hash values.
+        let depth = tree_depth(num_leaves);
+        let mut path = Vec::with_capacity(Self::DEPTH as usize);
+        for level in 0..depth {
+            let node_index = index >> level;
+            let node_hash = self.historical_node(num_leaves, &frontier, level, node_index);
+            let sibling_hash = self.historical_node(num_leaves, &frontier, level, node_index ^ 1);
+
+            if node_index % 2 == 0 {
+                path.push((node_hash, sibling_hash));
+            } else {
+                path.push((sibling_hash, node_hash));
+            }
+        }
+
+        // Pad the path with empty siblings up to the root.
+        if depth < Self::DEPTH as usize {
+            let empty_hash = &self.empty_hashes[0];
+            let mut current_hash = frontier[depth].clone();
+            path.push((current_hash.clone(), empty_hash.clone()));
+
+            for _ in (depth + 1)..Self::DEPTH as usize {
+                current_hash = self
+                    .parameters
+                    .hash_inner_node(&current_hash, empty_hash, &mut buffer)?;
+                path.push((current_hash.clone(), empty_hash.clone()));
+            }
+        }
+        end_timer!(prove_time);
+
+        Ok(MerklePath {
+            parameters: self.parameters.clone(),
+            path,
+        })
+    }
+
+    /// Compute the rightmost node of each level of the tree as it was when it held the given number of leaves.
+    fn historical_frontier(
+        &self,
+        num_leaves: usize,
+        buffer: &mut [u8],
+    ) -> Result<Vec<MerkleTreeDigest<P>>, MerkleError> {
+        if num_leaves > self.len() {
+            return Err(MerkleError::Message(format!(
+                "the tree holds {} leaves, not {}",
+                self.len(),
+                num_leaves
+            )));
+        }
+
+        if num_leaves == 0 {
+            return Ok(vec![]);
+        }
+
+        let depth = tree_depth(num_leaves);
+        let mut frontier = Vec::with_capacity(depth + 1);
+        frontier.push(self.levels[0][num_leaves - 1].clone());
+
+        for level in 1..=depth {
+            let last_index = (num_leaves - 1) >> level;
+            let left = self.historical_node(num_leaves, &frontier, level - 1, 2 * last_index);
+            let right = self.historical_node(num_leaves, &frontier, level - 1, 2 * last_index + 1);
+
+            frontier.push(self.parameters.hash_inner_node(&left, &right, buffer)?);
+        }
+
+        Ok(frontier)
+    }
+
+    /// Returns the node at a given index in a given level of the tree as it was when it held the
+    /// given number of leaves, given the frontier of the past tree up to that level.
+    fn historical_node(
+        &self,
+        num_leaves: usize,
+        frontier: &[MerkleTreeDigest<P>],
+        level: usize,
+        index: usize,
+    ) -> MerkleTreeDigest<P> {
+        let last_index = (num_leaves - 1) >> level;
+
+        if index < last_index {
+            self.levels[level][index].clone()
+        } else if index == last_index {
+            frontier[level].clone()
+        } else {
+            self.empty_hashes[level].clone()
+        }
+    }
+
+    /// Recompute the nodes of a level from a given index up to the end of the level.
+    fn update_level(&mut self, level: usize, first_index: usize, buffer: &mut [u8]) -> Result<(), MerkleError> {
+        let size = level_size(self.len(), level);
+
+        if self.levels.len() == level {
+            self.levels.push(Vec::with_capacity(size));
+        }
+        self.levels[level].truncate(first_index);
+
+        for index in first_index..size {
+            let hash = {
+                let children = &self.levels[level - 1];
+                let left = &children[2 * index];
+                let right = children.get(2 * index + 1).unwrap_or(&self.empty_hashes[level - 1]);
+
+                self.parameters.hash_inner_node(left, right, buffer)?
+            };
+            self.levels[level].push(hash);
+        }
+
+        Ok(())
+    }
+
+    /// Compute the root from the top of the tree.
+    fn compute_root(&self, buffer: &mut [u8]) -> Result<MerkleTreeDigest<P>, MerkleError> {
+        let depth = self.levels.len() - 1;
+        let top_hash = self.levels[depth].first().unwrap_or(&self.empty_hashes[0]).clone();
+
+        self.pad_root(top_hash, depth, buffer)
+    }
+
+    /// Pad the top of a tree of a given depth with empty siblings up to the root.
+    fn pad_root(
+        &self,
+        top_hash: MerkleTreeDigest<P>,
+        depth: usize,
+        buffer: &mut [u8],
+    ) -> Result<MerkleTreeDigest<P>, MerkleError> {
+        let empty_hash = &self.empty_hashes[0];
+
+        let mut current_hash = top_hash;
+        for _ in depth..Self::DEPTH as usize {
+            current_hash = self.parameters.hash_inner_node(&current_hash, empty_hash, buffer)?;
+        }
+
+        Ok(current_hash)
+    }
+}
+
+/// Returns the depth of the tree, given the number of leaves.
+#[inline]
+fn tree_depth(num_leaves: usize) -> usize {
+    num_leaves.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Returns the number of nodes covering at least one leaf in a given level, given the number of leaves.
+#[inline]
+fn level_size(num_leaves: usize, level: usize) -> usize {
+    (num_leaves + (1 << level) - 1) >> level
+}
@@ -16,6 +16,9 @@
 
 #![allow(clippy::module_inception)]
 
+pub mod incremental_merkle_tree;
+pub use incremental_merkle_tree::*;
+
 pub mod merkle_path;
 pub use merkle_path::*;
 
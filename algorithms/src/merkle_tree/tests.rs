@@ -17,7 +17,7 @@
 use crate::{
     crh::{PedersenCRH, PedersenCompressedCRH, PedersenSize},
     define_merkle_tree_parameters,
-    merkle_tree::MerkleTree,
+    merkle_tree::{IncrementalMerkleTree, MerkleTree},
 };
 use snarkos_models::algorithms::{crh::CRH, merkle_tree::LoadableMerkleParameters};
 use snarkos_utilities::{to_bytes, ToBytes};
@@ -164,6 +164,62 @@ fn run_padded_merkle_tree_matches_hashing_test<P: LoadableMerkleParameters>() {
     assert_eq!(merkle_tree_root, expected_root);
 }
 
+fn run_incremental_merkle_tree_matches_merkle_tree_test<P: LoadableMerkleParameters>() {
+    let parameters = &P::default();
+
+    let mut leaves = vec![];
+    for i in 0..18u8 {
+        leaves.push([i, i, i, i, i, i, i, i]);
+    }
+
+    // Append the leaves one at a time and in batches
+
+    let mut tree = IncrementalMerkleTree::<P>::new(parameters.clone(), &leaves[..0]).unwrap();
+    for num_leaves in 0..=leaves.len() {
+        let batch_tree = IncrementalMerkleTree::<P>::new(parameters.clone(), &leaves[..num_leaves]).unwrap();
+        let merkle_tree = MerkleTree::<P>::new(parameters.clone(), &leaves[..num_leaves]).unwrap();
+
+        assert_eq!(merkle_tree.root(), tree.root());
+        assert_eq!(merkle_tree.root(), batch_tree.root());
+        assert_eq!(merkle_tree.hashed_leaves()[..num_leaves], tree.hashed_leaves()[..]);
+
+        for (i, leaf) in leaves[..num_leaves].iter().enumerate() {
+            let proof = tree.generate_proof(i, &leaf).unwrap();
+            assert_eq!(merkle_tree.generate_proof(i, &leaf).unwrap().path, proof.path);
+            assert!(proof.verify(&tree.root(), &leaf).unwrap());
+        }
+
+        if num_leaves < leaves.len() {
+            tree.append(&leaves[num_leaves..=num_leaves]).unwrap();
+        }
+    }
+
+    // Query the past trees from the full tree
+
+    for num_leaves in 0..=leaves.len() {
+        let merkle_tree = MerkleTree::<P>::new(parameters.clone(), &leaves[..num_leaves]).unwrap();
+
+        assert_eq!(merkle_tree.root(), tree.historical_root(num_leaves).unwrap());
+
+        for (i, leaf) in leaves[..num_leaves].iter().enumerate() {
+            let proof = tree.generate_historical_proof(num_leaves, i, &leaf).unwrap();
+            assert_eq!(merkle_tree.generate_proof(i, &leaf).unwrap().path, proof.path);
+        }
+    }
+    assert!(tree.historical_root(leaves.len() + 1).is_err());
+
+    // Remove the leaves and reload the tree from its levels
+
+    for num_leaves in (0..leaves.len()).rev() {
+        tree.truncate(num_leaves).unwrap();
+        let merkle_tree = MerkleTree::<P>::new(parameters.clone(), &leaves[..num_leaves]).unwrap();
+        let loaded_tree = IncrementalMerkleTree::<P>::from_levels(parameters.clone(), tree.levels().to_vec()).unwrap();
+
+        assert_eq!(merkle_tree.root(), tree.root());
+        assert_eq!(merkle_tree.root(), loaded_tree.root());
+    }
+}
+
 mod pedersen_crh_on_affine {
     use super::*;
     use snarkos_curves::edwards_bls12::EdwardsAffine as Edwards;
@@ -194,6 +250,12 @@ mod pedersen_crh_on_affine {
         run_bad_root_test::<MTParameters>();
     }
 
+    #[test]
+    fn incremental_merkle_tree_matches_merkle_tree_test() {
+        define_merkle_tree_parameters!(MTParameters, PedersenCRH<Edwards, Size>, 32);
+        run_incremental_merkle_tree_matches_merkle_tree_test::<MTParameters>();
+    }
+
     #[test]
     fn depth2_merkle_tree_matches_hashing_test() {
         define_merkle_tree_parameters!(MTParameters, PedersenCRH<Edwards, Size>, 2);
@@ -284,6 +346,12 @@ mod pedersen_compressed_crh_on_projective {
         run_bad_root_test::<MTParameters>();
     }
 
+    #[test]
+    fn incremental_merkle_tree_matches_merkle_tree_test() {
+        define_merkle_tree_parameters!(MTParameters, PedersenCompressedCRH<Edwards, Size>, 32);
+        run_incremental_merkle_tree_matches_merkle_tree_test::<MTParameters>();
+    }
+
     #[test]
     fn depth2_merkle_tree_matches_hashing_test() {
         define_merkle_tree_parameters!(MTParameters, PedersenCompressedCRH<Edwards, Size>, 2);
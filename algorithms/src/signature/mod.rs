@@ -20,5 +20,8 @@ pub use schnorr::*;
 pub mod schnorr_parameters;
 pub use schnorr_parameters::*;
 
+pub mod threshold_schnorr;
+pub use threshold_schnorr::*;
+
 #[cfg(test)]
 mod tests;
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::signature::SchnorrSignature;
+use crate::signature::{SchnorrKeyShare, SchnorrNonceCommitments, SchnorrSignature, SchnorrSignatureShare};
 use snarkos_curves::edwards_sw6::EdwardsAffine as Edwards;
 use snarkos_models::{algorithms::SignatureScheme, curves::Group};
 use snarkos_utilities::{
@@ -57,11 +57,9 @@ fn randomize_and_verify<S: SignatureScheme>(message: &[u8], randomness: &[u8]) {
 
     let randomized_public_key = schnorr_signature.randomize_public_key(&public_key, randomness).unwrap();
     let randomized_signature = schnorr_signature.randomize_signature(&signature, randomness).unwrap();
-    assert!(
-        schnorr_signature
-            .verify(&randomized_public_key, &message, &randomized_signature)
-            .unwrap()
-    );
+    assert!(schnorr_signature
+        .verify(&randomized_public_key, &message, &randomized_signature)
+        .unwrap());
 }
 
 fn signature_scheme_parameter_serialization<S: SignatureScheme>() {
@@ -91,3 +89,118 @@ fn schnorr_signature_test() {
 fn schnorr_signature_scheme_parameters_serialization() {
     signature_scheme_parameter_serialization::<TestSignature>();
 }
+
+fn threshold_sign(
+    schnorr_signature: &TestSignature,
+    signers: &[&SchnorrKeyShare<Edwards>],
+    message: &[u8],
+    rng: &mut XorShiftRng,
+) -> (
+    Vec<SchnorrNonceCommitments<Edwards>>,
+    Vec<SchnorrSignatureShare<Edwards>>,
+) {
+    let (nonces, commitments): (Vec<_>, Vec<_>) = signers
+        .iter()
+        .map(|key_share| schnorr_signature.commit_nonces(key_share, rng).unwrap())
+        .unzip();
+
+    let signature_shares = signers
+        .iter()
+        .zip(nonces)
+        .map(|(key_share, nonces)| {
+            schnorr_signature
+                .sign_share(key_share, nonces, &commitments, message)
+                .unwrap()
+        })
+        .collect();
+
+    (commitments, signature_shares)
+}
+
+#[test]
+fn threshold_schnorr_signature_test() {
+    let message = b"Hi, I am a threshold Schnorr signature!";
+    let rng = &mut XorShiftRng::seed_from_u64(1231275789u64);
+
+    let schnorr_signature = TestSignature::setup(rng).unwrap();
+    let private_key = schnorr_signature.generate_private_key(rng).unwrap();
+    let public_key = schnorr_signature.generate_public_key(&private_key).unwrap();
+
+    let key_shares = schnorr_signature.split_private_key(&private_key, 2, 3, rng).unwrap();
+    assert_eq!(key_shares.len(), 3);
+
+    // Every pair and the full set of signers sign for the public key
+    let signing_sets = vec![
+        vec![&key_shares[0], &key_shares[1]],
+        vec![&key_shares[0], &key_shares[2]],
+        vec![&key_shares[2], &key_shares[1]],
+        vec![&key_shares[0], &key_shares[1], &key_shares[2]],
+    ];
+
+    for signers in signing_sets {
+        let (commitments, mut signature_shares) = threshold_sign(&schnorr_signature, &signers, message, rng);
+
+        let signature = schnorr_signature
+            .aggregate_signature_shares(&public_key, &commitments, &signature_shares, message)
+            .unwrap();
+        assert!(schnorr_signature.verify(&public_key, message, &signature).unwrap());
+        assert!(!schnorr_signature
+            .verify(&public_key, b"Bad message", &signature)
+            .unwrap());
+
+        // The threshold signature randomizes like any other signature
+        let randomness = to_bytes!(<Edwards as Group>::ScalarField::rand(rng)).unwrap();
+        let randomized_public_key = schnorr_signature
+            .randomize_public_key(&public_key, &randomness)
+            .unwrap();
+        let randomized_signature = schnorr_signature.randomize_signature(&signature, &randomness).unwrap();
+        assert!(schnorr_signature
+            .verify(&randomized_public_key, message, &randomized_signature)
+            .unwrap());
+
+        // A share of another message does not aggregate
+        signature_shares[0] = threshold_sign(&schnorr_signature, &signers, b"Bad message", rng)
+            .1
+            .remove(0);
+        assert!(schnorr_signature
+            .aggregate_signature_shares(&public_key, &commitments, &signature_shares, message)
+            .is_err());
+    }
+
+    // A single signer does not meet the threshold
+    let (nonces, commitments) = schnorr_signature.commit_nonces(&key_shares[0], rng).unwrap();
+    assert!(schnorr_signature
+        .sign_share(&key_shares[0], nonces, &[commitments], message)
+        .is_err());
+
+    // The threshold is at most the number of signers
+    assert!(schnorr_signature.split_private_key(&private_key, 0, 3, rng).is_err());
+    assert!(schnorr_signature.split_private_key(&private_key, 4, 3, rng).is_err());
+}
+
+#[test]
+fn threshold_schnorr_serialization_test() {
+    let rng = &mut XorShiftRng::seed_from_u64(1231275789u64);
+
+    let schnorr_signature = TestSignature::setup(rng).unwrap();
+    let private_key = schnorr_signature.generate_private_key(rng).unwrap();
+    let key_shares = schnorr_signature.split_private_key(&private_key, 2, 2, rng).unwrap();
+
+    let key_share_bytes = to_bytes![key_shares[0]].unwrap();
+    assert!(SchnorrKeyShare::<Edwards>::read(&key_share_bytes[..]).unwrap() == key_shares[0]);
+
+    let (commitments, signature_shares) =
+        threshold_sign(&schnorr_signature, &[&key_shares[0], &key_shares[1]], b"message", rng);
+
+    let commitments_bytes = to_bytes![commitments[0]].unwrap();
+    assert_eq!(
+        SchnorrNonceCommitments::<Edwards>::read(&commitments_bytes[..]).unwrap(),
+        commitments[0]
+    );
+
+    let signature_share_bytes = to_bytes![signature_shares[1]].unwrap();
+    assert_eq!(
+        SchnorrSignatureShare::<Edwards>::read(&signature_share_bytes[..]).unwrap(),
+        signature_shares[1]
+    );
+}
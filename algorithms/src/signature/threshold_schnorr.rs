@@ -0,0 +1,413 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Threshold signing for Schnorr private keys shared among signers.
+//!
+//! A private key is split by a dealer into `n` Shamir shares, any `t` of which sign together. Signing takes
+//! two rounds: each signer publishes the commitments to a pair of fresh nonces, then each signer returns the
+//! share of its response for the commitments of the signing set. The sum of the responses is an ordinary
+//! `SchnorrOutput`, which verifies under the public key of the shared private key and randomizes like any
+//! other Schnorr signature.
+//!
+//! The commitments of a signer are bound to the message and to the whole signing set, so a signer only ever
+//! responds to the nonce commitment it can predict. A challenge sampled from a digest may fall outside of the
+//! scalar field, in which case the binding factors are rederived with an incremented counter. Every party
+//! derives the same counter from the public commitments, so no additional round is needed.
+//!
+//! The nonces of a signer must only be used for a single signature.
+
+use crate::signature::{SchnorrOutput, SchnorrPublicKey, SchnorrSignature};
+use snarkos_errors::algorithms::SignatureError;
+use snarkos_models::{
+    algorithms::SignatureScheme,
+    curves::{Field, Group, One, PrimeField, Zero},
+};
+use snarkos_utilities::{
+    bytes::{bytes_to_bits, FromBytes, ToBytes},
+    has_duplicates,
+    rand::UniformRand,
+    serialize::*,
+    to_bytes,
+};
+
+use digest::Digest;
+use itertools::Itertools;
+use rand::Rng;
+use std::{
+    hash::Hash,
+    io::{Read, Result as IoResult, Write},
+};
+
+/// The maximum number of binding factor derivations attempted for a signing set.
+const MAX_BINDING_ATTEMPTS: u32 = 256;
+
+/// The domain of the binding factors of the nonce commitments.
+const BINDING_FACTOR_DOMAIN: &[u8] = b"snarkOS threshold schnorr binding";
+
+/// The share of a Schnorr private key held by a signer of a threshold key.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "G: Group"), PartialEq(bound = "G: Group"), Eq(bound = "G: Group"))]
+pub struct SchnorrKeyShare<G: Group> {
+    /// The index of the signer, starting at 1
+    pub index: u16,
+    /// The number of signers required to sign
+    pub threshold: u16,
+    /// The evaluation of the sharing polynomial at the index
+    pub private_share: <G as Group>::ScalarField,
+}
+
+/// The secret nonces of a signer for a single signature.
+pub struct SchnorrSigningNonces<G: Group> {
+    pub index: u16,
+    pub hiding: <G as Group>::ScalarField,
+    pub binding: <G as Group>::ScalarField,
+}
+
+/// The commitments to the nonces of a signer, published in the first round of signing.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "G: Group"),
+    Debug(bound = "G: Group"),
+    PartialEq(bound = "G: Group"),
+    Eq(bound = "G: Group")
+)]
+pub struct SchnorrNonceCommitments<G: Group> {
+    pub index: u16,
+    pub hiding: G,
+    pub binding: G,
+}
+
+/// The share of the response of a signer, returned in the second round of signing.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "G: Group"), Debug(bound = "G: Group"), PartialEq(bound = "G: Group"))]
+pub struct SchnorrSignatureShare<G: Group> {
+    pub index: u16,
+    pub response: <G as Group>::ScalarField,
+}
+
+impl<G: Group + Hash + CanonicalSerialize + CanonicalDeserialize, D: Digest + Send + Sync> SchnorrSignature<G, D>
+where
+    <G as Group>::ScalarField: PrimeField,
+{
+    /// Splits a private key into the given number of shares, any `threshold` of which sign for its public key.
+    /// The dealer learns the private key, and must erase it along with the shares it hands out.
+    pub fn split_private_key<R: Rng>(
+        &self,
+        private_key: &<G as Group>::ScalarField,
+        threshold: u16,
+        num_signers: u16,
+        rng: &mut R,
+    ) -> Result<Vec<SchnorrKeyShare<G>>, SignatureError> {
+        if threshold == 0 || threshold > num_signers {
+            return Err(SignatureError::Message(format!(
+                "invalid threshold of {} out of {} signers",
+                threshold, num_signers
+            )));
+        }
+
+        // Sample the sharing polynomial, whose constant term is the private key.
+        let mut coefficients = vec![*private_key];
+        for _ in 1..threshold {
+            coefficients.push(<G as Group>::ScalarField::rand(rng));
+        }
+
+        Ok((1..=num_signers)
+            .map(|index| {
+                let x = <G as Group>::ScalarField::from(index);
+                let private_share = coefficients
+                    .iter()
+                    .rev()
+                    .fold(<G as Group>::ScalarField::zero(), |sum, coefficient| {
+                        sum * &x + coefficient
+                    });
+
+                SchnorrKeyShare {
+                    index,
+                    threshold,
+                    private_share,
+                }
+            })
+            .collect())
+    }
+
+    /// Samples the nonces of a signer for a single signature, and returns them with their commitments.
+    pub fn commit_nonces<R: Rng>(
+        &self,
+        key_share: &SchnorrKeyShare<G>,
+        rng: &mut R,
+    ) -> Result<(SchnorrSigningNonces<G>, SchnorrNonceCommitments<G>), SignatureError> {
+        let nonces = SchnorrSigningNonces {
+            index: key_share.index,
+            hiding: <G as Group>::ScalarField::rand(rng),
+            binding: <G as Group>::ScalarField::rand(rng),
+        };
+
+        let commitments = SchnorrNonceCommitments {
+            index: key_share.index,
+            hiding: self.generator_mul(&nonces.hiding)?,
+            binding: self.generator_mul(&nonces.binding)?,
+        };
+
+        Ok((nonces, commitments))
+    }
+
+    /// Returns the share of the signer of the response to the message, given the nonce commitments
+    /// of the signing set. The nonces are consumed, as they must not sign another message.
+    pub fn sign_share(
+        &self,
+        key_share: &SchnorrKeyShare<G>,
+        nonces: SchnorrSigningNonces<G>,
+        commitments: &[SchnorrNonceCommitments<G>],
+        message: &[u8],
+    ) -> Result<SchnorrSignatureShare<G>, SignatureError> {
+        if commitments.len() < key_share.threshold as usize {
+            return Err(SignatureError::Message(format!(
+                "the signing set has {} signers, expected at least {}",
+                commitments.len(),
+                key_share.threshold
+            )));
+        }
+
+        let position = commitments
+            .iter()
+            .position(|signer_commitments| signer_commitments.index == key_share.index)
+            .ok_or_else(|| SignatureError::Message(format!("signer {} is not in the signing set", key_share.index)))?;
+
+        let own_commitments = &commitments[position];
+        if nonces.index != key_share.index
+            || own_commitments.hiding != self.generator_mul(&nonces.hiding)?
+            || own_commitments.binding != self.generator_mul(&nonces.binding)?
+        {
+            return Err(SignatureError::Message(format!(
+                "the commitments of signer {} do not match its nonces",
+                key_share.index
+            )));
+        }
+
+        let (binding_factors, verifier_challenge) = self.binding_factors_and_challenge(commitments, message)?;
+        let binding_factor = binding_factors[position];
+        let lagrange_coefficient = lagrange_coefficient::<G>(key_share.index, commitments)?;
+
+        // d + ρe - λxc, so the responses of the signing set sum to k - xc.
+        let response = nonces.hiding + &(binding_factor * &nonces.binding)
+            - &(lagrange_coefficient * &key_share.private_share * &verifier_challenge);
+
+        Ok(SchnorrSignatureShare {
+            index: key_share.index,
+            response,
+        })
+    }
+
+    /// Sums the response shares of the signing set into a signature of the message, which is checked
+    /// against the public key of the shared private key.
+    pub fn aggregate_signature_shares(
+        &self,
+        public_key: &SchnorrPublicKey<G>,
+        commitments: &[SchnorrNonceCommitments<G>],
+        signature_shares: &[SchnorrSignatureShare<G>],
+        message: &[u8],
+    ) -> Result<SchnorrOutput<G>, SignatureError> {
+        let commitment_indices: Vec<_> = commitments
+            .iter()
+            .map(|signer_commitments| signer_commitments.index)
+            .sorted()
+            .collect();
+        let share_indices: Vec<_> = signature_shares.iter().map(|share| share.index).sorted().collect();
+        if commitment_indices != share_indices {
+            return Err(SignatureError::Message(
+                "the signature shares do not match the signing set".into(),
+            ));
+        }
+
+        let (_, verifier_challenge) = self.binding_factors_and_challenge(commitments, message)?;
+        let prover_response = signature_shares
+            .iter()
+            .fold(<G as Group>::ScalarField::zero(), |sum, share| sum + &share.response);
+
+        let signature = SchnorrOutput {
+            prover_response,
+            verifier_challenge,
+        };
+
+        if !self.verify(public_key, message, &signature)? {
+            return Err(SignatureError::Message(
+                "the signature shares do not sign for the public key".into(),
+            ));
+        }
+
+        Ok(signature)
+    }
+
+    /// Returns the binding factors of the commitments of the signing set, in order, with the challenge
+    /// of the joint nonce commitment, at the first counter yielding them all in the scalar field.
+    fn binding_factors_and_challenge(
+        &self,
+        commitments: &[SchnorrNonceCommitments<G>],
+        message: &[u8],
+    ) -> Result<(Vec<<G as Group>::ScalarField>, <G as Group>::ScalarField), SignatureError> {
+        let indices: Vec<_> = commitments
+            .iter()
+            .map(|signer_commitments| signer_commitments.index)
+            .collect();
+        if indices.contains(&0) || has_duplicates(&indices) {
+            return Err(SignatureError::Message(
+                "the signing set has an invalid or duplicate signer index".into(),
+            ));
+        }
+
+        let mut encoded_commitments = vec![];
+        for signer_commitments in commitments {
+            signer_commitments.write(&mut encoded_commitments)?;
+        }
+
+        'attempts: for counter in 0..MAX_BINDING_ATTEMPTS {
+            let mut binding_factors = Vec::with_capacity(commitments.len());
+            let mut prover_commitment = G::zero();
+
+            for signer_commitments in commitments {
+                let mut hash_input = Vec::new();
+                hash_input.extend_from_slice(&self.parameters.salt);
+                hash_input.extend_from_slice(BINDING_FACTOR_DOMAIN);
+                hash_input.extend_from_slice(&to_bytes![counter, signer_commitments.index]?);
+                hash_input.extend_from_slice(&encoded_commitments);
+                hash_input.extend_from_slice(message);
+
+                let binding_factor = match <G as Group>::ScalarField::from_random_bytes(&D::digest(&hash_input)) {
+                    Some(binding_factor) => binding_factor,
+                    None => continue 'attempts,
+                };
+
+                prover_commitment += &signer_commitments.hiding;
+                prover_commitment += &signer_commitments.binding.mul(&binding_factor);
+                binding_factors.push(binding_factor);
+            }
+
+            // Hash everything to get the verifier challenge, as a single signer would.
+            let mut hash_input = Vec::new();
+            hash_input.extend_from_slice(&self.parameters.salt);
+            hash_input.extend_from_slice(&to_bytes![prover_commitment]?);
+            hash_input.extend_from_slice(message);
+
+            if let Some(verifier_challenge) = <G as Group>::ScalarField::from_random_bytes(&D::digest(&hash_input)) {
+                return Ok((binding_factors, verifier_challenge));
+            }
+        }
+
+        Err(SignatureError::Message(
+            "the nonce commitments yield no challenge, sign with fresh nonces".into(),
+        ))
+    }
+
+    /// Returns the product of the scalar and the generator.
+    fn generator_mul(&self, scalar: &<G as Group>::ScalarField) -> Result<G, SignatureError> {
+        let mut output = G::zero();
+        for (bit, base_power) in bytes_to_bits(&to_bytes![scalar]?).zip_eq(&self.parameters.generator_powers) {
+            if bit {
+                output += &base_power;
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Returns the Lagrange coefficient at zero of the signer with the given index, over the signing set.
+fn lagrange_coefficient<G: Group>(
+    index: u16,
+    commitments: &[SchnorrNonceCommitments<G>],
+) -> Result<<G as Group>::ScalarField, SignatureError> {
+    let x = <G as Group>::ScalarField::from(index);
+
+    let mut numerator = <G as Group>::ScalarField::one();
+    let mut denominator = <G as Group>::ScalarField::one();
+    for other in commitments
+        .iter()
+        .filter(|signer_commitments| signer_commitments.index != index)
+    {
+        let other_x = <G as Group>::ScalarField::from(other.index);
+        numerator *= &other_x;
+        denominator *= &(other_x - &x);
+    }
+
+    match denominator.inverse() {
+        Some(inverse) => Ok(numerator * &inverse),
+        None => Err(SignatureError::Message(
+            "the signing set has a duplicate signer index".into(),
+        )),
+    }
+}
+
+impl<G: Group> ToBytes for SchnorrKeyShare<G> {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.index.write(&mut writer)?;
+        self.threshold.write(&mut writer)?;
+        self.private_share.write(&mut writer)
+    }
+}
+
+impl<G: Group> FromBytes for SchnorrKeyShare<G> {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let index = u16::read(&mut reader)?;
+        let threshold = u16::read(&mut reader)?;
+        let private_share = <G as Group>::ScalarField::read(&mut reader)?;
+
+        Ok(Self {
+            index,
+            threshold,
+            private_share,
+        })
+    }
+}
+
+impl<G: Group> ToBytes for SchnorrNonceCommitments<G> {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.index.write(&mut writer)?;
+        self.hiding.write(&mut writer)?;
+        self.binding.write(&mut writer)
+    }
+}
+
+impl<G: Group> FromBytes for SchnorrNonceCommitments<G> {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let index = u16::read(&mut reader)?;
+        let hiding = G::read(&mut reader)?;
+        let binding = G::read(&mut reader)?;
+
+        Ok(Self { index, hiding, binding })
+    }
+}
+
+impl<G: Group> ToBytes for SchnorrSignatureShare<G> {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.index.write(&mut writer)?;
+        self.response.write(&mut writer)
+    }
+}
+
+impl<G: Group> FromBytes for SchnorrSignatureShare<G> {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let index = u16::read(&mut reader)?;
+        let response = <G as Group>::ScalarField::read(&mut reader)?;
+
+        Ok(Self { index, response })
+    }
+}
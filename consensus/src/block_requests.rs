@@ -0,0 +1,117 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// The blocks requested from the miner of a development network, which mines blocks on demand
+/// rather than continuously.
+pub struct BlockRequests {
+    /// The number of blocks requested and not yet taken by the miner
+    pending: Mutex<u32>,
+    /// Wakes the miner waiting for a block request
+    notify: Notify,
+}
+
+impl BlockRequests {
+    /// Creates the block requests, without a pending block.
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(0),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Requests the given number of blocks, and wakes the miner. Returns the number of pending blocks.
+    pub fn request(&self, count: u32) -> u32 {
+        let pending = {
+            let mut pending = self.pending.lock().expect("block requests lock poisoned");
+            *pending = pending.saturating_add(count);
+            *pending
+        };
+
+        if count > 0 {
+            self.notify.notify();
+        }
+
+        pending
+    }
+
+    /// Returns the number of blocks requested and not yet taken by the miner.
+    pub fn pending(&self) -> u32 {
+        *self.pending.lock().expect("block requests lock poisoned")
+    }
+
+    /// Takes a block request. Returns false if no block is pending.
+    pub fn take(&self) -> bool {
+        let mut pending = self.pending.lock().expect("block requests lock poisoned");
+
+        match pending.checked_sub(1) {
+            Some(remaining) => {
+                *pending = remaining;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Waits for a block request, and takes it.
+    pub async fn next(&self) {
+        while !self.take() {
+            self.notify.notified().await;
+        }
+    }
+}
+
+impl Default for BlockRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_requested_blocks() {
+        let requests = BlockRequests::new();
+        assert!(!requests.take());
+
+        assert_eq!(requests.request(2), 2);
+        assert_eq!(requests.request(1), 3);
+
+        assert!(requests.take());
+        assert!(requests.take());
+        assert_eq!(requests.pending(), 1);
+        assert!(requests.take());
+        assert!(!requests.take());
+        assert_eq!(requests.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn wait_for_requested_block() {
+        let requests = std::sync::Arc::new(BlockRequests::new());
+
+        let waiting_requests = requests.clone();
+        let waiter = tokio::spawn(async move { waiting_requests.next().await });
+
+        requests.request(1);
+        waiter.await.unwrap();
+
+        assert_eq!(requests.pending(), 0);
+    }
+}
@@ -14,27 +14,40 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{difficulty::bitcoin_retarget, memory_pool::MemoryPool, MerkleTreeLedger};
+use crate::{
+    difficulty::bitcoin_retarget,
+    dry_run::TransactionDryRun,
+    memo::{encrypt_memo, TransactionMemo},
+    memory_pool::{MemoryPool, MemoryPoolEvent},
+    miner_tag::miner_tag_memo,
+    verification_pool::verify_in_pool,
+    wallet::Wallet,
+    MerkleTreeLedger,
+};
+use snarkos_algorithms::merkle_tree::{MerklePath, MerkleTreeDigest};
 use snarkos_curves::bls12_377::Bls12_377;
 use snarkos_dpc::base_dpc::{
     instantiated::*,
     parameters::PublicParameters,
-    program::NoopProgram,
+    program::ProgramRegistry,
     record::DPCRecord,
+    record_encryption::RecordEncryption,
     record_payload::RecordPayload,
-    BaseDPCComponents,
+    ExecuteContext,
+    TransactionAuthorization,
 };
 use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::{
     algorithms::{CRH, SNARK},
-    dpc::{DPCComponents, DPCScheme, Program},
-    objects::{AccountScheme, LedgerScheme},
+    dpc::{DPCComponents, DPCScheme, Record},
+    objects::{AccountScheme, LedgerScheme, Transaction},
 };
 use snarkos_objects::{
     dpc::DPCTransactions,
     Account,
     AccountAddress,
     AccountPrivateKey,
+    AccountViewKey,
     AleoAmount,
     Block,
     BlockHeader,
@@ -44,12 +57,13 @@ use snarkos_objects::{
     PedersenMerkleRootHash,
 };
 use snarkos_posw::{txids_to_roots, Marlin, PoswMarlin};
-use snarkos_profiler::{end_timer, start_timer};
+use snarkos_profiler::{end_timer, start_timer, METRICS};
 use snarkos_storage::BlockPath;
-use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
+use snarkos_utilities::{has_duplicates, to_bytes, FromBytes, ToBytes};
 
 use chrono::Utc;
 use rand::Rng;
+use rayon::prelude::*;
 
 pub const TWO_HOURS_UNIX: i64 = 7200;
 
@@ -73,6 +87,17 @@ pub struct ConsensusParameters {
 
     /// The authorized inner SNARK IDs
     pub authorized_inner_snark_ids: Vec<Vec<u8>>,
+
+    /// The difficulty target of every block in place of the retargeted difficulty, such as the
+    /// trivial difficulty of a development network
+    pub fixed_difficulty_target: Option<u64>,
+
+    /// Maximum size in bytes of the block templates built by the node, in place of the maximum block size
+    pub max_template_size: Option<usize>,
+
+    /// Maximum weight of the block templates built by the node, the number of ledger entries their transactions
+    /// add, or no limit if unset
+    pub max_template_weight: Option<usize>,
 }
 
 /// Calculate a block reward that halves every 4 years * 365 days * 24 hours * 100 blocks/hr = 3,504,000 blocks.
@@ -92,8 +117,25 @@ pub fn get_block_reward(block_num: u32) -> AleoAmount {
 }
 
 impl ConsensusParameters {
+    /// Returns the maximum size of the block templates, the configured size capped at the maximum block size.
+    pub fn template_size(&self) -> usize {
+        match self.max_template_size {
+            Some(max_template_size) => max_template_size.min(self.max_block_size),
+            None => self.max_block_size,
+        }
+    }
+
+    /// Returns the maximum weight of the block templates, or `usize::MAX` if it is not configured.
+    pub fn template_weight(&self) -> usize {
+        self.max_template_weight.unwrap_or(usize::MAX)
+    }
+
     /// Calculate the difficulty for the next block based off how long it took to mine the last one.
     pub fn get_block_difficulty(&self, prev_header: &BlockHeader, block_timestamp: i64) -> u64 {
+        if let Some(difficulty_target) = self.fixed_difficulty_target {
+            return difficulty_target;
+        }
+
         bitcoin_retarget(
             block_timestamp,
             prev_header.time,
@@ -120,12 +162,6 @@ impl ConsensusParameters {
         merkle_root_hash: &MerkleRootHash,
         pedersen_merkle_root_hash: &PedersenMerkleRootHash,
     ) -> Result<(), ConsensusError> {
-        let hash_result = header.to_difficulty_hash();
-
-        let now = Utc::now().timestamp();
-        let future_timelimit: i64 = now + TWO_HOURS_UNIX;
-        let expected_difficulty = self.get_block_difficulty(parent_header, header.time);
-
         if parent_header.get_hash() != header.previous_block_hash {
             return Err(ConsensusError::NoParent(
                 parent_header.get_hash().to_string(),
@@ -137,7 +173,21 @@ impl ConsensusParameters {
             return Err(ConsensusError::PedersenMerkleRoot(
                 header.pedersen_merkle_root_hash.to_string(),
             ));
-        } else if header.time > future_timelimit {
+        }
+
+        self.verify_header_work(header, parent_header)
+    }
+
+    /// Verify the timestamp, the difficulty, and the proof of work of a block header against its parent
+    /// block header, such as the block headers of a ledger snapshot, whose transactions are not known.
+    pub fn verify_header_work(&self, header: &BlockHeader, parent_header: &BlockHeader) -> Result<(), ConsensusError> {
+        let hash_result = header.to_difficulty_hash();
+
+        let now = Utc::now().timestamp();
+        let future_timelimit: i64 = now + TWO_HOURS_UNIX;
+        let expected_difficulty = self.get_block_difficulty(parent_header, header.time);
+
+        if header.time > future_timelimit {
             return Err(ConsensusError::FuturisticTimestamp(future_timelimit, header.time));
         } else if header.time < parent_header.time {
             return Err(ConsensusError::TimestampInvalid(header.time, parent_header.time));
@@ -152,11 +202,22 @@ impl ConsensusParameters {
             ));
         }
 
-        // Verify the proof
+        self.verify_proof_of_work(header)
+    }
+
+    /// Verify the proof of succinct work of a block header against its nonce and pedersen merkle root hash.
+    /// The difficulty of the proof is not checked.
+    pub fn verify_proof_of_work(&self, header: &BlockHeader) -> Result<(), ConsensusError> {
         let proof = <Marlin<Bls12_377> as SNARK>::Proof::read(&header.proof.0[..])?;
         let verification_timer = start_timer!(|| "POSW verify");
-        self.verifier
-            .verify(header.nonce, &proof, &header.pedersen_merkle_root_hash)?;
+        METRICS.time(
+            "snarkos_posw_verification_seconds",
+            "The time spent verifying block proofs of work",
+            || {
+                self.verifier
+                    .verify(header.nonce, &proof, &header.pedersen_merkle_root_hash)
+            },
+        )?;
         end_timer!(verification_timer);
 
         Ok(())
@@ -179,7 +240,7 @@ impl ConsensusParameters {
         Ok(InstantiatedDPC::verify(parameters, transaction, ledger)?)
     }
 
-    /// Check if the transactions are valid.
+    /// Check if the transactions are valid, verifying them in parallel on the verification threads.
     pub fn verify_transactions(
         &self,
         parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
@@ -192,7 +253,118 @@ impl ConsensusParameters {
             }
         }
 
-        Ok(InstantiatedDPC::verify_transactions(parameters, transactions, ledger)?)
+        Ok(METRICS.time(
+            "snarkos_transaction_verification_seconds",
+            "The time spent verifying the transactions of blocks",
+            || {
+                verify_in_pool(|| {
+                    transactions
+                        .par_iter()
+                        .map(|tx| InstantiatedDPC::verify(parameters, tx, ledger))
+                        .collect::<Result<Vec<bool>, _>>()
+                })
+                .map(|valid| valid.into_iter().all(|valid| valid))
+            },
+        )?)
+    }
+
+    /// Runs the checks of a transaction that do not depend on the ledger state, against the given ledger parameters.
+    pub fn dry_run_transaction_stateless(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        transaction: &Tx,
+        ledger_parameters: &CommitmentMerkleParameters,
+    ) -> Result<TransactionDryRun, ConsensusError> {
+        let mut dry_run = TransactionDryRun::new(transaction.transaction_id()?);
+
+        dry_run.check(
+            "inner_snark_id",
+            self.authorized_inner_snark_ids
+                .contains(&to_bytes![transaction.inner_snark_id]?),
+            || "the inner snark id is not authorized".into(),
+        );
+        dry_run.check("size", transaction.size() <= self.max_block_size, || {
+            format!(
+                "the transaction is larger than the maximum block size of {} bytes",
+                self.max_block_size
+            )
+        });
+        dry_run.check(
+            "unique_serial_numbers",
+            !has_duplicates(transaction.old_serial_numbers()),
+            || "the transaction contains duplicate serial numbers".into(),
+        );
+        dry_run.check(
+            "unique_commitments",
+            !has_duplicates(transaction.new_commitments()),
+            || "the transaction contains duplicate commitments".into(),
+        );
+        dry_run.check("value_balance", !transaction.value_balance.is_negative(), || {
+            "coinbase transactions are not relayed".into()
+        });
+        dry_run.check(
+            "signatures",
+            InstantiatedDPC::verify_signatures(parameters, transaction)?,
+            || "the signatures did not verify".into(),
+        );
+        dry_run.check(
+            "proof",
+            InstantiatedDPC::verify_proof(parameters, transaction, ledger_parameters)?,
+            || "the transaction proof did not verify".into(),
+        );
+
+        Ok(dry_run)
+    }
+
+    /// Runs every check of a transaction against the current state of the ledger, including the double spends
+    /// and the ledger digest, and returns the result of each check. The memory pool is left untouched.
+    pub fn dry_run_transaction(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        transaction: &Tx,
+        ledger: &MerkleTreeLedger,
+    ) -> Result<TransactionDryRun, ConsensusError> {
+        let mut dry_run = self.dry_run_transaction_stateless(parameters, transaction, ledger.parameters())?;
+
+        // The ledger checks are run against a single ledger state
+        let ledger = ledger.view();
+
+        let mut spent_serial_numbers = vec![];
+        for sn in transaction.old_serial_numbers() {
+            if ledger.contains_sn(sn) {
+                spent_serial_numbers.push(hex::encode(to_bytes![sn]?));
+            }
+        }
+
+        let mut existing_commitments = vec![];
+        for cm in transaction.new_commitments() {
+            if ledger.contains_cm(cm) {
+                existing_commitments.push(hex::encode(to_bytes![cm]?));
+            }
+        }
+
+        dry_run.check("memo", !ledger.contains_memo(transaction.memorandum()), || {
+            "the memo is already in the ledger".into()
+        });
+        dry_run.check("serial_numbers", spent_serial_numbers.is_empty(), || {
+            format!(
+                "the serial numbers {} are already spent",
+                spent_serial_numbers.join(", ")
+            )
+        });
+        dry_run.check("commitments", existing_commitments.is_empty(), || {
+            format!(
+                "the commitments {} are already in the ledger",
+                existing_commitments.join(", ")
+            )
+        });
+        dry_run.check(
+            "ledger_digest",
+            ledger.validate_digest(&transaction.ledger_digest),
+            || "the ledger digest is not a digest of the ledger".into(),
+        );
+
+        Ok(dry_run)
     }
 
     /// Check if the block is valid.
@@ -203,19 +375,36 @@ impl ConsensusParameters {
         block: &Block<Tx>,
         ledger: &MerkleTreeLedger,
     ) -> Result<bool, ConsensusError> {
+        match self.check_block(parameters, block, ledger) {
+            Ok(()) => Ok(true),
+            Err(ConsensusError::StorageError(error)) => Err(ConsensusError::StorageError(error)),
+            Err(error) => {
+                warn!(%error, "Block failed to verify");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Check that the block is valid, and return the reason it is rejected otherwise.
+    /// 1. The block header is valid.
+    /// 2. The block has at most one coinbase transaction.
+    /// 3. The block value balances match the block reward.
+    /// 4. The block transactions are valid.
+    pub fn check_block(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        block: &Block<Tx>,
+        ledger: &MerkleTreeLedger,
+    ) -> Result<(), ConsensusError> {
         let transaction_ids: Vec<_> = block.transactions.to_transaction_ids()?;
         let (merkle_root, pedersen_merkle_root, _) = txids_to_roots(&transaction_ids);
 
         // Verify the block header
         if !Self::is_genesis(&block.header) {
             let parent_block = ledger.get_latest_block()?;
-            if let Err(err) =
-                self.verify_header(&block.header, &parent_block.header, &merkle_root, &pedersen_merkle_root)
-            {
-                println!("header failed to verify: {:?}", err);
-                return Ok(false);
-            }
+            self.verify_header(&block.header, &parent_block.header, &merkle_root, &pedersen_merkle_root)?;
         }
+
         // Verify block amounts and check that there is a single coinbase transaction
 
         let mut coinbase_transaction_count = 0;
@@ -233,27 +422,32 @@ impl ConsensusParameters {
 
         // Check that there is only 1 coinbase transaction
         if coinbase_transaction_count > 1 {
-            println!("error - multiple coinbase transactions");
-            return Ok(false);
+            return Err(ConsensusError::MultipleCoinbaseTransactions(coinbase_transaction_count));
         }
 
         // Check that the block value balances are correct
         let expected_block_reward = get_block_reward(ledger.len() as u32).0;
         if total_value_balance.0 + expected_block_reward != 0 {
-            println!("total_value_balance: {:?}", total_value_balance);
-            println!("expected_block_reward: {:?}", expected_block_reward);
-
-            return Ok(false);
+            return Err(ConsensusError::InvalidBlockValueBalance(
+                total_value_balance.0,
+                -expected_block_reward,
+            ));
         }
 
         // Check that all the transction proofs verify
-        Ok(self.verify_transactions(parameters, &block.transactions.0, ledger)?)
+        if !self.verify_transactions(parameters, &block.transactions.0, ledger)? {
+            return Err(ConsensusError::InvalidTransactions);
+        }
+
+        Ok(())
     }
 
     /// Return whether or not the given block is valid and insert it.
     /// 1. Verify that the block header is valid.
     /// 2. Verify that the transactions are valid.
     /// 3. Insert/canonize block.
+    /// 4. Index the block records for the registered account view keys.
+    /// 5. Update the wallet accounts with the records received and spent in the block.
     pub fn process_block(
         &self,
         parameters: &PublicParameters<Components>,
@@ -266,21 +460,133 @@ impl ConsensusParameters {
         }
 
         // 1. Verify that the block valid
-        if !self.verify_block(parameters, block, storage)? {
+        let is_valid = METRICS.time(
+            "snarkos_block_verification_seconds",
+            "The time spent verifying blocks",
+            || self.verify_block(parameters, block, storage),
+        )?;
+
+        if !is_valid {
             return Err(ConsensusError::InvalidBlock(block.header.get_hash().0.to_vec()));
         }
 
         // 2. Insert/canonize block
         storage.insert_and_commit(block)?;
 
-        // 3. Remove transactions from the mempool
-        for transaction_id in block.transactions.to_transaction_ids()? {
-            memory_pool.remove_by_hash(&transaction_id)?;
+        // 3. Remove transactions and their conflicts from the mempool
+        for transaction_id in memory_pool.remove_block_transactions(&block.transactions)? {
+            debug!(
+                "Evicted transaction {} from the memory pool for conflicting with a block",
+                hex::encode(&transaction_id)
+            );
         }
 
+        // 4. Index the block records for the registered account view keys
+        Self::index_block_records(parameters, storage, block, storage.get_latest_block_height())?;
+
+        // 5. Update the wallet accounts with the records received and spent in the block
+        Wallet::new(parameters, storage).index_block(block, storage.get_latest_block_height())?;
+
+        // 6. Notify the memory pool subscribers, whose block templates no longer extend the chain
+        memory_pool.notify(MemoryPoolEvent::NewBlock(storage.get_latest_block_height()));
+
         Ok(())
     }
 
+    /// Register an account view key with the record index of the ledger and index
+    /// the records it can decrypt from the blocks that are already canon.
+    /// Records in pruned blocks are not indexed.
+    pub fn register_view_key(
+        parameters: &PublicParameters<Components>,
+        storage: &MerkleTreeLedger,
+        view_key: &AccountViewKey<Components>,
+    ) -> Result<(), ConsensusError> {
+        let view_key_bytes = to_bytes![view_key]?;
+
+        if storage.get_indexed_records(&view_key_bytes)?.is_some() {
+            return Ok(());
+        }
+
+        storage.register_view_key(&view_key_bytes)?;
+
+        let pruned_block_height = storage.get_pruned_block_height()?;
+
+        for block_number in 0..=storage.get_latest_block_height() {
+            if block_number != 0 && block_number <= pruned_block_height {
+                continue;
+            }
+
+            let block = storage.get_block_from_block_number(block_number)?;
+            let records = Self::decrypt_block_records(parameters, view_key, &block)?;
+
+            if !records.is_empty() {
+                storage.index_records(&view_key_bytes, block_number, &records)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Index the records of a canon block for every account view key registered with the record index.
+    pub fn index_block_records(
+        parameters: &PublicParameters<Components>,
+        storage: &MerkleTreeLedger,
+        block: &Block<Tx>,
+        block_number: u32,
+    ) -> Result<(), ConsensusError> {
+        for view_key_bytes in storage.get_registered_view_keys()? {
+            let view_key = AccountViewKey::<Components>::read(&view_key_bytes[..])?;
+            let records = Self::decrypt_block_records(parameters, &view_key, block)?;
+
+            if !records.is_empty() {
+                storage.index_records(&view_key_bytes, block_number, &records)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the non-dummy records of the block that can be decrypted by the account view key.
+    pub fn decrypt_block_records(
+        parameters: &PublicParameters<Components>,
+        view_key: &AccountViewKey<Components>,
+        block: &Block<Tx>,
+    ) -> Result<Vec<DPCRecord<Components>>, ConsensusError> {
+        let mut records = vec![];
+
+        for transaction in block.transactions.iter() {
+            records.extend(Self::decrypt_transaction_records(parameters, view_key, transaction)?);
+        }
+
+        Ok(records)
+    }
+
+    /// Returns the non-dummy records of the transaction that can be decrypted by the account view key.
+    pub fn decrypt_transaction_records(
+        parameters: &PublicParameters<Components>,
+        view_key: &AccountViewKey<Components>,
+        transaction: &Tx,
+    ) -> Result<Vec<DPCRecord<Components>>, ConsensusError> {
+        let decrypted_records =
+            RecordEncryption::decrypt_records(&parameters.system_parameters, view_key, &transaction.encrypted_records)?;
+
+        let mut records = vec![];
+
+        for (commitment, record) in transaction.new_commitments.iter().zip(decrypted_records) {
+            // A record encrypted for another account fails to decrypt or yields a different commitment
+            let record = match record {
+                Some(record) => record,
+                None => continue,
+            };
+
+            if !record.is_dummy() && &record.commitment() == commitment {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
     /// Receive a block from an external source and process it based on ledger state.
     pub fn receive_block(
         &self,
@@ -294,6 +600,9 @@ impl ConsensusParameters {
             return Err(ConsensusError::BlockTooLarge(block_size, self.max_block_size));
         }
 
+        // Connect the block and its descendants, or fork to its side chain, exclusively
+        let _block_lock = storage.lock_blocks();
+
         // Block is an unknown orphan
         if !storage.previous_block_hash_exists(block) && !storage.is_previous_block_canon(&block.header) {
             debug!("Processing a block that is an unknown orphan");
@@ -341,16 +650,27 @@ impl ConsensusParameters {
                         // Fork to superior side chain
                         storage.revert_for_fork(&side_chain_path)?;
 
-                        if !side_chain_path.path.is_empty() {
-                            for block_hash in side_chain_path.path {
-                                if block_hash == block.header.get_hash() {
-                                    self.process_block(parameters, &storage, memory_pool, &block)?
-                                } else {
-                                    let new_block = storage.get_block(&block_hash)?;
-                                    self.process_block(parameters, &storage, memory_pool, &new_block)?;
-                                }
+                        for block_hash in side_chain_path.path {
+                            let result = if block_hash == block.header.get_hash() {
+                                self.process_block(parameters, &storage, memory_pool, &block)
+                            } else {
+                                storage
+                                    .get_block(&block_hash)
+                                    .map_err(ConsensusError::from)
+                                    .and_then(|new_block| {
+                                        self.process_block(parameters, &storage, memory_pool, &new_block)
+                                    })
+                            };
+
+                            // Restore the canon chain if the side chain is invalid
+                            if let Err(error) = result {
+                                warn!("Failed to fork to the side chain, restoring the canon chain");
+                                storage.rollback_fork()?;
+                                return Err(error);
                             }
                         }
+
+                        storage.complete_fork()?;
                     } else {
                         // If the sidechain is not longer than the main canon chain, simply store the block
                         storage.insert_only(block)?;
@@ -376,6 +696,53 @@ impl ConsensusParameters {
         ledger: &MerkleTreeLedger,
         rng: &mut R,
     ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        self.create_split_coinbase_transaction(
+            block_num,
+            transactions,
+            parameters,
+            program_vk_hash,
+            new_birth_program_ids,
+            new_death_program_ids,
+            &[(recipient, 100)],
+            None,
+            ledger,
+            rng,
+        )
+    }
+
+    /// Generate a coinbase transaction given candidate block transactions, splitting the block reward
+    /// and fees between the recipients by percentage, with one output record for each recipient.
+    /// The remainder of the split is sent to the first recipient. The memo carries the miner tag, if any.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_split_coinbase_transaction<R: Rng>(
+        &self,
+        block_num: u32,
+        transactions: &DPCTransactions<Tx>,
+        parameters: &PublicParameters<Components>,
+        program_vk_hash: Vec<u8>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        recipients: &[(AccountAddress<Components>, u8)],
+        miner_tag: Option<&str>,
+        ledger: &MerkleTreeLedger,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        if recipients.is_empty() || recipients.len() > Components::NUM_OUTPUT_RECORDS {
+            return Err(ConsensusError::InvalidCoinbasePayout(format!(
+                "{} recipients, expected 1 to {}",
+                recipients.len(),
+                Components::NUM_OUTPUT_RECORDS
+            )));
+        }
+
+        let total_percent: u32 = recipients.iter().map(|(_, percent)| u32::from(*percent)).sum();
+        if total_percent != 100 {
+            return Err(ConsensusError::InvalidCoinbasePayout(format!(
+                "the percentages sum to {} instead of 100",
+                total_percent
+            )));
+        }
+
         let mut total_value_balance = get_block_reward(block_num);
 
         for transaction in transactions.iter() {
@@ -422,17 +789,25 @@ impl ConsensusParameters {
             old_records.push(old_record);
         }
 
-        let new_record_owners = vec![recipient; Components::NUM_OUTPUT_RECORDS];
-        let new_is_dummy_flags = [vec![false], vec![true; Components::NUM_OUTPUT_RECORDS - 1]].concat();
-        let new_values = [vec![total_value_balance.0 as u64], vec![
-            0;
-            Components::NUM_OUTPUT_RECORDS
-                - 1
-        ]]
-        .concat();
+        let total_value = total_value_balance.0 as u64;
+        let mut new_values: Vec<u64> = recipients
+            .iter()
+            .map(|(_, percent)| (u128::from(total_value) * u128::from(*percent) / 100) as u64)
+            .collect();
+        new_values[0] += total_value - new_values.iter().sum::<u64>();
+
+        // The dummy output records are owned by the first recipient
+        let num_dummy_records = Components::NUM_OUTPUT_RECORDS - recipients.len();
+        let mut new_record_owners: Vec<_> = recipients.iter().map(|(recipient, _)| recipient.clone()).collect();
+        new_record_owners.extend(vec![recipients[0].0.clone(); num_dummy_records]);
+        let new_is_dummy_flags = [vec![false; recipients.len()], vec![true; num_dummy_records]].concat();
+        new_values.extend(vec![0; num_dummy_records]);
         let new_payloads = vec![RecordPayload::default(); NUM_OUTPUT_RECORDS];
 
-        let memo: [u8; 32] = rng.gen();
+        let memo = match miner_tag {
+            Some(tag) => TransactionMemo::Plaintext(miner_tag_memo(tag, rng)?),
+            None => TransactionMemo::random(rng),
+        };
 
         self.create_transaction(
             parameters,
@@ -450,7 +825,8 @@ impl ConsensusParameters {
         )
     }
 
-    /// Generate a transaction by spending old records and specifying new record attributes
+    /// Generate a transaction by spending old records and specifying new record attributes.
+    /// The birth and death programs of the records are the noop program.
     #[allow(clippy::too_many_arguments)]
     pub fn create_transaction<R: Rng>(
         &self,
@@ -463,76 +839,234 @@ impl ConsensusParameters {
         new_is_dummy_flags: Vec<bool>,
         new_values: Vec<u64>,
         new_payloads: Vec<RecordPayload>,
-        memo: [u8; 32],
+        memo: TransactionMemo,
         ledger: &MerkleTreeLedger,
         rng: &mut R,
     ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
-        // Offline execution to generate a DPC transaction
-        let execute_context = <InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::execute_offline(
-            parameters.system_parameters.clone(),
+        self.create_transaction_with_programs(
+            parameters,
+            &ProgramRegistry::new(parameters)?,
             old_records,
             old_account_private_keys,
             new_record_owners,
-            &new_is_dummy_flags,
-            &new_values,
+            new_birth_program_ids,
+            new_death_program_ids,
+            new_is_dummy_flags,
+            new_values,
             new_payloads,
+            memo,
+            ledger,
+            rng,
+        )
+    }
+
+    /// Generate a transaction like `create_transaction`, proving the birth and death of each record
+    /// with its program in the given registry.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_transaction_with_programs<R: Rng>(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        programs: &ProgramRegistry<Components>,
+        old_records: Vec<DPCRecord<Components>>,
+        old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+        new_record_owners: Vec<AccountAddress<Components>>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        new_is_dummy_flags: Vec<bool>,
+        new_values: Vec<u64>,
+        new_payloads: Vec<RecordPayload>,
+        memo: TransactionMemo,
+        ledger: &MerkleTreeLedger,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        let execute_context = self.execute_offline(
+            parameters,
+            old_records,
+            old_account_private_keys,
+            new_record_owners,
             new_birth_program_ids,
             new_death_program_ids,
+            new_is_dummy_flags,
+            new_values,
+            new_payloads,
             memo,
-            self.network.id(),
             rng,
         )?;
 
-        // Construct the program proofs
+        let (old_death_program_proofs, new_birth_program_proofs) =
+            programs.execute(&execute_context.into_local_data(), rng)?;
 
-        let local_data = execute_context.into_local_data();
+        // Online execution to generate a DPC transaction
+        let (new_records, transaction) = InstantiatedDPC::execute_online(
+            &parameters,
+            execute_context,
+            old_death_program_proofs,
+            new_birth_program_proofs,
+            ledger,
+            rng,
+        )?;
 
-        let noop_program_snark_id = to_bytes![ProgramVerificationKeyCRH::hash(
-            &parameters.system_parameters.program_verification_key_crh,
-            &to_bytes![parameters.noop_program_snark_parameters.verification_key]?
-        )?]?;
+        Ok((new_records, transaction))
+    }
 
-        let dpc_program =
-            NoopProgram::<_, <Components as BaseDPCComponents>::NoopProgramSNARK>::new(noop_program_snark_id);
+    /// Generate a transaction like `create_transaction`, without access to the ledger.
+    /// The ledger membership of the old records is proven with the given witnesses against the
+    /// given ledger digest, as returned by the `createtransactionskeleton` RPC endpoint of a full node.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_transaction_with_witnesses<R: Rng>(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        old_records: Vec<DPCRecord<Components>>,
+        old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+        new_record_owners: Vec<AccountAddress<Components>>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        new_is_dummy_flags: Vec<bool>,
+        new_values: Vec<u64>,
+        new_payloads: Vec<RecordPayload>,
+        memo: TransactionMemo,
+        ledger_parameters: &CommitmentMerkleParameters,
+        ledger_digest: MerkleTreeDigest<CommitmentMerkleParameters>,
+        old_witnesses: Vec<MerklePath<CommitmentMerkleParameters>>,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        let authorization = self.authorize_transaction(
+            parameters,
+            old_records,
+            old_account_private_keys,
+            new_record_owners,
+            new_birth_program_ids,
+            new_death_program_ids,
+            new_is_dummy_flags,
+            new_values,
+            new_payloads,
+            memo,
+            ledger_digest,
+            old_witnesses,
+            rng,
+        )?;
 
-        let mut old_death_program_proofs = Vec::with_capacity(NUM_INPUT_RECORDS);
-        for i in 0..NUM_INPUT_RECORDS {
-            let private_input = dpc_program.execute(
-                &parameters.noop_program_snark_parameters.proving_key,
-                &parameters.noop_program_snark_parameters.verification_key,
-                &local_data,
-                i as u8,
-                rng,
-            )?;
+        Self::prove_transaction(
+            parameters,
+            &ProgramRegistry::new(parameters)?,
+            authorization,
+            ledger_parameters,
+            rng,
+        )
+    }
 
-            old_death_program_proofs.push(private_input);
-        }
+    /// Authorize a transaction spending old records into new records, without proving it.
+    /// Only the authorization needs the private keys of the old records, and it is cheap enough
+    /// for an offline wallet. The authorization is proven by `prove_transaction`, on any machine.
+    #[allow(clippy::too_many_arguments)]
+    pub fn authorize_transaction<R: Rng>(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        old_records: Vec<DPCRecord<Components>>,
+        old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+        new_record_owners: Vec<AccountAddress<Components>>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        new_is_dummy_flags: Vec<bool>,
+        new_values: Vec<u64>,
+        new_payloads: Vec<RecordPayload>,
+        memo: TransactionMemo,
+        ledger_digest: MerkleTreeDigest<CommitmentMerkleParameters>,
+        old_witnesses: Vec<MerklePath<CommitmentMerkleParameters>>,
+        rng: &mut R,
+    ) -> Result<TransactionAuthorization<Components>, ConsensusError> {
+        let execute_context = self.execute_offline(
+            parameters,
+            old_records,
+            old_account_private_keys,
+            new_record_owners,
+            new_birth_program_ids,
+            new_death_program_ids,
+            new_is_dummy_flags,
+            new_values,
+            new_payloads,
+            memo,
+            rng,
+        )?;
 
-        let mut new_birth_program_proofs = Vec::with_capacity(NUM_OUTPUT_RECORDS);
-        for j in 0..NUM_OUTPUT_RECORDS {
-            let private_input = dpc_program.execute(
-                &parameters.noop_program_snark_parameters.proving_key,
-                &parameters.noop_program_snark_parameters.verification_key,
-                &local_data,
-                (NUM_INPUT_RECORDS + j) as u8,
-                rng,
-            )?;
+        Ok(InstantiatedDPC::authorize(
+            execute_context,
+            ledger_digest,
+            old_witnesses,
+            rng,
+        )?)
+    }
 
-            new_birth_program_proofs.push(private_input);
-        }
+    /// Prove a transaction authorized by `authorize_transaction`, which needs no private keys.
+    /// The birth and death of each record is proven with its program in the given registry.
+    pub fn prove_transaction<R: Rng>(
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        programs: &ProgramRegistry<Components>,
+        authorization: TransactionAuthorization<Components>,
+        ledger_parameters: &CommitmentMerkleParameters,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        let local_data = authorization.to_local_data(&parameters.system_parameters)?;
+        let (old_death_program_proofs, new_birth_program_proofs) = programs.execute(&local_data, rng)?;
 
-        // Online execution to generate a DPC transaction
-        let (new_records, transaction) = InstantiatedDPC::execute_online(
-            &parameters,
-            execute_context,
+        let (new_records, transaction) = InstantiatedDPC::execute_authorized(
+            parameters,
+            authorization,
             old_death_program_proofs,
             new_birth_program_proofs,
-            ledger,
+            ledger_parameters,
             rng,
         )?;
 
         Ok((new_records, transaction))
     }
+
+    /// Execute the offline part of a transaction.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_offline<R: Rng>(
+        &self,
+        parameters: &<InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::Parameters,
+        old_records: Vec<DPCRecord<Components>>,
+        old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+        new_record_owners: Vec<AccountAddress<Components>>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        new_is_dummy_flags: Vec<bool>,
+        new_values: Vec<u64>,
+        new_payloads: Vec<RecordPayload>,
+        memo: TransactionMemo,
+        rng: &mut R,
+    ) -> Result<ExecuteContext<Components>, ConsensusError> {
+        // The encrypted memo is derived from the first new record, so the records are generated
+        // with a random memo first
+        let (plaintext_memo, encrypted_message) = match memo {
+            TransactionMemo::Plaintext(memo) => (memo, None),
+            TransactionMemo::Encrypted(message) => (rng.gen(), Some(message)),
+        };
+
+        // Offline execution to generate a DPC transaction
+        let mut execute_context = <InstantiatedDPC as DPCScheme<MerkleTreeLedger>>::execute_offline(
+            parameters.system_parameters.clone(),
+            old_records,
+            old_account_private_keys,
+            new_record_owners,
+            &new_is_dummy_flags,
+            &new_values,
+            new_payloads,
+            new_birth_program_ids,
+            new_death_program_ids,
+            plaintext_memo,
+            self.network.id(),
+            rng,
+        )?;
+
+        if let Some(message) = encrypted_message {
+            let memo = encrypt_memo(&execute_context.new_records()[0], &message)?;
+            execute_context.set_memorandum(memo)?;
+        }
+
+        Ok(execute_context)
+    }
 }
 
 #[cfg(test)]
@@ -540,7 +1074,12 @@ mod tests {
     use super::*;
     use rand::{thread_rng, Rng};
     use snarkos_objects::PedersenMerkleRootHash;
-    use snarkos_testing::consensus::DATA;
+    use snarkos_testing::{
+        consensus::{DATA, FIXTURE_VK, TEST_CONSENSUS, TRANSACTION_1},
+        storage::kill_storage_sync,
+    };
+
+    use std::sync::Arc;
 
     #[test]
     fn test_block_rewards() {
@@ -595,6 +1134,9 @@ mod tests {
             network: Network::Mainnet,
             verifier: posw,
             authorized_inner_snark_ids: vec![],
+            fixed_difficulty_target: None,
+            max_template_size: None,
+            max_template_weight: None,
         };
 
         let b1 = DATA.block_1.clone();
@@ -610,6 +1152,14 @@ mod tests {
             .verify_header(&h2, &h1, &merkle_root_hash, &pedersen_merkle_root)
             .unwrap();
 
+        // the work of a header is verified without its transactions
+        let mut h2_work = h2.clone();
+        h2_work.merkle_root_hash = MerkleRootHash([3; 32]);
+        consensus.verify_header_work(&h2_work, &h1).unwrap();
+
+        h2_work.difficulty_target = 100;
+        consensus.verify_header_work(&h2_work, &h1).unwrap_err();
+
         // invalid parent hash
         let mut h2_err = h2.clone();
         h2_err.previous_block_hash = BlockHeaderHash([9; 32]);
@@ -666,4 +1216,157 @@ mod tests {
             .verify_header(&h2_err, &h1, &merkle_root_hash, &pedersen_merkle_root)
             .unwrap_err();
     }
+
+    #[test]
+    fn fixed_difficulty_target() {
+        let consensus = ConsensusParameters {
+            max_block_size: 1_000_000usize,
+            max_nonce: std::u32::MAX - 1,
+            target_block_time: 2i64, //unix seconds
+            network: Network::Devnet,
+            verifier: PoswMarlin::verify_only().unwrap(),
+            authorized_inner_snark_ids: vec![],
+            fixed_difficulty_target: Some(u64::MAX),
+            max_template_size: None,
+            max_template_weight: None,
+        };
+
+        let h1 = DATA.block_1.header.clone();
+
+        // The difficulty is not retargeted, however fast or slow the block is found
+        assert_eq!(consensus.get_block_difficulty(&h1, h1.time + 1), u64::MAX);
+        assert_eq!(consensus.get_block_difficulty(&h1, h1.time + 1000), u64::MAX);
+    }
+
+    #[test]
+    fn template_size() {
+        let mut consensus = TEST_CONSENSUS.clone();
+        assert_eq!(consensus.template_size(), consensus.max_block_size);
+
+        // The configured size only ever lowers the maximum block size
+        consensus.max_template_size = Some(consensus.max_block_size / 2);
+        assert_eq!(consensus.template_size(), consensus.max_block_size / 2);
+
+        consensus.max_template_size = Some(consensus.max_block_size * 2);
+        assert_eq!(consensus.template_size(), consensus.max_block_size);
+    }
+
+    #[test]
+    fn template_weight() {
+        let mut consensus = TEST_CONSENSUS.clone();
+        assert_eq!(consensus.template_weight(), usize::MAX);
+
+        consensus.max_template_weight = Some(10);
+        assert_eq!(consensus.template_weight(), 10);
+    }
+
+    #[test]
+    fn index_block_records() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let parameters = &FIXTURE_VK.parameters;
+        let account = &FIXTURE_VK.test_accounts[0];
+
+        let view_key = AccountViewKey::<Components>::from_private_key(
+            &parameters.system_parameters.account_signature,
+            &parameters.system_parameters.account_commitment,
+            &account.private_key,
+        )
+        .unwrap();
+        let view_key_bytes = to_bytes![view_key].unwrap();
+
+        let records_for_account = |records: &[DPCRecord<Components>]| -> Vec<Vec<u8>> {
+            records
+                .iter()
+                .filter(|record| !record.is_dummy() && record.owner() == &account.address)
+                .map(|record| to_bytes![record.commitment()].unwrap().to_vec())
+                .collect()
+        };
+        let records_1 = records_for_account(&DATA.records_1);
+        let records_2 = records_for_account(&DATA.records_2);
+        assert!(!records_1.is_empty());
+
+        blockchain.insert_and_commit(&DATA.block_1).unwrap();
+
+        // Registering the view key indexes the records of the canon blocks
+
+        ConsensusParameters::register_view_key(parameters, &blockchain, &view_key).unwrap();
+
+        let indexed_records = blockchain.get_indexed_records(&view_key_bytes).unwrap().unwrap();
+        assert_eq!(
+            indexed_records,
+            records_1.iter().map(|cm| (1, cm.clone())).collect::<Vec<_>>()
+        );
+
+        // Newly committed blocks are indexed
+
+        blockchain.insert_and_commit(&DATA.block_2).unwrap();
+        ConsensusParameters::index_block_records(parameters, &blockchain, &DATA.block_2, 2).unwrap();
+
+        let indexed_records = blockchain.get_indexed_records(&view_key_bytes).unwrap().unwrap();
+        // Besides its coinbase records, block 2 holds other transactions paying the account
+        assert!(indexed_records.len() >= records_1.len() + records_2.len());
+        for commitment in &records_2 {
+            assert!(indexed_records.contains(&(2, commitment.clone())));
+        }
+
+        for (_, commitment) in &indexed_records {
+            assert!(blockchain
+                .get_record::<DPCRecord<Components>>(commitment)
+                .unwrap()
+                .is_some());
+        }
+
+        // Decommitted blocks are removed from the index
+
+        blockchain.decommit_latest_block().unwrap();
+
+        let indexed_records = blockchain.get_indexed_records(&view_key_bytes).unwrap().unwrap();
+        assert_eq!(
+            indexed_records,
+            records_1.iter().map(|cm| (1, cm.clone())).collect::<Vec<_>>()
+        );
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn dry_run_transaction_diagnostics() {
+        let parameters = &FIXTURE_VK.parameters;
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+
+        let dry_run = TEST_CONSENSUS
+            .dry_run_transaction(parameters, &transaction, &blockchain)
+            .unwrap();
+        assert_eq!(dry_run.transaction_id, transaction.transaction_id().unwrap());
+        assert_eq!(dry_run.checks.len(), 11);
+
+        // The coinbase transaction is only rejected for its value balance against the fresh ledger
+        let failed_checks: Vec<_> = dry_run.failed_checks().iter().map(|check| check.name).collect();
+        assert_eq!(failed_checks, vec!["value_balance"]);
+
+        // Once the transaction is in the ledger, its memo, serial numbers and commitments are double spends
+        blockchain.insert_and_commit(&DATA.block_1).unwrap();
+
+        let dry_run = TEST_CONSENSUS
+            .dry_run_transaction(parameters, &transaction, &blockchain)
+            .unwrap();
+        let failed_checks: Vec<_> = dry_run.failed_checks().iter().map(|check| check.name).collect();
+        assert_eq!(failed_checks, vec![
+            "value_balance",
+            "memo",
+            "serial_numbers",
+            "commitments"
+        ]);
+        assert!(!dry_run.is_valid());
+        assert!(dry_run.failed_checks()[2].reason.is_some());
+
+        // The stateless checks are unchanged
+        let stateless_dry_run = TEST_CONSENSUS
+            .dry_run_transaction_stateless(parameters, &transaction, blockchain.parameters())
+            .unwrap();
+        assert_eq!(stateless_dry_run.checks[..], dry_run.checks[..7]);
+
+        kill_storage_sync(blockchain);
+    }
 }
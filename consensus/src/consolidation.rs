@@ -0,0 +1,198 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ConsensusParameters, MerkleTreeLedger, TransactionBuilder};
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
+use snarkos_errors::{consensus::ConsensusError, dpc::DPCError};
+use snarkos_models::{
+    dpc::{DPCComponents, Record},
+    objects::LedgerScheme,
+};
+use snarkos_objects::{AccountAddress, AccountPrivateKey};
+
+use rand::Rng;
+
+/// Consolidates many small records of an account into fewer records, for accounts whose value is fragmented
+/// across more records than a transaction can spend.
+///
+/// A transaction spends at most `NUM_INPUT_RECORDS` records, so the consolidation runs in rounds. Each round
+/// spends the smallest records in groups of `NUM_INPUT_RECORDS`, each group into a single record sent back to the
+/// account, and the records of a round are spent by the next round. A transaction only spends records
+/// of the ledger, so the next round is built once the transactions of the previous round are confirmed.
+#[derive(Clone)]
+pub struct ConsolidationBuilder {
+    private_key: AccountPrivateKey<Components>,
+    records: Vec<DPCRecord<Components>>,
+    max_record_value: Option<u64>,
+    target_records: usize,
+    fee: u64,
+}
+
+impl ConsolidationBuilder {
+    /// Creates a consolidation of the records owned by the account of the given private key,
+    /// into a single record sent back to the account, without fees.
+    pub fn new(private_key: AccountPrivateKey<Components>) -> Self {
+        Self {
+            private_key,
+            records: vec![],
+            max_record_value: None,
+            target_records: 1,
+            fee: 0,
+        }
+    }
+
+    /// Only selects the records of at most the given value. Applies to the records selected afterwards.
+    pub fn max_record_value(mut self, max_record_value: u64) -> Self {
+        self.max_record_value = Some(max_record_value);
+        self
+    }
+
+    /// Stops the consolidation once at most the given number of records are left, one by default.
+    pub fn target_records(mut self, target_records: usize) -> Self {
+        self.target_records = target_records.max(1);
+        self
+    }
+
+    /// Pays the given fee in each transaction of the consolidation.
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Selects the records to consolidate among the given unspent records. The dummy records, the records
+    /// owned by other accounts and the records above the maximum value are left out.
+    pub fn select_records<I: IntoIterator<Item = DPCRecord<Components>>>(
+        mut self,
+        parameters: &PublicParameters<Components>,
+        records: I,
+    ) -> Result<Self, ConsensusError> {
+        let address = self.address(parameters)?;
+
+        for record in records {
+            if record.is_dummy() || record.owner() != &address {
+                continue;
+            }
+            if let Some(max_record_value) = self.max_record_value {
+                if record.value() > max_record_value {
+                    continue;
+                }
+            }
+            if self
+                .records
+                .iter()
+                .any(|selected| selected.commitment() == record.commitment())
+            {
+                continue;
+            }
+
+            self.records.push(record);
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the records left to consolidate, including the records created by the previous rounds.
+    pub fn records(&self) -> &[DPCRecord<Components>] {
+        &self.records
+    }
+
+    /// Returns true if no more than the target number of records are left.
+    pub fn is_complete(&self) -> bool {
+        self.records.len() <= self.target_records
+    }
+
+    /// Returns true if the records left to consolidate are all in the ledger,
+    /// so the transactions of the previous round are confirmed.
+    pub fn is_ready(&self, ledger: &MerkleTreeLedger) -> bool {
+        self.records
+            .iter()
+            .all(|record| ledger.contains_cm(&record.commitment()))
+    }
+
+    /// Generates the transactions of the next round of the consolidation, with the given program as the
+    /// birth and death program of their records. The records they create replace the records they spend,
+    /// and are spent by the next round once the transactions are confirmed. Returns no transactions once
+    /// the consolidation is complete.
+    pub fn build_round<R: Rng>(
+        &mut self,
+        consensus: &ConsensusParameters,
+        parameters: &PublicParameters<Components>,
+        program_id: Vec<u8>,
+        ledger: &MerkleTreeLedger,
+        rng: &mut R,
+    ) -> Result<Vec<Tx>, ConsensusError> {
+        if self.is_complete() {
+            return Ok(vec![]);
+        }
+        if !self.is_ready(ledger) {
+            return Err(ConsensusError::InvalidTransactionRecords(
+                "the records of the previous round are not in the ledger".into(),
+            ));
+        }
+
+        let address = self.address(parameters)?;
+
+        // The smallest records are consolidated first, each group removing all but one of its records
+        let mut records = self.records.clone();
+        records.sort_by_key(|record| record.value());
+
+        let mut excess_records = records.len() - self.target_records;
+        let mut remaining_records = records.into_iter();
+        let mut consolidated_records = vec![];
+        let mut transactions = vec![];
+
+        while excess_records > 0 && remaining_records.len() > 1 {
+            let group: Vec<_> = remaining_records
+                .by_ref()
+                .take(Components::NUM_INPUT_RECORDS.min(excess_records + 1))
+                .collect();
+            excess_records -= group.len() - 1;
+
+            let value: u128 = group.iter().map(|record| u128::from(record.value())).sum();
+            if value <= u128::from(self.fee) || value - u128::from(self.fee) > u128::from(u64::MAX) {
+                return Err(ConsensusError::TransactionOverspending);
+            }
+
+            let mut builder = TransactionBuilder::new();
+            for record in group {
+                builder = builder.add_input(record, self.private_key.clone())?;
+            }
+
+            let (new_records, transaction) = builder
+                .add_output(address.clone(), (value - u128::from(self.fee)) as u64)?
+                .build(consensus, parameters, program_id.clone(), ledger, rng)?;
+
+            consolidated_records.extend(new_records.into_iter().filter(|record| !record.is_dummy()));
+            transactions.push(transaction);
+        }
+
+        consolidated_records.extend(remaining_records);
+        self.records = consolidated_records;
+
+        Ok(transactions)
+    }
+
+    /// Returns the address of the account consolidating its records.
+    fn address(&self, parameters: &PublicParameters<Components>) -> Result<AccountAddress<Components>, ConsensusError> {
+        Ok(AccountAddress::<Components>::from_private_key(
+            parameters.account_signature_parameters(),
+            parameters.account_commitment_parameters(),
+            parameters.account_encryption_parameters(),
+            &self.private_key,
+        )
+        .map_err(DPCError::from)?)
+    }
+}
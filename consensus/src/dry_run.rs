@@ -0,0 +1,67 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+/// A check a transaction is verified with, and the reason it failed the check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionCheck {
+    /// The name of the check
+    pub name: &'static str,
+    /// Whether the transaction passed the check
+    pub passed: bool,
+    /// The reason the transaction failed the check
+    pub reason: Option<String>,
+}
+
+/// The diagnostics of a transaction verified without being added to the memory pool.
+///
+/// Every check is run, rather than stopping at the first failed check, so that each reason a transaction
+/// is invalid for is reported.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionDryRun {
+    /// The id of the transaction
+    pub transaction_id: [u8; 32],
+    /// The result of each check, in order
+    pub checks: Vec<TransactionCheck>,
+}
+
+impl TransactionDryRun {
+    /// Creates the diagnostics of the given transaction, without checks.
+    pub fn new(transaction_id: [u8; 32]) -> Self {
+        Self {
+            transaction_id,
+            checks: vec![],
+        }
+    }
+
+    /// Records a check, with the reason it failed for if the transaction did not pass it.
+    pub fn check<F: FnOnce() -> String>(&mut self, name: &'static str, passed: bool, reason: F) {
+        self.checks.push(TransactionCheck {
+            name,
+            passed,
+            reason: if passed { None } else { Some(reason()) },
+        });
+    }
+
+    /// Returns true if the transaction passed every check.
+    pub fn is_valid(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Returns the checks the transaction failed.
+    pub fn failed_checks(&self) -> Vec<&TransactionCheck> {
+        self.checks.iter().filter(|check| !check.passed).collect()
+    }
+}
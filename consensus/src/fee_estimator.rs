@@ -0,0 +1,258 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    memory_pool::{MemoryPool, BLOCK_HEADER_SIZE, COINBASE_TRANSACTION_SIZE},
+    ConsensusParameters,
+    MerkleTreeLedger,
+};
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_models::objects::Transaction;
+use snarkos_objects::Block;
+
+use std::cmp::Ordering;
+
+/// The number of recent canon blocks sampled by the fee estimator.
+pub const FEE_ESTIMATION_BLOCKS: u32 = 100;
+
+/// The ratio of the sampled blocks a fee estimate aims to have been sufficient for.
+pub const FEE_ESTIMATION_CONFIDENCE: f64 = 0.9;
+
+/// A recommended fee rate for a transaction to be included within a target number of blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeEstimate {
+    /// The number of blocks the transaction should be included within
+    pub target_blocks: u32,
+    /// The recommended fee rate, per byte of the transaction
+    pub fee_rate: f64,
+    /// The ratio of the sampled runs of `target_blocks` consecutive blocks that would have
+    /// included a transaction paying the recommended fee rate
+    pub confidence: f64,
+    /// The fee rate outbidding the memory pool transactions filling the next `target_blocks` blocks
+    pub memory_pool_fee_rate: f64,
+    /// The number of sampled blocks
+    pub sampled_blocks: u32,
+}
+
+/// The fee of a transaction paying a fee rate, computed before the transaction is proven.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeCalculation {
+    /// The size of the transaction, in bytes
+    pub transaction_size: usize,
+    /// The fee rate, per byte of the transaction
+    pub fee_rate: f64,
+    /// The fee paying the fee rate, rounded up
+    pub fee: u64,
+}
+
+/// Returns the fee of a transaction paying the given fee rate, per byte of the transaction.
+///
+/// Every transaction is of the size of `Tx::estimated_size`, which counts the serial numbers, commitments,
+/// signatures, encrypted records, proof and memorandum of the transaction. The fee is hence exact before
+/// the transaction is proven, and is paid as the value balance of the transaction.
+pub fn calculate_fee(fee_rate: f64) -> Result<FeeCalculation, ConsensusError> {
+    if !fee_rate.is_finite() || fee_rate < 0.0 {
+        return Err(ConsensusError::InvalidFeeRate(fee_rate));
+    }
+
+    let transaction_size = Tx::estimated_size()?;
+    let fee = (fee_rate * transaction_size as f64).ceil() as u64;
+
+    Ok(FeeCalculation {
+        transaction_size,
+        fee_rate,
+        fee,
+    })
+}
+
+impl ConsensusParameters {
+    /// Estimate the fee rate for a transaction to be included within the target number of blocks.
+    ///
+    /// The estimate is the higher of the fee rate needed to outbid the memory pool transactions
+    /// filling the next `target_blocks` blocks, and the fee rate that would have been included
+    /// within `target_blocks` blocks for `FEE_ESTIMATION_CONFIDENCE` of the recent canon blocks.
+    pub fn estimate_fee(
+        &self,
+        storage: &MerkleTreeLedger,
+        memory_pool: &MemoryPool<Tx>,
+        target_blocks: u32,
+    ) -> Result<FeeEstimate, ConsensusError> {
+        if target_blocks == 0 || target_blocks > FEE_ESTIMATION_BLOCKS {
+            return Err(ConsensusError::InvalidFeeEstimationTarget(
+                target_blocks,
+                FEE_ESTIMATION_BLOCKS,
+            ));
+        }
+
+        let memory_pool_fee_rate = self.memory_pool_fee_rate(memory_pool, target_blocks);
+
+        // The lowest fee rate each recent block would have included, from the oldest block
+        let latest_block_height = storage.get_latest_block_height();
+        let start_block_height = latest_block_height.saturating_sub(FEE_ESTIMATION_BLOCKS - 1);
+
+        let mut block_fee_rates = vec![];
+        for block_height in start_block_height..=latest_block_height {
+            let block = storage.get_block_from_block_number(block_height)?;
+            block_fee_rates.push(self.block_fee_rate(&block)?);
+        }
+
+        // A transaction is included within a run of blocks if it outbids any block of the run
+        let mut run_fee_rates: Vec<f64> = block_fee_rates
+            .windows((target_blocks as usize).min(block_fee_rates.len()))
+            .map(|run| run.iter().cloned().fold(f64::INFINITY, f64::min))
+            .collect();
+        run_fee_rates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let confident_index = (run_fee_rates.len() as f64 * FEE_ESTIMATION_CONFIDENCE).ceil() as usize;
+        let historical_fee_rate = run_fee_rates[confident_index.saturating_sub(1)];
+
+        let fee_rate = historical_fee_rate.max(memory_pool_fee_rate);
+        let sufficient_runs = run_fee_rates
+            .iter()
+            .filter(|run_fee_rate| **run_fee_rate <= fee_rate)
+            .count();
+
+        Ok(FeeEstimate {
+            target_blocks,
+            fee_rate,
+            confidence: sufficient_runs as f64 / run_fee_rates.len() as f64,
+            memory_pool_fee_rate,
+            sampled_blocks: block_fee_rates.len() as u32,
+        })
+    }
+
+    /// Returns the fee rate of the memory pool transaction that would not fit in the next
+    /// `target_blocks` blocks, or zero if the memory pool transactions all fit.
+    fn memory_pool_fee_rate(&self, memory_pool: &MemoryPool<Tx>, target_blocks: u32) -> f64 {
+        let block_capacity = self
+            .max_block_size
+            .saturating_sub(BLOCK_HEADER_SIZE + COINBASE_TRANSACTION_SIZE);
+        let capacity = block_capacity.saturating_mul(target_blocks as usize);
+
+        let mut fee_rates: Vec<(f64, usize)> = memory_pool
+            .transactions
+            .values()
+            .map(|entry| (fee_rate(&entry.transaction, entry.size), entry.size))
+            .collect();
+        fee_rates.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        let mut size = 0;
+        for (fee_rate, transaction_size) in fee_rates {
+            size += transaction_size;
+
+            if size > capacity {
+                return fee_rate;
+            }
+        }
+
+        0.0
+    }
+
+    /// Returns the lowest fee rate of the transactions in a full block,
+    /// or zero if the block had room for another of its transactions.
+    fn block_fee_rate(&self, block: &Block<Tx>) -> Result<f64, ConsensusError> {
+        let mut lowest_fee_rate = f64::INFINITY;
+        let mut smallest_size = usize::MAX;
+
+        for transaction in block.transactions.iter() {
+            // The coinbase transaction pays no fee
+            if transaction.value_balance().is_negative() {
+                continue;
+            }
+
+            let size = transaction.size();
+
+            lowest_fee_rate = lowest_fee_rate.min(fee_rate(transaction, size));
+            smallest_size = smallest_size.min(size);
+        }
+
+        let block_size = block.serialize()?.len();
+
+        if smallest_size == usize::MAX || block_size + smallest_size <= self.max_block_size {
+            return Ok(0.0);
+        }
+
+        Ok(lowest_fee_rate)
+    }
+}
+
+/// Returns the fee paid by the transaction, per byte.
+fn fee_rate(transaction: &Tx, size: usize) -> f64 {
+    let fee: i64 = transaction.value_balance().into();
+
+    fee as f64 / size as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_pool::Entry;
+    use snarkos_testing::{consensus::*, storage::*};
+    use snarkos_utilities::FromBytes;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn estimate_fee() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut memory_pool = MemoryPool::new();
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        memory_pool.insert(&blockchain, Entry { size, transaction }).unwrap();
+
+        // The memory pool and the genesis block have room for any transaction
+        let fee_estimate = TEST_CONSENSUS.estimate_fee(&blockchain, &memory_pool, 1).unwrap();
+
+        assert_eq!(fee_estimate, FeeEstimate {
+            target_blocks: 1,
+            fee_rate: 0.0,
+            confidence: 1.0,
+            memory_pool_fee_rate: 0.0,
+            sampled_blocks: 1,
+        });
+
+        assert!(TEST_CONSENSUS.estimate_fee(&blockchain, &memory_pool, 0).is_err());
+        assert!(TEST_CONSENSUS
+            .estimate_fee(&blockchain, &memory_pool, FEE_ESTIMATION_BLOCKS + 1)
+            .is_err());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn calculate_transaction_fee() {
+        // Every transaction is of the estimated size
+        let transaction_size = Tx::estimated_size().unwrap();
+        assert_eq!(transaction_size, TRANSACTION_1.len());
+        assert_eq!(transaction_size, TRANSACTION_2.len());
+
+        let fee_calculation = calculate_fee(1.5).unwrap();
+
+        assert_eq!(fee_calculation, FeeCalculation {
+            transaction_size,
+            fee_rate: 1.5,
+            fee: (transaction_size as f64 * 1.5).ceil() as u64,
+        });
+        assert_eq!(calculate_fee(0.0).unwrap().fee, 0);
+
+        assert!(calculate_fee(-1.0).is_err());
+        assert!(calculate_fee(f64::NAN).is_err());
+        assert!(calculate_fee(f64::INFINITY).is_err());
+    }
+}
@@ -37,17 +37,68 @@
 #[macro_use]
 extern crate tracing;
 
+pub mod block_requests;
+pub use block_requests::*;
+
 pub mod consensus;
 pub use consensus::*;
 
+pub mod consolidation;
+pub use consolidation::*;
+
 pub mod difficulty;
 pub use difficulty::*;
 
+pub mod dry_run;
+pub use dry_run::*;
+
+pub mod fee_estimator;
+pub use fee_estimator::*;
+
+pub mod memo;
+pub use memo::*;
+
+pub mod miner_tag;
+pub use miner_tag::*;
+
 pub mod miner;
-pub use miner::Miner;
+pub use miner::{CoinbasePayout, Miner, MiningBenchmark};
+
+pub mod mining_stats;
+pub use mining_stats::*;
 
 pub mod memory_pool;
-pub use memory_pool::MemoryPool;
+pub use memory_pool::{MemoryPool, MemoryPoolEvent};
+
+pub mod pool;
+pub use pool::*;
+
+pub mod prover;
+pub use prover::*;
+
+pub mod record_scanner;
+pub use record_scanner::*;
+
+pub mod record_selection;
+pub use record_selection::*;
+
+pub mod sent_transactions;
+pub use sent_transactions::*;
+
+pub mod thread_policy;
+pub use thread_policy::*;
+
+pub mod transaction_audit;
+pub use transaction_audit::*;
+
+pub mod transaction_builder;
+pub use transaction_builder::*;
+
+pub mod verification_pool;
+pub use verification_pool::*;
+
+pub mod wallet;
+pub use wallet::*;
 
 use snarkos_dpc::base_dpc::instantiated::{CommitmentMerkleParameters, Tx};
 use snarkos_storage::Ledger;
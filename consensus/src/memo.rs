@@ -0,0 +1,177 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! The memos of the transactions encrypted to the recipient of their first output record.
+//!
+//! The memo of a transaction is public, so a memo carrying payment metadata is encrypted with a key
+//! derived from the commitment randomness of the first output record. The randomness is only known to
+//! the sender and to the owner of the record, who decrypts it from the record with the account view key.
+//!
+//! An encrypted memo starts with `ENCRYPTED_MEMO_MAGIC`, followed by the encrypted length and message,
+//! and a check tag telling the owner of the record apart from the owners of the other records.
+//! The key is fresh for every record, so every encrypted memo is unique in the ledger.
+
+use snarkos_algorithms::prf::Blake2s;
+use snarkos_dpc::base_dpc::{instantiated::Components, record::DPCRecord};
+use snarkos_errors::{consensus::ConsensusError, dpc::DPCError};
+use snarkos_models::{algorithms::PRF, dpc::Record};
+use snarkos_utilities::{to_bytes, ToBytes};
+
+use rand::Rng;
+
+/// The bytes starting the encrypted memos.
+pub const ENCRYPTED_MEMO_MAGIC: [u8; 2] = *b"em";
+
+/// The maximum size of the message of an encrypted memo, in bytes.
+pub const MAX_ENCRYPTED_MEMO_SIZE: usize = 25;
+
+/// The size of the check tag ending the encrypted memos, in bytes.
+const CHECK_TAG_SIZE: usize = 4;
+
+/// The domain of the memo key derivation from the commitment randomness of a record.
+const MEMO_KEY_DOMAIN: &[u8; 32] = b"snarkOS memo encryption\0\0\0\0\0\0\0\0\0";
+
+/// The memo of a new transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionMemo {
+    /// A memo readable by anyone
+    Plaintext([u8; 32]),
+    /// A message of at most `MAX_ENCRYPTED_MEMO_SIZE` bytes, encrypted to the owner of the first output record
+    Encrypted(Vec<u8>),
+}
+
+impl TransactionMemo {
+    /// Returns a random plaintext memo.
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        TransactionMemo::Plaintext(rng.gen())
+    }
+}
+
+/// Returns the key stream of the memo encrypted to the owner of the given record.
+fn memo_key(record: &DPCRecord<Components>) -> Result<[u8; 32], ConsensusError> {
+    let randomness = to_bytes![record.commitment_randomness()]?;
+
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&randomness[..32]);
+
+    Ok(Blake2s::evaluate(&seed, MEMO_KEY_DOMAIN).map_err(DPCError::from)?)
+}
+
+/// Returns the memo carrying the given message, encrypted to the owner of the given record.
+pub fn encrypt_memo(record: &DPCRecord<Components>, message: &[u8]) -> Result<[u8; 32], ConsensusError> {
+    if message.len() > MAX_ENCRYPTED_MEMO_SIZE {
+        return Err(ConsensusError::InvalidMemo(format!(
+            "the message is {} bytes, expected at most {}",
+            message.len(),
+            MAX_ENCRYPTED_MEMO_SIZE
+        )));
+    }
+
+    let key = memo_key(record)?;
+    let message_start = ENCRYPTED_MEMO_MAGIC.len() + 1;
+    let check_tag_start = 32 - CHECK_TAG_SIZE;
+
+    let mut memo = [0u8; 32];
+    memo[..ENCRYPTED_MEMO_MAGIC.len()].copy_from_slice(&ENCRYPTED_MEMO_MAGIC);
+    memo[ENCRYPTED_MEMO_MAGIC.len()] = message.len() as u8;
+    memo[message_start..message_start + message.len()].copy_from_slice(message);
+
+    for (byte, key_byte) in memo[ENCRYPTED_MEMO_MAGIC.len()..check_tag_start]
+        .iter_mut()
+        .zip(&key[ENCRYPTED_MEMO_MAGIC.len()..check_tag_start])
+    {
+        *byte ^= key_byte;
+    }
+    memo[check_tag_start..].copy_from_slice(&key[check_tag_start..]);
+
+    Ok(memo)
+}
+
+/// Returns the message of the memo if it is encrypted to the owner of the given record.
+pub fn decrypt_memo(record: &DPCRecord<Components>, memo: &[u8; 32]) -> Option<Vec<u8>> {
+    if memo[..ENCRYPTED_MEMO_MAGIC.len()] != ENCRYPTED_MEMO_MAGIC {
+        return None;
+    }
+
+    let key = memo_key(record).ok()?;
+    let message_start = ENCRYPTED_MEMO_MAGIC.len() + 1;
+    let check_tag_start = 32 - CHECK_TAG_SIZE;
+
+    if memo[check_tag_start..] != key[check_tag_start..] {
+        return None;
+    }
+
+    let message_length = (memo[ENCRYPTED_MEMO_MAGIC.len()] ^ key[ENCRYPTED_MEMO_MAGIC.len()]) as usize;
+    if message_length > MAX_ENCRYPTED_MEMO_SIZE {
+        return None;
+    }
+
+    let message_end = message_start + message_length;
+    Some(
+        memo[message_start..message_end]
+            .iter()
+            .zip(&key[message_start..message_end])
+            .map(|(byte, key_byte)| byte ^ key_byte)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_dpc::base_dpc::{instantiated::InstantiatedDPC, record_payload::RecordPayload};
+    use snarkos_models::dpc::Program;
+    use snarkos_testing::consensus::FIXTURE;
+
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    fn record(index: usize) -> DPCRecord<Components> {
+        let mut rng = XorShiftRng::seed_from_u64(index as u64);
+
+        InstantiatedDPC::generate_record(
+            FIXTURE.parameters.system_parameters.clone(),
+            Default::default(),
+            FIXTURE.test_accounts[index].address.clone(),
+            false,
+            10,
+            RecordPayload::default(),
+            FIXTURE.program.into_compact_repr(),
+            FIXTURE.program.into_compact_repr(),
+            &mut rng,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn encrypt_memo_to_record_owner() {
+        let record = record(1);
+        let other_record = record(2);
+
+        let memo = encrypt_memo(&record, b"invoice 42").unwrap();
+        assert_eq!(memo[..ENCRYPTED_MEMO_MAGIC.len()], ENCRYPTED_MEMO_MAGIC);
+        assert_eq!(decrypt_memo(&record, &memo), Some(b"invoice 42".to_vec()));
+
+        // The memo is only decrypted with the record it is encrypted to
+        assert_eq!(decrypt_memo(&other_record, &memo), None);
+
+        // The plaintext memos are not decrypted
+        assert_eq!(decrypt_memo(&record, &[6u8; 32]), None);
+
+        assert!(encrypt_memo(&record, &[0u8; MAX_ENCRYPTED_MEMO_SIZE]).is_ok());
+        assert!(encrypt_memo(&record, &[0u8; MAX_ENCRYPTED_MEMO_SIZE + 1]).is_err());
+    }
+}
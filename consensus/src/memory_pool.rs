@@ -17,6 +17,11 @@
 //! Transactions memory pool
 //!
 //! `MemoryPool` keeps a vector of transactions seen by the miner.
+//!
+//! The serial numbers, commitments, and memos of every pooled transaction are indexed,
+//! so conflicting transactions can be detected when they are received. A conflict is
+//! resolved in favour of the transaction paying the higher fee rate, the order in which
+//! the transactions are packed in the block templates.
 
 use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::{
@@ -31,7 +36,13 @@ use snarkos_utilities::{
     to_bytes,
 };
 
-use std::collections::HashMap;
+use chrono::Utc;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    mem,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 /// Stores a transaction and it's size in the memory pool.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -40,6 +51,35 @@ pub struct Entry<T: Transaction> {
     pub transaction: T,
 }
 
+impl<T: Transaction> Entry<T> {
+    /// Compares the fee rates of two entries, the fees they pay per byte.
+    pub fn cmp_fee_rate(&self, other: &Self) -> Ordering {
+        // Compare fee rates by cross-multiplying to avoid rounding
+        let fee: i64 = self.transaction.value_balance().into();
+        let other_fee: i64 = other.transaction.value_balance().into();
+
+        (i128::from(fee) * other.size as i128).cmp(&(i128::from(other_fee) * self.size as i128))
+    }
+
+    /// Returns the weight of the entry, the number of ledger entries its transaction adds:
+    /// its serial numbers, its commitments and its memo.
+    ///
+    /// Each ledger entry is checked against the ledger when the transaction is verified, and indexed
+    /// when its block is committed, so the weight bounds the cost of a block beyond its byte size.
+    pub fn weight(&self) -> usize {
+        self.transaction.old_serial_numbers().len() + self.transaction.new_commitments().len() + 1
+    }
+}
+
+/// The changes to the chain and the memory pool, which may make the block templates stale.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryPoolEvent {
+    /// A block was committed to the canon chain at the given height
+    NewBlock(u32),
+    /// A transaction paying the given fee was added to the memory pool
+    NewTransaction(i64),
+}
+
 /// Stores transactions received by the server.
 /// Transaction entries will eventually be fetched by the miner and assembled into blocks.
 #[derive(Debug, Clone)]
@@ -48,10 +88,25 @@ pub struct MemoryPool<T: Transaction> {
 
     // Hashmap transaction_id -> Entry
     pub transactions: HashMap<Vec<u8>, Entry<T>>,
+
+    // Hashmap serial_number -> transaction_id
+    pub serial_numbers: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap commitment -> transaction_id
+    pub commitments: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap memo -> transaction_id
+    pub memos: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap transaction_id -> time the transaction entered the memory pool
+    pub insertion_times: HashMap<Vec<u8>, i64>,
+
+    // The channels notified of the memory pool events
+    subscribers: Vec<UnboundedSender<MemoryPoolEvent>>,
 }
 
-const BLOCK_HEADER_SIZE: usize = BlockHeader::size();
-const COINBASE_TRANSACTION_SIZE: usize = 1490; // TODO Find the value for actual coinbase transaction size
+pub(crate) const BLOCK_HEADER_SIZE: usize = BlockHeader::size();
+pub(crate) const COINBASE_TRANSACTION_SIZE: usize = 1490; // TODO Find the value for actual coinbase transaction size
 
 impl<T: Transaction> MemoryPool<T> {
     /// Initialize a new memory pool with no transactions
@@ -95,6 +150,10 @@ impl<T: Transaction> MemoryPool<T> {
     }
 
     /// Adds entry to memory pool if valid in the current ledger.
+    ///
+    /// If the entry conflicts with transactions already in the memory pool, it is only
+    /// accepted if it pays a strictly higher fee rate than each of them, in which case the
+    /// conflicting transactions are evicted.
     #[inline]
     pub fn insert<P: LoadableMerkleParameters>(
         &mut self,
@@ -112,51 +171,150 @@ impl<T: Transaction> MemoryPool<T> {
             return Ok(None);
         }
 
-        let mut holding_serial_numbers = vec![];
-        let mut holding_commitments = vec![];
-        let mut holding_memos = Vec::with_capacity(self.transactions.len());
-
-        for (_, tx) in self.transactions.iter() {
-            holding_serial_numbers.extend(tx.transaction.old_serial_numbers());
-            holding_commitments.extend(tx.transaction.new_commitments());
-            holding_memos.push(tx.transaction.memorandum());
-        }
+        // Check the transaction against a single ledger state
+        let storage = storage.view();
 
         for sn in transaction_serial_numbers {
-            if storage.contains_sn(sn) || holding_serial_numbers.contains(&sn) {
+            if storage.contains_sn(sn) {
                 return Ok(None);
             }
         }
 
         for cm in transaction_commitments {
-            if storage.contains_cm(cm) || holding_commitments.contains(&cm) {
+            if storage.contains_cm(cm) {
                 return Ok(None);
             }
         }
 
-        if storage.contains_memo(transaction_memo) || holding_memos.contains(&transaction_memo) {
+        if storage.contains_memo(transaction_memo) {
             return Ok(None);
         }
 
+        // Resolve conflicts with the memory pool by fee rate.
+        let conflicting_transaction_ids = self.get_conflicts(&entry.transaction)?;
+
+        for transaction_id in &conflicting_transaction_ids {
+            if let Some(conflicting_entry) = self.transactions.get(transaction_id) {
+                if entry.cmp_fee_rate(conflicting_entry) != Ordering::Greater {
+                    return Ok(None);
+                }
+            }
+        }
+
+        for transaction_id in &conflicting_transaction_ids {
+            if self.remove_by_hash(transaction_id)?.is_some() {
+                debug!(
+                    "Evicted transaction {} from the memory pool for a higher fee rate conflict",
+                    hex::encode(transaction_id)
+                );
+            }
+        }
+
         let transaction_id = entry.transaction.transaction_id()?.to_vec();
+        let fee: i64 = entry.transaction.value_balance().into();
+
+        for sn in transaction_serial_numbers {
+            self.serial_numbers.insert(to_bytes![sn]?, transaction_id.clone());
+        }
+
+        for cm in transaction_commitments {
+            self.commitments.insert(to_bytes![cm]?, transaction_id.clone());
+        }
+
+        self.memos.insert(to_bytes![transaction_memo]?, transaction_id.clone());
+        self.insertion_times
+            .insert(transaction_id.clone(), Utc::now().timestamp());
 
         self.total_size += entry.size;
         self.transactions.insert(transaction_id.clone(), entry);
 
+        self.notify(MemoryPoolEvent::NewTransaction(fee));
+
         Ok(Some(transaction_id))
     }
 
+    /// Returns a channel notified of the later memory pool events.
+    pub fn subscribe(&mut self) -> UnboundedReceiver<MemoryPoolEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Notifies the subscribers of an event. The subscribers whose channel is closed are dropped.
+    pub fn notify(&mut self, event: MemoryPoolEvent) {
+        self.subscribers.retain(|subscriber| subscriber.send(event).is_ok());
+    }
+
+    /// Returns the ids of the memory pool transactions that spend a serial number, or create
+    /// a commitment or memo, that the given transaction also does.
+    #[inline]
+    pub fn get_conflicts(&self, transaction: &T) -> Result<HashSet<Vec<u8>>, ConsensusError> {
+        let mut conflicting_transaction_ids = HashSet::new();
+
+        for sn in transaction.old_serial_numbers() {
+            if let Some(transaction_id) = self.serial_numbers.get(&to_bytes![sn]?) {
+                conflicting_transaction_ids.insert(transaction_id.clone());
+            }
+        }
+
+        for cm in transaction.new_commitments() {
+            if let Some(transaction_id) = self.commitments.get(&to_bytes![cm]?) {
+                conflicting_transaction_ids.insert(transaction_id.clone());
+            }
+        }
+
+        if let Some(transaction_id) = self.memos.get(&to_bytes![transaction.memorandum()]?) {
+            conflicting_transaction_ids.insert(transaction_id.clone());
+        }
+
+        // A transaction does not conflict with itself.
+        conflicting_transaction_ids.remove(&transaction.transaction_id()?.to_vec());
+
+        Ok(conflicting_transaction_ids)
+    }
+
+    /// Removes the transactions of a newly connected block from the memory pool, and evicts
+    /// the memory pool transactions that lost a conflict against them.
+    ///
+    /// Transactions may only spend records committed to the ledger, so a memory pool transaction
+    /// has no dependents in the memory pool, and evicting the losing transactions is sufficient.
+    #[inline]
+    pub fn remove_block_transactions(
+        &mut self,
+        transactions: &DPCTransactions<T>,
+    ) -> Result<Vec<Vec<u8>>, ConsensusError> {
+        let mut evicted_transaction_ids = vec![];
+
+        for transaction in transactions.iter() {
+            self.remove_by_hash(&transaction.transaction_id()?)?;
+
+            for transaction_id in self.get_conflicts(transaction)? {
+                if self.remove_by_hash(&transaction_id)?.is_some() {
+                    evicted_transaction_ids.push(transaction_id);
+                }
+            }
+        }
+
+        Ok(evicted_transaction_ids)
+    }
+
     /// Cleanse the memory pool of outdated transactions.
     #[inline]
     pub fn cleanse<P: LoadableMerkleParameters>(&mut self, storage: &Ledger<T, P>) -> Result<(), ConsensusError> {
         let mut new_memory_pool = Self::new();
 
         for (_, entry) in self.clone().transactions.iter() {
-            new_memory_pool.insert(&storage, entry.clone())?;
+            if let Some(transaction_id) = new_memory_pool.insert(&storage, entry.clone())? {
+                // Re-inserted transactions keep the time they entered the memory pool
+                if let Some(insertion_time) = self.insertion_times.get(&transaction_id) {
+                    new_memory_pool.insertion_times.insert(transaction_id, *insertion_time);
+                }
+            }
         }
 
-        self.total_size = new_memory_pool.total_size;
-        self.transactions = new_memory_pool.transactions;
+        // The subscribers are kept, and are not notified of the re-inserted transactions
+        new_memory_pool.subscribers = mem::take(&mut self.subscribers);
+        *self = new_memory_pool;
 
         Ok(())
     }
@@ -164,28 +322,33 @@ impl<T: Transaction> MemoryPool<T> {
     /// Removes transaction from memory pool or error.
     #[inline]
     pub fn remove(&mut self, entry: &Entry<T>) -> Result<Option<Vec<u8>>, ConsensusError> {
-        if self.contains(entry) {
-            self.total_size -= entry.size;
-
-            let transaction_id = entry.transaction.transaction_id()?.to_vec();
-
-            self.transactions.remove(&transaction_id);
+        let transaction_id = entry.transaction.transaction_id()?.to_vec();
 
-            return Ok(Some(transaction_id));
+        match self.remove_by_hash(&transaction_id)? {
+            Some(_) => Ok(Some(transaction_id)),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
 
     /// Removes transaction from memory pool based on the transaction id.
     #[inline]
     pub fn remove_by_hash(&mut self, transaction_id: &[u8]) -> Result<Option<Entry<T>>, ConsensusError> {
-        match self.transactions.clone().get(transaction_id) {
+        match self.transactions.remove(transaction_id) {
             Some(entry) => {
                 self.total_size -= entry.size;
-                self.transactions.remove(transaction_id);
 
-                Ok(Some(entry.clone()))
+                for sn in entry.transaction.old_serial_numbers() {
+                    self.serial_numbers.remove(&to_bytes![sn]?);
+                }
+
+                for cm in entry.transaction.new_commitments() {
+                    self.commitments.remove(&to_bytes![cm]?);
+                }
+
+                self.memos.remove(&to_bytes![entry.transaction.memorandum()]?);
+                self.insertion_times.remove(transaction_id);
+
+                Ok(Some(entry))
             }
             None => Ok(None),
         }
@@ -201,30 +364,41 @@ impl<T: Transaction> MemoryPool<T> {
     }
 
     /// Get candidate transactions for a new block.
+    ///
+    /// Transactions are packed in order of decreasing fee rate while they fit in the block size budget
+    /// and the block weight budget, with ties broken by transaction id, so identical memory pools always yield
+    /// identical candidates. Transactions in the memory pool can only spend records committed
+    /// on the ledger, so no transaction has to wait for an ancestor to be included first.
     #[inline]
     pub fn get_candidates<P: LoadableMerkleParameters>(
         &self,
         storage: &Ledger<T, P>,
         max_size: usize,
+        max_weight: usize,
     ) -> Result<DPCTransactions<T>, ConsensusError> {
-        let max_size = max_size - (BLOCK_HEADER_SIZE + COINBASE_TRANSACTION_SIZE);
+        let max_size = max_size.saturating_sub(BLOCK_HEADER_SIZE + COINBASE_TRANSACTION_SIZE);
+
+        let mut entries: Vec<(&Vec<u8>, &Entry<T>)> = self.transactions.iter().collect();
+        entries.sort_by(|(id_a, entry_a), (id_b, entry_b)| entry_b.cmp_fee_rate(entry_a).then_with(|| id_a.cmp(id_b)));
 
+        // Stage the candidates, so each one is checked against the ledger and the candidates before it
+        let mut overlay = storage.overlay();
         let mut block_size = 0;
-        let mut transactions = DPCTransactions::new();
+        let mut block_weight = 0;
 
-        // TODO Change naive transaction selection
-        for (_transaction_id, entry) in self.transactions.clone() {
-            if block_size + entry.size <= max_size {
-                if storage.transcation_conflicts(&entry.transaction) || transactions.conflicts(&entry.transaction) {
-                    continue;
-                }
+        for (_transaction_id, entry) in entries {
+            let weight = entry.weight();
 
+            if block_size + entry.size <= max_size
+                && block_weight + weight <= max_weight
+                && overlay.stage(entry.transaction.clone())?
+            {
                 block_size += entry.size;
-                transactions.push(entry.transaction.clone());
+                block_weight += weight;
             }
         }
 
-        Ok(transactions)
+        Ok(overlay.commit())
     }
 }
 
@@ -233,6 +407,11 @@ impl<T: Transaction> Default for MemoryPool<T> {
         Self {
             total_size: 0,
             transactions: HashMap::<Vec<u8>, Entry<T>>::new(),
+            serial_numbers: HashMap::new(),
+            commitments: HashMap::new(),
+            memos: HashMap::new(),
+            insertion_times: HashMap::new(),
+            subscribers: vec![],
         }
     }
 }
@@ -241,7 +420,7 @@ impl<T: Transaction> Default for MemoryPool<T> {
 mod tests {
     use super::*;
     use snarkos_dpc::base_dpc::instantiated::Tx;
-    use snarkos_objects::Block;
+    use snarkos_objects::{AleoAmount, Block};
     use snarkos_testing::{consensus::*, storage::*};
 
     use std::sync::Arc;
@@ -327,6 +506,92 @@ mod tests {
         kill_storage_sync(blockchain);
     }
 
+    #[test]
+    fn conflicting_transactions() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut mem_pool = MemoryPool::new();
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        mem_pool
+            .insert(&blockchain, Entry {
+                size,
+                transaction: transaction.clone(),
+            })
+            .unwrap();
+
+        // A transaction spending the same serial numbers for the same fee is rejected
+
+        let mut conflicting_transaction = transaction.clone();
+        conflicting_transaction.memorandum = [1u8; 32];
+
+        let inserted = mem_pool
+            .insert(&blockchain, Entry {
+                size,
+                transaction: conflicting_transaction.clone(),
+            })
+            .unwrap();
+
+        assert!(inserted.is_none());
+        assert!(mem_pool.contains(&Entry {
+            size,
+            transaction: transaction.clone(),
+        }));
+
+        // A larger transaction spending the same serial numbers for a higher fee, but a lower fee rate, is rejected
+
+        conflicting_transaction.value_balance = conflicting_transaction.value_balance.add(AleoAmount::ONE_BYTE);
+
+        let inserted = mem_pool
+            .insert(&blockchain, Entry {
+                size: 2 * size,
+                transaction: conflicting_transaction.clone(),
+            })
+            .unwrap();
+
+        assert!(inserted.is_none());
+        assert!(mem_pool.contains(&Entry {
+            size,
+            transaction: transaction.clone(),
+        }));
+
+        // A transaction spending the same serial numbers for a higher fee rate replaces it
+
+        let inserted = mem_pool
+            .insert(&blockchain, Entry {
+                size,
+                transaction: conflicting_transaction.clone(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            Some(conflicting_transaction.transaction_id().unwrap().to_vec()),
+            inserted
+        );
+        assert_eq!(1, mem_pool.transactions.len());
+        assert_eq!(size, mem_pool.total_size);
+        assert!(!mem_pool.contains(&Entry { size, transaction }));
+
+        // Connecting a block with the original transaction evicts the conflicting transaction
+
+        let block_transactions = DPCTransactions(vec![Tx::read(&TRANSACTION_2[..]).unwrap()]);
+        let evicted = mem_pool.remove_block_transactions(&block_transactions).unwrap();
+
+        assert_eq!(
+            vec![conflicting_transaction.transaction_id().unwrap().to_vec()],
+            evicted
+        );
+        assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.total_size);
+        assert!(mem_pool.serial_numbers.is_empty());
+        assert!(mem_pool.commitments.is_empty());
+        assert!(mem_pool.memos.is_empty());
+        assert!(mem_pool.insertion_times.is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
     #[test]
     fn get_candidates() {
         let blockchain = Arc::new(FIXTURE_VK.ledger());
@@ -341,13 +606,77 @@ mod tests {
 
         let max_block_size = size + BLOCK_HEADER_SIZE + COINBASE_TRANSACTION_SIZE;
 
-        let candidates = mem_pool.get_candidates(&blockchain, max_block_size).unwrap();
+        let candidates = mem_pool
+            .get_candidates(&blockchain, max_block_size, usize::MAX)
+            .unwrap();
 
         assert!(candidates.contains(&expected_transaction));
 
         kill_storage_sync(blockchain);
     }
 
+    #[test]
+    fn get_candidates_by_fee_rate() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut mem_pool = MemoryPool::new();
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        // Bypass conflict resolution to pool two transactions spending the same records
+
+        let mut higher_fee_transaction = transaction.clone();
+        higher_fee_transaction.memorandum = [1u8; 32];
+        higher_fee_transaction.value_balance = transaction.value_balance.add(AleoAmount::ONE_BYTE);
+
+        for transaction in vec![transaction, higher_fee_transaction.clone()] {
+            mem_pool.total_size += size;
+            mem_pool
+                .transactions
+                .insert(transaction.transaction_id().unwrap().to_vec(), Entry {
+                    size,
+                    transaction,
+                });
+        }
+
+        let max_block_size = 2 * size + BLOCK_HEADER_SIZE + COINBASE_TRANSACTION_SIZE;
+
+        let candidates = mem_pool
+            .get_candidates(&blockchain, max_block_size, usize::MAX)
+            .unwrap();
+
+        assert_eq!(vec![higher_fee_transaction], candidates.0);
+
+        // A budget smaller than the block overhead yields no candidates
+
+        let candidates = mem_pool
+            .get_candidates(&blockchain, BLOCK_HEADER_SIZE, usize::MAX)
+            .unwrap();
+
+        assert!(candidates.0.is_empty());
+
+        // A weight budget smaller than the weight of a transaction yields no candidates
+
+        let weight = Entry {
+            size,
+            transaction: higher_fee_transaction.clone(),
+        }
+        .weight();
+        assert_eq!(weight, 5);
+
+        let candidates = mem_pool
+            .get_candidates(&blockchain, max_block_size, weight - 1)
+            .unwrap();
+
+        assert!(candidates.0.is_empty());
+
+        let candidates = mem_pool.get_candidates(&blockchain, max_block_size, weight).unwrap();
+
+        assert_eq!(vec![higher_fee_transaction], candidates.0);
+
+        kill_storage_sync(blockchain);
+    }
+
     #[test]
     fn store_memory_pool() {
         let blockchain = Arc::new(FIXTURE_VK.ledger());
@@ -402,4 +731,36 @@ mod tests {
 
         kill_storage_sync(blockchain);
     }
+
+    #[test]
+    fn notify_subscribers() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut mem_pool = MemoryPool::new();
+        let mut events = mem_pool.subscribe();
+        let closed_events = mem_pool.subscribe();
+        drop(closed_events);
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let fee: i64 = transaction.value_balance().into();
+        mem_pool
+            .insert(&blockchain, Entry {
+                size: TRANSACTION_2.len(),
+                transaction,
+            })
+            .unwrap();
+
+        assert_eq!(events.try_recv().unwrap(), MemoryPoolEvent::NewTransaction(fee));
+        assert_eq!(1, mem_pool.subscribers.len());
+
+        // The subscribers are kept through a cleanse, without notifying the re-inserted transactions
+        mem_pool.cleanse(&blockchain).unwrap();
+        assert_eq!(1, mem_pool.transactions.len());
+        assert!(events.try_recv().is_err());
+
+        mem_pool.notify(MemoryPoolEvent::NewBlock(1));
+        assert_eq!(events.try_recv().unwrap(), MemoryPoolEvent::NewBlock(1));
+
+        kill_storage_sync(blockchain);
+    }
 }
@@ -14,7 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
+use crate::{
+    validate_miner_tag,
+    CheckedProver,
+    ConsensusParameters,
+    CpuProver,
+    MemoryPool,
+    MemoryPoolEvent,
+    MerkleTreeLedger,
+    MiningStats,
+    MiningThreadPolicy,
+    PoswProver,
+};
 use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
 use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::{
@@ -22,38 +33,364 @@ use snarkos_models::{
     dpc::{DPCScheme, Record},
     objects::Transaction,
 };
-use snarkos_objects::{dpc::DPCTransactions, AccountAddress, Block, BlockHeader};
+use snarkos_objects::{dpc::DPCTransactions, AccountAddress, Block, BlockHeader, BlockHeaderHash};
 use snarkos_posw::{txids_to_roots, PoswMarlin};
 use snarkos_storage::Ledger;
 use snarkos_utilities::{bytes::ToBytes, to_bytes};
 
 use chrono::Utc;
 use rand::{thread_rng, Rng};
-use std::sync::Arc;
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering},
+        mpsc,
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 use tokio::sync::Mutex;
 
+/// The percentage of the fees of a block template that the fees of the transactions added to the
+/// memory pool since the template was built must reach for the template to be rebuilt.
+pub const FEE_RESTART_PERCENT: i64 = 10;
+
+/// Splits the nonces below the maximum nonce into disjoint ranges, one for each mining thread.
+/// There are at most as many ranges as nonces.
+pub fn nonce_ranges(max_nonce: u32, threads: usize) -> Vec<Range<u32>> {
+    let threads = (threads.max(1) as u64).min(u64::from(max_nonce).max(1));
+    let range_size = u64::from(max_nonce) / threads;
+
+    (0..threads)
+        .map(|index| {
+            let start = index * range_size;
+            // The last range holds the remaining nonces
+            let end = if index == threads - 1 {
+                u64::from(max_nonce)
+            } else {
+                start + range_size
+            };
+
+            start as u32..end as u32
+        })
+        .collect()
+}
+
+/// The recipients of the coinbase rewards of the mined blocks.
+#[derive(Clone, Debug)]
+pub enum CoinbasePayout {
+    /// The rewards of each block are sent to one of the addresses, in turn by block height
+    Rotation(Vec<AccountAddress<Components>>),
+    /// The rewards of each block are split between the addresses, by percentage
+    Split(Vec<(AccountAddress<Components>, u8)>),
+}
+
+impl CoinbasePayout {
+    /// Checks that the payout has an address, and that a split has one output record for each address
+    /// and percentages summing to 100.
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        match self {
+            CoinbasePayout::Rotation(addresses) if addresses.is_empty() => Err(ConsensusError::InvalidCoinbasePayout(
+                "the rotation has no addresses".into(),
+            )),
+            CoinbasePayout::Rotation(_) => Ok(()),
+            CoinbasePayout::Split(shares) if shares.is_empty() || shares.len() > NUM_OUTPUT_RECORDS => {
+                Err(ConsensusError::InvalidCoinbasePayout(format!(
+                    "the split has {} addresses, expected 1 to {}",
+                    shares.len(),
+                    NUM_OUTPUT_RECORDS
+                )))
+            }
+            CoinbasePayout::Split(shares) => {
+                let total_percent: u32 = shares.iter().map(|(_, percent)| u32::from(*percent)).sum();
+                if total_percent == 100 {
+                    Ok(())
+                } else {
+                    Err(ConsensusError::InvalidCoinbasePayout(format!(
+                        "the split percentages sum to {} instead of 100",
+                        total_percent
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Returns the recipients of the rewards of the block of the given height, with their percentage.
+    pub fn recipients(&self, block_height: u32) -> Vec<(AccountAddress<Components>, u8)> {
+        match self {
+            CoinbasePayout::Rotation(addresses) => {
+                let address = &addresses[block_height as usize % addresses.len()];
+                vec![(address.clone(), 100)]
+            }
+            CoinbasePayout::Split(shares) => shares.clone(),
+        }
+    }
+}
+
+/// The proof of work attempts of a mining thread.
+#[derive(Debug, Default)]
+pub struct MiningThreadStats {
+    attempts: AtomicU64,
+    mining_micros: AtomicU64,
+}
+
+impl MiningThreadStats {
+    /// Returns the number of proofs of work attempted by the thread.
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Returns the time the thread spent mining.
+    pub fn mining_time(&self) -> Duration {
+        Duration::from_micros(self.mining_micros.load(Ordering::Relaxed))
+    }
+
+    /// Returns the number of proofs of work attempted by the thread per second of mining.
+    pub fn hashrate(&self) -> f64 {
+        let mining_secs = self.mining_time().as_secs_f64();
+        if mining_secs > 0.0 {
+            self.attempts() as f64 / mining_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The timings of a mining benchmark, which generates proofs of work against a fixed template.
+#[derive(Clone, Debug, Default)]
+pub struct MiningBenchmark {
+    /// The number of proofs of work generated
+    pub proofs: u64,
+    /// The number of threads generating the proofs of work
+    pub threads: usize,
+    /// The time spent computing the roots of the template
+    pub template_time: Duration,
+    /// The time spent generating the proofs of work, on all the threads
+    pub elapsed: Duration,
+    /// The time spent generating each proof of work, summed over the proofs
+    pub proving_time: Duration,
+    /// The time spent verifying each proof of work, summed over the proofs
+    pub verification_time: Duration,
+}
+
+impl MiningBenchmark {
+    /// Returns the number of proofs of work generated per second, on all the threads.
+    pub fn proofs_per_second(&self) -> f64 {
+        let elapsed = self.elapsed.as_secs_f64();
+        if elapsed > 0.0 {
+            self.proofs as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the average time spent generating a proof of work on a thread.
+    pub fn average_proving_time(&self) -> Duration {
+        match self.proofs {
+            0 => Duration::default(),
+            proofs => self.proving_time / proofs as u32,
+        }
+    }
+
+    /// Returns the average time spent verifying a proof of work.
+    pub fn average_verification_time(&self) -> Duration {
+        match self.proofs {
+            0 => Duration::default(),
+            proofs => self.verification_time / proofs as u32,
+        }
+    }
+}
+
 /// Compiles transactions into blocks to be submitted to the network.
-/// Uses a proof of work based algorithm to find valid blocks.
+/// Uses a proof of work based algorithm to find valid blocks, run on several threads.
 #[derive(Clone)]
 pub struct Miner {
     /// Receiving address that block rewards will be sent to.
     address: AccountAddress<Components>,
 
+    /// The recipients of the block rewards, in place of the receiving address.
+    payout: Option<CoinbasePayout>,
+
+    /// The tag embedded in the memo of the coinbase transactions, if any.
+    tag: Option<String>,
+
     /// Parameters for current blockchain consensus.
     pub consensus: ConsensusParameters,
 
-    /// The miner instance (must be initialized with a Proving Key)
-    miner: PoswMarlin,
+    /// The prover of the proofs of work, shared by the mining threads
+    prover: Arc<dyn PoswProver>,
+
+    /// The number of threads running the proof of work.
+    threads: usize,
+
+    /// The placement and the priority of the mining threads.
+    thread_policy: MiningThreadPolicy,
+
+    /// The proof of work attempts of each mining thread.
+    thread_stats: Arc<Vec<MiningThreadStats>>,
+
+    /// The statistics of the miner, shared with the services reporting them.
+    stats: Arc<MiningStats>,
+
+    /// The number of cancelled searches, which stops the current search when incremented.
+    cancellations: Arc<AtomicU64>,
+
+    /// The height of the block of the latest template.
+    template_height: Arc<AtomicU32>,
+
+    /// The fees of the transactions of the latest template.
+    template_fees: Arc<AtomicI64>,
+
+    /// The fees of the transactions added to the memory pool since the latest template was built.
+    pending_fees: Arc<AtomicI64>,
 }
 
 impl Miner {
-    /// Returns a new instance of a miner with consensus params.
+    /// Returns a new instance of a miner with consensus params, mining on a single thread.
     pub fn new(address: AccountAddress<Components>, consensus: ConsensusParameters) -> Self {
+        Self::with_threads(address, consensus, 1)
+    }
+
+    /// Returns a new instance of a miner with consensus params, mining on the given number of threads.
+    pub fn with_threads(address: AccountAddress<Components>, consensus: ConsensusParameters, threads: usize) -> Self {
+        // load the miner with the proving key, this should never fail
+        let miner = PoswMarlin::load().expect("could not instantiate the miner");
+        Self::from_posw(address, consensus, miner, threads)
+    }
+
+    /// Returns a new instance of a miner that establishes blocks for external workers.
+    /// The proving key is not loaded, so the miner cannot run the proof of work itself.
+    pub fn verify_only(
+        address: AccountAddress<Components>,
+        consensus: ConsensusParameters,
+    ) -> Result<Self, ConsensusError> {
+        Ok(Self::from_posw(address, consensus, PoswMarlin::verify_only()?, 1))
+    }
+
+    fn from_posw(
+        address: AccountAddress<Components>,
+        consensus: ConsensusParameters,
+        miner: PoswMarlin,
+        threads: usize,
+    ) -> Self {
+        let threads = threads.max(1);
+
         Self {
             address,
+            payout: None,
+            tag: None,
             consensus,
-            // load the miner with the proving key, this should never fail
-            miner: PoswMarlin::load().expect("could not instantiate the miner"),
+            prover: Arc::new(CpuProver::new(miner)),
+            threads,
+            thread_policy: MiningThreadPolicy::default(),
+            thread_stats: Arc::new((0..threads).map(|_| MiningThreadStats::default()).collect()),
+            stats: Arc::new(MiningStats::default()),
+            cancellations: Arc::new(AtomicU64::new(0)),
+            template_height: Arc::new(AtomicU32::new(0)),
+            template_fees: Arc::new(AtomicI64::new(0)),
+            pending_fees: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Sends the block rewards to the recipients of the given payout, in place of the receiving address.
+    pub fn with_payout(mut self, payout: CoinbasePayout) -> Result<Self, ConsensusError> {
+        payout.validate()?;
+        self.payout = Some(payout);
+        Ok(self)
+    }
+
+    /// Returns the recipients of the rewards of the block of the given height, with their percentage.
+    pub fn coinbase_recipients(&self, block_height: u32) -> Vec<(AccountAddress<Components>, u8)> {
+        match &self.payout {
+            Some(payout) => payout.recipients(block_height),
+            None => vec![(self.address.clone(), 100)],
+        }
+    }
+
+    /// Embeds the given tag, such as a pool name or a node id, in the memo of the coinbase transactions.
+    pub fn with_tag(mut self, tag: String) -> Result<Self, ConsensusError> {
+        validate_miner_tag(&tag)?;
+        self.tag = Some(tag);
+        Ok(self)
+    }
+
+    /// Returns the tag embedded in the memo of the coinbase transactions, if any.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Runs the proof of work with the given prover in place of the CPU prover.
+    /// The solutions of the prover are verified before they are put in a block.
+    pub fn with_prover<P: PoswProver + 'static>(mut self, prover: P) -> Self {
+        self.prover = Arc::new(CheckedProver::new(prover, self.consensus.verifier.clone()));
+        self
+    }
+
+    /// Returns the name of the prover of the proofs of work.
+    pub fn prover_name(&self) -> &str {
+        self.prover.name()
+    }
+
+    /// Places the mining threads on the cores and at the priority of the given policy.
+    pub fn with_thread_policy(mut self, thread_policy: MiningThreadPolicy) -> Result<Self, ConsensusError> {
+        thread_policy.validate()?;
+        self.thread_policy = thread_policy;
+        Ok(self)
+    }
+
+    /// Records the statistics of the miner in the given shared statistics.
+    pub fn with_stats(mut self, stats: Arc<MiningStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Returns the statistics of the miner.
+    pub fn stats(&self) -> &Arc<MiningStats> {
+        &self.stats
+    }
+
+    /// Returns the number of threads running the proof of work.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns the proof of work attempts of each mining thread.
+    pub fn thread_stats(&self) -> &[MiningThreadStats] {
+        &self.thread_stats
+    }
+
+    /// Returns the number of proofs of work attempted per second of mining, summed over the threads.
+    pub fn hashrate(&self) -> f64 {
+        self.thread_stats.iter().map(MiningThreadStats::hashrate).sum()
+    }
+
+    /// Cancels the current search for a block, which fails with `ConsensusError::MiningCancelled`
+    /// once each thread finished its current attempt. The later searches are not affected.
+    pub fn cancel(&self) {
+        self.cancellations.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Handles an event of the memory pool, and cancels the current search for a block once its
+    /// template is stale: when a block is committed at the height of the template, or when the fees
+    /// of the transactions added to the memory pool since the template was built reach
+    /// `FEE_RESTART_PERCENT` percent of the fees of the template.
+    pub fn handle_memory_pool_event(&self, event: MemoryPoolEvent) {
+        match event {
+            MemoryPoolEvent::NewBlock(block_height) => {
+                if block_height >= self.template_height.load(Ordering::SeqCst) {
+                    self.cancel();
+                }
+            }
+            MemoryPoolEvent::NewTransaction(fee) if fee > 0 => {
+                let pending_fees = self.pending_fees.fetch_add(fee, Ordering::SeqCst).saturating_add(fee);
+                let template_fees = self.template_fees.load(Ordering::SeqCst);
+
+                if pending_fees.saturating_mul(100) >= template_fees.saturating_mul(FEE_RESTART_PERCENT) {
+                    self.cancel();
+                }
+            }
+            MemoryPoolEvent::NewTransaction(_) => {}
         }
     }
 
@@ -62,9 +399,10 @@ impl Miner {
         storage: &Arc<Ledger<T, P>>,
         memory_pool: &Arc<Mutex<MemoryPool<T>>>,
         max_size: usize,
+        max_weight: usize,
     ) -> Result<DPCTransactions<T>, ConsensusError> {
         let memory_pool = memory_pool.lock().await;
-        Ok(memory_pool.get_candidates(&storage, max_size)?)
+        Ok(memory_pool.get_candidates(&storage, max_size, max_weight)?)
     }
 
     /// Add a coinbase transaction to a list of candidate block transactions
@@ -92,14 +430,16 @@ impl Miner {
             }
         }
 
-        let (records, tx) = self.consensus.create_coinbase_transaction(
-            storage.get_latest_block_height() + 1,
+        let block_height = storage.get_latest_block_height() + 1;
+        let (records, tx) = self.consensus.create_split_coinbase_transaction(
+            block_height,
             transactions,
             parameters,
             program_vk_hash,
             new_birth_programs,
             new_death_programs,
-            self.address.clone(),
+            &self.coinbase_recipients(block_height),
+            self.tag(),
             &storage,
             rng,
         )?;
@@ -116,6 +456,13 @@ impl Miner {
         storage: &MerkleTreeLedger,
         transactions: &DPCTransactions<Tx>,
     ) -> Result<(BlockHeader, DPCTransactions<Tx>, Vec<DPCRecord<Components>>), ConsensusError> {
+        let fees = transactions
+            .iter()
+            .map(|transaction| -> i64 { transaction.value_balance().into() })
+            .sum();
+        self.template_fees.store(fees, Ordering::SeqCst);
+        self.pending_fees.store(0, Ordering::SeqCst);
+
         let rng = &mut thread_rng();
         let mut transactions = transactions.clone();
         let coinbase_records = self.add_coinbase_transaction(parameters, &storage, &mut transactions, rng)?;
@@ -128,6 +475,8 @@ impl Miner {
         )?);
 
         let previous_block_header = storage.get_latest_block()?.header;
+        self.template_height
+            .store(storage.get_latest_block_height() + 1, Ordering::SeqCst);
 
         Ok((previous_block_header, transactions, coinbase_records))
     }
@@ -139,22 +488,59 @@ impl Miner {
         transactions: &DPCTransactions<T>,
         parent_header: &BlockHeader,
     ) -> Result<BlockHeader, ConsensusError> {
-        let txids = transactions.to_transaction_ids()?;
-        let (merkle_root_hash, pedersen_merkle_root_hash, subroots) = txids_to_roots(&txids);
+        let cancellation = self.cancellations.load(Ordering::SeqCst);
+        self.find_block_until_cancelled(transactions, parent_header, cancellation)
+    }
 
+    /// Run proof of work to find block, unless the search is cancelled after the given number of cancellations.
+    fn find_block_until_cancelled<T: Transaction>(
+        &self,
+        transactions: &DPCTransactions<T>,
+        parent_header: &BlockHeader,
+        cancellation: u64,
+    ) -> Result<BlockHeader, ConsensusError> {
         let time = Utc::now().timestamp();
         let difficulty_target = self.consensus.get_block_difficulty(parent_header, time);
 
-        // TODO: Switch this to use a user-provided RNG
-        let (nonce, proof) = self.miner.mine(
-            &subroots,
+        self.solve_block_header(
+            transactions,
+            parent_header.get_hash(),
+            time,
             difficulty_target,
-            &mut thread_rng(),
-            self.consensus.max_nonce,
-        )?;
+            cancellation,
+        )
+    }
+
+    /// Run proof of work to find a block of a template built by another node, from the hash of its parent block,
+    /// its time and its difficulty target. Returns BlockHeader with nonce solution.
+    pub fn find_block_for_template<T: Transaction>(
+        &self,
+        transactions: &DPCTransactions<T>,
+        previous_block_hash: BlockHeaderHash,
+        time: i64,
+        difficulty_target: u64,
+    ) -> Result<BlockHeader, ConsensusError> {
+        let cancellation = self.cancellations.load(Ordering::SeqCst);
+        self.solve_block_header(transactions, previous_block_hash, time, difficulty_target, cancellation)
+    }
+
+    /// Runs the proof of work on the roots of the given transactions, unless the search is cancelled
+    /// after the given number of cancellations, and returns the block header with the solution.
+    fn solve_block_header<T: Transaction>(
+        &self,
+        transactions: &DPCTransactions<T>,
+        previous_block_hash: BlockHeaderHash,
+        time: i64,
+        difficulty_target: u64,
+        cancellation: u64,
+    ) -> Result<BlockHeader, ConsensusError> {
+        let txids = transactions.to_transaction_ids()?;
+        let (merkle_root_hash, pedersen_merkle_root_hash, subroots) = txids_to_roots(&txids);
+
+        let (nonce, proof) = self.run_proof_of_work(&subroots, difficulty_target, cancellation)?;
 
         Ok(BlockHeader {
-            previous_block_hash: parent_header.get_hash(),
+            previous_block_hash,
             merkle_root_hash,
             pedersen_merkle_root_hash,
             time,
@@ -164,6 +550,159 @@ impl Miner {
         })
     }
 
+    /// Runs the proof of work on the mining threads, each trying the nonces of its own range,
+    /// and returns the first solution found. The other threads stop once a solution is found,
+    /// and every thread stops once the search is cancelled after the given number of cancellations.
+    fn run_proof_of_work(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        cancellation: u64,
+    ) -> Result<(u32, Vec<u8>), ConsensusError> {
+        let is_stopped = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+
+        let handles: Vec<JoinHandle<()>> = nonce_ranges(self.consensus.max_nonce, self.threads)
+            .into_iter()
+            .enumerate()
+            .map(|(index, nonces)| {
+                let prover = self.prover.clone();
+                let subroots = subroots.to_vec();
+                let thread_policy = self.thread_policy.clone();
+                let thread_stats = self.thread_stats.clone();
+                let mining_stats = self.stats.clone();
+                let cancellations = self.cancellations.clone();
+                let is_stopped = is_stopped.clone();
+                let sender = sender.clone();
+
+                thread::spawn(move || {
+                    thread_policy.apply(index);
+
+                    let stats = &thread_stats[index];
+                    let start = Instant::now();
+
+                    let result = prover.mine_nonces(&subroots, difficulty_target, nonces, &mut || {
+                        let keep_mining =
+                            !is_stopped.load(Ordering::SeqCst) && cancellations.load(Ordering::SeqCst) == cancellation;
+                        if keep_mining {
+                            stats.attempts.fetch_add(1, Ordering::Relaxed);
+                            mining_stats.record_attempt();
+                        }
+                        keep_mining
+                    });
+
+                    stats
+                        .mining_micros
+                        .fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+
+                    // The first solution or failure stops the other threads
+                    if let Some(result) = result.transpose() {
+                        is_stopped.store(true, Ordering::SeqCst);
+                        let _ = sender.send(result);
+                    }
+                })
+            })
+            .collect();
+
+        drop(sender);
+
+        let result = receiver.recv();
+        is_stopped.store(true, Ordering::SeqCst);
+
+        // Every thread is joined, so none keeps mining once the search ended
+        let is_panicked = handles.into_iter().map(JoinHandle::join).filter(Result::is_err).count() > 0;
+
+        match result {
+            Ok(solution) => Ok(solution?),
+            Err(_) if is_panicked => Err(ConsensusError::Message("a mining thread panicked".into())),
+            // The threads only end without a result once the search is cancelled
+            Err(_) => Err(ConsensusError::MiningCancelled),
+        }
+    }
+
+    /// Generates the given number of proofs of work for the given transactions on the mining threads,
+    /// regardless of their difficulty, and verifies each of them. No block is built or added to the chain.
+    pub fn benchmark<T: Transaction>(
+        &self,
+        transactions: &DPCTransactions<T>,
+        parent_header: &BlockHeader,
+        proofs: u64,
+    ) -> Result<MiningBenchmark, ConsensusError> {
+        let template_start = Instant::now();
+        let txids = transactions.to_transaction_ids()?;
+        let (merkle_root_hash, pedersen_merkle_root_hash, subroots) = txids_to_roots(&txids);
+        let template_time = template_start.elapsed();
+
+        let num_started = Arc::new(AtomicU64::new(0));
+        let proving_start = Instant::now();
+
+        let handles: Vec<JoinHandle<Result<Vec<(u32, Vec<u8>, Duration)>, ConsensusError>>> =
+            nonce_ranges(self.consensus.max_nonce, self.threads)
+                .into_iter()
+                .enumerate()
+                .map(|(index, nonces)| {
+                    let prover = self.prover.clone();
+                    let subroots = subroots.clone();
+                    let thread_policy = self.thread_policy.clone();
+                    let num_started = num_started.clone();
+
+                    thread::spawn(move || {
+                        thread_policy.apply(index);
+
+                        let mut solutions = vec![];
+
+                        // Every proof of work is under the maximum target, so each search is a single attempt
+                        while num_started.fetch_add(1, Ordering::SeqCst) < proofs {
+                            let start = Instant::now();
+                            let solution = prover.mine_nonces(&subroots, u64::MAX, nonces.clone(), &mut || true)?;
+
+                            if let Some((nonce, proof)) = solution {
+                                solutions.push((nonce, proof, start.elapsed()));
+                            }
+                        }
+
+                        Ok(solutions)
+                    })
+                })
+                .collect();
+
+        let mut solutions = vec![];
+        for handle in handles {
+            let thread_solutions = handle
+                .join()
+                .map_err(|_| ConsensusError::Message("a mining thread panicked".into()))??;
+            solutions.extend(thread_solutions);
+        }
+
+        let mut benchmark = MiningBenchmark {
+            proofs: solutions.len() as u64,
+            threads: self.threads,
+            template_time,
+            elapsed: proving_start.elapsed(),
+            ..Default::default()
+        };
+
+        for (nonce, proof, proving_time) in solutions {
+            let header = BlockHeader {
+                previous_block_hash: parent_header.get_hash(),
+                merkle_root_hash: merkle_root_hash.clone(),
+                pedersen_merkle_root_hash: pedersen_merkle_root_hash.clone(),
+                time: Utc::now().timestamp(),
+                difficulty_target: u64::MAX,
+                nonce,
+                proof: proof.into(),
+            };
+
+            let verification_start = Instant::now();
+            self.consensus.verify_proof_of_work(&header)?;
+
+            benchmark.verification_time += verification_start.elapsed();
+            benchmark.proving_time += proving_time;
+        }
+
+        Ok(benchmark)
+    }
+
     /// Returns a mined block.
     /// Calls methods to fetch transactions, run proof of work, and add the block into the chain for storage.
     pub async fn mine_block(
@@ -172,24 +711,37 @@ impl Miner {
         storage: &Arc<MerkleTreeLedger>,
         memory_pool: &Arc<Mutex<MemoryPool<Tx>>>,
     ) -> Result<(Vec<u8>, Vec<DPCRecord<Components>>), ConsensusError> {
-        let candidate_transactions =
-            Self::fetch_memory_pool_transactions(&storage.clone(), memory_pool, self.consensus.max_block_size).await?;
-
-        println!("Miner creating block");
+        // The events received while the template is built also cancel the search
+        let cancellation = self.cancellations.load(Ordering::SeqCst);
+        let template_start = Instant::now();
+
+        let candidate_transactions = Self::fetch_memory_pool_transactions(
+            &storage.clone(),
+            memory_pool,
+            self.consensus.template_size(),
+            self.consensus.template_weight(),
+        )
+        .await?;
+
+        debug!(
+            candidate_transactions = candidate_transactions.len(),
+            "Miner creating block"
+        );
 
         let (previous_block_header, transactions, coinbase_records) =
             self.establish_block(parameters, storage, &candidate_transactions)?;
+        self.stats.record_template(template_start.elapsed());
 
-        println!("Miner generated coinbase transaction");
+        debug!("Miner generated coinbase transaction");
 
         for (index, record) in coinbase_records.iter().enumerate() {
             let record_commitment = hex::encode(&to_bytes![record.commitment()]?);
-            println!("Coinbase record {:?} commitment: {:?}", index, record_commitment);
+            debug!(index, commitment = %record_commitment, "Coinbase record");
         }
 
-        let header = self.find_block(&transactions, &previous_block_header)?;
+        let header = self.find_block_until_cancelled(&transactions, &previous_block_header, cancellation)?;
 
-        println!("Miner found block");
+        debug!(hash = %header.get_hash(), "Miner found block");
 
         let block = Block { header, transactions };
 
@@ -198,6 +750,13 @@ impl Miner {
         self.consensus
             .receive_block(parameters, storage, &mut memory_pool, &block)?;
 
+        // The block is stale if another block was committed at its height during the search
+        if storage.is_canon(&block.header.get_hash()) {
+            self.stats.record_accepted_block();
+        } else {
+            self.stats.record_stale_block();
+        }
+
         // Store the non-dummy coinbase records.
         let mut records_to_store = vec![];
         for record in &coinbase_records {
@@ -0,0 +1,113 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! The miner tags embedded in the memos of the coinbase transactions, naming the pool or the node
+//! that produced a block.
+//!
+//! A tagged memo starts with `MINER_TAG_MAGIC`, followed by the length of the tag, the UTF-8 tag
+//! itself, and random bytes. The memos of the ledger must be unique, so the size of a tag is
+//! limited to `MAX_MINER_TAG_SIZE` bytes to keep at least 13 random bytes in every coinbase memo.
+
+use snarkos_errors::consensus::ConsensusError;
+
+use rand::Rng;
+
+/// The bytes starting the coinbase memos that carry a miner tag.
+pub const MINER_TAG_MAGIC: [u8; 2] = *b"mt";
+
+/// The maximum size of a miner tag, in bytes.
+pub const MAX_MINER_TAG_SIZE: usize = 16;
+
+/// Checks that a miner tag is non-empty text of at most `MAX_MINER_TAG_SIZE` bytes, without control characters.
+pub fn validate_miner_tag(tag: &str) -> Result<(), ConsensusError> {
+    if tag.is_empty() || tag.len() > MAX_MINER_TAG_SIZE {
+        return Err(ConsensusError::InvalidMinerTag(format!(
+            "the tag is {} bytes, expected 1 to {}",
+            tag.len(),
+            MAX_MINER_TAG_SIZE
+        )));
+    }
+
+    if tag.chars().any(char::is_control) {
+        return Err(ConsensusError::InvalidMinerTag(
+            "the tag contains control characters".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns a coinbase memo carrying the given miner tag, completed by random bytes.
+pub fn miner_tag_memo<R: Rng>(tag: &str, rng: &mut R) -> Result<[u8; 32], ConsensusError> {
+    validate_miner_tag(tag)?;
+
+    let mut memo: [u8; 32] = rng.gen();
+    let tag_start = MINER_TAG_MAGIC.len() + 1;
+
+    memo[..MINER_TAG_MAGIC.len()].copy_from_slice(&MINER_TAG_MAGIC);
+    memo[MINER_TAG_MAGIC.len()] = tag.len() as u8;
+    memo[tag_start..tag_start + tag.len()].copy_from_slice(tag.as_bytes());
+
+    Ok(memo)
+}
+
+/// Returns the miner tag carried by a coinbase memo, if any.
+pub fn miner_tag(memo: &[u8; 32]) -> Option<String> {
+    if memo[..MINER_TAG_MAGIC.len()] != MINER_TAG_MAGIC {
+        return None;
+    }
+
+    let tag_length = memo[MINER_TAG_MAGIC.len()] as usize;
+    let tag_start = MINER_TAG_MAGIC.len() + 1;
+    if tag_length == 0 || tag_length > MAX_MINER_TAG_SIZE {
+        return None;
+    }
+
+    let tag = std::str::from_utf8(&memo[tag_start..tag_start + tag_length]).ok()?;
+    match validate_miner_tag(tag) {
+        Ok(()) => Some(tag.to_string()),
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn tag_memo() {
+        let rng = &mut XorShiftRng::seed_from_u64(1231275789u64);
+
+        let memo = miner_tag_memo("aleo-pool", rng).unwrap();
+        assert_eq!(miner_tag(&memo), Some("aleo-pool".to_string()));
+
+        // The memos of the same tag differ by their random bytes
+        assert_ne!(miner_tag_memo("aleo-pool", rng).unwrap(), memo);
+
+        assert_eq!(miner_tag(&[0u8; 32]), None);
+    }
+
+    #[test]
+    fn invalid_tags() {
+        assert!(validate_miner_tag("").is_err());
+        assert!(validate_miner_tag("a tag longer than 16 bytes").is_err());
+        assert!(validate_miner_tag("pool\n").is_err());
+        assert!(validate_miner_tag("node-1").is_ok());
+    }
+}
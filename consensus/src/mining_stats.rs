@@ -0,0 +1,216 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_profiler::METRICS;
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// The period over which the rolling hashrate of the miner is computed.
+pub const HASHRATE_WINDOW: Duration = Duration::from_secs(600);
+
+/// The proof of work attempts within the hashrate window.
+#[derive(Debug, Default)]
+struct RecentAttempts {
+    /// The time of the first attempt ever recorded
+    since: Option<Instant>,
+    /// The times of the attempts within the window, oldest first
+    times: VecDeque<Instant>,
+}
+
+impl RecentAttempts {
+    /// Removes the attempts older than the hashrate window.
+    fn prune(&mut self, now: Instant) {
+        while let Some(time) = self.times.front() {
+            if now.duration_since(*time) <= HASHRATE_WINDOW {
+                break;
+            }
+            self.times.pop_front();
+        }
+    }
+}
+
+/// The statistics of a miner, shared with the services reporting them.
+#[derive(Debug, Default)]
+pub struct MiningStats {
+    attempts: AtomicU64,
+    accepted_blocks: AtomicU64,
+    stale_blocks: AtomicU64,
+    stale_shares: AtomicU64,
+    templates: AtomicU64,
+    template_micros: AtomicU64,
+    recent_attempts: Mutex<RecentAttempts>,
+}
+
+impl MiningStats {
+    /// Returns the number of proofs of work attempted.
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of mined blocks committed to the canon chain.
+    pub fn accepted_blocks(&self) -> u64 {
+        self.accepted_blocks.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of mined blocks whose parent was no longer the tip of the canon chain.
+    pub fn stale_blocks(&self) -> u64 {
+        self.stale_blocks.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of pool shares submitted for a template whose parent or ledger digest was superseded.
+    pub fn stale_shares(&self) -> u64 {
+        self.stale_shares.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of block templates built.
+    pub fn templates(&self) -> u64 {
+        self.templates.load(Ordering::Relaxed)
+    }
+
+    /// Returns the average time spent building a block template.
+    pub fn average_template_latency(&self) -> Duration {
+        match self.templates() {
+            0 => Duration::default(),
+            templates => Duration::from_micros(self.template_micros.load(Ordering::Relaxed) / templates),
+        }
+    }
+
+    /// Returns the number of proofs of work attempted per second over the hashrate window,
+    /// or since the first attempt if the miner started within the window.
+    pub fn hashrate(&self) -> f64 {
+        let now = Instant::now();
+        let mut recent_attempts = self.recent_attempts.lock().expect("the mining stats lock is poisoned");
+        recent_attempts.prune(now);
+
+        let elapsed = match recent_attempts.since {
+            Some(since) => now.duration_since(since).min(HASHRATE_WINDOW).as_secs_f64(),
+            None => return 0.0,
+        };
+
+        if elapsed > 0.0 {
+            recent_attempts.times.len() as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Records the start of a proof of work attempt.
+    pub fn record_attempt(&self) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+
+        let now = Instant::now();
+        {
+            let mut recent_attempts = self.recent_attempts.lock().expect("the mining stats lock is poisoned");
+            recent_attempts.since.get_or_insert(now);
+            recent_attempts.times.push_back(now);
+            recent_attempts.prune(now);
+        }
+
+        METRICS.increment_counter(
+            "snarkos_miner_attempts",
+            "The number of proofs of work attempted by the miner",
+            1,
+        );
+        METRICS.set_gauge(
+            "snarkos_miner_hashrate",
+            "The number of proofs of work attempted per second by the miner",
+            self.hashrate(),
+        );
+    }
+
+    /// Records a block template built in the given time.
+    pub fn record_template(&self, latency: Duration) {
+        self.templates.fetch_add(1, Ordering::Relaxed);
+        self.template_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+
+        METRICS.record_timing(
+            "snarkos_miner_template_seconds",
+            "The time spent building block templates",
+            latency,
+        );
+    }
+
+    /// Records a mined block committed to the canon chain.
+    pub fn record_accepted_block(&self) {
+        self.accepted_blocks.fetch_add(1, Ordering::Relaxed);
+
+        METRICS.increment_counter(
+            "snarkos_miner_accepted_blocks",
+            "The number of mined blocks committed to the canon chain",
+            1,
+        );
+    }
+
+    /// Records a mined block whose parent was no longer the tip of the canon chain.
+    pub fn record_stale_block(&self) {
+        self.stale_blocks.fetch_add(1, Ordering::Relaxed);
+
+        METRICS.increment_counter(
+            "snarkos_miner_stale_blocks",
+            "The number of mined blocks whose parent was no longer the tip of the canon chain",
+            1,
+        );
+    }
+
+    /// Records a pool share submitted for a template whose parent or ledger digest was superseded.
+    pub fn record_stale_share(&self) {
+        self.stale_shares.fetch_add(1, Ordering::Relaxed);
+
+        METRICS.increment_counter(
+            "snarkos_miner_stale_shares",
+            "The number of pool shares submitted for superseded templates",
+            1,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mining_stats() {
+        let stats = MiningStats::default();
+        assert_eq!(stats.hashrate(), 0.0);
+        assert_eq!(stats.average_template_latency(), Duration::default());
+
+        stats.record_attempt();
+        stats.record_attempt();
+        stats.record_template(Duration::from_millis(10));
+        stats.record_template(Duration::from_millis(30));
+        stats.record_accepted_block();
+        stats.record_stale_block();
+        stats.record_stale_share();
+
+        assert_eq!(stats.attempts(), 2);
+        assert_eq!(stats.templates(), 2);
+        assert_eq!(stats.average_template_latency(), Duration::from_millis(20));
+        assert_eq!(stats.accepted_blocks(), 1);
+        assert_eq!(stats.stale_blocks(), 1);
+        assert_eq!(stats.stale_shares(), 1);
+
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(stats.hashrate() > 0.0);
+    }
+}
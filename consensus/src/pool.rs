@@ -0,0 +1,222 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ConsensusParameters;
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_objects::BlockHeader;
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// Returns the weight of a share under the given target, which is the expected number of proofs
+/// of work needed to find a share under the maximum target.
+pub fn share_weight(share_target: u64) -> u64 {
+    u64::MAX / share_target.max(1)
+}
+
+/// A share found valid by the share validator.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ValidShare {
+    /// The weight of the share, given by the share target
+    pub weight: u64,
+    /// Flag indicating if the share is under the difficulty target of its block
+    pub solves_block: bool,
+}
+
+/// Validates the proofs of work of the workers of a pool against a share target, above the
+/// difficulty target of the blocks, so that the workers prove their work between the blocks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ShareValidator {
+    share_target: u64,
+}
+
+impl ShareValidator {
+    /// Creates a share validator accepting the proofs of work whose hash is under the given target.
+    pub fn new(share_target: u64) -> Self {
+        Self { share_target }
+    }
+
+    /// Returns the target of the shares for a block of the given difficulty target.
+    /// The proofs of work solving the block are always shares.
+    pub fn share_target(&self, difficulty_target: u64) -> u64 {
+        self.share_target.max(difficulty_target)
+    }
+
+    /// Validates the proof of work of a share for the given block header.
+    /// The hash of the proof and the nonce are checked before the proof itself is verified,
+    /// so the shares above the share target are rejected without verifying their proof.
+    pub fn validate(
+        &self,
+        consensus: &ConsensusParameters,
+        header: &BlockHeader,
+    ) -> Result<ValidShare, ConsensusError> {
+        let share_target = self.share_target(header.difficulty_target);
+        let hash = header.to_difficulty_hash();
+
+        if hash > share_target {
+            return Err(ConsensusError::LowDifficultyShare(share_target, hash));
+        }
+        if header.nonce >= consensus.max_nonce {
+            return Err(ConsensusError::NonceInvalid(header.nonce, consensus.max_nonce));
+        }
+
+        consensus.verify_proof_of_work(header)?;
+
+        Ok(ValidShare {
+            weight: share_weight(share_target),
+            solves_block: hash <= header.difficulty_target,
+        })
+    }
+}
+
+/// A share recorded for the payouts of a pool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolShare {
+    /// The worker that submitted the share
+    pub worker: String,
+    /// The weight of the share
+    pub weight: u64,
+}
+
+/// The latest shares of the workers of a pool, whose total weight covers the given window,
+/// from which the block rewards are split as in the PPLNS (pay per last N shares) payouts.
+#[derive(Clone, Debug)]
+pub struct PoolShares {
+    window: u64,
+    shares: VecDeque<PoolShare>,
+    total_weight: u64,
+}
+
+impl PoolShares {
+    /// Creates an empty record of the shares, keeping the latest shares of the given total weight.
+    pub fn new(window: u64) -> Self {
+        Self {
+            window,
+            shares: VecDeque::new(),
+            total_weight: 0,
+        }
+    }
+
+    /// Records a share, and drops the oldest shares once the latest shares cover the window.
+    pub fn record(&mut self, share: PoolShare) {
+        self.total_weight = self.total_weight.saturating_add(share.weight);
+        self.shares.push_back(share);
+
+        while let Some(oldest) = self.shares.front() {
+            if self.total_weight - oldest.weight < self.window {
+                break;
+            }
+
+            self.total_weight -= oldest.weight;
+            self.shares.pop_front();
+        }
+    }
+
+    /// Returns the number of recorded shares.
+    pub fn len(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Returns `true` if no share is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
+    }
+
+    /// Returns the total weight of the recorded shares.
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+
+    /// Returns the total weight of the recorded shares of each worker.
+    pub fn contributions(&self) -> BTreeMap<String, u64> {
+        let mut contributions = BTreeMap::new();
+        for share in &self.shares {
+            *contributions.entry(share.worker.clone()).or_insert(0) += share.weight;
+        }
+
+        contributions
+    }
+
+    /// Splits the given reward between the workers in proportion to their contributions.
+    /// The amounts are rounded down, so the remainder is kept by the pool.
+    pub fn payouts(&self, reward: u64) -> BTreeMap<String, u64> {
+        if self.total_weight == 0 {
+            return BTreeMap::new();
+        }
+
+        self.contributions()
+            .into_iter()
+            .map(|(worker, weight)| {
+                let amount = u128::from(reward) * u128::from(weight) / u128::from(self.total_weight);
+                (worker, amount as u64)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(worker: &str, weight: u64) -> PoolShare {
+        PoolShare {
+            worker: worker.into(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn share_targets() {
+        let validator = ShareValidator::new(u64::MAX / 16);
+
+        assert_eq!(validator.share_target(1000), u64::MAX / 16);
+        assert_eq!(validator.share_target(u64::MAX), u64::MAX);
+        assert_eq!(share_weight(u64::MAX / 16), 16);
+        assert_eq!(share_weight(u64::MAX), 1);
+        assert_eq!(share_weight(0), u64::MAX);
+    }
+
+    #[test]
+    fn record_shares_in_window() {
+        let mut shares = PoolShares::new(10);
+
+        shares.record(share("alice", 4));
+        shares.record(share("bob", 4));
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares.total_weight(), 8);
+
+        // The oldest share is dropped once the latest shares cover the window
+        shares.record(share("bob", 6));
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares.total_weight(), 10);
+
+        let contributions = shares.contributions();
+        assert_eq!(contributions.get("alice"), None);
+        assert_eq!(contributions.get("bob"), Some(&10));
+    }
+
+    #[test]
+    fn split_payouts() {
+        let mut shares = PoolShares::new(100);
+        assert!(shares.payouts(1000).is_empty());
+
+        shares.record(share("alice", 1));
+        shares.record(share("bob", 2));
+
+        let payouts = shares.payouts(1000);
+        assert_eq!(payouts.get("alice"), Some(&333));
+        assert_eq!(payouts.get("bob"), Some(&666));
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! The provers of the proofs of succinct work of the miner.
+//!
+//! The miner runs the proof of work through a `PoswProver`, which is the CPU prover by default.
+//! Other provers, such as the GPU provers, are plugged in as external processes by the `ProcessProver`.
+//! The solutions of a prover other than the CPU prover are checked by a `CheckedProver` before
+//! they are put in a block, so a faulty prover cannot make the miner publish an invalid block.
+
+use snarkos_algorithms::crh::sha256d_to_u64;
+use snarkos_curves::bls12_377::Bls12_377;
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_models::algorithms::SNARK;
+use snarkos_objects::pedersen_merkle_tree::pedersen_merkle_root;
+use snarkos_posw::{Marlin, PoswMarlin};
+use snarkos_utilities::bytes::FromBytes;
+
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Write},
+    ops::Range,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Mutex,
+};
+
+/// A prover of the proofs of succinct work of the miner, shared by the mining threads.
+pub trait PoswProver: Send + Sync {
+    /// Returns the name of the prover, as reported in the logs.
+    fn name(&self) -> &str;
+
+    /// Searches the given nonces for a proof of work under the difficulty target, for the given subroots.
+    /// The `keep_mining` closure is called before each attempt, and the search stops without a solution
+    /// once it returns false.
+    fn mine_nonces(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        nonces: Range<u32>,
+        keep_mining: &mut dyn FnMut() -> bool,
+    ) -> Result<Option<(u32, Vec<u8>)>, ConsensusError>;
+}
+
+/// The prover running the proof of work on the CPU, with the proving key of the PoSW.
+pub struct CpuProver {
+    posw: PoswMarlin,
+}
+
+impl CpuProver {
+    /// Creates a CPU prover with the given PoSW runner, which must have a proving key to mine.
+    pub fn new(posw: PoswMarlin) -> Self {
+        Self { posw }
+    }
+}
+
+impl PoswProver for CpuProver {
+    fn name(&self) -> &str {
+        "cpu"
+    }
+
+    fn mine_nonces(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        nonces: Range<u32>,
+        keep_mining: &mut dyn FnMut() -> bool,
+    ) -> Result<Option<(u32, Vec<u8>)>, ConsensusError> {
+        // TODO: Switch this to use a user-provided RNG
+        Ok(self
+            .posw
+            .mine_nonces(subroots, difficulty_target, &mut thread_rng(), nonces, keep_mining)?)
+    }
+}
+
+/// A prover checking the solutions of another prover before returning them.
+/// Each solution must be under the difficulty target, and its proof must be verified by the PoSW verifier.
+pub struct CheckedProver<P: PoswProver> {
+    prover: P,
+    verifier: PoswMarlin,
+}
+
+impl<P: PoswProver> CheckedProver<P> {
+    /// Checks the solutions of the given prover with the given PoSW verifier.
+    pub fn new(prover: P, verifier: PoswMarlin) -> Self {
+        Self { prover, verifier }
+    }
+
+    /// Checks that the proof of a solution is under the difficulty target and proves the work on the subroots.
+    pub fn check(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        nonces: &Range<u32>,
+        nonce: u32,
+        proof: &[u8],
+    ) -> Result<(), ConsensusError> {
+        if nonce < nonces.start || nonce >= nonces.end {
+            return Err(ConsensusError::InvalidProverSolution(format!(
+                "the nonce {} is not in the range {:?}",
+                nonce, nonces
+            )));
+        }
+
+        let hash = sha256d_to_u64(proof);
+        if hash > difficulty_target {
+            return Err(ConsensusError::InvalidProverSolution(format!(
+                "the proof hash {} is above the difficulty target {}",
+                hash, difficulty_target
+            )));
+        }
+
+        let proof = <Marlin<Bls12_377> as SNARK>::Proof::read(proof)
+            .map_err(|error| ConsensusError::InvalidProverSolution(format!("the proof is malformed: {}", error)))?;
+        self.verifier
+            .verify(nonce, &proof, &pedersen_merkle_root(subroots))
+            .map_err(|error| ConsensusError::InvalidProverSolution(format!("the proof is not verified: {}", error)))
+    }
+}
+
+impl<P: PoswProver> PoswProver for CheckedProver<P> {
+    fn name(&self) -> &str {
+        self.prover.name()
+    }
+
+    fn mine_nonces(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        nonces: Range<u32>,
+        keep_mining: &mut dyn FnMut() -> bool,
+    ) -> Result<Option<(u32, Vec<u8>)>, ConsensusError> {
+        let solution = self
+            .prover
+            .mine_nonces(subroots, difficulty_target, nonces.clone(), keep_mining)?;
+
+        if let Some((nonce, proof)) = &solution {
+            if let Err(error) = self.check(subroots, difficulty_target, &nonces, *nonce, proof) {
+                error!("The {} prover returned an invalid solution: {}", self.name(), error);
+                return Err(error);
+            }
+        }
+
+        Ok(solution)
+    }
+}
+
+/// Defines an external prover process
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProcessProverConfig {
+    /// The name of the prover, as reported in the logs
+    #[serde(default = "default_process_prover_name")]
+    pub name: String,
+    /// The command running the prover
+    pub command: String,
+    /// The arguments of the command
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The number of attempts of each request to the prover
+    #[serde(default = "default_process_prover_attempts")]
+    pub attempts: u32,
+}
+
+fn default_process_prover_name() -> String {
+    "external".into()
+}
+
+fn default_process_prover_attempts() -> u32 {
+    1
+}
+
+/// A request to an external prover, searching the nonces of the range for a proof of work under the target.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessProverRequest {
+    subroots: Vec<String>,
+    difficulty_target: u64,
+    nonces: (u32, u32),
+    attempts: u32,
+}
+
+/// The answer of an external prover, with the solution found within the attempts of the request, if any.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessProverResponse {
+    nonce: Option<u32>,
+    proof: Option<String>,
+    error: Option<String>,
+}
+
+/// The input and output of an external prover process.
+struct ProcessProverPipes {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// A prover bridging the miner to an external process, such as a GPU prover.
+///
+/// The process reads newline-delimited JSON requests from its standard input, each searching the
+/// nonces of a range for a proof of work under a target with a number of attempts:
+/// `{"subroots": [hex], "difficulty_target": u64, "nonces": [start, end], "attempts": u32}`,
+/// and answers each of them on its standard output with `{"nonce": u32, "proof": hex}` once a
+/// solution is found, `{"nonce": null}` once the attempts are exhausted, or `{"error": message}`.
+/// The requests of the mining threads are sent to the process in turn, and the `keep_mining`
+/// closure is called before each request.
+pub struct ProcessProver {
+    name: String,
+    attempts: u32,
+    pipes: Mutex<ProcessProverPipes>,
+}
+
+impl ProcessProver {
+    /// Starts the prover process of the given configuration.
+    pub fn spawn(config: &ProcessProverConfig) -> Result<Self, ConsensusError> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().ok_or_else(|| missing_pipe("standard input"))?;
+        let stdout = child.stdout.take().ok_or_else(|| missing_pipe("standard output"))?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            attempts: config.attempts.max(1),
+            pipes: Mutex::new(ProcessProverPipes {
+                child,
+                stdin,
+                stdout: BufReader::new(stdout),
+            }),
+        })
+    }
+
+    /// Sends a request to the prover process, and returns its answer.
+    fn request(&self, request: &ProcessProverRequest) -> Result<ProcessProverResponse, ConsensusError> {
+        let mut pipes = self.pipes.lock().expect("the prover process lock is poisoned");
+
+        let mut line = serde_json::to_string(request).map_err(serde_json_error)?;
+        line.push('\n');
+        pipes.stdin.write_all(line.as_bytes())?;
+        pipes.stdin.flush()?;
+
+        let mut answer = String::new();
+        if pipes.stdout.read_line(&mut answer)? == 0 {
+            return Err(ConsensusError::InvalidProverSolution(format!(
+                "the {} prover process exited",
+                self.name
+            )));
+        }
+
+        serde_json::from_str(&answer).map_err(serde_json_error)
+    }
+}
+
+impl PoswProver for ProcessProver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn mine_nonces(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        nonces: Range<u32>,
+        keep_mining: &mut dyn FnMut() -> bool,
+    ) -> Result<Option<(u32, Vec<u8>)>, ConsensusError> {
+        let request = ProcessProverRequest {
+            subroots: subroots.iter().map(hex::encode).collect(),
+            difficulty_target,
+            nonces: (nonces.start, nonces.end),
+            attempts: self.attempts,
+        };
+
+        while keep_mining() {
+            let response = self.request(&request)?;
+
+            if let Some(error) = response.error {
+                return Err(ConsensusError::Message(format!(
+                    "the {} prover failed: {}",
+                    self.name, error
+                )));
+            }
+
+            match (response.nonce, response.proof) {
+                (Some(nonce), Some(proof)) => {
+                    let proof = hex::decode(proof).map_err(|error| {
+                        ConsensusError::InvalidProverSolution(format!("the proof is not hex encoded: {}", error))
+                    })?;
+                    return Ok(Some((nonce, proof)));
+                }
+                (Some(_), None) => {
+                    return Err(ConsensusError::InvalidProverSolution("the proof is missing".into()));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Drop for ProcessProver {
+    fn drop(&mut self) {
+        if let Ok(pipes) = self.pipes.get_mut() {
+            let _ = pipes.child.kill();
+            let _ = pipes.child.wait();
+        }
+    }
+}
+
+fn missing_pipe(pipe: &str) -> ConsensusError {
+    ConsensusError::Message(format!("the {} of the prover process is not piped", pipe))
+}
+
+fn serde_json_error(error: serde_json::Error) -> ConsensusError {
+    ConsensusError::Crate("serde_json", format!("{:?}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A prover returning the same solution to every search.
+    struct FixedProver {
+        nonce: u32,
+        proof: Vec<u8>,
+    }
+
+    impl PoswProver for FixedProver {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn mine_nonces(
+            &self,
+            _subroots: &[[u8; 32]],
+            _difficulty_target: u64,
+            _nonces: Range<u32>,
+            _keep_mining: &mut dyn FnMut() -> bool,
+        ) -> Result<Option<(u32, Vec<u8>)>, ConsensusError> {
+            Ok(Some((self.nonce, self.proof.clone())))
+        }
+    }
+
+    fn checked_prover(nonce: u32, proof: Vec<u8>) -> CheckedProver<FixedProver> {
+        CheckedProver::new(FixedProver { nonce, proof }, PoswMarlin::verify_only().unwrap())
+    }
+
+    #[test]
+    fn reject_nonce_out_of_range() {
+        let prover = checked_prover(100, vec![0u8; 32]);
+
+        match prover.mine_nonces(&[[0u8; 32]], u64::MAX, 0..10, &mut || true) {
+            Err(ConsensusError::InvalidProverSolution(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn reject_proof_above_target() {
+        let proof = vec![1u8; 32];
+        let prover = checked_prover(1, proof.clone());
+        let difficulty_target = sha256d_to_u64(&proof) - 1;
+
+        match prover.mine_nonces(&[[0u8; 32]], difficulty_target, 0..10, &mut || true) {
+            Err(ConsensusError::InvalidProverSolution(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn reject_malformed_proof() {
+        let prover = checked_prover(1, vec![0u8; 32]);
+
+        match prover.mine_nonces(&[[0u8; 32]], u64::MAX, 0..10, &mut || true) {
+            Err(ConsensusError::InvalidProverSolution(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{memo::decrypt_memo, ConsensusParameters, MerkleTreeLedger};
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
+use snarkos_errors::{consensus::ConsensusError, dpc::DPCError};
+use snarkos_models::{
+    algorithms::CRH,
+    dpc::{DPCComponents, Record},
+    objects::{LedgerScheme, Transaction},
+};
+use snarkos_objects::{AccountPrivateKey, AccountViewKey};
+
+use std::ops::Range;
+
+/// Whether a scanned record is spent as of the latest canon block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecordSpentness {
+    /// The serial number of the record is not in the ledger
+    Unspent,
+    /// The serial number of the record is in the ledger
+    Spent,
+    /// The serial number of the record is unknown, as it is derived from the private key of its owner
+    Unknown,
+}
+
+/// A record of a canon block decrypted by a record scanner.
+#[derive(Clone, Debug)]
+pub struct ScannedRecord {
+    /// The number of the block of the record
+    pub block_number: u32,
+    /// The id of the transaction of the record
+    pub transaction_id: [u8; 32],
+    /// The decrypted record
+    pub record: DPCRecord<Components>,
+    /// Whether the record is spent
+    pub spentness: RecordSpentness,
+    /// The message of the memo of the transaction, if it is encrypted to the owner of the record
+    pub memo: Option<Vec<u8>>,
+}
+
+impl ScannedRecord {
+    /// Returns the value of the record.
+    pub fn value(&self) -> u64 {
+        self.record.value()
+    }
+
+    /// Returns the serial number nonce of the record.
+    pub fn serial_number_nonce(&self) -> &<<Components as DPCComponents>::SerialNumberNonceCRH as CRH>::Output {
+        self.record.serial_number_nonce()
+    }
+}
+
+/// Scans the canon blocks for the output records an account view key can decrypt, so wallets find
+/// the records of an account without decrypting them on their own. Unlike the record index of the
+/// ledger, the view key is not registered with the node, and the blocks are scanned on each call.
+pub struct RecordScanner<'a> {
+    parameters: &'a PublicParameters<Components>,
+    view_key: AccountViewKey<Components>,
+    private_key: Option<AccountPrivateKey<Components>>,
+}
+
+impl<'a> RecordScanner<'a> {
+    /// Creates a record scanner for the given account view key.
+    /// The spentness of the records is unknown without the private key of the account.
+    pub fn new(parameters: &'a PublicParameters<Components>, view_key: AccountViewKey<Components>) -> Self {
+        Self {
+            parameters,
+            view_key,
+            private_key: None,
+        }
+    }
+
+    /// Derives the serial numbers of the scanned records from the given private key, which tells
+    /// whether they are spent. The private key must be the key of the account view key.
+    pub fn with_private_key(mut self, private_key: AccountPrivateKey<Components>) -> Result<Self, ConsensusError> {
+        let view_key = AccountViewKey::<Components>::from_private_key(
+            self.parameters.account_signature_parameters(),
+            self.parameters.account_commitment_parameters(),
+            &private_key,
+        )
+        .map_err(DPCError::from)?;
+
+        if view_key != self.view_key {
+            return Err(ConsensusError::PrivateKeyMismatch);
+        }
+
+        self.private_key = Some(private_key);
+        Ok(self)
+    }
+
+    /// Returns the non-dummy records of the canon blocks in the given range that can be decrypted by the
+    /// account view key, ordered by block. The range is bounded by the latest block, and the records of
+    /// the pruned blocks are not scanned.
+    pub fn scan(&self, ledger: &MerkleTreeLedger, blocks: Range<u32>) -> Result<Vec<ScannedRecord>, ConsensusError> {
+        let latest_block_height = ledger.get_latest_block_height();
+        let pruned_block_height = ledger.get_pruned_block_height()?;
+
+        let mut scanned_records = vec![];
+
+        for block_number in blocks.start..blocks.end.min(latest_block_height.saturating_add(1)) {
+            if block_number != 0 && block_number <= pruned_block_height {
+                continue;
+            }
+
+            let block = ledger.get_block_from_block_number(block_number)?;
+
+            for transaction in block.transactions.iter() {
+                let records =
+                    ConsensusParameters::decrypt_transaction_records(self.parameters, &self.view_key, transaction)?;
+                if records.is_empty() {
+                    continue;
+                }
+
+                let transaction_id = transaction.transaction_id()?;
+                let spentness = self.spentness(ledger, &records)?;
+
+                for (record, spentness) in records.into_iter().zip(spentness) {
+                    let memo = decrypt_memo(&record, transaction.memorandum());
+
+                    scanned_records.push(ScannedRecord {
+                        block_number,
+                        transaction_id,
+                        record,
+                        spentness,
+                        memo,
+                    });
+                }
+            }
+        }
+
+        Ok(scanned_records)
+    }
+
+    /// Returns whether the records are spent, if the private key of their owner is known.
+    fn spentness(
+        &self,
+        ledger: &MerkleTreeLedger,
+        records: &[DPCRecord<Components>],
+    ) -> Result<Vec<RecordSpentness>, ConsensusError> {
+        let private_key = match &self.private_key {
+            Some(private_key) => private_key,
+            None => return Ok(vec![RecordSpentness::Unknown; records.len()]),
+        };
+
+        let serial_numbers = InstantiatedDPC::generate_sns(&self.parameters.system_parameters, records, private_key)?;
+
+        Ok(serial_numbers
+            .iter()
+            .map(|(serial_number, _)| {
+                if ledger.contains_sn(serial_number) {
+                    RecordSpentness::Spent
+                } else {
+                    RecordSpentness::Unspent
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_dpc::base_dpc::{instantiated::Components, record::DPCRecord};
+use snarkos_models::dpc::Record;
+use snarkos_utilities::{to_bytes, ToBytes};
+
+use rand::{seq::SliceRandom, Rng};
+use std::cmp::Reverse;
+
+/// A strategy selecting the input records of a transaction among the records of an account.
+pub trait RecordSelection {
+    /// Returns the indices of the records selected to cover the target value with at most `max_records`
+    /// records, or `None` if the records cannot cover it.
+    fn select<R: Rng>(
+        &self,
+        records: &[DPCRecord<Components>],
+        target: u64,
+        max_records: usize,
+        rng: &mut R,
+    ) -> Option<Vec<usize>>;
+}
+
+/// Selects the largest records first, spending as few records as possible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargestFirst;
+
+impl RecordSelection for LargestFirst {
+    fn select<R: Rng>(
+        &self,
+        records: &[DPCRecord<Components>],
+        target: u64,
+        max_records: usize,
+        _rng: &mut R,
+    ) -> Option<Vec<usize>> {
+        select_largest_first(&values(records), target, max_records)
+    }
+}
+
+/// Searches for the records whose value matches the target exactly, or exceeds it by at most the
+/// cost of change, so the transaction needs no change record. Selects nothing if there is no match.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BranchAndBound {
+    /// The value the selected records may exceed the target by
+    pub cost_of_change: u64,
+}
+
+impl BranchAndBound {
+    /// The number of branches explored before the search settles for the best match found
+    pub const MAX_TRIES: usize = 100_000;
+}
+
+impl RecordSelection for BranchAndBound {
+    fn select<R: Rng>(
+        &self,
+        records: &[DPCRecord<Components>],
+        target: u64,
+        max_records: usize,
+        _rng: &mut R,
+    ) -> Option<Vec<usize>> {
+        select_branch_and_bound(&values(records), target, self.cost_of_change, max_records)
+    }
+}
+
+/// Selects the records in a random order, so the records spent together reveal less about the
+/// account. Falls back to the largest records if the random records do not cover the target.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomSelection;
+
+impl RecordSelection for RandomSelection {
+    fn select<R: Rng>(
+        &self,
+        records: &[DPCRecord<Components>],
+        target: u64,
+        max_records: usize,
+        rng: &mut R,
+    ) -> Option<Vec<usize>> {
+        let values = values(records);
+
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.shuffle(rng);
+
+        select_in_order(&values, &order, target, max_records)
+            .or_else(|| select_largest_first(&values, target, max_records))
+    }
+}
+
+/// Selects the records of the given commitments, in their order, whatever the target.
+/// Selects nothing if a record is missing or if the records do not cover the target.
+#[derive(Clone, Debug, Default)]
+pub struct ManualSelection {
+    /// The serialized commitments of the records to spend
+    pub commitments: Vec<Vec<u8>>,
+}
+
+impl RecordSelection for ManualSelection {
+    fn select<R: Rng>(
+        &self,
+        records: &[DPCRecord<Components>],
+        target: u64,
+        max_records: usize,
+        _rng: &mut R,
+    ) -> Option<Vec<usize>> {
+        if self.commitments.len() > max_records {
+            return None;
+        }
+
+        let mut commitments = Vec::with_capacity(records.len());
+        for record in records {
+            commitments.push(to_bytes![record.commitment()].ok()?);
+        }
+
+        let mut selection = Vec::with_capacity(self.commitments.len());
+        for commitment in &self.commitments {
+            selection.push(commitments.iter().position(|candidate| candidate == commitment)?);
+        }
+
+        let value: u128 = selection.iter().map(|index| u128::from(records[*index].value())).sum();
+        if value < u128::from(target) {
+            return None;
+        }
+
+        Some(selection)
+    }
+}
+
+fn values(records: &[DPCRecord<Components>]) -> Vec<u64> {
+    records.iter().map(|record| record.value()).collect()
+}
+
+/// Selects the values in the given order until they cover the target.
+fn select_in_order(values: &[u64], order: &[usize], target: u64, max_records: usize) -> Option<Vec<usize>> {
+    let mut selection = vec![];
+    let mut sum = 0u128;
+
+    for &index in order.iter().take(max_records) {
+        if sum >= u128::from(target) {
+            break;
+        }
+
+        selection.push(index);
+        sum += u128::from(values[index]);
+    }
+
+    if sum < u128::from(target) {
+        return None;
+    }
+
+    Some(selection)
+}
+
+fn select_largest_first(values: &[u64], target: u64, max_records: usize) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|index| Reverse(values[*index]));
+
+    select_in_order(values, &order, target, max_records)
+}
+
+fn select_branch_and_bound(values: &[u64], target: u64, cost_of_change: u64, max_records: usize) -> Option<Vec<usize>> {
+    // The largest values are explored first, and the sums of the values left bound the branches
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|index| Reverse(values[*index]));
+
+    let sorted_values: Vec<u128> = order.iter().map(|index| u128::from(values[*index])).collect();
+    let mut remaining = vec![0u128; sorted_values.len() + 1];
+    for index in (0..sorted_values.len()).rev() {
+        remaining[index] = remaining[index + 1] + sorted_values[index];
+    }
+
+    let mut search = BranchAndBoundSearch {
+        values: &sorted_values,
+        remaining: &remaining,
+        target: u128::from(target),
+        upper_bound: u128::from(target) + u128::from(cost_of_change),
+        max_records,
+        current: vec![],
+        best: None,
+        tries: 0,
+    };
+    search.explore(0, 0);
+
+    search
+        .best
+        .map(|(_, selection)| selection.into_iter().map(|index| order[index]).collect())
+}
+
+struct BranchAndBoundSearch<'a> {
+    values: &'a [u128],
+    remaining: &'a [u128],
+    target: u128,
+    upper_bound: u128,
+    max_records: usize,
+    current: Vec<usize>,
+    /// The selection wasting the least value over the target so far
+    best: Option<(u128, Vec<usize>)>,
+    tries: usize,
+}
+
+impl<'a> BranchAndBoundSearch<'a> {
+    fn explore(&mut self, index: usize, sum: u128) {
+        if self.tries >= BranchAndBound::MAX_TRIES || matches!(self.best, Some((0, _))) {
+            return;
+        }
+        self.tries += 1;
+
+        if sum >= self.target {
+            let waste = sum - self.target;
+            if self.best.as_ref().map_or(true, |(best_waste, _)| waste < *best_waste) {
+                self.best = Some((waste, self.current.clone()));
+            }
+            return;
+        }
+
+        if index == self.values.len()
+            || self.current.len() == self.max_records
+            || sum + self.remaining[index] < self.target
+        {
+            return;
+        }
+
+        // Include the value, then skip it
+        let with_value = sum + self.values[index];
+        if with_value <= self.upper_bound {
+            self.current.push(index);
+            self.explore(index + 1, with_value);
+            self.current.pop();
+        }
+
+        self.explore(index + 1, sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_first_selection() {
+        let values = [5, 20, 10, 1];
+
+        assert_eq!(select_largest_first(&values, 25, 2), Some(vec![1, 2]));
+        assert_eq!(select_largest_first(&values, 15, 2), Some(vec![1]));
+        assert_eq!(select_largest_first(&values, 31, 2), None);
+        assert_eq!(select_largest_first(&values, 31, 3), Some(vec![1, 2, 0]));
+    }
+
+    #[test]
+    fn branch_and_bound_selection() {
+        let values = [5, 20, 10, 1];
+
+        // The exact match is preferred over the largest records
+        assert_eq!(select_branch_and_bound(&values, 15, 0, 2), Some(vec![2, 0]));
+        assert_eq!(select_branch_and_bound(&values, 21, 0, 2), Some(vec![1, 3]));
+
+        // Without an exact match, the selection exceeds the target by at most the cost of change
+        assert_eq!(select_branch_and_bound(&values, 14, 0, 2), None);
+        assert_eq!(select_branch_and_bound(&values, 14, 1, 2), Some(vec![2, 0]));
+
+        assert_eq!(select_branch_and_bound(&values, 36, 0, 2), None);
+        assert_eq!(select_branch_and_bound(&values, 36, 0, 4), Some(vec![1, 2, 0, 3]));
+    }
+
+    #[test]
+    fn in_order_selection() {
+        let values = [5, 20, 10, 1];
+
+        assert_eq!(select_in_order(&values, &[3, 0, 2], 6, 2), Some(vec![3, 0]));
+        assert_eq!(select_in_order(&values, &[3, 0, 2], 7, 2), None);
+    }
+}
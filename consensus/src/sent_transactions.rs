@@ -0,0 +1,202 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::MerkleTreeLedger;
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_models::objects::Transaction;
+use snarkos_storage::SentTransaction;
+use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
+
+/// The number of seconds between two broadcasts of an unconfirmed sent transaction, ten minutes
+pub const SENT_TRANSACTION_REBROADCAST_INTERVAL: i64 = 600;
+
+/// The number of seconds a sent transaction is abandoned after if it is not confirmed, one day
+pub const SENT_TRANSACTION_EXPIRY: i64 = 86400;
+
+/// The changes to the sent transactions after checking them against the ledger.
+#[derive(Clone, Debug, Default)]
+pub struct SentTransactionsUpdate {
+    /// The ids of the transactions found in a canon block
+    pub confirmed: Vec<Vec<u8>>,
+    /// The ids of the transactions conflicting with a canon block, which can no longer be confirmed
+    pub conflicted: Vec<Vec<u8>>,
+    /// The ids of the transactions abandoned after staying unconfirmed past the expiry
+    pub expired: Vec<Vec<u8>>,
+    /// The transactions to broadcast to the peers again
+    pub rebroadcast: Vec<Tx>,
+}
+
+/// The transactions sent by the node that are not yet confirmed.
+///
+/// The sent transactions are persisted, so they survive restarts of the node, and they are broadcast again
+/// until they are confirmed, in case their first propagation failed or the peers dropped them from their memory
+/// pools. A transaction stops being tracked once it is in a canon block, once a canon block spends its records or
+/// reuses its commitments, or once it expires.
+pub struct SentTransactions<'a> {
+    storage: &'a MerkleTreeLedger,
+    rebroadcast_interval: i64,
+    expiry: i64,
+}
+
+impl<'a> SentTransactions<'a> {
+    /// Creates the sent transactions of the given ledger, with the default rebroadcast interval and expiry.
+    pub fn new(storage: &'a MerkleTreeLedger) -> Self {
+        Self {
+            storage,
+            rebroadcast_interval: SENT_TRANSACTION_REBROADCAST_INTERVAL,
+            expiry: SENT_TRANSACTION_EXPIRY,
+        }
+    }
+
+    /// Sets the number of seconds between two broadcasts of a sent transaction.
+    pub fn with_rebroadcast_interval(mut self, rebroadcast_interval: i64) -> Self {
+        self.rebroadcast_interval = rebroadcast_interval;
+        self
+    }
+
+    /// Sets the number of seconds a sent transaction is abandoned after.
+    pub fn with_expiry(mut self, expiry: i64) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Tracks a transaction sent by the node at the given time, which was just broadcast.
+    /// A transaction sent again keeps the time it was first sent.
+    pub fn track(&self, transaction: &Tx, now: i64) -> Result<(), ConsensusError> {
+        let transaction_id = transaction.transaction_id()?.to_vec();
+
+        let sent_at = match self.storage.get_sent_transaction(&transaction_id)? {
+            Some(sent_transaction) => sent_transaction.sent_at,
+            None => now,
+        };
+
+        let sent_transaction = SentTransaction {
+            transaction: to_bytes![transaction]?,
+            sent_at,
+            broadcast_at: now,
+        };
+
+        Ok(self
+            .storage
+            .store_sent_transactions(&[(transaction_id, sent_transaction)])?)
+    }
+
+    /// Returns the transactions sent by the node that are not yet confirmed, with the time they were sent.
+    pub fn pending(&self) -> Result<Vec<(Tx, i64)>, ConsensusError> {
+        let mut pending = vec![];
+
+        for (_, sent_transaction) in self.storage.get_sent_transactions()? {
+            pending.push((Tx::read(&sent_transaction.transaction[..])?, sent_transaction.sent_at));
+        }
+
+        Ok(pending)
+    }
+
+    /// Checks the sent transactions against the ledger at the given time. The confirmed, conflicting and
+    /// expired transactions are no longer tracked, and the transactions due for a broadcast are returned
+    /// to be broadcast again.
+    pub fn update(&self, now: i64) -> Result<SentTransactionsUpdate, ConsensusError> {
+        let mut update = SentTransactionsUpdate::default();
+        let mut rebroadcast = vec![];
+
+        for (transaction_id, mut sent_transaction) in self.storage.get_sent_transactions()? {
+            let transaction = Tx::read(&sent_transaction.transaction[..])?;
+
+            if self.storage.get_transaction_location(&transaction_id)?.is_some() {
+                update.confirmed.push(transaction_id);
+            } else if self.storage.transcation_conflicts(&transaction) {
+                update.conflicted.push(transaction_id);
+            } else if now - sent_transaction.sent_at >= self.expiry {
+                update.expired.push(transaction_id);
+            } else if now - sent_transaction.broadcast_at >= self.rebroadcast_interval {
+                sent_transaction.broadcast_at = now;
+                rebroadcast.push((transaction_id, sent_transaction));
+                update.rebroadcast.push(transaction);
+            }
+        }
+
+        let mut removed = update.confirmed.clone();
+        removed.extend(update.conflicted.iter().cloned());
+        removed.extend(update.expired.iter().cloned());
+
+        self.storage.remove_sent_transactions(&removed)?;
+        self.storage.store_sent_transactions(&rebroadcast)?;
+
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::{consensus::*, storage::kill_storage_sync};
+
+    use std::sync::Arc;
+
+    #[test]
+    fn sent_transactions_lifecycle() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let sent_transactions = SentTransactions::new(&blockchain)
+            .with_rebroadcast_interval(10)
+            .with_expiry(100);
+
+        let transaction_1 = Tx::read(&TRANSACTION_1[..]).unwrap();
+        let transaction_2 = Tx::read(&TRANSACTION_2[..]).unwrap();
+
+        // A transaction spending the records of the first transaction, under another id
+        let mut conflicting_transaction = transaction_1.clone();
+        conflicting_transaction.memorandum = [1u8; 32];
+
+        sent_transactions.track(&transaction_1, 0).unwrap();
+        sent_transactions.track(&transaction_2, 0).unwrap();
+        sent_transactions.track(&conflicting_transaction, 0).unwrap();
+        assert_eq!(sent_transactions.pending().unwrap().len(), 3);
+
+        // The transactions are broadcast again once the interval elapsed
+        let update = sent_transactions.update(5).unwrap();
+        assert!(update.rebroadcast.is_empty());
+
+        let update = sent_transactions.update(10).unwrap();
+        assert_eq!(update.rebroadcast.len(), 3);
+        assert!(sent_transactions.update(15).unwrap().rebroadcast.is_empty());
+
+        // A transaction sent again keeps the time it was first sent
+        sent_transactions.track(&transaction_2, 50).unwrap();
+        assert!(sent_transactions
+            .pending()
+            .unwrap()
+            .contains(&(transaction_2.clone(), 0)));
+
+        // The confirmed transaction and its conflicts are no longer tracked
+        blockchain.insert_and_commit(&DATA.block_1).unwrap();
+
+        let update = sent_transactions.update(20).unwrap();
+        assert_eq!(update.confirmed, vec![transaction_1.transaction_id().unwrap().to_vec()]);
+        assert_eq!(update.conflicted, vec![conflicting_transaction
+            .transaction_id()
+            .unwrap()
+            .to_vec()]);
+        assert_eq!(sent_transactions.pending().unwrap(), vec![(transaction_2.clone(), 0)]);
+
+        // The unconfirmed transaction is abandoned after the expiry
+        let update = sent_transactions.update(100).unwrap();
+        assert_eq!(update.expired, vec![transaction_2.transaction_id().unwrap().to_vec()]);
+        assert!(sent_transactions.pending().unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+}
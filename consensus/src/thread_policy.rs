@@ -0,0 +1,136 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::consensus::ConsensusError;
+
+use core_affinity::CoreId;
+use serde::{Deserialize, Serialize};
+
+/// The placement of the mining threads on the cores of the machine, and their scheduling priority,
+/// so that a mining node keeps serving its peers and its RPC clients while every thread is mining.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MiningThreadPolicy {
+    /// The cores the mining threads are pinned to, in turn by thread index, if any
+    pub cores: Vec<usize>,
+    /// Flag indicating if the mining threads run at the lowest priority of the operating system
+    pub low_priority: bool,
+}
+
+impl MiningThreadPolicy {
+    /// Checks that the cores of the policy are available on this machine.
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        if self.cores.is_empty() {
+            return Ok(());
+        }
+
+        let available = core_affinity::get_core_ids()
+            .ok_or_else(|| ConsensusError::InvalidThreadPolicy("the cores of this machine are unknown".into()))?;
+
+        match self
+            .cores
+            .iter()
+            .find(|core| !available.iter().any(|id| id.id == **core))
+        {
+            Some(core) => Err(ConsensusError::InvalidThreadPolicy(format!(
+                "the core {} is not one of the {} available cores",
+                core,
+                available.len()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the core the mining thread of the given index is pinned to, if any.
+    pub fn core(&self, thread_index: usize) -> Option<usize> {
+        match self.cores.len() {
+            0 => None,
+            len => Some(self.cores[thread_index % len]),
+        }
+    }
+
+    /// Applies the policy to the current thread, which is the mining thread of the given index.
+    /// A policy that cannot be applied is logged, and the thread keeps mining.
+    pub fn apply(&self, thread_index: usize) {
+        if let Some(core) = self.core(thread_index) {
+            core_affinity::set_for_current(CoreId { id: core });
+        }
+
+        if self.low_priority {
+            if let Err(error) = lower_current_thread_priority() {
+                warn!(
+                    "Failed to lower the priority of the mining thread {}: {}",
+                    thread_index, error
+                );
+            }
+        }
+    }
+}
+
+/// Moves the current thread to the idle scheduling policy, so it only runs when no other thread is ready.
+#[cfg(target_os = "linux")]
+fn lower_current_thread_priority() -> Result<(), String> {
+    use thread_priority::{
+        set_thread_priority,
+        thread_native_id,
+        NormalThreadSchedulePolicy,
+        ThreadPriority,
+        ThreadSchedulePolicy,
+    };
+
+    set_thread_priority(
+        thread_native_id(),
+        ThreadPriority::Min,
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle),
+    )
+    .map_err(|error| format!("{:?}", error))
+}
+
+/// Sets the current thread to the lowest priority of its scheduling policy.
+#[cfg(not(target_os = "linux"))]
+fn lower_current_thread_priority() -> Result<(), String> {
+    thread_priority::set_current_thread_priority(thread_priority::ThreadPriority::Min)
+        .map_err(|error| format!("{:?}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_threads_in_turn() {
+        let policy = MiningThreadPolicy {
+            cores: vec![2, 3],
+            low_priority: false,
+        };
+
+        assert_eq!(policy.core(0), Some(2));
+        assert_eq!(policy.core(1), Some(3));
+        assert_eq!(policy.core(2), Some(2));
+        assert_eq!(MiningThreadPolicy::default().core(0), None);
+    }
+
+    #[test]
+    fn validate_cores() {
+        assert!(MiningThreadPolicy::default().validate().is_ok());
+
+        let policy = MiningThreadPolicy {
+            cores: vec![usize::MAX],
+            low_priority: true,
+        };
+        assert!(policy.validate().is_err());
+    }
+}
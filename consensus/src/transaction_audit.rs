@@ -0,0 +1,98 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ConsensusParameters;
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_models::{dpc::Record, objects::Transaction};
+use snarkos_objects::{AccountViewKey, AleoAmount};
+
+/// The audit of the value flow of a transaction, computed from its public values alone.
+///
+/// The value balance of a transaction is the value of its input records minus the value of its output records.
+/// It is a public input of the transaction proof, which enforces the record values to be 64-bit integers opening
+/// the record commitments, and it is signed by the owners of the input records. A transaction auditing as valid
+/// hence moves exactly its value balance out of the shielded records, as a fee, or into them for a coinbase.
+#[derive(Clone, Debug)]
+pub struct TransactionAudit {
+    /// The id of the transaction
+    pub transaction_id: [u8; 32],
+    /// The value balance of the transaction
+    pub value_balance: AleoAmount,
+    /// Whether the signatures over the value balance verify
+    pub valid_signatures: bool,
+    /// Whether the transaction proof verifies against the value balance
+    pub valid_proof: bool,
+    /// The non-dummy output records opened by the given view keys, whose commitments match the transaction
+    pub disclosed_records: Vec<DPCRecord<Components>>,
+}
+
+impl TransactionAudit {
+    /// Audits the value balance of a transaction against its signatures and its proof, and opens the output
+    /// records encrypted to the given view keys. The transaction is not checked against the ledger state.
+    pub fn new(
+        parameters: &PublicParameters<Components>,
+        ledger_parameters: &CommitmentMerkleParameters,
+        transaction: &Tx,
+        view_keys: &[AccountViewKey<Components>],
+    ) -> Result<Self, ConsensusError> {
+        let valid_signatures = InstantiatedDPC::verify_signatures(parameters, transaction)?;
+        let valid_proof = InstantiatedDPC::verify_proof(parameters, transaction, ledger_parameters)?;
+
+        let mut disclosed_records = vec![];
+        for view_key in view_keys {
+            disclosed_records.extend(ConsensusParameters::decrypt_transaction_records(
+                parameters,
+                view_key,
+                transaction,
+            )?);
+        }
+
+        Ok(Self {
+            transaction_id: transaction.transaction_id()?,
+            value_balance: transaction.value_balance,
+            valid_signatures,
+            valid_proof,
+            disclosed_records,
+        })
+    }
+
+    /// Returns true if the value balance is bound by both the signatures and the proof.
+    pub fn is_valid(&self) -> bool {
+        self.valid_signatures && self.valid_proof
+    }
+
+    /// Returns true if the transaction mints new value, as a coinbase transaction.
+    pub fn is_coinbase(&self) -> bool {
+        self.value_balance.is_negative()
+    }
+
+    /// Returns the fee paid by the transaction, which is zero for a coinbase transaction.
+    pub fn fee(&self) -> u64 {
+        if self.is_coinbase() {
+            0
+        } else {
+            self.value_balance.0 as u64
+        }
+    }
+
+    /// Returns the total value of the disclosed output records.
+    pub fn disclosed_value(&self) -> u64 {
+        self.disclosed_records
+            .iter()
+            .fold(0u64, |total, record| total.saturating_add(record.value()))
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    memo::{TransactionMemo, MAX_ENCRYPTED_MEMO_SIZE},
+    record_selection::RecordSelection,
+    ConsensusParameters,
+    MerkleTreeLedger,
+};
+use snarkos_dpc::base_dpc::{
+    instantiated::*,
+    parameters::PublicParameters,
+    program::ProgramRegistry,
+    record::DPCRecord,
+    record_payload::RecordPayload,
+};
+use snarkos_errors::{consensus::ConsensusError, dpc::DPCError};
+use snarkos_models::{
+    algorithms::CRH,
+    dpc::{DPCComponents, Record},
+};
+use snarkos_objects::{AccountAddress, AccountPrivateKey, Network};
+use snarkos_utilities::{to_bytes, ToBytes};
+
+use rand::Rng;
+
+/// An output record of a transaction, sent to its recipient with its own value and payload.
+#[derive(Clone)]
+pub struct TransactionOutput {
+    /// The owner of the record
+    pub recipient: AccountAddress<Components>,
+    /// The value of the record
+    pub value: u64,
+    /// The payload of the record
+    pub payload: RecordPayload,
+}
+
+/// Builds a transaction spending the given records, each with the private key of its owner,
+/// into output records sent to any recipients. The unused input and output records are filled
+/// with dummy records, and the value of the inputs left over by the outputs is paid as a fee.
+#[derive(Clone, Default)]
+pub struct TransactionBuilder {
+    old_records: Vec<DPCRecord<Components>>,
+    old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+    outputs: Vec<TransactionOutput>,
+    memo: Option<TransactionMemo>,
+}
+
+impl TransactionBuilder {
+    /// Creates a transaction builder without inputs or outputs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spends the given record with the private key of its owner.
+    pub fn add_input(
+        mut self,
+        record: DPCRecord<Components>,
+        private_key: AccountPrivateKey<Components>,
+    ) -> Result<Self, ConsensusError> {
+        if self.old_records.len() == Components::NUM_INPUT_RECORDS {
+            return Err(ConsensusError::InvalidTransactionRecords(format!(
+                "more than {} input records",
+                Components::NUM_INPUT_RECORDS
+            )));
+        }
+        if record.is_dummy() {
+            return Err(ConsensusError::InvalidTransactionRecords(
+                "the input records are dummy records".into(),
+            ));
+        }
+
+        self.old_records.push(record);
+        self.old_account_private_keys.push(private_key);
+        Ok(self)
+    }
+
+    /// Selects input records among the given records of the account of the private key with the given strategy,
+    /// to cover the value of the outputs and the fee along with the inputs already added. The value of the selected
+    /// inputs over the outputs and the fee is sent back to the account in a change record, so the outputs are
+    /// added first.
+    pub fn select_inputs<S: RecordSelection, R: Rng>(
+        mut self,
+        parameters: &PublicParameters<Components>,
+        strategy: &S,
+        records: &[DPCRecord<Components>],
+        private_key: &AccountPrivateKey<Components>,
+        fee: u64,
+        rng: &mut R,
+    ) -> Result<Self, ConsensusError> {
+        let address = AccountAddress::<Components>::from_private_key(
+            parameters.account_signature_parameters(),
+            parameters.account_commitment_parameters(),
+            parameters.account_encryption_parameters(),
+            private_key,
+        )
+        .map_err(DPCError::from)?;
+
+        let mut added_commitments = Vec::with_capacity(self.old_records.len());
+        for record in &self.old_records {
+            added_commitments.push(to_bytes![record.commitment()]?);
+        }
+
+        // The candidates are the records of the account that are not spent by the transaction yet
+        let mut candidates = vec![];
+        for record in records {
+            if !record.is_dummy()
+                && record.owner() == &address
+                && !added_commitments.contains(&to_bytes![record.commitment()]?)
+            {
+                candidates.push(record.clone());
+            }
+        }
+
+        let inputs: u128 = self.old_records.iter().map(|record| u128::from(record.value())).sum();
+        let outputs: u128 = self.outputs.iter().map(|output| u128::from(output.value)).sum();
+        let max_records = Components::NUM_INPUT_RECORDS - self.old_records.len();
+
+        let target = (outputs + u128::from(fee)).saturating_sub(inputs);
+        if target > u128::from(u64::MAX) {
+            return Err(ConsensusError::InsufficientRecords(u64::MAX, max_records));
+        }
+        let target = target as u64;
+
+        let selection = strategy
+            .select(&candidates, target, max_records, rng)
+            .ok_or(ConsensusError::InsufficientRecords(target, max_records))?;
+
+        let mut selected_value = 0u128;
+        for index in selection {
+            selected_value += u128::from(candidates[index].value());
+            self = self.add_input(candidates[index].clone(), private_key.clone())?;
+        }
+
+        let change = (inputs + selected_value).saturating_sub(outputs + u128::from(fee));
+        if change > 0 {
+            if self.outputs.len() == Components::NUM_OUTPUT_RECORDS {
+                return Err(ConsensusError::InvalidTransactionRecords(format!(
+                    "no output record left for a change of {}",
+                    change
+                )));
+            }
+
+            self = self.add_output(address, change as u64)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Sends an output record of the given value to the recipient, with the default payload.
+    pub fn add_output(self, recipient: AccountAddress<Components>, value: u64) -> Result<Self, ConsensusError> {
+        self.add_output_with_payload(recipient, value, RecordPayload::default())
+    }
+
+    /// Sends an output record of the given value to the recipient address, read in strictly for the given
+    /// network. An address of another network, or failing its checksum, is rejected.
+    pub fn add_output_to_address(self, network: Network, address: &str, value: u64) -> Result<Self, ConsensusError> {
+        let recipient = AccountAddress::from_network_str(address, network).map_err(DPCError::from)?;
+
+        self.add_output(recipient, value)
+    }
+
+    /// Sends an output record of the given value and payload to the recipient.
+    pub fn add_output_with_payload(
+        mut self,
+        recipient: AccountAddress<Components>,
+        value: u64,
+        payload: RecordPayload,
+    ) -> Result<Self, ConsensusError> {
+        if self.outputs.len() == Components::NUM_OUTPUT_RECORDS {
+            return Err(ConsensusError::InvalidTransactionRecords(format!(
+                "more than {} output records",
+                Components::NUM_OUTPUT_RECORDS
+            )));
+        }
+
+        self.outputs.push(TransactionOutput {
+            recipient,
+            value,
+            payload,
+        });
+        Ok(self)
+    }
+
+    /// Sets the memo of the transaction, readable by anyone. The memo is random otherwise.
+    pub fn memo(mut self, memo: [u8; 32]) -> Self {
+        self.memo = Some(TransactionMemo::Plaintext(memo));
+        self
+    }
+
+    /// Sets the memo of the transaction to the given message, encrypted to the first recipient.
+    /// The message is at most `MAX_ENCRYPTED_MEMO_SIZE` bytes.
+    pub fn encrypted_memo(mut self, message: &[u8]) -> Result<Self, ConsensusError> {
+        if message.len() > MAX_ENCRYPTED_MEMO_SIZE {
+            return Err(ConsensusError::InvalidMemo(format!(
+                "the message is {} bytes, expected at most {}",
+                message.len(),
+                MAX_ENCRYPTED_MEMO_SIZE
+            )));
+        }
+
+        self.memo = Some(TransactionMemo::Encrypted(message.to_vec()));
+        Ok(self)
+    }
+
+    /// Returns the value of the input records left over by the output records.
+    pub fn fee(&self) -> Result<u64, ConsensusError> {
+        let inputs: u128 = self.old_records.iter().map(|record| u128::from(record.value())).sum();
+        let outputs: u128 = self.outputs.iter().map(|output| u128::from(output.value)).sum();
+
+        if outputs > inputs {
+            return Err(ConsensusError::TransactionOverspending);
+        }
+
+        Ok((inputs - outputs) as u64)
+    }
+
+    /// Generates the transaction, with the given program as the birth and death program of its records,
+    /// and returns its new records. The dummy input records are owned by the owner of the first input record,
+    /// and the dummy output records by the first recipient.
+    pub fn build<R: Rng>(
+        self,
+        consensus: &ConsensusParameters,
+        parameters: &PublicParameters<Components>,
+        program_id: Vec<u8>,
+        ledger: &MerkleTreeLedger,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        self.build_with_programs(
+            consensus,
+            parameters,
+            &ProgramRegistry::new(parameters)?,
+            program_id,
+            ledger,
+            rng,
+        )
+    }
+
+    /// Generates the transaction like `build`, proving the records with the programs of the given registry.
+    pub fn build_with_programs<R: Rng>(
+        self,
+        consensus: &ConsensusParameters,
+        parameters: &PublicParameters<Components>,
+        programs: &ProgramRegistry<Components>,
+        program_id: Vec<u8>,
+        ledger: &MerkleTreeLedger,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, Tx), ConsensusError> {
+        if self.old_records.is_empty() {
+            return Err(ConsensusError::InvalidTransactionRecords("no input records".into()));
+        }
+        if self.outputs.is_empty() {
+            return Err(ConsensusError::InvalidTransactionRecords("no output records".into()));
+        }
+        self.fee()?;
+
+        let mut old_records = self.old_records;
+        let mut old_account_private_keys = self.old_account_private_keys;
+
+        // Fill any unused old_record indices with dummy records
+        let private_key = old_account_private_keys[0].clone();
+        let address = AccountAddress::<Components>::from_private_key(
+            parameters.account_signature_parameters(),
+            parameters.account_commitment_parameters(),
+            parameters.account_encryption_parameters(),
+            &private_key,
+        )
+        .map_err(DPCError::from)?;
+
+        while old_records.len() < Components::NUM_INPUT_RECORDS {
+            let sn_randomness: [u8; 32] = rng.gen();
+            let old_sn_nonce =
+                SerialNumberNonce::hash(&parameters.system_parameters.serial_number_nonce, &sn_randomness)?;
+
+            let dummy_record = InstantiatedDPC::generate_record(
+                parameters.system_parameters.clone(),
+                old_sn_nonce,
+                address.clone(),
+                true, // The input record is dummy
+                0,
+                RecordPayload::default(),
+                program_id.clone(),
+                program_id.clone(),
+                rng,
+            )?;
+
+            old_records.push(dummy_record);
+            old_account_private_keys.push(private_key.clone());
+        }
+
+        // Fill any unused new_record indices with dummy output records
+        let num_dummy_records = Components::NUM_OUTPUT_RECORDS - self.outputs.len();
+        let dummy_owner = self.outputs[0].recipient.clone();
+
+        let mut new_record_owners = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        let mut new_values = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        let mut new_payloads = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        for output in self.outputs {
+            new_record_owners.push(output.recipient);
+            new_values.push(output.value);
+            new_payloads.push(output.payload);
+        }
+        let new_is_dummy_flags = [vec![false; new_record_owners.len()], vec![true; num_dummy_records]].concat();
+        new_record_owners.extend(vec![dummy_owner; num_dummy_records]);
+        new_values.extend(vec![0; num_dummy_records]);
+        new_payloads.extend(vec![RecordPayload::default(); num_dummy_records]);
+
+        let new_birth_program_ids = vec![program_id.clone(); Components::NUM_OUTPUT_RECORDS];
+        let new_death_program_ids = vec![program_id; Components::NUM_OUTPUT_RECORDS];
+
+        let memo = match self.memo {
+            Some(memo) => memo,
+            None => TransactionMemo::random(rng),
+        };
+
+        consensus.create_transaction_with_programs(
+            parameters,
+            programs,
+            old_records,
+            old_account_private_keys,
+            new_record_owners,
+            new_birth_program_ids,
+            new_death_program_ids,
+            new_is_dummy_flags,
+            new_values,
+            new_payloads,
+            memo,
+            ledger,
+            rng,
+        )
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::consensus::ConsensusError;
+
+use once_cell::sync::Lazy;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{Arc, RwLock};
+
+/// The worker threads verifying the transactions of the blocks, shared by every consensus of the node.
+static VERIFICATION_POOL: Lazy<RwLock<Arc<ThreadPool>>> = Lazy::new(|| {
+    RwLock::new(Arc::new(
+        build_pool(None).expect("could not start the verification threads"),
+    ))
+});
+
+/// Builds a pool of the given number of threads, or of one thread per core.
+fn build_pool(threads: Option<usize>) -> Result<ThreadPool, ConsensusError> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or(0))
+        .thread_name(|index| format!("verification-{}", index))
+        .build()
+        .map_err(|error| ConsensusError::Crate("rayon", error.to_string()))
+}
+
+/// Sets the number of threads verifying transactions, or one thread per core if `None`.
+/// The verifications already running finish on the previous threads.
+pub fn set_verification_threads(threads: Option<usize>) -> Result<(), ConsensusError> {
+    let pool = Arc::new(build_pool(threads)?);
+    *VERIFICATION_POOL.write().expect("verification pool lock poisoned") = pool;
+
+    Ok(())
+}
+
+/// Returns the number of threads verifying transactions.
+pub fn verification_threads() -> usize {
+    VERIFICATION_POOL
+        .read()
+        .expect("verification pool lock poisoned")
+        .current_num_threads()
+}
+
+/// Runs the given verification on the verification threads, so its parallel iterators use those threads only.
+pub fn verify_in_pool<R: Send, F: FnOnce() -> R + Send>(verification: F) -> R {
+    let pool = VERIFICATION_POOL
+        .read()
+        .expect("verification pool lock poisoned")
+        .clone();
+
+    pool.install(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_verification_threads() {
+        set_verification_threads(Some(2)).unwrap();
+        assert_eq!(verification_threads(), 2);
+        assert_eq!(verify_in_pool(rayon::current_num_threads), 2);
+
+        set_verification_threads(None).unwrap();
+        assert!(verification_threads() >= 1);
+    }
+}
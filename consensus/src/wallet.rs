@@ -0,0 +1,301 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ConsensusParameters, MerkleTreeLedger};
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
+use snarkos_errors::{consensus::ConsensusError, dpc::DPCError};
+use snarkos_models::{dpc::Record, objects::Transaction};
+use snarkos_objects::{AccountProofKey, AccountViewKey, Block};
+use snarkos_storage::{WalletAccount, WalletRecord, WalletSpend};
+use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
+
+use std::collections::BTreeMap;
+
+/// The balance of a wallet account as of the latest canon block.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WalletBalance {
+    /// The value of the unspent records with at least the requested number of confirmations
+    pub confirmed: u64,
+    /// The value of the unspent records with fewer confirmations
+    pub pending: u64,
+    /// Whether the spends of the records are tracked; if not, every record counts as unspent
+    pub tracks_spends: bool,
+}
+
+/// A canon transaction paying or spending the records of a wallet account.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletTransaction {
+    /// The id of the transaction
+    pub transaction_id: Vec<u8>,
+    /// The number of the block of the transaction
+    pub block_number: u32,
+    /// The number of blocks built on top of the block of the transaction
+    pub confirmations: u32,
+    /// The value of the records of the account created by the transaction
+    pub received: u64,
+    /// The value of the records of the account spent by the transaction
+    pub spent: u64,
+}
+
+/// Tracks the records received by the account view keys registered with the node, and their balances.
+///
+/// Unlike the record index of the ledger, a wallet account also records whether its records are spent,
+/// by watching the canon blocks for their serial numbers. The serial numbers are derived from the proof
+/// key of the account, so an account registered with its view key alone counts every record as unspent.
+/// The wallet accounts are persisted, updated as blocks are committed and unwound as they are decommitted.
+pub struct Wallet<'a> {
+    parameters: &'a PublicParameters<Components>,
+    storage: &'a MerkleTreeLedger,
+}
+
+impl<'a> Wallet<'a> {
+    /// Creates a wallet over the given ledger.
+    pub fn new(parameters: &'a PublicParameters<Components>, storage: &'a MerkleTreeLedger) -> Self {
+        Self { parameters, storage }
+    }
+
+    /// Registers an account view key with the wallet, along with the proof key of the account if the spends
+    /// of its records are to be tracked, and scans the canon blocks for its records. Registering an account
+    /// again rescans the blocks, and the records of the pruned blocks are not scanned.
+    pub fn register_account(
+        &self,
+        view_key: &AccountViewKey<Components>,
+        proof_key: Option<&AccountProofKey<Components>>,
+    ) -> Result<(), ConsensusError> {
+        if let Some(proof_key) = proof_key {
+            let decryption_key = proof_key
+                .to_decryption_key(self.parameters.account_commitment_parameters())
+                .map_err(DPCError::from)?;
+
+            if decryption_key != view_key.decryption_key {
+                return Err(ConsensusError::ProofKeyMismatch);
+            }
+        }
+
+        let proof_key_bytes = match proof_key {
+            Some(proof_key) => Some(to_bytes![proof_key]?),
+            None => None,
+        };
+        let mut wallet_account = WalletAccount::new(proof_key_bytes);
+        let mut new_records = vec![];
+
+        // The account is scanned and stored before another block is committed
+        let _block_lock = self.storage.lock_blocks();
+
+        let pruned_block_height = self.storage.get_pruned_block_height()?;
+
+        for block_number in 0..=self.storage.get_latest_block_height() {
+            if block_number != 0 && block_number <= pruned_block_height {
+                continue;
+            }
+
+            let block = self.storage.get_block_from_block_number(block_number)?;
+            new_records.extend(self.scan_block(&mut wallet_account, view_key, proof_key, &block, block_number)?);
+        }
+
+        self.storage
+            .store_wallet_account(&to_bytes![view_key]?, &wallet_account, &new_records)?;
+
+        Ok(())
+    }
+
+    /// Removes an account view key and its records from the wallet.
+    /// Returns false if the view key is not registered.
+    pub fn unregister_account(&self, view_key: &AccountViewKey<Components>) -> Result<bool, ConsensusError> {
+        let view_key_bytes = to_bytes![view_key]?;
+
+        if self.storage.get_wallet_account(&view_key_bytes)?.is_none() {
+            return Ok(false);
+        }
+
+        self.storage.remove_wallet_account(&view_key_bytes)?;
+
+        Ok(true)
+    }
+
+    /// Updates every wallet account with the records received and spent in a canon block.
+    pub fn index_block(&self, block: &Block<Tx>, block_number: u32) -> Result<(), ConsensusError> {
+        for view_key_bytes in self.storage.get_wallet_view_keys()? {
+            let mut wallet_account = match self.storage.get_wallet_account(&view_key_bytes)? {
+                Some(wallet_account) => wallet_account,
+                None => continue,
+            };
+
+            let view_key = AccountViewKey::<Components>::read(&view_key_bytes[..])?;
+            let proof_key = match &wallet_account.proof_key {
+                Some(proof_key_bytes) => Some(AccountProofKey::<Components>::read(&proof_key_bytes[..])?),
+                None => None,
+            };
+
+            let previous_wallet_account = wallet_account.clone();
+            let new_records =
+                self.scan_block(&mut wallet_account, &view_key, proof_key.as_ref(), block, block_number)?;
+
+            if wallet_account != previous_wallet_account {
+                self.storage
+                    .store_wallet_account(&view_key_bytes, &wallet_account, &new_records)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the balance of a registered account view key, counting the records with fewer than
+    /// `min_confirmations` confirmations as pending. Returns `None` if the view key is not registered.
+    pub fn balance(
+        &self,
+        view_key: &AccountViewKey<Components>,
+        min_confirmations: u32,
+    ) -> Result<Option<WalletBalance>, ConsensusError> {
+        let wallet_account = match self.storage.get_wallet_account(&to_bytes![view_key]?)? {
+            Some(wallet_account) => wallet_account,
+            None => return Ok(None),
+        };
+
+        let mut balance = WalletBalance {
+            tracks_spends: wallet_account.tracks_spends(),
+            ..Default::default()
+        };
+
+        for record in wallet_account.records.iter().filter(|record| record.spend.is_none()) {
+            if self.confirmations(record.block_number) >= min_confirmations {
+                balance.confirmed = balance.confirmed.saturating_add(record.value);
+            } else {
+                balance.pending = balance.pending.saturating_add(record.value);
+            }
+        }
+
+        Ok(Some(balance))
+    }
+
+    /// Returns the canon transactions paying or spending the records of a registered account view key,
+    /// ordered by block. Returns `None` if the view key is not registered.
+    pub fn transactions(
+        &self,
+        view_key: &AccountViewKey<Components>,
+    ) -> Result<Option<Vec<WalletTransaction>>, ConsensusError> {
+        let wallet_account = match self.storage.get_wallet_account(&to_bytes![view_key]?)? {
+            Some(wallet_account) => wallet_account,
+            None => return Ok(None),
+        };
+
+        // The received and spent values, by block number and transaction id
+        let mut entries: BTreeMap<(u32, Vec<u8>), (u64, u64)> = BTreeMap::new();
+
+        for record in &wallet_account.records {
+            let entry = entries
+                .entry((record.block_number, record.transaction_id.clone()))
+                .or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(record.value);
+
+            if let Some(spend) = &record.spend {
+                let entry = entries
+                    .entry((spend.block_number, spend.transaction_id.clone()))
+                    .or_insert((0, 0));
+                entry.1 = entry.1.saturating_add(record.value);
+            }
+        }
+
+        Ok(Some(
+            entries
+                .into_iter()
+                .map(
+                    |((block_number, transaction_id), (received, spent))| WalletTransaction {
+                        transaction_id,
+                        block_number,
+                        confirmations: self.confirmations(block_number),
+                        received,
+                        spent,
+                    },
+                )
+                .collect(),
+        ))
+    }
+
+    /// Returns the number of blocks built on top of the canon block at `block_number`.
+    fn confirmations(&self, block_number: u32) -> u32 {
+        self.storage.get_latest_block_height().saturating_sub(block_number)
+    }
+
+    /// Adds the records of the account view key found in a canon block to the wallet account, and marks the
+    /// records whose serial numbers the block reveals as spent. Returns the records new to the account.
+    fn scan_block(
+        &self,
+        wallet_account: &mut WalletAccount,
+        view_key: &AccountViewKey<Components>,
+        proof_key: Option<&AccountProofKey<Components>>,
+        block: &Block<Tx>,
+        block_number: u32,
+    ) -> Result<Vec<DPCRecord<Components>>, ConsensusError> {
+        let mut new_records = vec![];
+
+        for transaction in block.transactions.iter() {
+            let transaction_id = transaction.transaction_id()?.to_vec();
+
+            // A record is spent in a later block than the block creating it
+            for serial_number in transaction.old_serial_numbers() {
+                let serial_number = to_bytes![serial_number]?;
+
+                for record in wallet_account.records.iter_mut() {
+                    if record.spend.is_none() && record.serial_number.as_ref() == Some(&serial_number) {
+                        record.spend = Some(WalletSpend {
+                            block_number,
+                            transaction_id: transaction_id.clone(),
+                        });
+                    }
+                }
+            }
+
+            for record in ConsensusParameters::decrypt_transaction_records(self.parameters, view_key, transaction)? {
+                let commitment = to_bytes![record.commitment()]?;
+
+                if wallet_account
+                    .records
+                    .iter()
+                    .any(|wallet_record| wallet_record.commitment == commitment)
+                {
+                    continue;
+                }
+
+                let serial_number = match proof_key {
+                    Some(proof_key) => {
+                        let (serial_number, _) = InstantiatedDPC::generate_sns_with_proof_key(
+                            &self.parameters.system_parameters,
+                            std::slice::from_ref(&record),
+                            proof_key,
+                        )?
+                        .remove(0);
+
+                        Some(to_bytes![serial_number]?)
+                    }
+                    None => None,
+                };
+
+                wallet_account.records.push(WalletRecord {
+                    block_number,
+                    transaction_id: transaction_id.clone(),
+                    commitment,
+                    value: record.value(),
+                    serial_number,
+                    spend: None,
+                });
+                new_records.push(record);
+            }
+        }
+
+        Ok(new_records)
+    }
+}
@@ -15,13 +15,32 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 mod consensus_dpc {
-    use snarkos_consensus::{get_block_reward, MemoryPool, Miner};
-    use snarkos_dpc::base_dpc::{instantiated::*, record::DPCRecord, record_payload::RecordPayload};
+    use snarkos_consensus::{
+        get_block_reward,
+        BranchAndBound,
+        ConsolidationBuilder,
+        LargestFirst,
+        ManualSelection,
+        MemoryPool,
+        Miner,
+        RandomSelection,
+        RecordScanner,
+        RecordSpentness,
+        TransactionBuilder,
+        TransactionMemo,
+        Wallet,
+    };
+    use snarkos_dpc::base_dpc::{
+        instantiated::*,
+        program::{NoopProgram, ProgramRegistry},
+        record::DPCRecord,
+        record_payload::RecordPayload,
+    };
     use snarkos_models::{
         dpc::{DPCScheme, Program, Record},
-        objects::LedgerScheme,
+        objects::{LedgerScheme, Transaction},
     };
-    use snarkos_objects::{dpc::DPCTransactions, Block};
+    use snarkos_objects::{dpc::DPCTransactions, AccountPrivateKey, AccountProofKey, AccountViewKey, Block, Network};
     use snarkos_testing::{consensus::*, storage::kill_storage};
     use snarkos_utilities::{bytes::ToBytes, to_bytes};
 
@@ -94,7 +113,7 @@ mod consensus_dpc {
                 new_is_dummy_flags,
                 new_values,
                 new_payloads,
-                memo,
+                TransactionMemo::Plaintext(memo),
                 &ledger,
                 &mut rng,
             )
@@ -153,4 +172,543 @@ mod consensus_dpc {
 
         kill_storage(ledger);
     }
+
+    #[test]
+    fn transaction_builder_multiple_recipients() {
+        let parameters = &FIXTURE.parameters;
+        let ledger = FIXTURE.ledger();
+        let program = FIXTURE.program.clone();
+        let [genesis_acc, miner_acc, recipient] = FIXTURE.test_accounts.clone();
+        let mut rng = FIXTURE.rng.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+        let miner = Miner::new(miner_acc.address, consensus.clone());
+
+        let transactions = DPCTransactions::<Tx>::new();
+        let (previous_block_header, transactions, coinbase_records) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+
+        let mut memory_pool = MemoryPool::new();
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+
+        let block_reward = get_block_reward(1);
+
+        // The outputs go to distinct recipients, with their own values and payloads
+        let payload = RecordPayload::from_bytes(&[7u8; 32]);
+        let builder = TransactionBuilder::new()
+            .add_input(coinbase_records[0].clone(), miner_acc.private_key.clone())
+            .unwrap()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .add_output_with_payload(genesis_acc.address.clone(), 20, payload.clone())
+            .unwrap()
+            .memo([6u8; 32]);
+
+        assert_eq!(builder.fee().unwrap(), block_reward.0 as u64 - 30);
+
+        // The dummy coinbase record cannot be spent, and the outputs are limited
+        assert!(builder
+            .clone()
+            .add_input(coinbase_records[1].clone(), miner_acc.private_key.clone())
+            .is_err());
+        assert!(builder.clone().add_output(recipient.address.clone(), 1).is_err());
+
+        let (new_records, transaction) = builder
+            .build(&consensus, &parameters, program.into_compact_repr(), &ledger, &mut rng)
+            .unwrap();
+
+        assert_eq!(new_records.len(), NUM_OUTPUT_RECORDS);
+        assert!(!new_records[0].is_dummy());
+        assert!(!new_records[1].is_dummy());
+        assert_eq!(new_records[0].value(), 10);
+        assert_eq!(new_records[1].value(), 20);
+        assert!(new_records[0].owner() == &recipient.address);
+        assert!(new_records[1].owner() == &genesis_acc.address);
+        assert_eq!(new_records[0].payload(), &RecordPayload::default());
+        assert_eq!(new_records[1].payload(), &payload);
+        assert_eq!(transaction.memorandum, [6u8; 32]);
+        assert_eq!(transaction.value_balance.0, block_reward.0 - 30);
+
+        assert!(InstantiatedDPC::verify(&parameters, &transaction, &ledger).unwrap());
+
+        // Outputs above the value of the inputs are rejected
+        let overspending = TransactionBuilder::new()
+            .add_input(coinbase_records[0].clone(), miner_acc.private_key.clone())
+            .unwrap()
+            .add_output(recipient.address.clone(), block_reward.0 as u64 + 1)
+            .unwrap();
+        assert!(overspending.fee().is_err());
+        assert!(overspending
+            .build(&consensus, &parameters, program.into_compact_repr(), &ledger, &mut rng)
+            .is_err());
+
+        // The recipient addresses are read in strictly for the network of the consensus
+        let network_address = recipient.address.to_network_string(consensus.network);
+        let builder = TransactionBuilder::new()
+            .add_output_to_address(consensus.network, &network_address, 10)
+            .unwrap();
+        assert_eq!(builder.fee().unwrap(), 0);
+
+        let other_network_address = recipient.address.to_network_string(Network::Custom(7));
+        assert!(TransactionBuilder::new()
+            .add_output_to_address(consensus.network, &other_network_address, 10)
+            .is_err());
+
+        // The inputs are selected among the records of the account, with the change sent back to it
+        let builder = TransactionBuilder::new()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .select_inputs(
+                &parameters,
+                &LargestFirst,
+                &coinbase_records,
+                &miner_acc.private_key,
+                5,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(builder.fee().unwrap(), 5);
+
+        // An exact match needs no change record, and the records of other accounts are not selected
+        assert!(TransactionBuilder::new()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .select_inputs(
+                &parameters,
+                &BranchAndBound::default(),
+                &coinbase_records,
+                &miner_acc.private_key,
+                5,
+                &mut rng,
+            )
+            .is_err());
+        assert!(TransactionBuilder::new()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .select_inputs(
+                &parameters,
+                &RandomSelection,
+                &coinbase_records,
+                &genesis_acc.private_key,
+                0,
+                &mut rng,
+            )
+            .is_err());
+
+        // The records are selected by commitment, whatever the strategy would prefer
+        let manual = ManualSelection {
+            commitments: vec![to_bytes![coinbase_records[0].commitment()].unwrap()],
+        };
+        let builder = TransactionBuilder::new()
+            .add_output(recipient.address.clone(), block_reward.0 as u64)
+            .unwrap()
+            .select_inputs(
+                &parameters,
+                &manual,
+                &coinbase_records,
+                &miner_acc.private_key,
+                0,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(builder.fee().unwrap(), 0);
+
+        kill_storage(ledger);
+    }
+
+    #[test]
+    fn program_registry_deployment() {
+        let parameters = &FIXTURE.parameters;
+        let ledger = FIXTURE.ledger();
+        let program = FIXTURE.program.clone();
+        let [_genesis_address, miner_acc, recipient] = FIXTURE.test_accounts.clone();
+        let mut rng = FIXTURE.rng.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+        let miner = Miner::new(miner_acc.address, consensus.clone());
+
+        let transactions = DPCTransactions::<Tx>::new();
+        let (previous_block_header, transactions, coinbase_records) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+
+        let mut memory_pool = MemoryPool::new();
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+
+        // The registry starts with the noop program
+        let mut programs = ProgramRegistry::new(&parameters).unwrap();
+        assert!(programs.contains(&program.into_compact_repr()));
+
+        // Deploy a program with its own keys
+        let program_parameters =
+            InstantiatedDPC::generate_noop_program_snark_parameters(&parameters.system_parameters, &mut rng).unwrap();
+        let program_id = ProgramRegistry::program_id(
+            &parameters.system_parameters,
+            &to_bytes![program_parameters.verification_key].unwrap(),
+        )
+        .unwrap();
+        assert_ne!(program_id, program.into_compact_repr());
+
+        // The records cannot name a program unknown to the registry
+        let builder = TransactionBuilder::new()
+            .add_input(coinbase_records[0].clone(), miner_acc.private_key.clone())
+            .unwrap()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap();
+        assert!(builder
+            .clone()
+            .build_with_programs(
+                &consensus,
+                &parameters,
+                &programs,
+                program_id.clone(),
+                &ledger,
+                &mut rng
+            )
+            .is_err());
+
+        // A program is registered under the hash of its verification key
+        assert!(programs
+            .register(
+                &parameters.system_parameters,
+                NoopProgram::<Components, NoopProgramSNARK<Components>>::new(program.into_compact_repr()),
+                program_parameters.proving_key.clone(),
+                program_parameters.verification_key.clone(),
+            )
+            .is_err());
+        assert_eq!(
+            programs
+                .register(
+                    &parameters.system_parameters,
+                    NoopProgram::<Components, NoopProgramSNARK<Components>>::new(program_id.clone()),
+                    program_parameters.proving_key,
+                    program_parameters.verification_key,
+                )
+                .unwrap(),
+            program_id
+        );
+        assert_eq!(programs.program_ids().len(), 2);
+
+        // The new records are born with the deployed program
+        let (new_records, transaction) = builder
+            .build_with_programs(
+                &consensus,
+                &parameters,
+                &programs,
+                program_id.clone(),
+                &ledger,
+                &mut rng,
+            )
+            .unwrap();
+
+        assert_eq!(new_records[0].birth_program_id(), &program_id[..]);
+        assert_eq!(new_records[0].death_program_id(), &program_id[..]);
+        assert!(InstantiatedDPC::verify(&parameters, &transaction, &ledger).unwrap());
+
+        kill_storage(ledger);
+    }
+
+    #[test]
+    fn record_scanner_spentness() {
+        let parameters = &FIXTURE.parameters;
+        let ledger = FIXTURE.ledger();
+        let program = FIXTURE.program.clone();
+        let [genesis_acc, miner_acc, recipient] = FIXTURE.test_accounts.clone();
+        let mut rng = FIXTURE.rng.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+        let miner = Miner::new(miner_acc.address.clone(), consensus.clone());
+        let mut memory_pool = MemoryPool::new();
+
+        let transactions = DPCTransactions::<Tx>::new();
+        let (previous_block_header, transactions, coinbase_records) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+
+        let view_key = AccountViewKey::from_private_key(
+            parameters.account_signature_parameters(),
+            parameters.account_commitment_parameters(),
+            &miner_acc.private_key,
+        )
+        .unwrap();
+
+        // Without the private key, the spentness of the coinbase record is unknown
+        let scanned_records = RecordScanner::new(&parameters, view_key.clone())
+            .scan(&ledger, 0..10)
+            .unwrap();
+        assert_eq!(scanned_records.len(), 1);
+        assert_eq!(scanned_records[0].block_number, 1);
+        assert_eq!(scanned_records[0].value(), coinbase_records[0].value());
+        assert_eq!(
+            scanned_records[0].serial_number_nonce(),
+            coinbase_records[0].serial_number_nonce()
+        );
+        assert_eq!(scanned_records[0].spentness, RecordSpentness::Unknown);
+
+        // The private key must be the key of the view key
+        assert!(RecordScanner::new(&parameters, view_key.clone())
+            .with_private_key(genesis_acc.private_key.clone())
+            .is_err());
+
+        let scanner = RecordScanner::new(&parameters, view_key)
+            .with_private_key(miner_acc.private_key.clone())
+            .unwrap();
+        assert_eq!(
+            scanner.scan(&ledger, 0..10).unwrap()[0].spentness,
+            RecordSpentness::Unspent
+        );
+        assert!(scanner.scan(&ledger, 2..10).unwrap().is_empty());
+
+        // Spend the coinbase record in the next block
+        let (_, transaction) = TransactionBuilder::new()
+            .add_input(coinbase_records[0].clone(), miner_acc.private_key.clone())
+            .unwrap()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .encrypted_memo(b"invoice 42")
+            .unwrap()
+            .build(&consensus, &parameters, program.into_compact_repr(), &ledger, &mut rng)
+            .unwrap();
+        let payment_memo = transaction.memorandum;
+
+        let mut transactions = DPCTransactions::new();
+        transactions.push(transaction);
+        let (previous_block_header, transactions, _) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+
+        let scanned_records = scanner.scan(&ledger, 0..10).unwrap();
+        assert_eq!(scanned_records[0].block_number, 1);
+        assert_eq!(scanned_records[0].spentness, RecordSpentness::Spent);
+
+        // The coinbase record of the second block is unspent
+        assert_eq!(scanned_records.len(), 2);
+        assert_eq!(scanned_records[1].block_number, 2);
+        assert_eq!(scanned_records[1].spentness, RecordSpentness::Unspent);
+        assert_eq!(scanned_records[0].memo, None);
+
+        // The memo of the payment is encrypted to its recipient
+        let recipient_view_key = AccountViewKey::from_private_key(
+            parameters.account_signature_parameters(),
+            parameters.account_commitment_parameters(),
+            &recipient.private_key,
+        )
+        .unwrap();
+        let recipient_records = RecordScanner::new(&parameters, recipient_view_key)
+            .scan(&ledger, 0..10)
+            .unwrap();
+        assert_eq!(recipient_records.len(), 1);
+        assert_eq!(recipient_records[0].memo, Some(b"invoice 42".to_vec()));
+        assert!(!payment_memo.windows(10).any(|window| window == b"invoice 42"));
+
+        kill_storage(ledger);
+    }
+
+    #[test]
+    fn wallet_balance_and_history() {
+        let parameters = &FIXTURE.parameters;
+        let ledger = FIXTURE.ledger();
+        let program = FIXTURE.program.clone();
+        let [genesis_acc, miner_acc, recipient] = FIXTURE.test_accounts.clone();
+        let mut rng = FIXTURE.rng.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+        let miner = Miner::new(miner_acc.address.clone(), consensus.clone());
+        let mut memory_pool = MemoryPool::new();
+        let wallet = Wallet::new(&parameters, &ledger);
+
+        let transactions = DPCTransactions::<Tx>::new();
+        let (previous_block_header, transactions, coinbase_records) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+        let coinbase_value = coinbase_records[0].value();
+
+        let view_key = |private_key: &AccountPrivateKey<Components>| {
+            AccountViewKey::from_private_key(
+                parameters.account_signature_parameters(),
+                parameters.account_commitment_parameters(),
+                private_key,
+            )
+            .unwrap()
+        };
+        let proof_key = |private_key: &AccountPrivateKey<Components>| {
+            AccountProofKey::from_private_key(parameters.account_signature_parameters(), private_key).unwrap()
+        };
+        let miner_view_key = view_key(&miner_acc.private_key);
+        let recipient_view_key = view_key(&recipient.private_key);
+
+        // The proof key must be the key of the view key
+        assert!(wallet
+            .register_account(&miner_view_key, Some(&proof_key(&genesis_acc.private_key)))
+            .is_err());
+        assert_eq!(wallet.balance(&miner_view_key, 0).unwrap(), None);
+
+        // Registering the accounts scans the canon blocks
+        wallet
+            .register_account(&miner_view_key, Some(&proof_key(&miner_acc.private_key)))
+            .unwrap();
+        wallet.register_account(&recipient_view_key, None).unwrap();
+
+        let balance = wallet.balance(&miner_view_key, 0).unwrap().unwrap();
+        assert_eq!(balance.confirmed, coinbase_value);
+        assert!(balance.tracks_spends);
+
+        // The record of the latest block has no confirmation yet
+        let balance = wallet.balance(&miner_view_key, 1).unwrap().unwrap();
+        assert_eq!((balance.confirmed, balance.pending), (0, coinbase_value));
+
+        // Spend the coinbase record in the next block, which the wallet indexes as it is received
+        let (_, transaction) = TransactionBuilder::new()
+            .add_input(coinbase_records[0].clone(), miner_acc.private_key.clone())
+            .unwrap()
+            .add_output(recipient.address.clone(), 10)
+            .unwrap()
+            .build(&consensus, &parameters, program.into_compact_repr(), &ledger, &mut rng)
+            .unwrap();
+        let payment_id = transaction.transaction_id().unwrap().to_vec();
+
+        let mut transactions = DPCTransactions::new();
+        transactions.push(transaction);
+        let (previous_block_header, transactions, coinbase_records) =
+            miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+        let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+        let block = Block { header, transactions };
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+            .unwrap();
+        let second_coinbase_value = coinbase_records[0].value();
+
+        let balance = wallet.balance(&miner_view_key, 1).unwrap().unwrap();
+        assert_eq!((balance.confirmed, balance.pending), (0, second_coinbase_value));
+
+        let history = wallet.transactions(&miner_view_key).unwrap().unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].block_number, 1);
+        assert_eq!(history[0].confirmations, 1);
+        assert_eq!((history[0].received, history[0].spent), (coinbase_value, 0));
+
+        let payment = history.iter().find(|entry| entry.transaction_id == payment_id).unwrap();
+        assert_eq!(payment.block_number, 2);
+        assert_eq!((payment.received, payment.spent), (0, coinbase_value));
+
+        // Without the proof key of the recipient, its records count as unspent
+        let balance = wallet.balance(&recipient_view_key, 0).unwrap().unwrap();
+        assert_eq!(balance.confirmed, 10);
+        assert!(!balance.tracks_spends);
+
+        let history = wallet.transactions(&recipient_view_key).unwrap().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].transaction_id, payment_id);
+        assert_eq!((history[0].received, history[0].spent), (10, 0));
+
+        // Decommitting the block unwinds its records and spends
+        ledger.decommit_latest_block().unwrap();
+
+        let balance = wallet.balance(&miner_view_key, 0).unwrap().unwrap();
+        assert_eq!((balance.confirmed, balance.pending), (coinbase_value, 0));
+        assert_eq!(wallet.transactions(&miner_view_key).unwrap().unwrap().len(), 1);
+        assert!(wallet.transactions(&recipient_view_key).unwrap().unwrap().is_empty());
+
+        assert!(wallet.unregister_account(&recipient_view_key).unwrap());
+        assert!(!wallet.unregister_account(&recipient_view_key).unwrap());
+        assert_eq!(wallet.transactions(&recipient_view_key).unwrap(), None);
+
+        kill_storage(ledger);
+    }
+
+    #[test]
+    fn consolidation_builder_rounds() {
+        let parameters = &FIXTURE.parameters;
+        let ledger = FIXTURE.ledger();
+        let program_id = FIXTURE.program.into_compact_repr();
+        let [genesis_acc, miner_acc, _recipient] = FIXTURE.test_accounts.clone();
+        let mut rng = FIXTURE.rng.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+        let miner = Miner::new(miner_acc.address.clone(), consensus.clone());
+        let mut memory_pool = MemoryPool::new();
+
+        let mut mine_block = |transactions: Vec<Tx>| {
+            let transactions = DPCTransactions(transactions);
+            let (previous_block_header, transactions, coinbase_records) =
+                miner.establish_block(&parameters, &ledger, &transactions).unwrap();
+            let header = miner.find_block(&transactions, &previous_block_header).unwrap();
+            let block = Block { header, transactions };
+            consensus
+                .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+                .unwrap();
+            coinbase_records
+        };
+
+        // The miner receives a coinbase record in each of three blocks
+        let mut records = vec![];
+        for _ in 0..3 {
+            records.extend(mine_block(vec![]));
+        }
+        let total_value: u64 = records.iter().map(|record| record.value()).sum();
+
+        // The records of other accounts and the dummy records are not selected
+        let mut builder = ConsolidationBuilder::new(miner_acc.private_key.clone())
+            .fee(1)
+            .select_records(&parameters, records)
+            .unwrap();
+        assert_eq!(builder.records().len(), 3);
+        assert!(ConsolidationBuilder::new(genesis_acc.private_key.clone())
+            .select_records(&parameters, builder.records().to_vec())
+            .unwrap()
+            .records()
+            .is_empty());
+
+        // The first round spends the two smallest records, and carries the third record over
+        let transactions = builder
+            .build_round(&consensus, &parameters, program_id.clone(), &ledger, &mut rng)
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(builder.records().len(), 2);
+        assert!(!builder.is_complete());
+
+        // The next round waits for the transactions of the first round to be confirmed
+        assert!(!builder.is_ready(&ledger));
+        assert!(builder
+            .build_round(&consensus, &parameters, program_id.clone(), &ledger, &mut rng)
+            .is_err());
+        mine_block(transactions);
+        assert!(builder.is_ready(&ledger));
+
+        let transactions = builder
+            .build_round(&consensus, &parameters, program_id.clone(), &ledger, &mut rng)
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+        mine_block(transactions);
+
+        // A single record is left, holding the value of the records less the fees
+        assert!(builder.is_complete());
+        assert_eq!(builder.records().len(), 1);
+        assert_eq!(builder.records()[0].value(), total_value - 2);
+        assert!(builder
+            .build_round(&consensus, &parameters, program_id, &ledger, &mut rng)
+            .unwrap()
+            .is_empty());
+
+        kill_storage(ledger);
+    }
 }
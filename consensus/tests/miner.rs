@@ -15,7 +15,8 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 mod miner {
-    use snarkos_consensus::Miner;
+    use snarkos_consensus::{miner::nonce_ranges, CoinbasePayout, Miner};
+    use snarkos_dpc::base_dpc::instantiated::Components;
     use snarkos_models::{
         algorithms::{commitment::CommitmentScheme, encryption::EncryptionScheme, signature::SignatureScheme},
         dpc::DPCComponents,
@@ -64,4 +65,83 @@ mod miner {
         let parent_header = genesis().header;
         test_find_block(&transactions, &parent_header);
     }
+
+    #[test]
+    fn find_valid_block_with_threads() {
+        let consensus = TEST_CONSENSUS.clone();
+        let mut rng = XorShiftRng::seed_from_u64(3);
+
+        let (_, miner_address) = keygen(&mut rng);
+        let miner = Miner::with_threads(miner_address, consensus.clone(), 4);
+
+        let transactions = DPCTransactions(vec![TestTx; 3]);
+        let parent_header = genesis().header;
+        let header = miner.find_block(&transactions, &parent_header).unwrap();
+
+        let (merkle_root, pedersen_merkle_root, _) = txids_to_roots(&transactions.to_transaction_ids().unwrap());
+        consensus
+            .verify_header(&header, &parent_header, &merkle_root, &pedersen_merkle_root)
+            .unwrap();
+
+        // Each thread attempted proofs within its own nonce range
+        assert_eq!(miner.thread_stats().len(), 4);
+        assert!(miner.thread_stats().iter().map(|stats| stats.attempts()).sum::<u64>() > 0);
+        assert!(miner.hashrate() > 0.0);
+    }
+
+    #[test]
+    fn benchmark_proofs() {
+        let consensus = TEST_CONSENSUS.clone();
+        let mut rng = XorShiftRng::seed_from_u64(3);
+
+        let (_, miner_address) = keygen(&mut rng);
+        let miner = Miner::with_threads(miner_address, consensus, 2);
+
+        let transactions = DPCTransactions(vec![TestTx; 3]);
+        let benchmark = miner.benchmark(&transactions, &genesis().header, 3).unwrap();
+
+        assert_eq!(benchmark.proofs, 3);
+        assert_eq!(benchmark.threads, 2);
+        assert!(benchmark.proofs_per_second() > 0.0);
+        assert!(benchmark.average_proving_time() > benchmark.average_verification_time());
+    }
+
+    #[test]
+    fn partition_nonces() {
+        assert_eq!(nonce_ranges(10, 3), vec![0..3, 3..6, 6..10]);
+        assert_eq!(nonce_ranges(10, 0), vec![0..10]);
+        assert_eq!(nonce_ranges(2, 4), vec![0..1, 1..2]);
+
+        let ranges = nonce_ranges(std::u32::MAX, 8);
+        assert_eq!(ranges.len(), 8);
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, std::u32::MAX);
+        assert!(ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+    }
+
+    #[test]
+    fn coinbase_payout_recipients() {
+        let mut rng = XorShiftRng::seed_from_u64(3);
+        let (_, first) = keygen::<Components, _>(&mut rng);
+        let (_, second) = keygen::<Components, _>(&mut rng);
+
+        let rotation = CoinbasePayout::Rotation(vec![first.clone(), second.clone()]);
+        assert!(rotation.validate().is_ok());
+        assert_eq!(rotation.recipients(1), vec![(second.clone(), 100)]);
+        assert_eq!(rotation.recipients(2), vec![(first.clone(), 100)]);
+
+        let split = CoinbasePayout::Split(vec![(first.clone(), 60), (second.clone(), 40)]);
+        assert!(split.validate().is_ok());
+        assert_eq!(split.recipients(1), vec![(first.clone(), 60), (second.clone(), 40)]);
+
+        assert!(CoinbasePayout::Rotation(vec![]).validate().is_err());
+        assert!(CoinbasePayout::Split(vec![(first.clone(), 60), (second.clone(), 30)])
+            .validate()
+            .is_err());
+        assert!(
+            CoinbasePayout::Split(vec![(first.clone(), 50), (second, 25), (first, 25)])
+                .validate()
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,395 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::base_dpc::{
+    parameters::SystemParameters,
+    record::{DPCRecord, EncryptedRecord},
+    BaseDPCComponents,
+    LocalData,
+    DPC,
+};
+use snarkos_algorithms::merkle_tree::{MerklePath, MerkleTreeDigest};
+use snarkos_errors::dpc::DPCError;
+use snarkos_models::algorithms::{CommitmentScheme, EncryptionScheme, MerkleParameters, SignatureScheme, CRH};
+use snarkos_objects::{AccountProofKey, AleoAmount};
+use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
+
+use std::io::{Read, Result as IoResult, Write};
+
+/// Returned by `DPC::authorize`. Stores the signed contents of a transaction, along with the
+/// private inputs of its proofs, so the transaction can be proven by `DPC::execute_authorized`
+/// without the private keys of the old records. The ledger digest is fixed by the signatures,
+/// so the transaction must be proven before the digest falls out of the ledger.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: BaseDPCComponents"))]
+pub struct TransactionAuthorization<C: BaseDPCComponents> {
+    // Old record stuff
+    pub old_records: Vec<DPCRecord<C>>,
+    pub old_account_proof_keys: Vec<AccountProofKey<C>>,
+    pub old_serial_numbers: Vec<<C::AccountSignature as SignatureScheme>::PublicKey>,
+    pub old_witness_paths: Vec<
+        Vec<(
+            MerkleTreeDigest<C::MerkleParameters>,
+            MerkleTreeDigest<C::MerkleParameters>,
+        )>,
+    >,
+    pub signatures: Vec<<C::AccountSignature as SignatureScheme>::Output>,
+
+    // New record stuff
+    pub new_records: Vec<DPCRecord<C>>,
+    pub new_sn_nonce_randomness: Vec<[u8; 32]>,
+    pub new_commitments: Vec<<C::RecordCommitment as CommitmentScheme>::Output>,
+
+    pub new_records_encryption_randomness: Vec<<C::AccountEncryption as EncryptionScheme>::Randomness>,
+    pub new_encrypted_records: Vec<EncryptedRecord<C>>,
+    pub new_encrypted_record_hashes: Vec<<C::EncryptedRecordCRH as CRH>::Output>,
+
+    // Program and local data randomness
+    pub program_commitment: <C::ProgramVerificationKeyCommitment as CommitmentScheme>::Output,
+    pub program_randomness: <C::ProgramVerificationKeyCommitment as CommitmentScheme>::Randomness,
+    pub local_data_commitment_randomizers: Vec<<C::LocalDataCommitment as CommitmentScheme>::Randomness>,
+
+    pub ledger_digest: MerkleTreeDigest<C::MerkleParameters>,
+    pub value_balance: AleoAmount,
+    pub memorandum: [u8; 32],
+    pub network_id: u8,
+}
+
+impl<C: BaseDPCComponents> TransactionAuthorization<C> {
+    /// Returns the local data of the transaction, to construct the program proofs of its records.
+    pub fn to_local_data(&self, system_parameters: &SystemParameters<C>) -> Result<LocalData<C>, DPCError> {
+        let local_data_merkle_tree = DPC::<C>::generate_local_data_merkle_tree(
+            system_parameters,
+            &self.old_records,
+            &self.old_serial_numbers,
+            &self.new_records,
+            &self.local_data_commitment_randomizers,
+            &self.memorandum,
+            self.network_id,
+        )?;
+
+        Ok(LocalData {
+            system_parameters: system_parameters.clone(),
+
+            old_records: self.old_records.clone(),
+            old_serial_numbers: self.old_serial_numbers.clone(),
+
+            new_records: self.new_records.clone(),
+
+            local_data_merkle_tree,
+            local_data_commitment_randomizers: self.local_data_commitment_randomizers.clone(),
+
+            memorandum: self.memorandum,
+            network_id: self.network_id,
+        })
+    }
+
+    /// Returns the ledger membership witnesses of the old records against the given ledger parameters.
+    pub fn old_witnesses(&self, ledger_parameters: &C::MerkleParameters) -> Vec<MerklePath<C::MerkleParameters>> {
+        self.old_witness_paths
+            .iter()
+            .map(|path| MerklePath {
+                parameters: ledger_parameters.clone(),
+                path: path.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the message signed by the old account private keys, given the local data root.
+    pub(crate) fn signature_message(&self, local_data_root: &<C::LocalDataCRH as CRH>::Output) -> IoResult<Vec<u8>> {
+        to_bytes![
+            self.network_id,
+            self.ledger_digest,
+            self.old_serial_numbers,
+            self.new_commitments,
+            self.program_commitment,
+            local_data_root,
+            self.value_balance,
+            self.memorandum
+        ]
+    }
+}
+
+/// Returned by `DPC::authorize_pending`. Stores a transaction authorization whose old records spent
+/// with a proof key are not signed yet. Their owners sign the signature message, after checking the
+/// contents of the authorization, and their signatures are randomized into signatures under the
+/// serial numbers of the records. A threshold account signs with `SchnorrSignature::sign_share`,
+/// whose aggregated signature is an ordinary signature under the signature public key of the account.
+pub struct PendingAuthorization<C: BaseDPCComponents> {
+    system_parameters: SystemParameters<C>,
+    authorization: TransactionAuthorization<C>,
+    signature_message: Vec<u8>,
+    old_randomizers: Vec<Vec<u8>>,
+    signatures: Vec<Option<<C::AccountSignature as SignatureScheme>::Output>>,
+}
+
+impl<C: BaseDPCComponents> PendingAuthorization<C> {
+    /// Creates a pending authorization without signatures.
+    pub(crate) fn new(
+        system_parameters: SystemParameters<C>,
+        authorization: TransactionAuthorization<C>,
+        signature_message: Vec<u8>,
+        old_randomizers: Vec<Vec<u8>>,
+    ) -> Self {
+        Self {
+            system_parameters,
+            authorization,
+            signature_message,
+            old_randomizers,
+            signatures: vec![None; C::NUM_INPUT_RECORDS],
+        }
+    }
+
+    /// Returns the authorization, whose signatures are only inserted once they are all known.
+    pub fn authorization(&self) -> &TransactionAuthorization<C> {
+        &self.authorization
+    }
+
+    /// Returns the message signed by the owners of the old records.
+    pub fn signature_message(&self) -> &[u8] {
+        &self.signature_message
+    }
+
+    /// Returns the positions of the old records whose signatures are missing.
+    pub fn missing_signatures(&self) -> Vec<usize> {
+        self.signatures
+            .iter()
+            .enumerate()
+            .filter(|(_, signature)| signature.is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Randomizes the signature of the owner of the old record at the given position, under the
+    /// signature public key of its account, and inserts it if it signs for the serial number.
+    pub fn insert_signature(
+        &mut self,
+        position: usize,
+        signature: &<C::AccountSignature as SignatureScheme>::Output,
+    ) -> Result<(), DPCError> {
+        if position >= C::NUM_INPUT_RECORDS {
+            return Err(DPCError::Message(format!(
+                "invalid old record position {}, expected less than {}",
+                position,
+                C::NUM_INPUT_RECORDS
+            )));
+        }
+
+        let randomized_signature = C::AccountSignature::randomize_signature(
+            &self.system_parameters.account_signature,
+            signature,
+            &self.old_randomizers[position],
+        )?;
+
+        if !C::AccountSignature::verify(
+            &self.system_parameters.account_signature,
+            &self.authorization.old_serial_numbers[position],
+            &self.signature_message,
+            &randomized_signature,
+        )? {
+            return Err(DPCError::InvalidAuthorization);
+        }
+
+        self.signatures[position] = Some(randomized_signature);
+
+        Ok(())
+    }
+
+    /// Returns the authorization with the signatures of all of its old records.
+    pub fn into_authorization(self) -> Result<TransactionAuthorization<C>, DPCError> {
+        let mut authorization = self.authorization;
+
+        for (i, signature) in self.signatures.into_iter().enumerate() {
+            match signature {
+                Some(signature) => authorization.signatures.push(signature),
+                None => return Err(DPCError::MissingSignature(i)),
+            }
+        }
+
+        Ok(authorization)
+    }
+}
+
+impl<C: BaseDPCComponents> ToBytes for TransactionAuthorization<C> {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        for old_record in &self.old_records {
+            old_record.write(&mut writer)?;
+        }
+
+        for old_account_proof_key in &self.old_account_proof_keys {
+            old_account_proof_key.write(&mut writer)?;
+        }
+
+        for old_serial_number in &self.old_serial_numbers {
+            old_serial_number.write(&mut writer)?;
+        }
+
+        for old_witness_path in &self.old_witness_paths {
+            for (left, right) in old_witness_path {
+                left.write(&mut writer)?;
+                right.write(&mut writer)?;
+            }
+        }
+
+        for signature in &self.signatures {
+            signature.write(&mut writer)?;
+        }
+
+        for new_record in &self.new_records {
+            new_record.write(&mut writer)?;
+        }
+
+        for sn_nonce_randomness in &self.new_sn_nonce_randomness {
+            sn_nonce_randomness.write(&mut writer)?;
+        }
+
+        for new_commitment in &self.new_commitments {
+            new_commitment.write(&mut writer)?;
+        }
+
+        for encryption_randomness in &self.new_records_encryption_randomness {
+            encryption_randomness.write(&mut writer)?;
+        }
+
+        for encrypted_record in &self.new_encrypted_records {
+            encrypted_record.write(&mut writer)?;
+        }
+
+        for encrypted_record_hash in &self.new_encrypted_record_hashes {
+            encrypted_record_hash.write(&mut writer)?;
+        }
+
+        self.program_commitment.write(&mut writer)?;
+        self.program_randomness.write(&mut writer)?;
+
+        for randomizer in &self.local_data_commitment_randomizers {
+            randomizer.write(&mut writer)?;
+        }
+
+        self.ledger_digest.write(&mut writer)?;
+        self.value_balance.write(&mut writer)?;
+        self.memorandum.write(&mut writer)?;
+        self.network_id.write(&mut writer)
+    }
+}
+
+impl<C: BaseDPCComponents> FromBytes for TransactionAuthorization<C> {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let num_input_records = C::NUM_INPUT_RECORDS;
+        let num_output_records = C::NUM_OUTPUT_RECORDS;
+
+        let mut old_records = Vec::with_capacity(num_input_records);
+        for _ in 0..num_input_records {
+            old_records.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut old_account_proof_keys = Vec::with_capacity(num_input_records);
+        for _ in 0..num_input_records {
+            old_account_proof_keys.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut old_serial_numbers = Vec::with_capacity(num_input_records);
+        for _ in 0..num_input_records {
+            old_serial_numbers.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut old_witness_paths = Vec::with_capacity(num_input_records);
+        for _ in 0..num_input_records {
+            let mut path = Vec::with_capacity(C::MerkleParameters::DEPTH);
+            for _ in 0..C::MerkleParameters::DEPTH {
+                let left: MerkleTreeDigest<C::MerkleParameters> = FromBytes::read(&mut reader)?;
+                let right: MerkleTreeDigest<C::MerkleParameters> = FromBytes::read(&mut reader)?;
+                path.push((left, right));
+            }
+            old_witness_paths.push(path);
+        }
+
+        let mut signatures = Vec::with_capacity(num_input_records);
+        for _ in 0..num_input_records {
+            signatures.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_records = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_records.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_sn_nonce_randomness = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_sn_nonce_randomness.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_commitments = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_commitments.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_records_encryption_randomness = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_records_encryption_randomness.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_encrypted_records = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_encrypted_records.push(FromBytes::read(&mut reader)?);
+        }
+
+        let mut new_encrypted_record_hashes = Vec::with_capacity(num_output_records);
+        for _ in 0..num_output_records {
+            new_encrypted_record_hashes.push(FromBytes::read(&mut reader)?);
+        }
+
+        let program_commitment = FromBytes::read(&mut reader)?;
+        let program_randomness = FromBytes::read(&mut reader)?;
+
+        let mut local_data_commitment_randomizers = Vec::with_capacity(num_input_records + num_output_records);
+        for _ in 0..(num_input_records + num_output_records) {
+            local_data_commitment_randomizers.push(FromBytes::read(&mut reader)?);
+        }
+
+        let ledger_digest = FromBytes::read(&mut reader)?;
+        let value_balance = FromBytes::read(&mut reader)?;
+        let memorandum = FromBytes::read(&mut reader)?;
+        let network_id = FromBytes::read(&mut reader)?;
+
+        Ok(Self {
+            old_records,
+            old_account_proof_keys,
+            old_serial_numbers,
+            old_witness_paths,
+            signatures,
+
+            new_records,
+            new_sn_nonce_randomness,
+            new_commitments,
+
+            new_records_encryption_randomness,
+            new_encrypted_records,
+            new_encrypted_record_hashes,
+
+            program_commitment,
+            program_randomness,
+            local_data_commitment_randomizers,
+
+            ledger_digest,
+            value_balance,
+            memorandum,
+            network_id,
+        })
+    }
+}
@@ -27,7 +27,7 @@ use snarkos_models::{
     algorithms::{CommitmentScheme, EncryptionScheme, SignatureScheme, CRH},
     gadgets::r1cs::{ConstraintSynthesizer, ConstraintSystem},
 };
-use snarkos_objects::{AccountPrivateKey, AleoAmount};
+use snarkos_objects::{AccountProofKey, AleoAmount};
 
 #[derive(Derivative)]
 #[derivative(Clone(bound = "C: BaseDPCComponents"))]
@@ -41,7 +41,7 @@ pub struct InnerCircuit<C: BaseDPCComponents> {
     // Inputs for old records.
     old_records: Vec<DPCRecord<C>>,
     old_witnesses: Vec<MerklePath<C::MerkleParameters>>,
-    old_account_private_keys: Vec<AccountPrivateKey<C>>,
+    old_account_proof_keys: Vec<AccountProofKey<C>>,
     old_serial_numbers: Vec<<C::AccountSignature as SignatureScheme>::PublicKey>,
 
     // Inputs for new records.
@@ -78,7 +78,7 @@ impl<C: BaseDPCComponents> InnerCircuit<C> {
             vec![<C::AccountSignature as SignatureScheme>::PublicKey::default(); num_input_records];
         let old_records = vec![DPCRecord::default(); num_input_records];
         let old_witnesses = vec![MerklePath::default(); num_input_records];
-        let old_account_private_keys = vec![AccountPrivateKey::default(); num_input_records];
+        let old_account_proof_keys = vec![AccountProofKey::default(); num_input_records];
 
         let new_commitments = vec![<C::RecordCommitment as CommitmentScheme>::Output::default(); num_output_records];
         let new_serial_number_nonce_randomness = vec![[0u8; 32]; num_output_records];
@@ -118,7 +118,7 @@ impl<C: BaseDPCComponents> InnerCircuit<C> {
             // Input records
             old_records,
             old_witnesses,
-            old_account_private_keys,
+            old_account_proof_keys,
             old_serial_numbers,
 
             // Output records
@@ -153,7 +153,7 @@ impl<C: BaseDPCComponents> InnerCircuit<C> {
         // Old records
         old_records: Vec<DPCRecord<C>>,
         old_witnesses: Vec<MerklePath<C::MerkleParameters>>,
-        old_account_private_keys: Vec<AccountPrivateKey<C>>,
+        old_account_proof_keys: Vec<AccountProofKey<C>>,
         old_serial_numbers: Vec<<C::AccountSignature as SignatureScheme>::PublicKey>,
 
         // New records
@@ -183,7 +183,7 @@ impl<C: BaseDPCComponents> InnerCircuit<C> {
 
         assert_eq!(num_input_records, old_records.len());
         assert_eq!(num_input_records, old_witnesses.len());
-        assert_eq!(num_input_records, old_account_private_keys.len());
+        assert_eq!(num_input_records, old_account_proof_keys.len());
         assert_eq!(num_input_records, old_serial_numbers.len());
 
         assert_eq!(num_output_records, new_records.len());
@@ -219,7 +219,7 @@ impl<C: BaseDPCComponents> InnerCircuit<C> {
             // Input records
             old_records,
             old_witnesses,
-            old_account_private_keys,
+            old_account_proof_keys,
             old_serial_numbers,
 
             // Output records
@@ -255,7 +255,7 @@ impl<C: BaseDPCComponents> ConstraintSynthesizer<C::InnerField> for InnerCircuit
             // Old records
             &self.old_records,
             &self.old_witnesses,
-            &self.old_account_private_keys,
+            &self.old_account_proof_keys,
             &self.old_serial_numbers,
             // New records
             &self.new_records,
@@ -52,7 +52,7 @@ use snarkos_models::{
         },
     },
 };
-use snarkos_objects::{AccountPrivateKey, AleoAmount};
+use snarkos_objects::{AccountProofKey, AleoAmount};
 use snarkos_utilities::{
     bits_to_bytes,
     bytes::{FromBytes, ToBytes},
@@ -75,7 +75,7 @@ pub fn execute_inner_proof_gadget<C: BaseDPCComponents, CS: ConstraintSystem<C::
     // Old record stuff
     old_records: &[DPCRecord<C>],
     old_witnesses: &[MerklePath<C::MerkleParameters>],
-    old_account_private_keys: &[AccountPrivateKey<C>],
+    old_account_proof_keys: &[AccountProofKey<C>],
     old_serial_numbers: &[<C::AccountSignature as SignatureScheme>::PublicKey],
 
     // New record stuff
@@ -127,7 +127,7 @@ pub fn execute_inner_proof_gadget<C: BaseDPCComponents, CS: ConstraintSystem<C::
         //
         old_records,
         old_witnesses,
-        old_account_private_keys,
+        old_account_proof_keys,
         old_serial_numbers,
         //
         new_records,
@@ -182,7 +182,7 @@ fn base_dpc_execute_gadget_helper<
     //
     old_records: &[DPCRecord<C>],
     old_witnesses: &[MerklePath<C::MerkleParameters>],
-    old_account_private_keys: &[AccountPrivateKey<C>],
+    old_account_proof_keys: &[AccountProofKey<C>],
     old_serial_numbers: &[AccountSignature::PublicKey],
 
     //
@@ -353,10 +353,10 @@ where
     let mut old_record_commitments_gadgets = Vec::with_capacity(old_records.len());
     let mut old_death_program_ids_gadgets = Vec::with_capacity(old_records.len());
 
-    for (i, (((record, witness), account_private_key), given_serial_number)) in old_records
+    for (i, (((record, witness), account_proof_key), given_serial_number)) in old_records
         .iter()
         .zip(old_witnesses)
-        .zip(old_account_private_keys)
+        .zip(old_account_proof_keys)
         .zip(old_serial_numbers)
         .enumerate()
     {
@@ -463,19 +463,16 @@ where
             // Declare variables for account contents.
             let account_cs = &mut cs.ns(|| "Check account");
 
-            // Allocate the account private key.
+            // Allocate the account proof key.
             let (pk_sig, sk_prf, r_pk) = {
-                let pk_sig_native = account_private_key
-                    .pk_sig(&system_parameters.account_signature)
-                    .map_err(|_| SynthesisError::AssignmentMissing)?;
                 let pk_sig =
                     AccountSignatureGadget::PublicKeyGadget::alloc(&mut account_cs.ns(|| "Declare pk_sig"), || {
-                        Ok(&pk_sig_native)
+                        Ok(&account_proof_key.pk_sig)
                     })?;
-                let sk_prf = PGadget::new_seed(&mut account_cs.ns(|| "Declare sk_prf"), &account_private_key.sk_prf);
+                let sk_prf = PGadget::new_seed(&mut account_cs.ns(|| "Declare sk_prf"), &account_proof_key.sk_prf);
                 let r_pk =
                     AccountCommitmentGadget::RandomnessGadget::alloc(&mut account_cs.ns(|| "Declare r_pk"), || {
-                        Ok(&account_private_key.r_pk)
+                        Ok(&account_proof_key.r_pk)
                     })?;
 
                 (pk_sig, sk_prf, r_pk)
@@ -505,15 +502,12 @@ where
                 // commitment contains the same bit-value as the scalar field element
                 // computed from the given account private key.
                 let given_account_view_key = {
-                    // Derive the given account view key based on the given account private key.
+                    // Derive the given account view key based on the given account proof key.
                     let given_account_view_key = AccountEncryptionGadget::PrivateKeyGadget::alloc(
                         &mut account_cs.ns(|| "Allocate account view key"),
                         || {
-                            Ok(account_private_key
-                                .to_decryption_key(
-                                    &system_parameters.account_signature,
-                                    &system_parameters.account_commitment,
-                                )
+                            Ok(account_proof_key
+                                .to_decryption_key(&system_parameters.account_commitment)
                                 .map_err(|_| SynthesisError::AssignmentMissing)?)
                         },
                     )?;
@@ -0,0 +1,298 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! The JSON representations of the transactions, records and encrypted records.
+//!
+//! Every field holding a cryptographic value is the lowercase hex of its encoding in the binary
+//! format, so the JSON and the binary formats encode the same values:
+//!
+//! | Transaction field    | Encoding                                                                      |
+//! |:-------------------- |:----------------------------------------------------------------------------- |
+//! | `network`            | The network id, as a number                                                   |
+//! | `ledger_digest`      | The hex of the ledger digest                                                  |
+//! | `old_serial_numbers` | The hex of the compressed serial numbers, one per input record                |
+//! | `new_commitments`    | The hex of the record commitments, one per output record                      |
+//! | `program_commitment` | The hex of the commitment to the program verification keys                    |
+//! | `local_data_root`    | The hex of the local data root                                                |
+//! | `value_balance`      | The value balance, as a number                                                |
+//! | `signatures`         | The hex of the signatures, one per input record                               |
+//! | `encrypted_records`  | The encrypted records, one per output record                                  |
+//! | `transaction_proof`  | The hex of the outer proof                                                    |
+//! | `memorandum`         | The hex of the 32 byte memorandum                                             |
+//! | `inner_snark_id`     | The hex of the id of the inner snark verification key                         |
+//!
+//! An encrypted record is the hex of its binary encoding: the number of ciphertext elements as a
+//! variable length integer, the x-coordinates of the elements, and their packed selector bits.
+//!
+//! A record has an `owner` address string, an `is_dummy` boolean, a `value` number, and the hex of its
+//! `payload`, `birth_program_id`, `death_program_id`, `serial_number_nonce`, `commitment` and
+//! `commitment_randomness`.
+//!
+//! The decoding is strict, so that every value has a single JSON representation: the unknown fields are
+//! rejected, the hex strings must be lowercase and encode exactly one value, the addresses must be in
+//! their canonical form, and a transaction must hold one value per input or output record.
+
+use crate::base_dpc::{
+    record::{encrypted_record::EncryptedRecord, record_payload::RecordPayload, DPCRecord},
+    transaction::DPCTransaction,
+    BaseDPCComponents,
+};
+use snarkos_objects::{AccountAddress, AleoAmount, Network};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    serialize::{CanonicalDeserialize, CanonicalSerialize},
+    to_bytes,
+};
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::{marker::PhantomData, str::FromStr};
+
+/// The JSON representation of a transaction.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionJson {
+    network: u8,
+    ledger_digest: String,
+    old_serial_numbers: Vec<String>,
+    new_commitments: Vec<String>,
+    program_commitment: String,
+    local_data_root: String,
+    value_balance: i64,
+    signatures: Vec<String>,
+    encrypted_records: Vec<String>,
+    transaction_proof: String,
+    memorandum: String,
+    inner_snark_id: String,
+}
+
+/// The JSON representation of a record.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecordJson {
+    owner: String,
+    is_dummy: bool,
+    value: u64,
+    payload: String,
+    birth_program_id: String,
+    death_program_id: String,
+    serial_number_nonce: String,
+    commitment: String,
+    commitment_randomness: String,
+}
+
+/// Returns the hex of the binary encoding of the value.
+fn encode<T: ToBytes>(value: &T) -> Result<String, String> {
+    Ok(hex::encode(to_bytes![value].map_err(|error| error.to_string())?))
+}
+
+/// Returns the hex of the compressed encoding of the value.
+fn encode_compressed<T: CanonicalSerialize>(value: &T) -> Result<String, String> {
+    let mut bytes = vec![];
+    value.serialize(&mut bytes).map_err(|error| error.to_string())?;
+
+    Ok(hex::encode(bytes))
+}
+
+/// Reads in a value from the hex of its binary encoding, which must encode exactly the value.
+fn decode<T: FromBytes + ToBytes>(field: &str, encoded: &str) -> Result<T, String> {
+    let bytes = hex::decode(encoded).map_err(|error| format!("invalid {}: {}", field, error))?;
+
+    let mut reader = &bytes[..];
+    let value = T::read(&mut reader).map_err(|error| format!("invalid {}: {}", field, error))?;
+
+    if !reader.is_empty() || encode(&value)? != encoded {
+        return Err(format!("non-canonical {}: {}", field, encoded));
+    }
+
+    Ok(value)
+}
+
+/// Reads in a value from the hex of its compressed encoding, which must encode exactly the value.
+fn decode_compressed<T: CanonicalSerialize + CanonicalDeserialize>(field: &str, encoded: &str) -> Result<T, String> {
+    let bytes = hex::decode(encoded).map_err(|error| format!("invalid {}: {}", field, error))?;
+
+    let mut reader = &bytes[..];
+    let value = T::deserialize(&mut reader).map_err(|error| format!("invalid {}: {}", field, error))?;
+
+    if !reader.is_empty() || encode_compressed(&value)? != encoded {
+        return Err(format!("non-canonical {}: {}", field, encoded));
+    }
+
+    Ok(value)
+}
+
+/// Reads in the values of a field holding one value per input or output record.
+fn decode_all<T>(
+    field: &str,
+    encoded: &[String],
+    expected_len: usize,
+    decode: impl Fn(&str, &str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    if encoded.len() != expected_len {
+        return Err(format!(
+            "invalid {}: {} values, expected {}",
+            field,
+            encoded.len(),
+            expected_len
+        ));
+    }
+
+    encoded.iter().map(|value| decode(field, value)).collect()
+}
+
+impl<C: BaseDPCComponents> Serialize for DPCTransaction<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TransactionJson {
+            network: self.network.id(),
+            ledger_digest: encode(&self.ledger_digest).map_err(ser::Error::custom)?,
+            old_serial_numbers: self
+                .old_serial_numbers
+                .iter()
+                .map(encode_compressed)
+                .collect::<Result<_, _>>()
+                .map_err(ser::Error::custom)?,
+            new_commitments: self
+                .new_commitments
+                .iter()
+                .map(encode)
+                .collect::<Result<_, _>>()
+                .map_err(ser::Error::custom)?,
+            program_commitment: encode(&self.program_commitment).map_err(ser::Error::custom)?,
+            local_data_root: encode(&self.local_data_root).map_err(ser::Error::custom)?,
+            value_balance: self.value_balance.0,
+            signatures: self
+                .signatures
+                .iter()
+                .map(encode)
+                .collect::<Result<_, _>>()
+                .map_err(ser::Error::custom)?,
+            encrypted_records: self
+                .encrypted_records
+                .iter()
+                .map(encode)
+                .collect::<Result<_, _>>()
+                .map_err(ser::Error::custom)?,
+            transaction_proof: encode(&self.transaction_proof).map_err(ser::Error::custom)?,
+            memorandum: hex::encode(self.memorandum),
+            inner_snark_id: encode(&self.inner_snark_id).map_err(ser::Error::custom)?,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C: BaseDPCComponents> Deserialize<'de> for DPCTransaction<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = TransactionJson::deserialize(deserializer)?;
+
+        let transaction = || -> Result<Self, String> {
+            Ok(Self {
+                network: Network::from_network_id(json.network),
+                ledger_digest: decode("ledger_digest", &json.ledger_digest)?,
+                old_serial_numbers: decode_all(
+                    "old_serial_numbers",
+                    &json.old_serial_numbers,
+                    C::NUM_INPUT_RECORDS,
+                    decode_compressed,
+                )?,
+                new_commitments: decode_all("new_commitments", &json.new_commitments, C::NUM_OUTPUT_RECORDS, decode)?,
+                program_commitment: decode("program_commitment", &json.program_commitment)?,
+                local_data_root: decode("local_data_root", &json.local_data_root)?,
+                value_balance: AleoAmount(json.value_balance),
+                signatures: decode_all("signatures", &json.signatures, C::NUM_INPUT_RECORDS, decode)?,
+                encrypted_records: decode_all(
+                    "encrypted_records",
+                    &json.encrypted_records,
+                    C::NUM_OUTPUT_RECORDS,
+                    decode,
+                )?,
+                transaction_proof: decode("transaction_proof", &json.transaction_proof)?,
+                memorandum: decode("memorandum", &json.memorandum)?,
+                inner_snark_id: decode("inner_snark_id", &json.inner_snark_id)?,
+            })
+        };
+
+        transaction().map_err(de::Error::custom)
+    }
+}
+
+impl<C: BaseDPCComponents> Serialize for EncryptedRecord<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        encode(self).map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de, C: BaseDPCComponents> Deserialize<'de> for EncryptedRecord<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+
+        decode("encrypted record", &encoded).map_err(de::Error::custom)
+    }
+}
+
+impl<C: BaseDPCComponents> Serialize for DPCRecord<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RecordJson {
+            owner: self.owner.to_string(),
+            is_dummy: self.is_dummy,
+            value: self.value,
+            payload: hex::encode(self.payload.to_bytes()),
+            birth_program_id: hex::encode(&self.birth_program_id),
+            death_program_id: hex::encode(&self.death_program_id),
+            serial_number_nonce: encode(&self.serial_number_nonce).map_err(ser::Error::custom)?,
+            commitment: encode(&self.commitment).map_err(ser::Error::custom)?,
+            commitment_randomness: encode(&self.commitment_randomness).map_err(ser::Error::custom)?,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C: BaseDPCComponents> Deserialize<'de> for DPCRecord<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = RecordJson::deserialize(deserializer)?;
+
+        let record = || -> Result<Self, String> {
+            let owner = AccountAddress::from_str(&json.owner).map_err(|error| error.to_string())?;
+            if owner.to_string() != json.owner {
+                return Err(format!("non-canonical owner: {}", json.owner));
+            }
+
+            Ok(Self {
+                owner,
+                is_dummy: json.is_dummy,
+                value: json.value,
+                payload: decode::<RecordPayload>("payload", &json.payload)?,
+                birth_program_id: decode_program_id("birth_program_id", &json.birth_program_id)?,
+                death_program_id: decode_program_id("death_program_id", &json.death_program_id)?,
+                serial_number_nonce: decode("serial_number_nonce", &json.serial_number_nonce)?,
+                commitment: decode("commitment", &json.commitment)?,
+                commitment_randomness: decode("commitment_randomness", &json.commitment_randomness)?,
+                _components: PhantomData,
+            })
+        };
+
+        record().map_err(de::Error::custom)
+    }
+}
+
+/// Reads in a program id from its lowercase hex.
+fn decode_program_id(field: &str, encoded: &str) -> Result<Vec<u8>, String> {
+    let program_id = hex::decode(encoded).map_err(|error| format!("invalid {}: {}", field, error))?;
+    if hex::encode(&program_id) != encoded {
+        return Err(format!("non-canonical {}: {}", field, encoded));
+    }
+
+    Ok(program_id)
+}
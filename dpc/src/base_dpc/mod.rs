@@ -36,7 +36,7 @@ use snarkos_models::{
     gadgets::algorithms::{CRHGadget, SNARKVerifierGadget},
     objects::{AccountScheme, LedgerScheme, Transaction},
 };
-use snarkos_objects::{Account, AccountAddress, AccountPrivateKey, AleoAmount, Network};
+use snarkos_objects::{Account, AccountAddress, AccountPrivateKey, AccountProofKey, AleoAmount, Network};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
     has_duplicates,
@@ -48,9 +48,14 @@ use itertools::{izip, Itertools};
 use rand::Rng;
 use std::marker::PhantomData;
 
+pub mod authorization;
+pub use authorization::*;
+
 pub mod inner_circuit;
 pub use inner_circuit::*;
 
+pub mod json;
+
 pub mod outer_circuit;
 pub use outer_circuit::*;
 
@@ -60,6 +65,9 @@ pub use parameters::*;
 pub mod program;
 pub use program::*;
 
+pub mod proving_key;
+pub use proving_key::*;
+
 pub mod record;
 pub use record::*;
 
@@ -128,7 +136,8 @@ pub struct ExecuteContext<Components: BaseDPCComponents> {
     system_parameters: SystemParameters<Components>,
 
     // Old record stuff
-    old_account_private_keys: Vec<AccountPrivateKey<Components>>,
+    old_account_proof_keys: Vec<AccountProofKey<Components>>,
+    old_account_signature_keys: Vec<Option<<Components::AccountSignature as SignatureScheme>::PrivateKey>>,
     old_records: Vec<DPCRecord<Components>>,
     old_serial_numbers: Vec<<Components::AccountSignature as SignatureScheme>::PublicKey>,
     old_randomizers: Vec<Vec<u8>>,
@@ -172,6 +181,46 @@ impl<Components: BaseDPCComponents> ExecuteContext<Components> {
             network_id: self.network_id,
         }
     }
+
+    /// Returns the new records of the transaction.
+    pub fn new_records(&self) -> &[DPCRecord<Components>] {
+        &self.new_records
+    }
+
+    /// Replaces the memorandum of the transaction, which is only known once its new records are
+    /// generated when it is derived from them, and recomputes the local data commitments.
+    pub fn set_memorandum(
+        &mut self,
+        memorandum: <DPCTransaction<Components> as Transaction>::Memorandum,
+    ) -> Result<(), DPCError> {
+        self.local_data_merkle_tree = DPC::<Components>::generate_local_data_merkle_tree(
+            &self.system_parameters,
+            &self.old_records,
+            &self.old_serial_numbers,
+            &self.new_records,
+            &self.local_data_commitment_randomizers,
+            &memorandum,
+            self.network_id,
+        )?;
+        self.memorandum = memorandum;
+
+        Ok(())
+    }
+}
+
+/// The key spending an old record of a transaction.
+///
+/// The inner circuit proves the spending of a record with the proof key of its owner, while the signature
+/// of the transaction under the signature public key of the owner is checked outside of the circuit. A record
+/// may hence be spent with the proof key alone, and signed by other parties, such as the signers of a threshold
+/// account, whose signature private key is shared with `SchnorrSignature::split_private_key`.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "Components: BaseDPCComponents"))]
+pub enum SpendingKey<Components: BaseDPCComponents> {
+    /// The account private key of the owner, which signs the transaction
+    PrivateKey(AccountPrivateKey<Components>),
+    /// The proof key of the owner, whose signature is inserted in the pending authorization
+    ProofKey(AccountProofKey<Components>),
 }
 
 /// Stores local data required to produce program proofs.
@@ -273,21 +322,55 @@ impl<Components: BaseDPCComponents> DPC<Components> {
         record: &DPCRecord<Components>,
         account_private_key: &AccountPrivateKey<Components>,
     ) -> Result<(<Components::AccountSignature as SignatureScheme>::PublicKey, Vec<u8>), DPCError> {
-        let sn_time = start_timer!(|| "Generate serial number");
-        let sk_prf = &account_private_key.sk_prf;
-        let sn_nonce = to_bytes!(record.serial_number_nonce())?;
-        // Compute the serial number.
-        let prf_input = FromBytes::read(sn_nonce.as_slice())?;
+        let mut serial_numbers =
+            Self::generate_sns(system_parameters, std::slice::from_ref(record), account_private_key)?;
+
+        Ok(serial_numbers.remove(0))
+    }
+
+    /// Returns the serial numbers of the given records of an account, with their randomizers, in order.
+    /// The signature public key and the PRF seed of the account are derived once for all the records.
+    pub fn generate_sns(
+        system_parameters: &SystemParameters<Components>,
+        records: &[DPCRecord<Components>],
+        account_private_key: &AccountPrivateKey<Components>,
+    ) -> Result<Vec<(<Components::AccountSignature as SignatureScheme>::PublicKey, Vec<u8>)>, DPCError> {
+        let account_proof_key =
+            AccountProofKey::from_private_key(&system_parameters.account_signature, account_private_key)?;
+
+        Self::generate_sns_with_proof_key(system_parameters, records, &account_proof_key)
+    }
+
+    /// Returns the serial numbers of the given records of an account like `generate_sns`, given the
+    /// proof key of the account, which holds the signature public key and the PRF seed of the account.
+    pub fn generate_sns_with_proof_key(
+        system_parameters: &SystemParameters<Components>,
+        records: &[DPCRecord<Components>],
+        account_proof_key: &AccountProofKey<Components>,
+    ) -> Result<Vec<(<Components::AccountSignature as SignatureScheme>::PublicKey, Vec<u8>)>, DPCError> {
+        let sn_time = start_timer!(|| "Generate serial numbers");
+        let sk_prf = &account_proof_key.sk_prf;
         let prf_seed = FromBytes::read(to_bytes!(sk_prf)?.as_slice())?;
-        let sig_and_pk_randomizer = to_bytes![Components::PRF::evaluate(&prf_seed, &prf_input)?]?;
+        let pk_sig = &account_proof_key.pk_sig;
 
-        let sn = Components::AccountSignature::randomize_public_key(
-            &system_parameters.account_signature,
-            &account_private_key.pk_sig(&system_parameters.account_signature)?,
-            &sig_and_pk_randomizer,
-        )?;
+        let mut serial_numbers = Vec::with_capacity(records.len());
+        for record in records {
+            let sn_nonce = to_bytes!(record.serial_number_nonce())?;
+            // Compute the serial number.
+            let prf_input = FromBytes::read(sn_nonce.as_slice())?;
+            let sig_and_pk_randomizer = to_bytes![Components::PRF::evaluate(&prf_seed, &prf_input)?]?;
+
+            let sn = Components::AccountSignature::randomize_public_key(
+                &system_parameters.account_signature,
+                pk_sig,
+                &sig_and_pk_randomizer,
+            )?;
+
+            serial_numbers.push((sn, sig_and_pk_randomizer));
+        }
         end_timer!(sn_time);
-        Ok((sn, sig_and_pk_randomizer))
+
+        Ok(serial_numbers)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -338,119 +421,80 @@ impl<Components: BaseDPCComponents> DPC<Components> {
         end_timer!(record_time);
         Ok(record)
     }
-}
-
-impl<Components: BaseDPCComponents, L: LedgerScheme> DPCScheme<L> for DPC<Components>
-where
-    L: LedgerScheme<
-        Commitment = <Components::RecordCommitment as CommitmentScheme>::Output,
-        MerkleParameters = Components::MerkleParameters,
-        MerklePath = MerklePath<Components::MerkleParameters>,
-        MerkleTreeDigest = MerkleTreeDigest<Components::MerkleParameters>,
-        SerialNumber = <Components::AccountSignature as SignatureScheme>::PublicKey,
-        Transaction = DPCTransaction<Components>,
-    >,
-{
-    type Account = Account<Components>;
-    type ExecuteContext = ExecuteContext<Components>;
-    type LocalData = LocalData<Components>;
-    type Metadata = [u8; 32];
-    type Parameters = PublicParameters<Components>;
-    type Payload = <Self::Record as Record>::Payload;
-    type PrivateProgramInput = PrivateProgramInput;
-    type Record = DPCRecord<Components>;
-    type SystemParameters = SystemParameters<Components>;
-    type Transaction = DPCTransaction<Components>;
-
-    fn setup<R: Rng>(
-        ledger_parameters: &Components::MerkleParameters,
-        rng: &mut R,
-    ) -> Result<Self::Parameters, DPCError> {
-        let setup_time = start_timer!(|| "BaseDPC::setup");
-        let system_parameters = Self::generate_system_parameters(rng)?;
-
-        let program_snark_setup_time = start_timer!(|| "Dummy program SNARK setup");
-        let noop_program_snark_parameters = Self::generate_noop_program_snark_parameters(&system_parameters, rng)?;
-        let program_snark_proof = Components::NoopProgramSNARK::prove(
-            &noop_program_snark_parameters.proving_key,
-            &NoopCircuit::blank(&system_parameters),
-            rng,
-        )?;
-        end_timer!(program_snark_setup_time);
-
-        let program_snark_vk_and_proof = PrivateProgramInput {
-            verification_key: to_bytes![noop_program_snark_parameters.verification_key]?,
-            proof: to_bytes![program_snark_proof]?,
-        };
 
-        let snark_setup_time = start_timer!(|| "Execute inner SNARK setup");
-        let inner_circuit = InnerCircuit::blank(&system_parameters, ledger_parameters);
-        let inner_snark_parameters = Components::InnerSNARK::setup(&inner_circuit, rng)?;
-        end_timer!(snark_setup_time);
+    /// Returns the local data merkle tree of a transaction, given the randomizers of its leaves.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate_local_data_merkle_tree(
+        parameters: &SystemParameters<Components>,
+        old_records: &[DPCRecord<Components>],
+        old_serial_numbers: &[<Components::AccountSignature as SignatureScheme>::PublicKey],
+        new_records: &[DPCRecord<Components>],
+        local_data_commitment_randomizers: &[<Components::LocalDataCommitment as CommitmentScheme>::Randomness],
+        memorandum: &<DPCTransaction<Components> as Transaction>::Memorandum,
+        network_id: u8,
+    ) -> Result<CommitmentMerkleTree<Components::LocalDataCommitment, Components::LocalDataCRH>, DPCError> {
+        assert_eq!(
+            Components::NUM_INPUT_RECORDS + Components::NUM_OUTPUT_RECORDS,
+            local_data_commitment_randomizers.len()
+        );
 
-        let snark_setup_time = start_timer!(|| "Execute outer SNARK setup");
-        let inner_snark_vk: <Components::InnerSNARK as SNARK>::VerificationParameters =
-            inner_snark_parameters.1.clone().into();
-        let inner_snark_proof = Components::InnerSNARK::prove(&inner_snark_parameters.0, &inner_circuit, rng)?;
+        let mut old_record_commitments = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+        for i in 0..Components::NUM_INPUT_RECORDS {
+            let record = &old_records[i];
+            let input_bytes = to_bytes![old_serial_numbers[i], record.commitment(), memorandum, network_id]?;
 
-        let outer_snark_parameters = Components::OuterSNARK::setup(
-            &OuterCircuit::blank(
-                system_parameters.clone(),
-                ledger_parameters.clone(),
-                inner_snark_vk,
-                inner_snark_proof,
-                program_snark_vk_and_proof,
-            ),
-            rng,
-        )?;
-        end_timer!(snark_setup_time);
-        end_timer!(setup_time);
+            let commitment = Components::LocalDataCommitment::commit(
+                &parameters.local_data_commitment,
+                &input_bytes,
+                &local_data_commitment_randomizers[i],
+            )?;
 
-        let inner_snark_parameters = (Some(inner_snark_parameters.0), inner_snark_parameters.1);
-        let outer_snark_parameters = (Some(outer_snark_parameters.0), outer_snark_parameters.1);
+            old_record_commitments.push(commitment);
+        }
 
-        Ok(PublicParameters {
-            system_parameters,
-            noop_program_snark_parameters,
-            inner_snark_parameters,
-            outer_snark_parameters,
-        })
-    }
+        let mut new_record_commitments = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        for (j, record) in new_records.iter().take(Components::NUM_OUTPUT_RECORDS).enumerate() {
+            let input_bytes = to_bytes![record.commitment(), memorandum, network_id]?;
 
-    fn create_account<R: Rng>(parameters: &Self::Parameters, rng: &mut R) -> Result<Self::Account, DPCError> {
-        let time = start_timer!(|| "BaseDPC::create_account");
+            let commitment = Components::LocalDataCommitment::commit(
+                &parameters.local_data_commitment,
+                &input_bytes,
+                &local_data_commitment_randomizers[Components::NUM_INPUT_RECORDS + j],
+            )?;
 
-        let account_signature_parameters = &parameters.system_parameters.account_signature;
-        let commitment_parameters = &parameters.system_parameters.account_commitment;
-        let encryption_parameters = &parameters.system_parameters.account_encryption;
-        let account = Account::new(
-            account_signature_parameters,
-            commitment_parameters,
-            encryption_parameters,
-            rng,
-        )?;
+            new_record_commitments.push(commitment);
+        }
 
-        end_timer!(time);
+        let leaves = [
+            old_record_commitments[0].clone(),
+            old_record_commitments[1].clone(),
+            new_record_commitments[0].clone(),
+            new_record_commitments[1].clone(),
+        ];
 
-        Ok(account)
+        Ok(CommitmentMerkleTree::new(parameters.local_data_crh.clone(), &leaves)?)
     }
 
-    fn execute_offline<R: Rng>(
-        parameters: Self::SystemParameters,
-        old_records: Vec<Self::Record>,
-        old_account_private_keys: Vec<<Self::Account as AccountScheme>::AccountPrivateKey>,
-        new_record_owners: Vec<<Self::Account as AccountScheme>::AccountAddress>,
+    /// Creates the serial numbers of the old records and the new records of a transaction like `execute_offline`,
+    /// given the key spending each old record. The old records spent with a proof key are not signed by
+    /// `authorize_pending`, which leaves their signatures to their owners.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_offline_with_spending_keys<R: Rng>(
+        parameters: SystemParameters<Components>,
+        old_records: Vec<DPCRecord<Components>>,
+        old_spending_keys: Vec<SpendingKey<Components>>,
+        new_record_owners: Vec<AccountAddress<Components>>,
         new_is_dummy_flags: &[bool],
         new_values: &[u64],
-        new_payloads: Vec<Self::Payload>,
+        new_payloads: Vec<RecordPayload>,
         new_birth_program_ids: Vec<Vec<u8>>,
         new_death_program_ids: Vec<Vec<u8>>,
-        memorandum: <Self::Transaction as Transaction>::Memorandum,
+        memorandum: <DPCTransaction<Components> as Transaction>::Memorandum,
         network_id: u8,
         rng: &mut R,
-    ) -> Result<Self::ExecuteContext, DPCError> {
+    ) -> Result<ExecuteContext<Components>, DPCError> {
         assert_eq!(Components::NUM_INPUT_RECORDS, old_records.len());
-        assert_eq!(Components::NUM_INPUT_RECORDS, old_account_private_keys.len());
+        assert_eq!(Components::NUM_INPUT_RECORDS, old_spending_keys.len());
 
         assert_eq!(Components::NUM_OUTPUT_RECORDS, new_record_owners.len());
         assert_eq!(Components::NUM_OUTPUT_RECORDS, new_is_dummy_flags.len());
@@ -458,6 +502,24 @@ where
         assert_eq!(Components::NUM_OUTPUT_RECORDS, new_birth_program_ids.len());
         assert_eq!(Components::NUM_OUTPUT_RECORDS, new_death_program_ids.len());
 
+        let mut old_account_proof_keys = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+        let mut old_account_signature_keys = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+        for spending_key in old_spending_keys {
+            match spending_key {
+                SpendingKey::PrivateKey(private_key) => {
+                    old_account_proof_keys.push(AccountProofKey::from_private_key(
+                        &parameters.account_signature,
+                        &private_key,
+                    )?);
+                    old_account_signature_keys.push(Some(private_key.sk_sig));
+                }
+                SpendingKey::ProofKey(proof_key) => {
+                    old_account_proof_keys.push(proof_key);
+                    old_account_signature_keys.push(None);
+                }
+            }
+        }
+
         let mut old_serial_numbers = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
         let mut old_randomizers = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
         let mut joint_serial_numbers = Vec::new();
@@ -473,7 +535,12 @@ where
                 value_balance = value_balance.add(AleoAmount::from_bytes(record.value() as i64));
             }
 
-            let (sn, randomizer) = Self::generate_sn(&parameters, record, &old_account_private_keys[i])?;
+            let (sn, randomizer) = Self::generate_sns_with_proof_key(
+                &parameters,
+                std::slice::from_ref(record),
+                &old_account_proof_keys[i],
+            )?
+            .remove(0);
             joint_serial_numbers.extend_from_slice(&to_bytes![sn]?);
             old_serial_numbers.push(sn);
             old_randomizers.push(randomizer);
@@ -531,46 +598,20 @@ where
         // TODO (raychu86) Add index and program register inputs + outputs to local data commitment leaves
         let local_data_merkle_tree_timer = start_timer!(|| "Compute local data merkle tree");
 
-        let mut local_data_commitment_randomizers = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
-
-        let mut old_record_commitments = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
-        for i in 0..Components::NUM_INPUT_RECORDS {
-            let record = &old_records[i];
-            let input_bytes = to_bytes![old_serial_numbers[i], record.commitment(), memorandum, network_id]?;
-
-            let commitment_randomness = <Components::LocalDataCommitment as CommitmentScheme>::Randomness::rand(rng);
-            let commitment = Components::LocalDataCommitment::commit(
-                &parameters.local_data_commitment,
-                &input_bytes,
-                &commitment_randomness,
-            )?;
-
-            old_record_commitments.push(commitment);
-            local_data_commitment_randomizers.push(commitment_randomness);
-        }
-
-        let mut new_record_commitments = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
-        for record in new_records.iter().take(Components::NUM_OUTPUT_RECORDS) {
-            let input_bytes = to_bytes![record.commitment(), memorandum, network_id]?;
-
-            let commitment_randomness = <Components::LocalDataCommitment as CommitmentScheme>::Randomness::rand(rng);
-            let commitment = Components::LocalDataCommitment::commit(
-                &parameters.local_data_commitment,
-                &input_bytes,
-                &commitment_randomness,
-            )?;
-
-            new_record_commitments.push(commitment);
-            local_data_commitment_randomizers.push(commitment_randomness);
-        }
-
-        let leaves = [
-            old_record_commitments[0].clone(),
-            old_record_commitments[1].clone(),
-            new_record_commitments[0].clone(),
-            new_record_commitments[1].clone(),
-        ];
-        let local_data_merkle_tree = CommitmentMerkleTree::new(parameters.local_data_crh.clone(), &leaves)?;
+        let local_data_commitment_randomizers: Vec<_> = (0..Components::NUM_INPUT_RECORDS
+            + Components::NUM_OUTPUT_RECORDS)
+            .map(|_| <Components::LocalDataCommitment as CommitmentScheme>::Randomness::rand(rng))
+            .collect();
+
+        let local_data_merkle_tree = Self::generate_local_data_merkle_tree(
+            &parameters,
+            &old_records,
+            &old_serial_numbers,
+            &new_records,
+            &local_data_commitment_randomizers,
+            &memorandum,
+            network_id,
+        )?;
 
         end_timer!(local_data_merkle_tree_timer);
 
@@ -621,7 +662,8 @@ where
             system_parameters: parameters,
 
             old_records,
-            old_account_private_keys,
+            old_account_proof_keys,
+            old_account_signature_keys,
             old_serial_numbers,
             old_randomizers,
 
@@ -645,24 +687,65 @@ where
         Ok(context)
     }
 
-    fn execute_online<R: Rng>(
-        parameters: &Self::Parameters,
-        context: Self::ExecuteContext,
-        old_death_program_proofs: Vec<Self::PrivateProgramInput>,
-        new_birth_program_proofs: Vec<Self::PrivateProgramInput>,
-        ledger: &L,
+    /// Completes a transaction like `execute_online`, given the ledger digest and the ledger membership
+    /// witnesses of the old records instead of the ledger itself, so the transaction can be proven
+    /// offline with the witnesses fetched from a full node. The dummy records have default witnesses.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_online_with_witnesses<R: Rng>(
+        parameters: &PublicParameters<Components>,
+        context: ExecuteContext<Components>,
+        old_death_program_proofs: Vec<PrivateProgramInput>,
+        new_birth_program_proofs: Vec<PrivateProgramInput>,
+        ledger_parameters: &Components::MerkleParameters,
+        ledger_digest: MerkleTreeDigest<Components::MerkleParameters>,
+        old_witnesses: Vec<MerklePath<Components::MerkleParameters>>,
         rng: &mut R,
-    ) -> Result<(Vec<Self::Record>, Self::Transaction), DPCError> {
-        assert_eq!(Components::NUM_INPUT_RECORDS, old_death_program_proofs.len());
-        assert_eq!(Components::NUM_OUTPUT_RECORDS, new_birth_program_proofs.len());
+    ) -> Result<(Vec<DPCRecord<Components>>, DPCTransaction<Components>), DPCError> {
+        let authorization = Self::authorize(context, ledger_digest, old_witnesses, rng)?;
+
+        Self::execute_authorized(
+            parameters,
+            authorization,
+            old_death_program_proofs,
+            new_birth_program_proofs,
+            ledger_parameters,
+            rng,
+        )
+    }
+
+    /// Signs the contents of a transaction with the private keys of its old records, against the given
+    /// ledger digest and ledger membership witnesses. The returned authorization holds no private keys,
+    /// so the transaction can be proven by `execute_authorized` on another machine.
+    pub fn authorize<R: Rng>(
+        context: ExecuteContext<Components>,
+        ledger_digest: MerkleTreeDigest<Components::MerkleParameters>,
+        old_witnesses: Vec<MerklePath<Components::MerkleParameters>>,
+        rng: &mut R,
+    ) -> Result<TransactionAuthorization<Components>, DPCError> {
+        Self::authorize_pending(context, ledger_digest, old_witnesses, rng)?.into_authorization()
+    }
+
+    /// Signs the contents of a transaction like `authorize`, with the private keys of the old records
+    /// spent with a private key. The old records spent with a proof key are left unsigned, and the returned
+    /// authorization is completed with the signatures of their owners on its signature message.
+    pub fn authorize_pending<R: Rng>(
+        context: ExecuteContext<Components>,
+        ledger_digest: MerkleTreeDigest<Components::MerkleParameters>,
+        old_witnesses: Vec<MerklePath<Components::MerkleParameters>>,
+        rng: &mut R,
+    ) -> Result<PendingAuthorization<Components>, DPCError> {
+        assert_eq!(Components::NUM_INPUT_RECORDS, old_witnesses.len());
+
+        let authorize_time = start_timer!(|| "BaseDPC::authorize");
 
-        let exec_time = start_timer!(|| "BaseDPC::execute_online");
+        let local_data_root = context.local_data_merkle_tree.root();
 
         let ExecuteContext {
             system_parameters,
 
             old_records,
-            old_account_private_keys,
+            old_account_proof_keys,
+            old_account_signature_keys,
             old_serial_numbers,
             old_randomizers,
 
@@ -676,82 +759,149 @@ where
 
             program_commitment,
             program_randomness,
-            local_data_merkle_tree,
+            local_data_merkle_tree: _,
             local_data_commitment_randomizers,
             value_balance,
             memorandum,
             network_id,
         } = context;
 
-        let local_data_root = local_data_merkle_tree.root();
-
-        let old_death_program_attributes = old_death_program_proofs;
-        let new_birth_program_attributes = new_birth_program_proofs;
+        let authorization = TransactionAuthorization {
+            old_records,
+            old_account_proof_keys,
+            old_serial_numbers,
+            old_witness_paths: old_witnesses.into_iter().map(|witness| witness.path).collect(),
+            signatures: Vec::with_capacity(Components::NUM_INPUT_RECORDS),
 
-        // Construct the ledger witnesses
+            new_records,
+            new_sn_nonce_randomness,
+            new_commitments,
 
-        let ledger_digest = ledger.digest().expect("could not get digest");
+            new_records_encryption_randomness,
+            new_encrypted_records,
+            new_encrypted_record_hashes,
 
-        // Generate the ledger membership witnesses
-        let mut old_witnesses = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+            program_commitment,
+            program_randomness,
+            local_data_commitment_randomizers,
 
-        // Compute the ledger membership witness and serial number from the old records.
-        for record in old_records.iter() {
-            if record.is_dummy() {
-                old_witnesses.push(MerklePath::default());
-            } else {
-                let witness = ledger.prove_cm(&record.commitment())?;
-                old_witnesses.push(witness);
-            }
-        }
+            ledger_digest,
+            value_balance,
+            memorandum,
+            network_id,
+        };
 
         // Generate Schnorr signature on transaction data
-        // TODO (raychu86) Remove ledger_digest from signature and move the schnorr signing into `execute_offline`
         let signature_time = start_timer!(|| "Sign and randomize transaction contents");
 
-        let signature_message = to_bytes![
-            network_id,
-            ledger_digest,
-            old_serial_numbers,
-            new_commitments,
-            program_commitment,
-            local_data_root,
-            value_balance,
-            memorandum
-        ]?;
-
-        let mut signatures = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
-        for i in 0..Components::NUM_INPUT_RECORDS {
-            let sk_sig = &old_account_private_keys[i].sk_sig;
-            let randomizer = &old_randomizers[i];
+        let signature_message = authorization.signature_message(&local_data_root)?;
 
+        let mut account_signatures = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+        for signature_private_key in old_account_signature_keys.iter() {
             // Sign the transaction data
-            let account_signature = Components::AccountSignature::sign(
-                &system_parameters.account_signature,
-                sk_sig,
-                &signature_message,
-                rng,
-            )?;
+            account_signatures.push(match signature_private_key {
+                Some(signature_private_key) => Some(Components::AccountSignature::sign(
+                    &system_parameters.account_signature,
+                    signature_private_key,
+                    &signature_message,
+                    rng,
+                )?),
+                None => None,
+            });
+        }
 
-            // Randomize the signature
-            let randomized_signature = Components::AccountSignature::randomize_signature(
-                &system_parameters.account_signature,
-                &account_signature,
-                randomizer,
-            )?;
+        let mut pending_authorization =
+            PendingAuthorization::new(system_parameters, authorization, signature_message, old_randomizers);
 
-            signatures.push(randomized_signature);
+        // Randomize and insert the signatures
+        for (i, account_signature) in account_signatures.iter().enumerate() {
+            if let Some(account_signature) = account_signature {
+                pending_authorization.insert_signature(i, account_signature)?;
+            }
         }
 
         end_timer!(signature_time);
+        end_timer!(authorize_time);
 
-        // Prepare record encryption components used in the inner SNARK
-
-        let mut new_records_encryption_gadget_components = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        Ok(pending_authorization)
+    }
 
-        for (record, ciphertext_randomness) in new_records.iter().zip_eq(&new_records_encryption_randomness) {
+    /// Proves a transaction authorized by `authorize`, given the program proofs of its records.
+    /// The signatures of the authorization are checked before proving, as the authorization
+    /// may come from an untrusted party.
+    pub fn execute_authorized<R: Rng>(
+        parameters: &PublicParameters<Components>,
+        authorization: TransactionAuthorization<Components>,
+        old_death_program_proofs: Vec<PrivateProgramInput>,
+        new_birth_program_proofs: Vec<PrivateProgramInput>,
+        ledger_parameters: &Components::MerkleParameters,
+        rng: &mut R,
+    ) -> Result<(Vec<DPCRecord<Components>>, DPCTransaction<Components>), DPCError> {
+        assert_eq!(Components::NUM_INPUT_RECORDS, old_death_program_proofs.len());
+        assert_eq!(Components::NUM_OUTPUT_RECORDS, new_birth_program_proofs.len());
+
+        let exec_time = start_timer!(|| "BaseDPC::execute_authorized");
+
+        let system_parameters = &parameters.system_parameters;
+
+        let local_data_root = authorization
+            .to_local_data(system_parameters)?
+            .local_data_merkle_tree
+            .root();
+
+        // Check the signatures on the transaction data
+        {
+            let signature_message = authorization.signature_message(&local_data_root)?;
+
+            for (serial_number, signature) in authorization.old_serial_numbers.iter().zip(&authorization.signatures) {
+                if !Components::AccountSignature::verify(
+                    &system_parameters.account_signature,
+                    serial_number,
+                    &signature_message,
+                    signature,
+                )? {
+                    return Err(DPCError::InvalidAuthorization);
+                }
+            }
+        }
+
+        let old_witnesses = authorization.old_witnesses(ledger_parameters);
+
+        let TransactionAuthorization {
+            old_records,
+            old_account_proof_keys,
+            old_serial_numbers,
+            old_witness_paths: _,
+            signatures,
+
+            new_records,
+            new_sn_nonce_randomness,
+            new_commitments,
+
+            new_records_encryption_randomness,
+            new_encrypted_records,
+            new_encrypted_record_hashes,
+
+            program_commitment,
+            program_randomness,
+            local_data_commitment_randomizers,
+
+            ledger_digest,
+            value_balance,
+            memorandum,
+            network_id,
+        } = authorization;
+
+        let old_death_program_attributes = old_death_program_proofs;
+        let new_birth_program_attributes = new_birth_program_proofs;
+
+        // Prepare record encryption components used in the inner SNARK
+
+        let mut new_records_encryption_gadget_components = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+
+        for (record, ciphertext_randomness) in new_records.iter().zip_eq(&new_records_encryption_randomness) {
             let record_encryption_gadget_components = RecordEncryption::prepare_encryption_gadget_components(
-                &system_parameters,
+                system_parameters,
                 &record,
                 ciphertext_randomness,
             )?;
@@ -762,11 +912,11 @@ where
         let inner_proof = {
             let circuit = InnerCircuit::new(
                 parameters.system_parameters.clone(),
-                ledger.parameters().clone(),
+                ledger_parameters.clone(),
                 ledger_digest.clone(),
                 old_records,
                 old_witnesses,
-                old_account_private_keys,
+                old_account_proof_keys,
                 old_serial_numbers.clone(),
                 new_records.clone(),
                 new_sn_nonce_randomness,
@@ -783,7 +933,7 @@ where
                 network_id,
             );
 
-            let inner_snark_parameters = match &parameters.inner_snark_parameters.0 {
+            let inner_snark_parameters = match parameters.inner_snark_parameters.0.get()? {
                 Some(inner_snark_parameters) => inner_snark_parameters,
                 None => return Err(DPCError::MissingInnerSnarkProvingParameters),
             };
@@ -795,7 +945,7 @@ where
         {
             let input = InnerCircuitVerifierInput {
                 system_parameters: parameters.system_parameters.clone(),
-                ledger_parameters: ledger.parameters().clone(),
+                ledger_parameters: ledger_parameters.clone(),
                 ledger_digest: ledger_digest.clone(),
                 old_serial_numbers: old_serial_numbers.clone(),
                 new_commitments: new_commitments.clone(),
@@ -823,7 +973,7 @@ where
         let transaction_proof = {
             let circuit = OuterCircuit::new(
                 parameters.system_parameters.clone(),
-                ledger.parameters().clone(),
+                ledger_parameters.clone(),
                 ledger_digest.clone(),
                 old_serial_numbers.clone(),
                 new_commitments.clone(),
@@ -841,7 +991,7 @@ where
                 inner_snark_id.clone(),
             );
 
-            let outer_snark_parameters = match &parameters.outer_snark_parameters.0 {
+            let outer_snark_parameters = match parameters.outer_snark_parameters.0.get()? {
                 Some(outer_snark_parameters) => outer_snark_parameters,
                 None => return Err(DPCError::MissingOuterSnarkProvingParameters),
             };
@@ -849,7 +999,7 @@ where
             Components::OuterSNARK::prove(&outer_snark_parameters, &circuit, rng)?
         };
 
-        let transaction = Self::Transaction::new(
+        let transaction = DPCTransaction::new(
             old_serial_numbers,
             new_commitments,
             memorandum,
@@ -869,55 +1019,11 @@ where
         Ok((new_records, transaction))
     }
 
-    fn verify(parameters: &Self::Parameters, transaction: &Self::Transaction, ledger: &L) -> Result<bool, DPCError> {
-        let verify_time = start_timer!(|| "BaseDPC::verify");
-
-        // Returns false if there are duplicate serial numbers in the transaction.
-        if has_duplicates(transaction.old_serial_numbers().iter()) {
-            eprintln!("Transaction contains duplicate serial numbers");
-            return Ok(false);
-        }
-
-        // Returns false if there are duplicate commitments numbers in the transaction.
-        if has_duplicates(transaction.new_commitments().iter()) {
-            eprintln!("Transaction contains duplicate commitments");
-            return Ok(false);
-        }
-
-        let ledger_time = start_timer!(|| "Ledger checks");
-
-        // Returns false if the transaction memo previously existed in the ledger.
-        if ledger.contains_memo(transaction.memorandum()) {
-            eprintln!("Ledger already contains this transaction memo.");
-            return Ok(false);
-        }
-
-        // Returns false if any transaction serial number previously existed in the ledger.
-        for sn in transaction.old_serial_numbers() {
-            if ledger.contains_sn(sn) {
-                eprintln!("Ledger already contains this transaction serial number.");
-                return Ok(false);
-            }
-        }
-
-        // Returns false if any transaction commitment previously existed in the ledger.
-        for cm in transaction.new_commitments() {
-            if ledger.contains_cm(cm) {
-                eprintln!("Ledger already contains this transaction commitment.");
-                return Ok(false);
-            }
-        }
-
-        // Returns false if the ledger digest in the transaction is invalid.
-        if !ledger.validate_digest(&transaction.ledger_digest) {
-            eprintln!("Ledger digest is invalid.");
-            return Ok(false);
-        }
-
-        end_timer!(ledger_time);
-
-        let signature_time = start_timer!(|| "Signature checks");
-
+    /// Returns true iff the signatures of the transaction verify against its serial numbers.
+    pub fn verify_signatures(
+        parameters: &PublicParameters<Components>,
+        transaction: &DPCTransaction<Components>,
+    ) -> Result<bool, DPCError> {
         let signature_message = &to_bytes![
             transaction.network_id(),
             transaction.ledger_digest(),
@@ -932,13 +1038,19 @@ where
         let account_signature = &parameters.system_parameters.account_signature;
         for (pk, sig) in transaction.old_serial_numbers().iter().zip(&transaction.signatures) {
             if !Components::AccountSignature::verify(account_signature, pk, signature_message, sig)? {
-                eprintln!("Signature didn't verify.");
                 return Ok(false);
             }
         }
 
-        end_timer!(signature_time);
+        Ok(true)
+    }
 
+    /// Returns true iff the proof of the transaction verifies against the given ledger parameters.
+    pub fn verify_proof(
+        parameters: &PublicParameters<Components>,
+        transaction: &DPCTransaction<Components>,
+        ledger_parameters: &Components::MerkleParameters,
+    ) -> Result<bool, DPCError> {
         // Construct the ciphertext hashes
 
         let mut new_encrypted_record_hashes = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
@@ -951,7 +1063,7 @@ where
 
         let inner_snark_input = InnerCircuitVerifierInput {
             system_parameters: parameters.system_parameters.clone(),
-            ledger_parameters: ledger.parameters().clone(),
+            ledger_parameters: ledger_parameters.clone(),
             ledger_digest: transaction.ledger_digest().clone(),
             old_serial_numbers: transaction.old_serial_numbers().to_vec(),
             new_commitments: transaction.new_commitments().to_vec(),
@@ -976,11 +1088,236 @@ where
             inner_snark_id,
         };
 
-        if !Components::OuterSNARK::verify(
+        Ok(Components::OuterSNARK::verify(
             &parameters.outer_snark_parameters.1,
             &outer_snark_input,
             &transaction.transaction_proof,
-        )? {
+        )?)
+    }
+}
+
+impl<Components: BaseDPCComponents, L: LedgerScheme> DPCScheme<L> for DPC<Components>
+where
+    L: LedgerScheme<
+        Commitment = <Components::RecordCommitment as CommitmentScheme>::Output,
+        MerkleParameters = Components::MerkleParameters,
+        MerklePath = MerklePath<Components::MerkleParameters>,
+        MerkleTreeDigest = MerkleTreeDigest<Components::MerkleParameters>,
+        SerialNumber = <Components::AccountSignature as SignatureScheme>::PublicKey,
+        Transaction = DPCTransaction<Components>,
+    >,
+{
+    type Account = Account<Components>;
+    type ExecuteContext = ExecuteContext<Components>;
+    type LocalData = LocalData<Components>;
+    type Metadata = [u8; 32];
+    type Parameters = PublicParameters<Components>;
+    type Payload = <Self::Record as Record>::Payload;
+    type PrivateProgramInput = PrivateProgramInput;
+    type Record = DPCRecord<Components>;
+    type SystemParameters = SystemParameters<Components>;
+    type Transaction = DPCTransaction<Components>;
+
+    fn setup<R: Rng>(
+        ledger_parameters: &Components::MerkleParameters,
+        rng: &mut R,
+    ) -> Result<Self::Parameters, DPCError> {
+        let setup_time = start_timer!(|| "BaseDPC::setup");
+        let system_parameters = Self::generate_system_parameters(rng)?;
+
+        let program_snark_setup_time = start_timer!(|| "Dummy program SNARK setup");
+        let noop_program_snark_parameters = Self::generate_noop_program_snark_parameters(&system_parameters, rng)?;
+        let program_snark_proof = Components::NoopProgramSNARK::prove(
+            &noop_program_snark_parameters.proving_key,
+            &NoopCircuit::blank(&system_parameters),
+            rng,
+        )?;
+        end_timer!(program_snark_setup_time);
+
+        let program_snark_vk_and_proof = PrivateProgramInput {
+            verification_key: to_bytes![noop_program_snark_parameters.verification_key]?,
+            proof: to_bytes![program_snark_proof]?,
+        };
+
+        let snark_setup_time = start_timer!(|| "Execute inner SNARK setup");
+        let inner_circuit = InnerCircuit::blank(&system_parameters, ledger_parameters);
+        let inner_snark_parameters = Components::InnerSNARK::setup(&inner_circuit, rng)?;
+        end_timer!(snark_setup_time);
+
+        let snark_setup_time = start_timer!(|| "Execute outer SNARK setup");
+        let inner_snark_vk: <Components::InnerSNARK as SNARK>::VerificationParameters =
+            inner_snark_parameters.1.clone().into();
+        let inner_snark_proof = Components::InnerSNARK::prove(&inner_snark_parameters.0, &inner_circuit, rng)?;
+
+        let outer_snark_parameters = Components::OuterSNARK::setup(
+            &OuterCircuit::blank(
+                system_parameters.clone(),
+                ledger_parameters.clone(),
+                inner_snark_vk,
+                inner_snark_proof,
+                program_snark_vk_and_proof,
+            ),
+            rng,
+        )?;
+        end_timer!(snark_setup_time);
+        end_timer!(setup_time);
+
+        let inner_snark_parameters = (ProvingKey::loaded(inner_snark_parameters.0), inner_snark_parameters.1);
+        let outer_snark_parameters = (ProvingKey::loaded(outer_snark_parameters.0), outer_snark_parameters.1);
+
+        Ok(PublicParameters {
+            system_parameters,
+            noop_program_snark_parameters,
+            inner_snark_parameters,
+            outer_snark_parameters,
+        })
+    }
+
+    fn create_account<R: Rng>(parameters: &Self::Parameters, rng: &mut R) -> Result<Self::Account, DPCError> {
+        let time = start_timer!(|| "BaseDPC::create_account");
+
+        let account_signature_parameters = &parameters.system_parameters.account_signature;
+        let commitment_parameters = &parameters.system_parameters.account_commitment;
+        let encryption_parameters = &parameters.system_parameters.account_encryption;
+        let account = Account::new(
+            account_signature_parameters,
+            commitment_parameters,
+            encryption_parameters,
+            rng,
+        )?;
+
+        end_timer!(time);
+
+        Ok(account)
+    }
+
+    fn execute_offline<R: Rng>(
+        parameters: Self::SystemParameters,
+        old_records: Vec<Self::Record>,
+        old_account_private_keys: Vec<<Self::Account as AccountScheme>::AccountPrivateKey>,
+        new_record_owners: Vec<<Self::Account as AccountScheme>::AccountAddress>,
+        new_is_dummy_flags: &[bool],
+        new_values: &[u64],
+        new_payloads: Vec<Self::Payload>,
+        new_birth_program_ids: Vec<Vec<u8>>,
+        new_death_program_ids: Vec<Vec<u8>>,
+        memorandum: <Self::Transaction as Transaction>::Memorandum,
+        network_id: u8,
+        rng: &mut R,
+    ) -> Result<Self::ExecuteContext, DPCError> {
+        Self::execute_offline_with_spending_keys(
+            parameters,
+            old_records,
+            old_account_private_keys
+                .into_iter()
+                .map(SpendingKey::PrivateKey)
+                .collect(),
+            new_record_owners,
+            new_is_dummy_flags,
+            new_values,
+            new_payloads,
+            new_birth_program_ids,
+            new_death_program_ids,
+            memorandum,
+            network_id,
+            rng,
+        )
+    }
+
+    fn execute_online<R: Rng>(
+        parameters: &Self::Parameters,
+        context: Self::ExecuteContext,
+        old_death_program_proofs: Vec<Self::PrivateProgramInput>,
+        new_birth_program_proofs: Vec<Self::PrivateProgramInput>,
+        ledger: &L,
+        rng: &mut R,
+    ) -> Result<(Vec<Self::Record>, Self::Transaction), DPCError> {
+        // Construct the ledger witnesses
+
+        let ledger_digest = ledger.digest().expect("could not get digest");
+
+        // Generate the ledger membership witnesses
+        let mut old_witnesses = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+
+        // Compute the ledger membership witness and serial number from the old records.
+        for record in context.old_records.iter() {
+            if record.is_dummy() {
+                old_witnesses.push(MerklePath::default());
+            } else {
+                let witness = ledger.prove_cm(&record.commitment())?;
+                old_witnesses.push(witness);
+            }
+        }
+
+        Self::execute_online_with_witnesses(
+            parameters,
+            context,
+            old_death_program_proofs,
+            new_birth_program_proofs,
+            ledger.parameters(),
+            ledger_digest,
+            old_witnesses,
+            rng,
+        )
+    }
+
+    fn verify(parameters: &Self::Parameters, transaction: &Self::Transaction, ledger: &L) -> Result<bool, DPCError> {
+        let verify_time = start_timer!(|| "BaseDPC::verify");
+
+        // Returns false if there are duplicate serial numbers in the transaction.
+        if has_duplicates(transaction.old_serial_numbers().iter()) {
+            eprintln!("Transaction contains duplicate serial numbers");
+            return Ok(false);
+        }
+
+        // Returns false if there are duplicate commitments numbers in the transaction.
+        if has_duplicates(transaction.new_commitments().iter()) {
+            eprintln!("Transaction contains duplicate commitments");
+            return Ok(false);
+        }
+
+        let ledger_time = start_timer!(|| "Ledger checks");
+
+        // Returns false if the transaction memo previously existed in the ledger.
+        if ledger.contains_memo(transaction.memorandum()) {
+            eprintln!("Ledger already contains this transaction memo.");
+            return Ok(false);
+        }
+
+        // Returns false if any transaction serial number previously existed in the ledger.
+        for sn in transaction.old_serial_numbers() {
+            if ledger.contains_sn(sn) {
+                eprintln!("Ledger already contains this transaction serial number.");
+                return Ok(false);
+            }
+        }
+
+        // Returns false if any transaction commitment previously existed in the ledger.
+        for cm in transaction.new_commitments() {
+            if ledger.contains_cm(cm) {
+                eprintln!("Ledger already contains this transaction commitment.");
+                return Ok(false);
+            }
+        }
+
+        // Returns false if the ledger digest in the transaction is invalid.
+        if !ledger.validate_digest(&transaction.ledger_digest) {
+            eprintln!("Ledger digest is invalid.");
+            return Ok(false);
+        }
+
+        end_timer!(ledger_time);
+
+        let signature_time = start_timer!(|| "Signature checks");
+
+        if !Self::verify_signatures(parameters, transaction)? {
+            eprintln!("Signature didn't verify.");
+            return Ok(false);
+        }
+
+        end_timer!(signature_time);
+
+        if !Self::verify_proof(parameters, transaction, ledger.parameters())? {
             eprintln!("Transaction proof failed to verify.");
             return Ok(false);
         }
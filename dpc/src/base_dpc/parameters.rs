@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::base_dpc::BaseDPCComponents;
+use crate::base_dpc::{BaseDPCComponents, ProvingKey};
 use snarkos_models::{algorithms::SNARK, parameters::Parameters};
 use snarkos_parameters::*;
 use snarkos_utilities::bytes::FromBytes;
@@ -110,11 +110,11 @@ pub struct PublicParameters<C: BaseDPCComponents> {
     pub system_parameters: SystemParameters<C>,
     pub noop_program_snark_parameters: NoopProgramSNARKParameters<C>,
     pub inner_snark_parameters: (
-        Option<<C::InnerSNARK as SNARK>::ProvingParameters>,
+        ProvingKey<<C::InnerSNARK as SNARK>::ProvingParameters>,
         <C::InnerSNARK as SNARK>::PreparedVerificationParameters,
     ),
     pub outer_snark_parameters: (
-        Option<<C::OuterSNARK as SNARK>::ProvingParameters>,
+        ProvingKey<<C::OuterSNARK as SNARK>::ProvingParameters>,
         <C::OuterSNARK as SNARK>::PreparedVerificationParameters,
     ),
 }
@@ -135,7 +135,7 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
     pub fn inner_snark_parameters(
         &self,
     ) -> &(
-        Option<<C::InnerSNARK as SNARK>::ProvingParameters>,
+        ProvingKey<<C::InnerSNARK as SNARK>::ProvingParameters>,
         <C::InnerSNARK as SNARK>::PreparedVerificationParameters,
     ) {
         &self.inner_snark_parameters
@@ -152,7 +152,7 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
     pub fn outer_snark_parameters(
         &self,
     ) -> &(
-        Option<<C::OuterSNARK as SNARK>::ProvingParameters>,
+        ProvingKey<<C::OuterSNARK as SNARK>::ProvingParameters>,
         <C::OuterSNARK as SNARK>::PreparedVerificationParameters,
     ) {
         &self.outer_snark_parameters
@@ -182,16 +182,16 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
         &self.system_parameters.serial_number_nonce
     }
 
+    /// Loads the parameters, with the proving keys of the inner and outer SNARKs unless they only verify the
+    /// transactions. The proving keys are memory-mapped from their files and deserialized on their first use.
     pub fn load(verify_only: bool) -> IoResult<Self> {
         let system_parameters = SystemParameters::<C>::load()?;
         let noop_program_snark_parameters = NoopProgramSNARKParameters::<C>::load()?;
 
         let inner_snark_parameters = {
             let inner_snark_pk = match verify_only {
-                true => None,
-                false => Some(<C::InnerSNARK as SNARK>::ProvingParameters::read(
-                    InnerSNARKPKParameters::load_bytes()?.as_slice(),
-                )?),
+                true => ProvingKey::none(),
+                false => ProvingKey::lazy(load_inner_snark_pk::<C>),
             };
 
             let inner_snark_vk: <C::InnerSNARK as SNARK>::VerificationParameters =
@@ -204,10 +204,8 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
 
         let outer_snark_parameters = {
             let outer_snark_pk = match verify_only {
-                true => None,
-                false => Some(<C::OuterSNARK as SNARK>::ProvingParameters::read(
-                    OuterSNARKPKParameters::load_bytes()?.as_slice(),
-                )?),
+                true => ProvingKey::none(),
+                false => ProvingKey::lazy(load_outer_snark_pk::<C>),
             };
 
             let outer_snark_vk: <C::OuterSNARK as SNARK>::VerificationParameters =
@@ -231,7 +229,7 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
         let noop_program_snark_parameters = NoopProgramSNARKParameters::<C>::load()?;
 
         let inner_snark_parameters = {
-            let inner_snark_pk = None;
+            let inner_snark_pk = ProvingKey::none();
             let inner_snark_vk: <C::InnerSNARK as SNARK>::VerificationParameters =
                 <C::InnerSNARK as SNARK>::VerificationParameters::read(
                     InnerSNARKVKParameters::load_bytes()?.as_slice(),
@@ -240,7 +238,7 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
         };
 
         let outer_snark_parameters = {
-            let outer_snark_pk = None;
+            let outer_snark_pk = ProvingKey::none();
             let outer_snark_vk: <C::OuterSNARK as SNARK>::VerificationParameters =
                 <C::OuterSNARK as SNARK>::VerificationParameters::read(
                     OuterSNARKVKParameters::load_bytes()?.as_slice(),
@@ -256,3 +254,26 @@ impl<C: BaseDPCComponents> PublicParameters<C> {
         })
     }
 }
+
+impl<C: BaseDPCComponents> PublicParameters<C>
+where
+    <C::InnerSNARK as SNARK>::ProvingParameters: Send + Sync + 'static,
+    <C::OuterSNARK as SNARK>::ProvingParameters: Send + Sync + 'static,
+{
+    /// Loads the proving keys on a background thread, if they are not loaded yet, so they are ready for the
+    /// first transaction created with the parameters.
+    pub fn load_proving_keys_in_background(&self) {
+        self.inner_snark_parameters.0.load_in_background();
+        self.outer_snark_parameters.0.load_in_background();
+    }
+}
+
+/// Loads the proving key of the inner SNARK, memory-mapped from its parameter file.
+fn load_inner_snark_pk<C: BaseDPCComponents>() -> IoResult<<C::InnerSNARK as SNARK>::ProvingParameters> {
+    <C::InnerSNARK as SNARK>::ProvingParameters::read(&InnerSNARKPKParameters::load_mapped()?[..])
+}
+
+/// Loads the proving key of the outer SNARK, memory-mapped from its parameter file.
+fn load_outer_snark_pk<C: BaseDPCComponents>() -> IoResult<<C::OuterSNARK as SNARK>::ProvingParameters> {
+    <C::OuterSNARK as SNARK>::ProvingParameters::read(&OuterSNARKPKParameters::load_mapped()?[..])
+}
@@ -22,3 +22,6 @@ pub use noop_program_circuit::*;
 
 pub mod program;
 pub use program::*;
+
+pub mod program_registry;
+pub use program_registry::*;
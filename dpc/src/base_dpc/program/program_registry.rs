@@ -0,0 +1,189 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::base_dpc::{
+    parameters::{PublicParameters, SystemParameters},
+    BaseDPCComponents,
+    LocalData,
+    NoopProgram,
+    PrivateProgramInput,
+};
+use snarkos_errors::dpc::DPCError;
+use snarkos_models::{
+    algorithms::CRH,
+    dpc::{Program, Record},
+};
+use snarkos_utilities::{to_bytes, ToBytes};
+
+use rand::RngCore;
+use std::collections::BTreeMap;
+
+/// A program deployed with its proving and verification keys, which records name by id
+/// as their birth or death program.
+pub trait DeployedProgram<C: BaseDPCComponents> {
+    /// Returns the id of the program, the hash of its verification key.
+    fn id(&self) -> Vec<u8>;
+
+    /// Returns the proof of the program for the record at the given position of the local data.
+    fn execute(
+        &self,
+        local_data: &LocalData<C>,
+        position: u8,
+        rng: &mut dyn RngCore,
+    ) -> Result<PrivateProgramInput, DPCError>;
+}
+
+/// A program with its proving and verification keys.
+pub struct ProgramDeployment<P: Program> {
+    program: P,
+    proving_key: P::ProvingParameters,
+    verification_key: P::VerificationParameters,
+}
+
+impl<C: BaseDPCComponents, P> DeployedProgram<C> for ProgramDeployment<P>
+where
+    P: Program<LocalData = LocalData<C>, PrivateWitness = PrivateProgramInput>,
+{
+    fn id(&self) -> Vec<u8> {
+        self.program.into_compact_repr()
+    }
+
+    fn execute(
+        &self,
+        local_data: &LocalData<C>,
+        position: u8,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<PrivateProgramInput, DPCError> {
+        self.program.execute(
+            &self.proving_key,
+            &self.verification_key,
+            local_data,
+            position,
+            &mut rng,
+        )
+    }
+}
+
+/// The programs a node or a wallet proves the birth and death of records with, by program id.
+///
+/// A record names its birth and death programs by id, and the outer proof of a transaction checks that
+/// each program proof verifies against a verification key hashing to the id of the program of its record.
+/// A program is hence deployed by registering its keys with the provers of the transactions spending or
+/// creating its records, and the ledger accepts its proofs without learning the ids. The programs must be
+/// proven with the program SNARK of the components, whose proofs the outer circuit verifies.
+pub struct ProgramRegistry<C: BaseDPCComponents> {
+    programs: BTreeMap<Vec<u8>, Box<dyn DeployedProgram<C>>>,
+}
+
+impl<C: BaseDPCComponents> ProgramRegistry<C> {
+    /// Creates a registry of the noop program of the given parameters.
+    pub fn new(parameters: &PublicParameters<C>) -> Result<Self, DPCError> {
+        let mut registry = Self {
+            programs: BTreeMap::new(),
+        };
+
+        let verification_key = &parameters.noop_program_snark_parameters.verification_key;
+        let noop_program_id = Self::program_id(&parameters.system_parameters, &to_bytes![verification_key]?)?;
+
+        registry.register(
+            &parameters.system_parameters,
+            NoopProgram::<C, C::NoopProgramSNARK>::new(noop_program_id),
+            parameters.noop_program_snark_parameters.proving_key.clone(),
+            verification_key.clone(),
+        )?;
+
+        Ok(registry)
+    }
+
+    /// Returns the id of the program of the given verification key.
+    pub fn program_id(system_parameters: &SystemParameters<C>, verification_key: &[u8]) -> Result<Vec<u8>, DPCError> {
+        Ok(to_bytes![C::ProgramVerificationKeyCRH::hash(
+            &system_parameters.program_verification_key_crh,
+            verification_key
+        )?]?)
+    }
+
+    /// Registers a program with its keys, and returns its id. The id of the program must be the
+    /// hash of its verification key. A program registered under the same id is replaced.
+    pub fn register<P>(
+        &mut self,
+        system_parameters: &SystemParameters<C>,
+        program: P,
+        proving_key: P::ProvingParameters,
+        verification_key: P::VerificationParameters,
+    ) -> Result<Vec<u8>, DPCError>
+    where
+        P: Program<LocalData = LocalData<C>, PrivateWitness = PrivateProgramInput> + 'static,
+        P::ProvingParameters: 'static,
+        P::VerificationParameters: ToBytes + 'static,
+    {
+        let program_id = Self::program_id(system_parameters, &to_bytes![verification_key]?)?;
+        if program.into_compact_repr() != program_id {
+            return Err(DPCError::InvalidProgramId(hex::encode(program.into_compact_repr())));
+        }
+
+        self.programs.insert(
+            program_id.clone(),
+            Box::new(ProgramDeployment {
+                program,
+                proving_key,
+                verification_key,
+            }),
+        );
+
+        Ok(program_id)
+    }
+
+    /// Returns true if a program is registered under the given id.
+    pub fn contains(&self, program_id: &[u8]) -> bool {
+        self.programs.contains_key(program_id)
+    }
+
+    /// Returns the ids of the registered programs.
+    pub fn program_ids(&self) -> Vec<Vec<u8>> {
+        self.programs.keys().cloned().collect()
+    }
+
+    /// Returns the death program proofs of the old records and the birth program proofs of the new records
+    /// of a transaction, given its local data. Each record is proven by its own registered program.
+    pub fn execute(
+        &self,
+        local_data: &LocalData<C>,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Vec<PrivateProgramInput>, Vec<PrivateProgramInput>), DPCError> {
+        let mut old_death_program_proofs = Vec::with_capacity(C::NUM_INPUT_RECORDS);
+        for (i, record) in local_data.old_records.iter().enumerate() {
+            let program = self.get(record.death_program_id())?;
+            old_death_program_proofs.push(program.execute(local_data, i as u8, rng)?);
+        }
+
+        let mut new_birth_program_proofs = Vec::with_capacity(C::NUM_OUTPUT_RECORDS);
+        for (j, record) in local_data.new_records.iter().enumerate() {
+            let program = self.get(record.birth_program_id())?;
+            new_birth_program_proofs.push(program.execute(local_data, (C::NUM_INPUT_RECORDS + j) as u8, rng)?);
+        }
+
+        Ok((old_death_program_proofs, new_birth_program_proofs))
+    }
+
+    /// Returns the program registered under the given id.
+    fn get(&self, program_id: &[u8]) -> Result<&dyn DeployedProgram<C>, DPCError> {
+        match self.programs.get(program_id) {
+            Some(program) => Ok(program.as_ref()),
+            None => Err(DPCError::UnknownProgram(hex::encode(program_id))),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use once_cell::sync::OnceCell;
+use std::{io::Result as IoResult, sync::Arc, thread};
+
+/// The proving key of a SNARK, loaded and deserialized on its first use rather than with the other parameters,
+/// as a proving key is hundreds of megabytes and only used to create transactions.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct ProvingKey<P> {
+    /// The proving key once it is loaded, shared by the clones of the parameters
+    key: Arc<OnceCell<P>>,
+    /// The loader of the proving key, if it is not loaded yet
+    loader: Option<fn() -> IoResult<P>>,
+}
+
+impl<P> ProvingKey<P> {
+    /// Returns the absent proving key of the parameters that only verify the transactions.
+    pub fn none() -> Self {
+        Self {
+            key: Arc::new(OnceCell::new()),
+            loader: None,
+        }
+    }
+
+    /// Returns the given proving key.
+    pub fn loaded(key: P) -> Self {
+        Self {
+            key: Arc::new(OnceCell::from(key)),
+            loader: None,
+        }
+    }
+
+    /// Returns the proving key of the given loader, which is called on the first use of the key.
+    pub fn lazy(loader: fn() -> IoResult<P>) -> Self {
+        Self {
+            key: Arc::new(OnceCell::new()),
+            loader: Some(loader),
+        }
+    }
+
+    /// Returns true if the proving key is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.key.get().is_some()
+    }
+
+    /// Returns the proving key, loaded first if it is not loaded yet, or `None` if there is no proving key.
+    /// The callers of a key being loaded wait for it, and a failed load is attempted again on the next call.
+    pub fn get(&self) -> IoResult<Option<&P>> {
+        match self.loader {
+            Some(loader) => self.key.get_or_try_init(loader).map(Some),
+            None => Ok(self.key.get()),
+        }
+    }
+}
+
+impl<P: Send + Sync + 'static> ProvingKey<P> {
+    /// Loads the proving key on a background thread, if it is not loaded yet, so it is ready for its first use.
+    pub fn load_in_background(&self) {
+        if self.loader.is_some() && !self.is_loaded() {
+            let key = self.clone();
+            thread::spawn(move || {
+                // A failed load is reported again on the first use of the key
+                let _ = key.get();
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    fn load_key() -> IoResult<u64> {
+        LOADS.fetch_add(1, Ordering::SeqCst);
+        Ok(42)
+    }
+
+    #[test]
+    fn load_proving_key_on_first_use() {
+        let key = ProvingKey::lazy(load_key);
+        let clone = key.clone();
+        assert!(!key.is_loaded());
+
+        assert_eq!(clone.get().unwrap(), Some(&42));
+        assert!(key.is_loaded());
+        assert_eq!(key.get().unwrap(), Some(&42));
+        assert_eq!(LOADS.load(Ordering::SeqCst), 1);
+
+        assert_eq!(ProvingKey::loaded(7u64).get().unwrap(), Some(&7));
+        assert_eq!(ProvingKey::<u64>::none().get().unwrap(), None);
+    }
+}
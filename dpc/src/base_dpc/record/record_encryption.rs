@@ -137,6 +137,57 @@ impl<C: BaseDPCComponents> RecordEncryption<C> {
         account_view_key: &AccountViewKey<C>,
         encrypted_record: &EncryptedRecord<C>,
     ) -> Result<DPCRecord<C>, DPCError> {
+        let record_components = Self::decrypt_record_components(system_parameters, account_view_key, encrypted_record)?;
+
+        // Construct the record account address
+        let owner = AccountAddress::from_view_key(&system_parameters.account_encryption, &account_view_key)?;
+
+        let mut records = Self::reconstruct_records(system_parameters, owner, vec![record_components])?;
+
+        Ok(records.remove(0))
+    }
+
+    /// Decrypt and reconstruct the encrypted records, in order, or `None` for the records that
+    /// fail to decrypt. The address of the account is derived once, and the record commitments
+    /// are computed in a batch.
+    pub fn decrypt_records(
+        system_parameters: &SystemParameters<C>,
+        account_view_key: &AccountViewKey<C>,
+        encrypted_records: &[EncryptedRecord<C>],
+    ) -> Result<Vec<Option<DPCRecord<C>>>, DPCError> {
+        let mut decrypted_indices = Vec::with_capacity(encrypted_records.len());
+        let mut records_components = Vec::with_capacity(encrypted_records.len());
+        for (i, encrypted_record) in encrypted_records.iter().enumerate() {
+            if let Ok(record_components) =
+                Self::decrypt_record_components(system_parameters, account_view_key, encrypted_record)
+            {
+                decrypted_indices.push(i);
+                records_components.push(record_components);
+            }
+        }
+
+        let mut records = vec![None; encrypted_records.len()];
+        if records_components.is_empty() {
+            return Ok(records);
+        }
+
+        // Construct the record account address
+        let owner = AccountAddress::from_view_key(&system_parameters.account_encryption, &account_view_key)?;
+
+        let decrypted_records = Self::reconstruct_records(system_parameters, owner, records_components)?;
+        for (i, record) in decrypted_indices.into_iter().zip(decrypted_records) {
+            records[i] = Some(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Decrypt the encrypted record into its record components
+    fn decrypt_record_components(
+        system_parameters: &SystemParameters<C>,
+        account_view_key: &AccountViewKey<C>,
+        encrypted_record: &EncryptedRecord<C>,
+    ) -> Result<DeserializedRecord<C>, DPCError> {
         // Decrypt the encrypted record
         let plaintext_elements = C::AccountEncryption::decrypt(
             &system_parameters.account_encryption,
@@ -152,65 +203,84 @@ impl<C: BaseDPCComponents> RecordEncryption<C> {
         }
 
         // Deserialize the plaintext record into record components
-        let record_components = RecordSerializer::<
+        RecordSerializer::<
             C,
             <C as BaseDPCComponents>::EncryptionModelParameters,
             <C as BaseDPCComponents>::EncryptionGroup,
-        >::deserialize(plaintext, encrypted_record.final_fq_high_selector)?;
-
-        let DeserializedRecord {
-            serial_number_nonce,
-            commitment_randomness,
-            birth_program_id,
-            death_program_id,
-            payload,
-            value,
-        } = record_components;
-
-        // Construct the record account address
-
-        let owner = AccountAddress::from_view_key(&system_parameters.account_encryption, &account_view_key)?;
-
-        // Determine if the record is a dummy
-
-        // TODO (raychu86) Establish `is_dummy` flag properly by checking that the value is 0 and the programs are equivalent to a global dummy
-        let dummy_program = birth_program_id.clone();
-
-        let is_dummy = (value == 0)
-            && (payload == RecordPayload::default())
-            && (death_program_id == dummy_program)
-            && (birth_program_id == dummy_program);
-
-        // Calculate record commitment
-
-        let commitment_input = to_bytes![
-            owner,
-            is_dummy,
-            value,
-            payload,
-            birth_program_id,
-            death_program_id,
-            serial_number_nonce
-        ]?;
+        >::deserialize(plaintext, encrypted_record.final_fq_high_selector)
+    }
 
-        let commitment = C::RecordCommitment::commit(
-            &system_parameters.record_commitment,
-            &commitment_input,
-            &commitment_randomness,
-        )?;
+    /// Reconstruct the records of the given owner from their record components,
+    /// computing the record commitments in a batch
+    fn reconstruct_records(
+        system_parameters: &SystemParameters<C>,
+        owner: AccountAddress<C>,
+        records_components: Vec<DeserializedRecord<C>>,
+    ) -> Result<Vec<DPCRecord<C>>, DPCError> {
+        let mut is_dummy_flags = Vec::with_capacity(records_components.len());
+        let mut commitment_inputs = Vec::with_capacity(records_components.len());
+        let mut commitment_randomness = Vec::with_capacity(records_components.len());
+
+        for record_components in &records_components {
+            // Determine if the record is a dummy
+
+            // TODO (raychu86) Establish `is_dummy` flag properly by checking that the value is 0 and the programs are equivalent to a global dummy
+            let dummy_program = &record_components.birth_program_id;
+
+            let is_dummy = (record_components.value == 0)
+                && (record_components.payload == RecordPayload::default())
+                && (&record_components.death_program_id == dummy_program)
+                && (&record_components.birth_program_id == dummy_program);
+
+            // Calculate record commitment
+
+            let commitment_input = to_bytes![
+                owner,
+                is_dummy,
+                record_components.value,
+                record_components.payload,
+                record_components.birth_program_id,
+                record_components.death_program_id,
+                record_components.serial_number_nonce
+            ]?;
+
+            is_dummy_flags.push(is_dummy);
+            commitment_inputs.push(commitment_input);
+            commitment_randomness.push(record_components.commitment_randomness.clone());
+        }
 
-        Ok(DPCRecord {
-            owner,
-            is_dummy,
-            value,
-            payload,
-            birth_program_id,
-            death_program_id,
-            serial_number_nonce,
-            commitment_randomness,
-            commitment,
-            _components: PhantomData,
-        })
+        let commitments = system_parameters
+            .record_commitment
+            .batch_commit(&commitment_inputs, &commitment_randomness)?;
+
+        Ok(records_components
+            .into_iter()
+            .zip(is_dummy_flags)
+            .zip(commitments)
+            .map(|((record_components, is_dummy), commitment)| {
+                let DeserializedRecord {
+                    serial_number_nonce,
+                    commitment_randomness,
+                    birth_program_id,
+                    death_program_id,
+                    payload,
+                    value,
+                } = record_components;
+
+                DPCRecord {
+                    owner: owner.clone(),
+                    is_dummy,
+                    value,
+                    payload,
+                    birth_program_id,
+                    death_program_id,
+                    serial_number_nonce,
+                    commitment_randomness,
+                    commitment,
+                    _components: PhantomData,
+                }
+            })
+            .collect())
     }
 
     /// Returns the encrypted record hash
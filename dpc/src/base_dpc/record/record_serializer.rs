@@ -66,6 +66,24 @@ pub struct RecordSerializer<
     G: Group + ProjectiveCurve,
 >(PhantomData<C>, PhantomData<P>, PhantomData<G>);
 
+impl<C: BaseDPCComponents, P: MontgomeryModelParameters + TEModelParameters, G: Group + ProjectiveCurve>
+    RecordSerializer<C, P, G>
+{
+    /// Returns the number of group elements a record is serialized into, given by the size of the payload.
+    pub fn num_serialized_elements() -> usize {
+        let payload_bits_count = RecordPayload::default().size() * 8;
+        let num_payload_elements = payload_bits_count / Self::PAYLOAD_ELEMENT_BITSIZE;
+        let payload_remainder_bits_count = payload_bits_count % Self::PAYLOAD_ELEMENT_BITSIZE;
+
+        // The value is appended to the payload remainder and the sign high bits, as in `serialize`
+        let num_sign_high_bits = 5 + num_payload_elements;
+        let value_does_not_fit = (payload_remainder_bits_count + num_sign_high_bits + (std::mem::size_of::<u64>() * 8))
+            > Self::PAYLOAD_ELEMENT_BITSIZE;
+
+        5 + num_payload_elements + (value_does_not_fit as usize) + 1
+    }
+}
+
 impl<C: BaseDPCComponents, P: MontgomeryModelParameters + TEModelParameters, G: Group + ProjectiveCurve>
     RecordSerializerScheme for RecordSerializer<C, P, G>
 {
@@ -15,13 +15,21 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use super::{record_encryption::*, record_serializer::*};
-use crate::base_dpc::{instantiated::*, record_payload::RecordPayload, DPC};
+use crate::base_dpc::{instantiated::*, record::DPCRecord, record_payload::RecordPayload, DPC};
 use snarkos_curves::edwards_bls12::{EdwardsParameters, EdwardsProjective as EdwardsBls};
-use snarkos_models::{algorithms::CRH, dpc::RecordSerializerScheme, objects::AccountScheme};
+use snarkos_models::{
+    algorithms::CRH,
+    dpc::{Record, RecordSerializerScheme},
+    objects::AccountScheme,
+};
 
 use snarkos_objects::{Account, AccountViewKey};
 
-use snarkos_utilities::{bytes::ToBytes, to_bytes};
+use snarkos_testing::consensus::DATA;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
 
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
@@ -76,6 +84,10 @@ fn test_record_serialization() {
 
             let (serialized_record, final_fq_high_bit) =
                 RecordSerializer::<_, EdwardsParameters, EdwardsBls>::serialize(&given_record).unwrap();
+            assert_eq!(
+                serialized_record.len(),
+                RecordSerializer::<Components, EdwardsParameters, EdwardsBls>::num_serialized_elements()
+            );
             let record_components = RecordSerializer::<Components, EdwardsParameters, EdwardsBls>::deserialize(
                 serialized_record,
                 final_fq_high_bit,
@@ -159,3 +171,125 @@ fn test_record_encryption() {
         }
     }
 }
+
+#[test]
+fn test_record_batch_derivation() {
+    let mut rng = XorShiftRng::seed_from_u64(1231275789u64);
+
+    let system_parameters = InstantiatedDPC::generate_system_parameters(&mut rng).unwrap();
+    let program_snark_pp =
+        InstantiatedDPC::generate_noop_program_snark_parameters(&system_parameters, &mut rng).unwrap();
+
+    let program_snark_vk_bytes = to_bytes![
+        ProgramVerificationKeyCRH::hash(
+            &system_parameters.program_verification_key_crh,
+            &to_bytes![program_snark_pp.verification_key].unwrap()
+        )
+        .unwrap()
+    ]
+    .unwrap();
+
+    let mut accounts = vec![];
+    for _ in 0..2 {
+        accounts.push(
+            Account::new(
+                &system_parameters.account_signature,
+                &system_parameters.account_commitment,
+                &system_parameters.account_encryption,
+                &mut rng,
+            )
+            .unwrap(),
+        );
+    }
+
+    // The records of the last iteration belong to another account
+    let mut given_records = vec![];
+    let mut encrypted_records = vec![];
+    for i in 0..ITERATIONS {
+        let owner = if i + 1 < ITERATIONS { &accounts[0] } else { &accounts[1] };
+
+        let sn_nonce_input: [u8; 32] = rng.gen();
+        let value = rng.gen();
+        let payload: [u8; 32] = rng.gen();
+
+        let given_record = DPC::generate_record(
+            system_parameters.clone(),
+            SerialNumberNonce::hash(&system_parameters.serial_number_nonce, &sn_nonce_input).unwrap(),
+            owner.address.clone(),
+            false,
+            value,
+            RecordPayload::from_bytes(&payload),
+            program_snark_vk_bytes.clone(),
+            program_snark_vk_bytes.clone(),
+            &mut rng,
+        )
+        .unwrap();
+
+        let (_, encrypted_record) =
+            RecordEncryption::encrypt_record(&system_parameters, &given_record, &mut rng).unwrap();
+
+        given_records.push(given_record);
+        encrypted_records.push(encrypted_record);
+    }
+
+    let account_view_key = AccountViewKey::from_private_key(
+        &system_parameters.account_signature,
+        &system_parameters.account_commitment,
+        &accounts[0].private_key,
+    )
+    .unwrap();
+
+    // The batch decryption matches the decryption of each record, and the commitments of the
+    // records of the other account differ from their given commitments
+    let decrypted_records =
+        RecordEncryption::decrypt_records(&system_parameters, &account_view_key, &encrypted_records).unwrap();
+    assert_eq!(decrypted_records.len(), ITERATIONS);
+
+    for (i, (given_record, decrypted_record)) in given_records.iter().zip(&decrypted_records).enumerate() {
+        if i + 1 < ITERATIONS {
+            assert_eq!(Some(given_record), decrypted_record.as_ref());
+        } else if let Some(decrypted_record) = decrypted_record {
+            assert_ne!(given_record.commitment, decrypted_record.commitment);
+        }
+    }
+
+    // The batch serial numbers match the serial number of each record
+    let serial_numbers = DPC::generate_sns(&system_parameters, &given_records, &accounts[0].private_key).unwrap();
+    assert_eq!(serial_numbers.len(), ITERATIONS);
+
+    for (given_record, serial_number) in given_records.iter().zip(&serial_numbers) {
+        assert_eq!(
+            &DPC::generate_sn(&system_parameters, given_record, &accounts[0].private_key).unwrap(),
+            serial_number
+        );
+    }
+}
+
+#[test]
+fn test_record_json() {
+    let record_bytes = to_bytes![DATA.records_1[0]].unwrap();
+    let record = DPCRecord::<Components>::read(&record_bytes[..]).unwrap();
+
+    // The record round-trips through its JSON representation
+    let json = serde_json::to_value(&record).unwrap();
+    let decoded: DPCRecord<Components> = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(decoded, record);
+    assert_eq!(to_bytes![decoded].unwrap(), record_bytes);
+
+    assert_eq!(json["owner"], record.owner().to_string());
+    assert_eq!(json["value"], record.value());
+    assert_eq!(json["birth_program_id"], hex::encode(record.birth_program_id()));
+
+    // The non-canonical representations are rejected
+    let mut uppercase_owner = json.clone();
+    uppercase_owner["owner"] = record.owner().to_string().to_uppercase().into();
+    assert!(serde_json::from_value::<DPCRecord<Components>>(uppercase_owner).is_err());
+
+    let mut short_payload = json.clone();
+    short_payload["payload"] = hex::encode([0u8; 31]).into();
+    assert!(serde_json::from_value::<DPCRecord<Components>>(short_payload).is_err());
+
+    let mut unknown_field = json;
+    unknown_field["ciphertext"] = "00".into();
+    assert!(serde_json::from_value::<DPCRecord<Components>>(unknown_field).is_err());
+}
@@ -24,12 +24,13 @@ use crate::base_dpc::{
     record_payload::RecordPayload,
     BaseDPCComponents,
     ExecuteContext,
+    SpendingKey,
     DPC,
 };
 use snarkos_algorithms::merkle_tree::MerklePath;
 use snarkos_curves::bls12_377::{Fq, Fr};
 use snarkos_models::{
-    algorithms::{MerkleParameters, CRH, SNARK},
+    algorithms::{MerkleParameters, SignatureScheme, CRH, SNARK},
     dpc::{DPCScheme, Program, Record},
     gadgets::r1cs::{ConstraintSystem, TestConstraintSystem},
     objects::{AccountScheme, LedgerScheme},
@@ -37,6 +38,7 @@ use snarkos_models::{
 use snarkos_objects::{
     dpc::DPCTransactions,
     Account,
+    AccountProofKey,
     Block,
     BlockHeader,
     BlockHeaderHash,
@@ -44,8 +46,11 @@ use snarkos_objects::{
     PedersenMerkleRootHash,
     ProofOfSuccinctWork,
 };
-use snarkos_testing::storage::*;
-use snarkos_utilities::{bytes::ToBytes, to_bytes};
+use snarkos_testing::{consensus::TRANSACTION_1, storage::*};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
 
 use itertools::Itertools;
 use rand::SeedableRng;
@@ -214,7 +219,8 @@ fn test_execute_base_dpc_constraints() {
         system_parameters: _,
 
         old_records,
-        old_account_private_keys,
+        old_account_proof_keys,
+        old_account_signature_keys: _,
         old_serial_numbers,
         old_randomizers: _,
 
@@ -274,7 +280,7 @@ fn test_execute_base_dpc_constraints() {
         &ledger_digest,
         &old_records,
         &old_witnesses,
-        &old_account_private_keys,
+        &old_account_proof_keys,
         &old_serial_numbers,
         &new_records,
         &new_sn_nonce_randomness,
@@ -335,7 +341,7 @@ fn test_execute_base_dpc_constraints() {
             ledger_digest,
             old_records,
             old_witnesses,
-            old_account_private_keys,
+            old_account_proof_keys,
             old_serial_numbers.clone(),
             new_records,
             new_sn_nonce_randomness,
@@ -400,3 +406,177 @@ fn test_execute_base_dpc_constraints() {
 
     kill_storage(ledger);
 }
+
+#[test]
+fn test_transaction_json() {
+    let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+
+    // The transaction round-trips through its JSON representation
+    let json = serde_json::to_value(&transaction).unwrap();
+    let decoded: Tx = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(decoded, transaction);
+    assert_eq!(to_bytes![decoded].unwrap(), *TRANSACTION_1);
+    assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+
+    assert_eq!(json["network"], transaction.network.id());
+    assert_eq!(json["value_balance"], transaction.value_balance.0);
+    assert_eq!(json["memorandum"], hex::encode(transaction.memorandum));
+    assert_eq!(
+        json["encrypted_records"][0],
+        hex::encode(to_bytes![transaction.encrypted_records[0]].unwrap())
+    );
+
+    // The non-canonical representations are rejected
+    let rejected = |update: &dyn Fn(&mut serde_json::Value)| {
+        let mut json = json.clone();
+        update(&mut json);
+        serde_json::from_value::<Tx>(json).is_err()
+    };
+
+    assert!(rejected(
+        &|json| json["memorandum"] = json["memorandum"].as_str().unwrap().to_uppercase().into()
+    ));
+    assert!(rejected(
+        &|json| json["ledger_digest"] = format!("{}00", json["ledger_digest"].as_str().unwrap()).into()
+    ));
+    assert!(rejected(&|json| json["signatures"].as_array_mut().unwrap().truncate(1)));
+    assert!(rejected(&|json| json["fee"] = 1.into()));
+    assert!(rejected(&|json| {
+        json.as_object_mut().unwrap().remove("inner_snark_id");
+    }));
+}
+
+#[test]
+fn test_threshold_account_authorization() {
+    let mut rng = XorShiftRng::seed_from_u64(1231275789u64);
+
+    let system_parameters = InstantiatedDPC::generate_system_parameters(&mut rng).unwrap();
+    let signature_parameters = &system_parameters.account_signature;
+    let commitment_parameters = &system_parameters.account_commitment;
+    let encryption_parameters = &system_parameters.account_encryption;
+
+    // Split the signature private key of an account among three signers, any two of which sign.
+    let account = Account::new(
+        signature_parameters,
+        commitment_parameters,
+        encryption_parameters,
+        &mut rng,
+    )
+    .unwrap();
+    let proof_key = AccountProofKey::from_private_key(signature_parameters, &account.private_key).unwrap();
+    let key_shares = signature_parameters
+        .split_private_key(&account.private_key.sk_sig, 2, 3, &mut rng)
+        .unwrap();
+
+    // The other old record is a dummy record of an account signing alone.
+    let dummy_account = Account::new(
+        signature_parameters,
+        commitment_parameters,
+        encryption_parameters,
+        &mut rng,
+    )
+    .unwrap();
+
+    let program_id = vec![0u8; 48];
+    let old_record = DPC::generate_record(
+        system_parameters.clone(),
+        SerialNumberNonce::hash(&system_parameters.serial_number_nonce, &[0u8; 1]).unwrap(),
+        account.address.clone(),
+        false,
+        10,
+        RecordPayload::default(),
+        program_id.clone(),
+        program_id.clone(),
+        &mut rng,
+    )
+    .unwrap();
+    let dummy_record = DPC::generate_record(
+        system_parameters.clone(),
+        SerialNumberNonce::hash(&system_parameters.serial_number_nonce, &[1u8; 1]).unwrap(),
+        dummy_account.address.clone(),
+        true,
+        0,
+        RecordPayload::default(),
+        program_id.clone(),
+        program_id.clone(),
+        &mut rng,
+    )
+    .unwrap();
+
+    let context = InstantiatedDPC::execute_offline_with_spending_keys(
+        system_parameters.clone(),
+        vec![old_record, dummy_record],
+        vec![
+            SpendingKey::ProofKey(proof_key.clone()),
+            SpendingKey::PrivateKey(dummy_account.private_key.clone()),
+        ],
+        vec![dummy_account.address.clone(); NUM_OUTPUT_RECORDS],
+        &[false, true],
+        &[10, 0],
+        vec![RecordPayload::default(); NUM_OUTPUT_RECORDS],
+        vec![program_id.clone(); NUM_OUTPUT_RECORDS],
+        vec![program_id; NUM_OUTPUT_RECORDS],
+        [0u8; 32],
+        0,
+        &mut rng,
+    )
+    .unwrap();
+
+    let old_witnesses = (0..NUM_INPUT_RECORDS).map(|_| MerklePath::default()).collect();
+    let mut pending_authorization =
+        InstantiatedDPC::authorize_pending(context, Default::default(), old_witnesses, &mut rng).unwrap();
+
+    // Only the record of the threshold account is left to sign
+    assert_eq!(pending_authorization.missing_signatures(), vec![0]);
+    let message = pending_authorization.signature_message().to_vec();
+
+    // The first and the third signers sign the transaction
+    let signers = [&key_shares[0], &key_shares[2]];
+    let (nonces, commitments): (Vec<_>, Vec<_>) = signers
+        .iter()
+        .map(|key_share| signature_parameters.commit_nonces(key_share, &mut rng).unwrap())
+        .unzip();
+    let signature_shares: Vec<_> = signers
+        .iter()
+        .zip(nonces)
+        .map(|(key_share, nonces)| {
+            signature_parameters
+                .sign_share(key_share, nonces, &commitments, &message)
+                .unwrap()
+        })
+        .collect();
+    let signature = signature_parameters
+        .aggregate_signature_shares(&proof_key.pk_sig, &commitments, &signature_shares, &message)
+        .unwrap();
+
+    // A signature of another account is rejected
+    let other_signature = AccountSignature::sign(
+        signature_parameters,
+        &dummy_account.private_key.sk_sig,
+        &message,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(pending_authorization.insert_signature(0, &other_signature).is_err());
+
+    pending_authorization.insert_signature(0, &signature).unwrap();
+    assert!(pending_authorization.missing_signatures().is_empty());
+
+    // The signatures of the authorization sign for the serial numbers
+    let authorization = pending_authorization.into_authorization().unwrap();
+    let local_data_root = authorization
+        .to_local_data(&system_parameters)
+        .unwrap()
+        .local_data_merkle_tree
+        .root();
+    let signature_message = authorization.signature_message(&local_data_root).unwrap();
+    assert_eq!(signature_message, message);
+
+    for (serial_number, signature) in authorization
+        .old_serial_numbers
+        .iter()
+        .zip_eq(&authorization.signatures)
+    {
+        assert!(AccountSignature::verify(signature_parameters, serial_number, &signature_message, signature).unwrap());
+    }
+}
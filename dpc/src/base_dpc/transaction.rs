@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::base_dpc::{record::encrypted_record::*, BaseDPCComponents};
+use crate::base_dpc::{
+    record::{encrypted_record::*, record_serializer::RecordSerializer},
+    BaseDPCComponents,
+};
 use snarkos_algorithms::merkle_tree::MerkleTreeDigest;
 use snarkos_errors::objects::TransactionError;
 use snarkos_models::{
@@ -34,6 +37,13 @@ use std::{
     io::{Read, Result as IoResult, Write},
 };
 
+/// A transaction spending exactly `C::NUM_INPUT_RECORDS` records into exactly `C::NUM_OUTPUT_RECORDS` records.
+///
+/// The unused records of a transaction are dummy records, which are not elided from the transaction:
+/// their serial numbers, commitments and encrypted record hashes are public inputs of the inner and outer
+/// proofs, whose circuits and parameters are set up for the fixed number of records. The dummy records are
+/// indistinguishable from the other records, so every transaction hides its actual number of records.
+/// Transactions of fewer records need components of a smaller arity, with their own parameters.
 #[derive(Derivative)]
 #[derivative(
     Clone(bound = "C: BaseDPCComponents"),
@@ -116,6 +126,43 @@ impl<C: BaseDPCComponents> DPCTransaction<C> {
             encrypted_records,
         }
     }
+
+    /// Returns the size in bytes of the transactions of the components, before they are proven.
+    ///
+    /// Every transaction has the same number of serial numbers, commitments, signatures and encrypted records,
+    /// of fixed sizes, along with a proof of a fixed size and a 32-byte memorandum. The encrypted records are
+    /// ciphertexts of the serialized records, whose payloads are of a fixed size, so a transaction is exactly
+    /// of the estimated size.
+    pub fn estimated_size() -> Result<usize, TransactionError>
+    where
+        <C::OuterSNARK as SNARK>::Proof: Default,
+    {
+        // The ciphertext of a record holds an element for each serialized record element,
+        // and an element for the encryption randomness
+        let num_record_elements =
+            RecordSerializer::<C, C::EncryptionModelParameters, C::EncryptionGroup>::num_serialized_elements();
+        let encrypted_record = EncryptedRecord::<C> {
+            encrypted_record: vec![Default::default(); num_record_elements + 1],
+            final_fq_high_selector: false,
+        };
+
+        let transaction = Self::new(
+            vec![Default::default(); C::NUM_INPUT_RECORDS],
+            vec![Default::default(); C::NUM_OUTPUT_RECORDS],
+            [0u8; 32],
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            AleoAmount::ZERO,
+            Network::Mainnet,
+            vec![Default::default(); C::NUM_INPUT_RECORDS],
+            vec![encrypted_record; C::NUM_OUTPUT_RECORDS],
+        );
+
+        Ok(to_bytes![transaction]?.len())
+    }
 }
 
 impl<C: BaseDPCComponents> Transaction for DPCTransaction<C> {
@@ -57,18 +57,57 @@ pub enum ConsensusError {
     #[error("timestamp more than 2 hours into the future {:?} actual {:?}", _0, _1)]
     FuturisticTimestamp(i64, i64),
 
+    #[error("the records cannot cover a value of {} with at most {} input records", _0, _1)]
+    InsufficientRecords(u64, usize),
+
     #[error("invalid block {:?}", _0)]
     InvalidBlock(Vec<u8>),
 
+    #[error("invalid block value balance {}, expected {}", _0, _1)]
+    InvalidBlockValueBalance(i64, i64),
+
+    #[error("invalid coinbase payout: {}", _0)]
+    InvalidCoinbasePayout(String),
+
     #[error("invalid coinbase transaction")]
     InvalidCoinbaseTransaction,
 
+    #[error("invalid fee estimation target {}, expected 1 to {} blocks", _0, _1)]
+    InvalidFeeEstimationTarget(u32, u32),
+
+    #[error("invalid fee rate {}, expected a finite non-negative fee rate", _0)]
+    InvalidFeeRate(f64),
+
+    #[error("invalid memo: {}", _0)]
+    InvalidMemo(String),
+
+    #[error("invalid miner tag: {}", _0)]
+    InvalidMinerTag(String),
+
+    #[error("invalid prover solution: {}", _0)]
+    InvalidProverSolution(String),
+
+    #[error("invalid mining thread policy: {}", _0)]
+    InvalidThreadPolicy(String),
+
+    #[error("invalid transaction records: {}", _0)]
+    InvalidTransactionRecords(String),
+
+    #[error("the block contains invalid transactions")]
+    InvalidTransactions,
+
+    #[error("the share hash {} is above the share target {}", _1, _0)]
+    LowDifficultyShare(u64, u64),
+
     #[error("block transactions do not hash to merkle root {:?}", _0)]
     MerkleRoot(String),
 
     #[error("{}", _0)]
     Message(String),
 
+    #[error("the mining of the block was cancelled")]
+    MiningCancelled,
+
     #[error("the block has multiple coinbase transactions: {:?}", _0)]
     MultipleCoinbaseTransactions(u32),
 
@@ -93,6 +132,12 @@ pub enum ConsensusError {
     #[error(transparent)]
     PoswError(#[from] PoswError),
 
+    #[error("the private key does not match the account view key")]
+    PrivateKeyMismatch,
+
+    #[error("the proof key does not match the account view key")]
+    ProofKeyMismatch,
+
     #[error("{}", _0)]
     StorageError(StorageError),
 
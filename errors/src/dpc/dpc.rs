@@ -50,6 +50,12 @@ pub enum DPCError {
     #[error("{}", _0)]
     EncryptionError(EncryptionError),
 
+    #[error("the signatures of the transaction authorization are invalid")]
+    InvalidAuthorization,
+
+    #[error("the program id {} is not the hash of its verification key", _0)]
+    InvalidProgramId(String),
+
     #[error("{}", _0)]
     LedgerError(LedgerError),
 
@@ -65,6 +71,9 @@ pub enum DPCError {
     #[error("missing outer snark proving parameters")]
     MissingOuterSnarkProvingParameters,
 
+    #[error("missing the signature of old record {}", _0)]
+    MissingSignature(usize),
+
     #[error("{}", _0)]
     ParametersError(ParametersError),
 
@@ -76,6 +85,9 @@ pub enum DPCError {
 
     #[error("{}", _0)]
     SNARKError(SNARKError),
+
+    #[error("unknown program {}", _0)]
+    UnknownProgram(String),
 }
 
 impl From<AccountError> for DPCError {
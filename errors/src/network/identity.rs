@@ -0,0 +1,39 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+#[derive(Debug, Error)]
+pub enum IdentityError {
+    #[error("{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[error("Invalid identity key in {}: {}", _0, _1)]
+    InvalidKey(String, String),
+
+    #[error("Invalid peer ID {}", _0)]
+    InvalidPeerId(String),
+}
+
+impl From<hex::FromHexError> for IdentityError {
+    fn from(error: hex::FromHexError) -> Self {
+        IdentityError::Crate("hex", format!("{:?}", error))
+    }
+}
+
+impl From<std::io::Error> for IdentityError {
+    fn from(error: std::io::Error) -> Self {
+        IdentityError::Crate("std::io", format!("{:?}", error))
+    }
+}
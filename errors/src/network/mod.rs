@@ -17,6 +17,9 @@
 pub mod connect;
 pub use connect::*;
 
+pub mod identity;
+pub use identity::*;
+
 pub mod protocol;
 pub use protocol::*;
 
@@ -43,6 +43,18 @@ pub enum HandshakeError {
     #[error("Expected nonce {}. Got {}", _0, _1)]
     InvalidNonce(u64, u64),
 
+    #[error("Invalid identity signature from peer {}", _0)]
+    InvalidSignature(SocketAddr),
+
+    #[error("Peer {} with ID {} is not allowed", _0, _1)]
+    PeerNotAllowed(SocketAddr, String),
+
+    #[error("Peer {} has no peer ID to check against the allowed peers", _0)]
+    UnidentifiedPeer(SocketAddr),
+
+    #[error("Peer {} at protocol version {} sent a Verack without peer ID", _0, _1)]
+    UnsignedVerack(SocketAddr, u64),
+
     #[error("{}", _0)]
     ConnectError(ConnectError),
 
@@ -14,11 +14,64 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::{
+    algorithms::CRHError,
+    consensus::ConsensusError,
+    dpc::DPCError,
+    objects::{AccountError, KeystoreError},
+    posw::PoswError,
+    storage::StorageError,
+};
+
 #[derive(Debug, Error)]
 pub enum CliError {
+    #[error("AccountError: {0}")]
+    AccountError(#[from] AccountError),
+
     #[error("{}: {}", _0, _1)]
     Crate(&'static str, String),
 
+    #[error("ConsensusError: {0}")]
+    ConsensusError(#[from] ConsensusError),
+
+    #[error("CRHError: {0}")]
+    CRHError(#[from] CRHError),
+
+    #[error(
+        "the data directory {} is used by {}, stop it or choose another directory with --data-dir",
+        _0,
+        _1
+    )]
+    DataDirLocked(String, String),
+
+    #[error("DPCError: {0}")]
+    DPCError(#[from] DPCError),
+
+    #[error("KeystoreError: {0}")]
+    KeystoreError(#[from] KeystoreError),
+
+    #[error("the {} action requires the {} argument", _0, _1)]
+    MissingArgument(String, String),
+
+    #[error("the {} storage action requires a file", _0)]
+    MissingStorageFile(String),
+
+    #[error("PoswError: {0}")]
+    PoswError(#[from] PoswError),
+
+    #[error("the RPC request failed with the error {}: {}", _0, _1)]
+    RpcError(i64, String),
+
+    #[error(
+        "the universal SRS must support at least {} constraints, the size of the PoSW circuit, not {}",
+        _0,
+        _1
+    )]
+    SrsTooSmall(usize, usize),
+
+    #[error("StorageError: {0}")]
+    StorageError(#[from] StorageError),
+
     #[error("IoError: {0}")]
     IoError(#[from] std::io::Error),
 
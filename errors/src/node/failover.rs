@@ -0,0 +1,48 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::consensus::ConsensusError;
+
+/// The errors of a solo miner pulling its block templates from the RPC servers of several nodes.
+#[derive(Debug, Error)]
+pub enum FailoverError {
+    #[error("{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[error("ConsensusError: {0}")]
+    ConsensusError(#[from] ConsensusError),
+
+    #[error("invalid block template: {}", _0)]
+    InvalidTemplate(String),
+
+    #[error("no node is healthy")]
+    NoHealthyNode,
+
+    #[error("the node answered with the error {}: {}", _0, _1)]
+    Rpc(i64, String),
+}
+
+impl From<hex::FromHexError> for FailoverError {
+    fn from(error: hex::FromHexError) -> Self {
+        FailoverError::InvalidTemplate(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for FailoverError {
+    fn from(error: std::io::Error) -> Self {
+        FailoverError::Crate("std::io", format!("{:?}", error))
+    }
+}
@@ -17,5 +17,11 @@
 pub mod cli;
 pub use cli::*;
 
+pub mod failover;
+pub use failover::*;
+
 pub mod node;
 pub use node::*;
+
+pub mod stratum;
+pub use stratum::*;
@@ -17,8 +17,8 @@
 use crate::{
     algorithms::CRHError,
     consensus::ConsensusError,
-    network::ServerError,
-    node::CliError,
+    network::{IdentityError, ServerError},
+    node::{CliError, FailoverError},
     objects::AccountError,
     storage::StorageError,
 };
@@ -40,6 +40,12 @@ pub enum NodeError {
     #[error("{}: {}", _0, _1)]
     Crate(&'static str, String),
 
+    #[error("{}", _0)]
+    FailoverError(FailoverError),
+
+    #[error("{}", _0)]
+    IdentityError(IdentityError),
+
     #[error("{}", _0)]
     Message(String),
 
@@ -74,12 +80,24 @@ impl From<ConsensusError> for NodeError {
     }
 }
 
+impl From<FailoverError> for NodeError {
+    fn from(error: FailoverError) -> Self {
+        NodeError::FailoverError(error)
+    }
+}
+
 impl From<hex::FromHexError> for NodeError {
     fn from(error: hex::FromHexError) -> Self {
         NodeError::Crate("hex", format!("{:?}", error))
     }
 }
 
+impl From<IdentityError> for NodeError {
+    fn from(error: IdentityError) -> Self {
+        NodeError::IdentityError(error)
+    }
+}
+
 impl From<ServerError> for NodeError {
     fn from(error: ServerError) -> Self {
         NodeError::ServerError(error)
@@ -0,0 +1,85 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{consensus::ConsensusError, network::SendError, storage::StorageError};
+
+/// The errors of the Stratum requests, answered with the code of the error.
+#[derive(Debug, Error)]
+pub enum StratumError {
+    #[error("{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[error("ConsensusError: {0}")]
+    ConsensusError(#[from] ConsensusError),
+
+    #[error("the share was already submitted")]
+    DuplicateShare,
+
+    #[error("invalid params: {}", _0)]
+    InvalidParams(String),
+
+    #[error("the share hash {} is above the share target {}", _1, _0)]
+    LowDifficultyShare(u64, u64),
+
+    #[error("{}", _0)]
+    Message(String),
+
+    #[error("the connection is not subscribed")]
+    NotSubscribed,
+
+    #[error("SendError: {0}")]
+    SendError(#[from] SendError),
+
+    #[error("the job {} is unknown or stale", _0)]
+    StaleJob(String),
+
+    #[error("StorageError: {0}")]
+    StorageError(#[from] StorageError),
+
+    #[error("the worker is not authorized")]
+    Unauthorized,
+
+    #[error("unknown method {}", _0)]
+    UnknownMethod(String),
+}
+
+impl StratumError {
+    /// Returns the code of the error, following the codes of the Stratum pools.
+    pub fn code(&self) -> i64 {
+        match self {
+            StratumError::StaleJob(_) => 21,
+            StratumError::DuplicateShare => 22,
+            StratumError::LowDifficultyShare(..) => 23,
+            StratumError::Unauthorized => 24,
+            StratumError::NotSubscribed => 25,
+            StratumError::UnknownMethod(_) => -32601,
+            StratumError::InvalidParams(_) => -32602,
+            _ => 20,
+        }
+    }
+}
+
+impl From<hex::FromHexError> for StratumError {
+    fn from(error: hex::FromHexError) -> Self {
+        StratumError::InvalidParams(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for StratumError {
+    fn from(error: std::io::Error) -> Self {
+        StratumError::Crate("std::io", format!("{:?}", error))
+    }
+}
@@ -33,12 +33,24 @@ pub enum AccountError {
     #[error("invalid account commitment")]
     InvalidAccountCommitment,
 
+    #[error("invalid address version: {}", _0)]
+    InvalidAddressVersion(u8),
+
     #[error("invalid byte length: {}", _0)]
     InvalidByteLength(usize),
 
     #[error("invalid character length: {}", _0)]
     InvalidCharacterLength(usize),
 
+    #[error("invalid derivation path: {:?}", _0)]
+    InvalidDerivationPath(String),
+
+    #[error("invalid mnemonic: {}", _0)]
+    InvalidMnemonic(String),
+
+    #[error("invalid network prefix: {:?}, expected {:?}", _0, _1)]
+    InvalidNetworkPrefix(String, String),
+
     #[error("invalid prefix: {:?}", _0)]
     InvalidPrefix(String),
 
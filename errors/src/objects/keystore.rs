@@ -0,0 +1,56 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::objects::AccountError;
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("{}", _0)]
+    AccountError(AccountError),
+
+    #[error("{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[error("incorrect passphrase")]
+    IncorrectPassphrase,
+
+    #[error("the keystore is locked")]
+    Locked,
+
+    #[error("{}", _0)]
+    Message(String),
+
+    #[error("unsupported keystore version: {}", _0)]
+    UnsupportedVersion(u32),
+}
+
+impl From<AccountError> for KeystoreError {
+    fn from(error: AccountError) -> Self {
+        KeystoreError::AccountError(error)
+    }
+}
+
+impl From<hex::FromHexError> for KeystoreError {
+    fn from(error: hex::FromHexError) -> Self {
+        KeystoreError::Crate("hex", format!("{:?}", error))
+    }
+}
+
+impl From<std::io::Error> for KeystoreError {
+    fn from(error: std::io::Error) -> Self {
+        KeystoreError::Crate("std::io", format!("{:?}", error))
+    }
+}
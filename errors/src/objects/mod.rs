@@ -23,5 +23,11 @@ pub use amount::*;
 pub mod block;
 pub use block::*;
 
+pub mod keystore;
+pub use keystore::*;
+
+pub mod payment_request;
+pub use payment_request::*;
+
 pub mod transaction;
 pub use transaction::*;
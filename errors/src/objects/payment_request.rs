@@ -0,0 +1,50 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::objects::AccountError;
+
+#[derive(Debug, Error)]
+pub enum PaymentRequestError {
+    #[error("{}", _0)]
+    AccountError(AccountError),
+
+    #[error("duplicate payment request parameter: {}", _0)]
+    DuplicateParameter(String),
+
+    #[error("invalid payment request amount: {}", _0)]
+    InvalidAmount(String),
+
+    #[error("invalid payment request encoding: {}", _0)]
+    InvalidEncoding(String),
+
+    #[error("invalid payment request expiry: {}", _0)]
+    InvalidExpiry(String),
+
+    #[error("invalid payment request memo: {}", _0)]
+    InvalidMemo(String),
+
+    #[error("invalid payment request scheme: {}", _0)]
+    InvalidScheme(String),
+
+    #[error("unsupported payment request parameter: {}", _0)]
+    UnsupportedParameter(String),
+}
+
+impl From<AccountError> for PaymentRequestError {
+    fn from(error: AccountError) -> Self {
+        PaymentRequestError::AccountError(error)
+    }
+}
@@ -24,9 +24,15 @@ pub enum ParametersError {
     #[error("{}: {}", _0, _1)]
     Crate(&'static str, String),
 
+    #[error("the parameters {} are not marked as insecure test parameters", _0)]
+    InsecureMarkerMissing(String),
+
     #[error("{}", _0)]
     Message(String),
 
+    #[error("the parameters {} are not provided", _0)]
+    Missing(String),
+
     #[error("Remote fetch is disabled, enable compiler flag for feature")]
     RemoteFetchDisabled,
 }
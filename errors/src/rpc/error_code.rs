@@ -0,0 +1,88 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+/// The stable error codes of the RPC responses.
+///
+/// The codes defined by the JSON-RPC 2.0 specification are reused where they apply, and the
+/// errors specific to the node are given codes in the server error range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(i64)]
+pub enum RpcErrorCode {
+    /// The request is not a valid JSON-RPC request
+    InvalidRequest = -32600,
+    /// The parameters of the call are invalid
+    InvalidParams = -32602,
+    /// The node failed to handle a valid call
+    InternalError = -32603,
+    /// The requested block, transaction, job or token is unknown to the node
+    NotFound = -32001,
+    /// The given block or transaction is not valid
+    VerificationFailed = -32002,
+    /// The given transaction is valid, but was rejected by the memory pool
+    MempoolRejected = -32003,
+    /// The node cannot answer the call until it is synced
+    NodeSyncing = -32004,
+    /// The call requires credentials or an API token
+    Unauthenticated = -32005,
+    /// The credentials or API token of the call do not grant access to the method
+    PermissionDenied = -32006,
+    /// The requested job is not completed
+    JobNotCompleted = -32007,
+    /// The requested job failed
+    JobFailed = -32008,
+    /// The requested service is disabled or unreachable
+    Unavailable = -32009,
+    /// The call exceeds the rate limit of its method
+    RateLimited = -32029,
+}
+
+impl RpcErrorCode {
+    /// Returns the numeric code sent in the `code` field of the errors.
+    pub fn code(self) -> i64 {
+        self as i64
+    }
+
+    /// Returns the name of the code sent in the `kind` field of the error data.
+    pub fn kind(self) -> &'static str {
+        match self {
+            RpcErrorCode::InvalidRequest => "invalid_request",
+            RpcErrorCode::InvalidParams => "invalid_params",
+            RpcErrorCode::InternalError => "internal_error",
+            RpcErrorCode::NotFound => "not_found",
+            RpcErrorCode::VerificationFailed => "verification_failed",
+            RpcErrorCode::MempoolRejected => "mempool_rejected",
+            RpcErrorCode::NodeSyncing => "node_syncing",
+            RpcErrorCode::Unauthenticated => "unauthenticated",
+            RpcErrorCode::PermissionDenied => "permission_denied",
+            RpcErrorCode::JobNotCompleted => "job_not_completed",
+            RpcErrorCode::JobFailed => "job_failed",
+            RpcErrorCode::Unavailable => "unavailable",
+            RpcErrorCode::RateLimited => "rate_limited",
+        }
+    }
+
+    /// Returns the JSON-RPC error of this code, with the given message.
+    /// The `data` field of the error holds the `kind` of the code.
+    pub fn error<T: Into<String>>(self, message: T) -> jsonrpc_core::Error {
+        let data = vec![("kind".to_string(), jsonrpc_core::Value::from(self.kind()))];
+
+        jsonrpc_core::Error {
+            code: jsonrpc_core::ErrorCode::from(self.code()),
+            message: message.into(),
+            data: Some(jsonrpc_core::Value::Object(data.into_iter().collect())),
+        }
+    }
+}
@@ -14,5 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod error_code;
+pub use error_code::*;
+
 pub mod rpc;
 pub use rpc::*;
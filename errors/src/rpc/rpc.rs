@@ -19,7 +19,8 @@ use crate::{
     consensus::ConsensusError,
     dpc::DPCError,
     network::SendError,
-    objects::{AccountError, BlockError, TransactionError},
+    objects::{AccountError, BlockError, PaymentRequestError, TransactionError},
+    rpc::RpcErrorCode,
     storage::StorageError,
 };
 
@@ -30,9 +31,21 @@ pub enum RpcError {
     #[error("{}", _0)]
     AccountError(AccountError),
 
+    #[error("Authentication Error")]
+    AuthenticationFailed,
+
+    #[error("peer is banned: {}", _0)]
+    BannedPeer(String),
+
     #[error("{}", _0)]
     BlockError(BlockError),
 
+    #[error("failed to reload the configuration: {}", _0)]
+    ConfigReloadFailed(String),
+
+    #[error("the configuration of the node cannot be reloaded")]
+    ConfigReloadDisabled,
+
     #[error("{}", _0)]
     ConsensusError(ConsensusError),
 
@@ -45,15 +58,69 @@ pub enum RpcError {
     #[error("{}", _0)]
     DPCError(DPCError),
 
+    #[error("the node is not running a development network")]
+    DevModeDisabled,
+
     #[error("invalid block hash: {}", _0)]
     InvalidBlockHash(String),
 
+    #[error("invalid commitment: {}", _0)]
+    InvalidCommitment(String),
+
+    #[error("invalid IPC config: {}", _0)]
+    InvalidIpcConfig(String),
+
+    #[error("invalid log filter: {}", _0)]
+    InvalidLogFilter(String),
+
     #[error("invalid metadata: {}", _0)]
     InvalidMetadata(String),
 
+    #[error("{}", _0)]
+    InvalidParams(String),
+
+    #[error("invalid peer address: {}", _0)]
+    InvalidPeerAddress(String),
+
+    #[error("invalid range: {}", _0)]
+    InvalidRange(String),
+
+    #[error("invalid serial number: {}", _0)]
+    InvalidSerialNumber(String),
+
+    #[error("invalid TLS config: {}", _0)]
+    InvalidTlsConfig(String),
+
+    #[error("invalid transaction: {}", _0)]
+    InvalidTransaction(String),
+
+    #[error("invalid transaction inputs: {}", _0)]
+    InvalidTransactionInputs(String),
+
+    #[error("invalid webhook config: {}", _0)]
+    InvalidWebhookConfig(String),
+
+    #[error("job failed: {}", _0)]
+    JobFailed(String),
+
+    #[error("job is not completed: {}", _0)]
+    JobNotCompleted(String),
+
+    #[error("logging is disabled")]
+    LoggingDisabled,
+
     #[error("{}", _0)]
     Message(String),
 
+    #[error("the node is syncing")]
+    NodeSyncing,
+
+    #[error("{}", _0)]
+    PaymentRequestError(PaymentRequestError),
+
+    #[error("Permission Error")]
+    PermissionDenied,
+
     #[error("{}", _0)]
     SendError(SendError),
 
@@ -62,6 +129,27 @@ pub enum RpcError {
 
     #[error("{}", _0)]
     TransactionError(TransactionError),
+
+    #[error("transaction rejected: {}", _0)]
+    TransactionRejected(String),
+
+    #[error("unknown commitment: {}", _0)]
+    UnknownCommitment(String),
+
+    #[error("unknown job: {}", _0)]
+    UnknownJob(String),
+
+    #[error("unknown ledger digest: {}", _0)]
+    UnknownLedgerDigest(String),
+
+    #[error("unknown API token")]
+    UnknownToken,
+
+    #[error("account view key {} is not registered with the wallet", _0)]
+    UnknownWalletAccount(String),
+
+    #[error("unsupported job method: {}", _0)]
+    UnsupportedJobMethod(String),
 }
 
 impl From<AccountError> for RpcError {
@@ -94,6 +182,12 @@ impl From<DPCError> for RpcError {
     }
 }
 
+impl From<PaymentRequestError> for RpcError {
+    fn from(error: PaymentRequestError) -> Self {
+        RpcError::PaymentRequestError(error)
+    }
+}
+
 impl From<SendError> for RpcError {
     fn from(error: SendError) -> Self {
         RpcError::SendError(error)
@@ -142,8 +236,79 @@ impl From<std::boxed::Box<dyn std::any::Any + std::marker::Send>> for RpcError {
     }
 }
 
+impl RpcError {
+    /// Returns the stable error code of the error, sent to the clients.
+    pub fn code(&self) -> RpcErrorCode {
+        match self {
+            RpcError::AuthenticationFailed => RpcErrorCode::Unauthenticated,
+            RpcError::ConsensusError(ConsensusError::InvalidFeeEstimationTarget(..))
+            | RpcError::ConsensusError(ConsensusError::InvalidFeeRate(..))
+            | RpcError::ConsensusError(ConsensusError::ProofKeyMismatch) => RpcErrorCode::InvalidParams,
+            RpcError::ConsensusError(ConsensusError::StorageError(error)) | RpcError::StorageError(error) => {
+                storage_error_code(error)
+            }
+            RpcError::ConsensusError(_) | RpcError::InvalidTransaction(_) => RpcErrorCode::VerificationFailed,
+            RpcError::AccountError(_)
+            | RpcError::BannedPeer(_)
+            | RpcError::BlockError(_)
+            | RpcError::Crate("hex", _)
+            | RpcError::InvalidBlockHash(_)
+            | RpcError::InvalidCommitment(_)
+            | RpcError::InvalidIpcConfig(_)
+            | RpcError::InvalidLogFilter(_)
+            | RpcError::InvalidMetadata(_)
+            | RpcError::InvalidParams(_)
+            | RpcError::InvalidPeerAddress(_)
+            | RpcError::InvalidRange(_)
+            | RpcError::InvalidSerialNumber(_)
+            | RpcError::InvalidTlsConfig(_)
+            | RpcError::InvalidTransactionInputs(_)
+            | RpcError::InvalidWebhookConfig(_)
+            | RpcError::PaymentRequestError(_)
+            | RpcError::TransactionError(_)
+            | RpcError::UnsupportedJobMethod(_) => RpcErrorCode::InvalidParams,
+            RpcError::JobFailed(_) => RpcErrorCode::JobFailed,
+            RpcError::JobNotCompleted(_) => RpcErrorCode::JobNotCompleted,
+            RpcError::ConfigReloadDisabled
+            | RpcError::DevModeDisabled
+            | RpcError::LoggingDisabled
+            | RpcError::SendError(_) => RpcErrorCode::Unavailable,
+            RpcError::NodeSyncing => RpcErrorCode::NodeSyncing,
+            RpcError::PermissionDenied => RpcErrorCode::PermissionDenied,
+            RpcError::TransactionRejected(_) => RpcErrorCode::MempoolRejected,
+            RpcError::UnknownCommitment(_)
+            | RpcError::UnknownJob(_)
+            | RpcError::UnknownLedgerDigest(_)
+            | RpcError::UnknownToken
+            | RpcError::UnknownWalletAccount(_) => RpcErrorCode::NotFound,
+            RpcError::ConfigReloadFailed(_)
+            | RpcError::Crate(..)
+            | RpcError::CRHError(_)
+            | RpcError::DPCError(_)
+            | RpcError::Message(_) => RpcErrorCode::InternalError,
+        }
+    }
+}
+
+/// Returns the error code of a storage error, which is `NotFound` if the requested item is missing.
+fn storage_error_code(error: &StorageError) -> RpcErrorCode {
+    match error {
+        StorageError::InvalidTransactionId(_)
+        | StorageError::MissingBlockHash(_)
+        | StorageError::MissingBlockHeader(_)
+        | StorageError::MissingBlockNumber(_)
+        | StorageError::MissingBlockTransactions(_)
+        | StorageError::MissingChildBlock(_)
+        | StorageError::MissingCommitment(..)
+        | StorageError::MissingTransactionMeta(_)
+        | StorageError::MissingValue(_)
+        | StorageError::PrunedBlockTransactions(_) => RpcErrorCode::NotFound,
+        _ => RpcErrorCode::InternalError,
+    }
+}
+
 impl From<RpcError> for jsonrpc_core::Error {
-    fn from(_error: RpcError) -> Self {
-        jsonrpc_core::Error::invalid_request()
+    fn from(error: RpcError) -> Self {
+        error.code().error(error.to_string())
     }
 }
@@ -48,9 +48,18 @@ pub enum StorageError {
     #[error("existing serial number {:?}", _0)]
     ExistingSn(Vec<u8>),
 
+    #[error("existing ledger at path {}", _0)]
+    ExistingLedger(String),
+
     #[error("Can't decommit the genesis block")]
     InvalidBlockDecommit,
 
+    #[error("invalid block export: {}", _0)]
+    InvalidBlockExport(String),
+
+    #[error("unsupported block export version {}", _0)]
+    InvalidBlockExportVersion(u32),
+
     #[error("Can't remove a canon block with hash")]
     InvalidBlockRemovalCanon(String),
 
@@ -60,9 +69,32 @@ pub enum StorageError {
     #[error("invalid column family {}", _0)]
     InvalidColumnFamily(u32),
 
+    #[error(
+        "the genesis block {} of the ledger is not the genesis block {} of the network",
+        _0,
+        _1
+    )]
+    InvalidGenesisBlock(String, String),
+
     #[error("missing outpoint with transaction with id {} and index {}", _0, _1)]
     InvalidOutpoint(String, usize),
 
+    #[error(
+        "cannot keep the transactions of only {} blocks, the deepest fork is {} blocks",
+        _0,
+        _1
+    )]
+    InvalidPruneRetention(u32, u32),
+
+    #[error("unsupported storage schema version {}. The latest supported version is {}", _0, _1)]
+    InvalidSchemaVersion(u32, u32),
+
+    #[error("invalid ledger snapshot: {}", _0)]
+    InvalidSnapshot(String),
+
+    #[error("unsupported ledger snapshot version {}", _0)]
+    InvalidSnapshotVersion(u32),
+
     #[error("missing transaction with id {}", _0)]
     InvalidTransactionId(String),
 
@@ -84,6 +116,9 @@ pub enum StorageError {
     #[error("missing child block hashes value for block hash {}", _0)]
     MissingChildBlock(String),
 
+    #[error("missing commitment {} in the ledger at block {}", _0, _1)]
+    MissingCommitment(String, u32),
+
     #[error("missing current commitment index")]
     MissingCurrentCmIndex,
 
@@ -111,6 +146,9 @@ pub enum StorageError {
     #[error("missing genesis serial number")]
     MissingGenesisSn,
 
+    #[error("missing ledger checkpoint for block {}", _0)]
+    MissingLedgerCheckpoint(u32),
+
     #[error("missing transaction meta value for transaction id {}", _0)]
     MissingTransactionMeta(String),
 
@@ -120,6 +158,15 @@ pub enum StorageError {
     #[error("Null Error {:?}", _0)]
     NullError(()),
 
+    #[error("the transactions of block {} have been pruned", _0)]
+    PrunedBlockTransactions(String),
+
+    #[error("cannot write to a read-only secondary storage instance")]
+    ReadOnlyStorage,
+
+    #[error("account view key {} is not registered with the record index", _0)]
+    UnregisteredViewKey(String),
+
     #[error("{}", _0)]
     BlockError(BlockError),
 
@@ -32,5 +32,26 @@ pub trait CommitmentScheme: Sized + Clone + From<<Self as CommitmentScheme>::Par
 
     fn commit(&self, input: &[u8], randomness: &Self::Randomness) -> Result<Self::Output, CommitmentError>;
 
+    /// Returns the commitments of the inputs with their randomness, in order.
+    fn batch_commit(
+        &self,
+        inputs: &[Vec<u8>],
+        randomness: &[Self::Randomness],
+    ) -> Result<Vec<Self::Output>, CommitmentError> {
+        if inputs.len() != randomness.len() {
+            return Err(CommitmentError::Message(format!(
+                "expected randomness for {} inputs, found {}",
+                inputs.len(),
+                randomness.len()
+            )));
+        }
+
+        inputs
+            .iter()
+            .zip(randomness)
+            .map(|(input, randomness)| self.commit(input, randomness))
+            .collect()
+    }
+
     fn parameters(&self) -> &Self::Parameters;
 }
@@ -28,7 +28,7 @@ pub trait Transaction: Clone + Eq + FromBytes + ToBytes {
     type ProgramCommitment: Clone + Eq + Hash + FromBytes + ToBytes;
     type SerialNumber: Clone + Eq + Hash + FromBytes + ToBytes;
     type EncryptedRecord: Clone + Eq + FromBytes + ToBytes;
-    type ValueBalance: Clone + Eq + FromBytes + ToBytes;
+    type ValueBalance: Clone + Eq + Ord + Into<i64> + FromBytes + ToBytes;
 
     /// Returns the transaction identifier.
     fn transaction_id(&self) -> Result<[u8; 32], TransactionError>;
@@ -0,0 +1,160 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::time::delay_for;
+
+/// The limiter of the bytes sent to all the peers of the node.
+pub static UPLOAD_LIMITER: Lazy<BandwidthLimiter> = Lazy::new(BandwidthLimiter::unlimited);
+
+/// The limiter of the bytes received from all the peers of the node.
+pub static DOWNLOAD_LIMITER: Lazy<BandwidthLimiter> = Lazy::new(BandwidthLimiter::unlimited);
+
+/// The bytes available to transfer at a given time.
+#[derive(Debug)]
+struct Bucket {
+    /// The bytes available, negative if the last transfers went over the rate
+    available: f64,
+    updated: Instant,
+}
+
+/// A token bucket limiting the rate of the bytes transferred by the channels to the peers.
+/// The bucket holds at most one second of transfers, so the rate can be exceeded by a burst
+/// after an idle second only.
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    /// The maximum number of bytes per second, or zero if the transfers are unlimited
+    rate: AtomicU64,
+    bucket: Mutex<Bucket>,
+}
+
+impl BandwidthLimiter {
+    /// Creates a limiter that lets every transfer through.
+    pub fn unlimited() -> Self {
+        Self {
+            rate: AtomicU64::new(0),
+            bucket: Mutex::new(Bucket {
+                available: 0.0,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Creates a limiter of the given number of bytes per second.
+    pub fn new(bytes_per_second: u64) -> Self {
+        let limiter = Self::unlimited();
+        limiter.set_rate(Some(bytes_per_second));
+        limiter
+    }
+
+    /// Returns the maximum number of bytes per second, if the transfers are limited.
+    pub fn rate(&self) -> Option<u64> {
+        match self.rate.load(Ordering::SeqCst) {
+            0 => None,
+            rate => Some(rate),
+        }
+    }
+
+    /// Sets the maximum number of bytes per second, or removes the limit.
+    pub fn set_rate(&self, bytes_per_second: Option<u64>) {
+        let mut bucket = self.bucket.lock().expect("bandwidth lock poisoned");
+        let rate = bytes_per_second.unwrap_or(0);
+
+        bucket.available = rate as f64;
+        bucket.updated = Instant::now();
+        self.rate.store(rate, Ordering::SeqCst);
+    }
+
+    /// Takes the given number of bytes from the bucket, and returns the time to wait for the bucket
+    /// to refill before the bytes are transferred.
+    pub fn reserve(&self, bytes: usize) -> Duration {
+        let rate = match self.rate() {
+            Some(rate) => rate as f64,
+            None => return Duration::from_secs(0),
+        };
+
+        let mut bucket = self.bucket.lock().expect("bandwidth lock poisoned");
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * rate;
+
+        bucket.available = (bucket.available + refill).min(rate) - bytes as f64;
+        bucket.updated = now;
+
+        match bucket.available {
+            available if available >= 0.0 => Duration::from_secs(0),
+            available => Duration::from_secs_f64(-available / rate),
+        }
+    }
+
+    /// Waits until the given number of bytes can be transferred without exceeding the rate.
+    pub async fn consume(&self, bytes: usize) {
+        let delay = self.reserve(bytes);
+
+        if delay > Duration::from_secs(0) {
+            delay_for(delay).await;
+        }
+    }
+}
+
+/// Sets the maximum number of bytes per second sent to and received from all the peers of the node.
+pub fn set_bandwidth_limits(upload: Option<u64>, download: Option<u64>) {
+    UPLOAD_LIMITER.set_rate(upload);
+    DOWNLOAD_LIMITER.set_rate(download);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_transfers() {
+        let limiter = BandwidthLimiter::unlimited();
+
+        assert_eq!(limiter.rate(), None);
+        assert_eq!(limiter.reserve(usize::MAX), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn delay_transfers_over_the_rate() {
+        let limiter = BandwidthLimiter::new(1000);
+
+        // The bucket starts with one second of transfers
+        assert_eq!(limiter.reserve(1000), Duration::from_secs(0));
+
+        // The next transfer waits for the bucket to refill
+        let delay = limiter.reserve(500);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+
+        // The transfers after it wait for the ones before
+        assert!(limiter.reserve(500) > Duration::from_millis(900));
+    }
+
+    #[test]
+    fn remove_limit() {
+        let limiter = BandwidthLimiter::new(1);
+        limiter.reserve(1000);
+
+        limiter.set_rate(None);
+        assert_eq!(limiter.reserve(1000), Duration::from_secs(0));
+    }
+}
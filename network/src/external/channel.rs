@@ -14,25 +14,35 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::external::message::{
-    read::{read_header, read_message},
-    Message,
-    MessageHeader,
-    MessageName,
+use crate::external::{
+    message::{
+        read::{read_header, read_message},
+        Message,
+        MessageHeader,
+        MessageName,
+    },
+    PeerStats,
+    DOWNLOAD_LIMITER,
+    UPLOAD_LIMITER,
 };
 use snarkos_errors::network::ConnectError;
 
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    net::{Shutdown, SocketAddr},
+    sync::Arc,
+};
 use tokio::{io::AsyncWriteExt, net::TcpStream, sync::Mutex};
 
 /// A channel for reading and writing messages to a peer.
 /// The channel manages two streams to allow for simultaneous reading and writing.
 /// Each stream is protected by an Arc + Mutex to allow for channel cloning.
+/// The statistics of the connection are kept by the channels derived from each other.
 #[derive(Clone, Debug)]
 pub struct Channel {
     pub address: SocketAddr,
     pub reader: Arc<Mutex<TcpStream>>,
     pub writer: Arc<Mutex<TcpStream>>,
+    pub stats: Arc<PeerStats>,
 }
 
 impl Channel {
@@ -45,6 +55,7 @@ impl Channel {
             address,
             reader,
             writer,
+            stats: Default::default(),
         })
     }
 
@@ -56,6 +67,7 @@ impl Channel {
             address,
             reader: stream.clone(),
             writer: stream,
+            stats: Default::default(),
         })
     }
 
@@ -68,6 +80,7 @@ impl Channel {
             address,
             reader: stream.clone(),
             writer: stream,
+            stats: Default::default(),
         })
     }
 
@@ -77,6 +90,7 @@ impl Channel {
             address,
             reader: self.reader.clone(),
             writer: self.writer.clone(),
+            stats: self.stats.clone(),
         }
     }
 
@@ -86,6 +100,7 @@ impl Channel {
             address: self.address,
             reader,
             writer: self.writer.clone(),
+            stats: self.stats.clone(),
         }
     }
 
@@ -95,6 +110,7 @@ impl Channel {
             address,
             reader: self.reader.clone(),
             writer: Arc::new(Mutex::new(TcpStream::connect(address).await?)),
+            stats: self.stats.clone(),
         })
     }
 
@@ -105,13 +121,25 @@ impl Channel {
         let serialized = message.serialize()?;
         let header = MessageHeader::new(M::name(), serialized.len() as u32);
 
+        let header = header.serialize()?;
+
+        UPLOAD_LIMITER.consume(header.len() + serialized.len()).await;
+
         let mut writer = self.writer.lock().await;
-        writer.write_all(&header.serialize()?).await?;
+        writer.write_all(&header).await?;
         writer.write_all(&serialized).await?;
 
+        self.stats.record_sent(header.len() + serialized.len());
+
         Ok(())
     }
 
+    /// Shuts down the writer stream, which closes the connection to the peer.
+    /// The reader stream is left to the connection thread, which stops when the peer hangs up.
+    pub async fn shutdown(&self) -> Result<(), ConnectError> {
+        Ok(self.writer.lock().await.shutdown(Shutdown::Both)?)
+    }
+
     /// Reads a message header + message.
     pub async fn read(&self) -> Result<(MessageName, Vec<u8>), ConnectError> {
         let header = read_header(&mut *self.reader.lock().await).await?;
@@ -122,10 +150,15 @@ impl Channel {
             self.address
         );
 
-        Ok((
-            header.name,
-            read_message(&mut *self.reader.lock().await, header.len as usize).await?,
-        ))
+        let message = read_message(&mut *self.reader.lock().await, header.len as usize).await?;
+
+        // The header has a fixed size of 16 bytes
+        self.stats.record_received(16 + message.len());
+
+        // The next message is read once the bytes of this one fit in the download rate
+        DOWNLOAD_LIMITER.consume(16 + message.len()).await;
+
+        Ok((header.name, message))
     }
 }
 
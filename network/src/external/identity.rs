@@ -0,0 +1,201 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::network::IdentityError;
+
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer, Verifier, SECRET_KEY_LENGTH};
+use std::{
+    convert::TryFrom,
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    str::FromStr,
+};
+
+/// The size of a peer ID, in bytes.
+pub const PEER_ID_SIZE: usize = 32;
+
+/// The size of a signature of a node identity, in bytes.
+pub const IDENTITY_SIGNATURE_SIZE: usize = 64;
+
+/// The stable identifier of a node, which is the public key of its identity.
+/// Unlike the address of a node, it does not change when the node moves to another IP address or port.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeerId([u8; PEER_ID_SIZE]);
+
+impl PeerId {
+    /// Returns the peer ID of the given public key bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IdentityError> {
+        let public_key = PublicKey::from_bytes(bytes).map_err(|_| IdentityError::InvalidPeerId(hex::encode(bytes)))?;
+
+        Ok(Self(public_key.to_bytes()))
+    }
+
+    /// Returns the public key bytes of the peer ID.
+    pub fn as_bytes(&self) -> &[u8; PEER_ID_SIZE] {
+        &self.0
+    }
+
+    /// Returns true if the signature of the message was made by the identity of this peer ID.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match (PublicKey::from_bytes(&self.0), Signature::try_from(signature)) {
+            (Ok(public_key), Ok(signature)) => public_key.verify(message, &signature).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for PeerId {
+    type Err = IdentityError;
+
+    fn from_str(peer_id: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(peer_id).map_err(|_| IdentityError::InvalidPeerId(peer_id.to_string()))?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PeerId({})", self)
+    }
+}
+
+/// The identity keypair of a node, which signs its handshakes to prove its peer ID.
+pub struct NodeIdentity {
+    keypair: Keypair,
+}
+
+impl NodeIdentity {
+    /// Generates a new random identity.
+    pub fn generate() -> Self {
+        Self {
+            keypair: Keypair::generate(&mut rand::thread_rng()),
+        }
+    }
+
+    /// Returns the identity of the given secret key bytes.
+    pub fn from_secret_key(bytes: &[u8]) -> Result<Self, IdentityError> {
+        let secret = SecretKey::from_bytes(bytes)
+            .map_err(|error| IdentityError::Crate("ed25519_dalek", format!("{:?}", error)))?;
+        let public = PublicKey::from(&secret);
+
+        Ok(Self {
+            keypair: Keypair { secret, public },
+        })
+    }
+
+    /// Loads the identity stored in the given key file, or generates a new identity and stores it
+    /// in the key file if the file does not exist yet. The key file holds the hex encoded secret key.
+    pub fn load_or_generate(path: &Path) -> Result<Self, IdentityError> {
+        if path.exists() {
+            let encoded = fs::read_to_string(path)?;
+            let bytes = hex::decode(encoded.trim())
+                .map_err(|error| IdentityError::InvalidKey(path.display().to_string(), error.to_string()))?;
+
+            if bytes.len() != SECRET_KEY_LENGTH {
+                return Err(IdentityError::InvalidKey(
+                    path.display().to_string(),
+                    format!("expected {} bytes, found {}", SECRET_KEY_LENGTH, bytes.len()),
+                ));
+            }
+
+            return Self::from_secret_key(&bytes);
+        }
+
+        let identity = Self::generate();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path)?;
+        file.write_all(hex::encode(identity.keypair.secret.as_bytes()).as_bytes())?;
+
+        Ok(identity)
+    }
+
+    /// Returns the peer ID of the identity.
+    pub fn peer_id(&self) -> PeerId {
+        PeerId(self.keypair.public.to_bytes())
+    }
+
+    /// Signs the message with the identity.
+    pub fn sign(&self, message: &[u8]) -> [u8; IDENTITY_SIGNATURE_SIZE] {
+        self.keypair.sign(message).to_bytes()
+    }
+}
+
+impl fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NodeIdentity")
+            .field("peer_id", &self.peer_id())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_signature() {
+        let identity = NodeIdentity::generate();
+        let signature = identity.sign(b"message");
+
+        assert!(identity.peer_id().verify(b"message", &signature));
+        assert!(!identity.peer_id().verify(b"another message", &signature));
+        assert!(!NodeIdentity::generate().peer_id().verify(b"message", &signature));
+    }
+
+    #[test]
+    fn test_peer_id_string() {
+        let peer_id = NodeIdentity::generate().peer_id();
+
+        assert_eq!(peer_id, peer_id.to_string().parse::<PeerId>().unwrap());
+        assert!("not a peer ID".parse::<PeerId>().is_err());
+        assert!("0011".parse::<PeerId>().is_err());
+    }
+
+    #[test]
+    fn test_persistent_identity() {
+        let path = std::env::temp_dir().join(format!("snarkos_identity_{}", rand::random::<u64>()));
+
+        let identity = NodeIdentity::load_or_generate(&path).unwrap();
+        let reloaded = NodeIdentity::load_or_generate(&path).unwrap();
+        assert_eq!(identity.peer_id(), reloaded.peer_id());
+
+        fs::write(&path, "not a key").unwrap();
+        assert!(NodeIdentity::load_or_generate(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
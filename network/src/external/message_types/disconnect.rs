@@ -0,0 +1,67 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_errors::network::message::MessageError;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/disconnect.md"))]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Disconnect {
+    /// The reason of the disconnection, which may be empty
+    pub reason: String,
+}
+
+impl Disconnect {
+    pub fn new(reason: &str) -> Self {
+        Self {
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl Message for Disconnect {
+    fn name() -> MessageName {
+        MessageName::from("disconnect")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        Ok(Self {
+            reason: String::from_utf8_lossy(&vec).into_owned(),
+        })
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(self.reason.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disconnect() {
+        let message = Disconnect::new("the node is shutting down");
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = Disconnect::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+
+        // The disconnections without a reason have an empty payload
+        assert_eq!(Disconnect::deserialize(vec![]).unwrap(), Disconnect::default());
+    }
+}
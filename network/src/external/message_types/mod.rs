@@ -19,6 +19,11 @@ pub mod block;
 #[doc(inline)]
 pub use block::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/disconnect.md"))]
+pub mod disconnect;
+#[doc(inline)]
+pub use disconnect::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_block.md"))]
 pub mod getblock;
 #[doc(inline)]
@@ -59,6 +64,11 @@ pub mod pong;
 #[doc(inline)]
 pub use pong::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/pruned.md"))]
+pub mod pruned;
+#[doc(inline)]
+pub use pruned::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/sync.md"))]
 pub mod sync;
 #[doc(inline)]
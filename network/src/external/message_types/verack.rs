@@ -14,7 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::external::message::{Message, MessageName};
+use crate::external::{
+    message::{Message, MessageName},
+    message_types::IDENTITY_PROTOCOL_VERSION,
+    NodeIdentity,
+    PeerId,
+    IDENTITY_SIGNATURE_SIZE,
+    PEER_ID_SIZE,
+};
 use snarkos_errors::network::message::MessageError;
 
 use std::net::SocketAddr;
@@ -30,16 +37,72 @@ pub struct Verack {
 
     /// Network address of sending node
     pub address_sender: SocketAddr,
+
+    /// Peer ID of sending node, or `None` for the nodes before `IDENTITY_PROTOCOL_VERSION`
+    pub peer_id: Option<PeerId>,
+
+    /// Signature of the nonce and the addresses by the identity of sending node
+    pub signature: Vec<u8>,
 }
 
 impl Verack {
-    pub fn new(nonce: u64, address_receiver: SocketAddr, address_sender: SocketAddr) -> Self {
+    pub fn new(nonce: u64, address_receiver: SocketAddr, address_sender: SocketAddr, identity: &NodeIdentity) -> Self {
+        let signature = identity
+            .sign(&Self::signed_bytes(nonce, address_receiver, address_sender))
+            .to_vec();
+
+        Self {
+            nonce,
+            address_receiver,
+            address_sender,
+            peer_id: Some(identity.peer_id()),
+            signature,
+        }
+    }
+
+    /// Returns a Verack without identity, for the peers before `IDENTITY_PROTOCOL_VERSION`.
+    pub fn unsigned(nonce: u64, address_receiver: SocketAddr, address_sender: SocketAddr) -> Self {
         Self {
             nonce,
             address_receiver,
             address_sender,
+            peer_id: None,
+            signature: vec![],
         }
     }
+
+    /// Returns a Verack signed by the identity if the peer protocol version supports it, or an unsigned Verack.
+    pub fn for_version(
+        version: u64,
+        nonce: u64,
+        address_receiver: SocketAddr,
+        address_sender: SocketAddr,
+        identity: &NodeIdentity,
+    ) -> Self {
+        if version >= IDENTITY_PROTOCOL_VERSION {
+            Self::new(nonce, address_receiver, address_sender, identity)
+        } else {
+            Self::unsigned(nonce, address_receiver, address_sender)
+        }
+    }
+
+    /// Returns true if the Verack is signed by the identity of its peer ID.
+    pub fn verify(&self) -> bool {
+        let message = Self::signed_bytes(self.nonce, self.address_receiver, self.address_sender);
+
+        match self.peer_id {
+            Some(peer_id) => peer_id.verify(&message, &self.signature),
+            None => false,
+        }
+    }
+
+    /// Returns the bytes signed by the sending node, binding its identity to the acknowledged nonce.
+    fn signed_bytes(nonce: u64, address_receiver: SocketAddr, address_sender: SocketAddr) -> Vec<u8> {
+        let mut bytes = nonce.to_le_bytes().to_vec();
+        bytes.extend_from_slice(address_receiver.to_string().as_bytes());
+        bytes.extend_from_slice(address_sender.to_string().as_bytes());
+        bytes
+    }
 }
 
 impl Message for Verack {
@@ -48,14 +111,29 @@ impl Message for Verack {
     }
 
     fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
-        if vec.len() != 28 {
-            return Err(MessageError::InvalidLength(vec.len(), 28));
+        // The Veracks of the nodes before `IDENTITY_PROTOCOL_VERSION` do not carry an identity
+        if vec.len() != 28 && vec.len() != 28 + PEER_ID_SIZE + IDENTITY_SIGNATURE_SIZE {
+            return Err(MessageError::InvalidLength(
+                vec.len(),
+                28 + PEER_ID_SIZE + IDENTITY_SIGNATURE_SIZE,
+            ));
         }
 
+        let (peer_id, signature) = if vec.len() > 28 {
+            let peer_id = PeerId::from_bytes(&vec[28..28 + PEER_ID_SIZE])
+                .map_err(|error| MessageError::Message(error.to_string()))?;
+
+            (Some(peer_id), vec[28 + PEER_ID_SIZE..].to_vec())
+        } else {
+            (None, vec![])
+        };
+
         Ok(Self {
             nonce: bincode::deserialize(&vec[0..8])?,
             address_receiver: bincode::deserialize(&vec[8..18])?,
             address_sender: bincode::deserialize(&vec[18..28])?,
+            peer_id,
+            signature,
         })
     }
 
@@ -64,6 +142,10 @@ impl Message for Verack {
         writer.extend_from_slice(&bincode::serialize(&self.nonce)?);
         writer.extend_from_slice(&bincode::serialize(&self.address_receiver)?);
         writer.extend_from_slice(&bincode::serialize(&self.address_sender)?);
+        if let Some(peer_id) = &self.peer_id {
+            writer.extend_from_slice(peer_id.as_bytes());
+            writer.extend_from_slice(&self.signature);
+        }
         Ok(writer)
     }
 }
@@ -78,11 +160,55 @@ mod tests {
     fn test_verack() {
         let version = Version::new(1u64, 1u32, random_socket_address(), random_socket_address());
 
-        let message = Verack::new(version.nonce, version.address_sender, version.address_receiver);
+        let message = Verack::new(
+            version.nonce,
+            version.address_sender,
+            version.address_receiver,
+            &NodeIdentity::generate(),
+        );
 
         let serialized = message.serialize().unwrap();
         let deserialized = Verack::deserialize(serialized).unwrap();
 
         assert_eq!(message, deserialized);
+        assert!(deserialized.verify());
+    }
+
+    #[test]
+    fn test_verack_forged_signature() {
+        let version = Version::new(1u64, 1u32, random_socket_address(), random_socket_address());
+
+        let mut message = Verack::new(
+            version.nonce,
+            version.address_sender,
+            version.address_receiver,
+            &NodeIdentity::generate(),
+        );
+        message.peer_id = Some(NodeIdentity::generate().peer_id());
+
+        assert!(!message.verify());
+    }
+
+    #[test]
+    fn test_verack_unsigned() {
+        let version = Version::new(1u64, 1u32, random_socket_address(), random_socket_address());
+
+        let message = Verack::for_version(
+            version.version,
+            version.nonce,
+            version.address_sender,
+            version.address_receiver,
+            &NodeIdentity::generate(),
+        );
+
+        // The Verack of the nodes before the identities is 28 bytes long
+        let serialized = message.serialize().unwrap();
+        assert_eq!(28, serialized.len());
+
+        let deserialized = Verack::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+        assert_eq!(None, deserialized.peer_id);
+        assert!(!deserialized.verify());
     }
 }
@@ -21,6 +21,15 @@ use chrono::Utc;
 use rand::Rng;
 use std::net::SocketAddr;
 
+/// The protocol version of this node.
+pub const PROTOCOL_VERSION: u64 = 3;
+
+/// The first protocol version whose Verack messages carry the peer ID of their sender.
+pub const IDENTITY_PROTOCOL_VERSION: u64 = 2;
+
+/// The first protocol version answering the requests of pruned blocks with a Pruned message.
+pub const PRUNED_PROTOCOL_VERSION: u64 = 3;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/version.md"))]
 #[derive(Debug, PartialEq, Clone)]
 pub struct Version {
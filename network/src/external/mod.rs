@@ -14,9 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod bandwidth;
+pub use bandwidth::*;
+
 pub mod channel;
 pub use channel::*;
 
+pub mod identity;
+pub use identity::*;
+
 pub mod message;
 pub use message::*;
 
@@ -25,6 +31,9 @@ pub mod message_types;
 #[doc(inline)]
 pub use message_types::*;
 
+pub mod peer_stats;
+pub use peer_stats::*;
+
 pub mod propagate;
 pub use propagate::*;
 
@@ -0,0 +1,155 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::PeerId;
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// The misbehavior score of a message that could not be decoded or is not part of the protocol.
+pub const MISBEHAVIOR_MALFORMED_MESSAGE: u32 = 10;
+
+/// The misbehavior score of an announced block that was rejected by consensus.
+pub const MISBEHAVIOR_INVALID_BLOCK: u32 = 20;
+
+/// The state of a peer learned from the messages exchanged with it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerState {
+    /// The peer ID proven by the peer in its handshake
+    pub peer_id: Option<PeerId>,
+
+    /// The protocol version of the latest Version message of the peer
+    pub version: Option<u64>,
+
+    /// The block height of the latest Version message of the peer
+    pub height: Option<u32>,
+
+    /// The hash of the latest block announced by the peer
+    pub last_block_hash: Option<Vec<u8>>,
+
+    /// The time the latest block was announced by the peer
+    pub last_block_time: Option<i64>,
+
+    /// The round trip time of the latest accepted ping
+    pub rtt: Option<Duration>,
+
+    /// The accumulated score of the peer misbehaviors
+    pub misbehavior_score: u32,
+}
+
+/// Statistics of the connection to a peer, shared by the clones of its channel.
+#[derive(Debug, Default)]
+pub struct PeerStats {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    state: Mutex<PeerState>,
+}
+
+impl PeerStats {
+    /// Returns the number of bytes sent to the peer.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes received from the peer.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// Returns a copy of the state of the peer.
+    pub fn state(&self) -> PeerState {
+        self.state.lock().expect("the peer state lock is poisoned").clone()
+    }
+
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Records the peer ID proven by the peer.
+    pub fn record_peer_id(&self, peer_id: PeerId) {
+        self.state.lock().expect("the peer state lock is poisoned").peer_id = Some(peer_id);
+    }
+
+    /// Records the version and block height advertised by the peer.
+    pub fn record_version(&self, version: u64, height: u32) {
+        let mut state = self.state.lock().expect("the peer state lock is poisoned");
+        state.version = Some(version);
+        state.height = Some(height);
+    }
+
+    /// Records a block announced by the peer at the given time.
+    pub fn record_block(&self, block_hash: Vec<u8>, time: i64) {
+        let mut state = self.state.lock().expect("the peer state lock is poisoned");
+        state.last_block_hash = Some(block_hash);
+        state.last_block_time = Some(time);
+    }
+
+    /// Records the round trip time of a ping.
+    pub fn record_rtt(&self, rtt: Duration) {
+        self.state.lock().expect("the peer state lock is poisoned").rtt = Some(rtt);
+    }
+
+    /// Adds to the misbehavior score of the peer, and returns the new score.
+    pub fn misbehave(&self, score: u32) -> u32 {
+        let mut state = self.state.lock().expect("the peer state lock is poisoned");
+        state.misbehavior_score = state.misbehavior_score.saturating_add(score);
+        state.misbehavior_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::NodeIdentity;
+
+    #[test]
+    fn test_peer_stats() {
+        let stats = PeerStats::default();
+        let peer_id = NodeIdentity::generate().peer_id();
+
+        stats.record_sent(20);
+        stats.record_sent(16);
+        stats.record_received(24);
+        stats.record_peer_id(peer_id);
+        stats.record_version(1, 10);
+        stats.record_block(vec![1u8; 32], 1000);
+        stats.record_rtt(Duration::from_millis(15));
+
+        assert_eq!(stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE), 10);
+        assert_eq!(stats.misbehave(MISBEHAVIOR_INVALID_BLOCK), 30);
+
+        assert_eq!(stats.bytes_sent(), 36);
+        assert_eq!(stats.bytes_received(), 24);
+        assert_eq!(stats.state(), PeerState {
+            peer_id: Some(peer_id),
+            version: Some(1),
+            height: Some(10),
+            last_block_hash: Some(vec![1u8; 32]),
+            last_block_time: Some(1000),
+            rtt: Some(Duration::from_millis(15)),
+            misbehavior_score: 30,
+        });
+    }
+}
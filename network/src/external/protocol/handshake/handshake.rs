@@ -15,8 +15,10 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::external::{
-    message_types::{Verack, Version},
+    message_types::{Verack, Version, IDENTITY_PROTOCOL_VERSION},
     Channel,
+    NodeIdentity,
+    PeerId,
 };
 use snarkos_errors::network::HandshakeError;
 
@@ -39,12 +41,23 @@ pub enum HandshakeState {
 /// Receiving a Version message means you should send a Verack message.
 /// If you receive a Verack message from a peer and accept it, then the handshake is complete.
 /// Peers with completed handshakes are added to your connections and your connected peer list.
+///
+/// Each Verack message is signed by the identity of its sender, so the accepted Verack proves the peer ID of the peer.
+/// The peers before `IDENTITY_PROTOCOL_VERSION` are sent and send Verack messages without identity, and a Verack
+/// without identity from a peer announcing a later version is rejected as a downgrade.
+///
+/// The peer ID of a peer accepted without identity is unknown, so the peers are only authenticated if
+/// `allowed_peers` is set. The messages are not encrypted.
 #[derive(Clone, Debug)]
 pub struct Handshake {
     pub channel: Arc<Channel>,
     pub state: HandshakeState,
     pub height: u32,
     pub nonce: u64,
+    /// The peer ID proven by the peer, once its Verack is accepted
+    pub peer_id: Option<PeerId>,
+    /// The protocol version announced by the peer, once its Version is received
+    pub peer_version: Option<u64>,
 }
 
 impl Handshake {
@@ -61,25 +74,30 @@ impl Handshake {
             state: HandshakeState::Waiting,
             height: local_version.height,
             nonce: local_version.nonce,
+            peer_id: None,
+            peer_version: None,
         })
     }
 
     /// Receive the initial Version message from a new peer.
-    /// Send a Verack message + Version message
+    /// Send a Verack message signed by the identity of this node + Version message
     pub async fn receive_new(
         channel: Channel,
         local_version: &Version,
         remote_version: &Version,
+        identity: &NodeIdentity,
     ) -> Result<Handshake, HandshakeError> {
         // Connect to the address specified in the peer_message
         let channel = channel.update_writer(local_version.address_receiver).await?;
 
         // Write Verack response
         channel
-            .write(&Verack::new(
+            .write(&Verack::for_version(
+                remote_version.version,
                 remote_version.nonce,
                 local_version.address_receiver,
                 local_version.address_sender,
+                identity,
             ))
             .await?;
 
@@ -91,32 +109,94 @@ impl Handshake {
             state: HandshakeState::Waiting,
             height: local_version.height,
             nonce: local_version.nonce,
+            peer_id: None,
+            peer_version: Some(remote_version.version),
         })
     }
 
     /// Receive the Version message for an existing peer handshake.
-    /// Send a Verack message.
-    pub async fn receive(&mut self, version: Version) -> Result<(), HandshakeError> {
+    /// Send a Verack message signed by the identity of this node.
+    /// The handshake is rejected if the peer sent a Verack without identity and announces a version with identities.
+    pub async fn receive(&mut self, version: Version, identity: &NodeIdentity) -> Result<(), HandshakeError> {
+        self.peer_version = Some(version.version);
+
+        if self.state == HandshakeState::Accepted
+            && self.peer_id.is_none()
+            && version.version >= IDENTITY_PROTOCOL_VERSION
+        {
+            self.state = HandshakeState::Rejected;
+
+            return Err(HandshakeError::UnsignedVerack(self.channel.address, version.version));
+        }
+
         // You are the new sender and your peer is the receiver
         let address_receiver = self.channel.address;
         let address_sender = version.address_receiver;
 
         self.channel
-            .write(&Verack::new(version.nonce, address_receiver, address_sender))
+            .write(&Verack::for_version(
+                version.version,
+                version.nonce,
+                address_receiver,
+                address_sender,
+                identity,
+            ))
             .await?;
         Ok(())
     }
 
     /// Accept the Verack from a peer.
-    pub async fn accept(&mut self, message: Verack) -> Result<(), HandshakeError> {
+    /// A Verack with a peer ID must be signed by its identity, and the peer ID must be allowed if `allowed_peers`
+    /// is not empty. A Verack without peer ID, from a peer before `IDENTITY_PROTOCOL_VERSION`, is only accepted
+    /// if `allowed_peers` is empty and the peer did not announce a later version.
+    pub async fn accept(&mut self, message: Verack, allowed_peers: &[PeerId]) -> Result<(), HandshakeError> {
         if self.nonce != message.nonce {
             self.state = HandshakeState::Rejected;
 
             return Err(HandshakeError::InvalidNonce(self.nonce, message.nonce));
-        } else if self.state == HandshakeState::Waiting {
+        }
+
+        match message.peer_id {
+            Some(_) if !message.verify() => {
+                self.state = HandshakeState::Rejected;
+
+                return Err(HandshakeError::InvalidSignature(self.channel.address));
+            }
+            Some(peer_id) if !allowed_peers.is_empty() && !allowed_peers.contains(&peer_id) => {
+                self.state = HandshakeState::Rejected;
+
+                return Err(HandshakeError::PeerNotAllowed(
+                    self.channel.address,
+                    peer_id.to_string(),
+                ));
+            }
+            None if !allowed_peers.is_empty() => {
+                self.state = HandshakeState::Rejected;
+
+                return Err(HandshakeError::UnidentifiedPeer(self.channel.address));
+            }
+            None => {
+                if let Some(version) = self
+                    .peer_version
+                    .filter(|version| *version >= IDENTITY_PROTOCOL_VERSION)
+                {
+                    self.state = HandshakeState::Rejected;
+
+                    return Err(HandshakeError::UnsignedVerack(self.channel.address, version));
+                }
+            }
+            _ => {}
+        }
+
+        if self.state == HandshakeState::Waiting {
             self.state = HandshakeState::Accepted;
         }
 
+        if let Some(peer_id) = message.peer_id {
+            self.peer_id = Some(peer_id);
+            self.channel.stats.record_peer_id(peer_id);
+        }
+
         Ok(())
     }
 
@@ -141,7 +221,7 @@ impl Handshake {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::external::message::Message;
+    use crate::external::{message::Message, message_types::PROTOCOL_VERSION};
     use snarkos_testing::network::random_socket_address;
 
     use serial_test::serial;
@@ -157,12 +237,16 @@ mod tests {
 
         let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
 
+        let local_identity = NodeIdentity::generate();
+        let remote_identity = NodeIdentity::generate();
+        let (local_peer_id, remote_peer_id) = (local_identity.peer_id(), remote_identity.peer_id());
+
         tokio::spawn(async move {
             let mut local_listener = TcpListener::bind(local_address).await.unwrap();
 
             // 2. Local node connects to remote. Remote node sends handshake Version
 
-            let local_version = Version::new(1u64, 0u32, remote_address, local_address);
+            let local_version = Version::new(PROTOCOL_VERSION, 0u32, remote_address, local_address);
             let mut handshake = Handshake::send_new(&local_version).await.unwrap();
 
             let (reader, _socket) = local_listener.accept().await.unwrap();
@@ -175,7 +259,8 @@ mod tests {
             let (_name, bytes) = handshake.channel.read().await.unwrap();
             let verack = Verack::deserialize(bytes).unwrap();
 
-            handshake.accept(verack).await.unwrap();
+            assert_eq!(Some(remote_peer_id), verack.peer_id);
+            handshake.accept(verack, &[]).await.unwrap();
 
             // 6. Local node receives handshake Version
 
@@ -184,7 +269,7 @@ mod tests {
 
             // 7. Local node sends handshake Verack
 
-            handshake.receive(remote_version).await.unwrap();
+            handshake.receive(remote_version, &local_identity).await.unwrap();
         });
 
         // 3. Remote node accepts Local node connection
@@ -196,10 +281,10 @@ mod tests {
         // 4. Remote node receives handshake Version.
         // Remote node sends handshake Verack, handshake Version
 
-        let local_version = Version::new(1u64, 0u32, local_address, remote_address);
+        let local_version = Version::new(PROTOCOL_VERSION, 0u32, local_address, remote_address);
         let remote_version = Version::deserialize(bytes).unwrap();
 
-        let mut handshake = Handshake::receive_new(channel, &local_version, &remote_version)
+        let mut handshake = Handshake::receive_new(channel, &local_version, &remote_version, &remote_identity)
             .await
             .unwrap();
 
@@ -208,6 +293,57 @@ mod tests {
         let (_name, bytes) = handshake.channel.read().await.unwrap();
         let verack = Verack::deserialize(bytes).unwrap();
 
-        handshake.accept(verack).await.unwrap();
+        // The peer ID of the local node is not allowed, then allowed
+        assert!(handshake.accept(verack.clone(), &[remote_peer_id]).await.is_err());
+        assert_eq!(HandshakeState::Rejected, handshake.get_state());
+
+        handshake.state = HandshakeState::Waiting;
+        handshake.accept(verack, &[local_peer_id]).await.unwrap();
+
+        assert_eq!(HandshakeState::Accepted, handshake.get_state());
+        assert_eq!(Some(local_peer_id), handshake.peer_id);
+        assert_eq!(Some(local_peer_id), handshake.channel.stats.state().peer_id);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_handshake_unsigned_verack() {
+        let local_address = random_socket_address();
+        let remote_address = random_socket_address();
+
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        tokio::spawn(async move {
+            let _ = remote_listener.accept().await.unwrap();
+        });
+
+        // A peer before the identities answers with an unsigned Verack
+
+        let local_version = Version::new(PROTOCOL_VERSION, 0u32, remote_address, local_address);
+        let mut handshake = Handshake::send_new(&local_version).await.unwrap();
+
+        let verack = Verack::unsigned(local_version.nonce, local_address, remote_address);
+
+        // The peer cannot be checked against the allowed peers, then any peer is allowed
+        let allowed_peer_id = NodeIdentity::generate().peer_id();
+        assert!(handshake.accept(verack.clone(), &[allowed_peer_id]).await.is_err());
+        assert_eq!(HandshakeState::Rejected, handshake.get_state());
+
+        handshake.state = HandshakeState::Waiting;
+        handshake.accept(verack.clone(), &[]).await.unwrap();
+
+        assert_eq!(HandshakeState::Accepted, handshake.get_state());
+        assert_eq!(None, handshake.peer_id);
+
+        // The unsigned Verack is a downgrade if the peer then announces a version with identities
+        let remote_version = Version::new(PROTOCOL_VERSION, 0u32, local_address, remote_address);
+        let identity = NodeIdentity::generate();
+        assert!(handshake.receive(remote_version, &identity).await.is_err());
+        assert_eq!(HandshakeState::Rejected, handshake.get_state());
+
+        // or if the peer announced a version with identities before
+        handshake.state = HandshakeState::Waiting;
+        assert!(handshake.accept(verack, &[]).await.is_err());
+        assert_eq!(HandshakeState::Rejected, handshake.get_state());
     }
 }
@@ -20,6 +20,8 @@ use crate::external::{
     Handshake,
     HandshakeState,
     Message,
+    NodeIdentity,
+    PeerId,
 };
 use snarkos_errors::network::HandshakeError;
 
@@ -30,6 +32,8 @@ use tokio::net::TcpStream;
 #[derive(Clone, Debug, Default)]
 pub struct Handshakes {
     handshakes: HashMap<SocketAddr, Handshake>,
+    /// The peer IDs allowed to complete a handshake, or any peer ID if empty
+    allowed_peers: Vec<PeerId>,
 }
 
 impl Handshakes {
@@ -38,6 +42,17 @@ impl Handshakes {
         Self::default()
     }
 
+    /// Returns the peer IDs allowed to complete a handshake, or an empty list if any peer ID is allowed.
+    pub fn allowed_peers(&self) -> &[PeerId] {
+        &self.allowed_peers
+    }
+
+    /// Restricts the handshakes to the given peer IDs, or allows any peer ID if the list is empty.
+    /// The handshakes completed before are kept.
+    pub fn set_allowed_peers(&mut self, allowed_peers: Vec<PeerId>) {
+        self.allowed_peers = allowed_peers;
+    }
+
     /// Create a new handshake with a peer and send a handshake request to them.
     /// If the request is sent successfully, the handshake is stored and returned.
     pub async fn send_request(&mut self, version: &Version) -> Result<(), HandshakeError> {
@@ -64,6 +79,7 @@ impl Handshakes {
         &mut self,
         version: u64,
         height: u32,
+        identity: &NodeIdentity,
         peer_address: SocketAddr,
         reader: TcpStream,
     ) -> Result<(Handshake, SocketAddr, Option<Version>), HandshakeError> {
@@ -81,7 +97,11 @@ impl Handshakes {
             let local_address = remote_version.address_receiver;
 
             let local_version = Version::new(version, height, remote_address, local_address);
-            let handshake = Handshake::receive_new(channel, &local_version, &remote_version).await?;
+            let handshake = Handshake::receive_new(channel, &local_version, &remote_version, identity).await?;
+            handshake
+                .channel
+                .stats
+                .record_version(remote_version.version, remote_version.height);
 
             self.handshakes.insert(remote_address, handshake.clone());
 
@@ -94,11 +114,16 @@ impl Handshakes {
             let remote_address = verack.address_sender;
             let local_address = verack.address_receiver;
 
+            let allowed_peers = self.allowed_peers.clone();
             match self.get_mut(&remote_address) {
                 Some(handshake) => {
-                    handshake.accept(verack).await?;
+                    handshake.accept(verack, &allowed_peers).await?;
                     handshake.update_reader(channel);
-                    info!("New handshake with: {:?}", remote_address);
+                    info!(
+                        "New handshake with: {:?} (peer ID {})",
+                        remote_address,
+                        peer_id_name(handshake.peer_id)
+                    );
 
                     // Get our new peer's peer_list
                     handshake.channel.write(&GetPeers).await?;
@@ -119,11 +144,12 @@ impl Handshakes {
         &mut self,
         message: Version,
         address_receiver: SocketAddr,
+        identity: &NodeIdentity,
     ) -> Result<(), HandshakeError> {
         match self.get_mut(&address_receiver) {
             Some(stored_handshake) => {
                 stored_handshake.update_address(address_receiver);
-                stored_handshake.receive(message).await?;
+                stored_handshake.receive(message, identity).await?;
 
                 Ok(())
             }
@@ -133,11 +159,17 @@ impl Handshakes {
 
     /// Accept a handshake response from a connected peer.
     pub async fn accept_response(&mut self, address: SocketAddr, message: Verack) -> Result<(), HandshakeError> {
+        let allowed_peers = self.allowed_peers.clone();
         match self.get_mut(&address) {
             Some(stored_handshake) => {
-                info!("New handshake with: {:?}", address);
+                stored_handshake.accept(message, &allowed_peers).await?;
+                info!(
+                    "New handshake with: {:?} (peer ID {})",
+                    address,
+                    peer_id_name(stored_handshake.peer_id)
+                );
 
-                stored_handshake.accept(message).await
+                Ok(())
             }
             None => Err(HandshakeError::HandshakeMissing(address)),
         }
@@ -162,10 +194,18 @@ impl Handshakes {
     }
 }
 
+/// Returns the peer ID to log for a handshake, which is unknown for the peers before `IDENTITY_PROTOCOL_VERSION`.
+fn peer_id_name(peer_id: Option<PeerId>) -> String {
+    match peer_id {
+        Some(peer_id) => peer_id.to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::external::Message;
+    use crate::external::{message_types::PROTOCOL_VERSION, Message};
     use snarkos_testing::network::random_socket_address;
 
     use serial_test::serial;
@@ -180,14 +220,19 @@ mod tests {
         // 1. Bind to remote address
         let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
 
+        let local_identity = NodeIdentity::generate();
+        let remote_identity = NodeIdentity::generate();
+        let (local_peer_id, remote_peer_id) = (local_identity.peer_id(), remote_identity.peer_id());
+
         tokio::spawn(async move {
             let mut local_listener = TcpListener::bind(local_address).await.unwrap();
 
             // 2. Local node sends handshake request
 
-            let local_version = Version::new(1u64, 0u32, remote_address, local_address);
+            let local_version = Version::new(PROTOCOL_VERSION, 0u32, remote_address, local_address);
 
             let mut handshake = Handshakes::new();
+            handshake.set_allowed_peers(vec![remote_peer_id]);
             handshake.send_request(&local_version).await.unwrap();
 
             // 5. Check local node handshake state
@@ -205,6 +250,7 @@ mod tests {
             handshake.accept_response(remote_address, verack).await.unwrap();
 
             assert_eq!(HandshakeState::Accepted, handshake.get_state(remote_address).unwrap());
+            assert_eq!(Some(remote_peer_id), handshake.get(&remote_address).unwrap().peer_id);
 
             // 7. Local node receives handshake request
 
@@ -213,7 +259,10 @@ mod tests {
 
             // 8. Local node sends handshake response
 
-            handshake.receive_request(remote_version, remote_address).await.unwrap();
+            handshake
+                .receive_request(remote_version, remote_address, &local_identity)
+                .await
+                .unwrap();
         });
 
         // 3. Remote node accepts Local node connection
@@ -223,7 +272,10 @@ mod tests {
         // 4. Remote node sends handshake response, handshake request
 
         let mut handshakes = Handshakes::new();
-        let (handshake, _, _) = handshakes.receive_any(1u64, 0u32, local_address, reader).await.unwrap();
+        let (handshake, _, _) = handshakes
+            .receive_any(PROTOCOL_VERSION, 0u32, &remote_identity, local_address, reader)
+            .await
+            .unwrap();
 
         assert_eq!(HandshakeState::Waiting, handshakes.get_state(local_address).unwrap());
 
@@ -234,6 +286,7 @@ mod tests {
 
         handshakes.accept_response(local_address, verack).await.unwrap();
 
-        assert_eq!(HandshakeState::Accepted, handshakes.get_state(local_address).unwrap())
+        assert_eq!(HandshakeState::Accepted, handshakes.get_state(local_address).unwrap());
+        assert_eq!(Some(local_peer_id), handshakes.get(&local_address).unwrap().peer_id);
     }
 }
@@ -20,7 +20,7 @@ use crate::external::{
 };
 use snarkos_errors::network::PingProtocolError;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum PingState {
@@ -38,6 +38,7 @@ pub struct PingProtocol {
     state: PingState,
     channel: Arc<Channel>,
     nonce: u64,
+    sent: Instant,
 }
 
 impl PingProtocol {
@@ -50,6 +51,7 @@ impl PingProtocol {
             state: PingState::Waiting,
             channel,
             nonce: message.nonce,
+            sent: Instant::now(),
         })
     }
 
@@ -68,6 +70,10 @@ impl PingProtocol {
             return Err(PingProtocolError::InvalidNonce(self.nonce, message.nonce));
         }
 
+        if self.state == PingState::Waiting {
+            self.channel.stats.record_rtt(self.sent.elapsed());
+        }
+
         self.state = PingState::Accepted;
 
         Ok(())
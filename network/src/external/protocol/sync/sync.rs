@@ -95,6 +95,14 @@ impl SyncHandler {
         }
     }
 
+    /// Stop syncing with the sync node, forgetting the blocks to request from it.
+    pub fn stop_syncing(&mut self) {
+        info!("Sync state is set to Idle");
+        self.block_headers.clear();
+        self.pending_blocks.clear();
+        self.sync_state = SyncState::Idle;
+    }
+
     /// Process a vector of block header hashes.
     /// Push new hashes to the sync handler so we can ask the sync node for them.
     pub fn receive_hashes(&mut self, hashes: Vec<BlockHeaderHash>, height: u32) {
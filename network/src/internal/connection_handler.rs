@@ -16,14 +16,15 @@
 
 use crate::{
     external::{
-        message_types::{GetMemoryPool, GetPeers, Version},
+        message_types::{GetMemoryPool, GetPeers, Version, PROTOCOL_VERSION},
         protocol::sync::SyncState,
     },
+    internal::context::Context,
     Server,
 };
 
 use chrono::{Duration as ChronoDuration, Utc};
-use std::time::Duration;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{task, time::delay_for};
 use tracing_futures::Instrument;
 
@@ -64,7 +65,7 @@ impl Server {
                     peer_book.forget_peer(local_address);
 
                     // We have less peers than our minimum peer requirement. Look for more peers.
-                    if peer_book.connected_total() < context.min_peers {
+                    if peer_book.connected_total() < context.min_peers() {
                         // Ask our connected peers.
                         for (address, _last_seen) in peer_book.get_connected() {
                             match connections.get(&address) {
@@ -85,30 +86,33 @@ impl Server {
                         for (remote_address, _last_seen) in peer_book.get_gossiped() {
                             if remote_address != local_address && !peer_book.connected_contains(&remote_address) {
                                 // Create a non-blocking handshake request
-                                {
-                                    let new_context = context.clone();
-                                    let latest_block_height = storage.get_latest_block_height();
-
-                                    let handshake_future = async move {
-                                        // TODO (raychu86) Establish a formal node version
-                                        let version =
-                                            Version::new(1u64, latest_block_height, remote_address, local_address);
-
-                                        let mut handshakes = new_context.handshakes.write().await; // Acquire the handshake lock
-                                        if handshakes.send_request(&version).await.is_err() {
-                                            debug!("Could not connect to gossiped peer {}", remote_address);
-                                        }
-                                    };
-                                    task::spawn(
-                                        handshake_future.instrument(debug_span!("handshake", addr = %remote_address)),
-                                    );
-                                }
+                                send_handshake_non_blocking(
+                                    context.clone(),
+                                    storage.get_latest_block_height(),
+                                    remote_address,
+                                    local_address,
+                                );
                             }
 
                             peer_book.remove_gossiped(remote_address);
                         }
                     }
 
+                    // Connect to the peers requested by the node operator.
+                    for remote_address in peer_book.take_requested() {
+                        if remote_address != local_address
+                            && !peer_book.connected_contains(&remote_address)
+                            && !peer_book.is_banned(&remote_address.ip())
+                        {
+                            send_handshake_non_blocking(
+                                context.clone(),
+                                storage.get_latest_block_height(),
+                                remote_address,
+                                local_address,
+                            );
+                        }
+                    }
+
                     // Send a ping protocol request to each of our connected peers to maintain the connection.
                     for (address, last_seen) in peer_book.get_connected() {
                         let time_since_last_seen = (Utc::now() - last_seen).num_milliseconds();
@@ -172,7 +176,7 @@ impl Server {
                                     // If they are behind, they will attempt to sync.
                                     if let Some(handshake) = context.handshakes.read().await.get(&remote_address) {
                                         let version = Version::from(
-                                            1u64, // TODO (raychu86) Establish a formal node version
+                                            PROTOCOL_VERSION,
                                             storage.get_latest_block_height(),
                                             remote_address,
                                             local_address,
@@ -229,3 +233,22 @@ impl Server {
         task::spawn(handler_future.instrument(debug_span!("connection_handler")));
     }
 }
+
+/// Send a handshake request to a peer without blocking the connection handler.
+fn send_handshake_non_blocking(
+    context: Arc<Context>,
+    latest_block_height: u32,
+    remote_address: SocketAddr,
+    local_address: SocketAddr,
+) {
+    let handshake_future = async move {
+        // TODO (raychu86) Establish a formal node version
+        let version = Version::new(1u64, latest_block_height, remote_address, local_address);
+
+        let mut handshakes = context.handshakes.write().await; // Acquire the handshake lock
+        if handshakes.send_request(&version).await.is_err() {
+            debug!("Could not connect to peer {}", remote_address);
+        }
+    };
+    task::spawn(handshake_future.instrument(debug_span!("handshake", addr = %remote_address)));
+}
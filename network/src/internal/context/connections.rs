@@ -40,5 +40,19 @@ impl Connections {
         self.channels.insert(channel.address, channel.clone());
     }
 
+    /// Removes and returns the channel stored at address if any.
+    pub fn remove(&mut self, address: &SocketAddr) -> Option<Arc<Channel>> {
+        self.channels.remove(address)
+    }
+
+    /// Returns the highest block height advertised by the given peers, if any of them advertised one.
+    pub fn highest_peer_height<'a>(&self, addresses: impl IntoIterator<Item = &'a SocketAddr>) -> Option<u32> {
+        addresses
+            .into_iter()
+            .filter_map(|address| self.channels.get(address))
+            .filter_map(|channel| channel.stats.state().height)
+            .max()
+    }
+
     // TODO (raychu86) Clean up connections if peers are disconnected
 }
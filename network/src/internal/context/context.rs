@@ -15,11 +15,19 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    external::{Handshakes, Pings},
+    external::{Handshakes, NodeIdentity, Pings},
     internal::{Connections, PeerBook},
 };
 
-use std::net::SocketAddr;
+use snarkos_consensus::MiningStats;
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU16, Ordering},
+        Arc,
+    },
+};
 use tokio::sync::RwLock;
 
 /// The network context for this node.
@@ -28,24 +36,30 @@ pub struct Context {
     /// The ip address/socket of this node.
     pub local_address: RwLock<SocketAddr>,
 
+    /// The identity keypair of this node, which proves its peer ID in handshakes.
+    pub identity: NodeIdentity,
+
     /// Frequency the server requests memory pool transactions.
     pub memory_pool_interval: u8,
 
-    /// Mininmum number of peers to connect to
-    pub min_peers: u16,
+    /// Mininmum number of peers to connect to, replaced by a configuration reload
+    min_peers: AtomicU16,
 
-    /// Maximum number of peers to connect to
-    pub max_peers: u16,
+    /// Maximum number of peers to connect to, replaced by a configuration reload
+    max_peers: AtomicU16,
 
     /// If enabled, node will not connect to bootnodes on startup.
     pub is_bootnode: bool,
 
     /// Hardcoded nodes and user-specified nodes this node should connect to on startup.
-    pub bootnodes: Vec<String>,
+    pub bootnodes: RwLock<Vec<String>>,
 
     /// If enabled, node will operate as a miner
     pub is_miner: bool,
 
+    /// Statistics of the miner of this node, if it is mining
+    pub mining_stats: Arc<MiningStats>,
+
     /// Manages connected, gossiped, and disconnected peers
     pub peer_book: RwLock<PeerBook>,
 
@@ -57,10 +71,16 @@ pub struct Context {
 
     /// Ping/pongs with connected peers
     pub pings: RwLock<Pings>,
+
+    /// Set once the node is shutting down, after which no new work is accepted
+    shutting_down: AtomicBool,
+
+    /// Set while the miner of this node is paused by a configuration reload
+    mining_paused: AtomicBool,
 }
 
 impl Context {
-    /// Construct a new network `Context`.
+    /// Construct a new network `Context`, with a new random identity.
     pub fn new(
         local_address: SocketAddr,
         memory_pool_interval: u8,
@@ -72,16 +92,63 @@ impl Context {
     ) -> Self {
         Self {
             local_address: RwLock::new(local_address),
+            identity: NodeIdentity::generate(),
             memory_pool_interval,
-            min_peers,
-            max_peers,
+            min_peers: AtomicU16::new(min_peers),
+            max_peers: AtomicU16::new(max_peers),
             is_bootnode,
-            bootnodes,
+            bootnodes: RwLock::new(bootnodes),
             is_miner,
+            mining_stats: Arc::new(MiningStats::default()),
             connections: RwLock::new(Connections::new()),
             peer_book: RwLock::new(PeerBook::new()),
             handshakes: RwLock::new(Handshakes::new()),
             pings: RwLock::new(Pings::new()),
+            shutting_down: AtomicBool::new(false),
+            mining_paused: AtomicBool::new(false),
         }
     }
+
+    /// Replaces the identity of this node, typically with the persistent identity loaded from its directory.
+    pub fn with_identity(mut self, identity: NodeIdentity) -> Self {
+        self.identity = identity;
+        self
+    }
+
+    /// Returns the minimum number of peers to connect to.
+    pub fn min_peers(&self) -> u16 {
+        self.min_peers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the maximum number of peers to connect to.
+    pub fn max_peers(&self) -> u16 {
+        self.max_peers.load(Ordering::SeqCst)
+    }
+
+    /// Replaces the minimum and the maximum number of peers to connect to.
+    /// The peers connected above the new maximum are kept.
+    pub fn set_peer_limits(&self, min_peers: u16, max_peers: u16) {
+        self.min_peers.store(min_peers, Ordering::SeqCst);
+        self.max_peers.store(max_peers, Ordering::SeqCst);
+    }
+
+    /// Pauses or resumes the miner of this node.
+    pub fn pause_mining(&self, is_paused: bool) {
+        self.mining_paused.store(is_paused, Ordering::SeqCst);
+    }
+
+    /// Returns true if the miner of this node is paused.
+    pub fn is_mining_paused(&self) -> bool {
+        self.mining_paused.load(Ordering::SeqCst)
+    }
+
+    /// Marks the node as shutting down, so the connections, messages and mining work are no longer accepted.
+    pub fn shut_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the node is shutting down.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
 }
@@ -20,7 +20,10 @@ use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction}
 use snarkos_storage::Ledger;
 
 use chrono::{DateTime, Utc};
-use std::{collections::HashMap, net::SocketAddr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+};
 
 /// Stores connected, disconnected, and known peers.
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
@@ -33,6 +36,12 @@ pub struct PeerBook {
 
     /// Gossiped but unconnected peers
     gossiped: AddressBook,
+
+    /// Banned peer ip addresses, with the time their bans expire
+    banned: HashMap<IpAddr, DateTime<Utc>>,
+
+    /// Peers requested by the node operator, to send a handshake request to in the next connection loop
+    requested: HashSet<SocketAddr>,
 }
 
 impl PeerBook {
@@ -98,6 +107,51 @@ impl PeerBook {
         self.gossiped.remove(&address).is_some()
     }
 
+    /// Ban a peer ip address until the given time.
+    /// Returns the connected peers at the ip address, which are moved to the disconnected peers.
+    pub fn ban_peer(&mut self, ip: IpAddr, until: DateTime<Utc>) -> Vec<SocketAddr> {
+        self.banned.insert(ip, until);
+
+        for (address, _) in self.get_gossiped() {
+            if address.ip() == ip {
+                self.gossiped.remove(&address);
+            }
+        }
+
+        let connected: Vec<SocketAddr> = self
+            .get_connected()
+            .into_iter()
+            .map(|(address, _)| address)
+            .filter(|address| address.ip() == ip)
+            .collect();
+        for address in &connected {
+            self.disconnect_peer(*address);
+        }
+
+        connected
+    }
+
+    /// Lift the ban of a peer ip address. Returns true if the ip address was banned.
+    pub fn unban_peer(&mut self, ip: &IpAddr) -> bool {
+        self.banned.remove(ip).map_or(false, |until| until > Utc::now())
+    }
+
+    /// Returns true if the peer ip address is banned.
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        self.banned.get(ip).map_or(false, |until| *until > Utc::now())
+    }
+
+    /// Request a handshake with a peer in the next connection loop.
+    /// Returns false if the peer was already requested.
+    pub fn request_peer(&mut self, address: SocketAddr) -> bool {
+        self.requested.insert(address)
+    }
+
+    /// Returns the requested peers, and clears the requests.
+    pub fn take_requested(&mut self) -> Vec<SocketAddr> {
+        self.requested.drain().collect()
+    }
+
     /// Returns the number of connected peers.
     pub fn connected_total(&self) -> u16 {
         self.connected.length()
@@ -16,19 +16,25 @@
 
 use crate::{
     external::{
-        message::{Message, MessageName},
+        message::Message,
         message_types::*,
         propagate_block,
         protocol::SyncState,
         Channel,
         Pings,
+        MISBEHAVIOR_INVALID_BLOCK,
+        MISBEHAVIOR_MALFORMED_MESSAGE,
     },
     internal::process_transaction_internal,
     Server,
 };
 use snarkos_consensus::memory_pool::Entry;
 use snarkos_dpc::base_dpc::instantiated::Tx;
-use snarkos_errors::network::ServerError;
+use snarkos_errors::{
+    consensus::ConsensusError,
+    network::{HandshakeError, ServerError},
+    storage::StorageError,
+};
 use snarkos_objects::Block as BlockStruct;
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
@@ -49,6 +55,14 @@ impl Server {
         // TODO (raychu86) Create a macro to the handle the error messages.
         // TODO (howardwu): Come back and add error handlers to these.
         while let Some((tx, name, bytes, mut channel)) = self.receiver.recv().await {
+            // No new blocks or transactions are processed while the node is shutting down
+            if self.context.is_shutting_down() {
+                if let Err(error) = tx.send(channel) {
+                    warn!("Error resetting connection thread ({:?})", error);
+                }
+                continue;
+            }
+
             if name == Block::name() {
                 if let Ok(block) = Block::deserialize(bytes) {
                     if let Err(err) = self.receive_block_message(block, channel.clone(), true).await {
@@ -57,6 +71,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == GetBlock::name() {
                 if let Ok(getblock) = GetBlock::deserialize(bytes) {
@@ -66,6 +82,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == GetMemoryPool::name() {
                 if let Ok(getmemorypool) = GetMemoryPool::deserialize(bytes) {
@@ -75,6 +93,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == GetPeers::name() {
                 if let Ok(getpeers) = GetPeers::deserialize(bytes) {
@@ -84,6 +104,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == GetSync::name() {
                 if let Ok(getsync) = GetSync::deserialize(bytes) {
@@ -93,6 +115,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == MemoryPool::name() {
                 if let Ok(mempool) = MemoryPool::deserialize(bytes) {
@@ -102,6 +126,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Peers::name() {
                 if let Ok(peers) = Peers::deserialize(bytes) {
@@ -111,6 +137,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Ping::name() {
                 if let Ok(ping) = Ping::deserialize(bytes) {
@@ -120,6 +148,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Pong::name() {
                 if let Ok(pong) = Pong::deserialize(bytes) {
@@ -129,6 +159,14 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
+                }
+            } else if name == Pruned::name() {
+                if let Ok(pruned) = Pruned::deserialize(bytes) {
+                    self.receive_pruned(pruned, channel.clone()).await;
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Sync::name() {
                 if let Ok(sync) = Sync::deserialize(bytes) {
@@ -138,6 +176,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == SyncBlock::name() {
                 if let Ok(block) = Block::deserialize(bytes) {
@@ -147,6 +187,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Transaction::name() {
                 if let Ok(transaction) = Transaction::deserialize(bytes) {
@@ -156,6 +198,8 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Version::name() {
                 if let Ok(version) = Version::deserialize(bytes) {
@@ -167,6 +211,8 @@ impl Server {
                             name, channel.address, err
                         ),
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
                 }
             } else if name == Verack::name() {
                 if let Ok(verack) = Verack::deserialize(bytes) {
@@ -176,15 +222,23 @@ impl Server {
                             name, channel.address, err
                         );
                     }
+                } else {
+                    channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
+                }
+            } else if name == Disconnect::name() {
+                match Disconnect::deserialize(bytes) {
+                    Ok(disconnect) if !disconnect.reason.is_empty() => {
+                        info!("Disconnected from peer {:?} ({})", channel.address, disconnect.reason)
+                    }
+                    _ => info!("Disconnected from peer {:?}", channel.address),
                 }
-            } else if name == MessageName::from("disconnect") {
-                info!("Disconnected from peer {:?}", channel.address);
                 {
                     let mut peer_book = self.context.peer_book.write().await;
                     peer_book.disconnect_peer(channel.address);
                 }
             } else {
                 debug!("Message name not recognized {:?}", name.to_string());
+                channel.stats.misbehave(MISBEHAVIOR_MALFORMED_MESSAGE);
             }
 
             if let Err(error) = tx.send(channel) {
@@ -208,14 +262,30 @@ impl Server {
             hex::encode(block.header.get_hash().0)
         );
 
+        // Blocks are announced with Block messages, and requested with SyncBlock messages
+        if propagate {
+            channel
+                .stats
+                .record_block(block.header.get_hash().0.to_vec(), Utc::now().timestamp());
+        }
+
         // Verify the block and insert it into the storage.
         if !self.storage.block_hash_exists(&block.header.get_hash()) {
             {
                 let mut memory_pool = self.memory_pool_lock.lock().await;
-                let inserted = self
-                    .consensus
-                    .receive_block(&self.parameters, &self.storage, &mut memory_pool, &block)
-                    .is_ok();
+                let inserted =
+                    match self
+                        .consensus
+                        .receive_block(&self.parameters, &self.storage, &mut memory_pool, &block)
+                    {
+                        Ok(()) => true,
+                        // The storage errors are not caused by the block
+                        Err(ConsensusError::StorageError(_)) => false,
+                        Err(_) => {
+                            channel.stats.misbehave(MISBEHAVIOR_INVALID_BLOCK);
+                            false
+                        }
+                    };
 
                 if inserted && propagate {
                     // This is a new block, send it to our peers.
@@ -240,14 +310,37 @@ impl Server {
     }
 
     /// A peer has requested a block.
+    /// The requests of pruned blocks are answered with a Pruned message, if the peer supports it.
     async fn receive_get_block(&mut self, message: GetBlock, channel: Arc<Channel>) -> Result<(), ServerError> {
-        if let Ok(block) = self.storage.get_block(&message.block_hash) {
-            channel.write(&SyncBlock::new(block.serialize()?)).await?;
+        match self.storage.get_block(&message.block_hash) {
+            Ok(block) => channel.write(&SyncBlock::new(block.serialize()?)).await?,
+            Err(StorageError::PrunedBlockTransactions(_)) => {
+                let version = channel.stats.state().version.unwrap_or(0);
+
+                if version >= PRUNED_PROTOCOL_VERSION {
+                    channel.write(&Pruned::new(message.block_hash)).await?;
+                }
+            }
+            Err(_) => {}
         }
 
         Ok(())
     }
 
+    /// A peer cannot send a requested block, as it has pruned its transactions.
+    /// The sync with the peer is stopped, so the blocks are requested from another peer.
+    async fn receive_pruned(&mut self, message: Pruned, channel: Arc<Channel>) {
+        let mut sync_handler = self.sync_handler_lock.lock().await;
+
+        if sync_handler.sync_node == channel.address {
+            warn!(
+                "Sync node {} has pruned block {}, stopping the sync",
+                channel.address, message.block_hash
+            );
+            sync_handler.stop_syncing();
+        }
+    }
+
     /// A peer has requested our memory pool transactions.
     async fn receive_get_memory_pool(
         &mut self,
@@ -311,7 +404,7 @@ impl Server {
     async fn receive_peers(&mut self, message: Peers, channel: Arc<Channel>) -> Result<(), ServerError> {
         let peer_book = &mut self.context.peer_book.write().await;
         for (addr, time) in message.addresses.iter() {
-            if &*self.context.local_address.read().await == addr {
+            if &*self.context.local_address.read().await == addr || peer_book.is_banned(&addr.ip()) {
                 continue;
             } else if peer_book.connected_contains(addr) {
                 peer_book.update_connected(*addr, *time);
@@ -367,12 +460,23 @@ impl Server {
     }
 
     /// A peer has requested our chain state to sync with.
+    /// A peer missing blocks whose transactions were pruned is answered with a Pruned message, if it supports it,
+    /// or with no block hashes.
     async fn receive_get_sync(&mut self, message: GetSync, channel: Arc<Channel>) -> Result<(), ServerError> {
         let latest_shared_hash = self.storage.get_latest_shared_hash(message.block_locator_hashes)?;
         let current_height = self.storage.get_latest_block_height();
 
         if let Ok(height) = self.storage.get_block_number(&latest_shared_hash) {
-            if height < current_height {
+            if height < self.storage.get_pruned_block_height()? {
+                let version = channel.stats.state().version.unwrap_or(0);
+
+                if version >= PRUNED_PROTOCOL_VERSION {
+                    let block_hash = self.storage.get_block_hash(height + 1)?;
+                    channel.write(&Pruned::new(block_hash)).await?;
+                } else {
+                    channel.write(&Sync::new(vec![])).await?;
+                }
+            } else if height < current_height {
                 let mut max_height = current_height;
 
                 // if the requester is behind more than 4000 blocks
@@ -453,6 +557,15 @@ impl Server {
                 // Ask connected peer for more peers.
                 channel.write(&GetPeers).await?;
             }
+            Err(error @ HandshakeError::InvalidSignature(_))
+            | Err(error @ HandshakeError::PeerNotAllowed(..))
+            | Err(error @ HandshakeError::UnsignedVerack(..)) => {
+                // Drop the connection of a peer without the identity this node expects
+                warn!("Rejected the handshake of {:?} ({})", channel.address, error);
+
+                channel.write(&Disconnect::new(&error.to_string())).await?;
+                self.context.connections.write().await.remove(&channel.address);
+            }
             Err(error) => {
                 let error = ServerError::HandshakeError(error);
                 debug!(
@@ -473,16 +586,31 @@ impl Server {
     async fn receive_version(&mut self, message: Version, channel: Arc<Channel>) -> Result<Arc<Channel>, ServerError> {
         let peer_address = SocketAddr::new(channel.address.ip(), message.address_sender.port());
 
+        channel.stats.record_version(message.version, message.height);
+
         let peer_book = &mut self.context.peer_book.read().await;
 
         if *self.context.local_address.read().await != peer_address {
-            if peer_book.connected_total() < self.context.max_peers {
-                self.context
+            if peer_book.connected_total() < self.context.max_peers() {
+                match self
+                    .context
                     .handshakes
                     .write()
                     .await
-                    .receive_request(message.clone(), peer_address)
-                    .await?;
+                    .receive_request(message.clone(), peer_address, &self.context.identity)
+                    .await
+                {
+                    Err(error @ HandshakeError::UnsignedVerack(..)) => {
+                        // Drop the connection of a peer that sent its Verack without the identity of its version
+                        warn!("Rejected the handshake of {:?} ({})", channel.address, error);
+
+                        channel.write(&Disconnect::new(&error.to_string())).await?;
+                        self.context.connections.write().await.remove(&channel.address);
+
+                        return Ok(channel);
+                    }
+                    result => result?,
+                }
             }
 
             // If our peer has a longer chain, send a sync message
@@ -23,18 +23,28 @@ pub use context::*;
 pub mod message_handler;
 pub use message_handler::*;
 
-use crate::{external::propagate_transaction, internal::Context};
+use crate::{
+    external::{message_types::Disconnect, propagate_transaction},
+    internal::Context,
+};
 use snarkos_consensus::{
     memory_pool::{Entry, MemoryPool},
     ConsensusParameters,
     MerkleTreeLedger,
+    SentTransactions,
 };
 use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
 };
-use snarkos_errors::network::SendError;
-use snarkos_utilities::bytes::FromBytes;
+use snarkos_errors::{consensus::ConsensusError, network::SendError};
+use snarkos_models::objects::Transaction;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use chrono::Utc;
 
 use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::Mutex;
@@ -77,3 +87,78 @@ pub async fn process_transaction_internal(
 
     Ok(())
 }
+
+/// Broadcast again the transactions sent by the node that are not yet confirmed, and stop tracking
+/// the transactions that are confirmed, conflict with the canon blocks or expired.
+/// A transaction dropped from the memory pool is added to it again before it is propagated.
+pub async fn rebroadcast_sent_transactions(
+    context: Arc<Context>,
+    consensus: &ConsensusParameters,
+    parameters: &PublicParameters<Components>,
+    storage: Arc<MerkleTreeLedger>,
+    memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
+) -> Result<(), SendError> {
+    let update = SentTransactions::new(&storage).update(Utc::now().timestamp())?;
+
+    for transaction_id in &update.conflicted {
+        warn!(
+            "Sent transaction {} conflicts with the canon blocks and is abandoned",
+            hex::encode(transaction_id)
+        );
+    }
+
+    for transaction_id in &update.expired {
+        warn!(
+            "Sent transaction {} was not confirmed before its expiry and is abandoned",
+            hex::encode(transaction_id)
+        );
+    }
+
+    let local_address = *context.local_address.read().await;
+
+    for transaction in update.rebroadcast {
+        let transaction_id = transaction.transaction_id().map_err(ConsensusError::from)?.to_vec();
+        let transaction_bytes = to_bytes![transaction].map_err(ConsensusError::from)?;
+
+        debug!("Broadcasting sent transaction {} again", hex::encode(&transaction_id));
+
+        let is_in_memory_pool = memory_pool_lock.lock().await.transactions.contains_key(&transaction_id);
+
+        if is_in_memory_pool {
+            propagate_transaction(context.clone(), transaction_bytes, local_address).await?;
+        } else {
+            process_transaction_internal(
+                context.clone(),
+                consensus,
+                parameters,
+                storage.clone(),
+                memory_pool_lock.clone(),
+                transaction_bytes,
+                local_address,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends a disconnect message with the given reason to every connected peer, and closes the connections.
+pub async fn disconnect_peers(context: Arc<Context>, reason: &str) {
+    let mut peer_book = context.peer_book.write().await;
+    let connections = context.connections.read().await;
+    let disconnect = Disconnect::new(reason);
+
+    for (remote_address, _last_seen) in peer_book.get_connected() {
+        if let Some(channel) = connections.get(&remote_address) {
+            if let Err(error) = channel.write(&disconnect).await {
+                debug!("Failed to send a disconnect message to {}: {}", remote_address, error);
+            }
+            if let Err(error) = channel.shutdown().await {
+                debug!("Failed to close the connection to {}: {}", remote_address, error);
+            }
+        }
+
+        peer_book.disconnect_peer(remote_address);
+    }
+}
@@ -15,7 +15,13 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    external::{message::MessageName, message_types::GetSync, protocol::*, Channel, Version},
+    external::{
+        message::{Message, MessageName},
+        message_types::{Disconnect, GetSync, PROTOCOL_VERSION},
+        protocol::*,
+        Channel,
+        Version,
+    },
     internal::context::Context,
 };
 use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
@@ -118,8 +124,25 @@ impl Server {
                     }
                 };
 
+                // Reject the connection requests once the node is shutting down.
+                if context.is_shutting_down() {
+                    if let Err(error) = reader.shutdown(Shutdown::Write) {
+                        error!("Failed to shutdown peer reader ({})", error);
+                    }
+                    continue;
+                }
+
+                // Reject the connection requests of banned peers.
+                if context.peer_book.read().await.is_banned(&remote_address.ip()) {
+                    warn!("Rejected a connection request from banned peer {}", remote_address);
+                    if let Err(error) = reader.shutdown(Shutdown::Write) {
+                        error!("Failed to shutdown peer reader ({})", error);
+                    }
+                    continue;
+                }
+
                 // Check if we've exceed our maximum number of allowed peers.
-                if context.peer_book.read().await.connected_total() >= context.max_peers {
+                if context.peer_book.read().await.connected_total() >= context.max_peers() {
                     warn!("Rejected a connection request as this exceeds the maximum number of peers allowed");
                     if let Err(error) = reader.shutdown(Shutdown::Write) {
                         error!("Failed to shutdown peer reader ({})", error);
@@ -130,9 +153,9 @@ impl Server {
                 // Follow handshake protocol and drop peer connection if unsuccessful.
                 let height = storage.get_latest_block_height();
                 let mut handshakes = context.handshakes.write().await; // Acquire the handshake lock
-                // TODO (raychu86) Establish a formal node version
-                if let Ok((handshake, discovered_local_address, version_message)) =
-                    handshakes.receive_any(1u64, height, remote_address, reader).await
+                if let Ok((handshake, discovered_local_address, version_message)) = handshakes
+                    .receive_any(PROTOCOL_VERSION, height, &context.identity, remote_address, reader)
+                    .await
                 {
                     // Bootstrap discovery of local node IP via VERACK responses
                     {
@@ -262,7 +285,7 @@ impl Server {
 
                         // Determine if we should send a disconnect message.
                         match disconnect_from_peer {
-                            true => (MessageName::from("disconnect"), vec![]),
+                            true => (Disconnect::name(), vec![]),
                             false => continue,
                         }
                     }
@@ -306,7 +329,12 @@ impl Server {
 
         let future = async move {
             let height = storage.get_latest_block_height();
-            let version = Version::new(1u64, height, remote_address, *context.local_address.read().await);
+            let version = Version::new(
+                PROTOCOL_VERSION,
+                height,
+                remote_address,
+                *context.local_address.read().await,
+            );
 
             let mut handshakes = context.handshakes.write().await;
             handshakes.send_request(&version).await.unwrap_or_else(|error| {
@@ -319,7 +347,8 @@ impl Server {
     /// Send a handshake request the first bootnode and store the rest as gossipped peers
     async fn connect_bootnodes(&mut self) {
         let local_address = *self.context.local_address.read().await;
-        for bootnode in self.context.bootnodes.iter() {
+        let bootnodes = self.context.bootnodes.read().await.clone();
+        for bootnode in bootnodes.iter() {
             if let Ok(bootnode_address) = bootnode.parse::<SocketAddr>() {
                 // This node should not attempt to connect to itself.
                 if local_address != bootnode_address {
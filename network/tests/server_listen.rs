@@ -26,6 +26,7 @@ mod server_listen {
             message_types::{GetPeers, GetSync, Verack},
             protocol::SyncHandler,
             Handshakes,
+            NodeIdentity,
         },
         internal::context::Context,
         server::Server,
@@ -152,7 +153,7 @@ mod server_listen {
 
             let mut bootnode_handshakes = Handshakes::new();
             let (mut bootnode_hand, _, _) = bootnode_handshakes
-                .receive_any(1u64, 1u32, server_address, reader)
+                .receive_any(1u64, 1u32, &NodeIdentity::generate(), server_address, reader)
                 .await
                 .unwrap();
 
@@ -168,7 +169,7 @@ mod server_listen {
 
             assert_eq!(Verack::name(), name);
             let verack_message = Verack::deserialize(bytes).unwrap();
-            bootnode_hand.accept(verack_message).await.unwrap();
+            bootnode_hand.accept(verack_message, &[]).await.unwrap();
 
             // 7. Check that bootnode received GetSync message
 
@@ -225,7 +226,7 @@ mod server_listen {
 
             let mut peer_handshakes = Handshakes::new();
             peer_handshakes
-                .receive_any(1u64, 1u32, server_address, reader)
+                .receive_any(1u64, 1u32, &NodeIdentity::generate(), server_address, reader)
                 .await
                 .unwrap();
         });
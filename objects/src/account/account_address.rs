@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{account_format, AccountPrivateKey, AccountViewKey};
+use crate::{account_format, AccountPrivateKey, AccountViewKey, Network};
 use snarkos_errors::objects::AccountError;
 use snarkos_models::{algorithms::EncryptionScheme, dpc::DPCComponents};
 use snarkos_utilities::{FromBytes, ToBytes};
@@ -65,6 +65,51 @@ impl<C: DPCComponents> AccountAddress<C> {
         Ok(Self { encryption_key })
     }
 
+    /// Returns the address encoded for the given network. The bech32 payload is the address version
+    /// followed by the encryption key, under the human-readable part of the network, so a mistyped
+    /// address fails its checksum and an address of another network is rejected.
+    pub fn to_network_string(&self, network: Network) -> String {
+        let mut address = [0u8; 33];
+        address[0] = account_format::ADDRESS_VERSION;
+        self.encryption_key
+            .write(&mut address[1..33])
+            .expect("address formatting failed");
+
+        let prefix = account_format::network_address_prefix(network);
+
+        Bech32::new(prefix, address.to_base32())
+            .expect("address formatting failed")
+            .to_string()
+    }
+
+    /// Reads in an address string strictly for the given network. The address is either encoded for the
+    /// network, with a valid checksum, a known version and an exact length, or is an unversioned address,
+    /// which does not name a network.
+    pub fn from_network_str(address: &str, network: Network) -> Result<Self, AccountError> {
+        let bech32 = Bech32::from_str(address)?;
+
+        // The unversioned addresses encode the encryption key alone
+        if bech32.hrp() == account_format::ADDRESS_PREFIX && bech32.data().len() == 52 {
+            return Self::from_str(address);
+        }
+
+        let prefix = account_format::network_address_prefix(network);
+        if bech32.hrp() != prefix {
+            return Err(AccountError::InvalidNetworkPrefix(bech32.hrp().to_string(), prefix));
+        }
+
+        // The conversion rejects a nonzero or overlong padding
+        let buffer = Vec::from_base32(&bech32.data())?;
+        if buffer.len() != 33 {
+            return Err(AccountError::InvalidByteLength(buffer.len()));
+        }
+        if buffer[0] != account_format::ADDRESS_VERSION {
+            return Err(AccountError::InvalidAddressVersion(buffer[0]));
+        }
+
+        Ok(Self::read(&buffer[1..])?)
+    }
+
     #[allow(clippy::wrong_self_convention)]
     pub fn into_repr(&self) -> &<C::AccountEncryption as EncryptionScheme>::PublicKey {
         &self.encryption_key
@@ -14,7 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::Network;
+
 pub static PRIVATE_KEY_PREFIX: [u8; 9] = [127, 134, 189, 116, 210, 221, 210, 137, 144]; // APrivateKey1
 pub static _PROVING_KEY_PREFIX: [u8; 10] = [109, 249, 98, 224, 36, 15, 213, 187, 79, 190]; // AProvingKey1
 pub static VIEW_KEY_PREFIX: [u8; 7] = [14, 138, 223, 204, 247, 224, 122]; // AViewKey1
 pub static ADDRESS_PREFIX: &str = "aleo";
+
+/// The version of the network address encoding, the first byte of its payload.
+pub static ADDRESS_VERSION: u8 = 1;
+
+/// Returns the human-readable part of the network-encoded addresses of the given network.
+pub fn network_address_prefix(network: Network) -> String {
+    match network {
+        Network::Mainnet => ADDRESS_PREFIX.to_string(),
+        Network::Testnet1 => format!("t{}", ADDRESS_PREFIX),
+        Network::Devnet => format!("d{}", ADDRESS_PREFIX),
+        Network::Custom(id) => format!("{}-{}", ADDRESS_PREFIX, id),
+    }
+}
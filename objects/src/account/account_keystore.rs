@@ -0,0 +1,342 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Account, AccountPrivateKey};
+use snarkos_errors::objects::KeystoreError;
+use snarkos_models::dpc::DPCComponents;
+
+use chacha20poly1305::{
+    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
+    ChaCha20Poly1305,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The version of the keystore file format.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// The key derivation function of the keystore files.
+const KDF_ALGORITHM: &str = "argon2id";
+
+/// The cipher of the keystore files.
+const CIPHER_ALGORITHM: &str = "chacha20poly1305";
+
+/// The cost of the Argon2id derivation of the encryption key from the passphrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreKdfParameters {
+    /// The memory cost, in KiB
+    pub memory_cost: u32,
+    /// The number of passes over the memory
+    pub time_cost: u32,
+    /// The number of lanes
+    pub lanes: u32,
+}
+
+impl Default for KeystoreKdfParameters {
+    fn default() -> Self {
+        Self {
+            memory_cost: 65536,
+            time_cost: 3,
+            lanes: 1,
+        }
+    }
+}
+
+/// The key derivation function of a keystore file, with its salt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KeystoreKdf {
+    algorithm: String,
+    salt: String,
+    #[serde(flatten)]
+    parameters: KeystoreKdfParameters,
+}
+
+/// The cipher of a keystore file, with its nonce.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KeystoreCipher {
+    algorithm: String,
+    nonce: String,
+}
+
+/// The contents of a keystore file. The address is stored in the clear, to tell the
+/// keystores apart without their passphrase, and is authenticated by the cipher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    address: String,
+    kdf: KeystoreKdf,
+    cipher: KeystoreCipher,
+    ciphertext: String,
+}
+
+impl KeystoreFile {
+    /// Encrypts the private key under the passphrase, with a fresh salt and nonce.
+    fn encrypt<C: DPCComponents, R: Rng>(
+        address: String,
+        private_key: &AccountPrivateKey<C>,
+        passphrase: &str,
+        parameters: KeystoreKdfParameters,
+        rng: &mut R,
+    ) -> Result<Self, KeystoreError> {
+        let salt: [u8; 16] = rng.gen();
+        let nonce: [u8; 12] = rng.gen();
+
+        let mut file = Self {
+            version: KEYSTORE_VERSION,
+            address,
+            kdf: KeystoreKdf {
+                algorithm: KDF_ALGORITHM.to_string(),
+                salt: hex::encode(salt),
+                parameters,
+            },
+            cipher: KeystoreCipher {
+                algorithm: CIPHER_ALGORITHM.to_string(),
+                nonce: hex::encode(nonce),
+            },
+            ciphertext: String::new(),
+        };
+
+        let cipher = file.cipher(passphrase)?;
+        let plaintext = private_key.to_string();
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), Payload {
+                msg: plaintext.as_bytes(),
+                aad: file.address.as_bytes(),
+            })
+            .map_err(|_| KeystoreError::Message("failed to encrypt the private key".into()))?;
+
+        file.ciphertext = hex::encode(ciphertext);
+
+        Ok(file)
+    }
+
+    /// Decrypts the private key with the passphrase.
+    fn decrypt<C: DPCComponents>(&self, passphrase: &str) -> Result<AccountPrivateKey<C>, KeystoreError> {
+        let nonce = hex::decode(&self.cipher.nonce)?;
+        if nonce.len() != 12 {
+            return Err(KeystoreError::Message("invalid keystore nonce".into()));
+        }
+        let ciphertext = hex::decode(&self.ciphertext)?;
+
+        let cipher = self.cipher(passphrase)?;
+        let plaintext = cipher
+            .decrypt(GenericArray::from_slice(&nonce), Payload {
+                msg: &ciphertext,
+                aad: self.address.as_bytes(),
+            })
+            .map_err(|_| KeystoreError::IncorrectPassphrase)?;
+
+        let private_key = String::from_utf8(plaintext).map_err(|_| KeystoreError::IncorrectPassphrase)?;
+
+        Ok(AccountPrivateKey::from_str(&private_key)?)
+    }
+
+    /// Derives the cipher of the file from the passphrase.
+    fn cipher(&self, passphrase: &str) -> Result<ChaCha20Poly1305, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(self.version));
+        }
+        if self.kdf.algorithm != KDF_ALGORITHM || self.cipher.algorithm != CIPHER_ALGORITHM {
+            return Err(KeystoreError::Message(format!(
+                "unsupported keystore algorithms: {} and {}",
+                self.kdf.algorithm, self.cipher.algorithm
+            )));
+        }
+
+        let salt = hex::decode(&self.kdf.salt)?;
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.kdf.parameters.memory_cost,
+            time_cost: self.kdf.parameters.time_cost,
+            lanes: self.kdf.parameters.lanes,
+            thread_mode: argon2::ThreadMode::Sequential,
+            secret: &[],
+            ad: &[],
+            hash_length: 32,
+        };
+
+        let key = argon2::hash_raw(passphrase.as_bytes(), &salt, &config)
+            .map_err(|error| KeystoreError::Crate("argon2", format!("{:?}", error)))?;
+
+        Ok(ChaCha20Poly1305::new(GenericArray::from_slice(&key)))
+    }
+}
+
+/// An account private key stored in a file, encrypted under a passphrase.
+/// The key is derived from the passphrase with Argon2id, and the private key is encrypted
+/// with ChaCha20-Poly1305. The private key is only held in memory while the keystore is unlocked.
+pub struct AccountKeystore<C: DPCComponents> {
+    path: PathBuf,
+    file: KeystoreFile,
+    private_key: Option<AccountPrivateKey<C>>,
+}
+
+impl<C: DPCComponents> AccountKeystore<C> {
+    /// Creates a keystore file at the given path, storing the private key of the account
+    /// under the passphrase. The keystore is returned locked.
+    pub fn create<P: AsRef<Path>, R: Rng>(
+        path: P,
+        account: &Account<C>,
+        passphrase: &str,
+        rng: &mut R,
+    ) -> Result<Self, KeystoreError> {
+        Self::create_with_parameters(path, account, passphrase, KeystoreKdfParameters::default(), rng)
+    }
+
+    /// Creates a keystore file like `create`, with the given cost of the key derivation.
+    pub fn create_with_parameters<P: AsRef<Path>, R: Rng>(
+        path: P,
+        account: &Account<C>,
+        passphrase: &str,
+        parameters: KeystoreKdfParameters,
+        rng: &mut R,
+    ) -> Result<Self, KeystoreError> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            return Err(KeystoreError::Message(format!(
+                "the keystore {} already exists",
+                path.display()
+            )));
+        }
+
+        let file = KeystoreFile::encrypt(
+            account.address.to_string(),
+            &account.private_key,
+            passphrase,
+            parameters,
+            rng,
+        )?;
+
+        let keystore = Self {
+            path,
+            file,
+            private_key: None,
+        };
+        keystore.write()?;
+
+        Ok(keystore)
+    }
+
+    /// Opens the keystore file at the given path. The keystore is returned locked.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, KeystoreError> {
+        let path = path.as_ref().to_path_buf();
+        let file: KeystoreFile = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|error| KeystoreError::Crate("serde_json", format!("{:?}", error)))?;
+
+        if file.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(file.version));
+        }
+
+        Ok(Self {
+            path,
+            file,
+            private_key: None,
+        })
+    }
+
+    /// Returns the path of the keystore file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the address of the account of the keystore, which is readable while locked.
+    pub fn address(&self) -> &str {
+        &self.file.address
+    }
+
+    /// Returns true if the private key is decrypted.
+    pub fn is_unlocked(&self) -> bool {
+        self.private_key.is_some()
+    }
+
+    /// Returns the private key, if the keystore is unlocked.
+    pub fn private_key(&self) -> Result<&AccountPrivateKey<C>, KeystoreError> {
+        self.private_key.as_ref().ok_or(KeystoreError::Locked)
+    }
+
+    /// Decrypts the private key with the passphrase, and holds it until the keystore is locked.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<&AccountPrivateKey<C>, KeystoreError> {
+        let private_key = self.file.decrypt(passphrase)?;
+
+        Ok(self.private_key.get_or_insert(private_key))
+    }
+
+    /// Drops the decrypted private key.
+    pub fn lock(&mut self) {
+        self.private_key = None;
+    }
+
+    /// Encrypts the private key under a new passphrase, with a fresh salt and nonce,
+    /// and replaces the keystore file. The keystore stays locked or unlocked.
+    pub fn change_passphrase<R: Rng>(
+        &mut self,
+        passphrase: &str,
+        new_passphrase: &str,
+        rng: &mut R,
+    ) -> Result<(), KeystoreError> {
+        let private_key: AccountPrivateKey<C> = self.file.decrypt(passphrase)?;
+
+        let file = KeystoreFile::encrypt(
+            self.file.address.clone(),
+            &private_key,
+            new_passphrase,
+            self.file.kdf.parameters,
+            rng,
+        )?;
+
+        let previous_file = std::mem::replace(&mut self.file, file);
+        if let Err(error) = self.write() {
+            self.file = previous_file;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the keystore file, through a temporary file so a failed write leaves the previous file.
+    /// The file is only readable by its owner.
+    fn write(&self) -> Result<(), KeystoreError> {
+        let contents = serde_json::to_vec_pretty(&self.file)
+            .map_err(|error| KeystoreError::Crate("serde_json", format!("{:?}", error)))?;
+
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut temporary_file = options.open(&temporary_path)?;
+        temporary_file.write_all(&contents)?;
+        temporary_file.sync_all()?;
+
+        fs::rename(&temporary_path, &self.path)?;
+
+        Ok(())
+    }
+}
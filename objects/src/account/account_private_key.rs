@@ -14,14 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::account_format;
+use crate::{account_format, AccountProofKey};
 use snarkos_algorithms::prf::Blake2s;
 use snarkos_errors::objects::AccountError;
 use snarkos_models::{
     algorithms::{CommitmentScheme, EncryptionScheme, SignatureScheme, PRF},
     dpc::DPCComponents,
 };
-use snarkos_utilities::{bytes_to_bits, to_bytes, FromBytes, ToBytes};
+use snarkos_utilities::{FromBytes, ToBytes};
 
 use base58::{FromBase58, ToBase58};
 use rand::Rng;
@@ -174,39 +174,7 @@ impl<C: DPCComponents> AccountPrivateKey<C> {
         signature_parameters: &C::AccountSignature,
         commitment_parameters: &C::AccountCommitment,
     ) -> Result<<C::AccountEncryption as EncryptionScheme>::PrivateKey, AccountError> {
-        let commitment = self.commit(signature_parameters, commitment_parameters)?;
-        let decryption_key_bytes = to_bytes![commitment]?;
-
-        // This operation implicitly enforces that the unused MSB bits
-        // for the scalar field representation are correctly set to 0.
-        let decryption_key = match self.is_dummy {
-            true => <C::AccountEncryption as EncryptionScheme>::PrivateKey::default(),
-            false => <C::AccountEncryption as EncryptionScheme>::PrivateKey::read(&decryption_key_bytes[..])?,
-        };
-
-        // This operation explicitly enforces that the unused MSB bits
-        // for the scalar field representation are correctly set to 0.
-        //
-        // To simplify verification of this isomorphism from the base field
-        // to the scalar field in the `inner_snark`, we additionally enforce
-        // that the MSB bit of the scalar field is also set to 0.
-        if !self.is_dummy {
-            let account_decryption_key_bits = bytes_to_bits(&decryption_key_bytes[..]).collect::<Vec<_>>();
-            let account_decryption_key_length = account_decryption_key_bits.len();
-
-            let decryption_private_key_length = C::AccountEncryption::private_key_size_in_bits();
-            assert!(decryption_private_key_length > 0);
-            assert!(decryption_private_key_length <= account_decryption_key_length);
-
-            for i in (decryption_private_key_length - 1)..account_decryption_key_length {
-                let bit_index = account_decryption_key_length - i - 1;
-                if account_decryption_key_bits[bit_index] {
-                    return Err(AccountError::InvalidAccountCommitment);
-                }
-            }
-        }
-
-        Ok(decryption_key)
+        AccountProofKey::from_private_key(signature_parameters, self)?.to_decryption_key(commitment_parameters)
     }
 
     /// Returns the signature public key for deriving the account view key.
@@ -219,23 +187,6 @@ impl<C: DPCComponents> AccountPrivateKey<C> {
             &self.sk_sig,
         )?)
     }
-
-    /// Returns the commitment output of the private key.
-    fn commit(
-        &self,
-        signature_parameters: &C::AccountSignature,
-        commitment_parameters: &C::AccountCommitment,
-    ) -> Result<<C::AccountCommitment as CommitmentScheme>::Output, AccountError> {
-        // Construct the commitment input for the account address.
-        let pk_sig = self.pk_sig(signature_parameters)?;
-        let commit_input = to_bytes![pk_sig, self.sk_prf]?;
-
-        Ok(C::AccountCommitment::commit(
-            commitment_parameters,
-            &commit_input,
-            &self.r_pk,
-        )?)
-    }
 }
 
 impl<C: DPCComponents> FromStr for AccountPrivateKey<C> {
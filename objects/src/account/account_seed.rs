@@ -0,0 +1,180 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Account, AccountAddress, AccountPrivateKey};
+use snarkos_algorithms::prf::Blake2s;
+use snarkos_errors::objects::AccountError;
+use snarkos_models::{algorithms::PRF, dpc::DPCComponents};
+
+use bip39::Mnemonic;
+use rand::Rng;
+use std::{fmt, str::FromStr};
+
+/// The domain of the seed derivation, which keeps the derived seeds apart from
+/// the inputs deriving the private key components of the parent seed.
+const DERIVATION_DOMAIN: &[u8; 28] = b"snarkOS account derivation\0\0";
+
+/// The master seed of a hierarchy of accounts. Each account is derived from the seed
+/// and an index path, so a wallet backs up the seed once, as a mnemonic phrase,
+/// and derives any number of accounts from it deterministically.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccountSeed {
+    seed: [u8; 32],
+}
+
+impl AccountSeed {
+    /// Samples a new master seed.
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
+        Self { seed: rng.gen() }
+    }
+
+    /// Returns the master seed of the given bytes.
+    pub fn from_bytes(seed: [u8; 32]) -> Self {
+        Self { seed }
+    }
+
+    /// Returns the bytes of the master seed.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Reads the master seed from a mnemonic phrase of 24 words.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, AccountError> {
+        let mnemonic =
+            Mnemonic::parse_normalized(phrase).map_err(|error| AccountError::InvalidMnemonic(error.to_string()))?;
+
+        let entropy = mnemonic.to_entropy();
+        if entropy.len() != 32 {
+            return Err(AccountError::InvalidMnemonic(format!(
+                "expected 24 words, found {}",
+                phrase.split_whitespace().count()
+            )));
+        }
+
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&entropy);
+
+        Ok(Self { seed })
+    }
+
+    /// Returns the mnemonic phrase of the master seed, of 24 words.
+    pub fn to_mnemonic(&self) -> String {
+        Mnemonic::from_entropy(&self.seed)
+            .expect("32 bytes of entropy are a valid mnemonic")
+            .to_string()
+    }
+
+    /// Derives the seed of the account at the given path. Each index of the path derives
+    /// a child seed from its parent, starting from the master seed for the empty path.
+    pub fn derive_seed(&self, path: &AccountDerivationPath) -> Result<[u8; 32], AccountError> {
+        let mut seed = self.seed;
+
+        for index in &path.0 {
+            let mut input = [0u8; 32];
+            input[0..4].copy_from_slice(&index.to_le_bytes());
+            input[4..32].copy_from_slice(DERIVATION_DOMAIN);
+
+            seed = Blake2s::evaluate(&seed, &input)?;
+        }
+
+        Ok(seed)
+    }
+
+    /// Derives the account private key at the given path.
+    pub fn derive_private_key<C: DPCComponents>(
+        &self,
+        signature_parameters: &C::AccountSignature,
+        commitment_parameters: &C::AccountCommitment,
+        path: &AccountDerivationPath,
+    ) -> Result<AccountPrivateKey<C>, AccountError> {
+        AccountPrivateKey::from_seed(signature_parameters, commitment_parameters, &self.derive_seed(path)?)
+    }
+
+    /// Derives the account at the given path.
+    pub fn derive_account<C: DPCComponents>(
+        &self,
+        signature_parameters: &C::AccountSignature,
+        commitment_parameters: &C::AccountCommitment,
+        encryption_parameters: &C::AccountEncryption,
+        path: &AccountDerivationPath,
+    ) -> Result<Account<C>, AccountError> {
+        let private_key = self.derive_private_key(signature_parameters, commitment_parameters, path)?;
+        let address = AccountAddress::from_private_key(
+            signature_parameters,
+            commitment_parameters,
+            encryption_parameters,
+            &private_key,
+        )?;
+
+        Ok(Account { private_key, address })
+    }
+}
+
+impl fmt::Debug for AccountSeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AccountSeed {{ seed: <hidden> }}")
+    }
+}
+
+/// The index path of an account derived from a master seed, written as `m/0/1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AccountDerivationPath(pub Vec<u32>);
+
+impl AccountDerivationPath {
+    /// Returns the path of the given indices.
+    pub fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    /// Returns the path of the child account at the given index.
+    pub fn child(&self, index: u32) -> Self {
+        let mut indices = self.0.clone();
+        indices.push(index);
+
+        Self(indices)
+    }
+}
+
+impl FromStr for AccountDerivationPath {
+    type Err = AccountError;
+
+    /// Reads in a derivation path string, such as `m/0/1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = s.split('/');
+
+        if components.next() != Some("m") {
+            return Err(AccountError::InvalidDerivationPath(s.to_string()));
+        }
+
+        let indices = components
+            .map(|index| index.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| AccountError::InvalidDerivationPath(s.to_string()))?;
+
+        Ok(Self(indices))
+    }
+}
+
+impl fmt::Display for AccountDerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}", index)?;
+        }
+
+        Ok(())
+    }
+}
@@ -25,9 +25,18 @@ pub use account_address::*;
 pub mod account_format;
 pub use account_format::*;
 
+pub mod account_keystore;
+pub use account_keystore::*;
+
 pub mod account_private_key;
 pub use account_private_key::*;
 
+pub mod account_proof_key;
+pub use account_proof_key::*;
+
+pub mod account_seed;
+pub use account_seed::*;
+
 pub mod account_view_key;
 pub use account_view_key::*;
 
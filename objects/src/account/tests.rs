@@ -14,11 +14,26 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::account::{Account, AccountAddress, AccountPrivateKey, AccountViewKey};
+use crate::{
+    account::{
+        Account,
+        AccountAddress,
+        AccountDerivationPath,
+        AccountKeystore,
+        AccountPrivateKey,
+        AccountSeed,
+        AccountViewKey,
+        KeystoreKdfParameters,
+    },
+    Network,
+};
 use snarkos_dpc::base_dpc::{instantiated::Components, parameters::SystemParameters};
+use snarkos_errors::objects::{AccountError, KeystoreError};
 use snarkos_models::objects::account::AccountScheme;
+use snarkos_utilities::{to_bytes, ToBytes};
 
-use rand::thread_rng;
+use bech32::{Bech32, ToBase32};
+use rand::{thread_rng, Rng};
 use std::str::FromStr;
 
 #[test]
@@ -66,3 +81,183 @@ pub fn test_address_from_str() {
     assert!(address.is_ok());
     assert_eq!(address_string, address.unwrap().to_string());
 }
+
+#[test]
+pub fn test_address_network_encoding() {
+    let address_string = "aleo1ag4alvc4g7d4apzgvr5f4jt44l0aezev2dx8m0klgwypnh9u5uxs42rclr";
+    let address = AccountAddress::<Components>::from_str(address_string).unwrap();
+
+    for network in &[Network::Mainnet, Network::Testnet1, Network::Devnet, Network::Custom(7)] {
+        let network_string = address.to_network_string(*network);
+        assert_ne!(network_string, address_string);
+        assert_eq!(
+            AccountAddress::<Components>::from_network_str(&network_string, *network).unwrap(),
+            address
+        );
+
+        // The unversioned address is accepted on every network
+        assert_eq!(
+            AccountAddress::<Components>::from_network_str(address_string, *network).unwrap(),
+            address
+        );
+    }
+
+    // The addresses of another network are rejected
+    let testnet_string = address.to_network_string(Network::Testnet1);
+    assert!(testnet_string.starts_with("taleo1"));
+    assert!(address.to_network_string(Network::Devnet).starts_with("daleo1"));
+    match AccountAddress::<Components>::from_network_str(&testnet_string, Network::Mainnet) {
+        Err(AccountError::InvalidNetworkPrefix(prefix, expected)) => {
+            assert_eq!(prefix, "taleo");
+            assert_eq!(expected, "aleo");
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // A mistyped character fails the checksum
+    let mut mistyped = testnet_string.clone().into_bytes();
+    let index = mistyped.len() - 10;
+    mistyped[index] = if mistyped[index] == b'q' { b'p' } else { b'q' };
+    let mistyped = String::from_utf8(mistyped).unwrap();
+    assert!(AccountAddress::<Components>::from_network_str(&mistyped, Network::Testnet1).is_err());
+
+    // An unknown version is rejected
+    let mut payload = [0u8; 33];
+    payload[0] = 2;
+    payload[1..].copy_from_slice(&to_bytes![address].unwrap());
+    let unknown_version = Bech32::new("taleo".to_string(), payload.to_base32())
+        .unwrap()
+        .to_string();
+    match AccountAddress::<Components>::from_network_str(&unknown_version, Network::Testnet1) {
+        Err(AccountError::InvalidAddressVersion(2)) => (),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // A truncated payload is rejected
+    let truncated = Bech32::new("taleo".to_string(), payload[..32].to_base32())
+        .unwrap()
+        .to_string();
+    assert!(AccountAddress::<Components>::from_network_str(&truncated, Network::Testnet1).is_err());
+}
+
+#[test]
+pub fn test_account_seed_derivation() {
+    let rng = &mut thread_rng();
+    let parameters = SystemParameters::<Components>::load().unwrap();
+
+    let seed = AccountSeed::new(rng);
+
+    // The seed is restored from its mnemonic phrase
+    let mnemonic = seed.to_mnemonic();
+    assert_eq!(mnemonic.split_whitespace().count(), 24);
+    assert_eq!(AccountSeed::from_mnemonic(&mnemonic).unwrap(), seed);
+    assert!(AccountSeed::from_mnemonic("not a mnemonic").is_err());
+
+    let derive = |seed: &AccountSeed, path: &str| {
+        seed.derive_account::<Components>(
+            &parameters.account_signature,
+            &parameters.account_commitment,
+            &parameters.account_encryption,
+            &AccountDerivationPath::from_str(path).unwrap(),
+        )
+        .unwrap()
+    };
+
+    // The accounts are derived deterministically, and differ by path
+    let restored_seed = AccountSeed::from_mnemonic(&mnemonic).unwrap();
+    assert_eq!(derive(&seed, "m/0/1").address, derive(&restored_seed, "m/0/1").address);
+    assert_ne!(derive(&seed, "m/0/1").address, derive(&seed, "m/0/2").address);
+    assert_ne!(derive(&seed, "m/0").address, derive(&seed, "m/1").address);
+}
+
+#[test]
+pub fn test_derivation_path_from_str() {
+    let path = AccountDerivationPath::from_str("m/0/1").unwrap();
+    assert_eq!(path, AccountDerivationPath::new(vec![0, 1]));
+    assert_eq!(path.to_string(), "m/0/1");
+    assert_eq!(AccountDerivationPath::new(vec![0]).child(1), path);
+    assert_eq!(
+        AccountDerivationPath::from_str("m").unwrap(),
+        AccountDerivationPath::default()
+    );
+
+    assert!(AccountDerivationPath::from_str("0/1").is_err());
+    assert!(AccountDerivationPath::from_str("m/a").is_err());
+    assert!(AccountDerivationPath::from_str("m/0/").is_err());
+}
+
+#[test]
+pub fn test_account_keystore() {
+    let rng = &mut thread_rng();
+    let parameters = SystemParameters::<Components>::load().unwrap();
+
+    let account = Account::<Components>::new(
+        &parameters.account_signature,
+        &parameters.account_commitment,
+        &parameters.account_encryption,
+        rng,
+    )
+    .unwrap();
+
+    // Keep the key derivation cheap in tests
+    let kdf_parameters = KeystoreKdfParameters {
+        memory_cost: 64,
+        time_cost: 1,
+        lanes: 1,
+    };
+
+    let path = std::env::temp_dir().join(format!("test_account_keystore_{}.json", rng.gen::<u64>()));
+
+    let mut keystore =
+        AccountKeystore::<Components>::create_with_parameters(&path, &account, "passphrase", kdf_parameters, rng)
+            .unwrap();
+    assert_eq!(keystore.address(), account.address.to_string());
+    assert!(!keystore.is_unlocked());
+    assert!(matches!(keystore.private_key(), Err(KeystoreError::Locked)));
+
+    // The private key is not stored in the clear
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(!contents.contains(&account.private_key.to_string()));
+
+    // A keystore is not overwritten
+    assert!(
+        AccountKeystore::<Components>::create_with_parameters(&path, &account, "passphrase", kdf_parameters, rng)
+            .is_err()
+    );
+
+    // The private key is decrypted with the passphrase only
+    assert!(matches!(
+        keystore.unlock("wrong passphrase"),
+        Err(KeystoreError::IncorrectPassphrase)
+    ));
+    assert!(!keystore.is_unlocked());
+    assert_eq!(keystore.unlock("passphrase").unwrap(), &account.private_key);
+    assert!(keystore.is_unlocked());
+
+    keystore.lock();
+    assert!(!keystore.is_unlocked());
+
+    // The passphrase is changed, and the reopened keystore is unlocked with the new passphrase
+    assert!(keystore
+        .change_passphrase("wrong passphrase", "new passphrase", rng)
+        .is_err());
+    keystore.change_passphrase("passphrase", "new passphrase", rng).unwrap();
+
+    let mut reopened_keystore = AccountKeystore::<Components>::open(&path).unwrap();
+    assert_eq!(reopened_keystore.address(), account.address.to_string());
+    assert!(reopened_keystore.unlock("passphrase").is_err());
+    assert_eq!(
+        reopened_keystore.unlock("new passphrase").unwrap(),
+        &account.private_key
+    );
+
+    // The keystores of other versions are rejected
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, contents.replace("\"version\": 1", "\"version\": 2")).unwrap();
+    assert!(matches!(
+        AccountKeystore::<Components>::open(&path),
+        Err(KeystoreError::UnsupportedVersion(2))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -110,6 +110,12 @@ impl AleoAmount {
     }
 }
 
+impl From<AleoAmount> for i64 {
+    fn from(amount: AleoAmount) -> Self {
+        amount.0
+    }
+}
+
 impl ToBytes for AleoAmount {
     fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
         self.0.write(&mut writer)
@@ -44,6 +44,9 @@ pub use merkle_tree::*;
 pub mod network;
 pub use network::*;
 
+pub mod payment_request;
+pub use payment_request::*;
+
 pub mod pedersen_merkle_tree;
 pub use pedersen_merkle_tree::*;
 
@@ -19,13 +19,16 @@ use snarkos_utilities::bytes::{FromBytes, ToBytes};
 use std::{
     fmt,
     io::{Read, Result as IoResult, Write},
+    str::FromStr,
 };
 
-/// Represents the network the node operating on
+/// Represents the network the node operating on.
+/// The development network takes the last id, so the custom networks of the previous versions keep their ids.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Network {
     Mainnet,
     Testnet1,
+    Devnet,
     Custom(u8),
 }
 
@@ -35,20 +38,49 @@ impl Network {
         match self {
             Network::Mainnet => 0,
             Network::Testnet1 => 1,
+            Network::Devnet => 255,
             Network::Custom(id) => *id,
         }
     }
 
+    /// Returns the name of the network, or its id for a custom network
+    pub fn name(&self) -> String {
+        match self {
+            Network::Mainnet => "mainnet".into(),
+            Network::Testnet1 => "testnet1".into(),
+            Network::Devnet => "devnet".into(),
+            Network::Custom(id) => id.to_string(),
+        }
+    }
+
     /// Returns the network from a given network id
     pub fn from_network_id(network_id: u8) -> Self {
         match network_id {
             0 => Network::Mainnet,
             1 => Network::Testnet1,
+            255 => Network::Devnet,
             id => Network::Custom(id),
         }
     }
 }
 
+impl FromStr for Network {
+    type Err = String;
+
+    /// Returns the network of the given name, or of the given network id
+    fn from_str(network: &str) -> Result<Self, Self::Err> {
+        match network {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet1" => Ok(Network::Testnet1),
+            "devnet" => Ok(Network::Devnet),
+            id => id
+                .parse()
+                .map(Self::from_network_id)
+                .map_err(|_| format!("unknown network {}", network)),
+        }
+    }
+}
+
 impl ToBytes for Network {
     #[inline]
     fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
@@ -70,3 +102,21 @@ impl fmt::Display for Network {
         write!(f, "{}", self.id())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_from_str() {
+        for network in &[Network::Mainnet, Network::Testnet1, Network::Devnet, Network::Custom(7)] {
+            assert_eq!(Network::from_str(&network.name()).unwrap(), *network);
+            assert_eq!(Network::from_str(&network.id().to_string()).unwrap(), *network);
+        }
+
+        assert!(Network::from_str("testnet2").is_err());
+
+        // The custom networks of the previous versions keep their ids
+        assert_eq!(Network::from_network_id(2), Network::Custom(2));
+    }
+}
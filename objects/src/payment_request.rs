@@ -0,0 +1,293 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{AccountAddress, Network};
+use snarkos_errors::objects::PaymentRequestError;
+use snarkos_models::dpc::DPCComponents;
+
+/// The scheme of the payment request URIs.
+pub const PAYMENT_REQUEST_SCHEME: &str = "aleo";
+
+/// The maximum size of the memo of a payment request, in bytes, the size of a transaction memorandum.
+pub const MAX_PAYMENT_REQUEST_MEMO_SIZE: usize = 32;
+
+/// A request for a payment to an address, shared between wallets and merchants as a URI.
+///
+/// The URI is `aleo:<address>?amount=<amount>&memo=<memo>&expires=<expiry>`, where the address is encoded
+/// for the network of the request, the amount is in bytes, the memo is percent-encoded UTF-8, and the expiry
+/// is a Unix timestamp, in seconds. The parameters are optional and unordered. The unknown parameters are
+/// ignored, unless they are prefixed with `req-`, marking them as required to honour the request.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "C: DPCComponents"),
+    Debug(bound = "C: DPCComponents"),
+    PartialEq(bound = "C: DPCComponents"),
+    Eq(bound = "C: DPCComponents")
+)]
+pub struct PaymentRequest<C: DPCComponents> {
+    /// The address receiving the payment
+    pub address: AccountAddress<C>,
+    /// The network of the address
+    pub network: Network,
+    /// The requested amount, in bytes
+    pub amount: Option<u64>,
+    /// The memo to attach to the payment
+    pub memo: Option<String>,
+    /// The time after which the request is not honoured, as a Unix timestamp
+    pub expires: Option<i64>,
+}
+
+impl<C: DPCComponents> PaymentRequest<C> {
+    /// Creates a request for a payment of any amount to the address.
+    pub fn new(address: AccountAddress<C>, network: Network) -> Self {
+        Self {
+            address,
+            network,
+            amount: None,
+            memo: None,
+            expires: None,
+        }
+    }
+
+    /// Reads in a payment request URI, whose address must be valid for the given network.
+    pub fn from_uri(uri: &str, network: Network) -> Result<Self, PaymentRequestError> {
+        let separator = uri
+            .find(':')
+            .ok_or_else(|| PaymentRequestError::InvalidScheme(uri.to_string()))?;
+        let (scheme, uri) = (&uri[..separator], &uri[separator + 1..]);
+        if !scheme.eq_ignore_ascii_case(PAYMENT_REQUEST_SCHEME) {
+            return Err(PaymentRequestError::InvalidScheme(scheme.to_string()));
+        }
+
+        let (address, query) = match uri.find('?') {
+            Some(separator) => (&uri[..separator], &uri[separator + 1..]),
+            None => (uri, ""),
+        };
+
+        let mut request = Self::new(AccountAddress::from_network_str(address, network)?, network);
+
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let separator = parameter
+                .find('=')
+                .ok_or_else(|| PaymentRequestError::InvalidEncoding(parameter.to_string()))?;
+            let (key, value) = (&parameter[..separator], percent_decode(&parameter[separator + 1..])?);
+
+            let duplicate = match key {
+                "amount" => {
+                    let amount = value
+                        .parse()
+                        .map_err(|_| PaymentRequestError::InvalidAmount(value.clone()))?;
+                    request.amount.replace(amount).is_some()
+                }
+                "memo" => {
+                    check_memo(&value)?;
+                    request.memo.replace(value).is_some()
+                }
+                "expires" => {
+                    let expires = value
+                        .parse()
+                        .map_err(|_| PaymentRequestError::InvalidExpiry(value.clone()))?;
+                    request.expires.replace(expires).is_some()
+                }
+                key if key.starts_with("req-") => {
+                    return Err(PaymentRequestError::UnsupportedParameter(key.to_string()));
+                }
+                _ => false,
+            };
+
+            if duplicate {
+                return Err(PaymentRequestError::DuplicateParameter(key.to_string()));
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// Returns the payment request URI.
+    pub fn to_uri(&self) -> Result<String, PaymentRequestError> {
+        let mut parameters = vec![];
+        if let Some(amount) = self.amount {
+            parameters.push(format!("amount={}", amount));
+        }
+        if let Some(memo) = &self.memo {
+            check_memo(memo)?;
+            parameters.push(format!("memo={}", percent_encode(memo)));
+        }
+        if let Some(expires) = self.expires {
+            parameters.push(format!("expires={}", expires));
+        }
+
+        let mut uri = format!(
+            "{}:{}",
+            PAYMENT_REQUEST_SCHEME,
+            self.address.to_network_string(self.network)
+        );
+        if !parameters.is_empty() {
+            uri.push('?');
+            uri.push_str(&parameters.join("&"));
+        }
+
+        Ok(uri)
+    }
+
+    /// Returns true if the request is expired at the given Unix timestamp.
+    pub fn is_expired(&self, timestamp: i64) -> bool {
+        match self.expires {
+            Some(expires) => timestamp > expires,
+            None => false,
+        }
+    }
+}
+
+/// Checks the memo fits in a transaction memorandum.
+fn check_memo(memo: &str) -> Result<(), PaymentRequestError> {
+    if memo.len() > MAX_PAYMENT_REQUEST_MEMO_SIZE {
+        return Err(PaymentRequestError::InvalidMemo(format!(
+            "the memo is {} bytes, expected at most {}",
+            memo.len(),
+            MAX_PAYMENT_REQUEST_MEMO_SIZE
+        )));
+    }
+
+    Ok(())
+}
+
+/// Percent-encodes the bytes of the value, other than the unreserved characters of RFC 3986.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Decodes a percent-encoded UTF-8 value.
+fn percent_decode(value: &str) -> Result<String, PaymentRequestError> {
+    let invalid_encoding = || PaymentRequestError::InvalidEncoding(value.to_string());
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3).ok_or_else(invalid_encoding)?;
+            if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return Err(invalid_encoding());
+            }
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid_encoding())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| invalid_encoding())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_dpc::base_dpc::instantiated::Components;
+    use snarkos_errors::objects::AccountError;
+
+    use std::str::FromStr;
+
+    const ADDRESS: &str = "aleo1ag4alvc4g7d4apzgvr5f4jt44l0aezev2dx8m0klgwypnh9u5uxs42rclr";
+
+    fn address() -> AccountAddress<Components> {
+        AccountAddress::from_str(ADDRESS).unwrap()
+    }
+
+    #[test]
+    fn test_payment_request_uri() {
+        let mut request = PaymentRequest::new(address(), Network::Testnet1);
+        request.amount = Some(1_500_000);
+        request.memo = Some("invoice #42 é".to_string());
+        request.expires = Some(1_600_000_000);
+
+        let uri = request.to_uri().unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "aleo:{}?amount=1500000&memo=invoice%20%2342%20%C3%A9&expires=1600000000",
+                address().to_network_string(Network::Testnet1)
+            )
+        );
+        assert_eq!(PaymentRequest::from_uri(&uri, Network::Testnet1).unwrap(), request);
+
+        // The parameters are optional
+        let request = PaymentRequest::new(address(), Network::Testnet1);
+        let uri = request.to_uri().unwrap();
+        assert!(!uri.contains('?'));
+        assert_eq!(PaymentRequest::from_uri(&uri, Network::Testnet1).unwrap(), request);
+
+        // The unversioned addresses and the unknown parameters are accepted
+        let request = PaymentRequest::<Components>::from_uri(
+            &format!("ALEO:{}?label=shop&expires=10&amount=7", ADDRESS),
+            Network::Testnet1,
+        )
+        .unwrap();
+        assert_eq!(request.address, address());
+        assert_eq!(request.amount, Some(7));
+        assert_eq!(request.memo, None);
+        assert!(!request.is_expired(10));
+        assert!(request.is_expired(11));
+    }
+
+    #[test]
+    fn test_invalid_payment_request_uri() {
+        let testnet_address = address().to_network_string(Network::Testnet1);
+        let from_uri = |uri: &str| PaymentRequest::<Components>::from_uri(uri, Network::Testnet1);
+
+        match from_uri(&format!("bitcoin:{}", testnet_address)) {
+            Err(PaymentRequestError::InvalidScheme(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}", address().to_network_string(Network::Mainnet))) {
+            Err(PaymentRequestError::AccountError(AccountError::InvalidNetworkPrefix(..))) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?amount=-1", testnet_address)) {
+            Err(PaymentRequestError::InvalidAmount(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?amount=1&amount=2", testnet_address)) {
+            Err(PaymentRequestError::DuplicateParameter(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?memo=%zz", testnet_address)) {
+            Err(PaymentRequestError::InvalidEncoding(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?memo={}", testnet_address, "m".repeat(33))) {
+            Err(PaymentRequestError::InvalidMemo(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?expires=tomorrow", testnet_address)) {
+            Err(PaymentRequestError::InvalidExpiry(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+        match from_uri(&format!("aleo:{}?req-asset=token", testnet_address)) {
+            Err(PaymentRequestError::UnsupportedParameter(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -14,8 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use snarkos_algorithms::merkle_tree::MerkleTree;
-use snarkos_consensus::{ConsensusParameters, MerkleTreeLedger};
+use snarkos_algorithms::merkle_tree::IncrementalMerkleTree;
+use snarkos_consensus::{ConsensusParameters, MerkleTreeLedger, TransactionMemo};
 use snarkos_dpc::base_dpc::{instantiated::*, record_payload::RecordPayload, BaseDPCComponents, DPC};
 use snarkos_errors::dpc::{DPCError, LedgerError};
 use snarkos_models::{
@@ -33,7 +33,7 @@ use snarkos_utilities::{
     to_bytes,
 };
 
-use parking_lot::RwLock;
+use parking_lot::{ReentrantMutex, RwLock};
 use rand::{thread_rng, Rng};
 use std::{
     fs::{self, File},
@@ -56,12 +56,16 @@ fn empty_ledger<T: Transaction, P: LoadableMerkleParameters>(
     };
 
     let leaves: Vec<[u8; 32]> = vec![];
-    let cm_merkle_tree = MerkleTree::<P>::new(parameters.clone(), &leaves)?;
+    let cm_merkle_tree = IncrementalMerkleTree::<P>::new(parameters.clone(), &leaves)?;
 
     Ok(Ledger {
         latest_block_height: RwLock::new(0),
         storage: Arc::new(storage),
+        block_archive: None,
+        startup_check: None,
         cm_merkle_tree: RwLock::new(cm_merkle_tree),
+        block_lock: ReentrantMutex::new(()),
+        filters: RwLock::new(None),
         ledger_parameters: parameters,
         _transaction: PhantomData,
     })
@@ -77,6 +81,9 @@ pub fn generate(recipient: &str, value: u64, network_id: u8, file_name: &str) ->
         network: Network::from_network_id(network_id),
         verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
         authorized_inner_snark_ids: vec![],
+        fixed_difficulty_target: None,
+        max_template_size: None,
+        max_template_weight: None,
     };
 
     let recipient = AccountAddress::<Components>::from_str(&recipient)?;
@@ -162,7 +169,7 @@ pub fn generate(recipient: &str, value: u64, network_id: u8, file_name: &str) ->
             new_is_dummy_flags,
             new_values,
             new_payloads,
-            memo,
+            TransactionMemo::Plaintext(memo),
             &ledger,
             rng,
         )
@@ -0,0 +1,349 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+#[cfg(any(test, feature = "remote"))]
+use crate::REMOTE_URL;
+#[cfg(any(test, feature = "remote"))]
+use snarkos_algorithms::crh::sha256::sha256;
+#[cfg(any(test, feature = "remote"))]
+use snarkos_errors::parameters::ParametersError;
+#[cfg(any(test, feature = "remote"))]
+use std::{
+    cell::{Cell, RefCell},
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+};
+
+/// The environment variable of the mirrors the missing parameters are downloaded from, separated by commas and in
+/// order of preference, in place of the default mirror.
+pub const PARAMETERS_MIRRORS_VAR: &str = "SNARKOS_PARAMETERS_MIRRORS";
+
+/// The downloads of the parameter files, by file name.
+static DOWNLOADS: Lazy<Mutex<BTreeMap<String, DownloadProgress>>> = Lazy::new(Default::default);
+
+/// The state of the download of a parameter file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DownloadState {
+    /// The file is being downloaded from a mirror
+    Downloading,
+    /// The size and the checksum of the downloaded file are being verified
+    Verifying,
+    /// The file is verified and stored in the parameters directory
+    Installed,
+    /// The file could not be downloaded from any of the mirrors
+    Failed(String),
+}
+
+impl fmt::Display for DownloadState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DownloadState::Downloading => write!(f, "downloading"),
+            DownloadState::Verifying => write!(f, "verifying"),
+            DownloadState::Installed => write!(f, "installed"),
+            DownloadState::Failed(_) => write!(f, "failed"),
+        }
+    }
+}
+
+/// The progress of the download of a parameter file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// The name of the parameter file
+    pub filename: String,
+    /// The mirror the file is downloaded from
+    pub mirror: String,
+    /// The number of bytes downloaded, including the bytes of a resumed download
+    pub downloaded: u64,
+    /// The size of the file
+    pub size: u64,
+    /// The state of the download
+    pub state: DownloadState,
+}
+
+impl DownloadProgress {
+    /// Returns the percentage of the file downloaded.
+    pub fn percent(&self) -> f64 {
+        match self.size {
+            0 => 100.0,
+            size => (self.downloaded as f64 / size as f64 * 100.0).min(100.0),
+        }
+    }
+}
+
+/// Returns the progress of the parameter files downloaded since the start of the process.
+pub fn download_progress() -> Vec<DownloadProgress> {
+    DOWNLOADS
+        .lock()
+        .expect("the download progress lock is poisoned")
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// Updates the progress of the download of the given file.
+fn update_progress(filename: &str, update: impl FnOnce(&mut DownloadProgress)) {
+    let mut downloads = DOWNLOADS.lock().expect("the download progress lock is poisoned");
+    if let Some(progress) = downloads.get_mut(filename) {
+        update(progress);
+    }
+}
+
+/// Returns the path of the partial file a parameter file is downloaded to, before it is verified and installed.
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut partial_path = path.as_os_str().to_owned();
+    partial_path.push(".partial");
+    PathBuf::from(partial_path)
+}
+
+/// Returns the mirrors the missing parameters are downloaded from, in order of preference.
+#[cfg(any(test, feature = "remote"))]
+pub fn mirrors() -> Vec<String> {
+    let mirrors = env::var(PARAMETERS_MIRRORS_VAR)
+        .map(|mirrors| {
+            mirrors
+                .split(',')
+                .map(|mirror| mirror.trim().trim_end_matches('/').to_string())
+                .filter(|mirror| !mirror.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    match mirrors.is_empty() {
+        true => vec![REMOTE_URL.to_string()],
+        false => mirrors,
+    }
+}
+
+/// Downloads a missing parameter file from the mirrors, in order, and installs it at the given path.
+///
+/// The file is downloaded to a partial file next to the path, which a later download resumes from with a range
+/// request, from the same mirror or another one. The file is installed once its size and checksum are verified,
+/// and a partial file that fails the verification is removed. Returns the bytes of the installed file.
+#[cfg(any(test, feature = "remote"))]
+pub fn download_parameters(filename: &str, checksum: &str, size: u64, path: &Path) -> Result<Vec<u8>, ParametersError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial_path = partial_path(path);
+
+    let mut errors = vec![];
+    for mirror in mirrors() {
+        DOWNLOADS
+            .lock()
+            .expect("the download progress lock is poisoned")
+            .insert(filename.to_string(), DownloadProgress {
+                filename: filename.to_string(),
+                mirror: mirror.clone(),
+                downloaded: 0,
+                size,
+                state: DownloadState::Downloading,
+            });
+
+        let url = format!("{}/{}", mirror, filename);
+        let result =
+            fetch(filename, &url, &partial_path, size).and_then(|()| verify(filename, &partial_path, checksum, size));
+
+        match result {
+            Ok(buffer) => {
+                fs::rename(&partial_path, path)?;
+                update_progress(filename, |progress| progress.state = DownloadState::Installed);
+                println!("\n{} - Installed {} from {}", module_path!(), filename, mirror);
+
+                return Ok(buffer);
+            }
+            Err(error) => {
+                eprintln!(
+                    "\n{} - Failed to download {} from {}: {}",
+                    module_path!(),
+                    filename,
+                    mirror,
+                    error
+                );
+                errors.push(format!("{}: {}", mirror, error));
+            }
+        }
+    }
+
+    let error = format!(
+        "failed to download {} from the mirrors ({})",
+        filename,
+        errors.join(", ")
+    );
+    update_progress(filename, |progress| {
+        progress.state = DownloadState::Failed(error.clone())
+    });
+
+    Err(ParametersError::Message(error))
+}
+
+/// Downloads the parameter file at the given URL to the partial file, from the end of the partial file.
+#[cfg(any(test, feature = "remote"))]
+fn fetch(filename: &str, url: &str, partial_path: &Path, size: u64) -> Result<(), ParametersError> {
+    use curl::easy::Easy;
+
+    let mut file = OpenOptions::new().create(true).append(true).open(partial_path)?;
+    let offset = file.metadata()?.len();
+    update_progress(filename, |progress| progress.downloaded = offset);
+    if offset >= size {
+        return Ok(());
+    }
+
+    let mut easy = Easy::new();
+    easy.url(url)?;
+    easy.follow_location(true)?;
+    easy.fail_on_error(true)?;
+    if offset > 0 {
+        println!(
+            "{} - Resuming the download of {} from {} bytes",
+            module_path!(),
+            filename,
+            offset
+        );
+        easy.resume_from(offset)?;
+    }
+
+    // A mirror that ignores the range request sends the whole file, which replaces the partial file
+    let ignores_range = Cell::new(false);
+    let restarted = Cell::new(false);
+    let downloaded = Cell::new(offset);
+    let write_error = RefCell::new(None);
+    let mut percent = (offset * 100 / size) as u8;
+
+    {
+        let mut transfer = easy.transfer();
+        transfer.header_function(|header| {
+            if header.starts_with(b"HTTP/") {
+                let status = header.split(|byte| *byte == b' ').nth(1);
+                ignores_range.set(offset > 0 && status == Some(&b"200"[..]));
+            }
+            true
+        })?;
+        transfer.write_function(|data| {
+            let restart = ignores_range.get() && !restarted.get();
+            if restart {
+                restarted.set(true);
+                downloaded.set(0);
+            }
+            let result = if restart { file.set_len(0) } else { Ok(()) };
+
+            match result.and_then(|()| file.write_all(data)) {
+                Ok(()) => {
+                    downloaded.set(downloaded.get() + data.len() as u64);
+                    update_progress(filename, |progress| progress.downloaded = downloaded.get());
+
+                    let current_percent = (downloaded.get().min(size) * 100 / size) as u8;
+                    if current_percent != percent {
+                        percent = current_percent;
+                        print!(
+                            "\r{} - Downloading {} - {}% complete ({} MB total)",
+                            module_path!(),
+                            filename,
+                            percent,
+                            size / 1_048_576
+                        );
+                    }
+                    Ok(data.len())
+                }
+                Err(error) => {
+                    *write_error.borrow_mut() = Some(error);
+                    // Aborts the transfer
+                    Ok(0)
+                }
+            }
+        })?;
+
+        let result = transfer.perform();
+        if let Some(error) = write_error.borrow_mut().take() {
+            return Err(error.into());
+        }
+        result?;
+    }
+
+    Ok(())
+}
+
+/// Verifies the size and the checksum of the downloaded partial file, and returns its bytes.
+/// A complete partial file that fails the verification is removed, and an incomplete one is kept to be resumed.
+#[cfg(any(test, feature = "remote"))]
+fn verify(filename: &str, partial_path: &Path, checksum: &str, size: u64) -> Result<Vec<u8>, ParametersError> {
+    update_progress(filename, |progress| progress.state = DownloadState::Verifying);
+
+    let buffer = fs::read(partial_path)?;
+    if (buffer.len() as u64) < size {
+        update_progress(filename, |progress| progress.state = DownloadState::Downloading);
+        return Err(ParametersError::Message(format!(
+            "the download is incomplete, with {} of {} bytes",
+            buffer.len(),
+            size
+        )));
+    }
+
+    let buffer_checksum = hex::encode(sha256(&buffer));
+    if buffer.len() as u64 != size || buffer_checksum != checksum {
+        fs::remove_file(partial_path)?;
+        return Err(ParametersError::ChecksumMismatch(checksum.into(), buffer_checksum));
+    }
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_download_path() {
+        assert_eq!(
+            partial_path(Path::new("/tmp/posw_snark_pk-1a2b3c4.params")),
+            PathBuf::from("/tmp/posw_snark_pk-1a2b3c4.params.partial")
+        );
+    }
+
+    #[test]
+    fn verify_downloaded_file() {
+        let dir = env::temp_dir().join(format!("snarkos_parameters_download_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let partial_path = partial_path(&dir.join("test.params"));
+
+        let bytes = b"snarkos test parameters".to_vec();
+        let checksum = hex::encode(sha256(&bytes));
+        let size = bytes.len() as u64;
+
+        // An incomplete download is kept to be resumed
+        fs::write(&partial_path, &bytes[..10]).unwrap();
+        assert!(verify("test.params", &partial_path, &checksum, size).is_err());
+        assert!(partial_path.exists());
+
+        fs::write(&partial_path, &bytes).unwrap();
+        assert_eq!(verify("test.params", &partial_path, &checksum, size).unwrap(), bytes);
+
+        // A corrupted download is removed
+        fs::write(&partial_path, b"snarkos test parameterz").unwrap();
+        assert!(verify("test.params", &partial_path, &checksum, size).is_err());
+        assert!(!partial_path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ParameterFile, ParameterProvider};
+use snarkos_errors::parameters::ParametersError;
+
+use std::{fs, path::PathBuf};
+
+/// The marker the insecure test parameters start with. Their randomness is derived from a public seed, so anyone
+/// can forge the proofs they verify, and they must never be used outside of local networks and tests.
+pub const INSECURE_PARAMETERS_MARKER: &[u8] = b"SNARKOS INSECURE TEST PARAMETERS - FOR TESTING ONLY\n";
+
+/// Returns the bytes of the given parameters, marked as insecure test parameters.
+pub fn mark_insecure(bytes: &[u8]) -> Vec<u8> {
+    let mut marked = Vec::with_capacity(INSECURE_PARAMETERS_MARKER.len() + bytes.len());
+    marked.extend_from_slice(INSECURE_PARAMETERS_MARKER);
+    marked.extend_from_slice(bytes);
+    marked
+}
+
+/// Returns the bytes of the given parameters without their insecure marker, or `None` if they are not marked as
+/// insecure test parameters.
+pub fn strip_insecure_marker(bytes: &[u8]) -> Option<&[u8]> {
+    match bytes.starts_with(INSECURE_PARAMETERS_MARKER) {
+        true => Some(&bytes[INSECURE_PARAMETERS_MARKER.len()..]),
+        false => None,
+    }
+}
+
+/// The insecure test parameters of the given directory, generated by `snarkos parameters generate` and named after
+/// the parameters. The files must carry the insecure marker, and their checksums are not verified.
+#[derive(Clone, Debug)]
+pub struct InsecureProvider {
+    dir: PathBuf,
+}
+
+impl InsecureProvider {
+    /// Returns the provider of the insecure test parameters of the given directory.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl ParameterProvider for InsecureProvider {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        let path = self.dir.join(file.filename());
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(&path)?;
+        match strip_insecure_marker(&bytes) {
+            Some(parameters) => {
+                eprintln!(
+                    "\nWARNING - loading the insecure test parameters {:?}. They are for testing only.\n",
+                    path
+                );
+                Ok(Some(parameters.to_vec()))
+            }
+            None => Err(ParametersError::InsecureMarkerMissing(file.name.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PoswSNARKVKParameters;
+
+    use std::env;
+
+    #[test]
+    fn strip_marker() {
+        let marked = mark_insecure(&[1, 2, 3]);
+        assert!(marked.starts_with(INSECURE_PARAMETERS_MARKER));
+        assert_eq!(strip_insecure_marker(&marked), Some(&[1u8, 2, 3][..]));
+        assert_eq!(strip_insecure_marker(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn load_insecure_parameters() {
+        let file = PoswSNARKVKParameters::FILE;
+
+        let dir = env::temp_dir().join(format!("snarkos_insecure_parameters_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let provider = InsecureProvider::new(dir.clone());
+        assert_eq!(provider.load(&file).unwrap(), None);
+
+        // The checksum of the insecure parameters is not verified
+        fs::write(dir.join(file.filename()), mark_insecure(&[1, 2, 3])).unwrap();
+        assert_eq!(provider.load(&file).unwrap(), Some(vec![1, 2, 3]));
+
+        // The parameters without the insecure marker are rejected
+        fs::write(dir.join(file.filename()), &[1, 2, 3]).unwrap();
+        assert!(provider.load(&file).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
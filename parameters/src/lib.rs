@@ -16,8 +16,20 @@
 
 #![allow(clippy::module_inception)]
 
+pub mod downloader;
+pub use downloader::*;
+
 pub mod genesis;
 pub use genesis::*;
 
+pub mod insecure;
+pub use insecure::*;
+
+pub mod mapped;
+pub use mapped::*;
+
 pub mod params;
 pub use params::*;
+
+pub mod provider;
+pub use provider::*;
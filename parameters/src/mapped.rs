@@ -0,0 +1,71 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use memmap::Mmap;
+use std::{fs::File, io, ops::Deref, path::Path};
+
+/// The bytes of a parameter file, memory-mapped from the file if it is stored locally, so the operating system
+/// pages them in as they are read, and out under memory pressure, rather than the node holding a copy of them.
+pub enum ParameterBytes {
+    /// The bytes read in memory, such as the downloaded parameters
+    Owned(Vec<u8>),
+    /// The bytes mapped from a local parameter file
+    Mapped(Mmap),
+}
+
+impl ParameterBytes {
+    /// Maps the bytes of the given parameter file.
+    pub fn map(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // The parameter files are stored once, before they are loaded, and are not modified while they are mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Ok(ParameterBytes::Mapped(mmap))
+    }
+}
+
+impl Deref for ParameterBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ParameterBytes::Owned(bytes) => bytes,
+            ParameterBytes::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{env, fs};
+
+    #[test]
+    fn map_parameter_file() {
+        let path = env::temp_dir().join(format!("snarkos_mapped_parameters_{}", std::process::id()));
+        let bytes: Vec<u8> = (0..=255).collect();
+        fs::write(&path, &bytes).unwrap();
+
+        let mapped = ParameterBytes::map(&path).unwrap();
+        assert_eq!(&mapped[..], &bytes[..]);
+        assert_eq!(&ParameterBytes::Owned(bytes.clone())[..], &bytes[..]);
+
+        drop(mapped);
+        fs::remove_file(&path).unwrap();
+    }
+}
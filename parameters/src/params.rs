@@ -14,18 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use snarkos_algorithms::crh::sha256::sha256;
+use crate::{load_mapped_parameters, load_parameters, ParameterBytes, ParameterFile};
 use snarkos_errors::parameters::ParametersError;
 use snarkos_models::parameters::Parameters;
 
-use std::{
-    fs::{self, File},
-    io::Write,
-    path::{Path, PathBuf},
-};
-
-#[cfg(any(test, feature = "remote"))]
-use curl::easy::Easy;
+/// The environment variable of the directory the remote parameters are loaded from and downloaded to,
+/// in place of the directory of this crate.
+pub const PARAMETERS_DIR_VAR: &str = "SNARKOS_PARAMETERS_DIR";
 
 #[cfg(any(test, feature = "remote"))]
 pub const REMOTE_URL: &str = "https://snarkos-testnet.s3-us-west-2.amazonaws.com";
@@ -35,17 +30,25 @@ macro_rules! impl_params {
         #[derive(Clone, Debug, PartialEq, Eq)]
         pub struct $name;
 
+        impl $name {
+            /// The parameter file, compiled into this crate unless the `embedded` feature is disabled.
+            pub const FILE: ParameterFile = ParameterFile {
+                name: $fname,
+                checksum: <Self as Parameters>::CHECKSUM,
+                size: $size,
+                #[cfg(feature = "embedded")]
+                embedded: Some(include_bytes!(concat!("params/", $fname, ".params"))),
+                #[cfg(not(feature = "embedded"))]
+                embedded: None,
+            };
+        }
+
         impl Parameters for $name {
             const CHECKSUM: &'static str = include_str!(concat!("params/", $fname, ".checksum"));
             const SIZE: u64 = $size;
 
             fn load_bytes() -> Result<Vec<u8>, ParametersError> {
-                let buffer = include_bytes!(concat!("params/", $fname, ".params"));
-                let checksum = hex::encode(sha256(buffer));
-                match Self::CHECKSUM == checksum {
-                    true => Ok(buffer.to_vec()),
-                    false => Err(ParametersError::ChecksumMismatch(Self::CHECKSUM.into(), checksum)),
-                }
+                load_parameters(&Self::FILE)
             }
         }
 
@@ -60,137 +63,33 @@ macro_rules! impl_params {
 
 macro_rules! impl_params_remote {
     ($name: ident, $fname: tt, $size: tt) => {
-
         pub struct $name;
 
+        impl $name {
+            /// The parameter file, stored in the parameters directory or downloaded from the mirrors.
+            pub const FILE: ParameterFile = ParameterFile {
+                name: $fname,
+                checksum: <Self as Parameters>::CHECKSUM,
+                size: $size,
+                embedded: None,
+            };
+
+            /// Loads the parameters memory-mapped from their local file, so they are paged in as they are read
+            /// rather than copied in memory, or downloads and stores them if they are not stored locally.
+            pub fn load_mapped() -> Result<ParameterBytes, ParametersError> {
+                load_mapped_parameters(&Self::FILE)
+            }
+        }
+
         impl Parameters for $name {
             const CHECKSUM: &'static str = include_str!(concat!("params/", $fname, ".checksum"));
             const SIZE: u64 = $size;
 
             fn load_bytes() -> Result<Vec<u8>, ParametersError> {
-                // Compose the correct file path for the parameter file.
-                let filename = Self::versioned_filename();
-                let mut file_path = PathBuf::from(file!());
-                file_path.pop();
-                file_path.push("params/");
-                file_path.push(&filename);
-
-                // Compute the relative path.
-                let relative_path = file_path.strip_prefix("parameters")?;
-
-                // Compute the absolute path.
-                let mut absolute_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-                absolute_path.push(&relative_path);
-
-                let buffer = if relative_path.exists() {
-                    // Attempts to load the parameter file locally with a relative path.
-                    fs::read(relative_path)?
-                } else if absolute_path.exists() {
-                    // Attempts to load the parameter file locally with an absolute path.
-                    fs::read(absolute_path)?
-                } else {
-                    // Downloads the missing parameters and stores it in the local directory for use.
-                    eprintln!(
-                        "\nWARNING - \"{}\" does not exist. snarkOS will download this file remotely and store it locally. Please ensure \"{}\" is stored in {:?}.\n",
-                        filename, filename, file_path
-                    );
-                    let output = Self::load_remote()?;
-                    match Self::store_bytes(&output, &relative_path, &absolute_path, &file_path) {
-                        Ok(()) => output,
-                        Err(_) => {
-                            eprintln!(
-                                "\nWARNING - Failed to store \"{}\" locally. Please download this file manually and ensure it is stored in {:?}.\n",
-                                filename, file_path
-                            );
-                            output
-                        }
-                    }
-                };
-
-                let checksum = hex::encode(sha256(&buffer));
-                match Self::CHECKSUM == checksum {
-                    true => Ok(buffer),
-                    false => Err(ParametersError::ChecksumMismatch(Self::CHECKSUM.into(), checksum)),
-                }
+                load_parameters(&Self::FILE)
             }
         }
-
-        impl $name {
-            #[cfg(any(test, feature = "remote"))]
-            pub fn load_remote() -> Result<Vec<u8>, ParametersError> {
-                println!("{} - Downloading parameters...", module_path!());
-                let mut buffer = vec![];
-                let url = Self::remote_url();
-                Self::remote_fetch(&mut buffer, &url)?;
-                println!("\n{} - Download complete", module_path!());
-
-                // Verify the checksum of the remote data before returning
-                let checksum = hex::encode(sha256(&buffer));
-                match Self::CHECKSUM == checksum {
-                    true => Ok(buffer),
-                    false => Err(ParametersError::ChecksumMismatch(Self::CHECKSUM.into(), checksum)),
-                }
-            }
-
-            #[cfg(not(any(test, feature = "remote")))]
-            pub fn load_remote() -> Result<Vec<u8>, ParametersError> {
-                Err(ParametersError::RemoteFetchDisabled)
-            }
-
-            fn versioned_filename() -> String {
-                match Self::CHECKSUM.get(0..7) {
-                    Some(sum) => format!("{}-{}.params", $fname, sum),
-                    _ => concat!($fname, ".params",).to_string()
-                }
-            }
-
-            #[cfg(any(test, feature = "remote"))]
-            fn remote_url() -> String {
-                format!("{}/{}", REMOTE_URL, Self::versioned_filename())
-            }
-
-            fn store_bytes(
-                buffer: &[u8],
-                relative_path: &Path,
-                absolute_path: &Path,
-                file_path: &Path,
-            ) -> Result<(), ParametersError> {
-                println!("{} - Storing parameters ({:?})", module_path!(), file_path);
-                // Attempt to write the parameter buffer to a file.
-                if let Ok(mut file) = File::create(relative_path) {
-                    file.write_all(&buffer)?;
-                } else if let Ok(mut file) = File::create(absolute_path) {
-                    file.write_all(&buffer)?;
-                }
-                Ok(())
-            }
-
-            #[cfg(any(test, feature = "remote"))]
-            fn remote_fetch(buffer: &mut Vec<u8>, url: &str) -> Result<(), ParametersError> {
-                let mut easy = Easy::new();
-                easy.url(url)?;
-                easy.progress(true)?;
-                easy.progress_function(|total_download, current_download, _, _| {
-                    let percent = (current_download / total_download) * 100.0;
-                    let size_in_megabytes = total_download as u64 / 1_048_576;
-                    print!(
-                        "\r{} - {:.2}% complete ({:#} MB total)",
-                        module_path!(),
-                        percent,
-                        size_in_megabytes
-                    );
-                    true
-                })?;
-
-                let mut transfer = easy.transfer();
-                transfer.write_function(|data| {
-                    buffer.extend_from_slice(data);
-                    Ok(data.len())
-                })?;
-                Ok(transfer.perform()?)
-            }
-        }
-    }
+    };
 }
 
 // Commitments
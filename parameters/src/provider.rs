@@ -0,0 +1,374 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ParameterBytes, PARAMETERS_DIR_VAR};
+use snarkos_algorithms::crh::sha256::sha256;
+use snarkos_errors::parameters::ParametersError;
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    env,
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+#[cfg(any(test, feature = "remote"))]
+use crate::download_parameters;
+
+/// The provider the parameters are loaded from, replaced by the embedders and the test harnesses.
+static PROVIDER: Lazy<RwLock<Arc<dyn ParameterProvider>>> = Lazy::new(|| RwLock::new(Arc::new(DefaultProvider)));
+
+/// A parameter file, as described by this crate.
+#[derive(Clone, Copy)]
+pub struct ParameterFile {
+    /// The name of the parameters, such as `posw_snark_pk`
+    pub name: &'static str,
+    /// The checksum of the parameters
+    pub checksum: &'static str,
+    /// The size of the parameters, in bytes
+    pub size: u64,
+    /// The parameters compiled into this crate, if they are embedded
+    pub embedded: Option<&'static [u8]>,
+}
+
+impl ParameterFile {
+    /// Returns the name of the file of the parameters, versioned by their checksum.
+    pub fn versioned_filename(&self) -> String {
+        match self.checksum.get(0..7) {
+            Some(sum) => format!("{}-{}.params", self.name, sum),
+            _ => format!("{}.params", self.name),
+        }
+    }
+
+    /// Returns the name of the file of the parameters in this crate.
+    pub fn filename(&self) -> String {
+        format!("{}.params", self.name)
+    }
+
+    /// Verifies the checksum of the given bytes of the parameters.
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), ParametersError> {
+        let checksum = hex::encode(sha256(bytes));
+        match self.checksum == checksum {
+            true => Ok(()),
+            false => Err(ParametersError::ChecksumMismatch(self.checksum.into(), checksum)),
+        }
+    }
+}
+
+/// A source of parameters. The node loads the parameters from the embedded bytes, the parameter files and the
+/// mirrors of the default provider, and the embedders and the test harnesses supply their own parameters, such as
+/// test-sized parameters, with a provider of their own.
+pub trait ParameterProvider: Send + Sync {
+    /// Returns the bytes of the given parameters, or `None` if they are not provided.
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError>;
+
+    /// Returns the bytes of the given parameters, memory-mapped if the provider loads them from a file.
+    fn load_mapped(&self, file: &ParameterFile) -> Result<Option<ParameterBytes>, ParametersError> {
+        Ok(self.load(file)?.map(ParameterBytes::Owned))
+    }
+}
+
+/// Replaces the provider the parameters are loaded from.
+pub fn set_parameter_provider(provider: Arc<dyn ParameterProvider>) {
+    *PROVIDER.write().expect("the parameter provider lock is poisoned") = provider;
+}
+
+/// Returns the provider the parameters are loaded from.
+pub fn parameter_provider() -> Arc<dyn ParameterProvider> {
+    PROVIDER
+        .read()
+        .expect("the parameter provider lock is poisoned")
+        .clone()
+}
+
+/// Loads the given parameters from the parameter provider.
+pub fn load_parameters(file: &ParameterFile) -> Result<Vec<u8>, ParametersError> {
+    parameter_provider()
+        .load(file)?
+        .ok_or_else(|| ParametersError::Missing(file.name.into()))
+}
+
+/// Loads the given parameters from the parameter provider, memory-mapped if they are loaded from a file.
+pub fn load_mapped_parameters(file: &ParameterFile) -> Result<ParameterBytes, ParametersError> {
+    parameter_provider()
+        .load_mapped(file)?
+        .ok_or_else(|| ParametersError::Missing(file.name.into()))
+}
+
+/// The parameters compiled into this crate.
+#[derive(Clone, Debug, Default)]
+pub struct EmbeddedProvider;
+
+impl ParameterProvider for EmbeddedProvider {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        match file.embedded {
+            Some(bytes) => {
+                file.verify(bytes)?;
+                Ok(Some(bytes.to_vec()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The parameter files of the given directories, searched in order, named after the parameters with or without
+/// their checksum.
+#[derive(Clone, Debug, Default)]
+pub struct FilesystemProvider {
+    dirs: Vec<PathBuf>,
+}
+
+impl FilesystemProvider {
+    /// Returns the provider of the parameter files of the given directories.
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        Self { dirs }
+    }
+
+    /// Returns the provider of the parameter files of the parameters directory, if one is set, and of this crate.
+    pub fn local() -> Self {
+        let crate_dir = PathBuf::from("src/params");
+        let absolute_crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/params");
+
+        Self::new(
+            env::var_os(PARAMETERS_DIR_VAR)
+                .map(PathBuf::from)
+                .into_iter()
+                .chain(vec![crate_dir, absolute_crate_dir])
+                .collect(),
+        )
+    }
+
+    /// Returns the path of the stored parameter file, if it exists.
+    pub fn path(&self, file: &ParameterFile) -> Option<PathBuf> {
+        let filenames = [file.versioned_filename(), file.filename()];
+
+        self.dirs
+            .iter()
+            .flat_map(|dir| filenames.iter().map(move |filename| dir.join(filename)))
+            .find(|path| path.is_file())
+    }
+}
+
+impl ParameterProvider for FilesystemProvider {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        match self.path(file) {
+            Some(path) => {
+                let bytes = fs::read(path)?;
+                file.verify(&bytes)?;
+                Ok(Some(bytes))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn load_mapped(&self, file: &ParameterFile) -> Result<Option<ParameterBytes>, ParametersError> {
+        match self.path(file) {
+            Some(path) => {
+                let bytes = ParameterBytes::map(&path)?;
+                file.verify(&bytes)?;
+                Ok(Some(bytes))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The parameters downloaded from the mirrors, and installed in the given directory.
+#[derive(Clone, Debug)]
+pub struct RemoteProvider {
+    install_dir: PathBuf,
+}
+
+impl RemoteProvider {
+    /// Returns the provider of the parameters downloaded to the given directory.
+    pub fn new(install_dir: PathBuf) -> Self {
+        Self { install_dir }
+    }
+
+    /// Returns the provider of the parameters downloaded to the parameters directory, if one is set,
+    /// or to this crate.
+    pub fn local() -> Self {
+        Self::new(
+            env::var_os(PARAMETERS_DIR_VAR)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/params")),
+        )
+    }
+}
+
+impl ParameterProvider for RemoteProvider {
+    #[cfg(any(test, feature = "remote"))]
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        let filename = file.versioned_filename();
+        let path = self.install_dir.join(&filename);
+        eprintln!(
+            "\nWARNING - \"{}\" does not exist. snarkOS will download this file remotely and store it locally. Please ensure \"{}\" is stored in {:?}.\n",
+            filename, filename, self.install_dir
+        );
+
+        Ok(Some(download_parameters(&filename, file.checksum, file.size, &path)?))
+    }
+
+    #[cfg(not(any(test, feature = "remote")))]
+    fn load(&self, _file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        Err(ParametersError::RemoteFetchDisabled)
+    }
+}
+
+/// The providers searched in order, until one of them provides the parameters.
+#[derive(Clone, Default)]
+pub struct ProviderChain {
+    providers: Vec<Arc<dyn ParameterProvider>>,
+}
+
+impl ProviderChain {
+    /// Returns the chain of the given providers.
+    pub fn new(providers: Vec<Arc<dyn ParameterProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Adds a provider, searched after the previous ones.
+    pub fn with(mut self, provider: Arc<dyn ParameterProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+}
+
+impl ParameterProvider for ProviderChain {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        for provider in &self.providers {
+            if let Some(bytes) = provider.load(file)? {
+                return Ok(Some(bytes));
+            }
+        }
+        Ok(None)
+    }
+
+    fn load_mapped(&self, file: &ParameterFile) -> Result<Option<ParameterBytes>, ParametersError> {
+        for provider in &self.providers {
+            if let Some(bytes) = provider.load_mapped(file)? {
+                return Ok(Some(bytes));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The provider of the node: the embedded parameters, then the parameter files of the parameters directory and of
+/// this crate, then the mirrors. The directories are read from the environment on each load.
+#[derive(Clone, Debug, Default)]
+pub struct DefaultProvider;
+
+impl DefaultProvider {
+    fn chain(&self) -> ProviderChain {
+        ProviderChain::default()
+            .with(Arc::new(EmbeddedProvider))
+            .with(Arc::new(FilesystemProvider::local()))
+            .with(Arc::new(RemoteProvider::local()))
+    }
+}
+
+impl ParameterProvider for DefaultProvider {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        self.chain().load(file)
+    }
+
+    fn load_mapped(&self, file: &ParameterFile) -> Result<Option<ParameterBytes>, ParametersError> {
+        self.chain().load_mapped(file)
+    }
+}
+
+/// A generator of parameters, run on each load.
+type ParameterGenerator = Box<dyn Fn() -> Result<Vec<u8>, ParametersError> + Send + Sync>;
+
+/// The parameters supplied in memory by an embedder or a test harness, by name, as bytes or as generators, such as
+/// the generators of test-sized parameters. Their checksums are not verified.
+#[derive(Default)]
+pub struct MemoryProvider {
+    parameters: HashMap<String, ParameterGenerator>,
+}
+
+impl MemoryProvider {
+    /// Returns the provider of no parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provides the given bytes as the parameters of the given name.
+    pub fn with_bytes(self, name: &str, bytes: Vec<u8>) -> Self {
+        self.with_generator(name, move || Ok(bytes.clone()))
+    }
+
+    /// Provides the output of the given generator as the parameters of the given name.
+    pub fn with_generator<F>(mut self, name: &str, generator: F) -> Self
+    where
+        F: Fn() -> Result<Vec<u8>, ParametersError> + Send + Sync + 'static,
+    {
+        self.parameters.insert(name.to_string(), Box::new(generator));
+        self
+    }
+}
+
+impl ParameterProvider for MemoryProvider {
+    fn load(&self, file: &ParameterFile) -> Result<Option<Vec<u8>>, ParametersError> {
+        self.parameters.get(file.name).map(|generator| generator()).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LedgerMerkleTreeParameters;
+    use snarkos_models::parameters::Parameters;
+
+    #[test]
+    fn load_from_provider_chain() {
+        let file = LedgerMerkleTreeParameters::FILE;
+        let embedded = LedgerMerkleTreeParameters::load_bytes().unwrap();
+
+        let memory = MemoryProvider::new().with_bytes(file.name, vec![1, 2, 3]);
+        let chain = ProviderChain::new(vec![Arc::new(memory), Arc::new(EmbeddedProvider)]);
+        assert_eq!(chain.load(&file).unwrap(), Some(vec![1, 2, 3]));
+
+        let chain = ProviderChain::new(vec![Arc::new(MemoryProvider::new()), Arc::new(EmbeddedProvider)]);
+        assert_eq!(chain.load(&file).unwrap(), Some(embedded));
+
+        assert_eq!(ProviderChain::default().load(&file).unwrap(), None);
+    }
+
+    #[test]
+    fn load_from_filesystem() {
+        let file = LedgerMerkleTreeParameters::FILE;
+        let bytes = LedgerMerkleTreeParameters::load_bytes().unwrap();
+
+        let dir = env::temp_dir().join(format!("snarkos_parameter_provider_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let provider = FilesystemProvider::new(vec![dir.clone()]);
+        assert_eq!(provider.load(&file).unwrap(), None);
+
+        fs::write(dir.join(file.versioned_filename()), &bytes).unwrap();
+        assert_eq!(provider.load(&file).unwrap(), Some(bytes.clone()));
+        assert_eq!(&provider.load_mapped(&file).unwrap().unwrap()[..], &bytes[..]);
+
+        // The checksum of the parameter files is verified
+        fs::write(dir.join(file.versioned_filename()), &bytes[1..]).unwrap();
+        assert!(provider.load(&file).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -22,9 +22,9 @@ use snarkos_curves::{
     bls12_377::Fr,
     edwards_bls12::{EdwardsProjective, Fq},
 };
-use snarkos_errors::posw::PoswError;
+use snarkos_errors::{algorithms::SNARKError, posw::PoswError};
 use snarkos_gadgets::{algorithms::crh::PedersenCompressedCRHGadget, curves::edwards_bls12::EdwardsBlsGadget};
-use snarkos_marlin::snark::SRS;
+use snarkos_marlin::{snark::SRS, AHPForR1CS};
 use snarkos_models::{
     algorithms::{MaskedMerkleParameters, SNARK},
     curves::{to_field_vec::ToConstraintField, PairingEngine, PrimeField},
@@ -45,7 +45,7 @@ use snarkos_utilities::{
 
 use blake2::{digest::Digest, Blake2s};
 use rand::{rngs::OsRng, Rng};
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
 // We need to instantiate the Merkle Tree and the Gadget, but these should not be
 // proving system specific
@@ -123,6 +123,25 @@ where
         }
     }
 
+    /// Returns the number of constraints, variables and non-zero matrix entries of the PoSW circuit,
+    /// which are the smallest sizes of a universal SRS the circuit can be indexed with.
+    pub fn circuit_size() -> Result<(usize, usize, usize), PoswError> {
+        let index = AHPForR1CS::<F>::index(&POSWCircuit::<F, M, HG, CP> {
+            // the circuit will be padded internally
+            leaves: vec![None; 0],
+            merkle_parameters: PARAMS.clone(),
+            mask: None,
+            root: None,
+            field_type: PhantomData,
+            crh_gadget_type: PhantomData,
+            circuit_parameters_type: PhantomData,
+        })
+        .map_err(|error| SNARKError::Crate("marlin", format!("{:?}", error)))?;
+
+        let info = index.index_info;
+        Ok((info.num_constraints, info.num_variables, info.num_non_zero))
+    }
+
     /// Hashes the proof and checks it against the difficulty
     fn check_difficulty(&self, proof: &[u8], difficulty_target: u64) -> bool {
         let hash_result = sha256d_to_u64(proof);
@@ -202,22 +221,34 @@ where
         rng: &mut R,
         max_nonce: u32,
     ) -> Result<(u32, Vec<u8>), PoswError> {
+        let solution = self.mine_nonces(subroots, difficulty_target, rng, 0..max_nonce, || true)?;
+        Ok(solution.expect("the mining is never stopped"))
+    }
+
+    /// Calculates a POSW and a nonce of the given range under the difficulty target, trying random
+    /// nonces of the range. The `keep_mining` closure is called before each attempt, and the mining
+    /// stops without a solution once it returns false.
+    pub fn mine_nonces<R: Rng, K: FnMut() -> bool>(
+        &self,
+        subroots: &[[u8; 32]],
+        difficulty_target: u64,
+        rng: &mut R,
+        nonces: Range<u32>,
+        mut keep_mining: K,
+    ) -> Result<Option<(u32, Vec<u8>)>, PoswError> {
         let pk = self.pk.as_ref().expect("tried to mine without a PK set up");
 
-        let mut nonce;
-        let mut proof;
-        let mut serialized_proof;
-        loop {
-            nonce = rng.gen_range(0, max_nonce);
-            proof = Self::prove(&pk, nonce, subroots, rng)?;
+        while keep_mining() {
+            let nonce = rng.gen_range(nonces.start, nonces.end);
+            let proof = Self::prove(&pk, nonce, subroots, rng)?;
 
-            serialized_proof = to_bytes!(proof)?;
+            let serialized_proof = to_bytes!(proof)?;
             if self.check_difficulty(&serialized_proof, difficulty_target) {
-                break;
+                return Ok(Some((nonce, serialized_proof)));
             }
         }
 
-        Ok((nonce, serialized_proof))
+        Ok(None)
     }
 
     /// Runs the internal SNARK `prove` function on the POSW circuit and returns
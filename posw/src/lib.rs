@@ -137,4 +137,21 @@ mod tests {
         let proof = <Marlin<Bls12_377> as SNARK>::Proof::read(&proof[..]).unwrap();
         posw.verify(nonce, &proof, &pedersen_merkle_root).unwrap();
     }
+    #[test]
+    fn marlin_index_with_circuit_sized_srs() {
+        let rng = &mut XorShiftRng::seed_from_u64(1234567);
+
+        // the smallest universal SRS the circuit can be indexed with
+        let (num_constraints, num_variables, num_non_zero) = PoswMarlin::circuit_size().unwrap();
+        let universal_srs = snarkos_marlin::snark::Marlin::<Bls12_377>::universal_setup(
+            num_constraints,
+            num_variables,
+            num_non_zero,
+            rng,
+        )
+        .unwrap();
+
+        let posw = PoswMarlin::index(universal_srs).unwrap();
+        assert!(posw.pk.is_some());
+    }
 }
@@ -17,6 +17,9 @@
 #![allow(unused_imports)]
 pub use inner::*;
 
+pub mod metrics;
+pub use metrics::*;
+
 #[cfg(feature = "print-trace")]
 #[macro_use]
 pub mod inner {
@@ -0,0 +1,152 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The metrics registry of the node, shared by its modules.
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+enum MetricValue {
+    Counter(u64),
+    Gauge(f64),
+    Timing { count: u64, sum: Duration },
+}
+
+struct Metric {
+    help: &'static str,
+    value: MetricValue,
+}
+
+/// A registry of named metrics, encoded in the Prometheus text format.
+#[derive(Default)]
+pub struct Metrics {
+    metrics: Mutex<BTreeMap<&'static str, Metric>>,
+}
+
+impl Metrics {
+    /// Increment the counter with the given name.
+    pub fn increment_counter(&self, name: &'static str, help: &'static str, amount: u64) {
+        let mut metrics = self.metrics.lock().expect("metrics lock poisoned");
+
+        let metric = metrics.entry(name).or_insert(Metric {
+            help,
+            value: MetricValue::Counter(0),
+        });
+
+        if let MetricValue::Counter(count) = &mut metric.value {
+            *count += amount;
+        }
+    }
+
+    /// Set the gauge with the given name.
+    pub fn set_gauge(&self, name: &'static str, help: &'static str, value: f64) {
+        let mut metrics = self.metrics.lock().expect("metrics lock poisoned");
+
+        metrics.insert(name, Metric {
+            help,
+            value: MetricValue::Gauge(value),
+        });
+    }
+
+    /// Add a duration to the timing with the given name.
+    pub fn record_timing(&self, name: &'static str, help: &'static str, duration: Duration) {
+        let mut metrics = self.metrics.lock().expect("metrics lock poisoned");
+
+        let metric = metrics.entry(name).or_insert(Metric {
+            help,
+            value: MetricValue::Timing {
+                count: 0,
+                sum: Duration::default(),
+            },
+        });
+
+        if let MetricValue::Timing { count, sum } = &mut metric.value {
+            *count += 1;
+            *sum += duration;
+        }
+    }
+
+    /// Run the given function, and add its duration to the timing with the given name.
+    pub fn time<T, F: FnOnce() -> T>(&self, name: &'static str, help: &'static str, f: F) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record_timing(name, help, start.elapsed());
+
+        result
+    }
+
+    /// Returns the metrics in the Prometheus text format.
+    /// The timings are encoded as summaries of their total duration in seconds and their count.
+    pub fn encode(&self) -> String {
+        let metrics = self.metrics.lock().expect("metrics lock poisoned");
+
+        let mut encoded = String::new();
+        for (name, metric) in metrics.iter() {
+            let _ = writeln!(encoded, "# HELP {} {}", name, metric.help);
+
+            let _ = match &metric.value {
+                MetricValue::Counter(count) => writeln!(encoded, "# TYPE {} counter\n{} {}", name, name, count),
+                MetricValue::Gauge(value) => writeln!(encoded, "# TYPE {} gauge\n{} {}", name, name, value),
+                MetricValue::Timing { count, sum } => writeln!(
+                    encoded,
+                    "# TYPE {} summary\n{}_sum {}\n{}_count {}",
+                    name,
+                    name,
+                    sum.as_secs_f64(),
+                    name,
+                    count
+                ),
+            };
+        }
+
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_metrics() {
+        let metrics = Metrics::default();
+
+        metrics.increment_counter("test_counter", "A test counter", 2);
+        metrics.increment_counter("test_counter", "A test counter", 1);
+        metrics.set_gauge("test_gauge", "A test gauge", 1.5);
+        metrics.record_timing("test_timing", "A test timing", Duration::from_millis(500));
+        metrics.record_timing("test_timing", "A test timing", Duration::from_secs(1));
+
+        let expected = "# HELP test_counter A test counter\n\
+                        # TYPE test_counter counter\n\
+                        test_counter 3\n\
+                        # HELP test_gauge A test gauge\n\
+                        # TYPE test_gauge gauge\n\
+                        test_gauge 1.5\n\
+                        # HELP test_timing A test timing\n\
+                        # TYPE test_timing summary\n\
+                        test_timing_sum 1.5\n\
+                        test_timing_count 2\n";
+
+        assert_eq!(metrics.encode(), expected);
+    }
+}
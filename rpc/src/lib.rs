@@ -22,6 +22,18 @@
 // #![cfg_attr(nightly, warn(missing_docs))]
 #![cfg_attr(nightly, doc(include = "../documentation/concepts/rpc_server.md"))]
 
+pub mod rpc_compression;
+#[doc(inline)]
+pub use rpc_compression::*;
+
+pub mod rpc_cors;
+#[doc(inline)]
+pub use rpc_cors::*;
+
+pub mod rpc_graphql;
+#[doc(inline)]
+pub use rpc_graphql::*;
+
 pub mod rpc_impl;
 #[doc(inline)]
 pub use rpc_impl::*;
@@ -30,10 +42,42 @@ pub mod rpc_impl_protected;
 #[doc(inline)]
 pub use rpc_impl_protected::*;
 
+pub mod rpc_ipc;
+#[doc(inline)]
+pub use rpc_ipc::*;
+
+pub mod rpc_jobs;
+#[doc(inline)]
+pub use rpc_jobs::*;
+
+pub mod rpc_limits;
+#[doc(inline)]
+pub use rpc_limits::*;
+
+pub mod rpc_metrics;
+#[doc(inline)]
+pub use rpc_metrics::*;
+
+pub mod rpc_rest;
+#[doc(inline)]
+pub use rpc_rest::*;
+
 pub mod rpc_server;
 #[doc(inline)]
 pub use rpc_server::*;
 
+pub mod rpc_subscriptions;
+#[doc(inline)]
+pub use rpc_subscriptions::*;
+
+pub mod rpc_tls;
+#[doc(inline)]
+pub use rpc_tls::*;
+
+pub mod rpc_tokens;
+#[doc(inline)]
+pub use rpc_tokens::*;
+
 pub mod rpc_trait;
 #[doc(inline)]
 pub use rpc_trait::*;
@@ -41,3 +85,11 @@ pub use rpc_trait::*;
 pub mod rpc_types;
 #[doc(inline)]
 pub use rpc_types::*;
+
+pub mod rpc_versions;
+#[doc(inline)]
+pub use rpc_versions::*;
+
+pub mod rpc_webhooks;
+#[doc(inline)]
+pub use rpc_webhooks::*;
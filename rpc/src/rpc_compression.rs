@@ -0,0 +1,384 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for compressing the responses of the RPC server.
+//!
+//! The JSON-RPC server does not encode its responses, so the requests of the clients accepting
+//! a compressed response are answered by a middleware calling the JSON-RPC handler itself.
+//! The compressed responses are streamed in chunks as they are encoded.
+
+use crate::{rpc_types::Meta, rpc_versions::RpcVersion};
+
+use flate2::{write::GzEncoder, Compression};
+use jsonrpc_core::{
+    futures::{future::Either, stream, Future, Stream},
+    MetaIoHandler,
+    Middleware,
+};
+use jsonrpc_http_server::{
+    hyper::{
+        self,
+        header::{HeaderValue, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY},
+        Body,
+        Method,
+        StatusCode,
+    },
+    RequestMiddleware,
+    RequestMiddlewareAction,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+/// The size of the pieces of a response encoded at once, each sent as a chunk.
+pub const COMPRESSION_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The zstd compression level of the responses, which favors speed.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The encodings of the compressed responses
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentEncoding {
+    /// The gzip encoding
+    Gzip,
+    /// The zstd encoding
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Returns the name of the encoding in the `Accept-Encoding` and `Content-Encoding` headers.
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Zstd => "zstd",
+        }
+    }
+}
+
+/// Defines the response compression of the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcCompressionConfig {
+    /// The encodings offered to the clients, by order of preference
+    pub encodings: Vec<ContentEncoding>,
+    /// The size in bytes below which the responses are not compressed
+    pub min_size: usize,
+}
+
+impl Default for RpcCompressionConfig {
+    fn default() -> Self {
+        Self {
+            encodings: vec![ContentEncoding::Zstd, ContentEncoding::Gzip],
+            min_size: 1024,
+        }
+    }
+}
+
+impl RpcCompressionConfig {
+    /// Returns the preferred encoding accepted by the given `Accept-Encoding` header, if any.
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<ContentEncoding> {
+        let accepted: Vec<&str> = accept_encoding
+            .split(',')
+            .filter_map(|coding| {
+                let mut parameters = coding.split(';').map(str::trim);
+                let name = parameters.next()?;
+
+                // The encodings with a zero quality value are refused
+                let is_refused = parameters.any(|parameter| {
+                    parameter.starts_with("q=") && parameter[2..].parse::<f32>().map_or(false, |q| q == 0.0)
+                });
+
+                if is_refused {
+                    None
+                } else {
+                    Some(name)
+                }
+            })
+            .collect();
+
+        self.encodings.iter().copied().find(|encoding| {
+            accepted
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(encoding.name()) || *name == "*")
+        })
+    }
+}
+
+/// The stream encoder of a response.
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Zstd(zstd::stream::write::Encoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: ContentEncoding) -> io::Result<Self> {
+        Ok(match encoding {
+            ContentEncoding::Gzip => Encoder::Gzip(GzEncoder::new(vec![], Compression::default())),
+            ContentEncoding::Zstd => Encoder::Zstd(zstd::stream::write::Encoder::new(vec![], ZSTD_LEVEL)?),
+        })
+    }
+
+    /// Encodes a piece of the response, and returns the encoded bytes.
+    fn encode(&mut self, piece: &[u8]) -> io::Result<Vec<u8>> {
+        let output = match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(piece)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Zstd(encoder) => {
+                encoder.write_all(piece)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+        };
+
+        Ok(std::mem::take(output))
+    }
+
+    /// Returns the last encoded bytes of the response.
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+/// Iterates over the chunks of an encoded response, encoding each piece of the response when it is reached.
+struct EncodedChunks {
+    encoder: Option<Encoder>,
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl Iterator for EncodedChunks {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.position >= self.data.len() {
+                return self.encoder.take().map(Encoder::finish);
+            }
+
+            let end = (self.position + COMPRESSION_CHUNK_SIZE).min(self.data.len());
+            let chunk = match self.encoder.as_mut()?.encode(&self.data[self.position..end]) {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    self.encoder = None;
+                    return Some(Err(error));
+                }
+            };
+            self.position = end;
+
+            // The encoder may buffer a whole piece
+            if !chunk.is_empty() {
+                return Some(Ok(chunk));
+            }
+        }
+    }
+}
+
+/// Returns the encoded bytes of the given data, as a body streamed in chunks.
+pub fn encoded_body(encoding: ContentEncoding, data: Vec<u8>) -> io::Result<Body> {
+    Ok(chunked_body(Encoder::new(encoding)?, data))
+}
+
+fn chunked_body(encoder: Encoder, data: Vec<u8>) -> Body {
+    let chunks = EncodedChunks {
+        encoder: Some(encoder),
+        data,
+        position: 0,
+    };
+
+    Body::wrap_stream(stream::iter_result(chunks))
+}
+
+/// Returns the response with the given body, compressed with the given encoding if it is large enough.
+fn compressed_response(
+    mut response: hyper::Response<Body>,
+    body: Vec<u8>,
+    encoding: ContentEncoding,
+    min_size: usize,
+) -> hyper::Response<Body> {
+    let headers = response.headers_mut();
+    headers.insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    // The small responses are not worth encoding
+    let encoder = if body.len() >= min_size {
+        Encoder::new(encoding).ok()
+    } else {
+        None
+    };
+
+    let encoder = match encoder {
+        Some(encoder) => encoder,
+        None => {
+            *response.body_mut() = Body::from(body);
+            return response;
+        }
+    };
+
+    // The encoded responses are sent in chunks
+    headers.remove(CONTENT_LENGTH);
+    headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.name()));
+    *response.body_mut() = chunked_body(encoder, body);
+
+    response
+}
+
+/// Compresses the responses of the RPC server for the clients accepting a supported encoding.
+/// The responses of the inner middleware are compressed, and the JSON-RPC requests are answered
+/// with the given handler.
+pub struct CompressionEndpoint<S: Middleware<Meta>, M> {
+    config: Option<RpcCompressionConfig>,
+    max_request_size: Option<usize>,
+    io: Arc<MetaIoHandler<Meta, S>>,
+    inner: M,
+}
+
+impl<S: Middleware<Meta>, M: RequestMiddleware> CompressionEndpoint<S, M>
+where
+    S::Future: Send,
+    S::CallFuture: Send,
+{
+    /// Creates the compression middleware of the given config, wrapping the given middleware
+    /// and JSON-RPC handler. The JSON-RPC requests larger than `max_request_size` are rejected.
+    /// If no config is given, the requests are passed to the inner middleware.
+    pub fn new(
+        config: Option<RpcCompressionConfig>,
+        max_request_size: Option<usize>,
+        io: MetaIoHandler<Meta, S>,
+        inner: M,
+    ) -> Self {
+        Self {
+            config,
+            max_request_size,
+            io: Arc::new(io),
+            inner,
+        }
+    }
+
+    /// Answers a JSON-RPC request with a response compressed with the given encoding.
+    fn handle_rpc_request(
+        &self,
+        request: hyper::Request<Body>,
+        encoding: ContentEncoding,
+        min_size: usize,
+    ) -> RequestMiddlewareAction {
+        let meta = Meta {
+            auth: request
+                .headers()
+                .get(AUTHORIZATION)
+                .map(|auth| auth.to_str().unwrap_or("").to_owned()),
+            is_local: false,
+            api_version: RpcVersion::from_path(request.uri().path()),
+        };
+
+        let io = self.io.clone();
+        let max_request_size = self.max_request_size;
+
+        // The bytes past the size limit are not kept
+        let body = request
+            .into_body()
+            .fold((vec![], false), move |(mut body, is_too_large), chunk| {
+                let is_too_large = is_too_large || max_request_size.map_or(false, |max| body.len() + chunk.len() > max);
+                if !is_too_large {
+                    body.extend_from_slice(&chunk);
+                }
+
+                Ok::<_, hyper::Error>((body, is_too_large))
+            });
+
+        let response = body.and_then(move |(body, is_too_large)| {
+            if is_too_large {
+                let mut response = hyper::Response::new(Body::empty());
+                *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                return Either::A(jsonrpc_core::futures::future::ok(response));
+            }
+
+            let request = String::from_utf8_lossy(&body).into_owned();
+
+            Either::B(io.handle_request(&request, meta).then(move |output| {
+                let mut response = hyper::Response::new(Body::empty());
+                response.headers_mut().insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/json; charset=utf-8"),
+                );
+
+                // Notifications are answered with an empty body
+                let body = output.ok().and_then(|output| output).unwrap_or_default();
+
+                Ok::<_, hyper::Error>(compressed_response(response, body.into_bytes(), encoding, min_size))
+            }))
+        });
+
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::new(response),
+        }
+    }
+}
+
+impl<S: Middleware<Meta>, M: RequestMiddleware> RequestMiddleware for CompressionEndpoint<S, M>
+where
+    S::Future: Send,
+    S::CallFuture: Send,
+{
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return self.inner.on_request(request),
+        };
+
+        let encoding = request
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|accept_encoding| accept_encoding.to_str().ok())
+            .and_then(|accept_encoding| config.negotiate(accept_encoding));
+
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => return self.inner.on_request(request),
+        };
+
+        let min_size = config.min_size;
+
+        match self.inner.on_request(request) {
+            RequestMiddlewareAction::Respond {
+                should_validate_hosts,
+                response,
+            } => RequestMiddlewareAction::Respond {
+                should_validate_hosts,
+                response: Box::new(response.and_then(move |response| {
+                    let (parts, body) = response.into_parts();
+
+                    body.concat2().map(move |body| {
+                        let response = hyper::Response::from_parts(parts, Body::empty());
+                        compressed_response(response, body.to_vec(), encoding, min_size)
+                    })
+                })),
+            },
+            RequestMiddlewareAction::Proceed { request, .. } if request.method() == Method::POST => {
+                self.handle_rpc_request(request, encoding, min_size)
+            }
+            action => action,
+        }
+    }
+}
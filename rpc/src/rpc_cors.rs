@@ -0,0 +1,221 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for answering the cross-origin requests of browser clients.
+
+use jsonrpc_core::futures::Future;
+use jsonrpc_http_server::{
+    cors::{AccessControlAllowHeaders, AccessControlAllowOrigin},
+    hyper::{
+        self,
+        header::{
+            HeaderValue,
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_METHODS,
+            ACCESS_CONTROL_ALLOW_ORIGIN,
+            ACCESS_CONTROL_MAX_AGE,
+            ACCESS_CONTROL_REQUEST_HEADERS,
+            ACCESS_CONTROL_REQUEST_METHOD,
+            ORIGIN,
+            VARY,
+        },
+        Body,
+        Method,
+        StatusCode,
+    },
+    DomainsValidation,
+    RequestMiddleware,
+    RequestMiddlewareAction,
+};
+use serde::{Deserialize, Serialize};
+
+/// The origin allowing any origin to query the node.
+pub const ANY_ORIGIN: &str = "*";
+
+fn default_allowed_methods() -> Vec<String> {
+    vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()]
+}
+
+/// Defines the CORS settings of the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcCorsConfig {
+    /// The origins of the browser clients allowed to query the node, or `*` to allow any origin
+    pub allowed_origins: Vec<String>,
+    /// The HTTP methods allowed in the cross-origin requests
+    #[serde(default = "default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    /// The headers allowed in the cross-origin requests. If empty, any header is allowed.
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    /// The number of seconds the browsers may cache the answers to the preflight requests
+    #[serde(default)]
+    pub max_age: Option<u32>,
+}
+
+impl RpcCorsConfig {
+    /// Returns the `Access-Control-Allow-Origin` value of a request from the given origin,
+    /// or `None` if the origin is not allowed.
+    pub fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self
+            .allowed_origins
+            .iter()
+            .any(|allowed_origin| allowed_origin == ANY_ORIGIN)
+        {
+            Some(ANY_ORIGIN.to_string())
+        } else if self
+            .allowed_origins
+            .iter()
+            .any(|allowed_origin| allowed_origin == origin)
+        {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the given HTTP method is allowed in the cross-origin requests.
+    pub fn allows_method(&self, method: &str) -> bool {
+        self.allowed_methods
+            .iter()
+            .any(|allowed_method| allowed_method.eq_ignore_ascii_case(method))
+    }
+
+    /// Returns the origins allowed by the JSON-RPC server.
+    pub fn server_origins(&self) -> DomainsValidation<AccessControlAllowOrigin> {
+        DomainsValidation::AllowOnly(
+            self.allowed_origins
+                .iter()
+                .map(|origin| AccessControlAllowOrigin::from(origin.as_str()))
+                .collect(),
+        )
+    }
+
+    /// Returns the headers allowed by the JSON-RPC server.
+    pub fn server_headers(&self) -> AccessControlAllowHeaders {
+        if self.allowed_headers.is_empty() {
+            AccessControlAllowHeaders::Any
+        } else {
+            AccessControlAllowHeaders::Only(self.allowed_headers.clone())
+        }
+    }
+}
+
+/// Answers the CORS preflight requests, and allows the browser clients to read the responses
+/// of the inner middleware. The JSON-RPC responses are given the CORS headers by the server.
+pub struct CorsEndpoint<M> {
+    cors: RpcCorsConfig,
+    inner: M,
+}
+
+impl<M: RequestMiddleware> CorsEndpoint<M> {
+    /// Creates the CORS middleware of the given config, wrapping the given middleware.
+    pub fn new(cors: RpcCorsConfig, inner: M) -> Self {
+        Self { cors, inner }
+    }
+
+    fn preflight(&self, request: &hyper::Request<Body>, origin: &str) -> RequestMiddlewareAction {
+        let requested_method = request
+            .headers()
+            .get(ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| method.to_str().ok())
+            .unwrap_or("");
+
+        let mut builder = hyper::Response::builder();
+        builder.header(VARY, "Origin");
+
+        let allow_origin = match self.cors.allow_origin(origin) {
+            Some(allow_origin) if self.cors.allows_method(requested_method) => allow_origin,
+            _ => return respond(builder.status(StatusCode::FORBIDDEN).body(Body::empty())),
+        };
+
+        // Any requested header is allowed if the allowed headers are not restricted
+        let allow_headers = if self.cors.allowed_headers.is_empty() {
+            request
+                .headers()
+                .get(ACCESS_CONTROL_REQUEST_HEADERS)
+                .and_then(|headers| headers.to_str().ok())
+                .unwrap_or("")
+                .to_string()
+        } else {
+            self.cors.allowed_headers.join(", ")
+        };
+
+        builder
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin.as_str())
+            .header(
+                ACCESS_CONTROL_ALLOW_METHODS,
+                self.cors.allowed_methods.join(", ").as_str(),
+            )
+            .header(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers.as_str());
+
+        if let Some(max_age) = self.cors.max_age {
+            builder.header(ACCESS_CONTROL_MAX_AGE, max_age.to_string().as_str());
+        }
+
+        respond(builder.status(StatusCode::NO_CONTENT).body(Body::empty()))
+    }
+}
+
+fn respond(response: Result<hyper::Response<Body>, hyper::http::Error>) -> RequestMiddlewareAction {
+    let response = response.unwrap_or_else(|_| {
+        let mut response = hyper::Response::new(Body::empty());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        response
+    });
+
+    RequestMiddlewareAction::Respond {
+        should_validate_hosts: true,
+        response: Box::new(jsonrpc_core::futures::future::ok(response)),
+    }
+}
+
+impl<M: RequestMiddleware> RequestMiddleware for CorsEndpoint<M> {
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        let origin = match request.headers().get(ORIGIN).and_then(|origin| origin.to_str().ok()) {
+            Some(origin) => origin.to_string(),
+            None => return self.inner.on_request(request),
+        };
+
+        if request.method() == Method::OPTIONS && request.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD) {
+            return self.preflight(&request, &origin);
+        }
+
+        let allow_origin = match self
+            .cors
+            .allow_origin(&origin)
+            .and_then(|allow_origin| HeaderValue::from_str(&allow_origin).ok())
+        {
+            Some(allow_origin) => allow_origin,
+            None => return self.inner.on_request(request),
+        };
+
+        match self.inner.on_request(request) {
+            RequestMiddlewareAction::Respond {
+                should_validate_hosts,
+                response,
+            } => RequestMiddlewareAction::Respond {
+                should_validate_hosts,
+                response: Box::new(response.map(move |mut response| {
+                    let headers = response.headers_mut();
+                    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+                    headers.insert(VARY, HeaderValue::from_static("Origin"));
+                    response
+                })),
+            },
+            action => action,
+        }
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for serving the chain data over a GraphQL endpoint, backed by the public RPC endpoints.
+//!
+//! The schema links the blocks to their transactions and the transactions to their records,
+//! so explorers can fetch nested data in a single round trip.
+
+use crate::{rpc_impl::paginate, rpc_rest::RestResponse, rpc_trait::RpcFunctions, rpc_types::*, RpcImpl};
+use snarkos_errors::rpc::RpcError;
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use jsonrpc_core::futures::{Future, Stream};
+use jsonrpc_http_server::{
+    hyper::{
+        self,
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        Body,
+        Method,
+        StatusCode,
+    },
+    RequestMiddleware,
+    RequestMiddlewareAction,
+};
+use juniper::{
+    http::GraphQLRequest,
+    DefaultScalarValue,
+    EmptyMutation,
+    FieldError,
+    FieldResult,
+    Object,
+    RootNode,
+    Value,
+};
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
+use tokio::runtime::Runtime;
+
+/// The path of the GraphQL endpoint of the RPC server.
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// The caching policy of the GraphQL responses, which may describe the current state of the node.
+const GRAPHQL_CACHE_CONTROL: &str = "no-cache";
+
+/// The GraphQL schema of the chain data.
+pub type GraphQLSchema = RootNode<'static, QueryRoot, EmptyMutation<RpcImpl>>;
+
+/// Returns the GraphQL schema of the chain data, which is read-only.
+pub fn graphql_schema() -> GraphQLSchema {
+    RootNode::new(QueryRoot, EmptyMutation::new())
+}
+
+impl juniper::Context for RpcImpl {}
+
+/// Returns the GraphQL error of a failed RPC call, carrying the stable code and kind of the error.
+fn field_error(error: RpcError) -> FieldError {
+    let code = error.code();
+
+    let mut extensions = Object::with_capacity(2);
+    extensions.add_field("code", Value::scalar(code as i32));
+    extensions.add_field("kind", Value::scalar(code.kind().to_string()));
+
+    FieldError::new(error, Value::<DefaultScalarValue>::Object(extensions))
+}
+
+/// Converts a GraphQL integer argument to an unsigned integer.
+fn unsigned<T: TryFrom<i32>>(name: &str, value: i32) -> Result<T, FieldError> {
+    T::try_from(value).map_err(|_| field_error(RpcError::InvalidParams(format!("invalid {}: {}", name, value))))
+}
+
+/// Converts an unsigned integer to a GraphQL integer, saturating at the largest one.
+fn int<T: TryInto<i32>>(value: T) -> i32 {
+    value.try_into().unwrap_or(i32::MAX)
+}
+
+/// The root of the GraphQL queries.
+pub struct QueryRoot;
+
+#[juniper::object(name = "Query", Context = RpcImpl)]
+impl QueryRoot {
+    /// The canon block at the given height, if any
+    fn block(context: &RpcImpl, height: i32) -> FieldResult<Option<BlockNode>> {
+        let height = unsigned("block height", height)?;
+        let mut blocks = context.get_blocks(height, height).map_err(field_error)?;
+
+        Ok(blocks.pop().map(BlockNode))
+    }
+
+    /// The block with the given hash
+    fn block_by_hash(context: &RpcImpl, hash: String) -> FieldResult<BlockNode> {
+        Ok(BlockNode(context.get_block(hash).map_err(field_error)?))
+    }
+
+    /// The canon blocks between the given heights, inclusive
+    fn blocks(context: &RpcImpl, start_height: i32, end_height: i32) -> FieldResult<Vec<BlockNode>> {
+        let start_height = unsigned("start height", start_height)?;
+        let end_height = unsigned("end height", end_height)?;
+
+        let blocks = context.get_blocks(start_height, end_height).map_err(field_error)?;
+
+        Ok(blocks.into_iter().map(BlockNode).collect())
+    }
+
+    /// The head of the canon chain
+    fn latest_block(context: &RpcImpl) -> FieldResult<BlockNode> {
+        let block_hash = context.get_best_block_hash().map_err(field_error)?;
+
+        Ok(BlockNode(context.get_block(block_hash).map_err(field_error)?))
+    }
+
+    /// The stored transaction with the given id
+    fn transaction(context: &RpcImpl, txid: String) -> FieldResult<TransactionNode> {
+        Ok(TransactionNode(
+            context.get_transaction_info(txid, None).map_err(field_error)?,
+        ))
+    }
+
+    /// The record with the given commitment, in the canon chain
+    fn record(context: &RpcImpl, commitment: String) -> FieldResult<RecordNode> {
+        let commitment_info = context.get_commitment_info(commitment).map_err(field_error)?;
+        let transaction = context
+            .get_transaction_info(commitment_info.txid, None)
+            .map_err(field_error)?;
+
+        TransactionNode(transaction)
+            .record_nodes()
+            .into_iter()
+            .find(|record| record.commitment == commitment_info.commitment)
+            .ok_or_else(|| field_error(RpcError::UnknownCommitment(commitment_info.commitment)))
+    }
+
+    /// A page of the transactions in the memory pool, sorted by id
+    fn memory_pool(context: &RpcImpl, limit: Option<i32>, offset: Option<i32>) -> FieldResult<Vec<TransactionNode>> {
+        let limit = limit.map(|limit| unsigned("limit", limit)).transpose()?;
+        let offset = offset.map(|offset| unsigned("offset", offset)).transpose()?;
+
+        context
+            .get_memory_pool_transactions(limit, offset)
+            .map_err(field_error)
+            .map(|transactions| transactions.into_iter().map(TransactionNode).collect())
+    }
+}
+
+/// A block of the chain.
+pub struct BlockNode(pub BlockInfo);
+
+#[juniper::object(name = "Block", Context = RpcImpl)]
+impl BlockNode {
+    /// The hash of the block
+    fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    /// The height of the block, if it is in the canon chain
+    fn height(&self) -> Option<i32> {
+        self.0.height.map(int)
+    }
+
+    /// The number of blocks following the block in the canon chain
+    fn confirmations(&self) -> i32 {
+        int(self.0.confirmations)
+    }
+
+    /// The size of the block in bytes
+    fn size(&self) -> i32 {
+        int(self.0.size)
+    }
+
+    /// The hash of the previous block
+    fn previous_block_hash(&self) -> &str {
+        &self.0.previous_block_hash
+    }
+
+    /// The merkle root of the transactions of the block
+    fn merkle_root(&self) -> &str {
+        &self.0.merkle_root
+    }
+
+    /// The block creation time, in seconds since the Unix epoch
+    fn time(&self) -> f64 {
+        self.0.time as f64
+    }
+
+    /// The difficulty target of the block, as a decimal string
+    fn difficulty_target(&self) -> String {
+        self.0.difficulty_target.to_string()
+    }
+
+    /// The nonce of the block
+    fn nonce(&self) -> f64 {
+        f64::from(self.0.nonce)
+    }
+
+    /// The transactions of the block
+    fn transactions(&self, context: &RpcImpl) -> FieldResult<Vec<TransactionNode>> {
+        self.0
+            .transactions
+            .iter()
+            .map(|txid| {
+                context
+                    .get_transaction_info(txid.clone(), None)
+                    .map(TransactionNode)
+                    .map_err(field_error)
+            })
+            .collect()
+    }
+}
+
+/// A transaction of the chain or of the memory pool.
+pub struct TransactionNode(pub TransactionInfo);
+
+impl TransactionNode {
+    fn record_nodes(&self) -> Vec<RecordNode> {
+        self.0
+            .new_commitments
+            .iter()
+            .zip(self.0.encrypted_records.iter())
+            .map(|(commitment, encrypted_record)| RecordNode {
+                commitment: commitment.clone(),
+                encrypted_record: encrypted_record.clone(),
+                txid: self.0.txid.clone(),
+                block_height: self.0.transaction_metadata.block_number,
+            })
+            .collect()
+    }
+}
+
+#[juniper::object(name = "Transaction", Context = RpcImpl)]
+impl TransactionNode {
+    /// The id of the transaction
+    fn txid(&self) -> &str {
+        &self.0.txid
+    }
+
+    /// The size of the transaction in bytes
+    fn size(&self) -> i32 {
+        int(self.0.size)
+    }
+
+    /// The serial numbers of the records spent by the transaction
+    fn old_serial_numbers(&self) -> &Vec<String> {
+        &self.0.old_serial_numbers
+    }
+
+    /// The commitments of the records created by the transaction
+    fn new_commitments(&self) -> &Vec<String> {
+        &self.0.new_commitments
+    }
+
+    /// The memo of the transaction
+    fn memo(&self) -> &str {
+        &self.0.memo
+    }
+
+    /// The ledger digest the transaction was built against
+    fn digest(&self) -> &str {
+        &self.0.digest
+    }
+
+    /// The value balance of the transaction
+    fn value_balance(&self) -> f64 {
+        self.0.value_balance as f64
+    }
+
+    /// The height of the block holding the transaction, if it is in the canon chain
+    fn block_height(&self) -> Option<i32> {
+        self.0.transaction_metadata.block_number.map(int)
+    }
+
+    /// The block holding the transaction, if it is in the canon chain
+    fn block(&self, context: &RpcImpl) -> FieldResult<Option<BlockNode>> {
+        match self.0.transaction_metadata.block_number {
+            Some(block_number) => {
+                let mut blocks = context.get_blocks(block_number, block_number).map_err(field_error)?;
+                Ok(blocks.pop().map(BlockNode))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The records created by the transaction
+    fn records(&self) -> Vec<RecordNode> {
+        self.record_nodes()
+    }
+}
+
+/// A record created by a transaction.
+pub struct RecordNode {
+    commitment: String,
+    encrypted_record: String,
+    txid: String,
+    block_height: Option<u32>,
+}
+
+#[juniper::object(name = "Record", Context = RpcImpl)]
+impl RecordNode {
+    /// The commitment of the record
+    fn commitment(&self) -> &str {
+        &self.commitment
+    }
+
+    /// The encrypted record
+    fn encrypted_record(&self) -> &str {
+        &self.encrypted_record
+    }
+
+    /// The index of the commitment in the ledger, if the record is in the canon chain
+    fn index(&self, context: &RpcImpl) -> FieldResult<Option<i32>> {
+        if self.block_height.is_none() {
+            return Ok(None);
+        }
+
+        let commitment_info = context
+            .get_commitment_info(self.commitment.clone())
+            .map_err(field_error)?;
+
+        Ok(Some(int(commitment_info.index)))
+    }
+
+    /// The transaction which created the record
+    fn transaction(&self, context: &RpcImpl) -> FieldResult<TransactionNode> {
+        Ok(TransactionNode(
+            context
+                .get_transaction_info(self.txid.clone(), None)
+                .map_err(field_error)?,
+        ))
+    }
+}
+
+impl RpcImpl {
+    /// Returns a page of the transactions in the memory pool, sorted by id.
+    pub fn get_memory_pool_transactions(
+        &self,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<TransactionInfo>, RpcError> {
+        let transactions = {
+            let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+            let mut transactions = BTreeMap::new();
+            for (transaction_id, entry) in memory_pool.transactions.iter() {
+                transactions.insert(transaction_id.clone(), to_bytes![entry.transaction]?);
+            }
+
+            paginate(transactions.into_iter(), limit, offset)?
+        };
+
+        transactions
+            .into_iter()
+            .map(|(_, transaction_bytes)| self.decode_raw_transaction(hex::encode(transaction_bytes), None))
+            .collect()
+    }
+
+    /// Returns the response of the GraphQL endpoint to the given request body.
+    pub fn graphql(&self, schema: &GraphQLSchema, request: &[u8]) -> RestResponse {
+        let (status, body) = match serde_json::from_slice::<GraphQLRequest>(request) {
+            Ok(request) => {
+                let response = request.execute(schema, self);
+                let status = if response.is_ok() {
+                    StatusCode::OK
+                } else {
+                    StatusCode::BAD_REQUEST
+                };
+
+                match serde_json::to_string(&response) {
+                    Ok(body) => (status, body),
+                    Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error_body(error)),
+                }
+            }
+            Err(error) => (StatusCode::BAD_REQUEST, error_body(error)),
+        };
+
+        RestResponse {
+            status,
+            body,
+            etag: None,
+            cache_control: GRAPHQL_CACHE_CONTROL,
+        }
+    }
+}
+
+/// Returns the body of a GraphQL response holding a single error.
+fn error_body<E: ToString>(error: E) -> String {
+    serde_json::json!({ "errors": [{ "message": error.to_string() }] }).to_string()
+}
+
+/// Serves the GraphQL endpoint, and passes the other requests to the RPC server.
+pub struct GraphQLEndpoint {
+    rpc: RpcImpl,
+    schema: Arc<GraphQLSchema>,
+}
+
+impl GraphQLEndpoint {
+    /// Creates the GraphQL endpoint of the node served by the given RPC endpoints.
+    pub fn new(rpc: RpcImpl) -> Self {
+        Self {
+            rpc,
+            schema: Arc::new(graphql_schema()),
+        }
+    }
+}
+
+impl RequestMiddleware for GraphQLEndpoint {
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        if request.uri().path() != GRAPHQL_PATH {
+            return request.into();
+        }
+
+        // The queries are only accepted in the body of POST requests
+        if request.method() != Method::POST {
+            let response = hyper::Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .expect("the response is valid");
+
+            return RequestMiddlewareAction::Respond {
+                should_validate_hosts: true,
+                response: Box::new(jsonrpc_core::futures::future::ok(response)),
+            };
+        }
+
+        let rpc = self.rpc.clone();
+        let schema = self.schema.clone();
+
+        let response = request.into_body().concat2().map(move |body| {
+            let response = rpc.graphql(&schema, &body);
+
+            hyper::Response::builder()
+                .status(response.status)
+                .header(CONTENT_TYPE, "application/json")
+                .header(CACHE_CONTROL, response.cache_control)
+                .body(Body::from(response.body))
+                .expect("the response is valid")
+        });
+
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::new(response),
+        }
+    }
+}
@@ -18,29 +18,69 @@
 //!
 //! See [RpcFunctions](../trait.RpcFunctions.html) for documentation of public endpoints.
 
-use crate::{rpc_trait::RpcFunctions, rpc_types::*};
-use snarkos_consensus::{get_block_reward, ConsensusParameters, MemoryPool, MerkleTreeLedger};
+use crate::{rpc_trait::RpcFunctions, rpc_types::*, JobQueue, TokenRotations};
+use snarkos_consensus::{
+    calculate_fee,
+    get_block_reward,
+    memory_pool::Entry,
+    miner_tag,
+    BlockRequests,
+    ConsensusParameters,
+    MemoryPool,
+    MerkleTreeLedger,
+    Miner,
+    SentTransactions,
+    TransactionAudit,
+};
 use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
 };
-use snarkos_errors::rpc::RpcError;
-use snarkos_models::objects::Transaction;
+use snarkos_errors::{consensus::ConsensusError, objects::PaymentRequestError, rpc::RpcError};
+use snarkos_models::{
+    dpc::Record,
+    objects::{LedgerScheme, Transaction},
+};
 use snarkos_network::{
-    external::SyncHandler,
+    external::{propagate_block, PeerStats, SyncHandler},
     internal::{context::Context, process_transaction_internal},
 };
-use snarkos_objects::BlockHeaderHash;
+use snarkos_objects::{AccountAddress, AccountViewKey, Block, BlockHeaderHash, PaymentRequest};
+use snarkos_parameters::{download_progress, DownloadState};
+use snarkos_storage::{sparse_merkle_leaf_key, StorageMetrics};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
     to_bytes,
+    CanonicalDeserialize,
     CanonicalSerialize,
 };
 
 use chrono::Utc;
-use std::{path::PathBuf, sync::Arc};
+use parking_lot::RwLock;
+use rand::thread_rng;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
 use tokio::{runtime::Runtime, sync::Mutex};
 
+/// The maximum number of blocks returned by `getblocks`.
+pub const MAX_BLOCKS_RANGE: u32 = 100;
+
+/// The maximum number of items in a page of the paginated endpoints.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Replaces the log filter of the node with the given filter directives, in the `RUST_LOG` format.
+pub type LogFilterReloader = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Reloads the configuration of the node, and returns the names of the settings it replaced.
+pub type ConfigReloader = Arc<dyn Fn() -> Result<Vec<String>, String> + Send + Sync>;
+
 /// Implements JSON-RPC HTTP endpoint functions for a node.
 /// The constructor is given Arc::clone() copies of all needed node components.
 #[derive(Clone)]
@@ -51,6 +91,9 @@ pub struct RpcImpl {
     /// The path to the Blockchain database storage.
     pub(crate) storage_path: PathBuf,
 
+    /// Blockchain database storage written by the node, which keeps the storage statistics.
+    pub(crate) primary_storage: Arc<MerkleTreeLedger>,
+
     /// Public Parameters
     pub(crate) parameters: PublicParameters<Components>,
 
@@ -66,87 +109,380 @@ pub struct RpcImpl {
     /// Handle to access the sync state of the node
     pub(crate) sync_handler_lock: Arc<Mutex<SyncHandler>>,
 
-    /// RPC credentials for accessing guarded endpoints
+    /// RPC credentials for accessing guarded endpoints with the admin scope
     pub(crate) credentials: Option<RpcCredentials>,
+
+    /// The hashes of the API tokens for accessing guarded endpoints, with their permission scopes
+    pub(crate) tokens: Arc<RwLock<HashMap<String, RpcScope>>>,
+
+    /// The rotations of the API tokens, applied to the configured tokens
+    pub(crate) token_rotations: Arc<RwLock<TokenRotations>>,
+
+    /// Handle to replace the log filter of the node, if logging is enabled
+    pub(crate) log_filter: Option<LogFilterReloader>,
+
+    /// The asynchronous jobs submitted to the node
+    pub(crate) jobs: Arc<JobQueue>,
+
+    /// The blocks requested from the miner, if the node runs a development network
+    pub(crate) block_requests: Option<Arc<BlockRequests>>,
+
+    /// Handle to reload the configuration of the node, if it can be reloaded
+    pub(crate) config_reloader: Option<ConfigReloader>,
+
+    /// The time the node started
+    pub(crate) start_time: Instant,
 }
 
 impl RpcImpl {
     /// Creates a new struct for calling public and private RPC endpoints.
+    /// Fails if the stored rotations of the API tokens cannot be loaded.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         storage: Arc<MerkleTreeLedger>,
         storage_path: PathBuf,
+        primary_storage: Arc<MerkleTreeLedger>,
         parameters: PublicParameters<Components>,
         server_context: Arc<Context>,
         consensus: ConsensusParameters,
         memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
         sync_handler_lock: Arc<Mutex<SyncHandler>>,
         credentials: Option<RpcCredentials>,
-    ) -> Self {
-        Self {
+        tokens: Vec<RpcToken>,
+        log_filter: Option<LogFilterReloader>,
+    ) -> Result<Self, RpcError> {
+        let token_rotations = TokenRotations::load(&primary_storage)?;
+        let tokens = token_rotations.tokens(tokens);
+        let jobs = JobQueue::load(&primary_storage);
+
+        Ok(Self {
             storage,
             storage_path,
+            primary_storage,
             parameters,
             server_context,
             consensus,
             memory_pool_lock,
             sync_handler_lock,
             credentials,
-        }
+            tokens: Arc::new(RwLock::new(tokens)),
+            token_rotations: Arc::new(RwLock::new(token_rotations)),
+            log_filter,
+            jobs: Arc::new(jobs),
+            block_requests: None,
+            config_reloader: None,
+            start_time: Instant::now(),
+        })
+    }
+
+    /// Requests the blocks of a development network from the miner with the given block requests.
+    pub fn with_block_requests(mut self, block_requests: Arc<BlockRequests>) -> Self {
+        self.block_requests = Some(block_requests);
+        self
+    }
+
+    /// Reloads the configuration of the node with the given reloader.
+    pub fn with_config_reloader(mut self, config_reloader: ConfigReloader) -> Self {
+        self.config_reloader = Some(config_reloader);
+        self
     }
 
     /// Open a new secondary storage instance.
     pub fn new_secondary_storage_instance(&self) -> Result<MerkleTreeLedger, RpcError> {
         Ok(MerkleTreeLedger::open_secondary_at_path(self.storage_path.clone())?)
     }
+
+    /// Returns the health of the storage, which is healthy if the latest state of the ledger can be read.
+    pub fn storage_health(&self) -> StorageHealth {
+        let result = self
+            .storage
+            .catch_up_secondary(false)
+            .and_then(|_| self.storage.get_block_hash(self.storage.get_latest_block_height()));
+
+        StorageHealth {
+            is_healthy: result.is_ok(),
+            error: result.err().map(|error| error.to_string()),
+        }
+    }
+
+    /// Returns true if the node is syncing blocks.
+    /// A busy sync handler is not waited for, and the node is then deemed synced.
+    pub fn is_syncing(&self) -> bool {
+        match self.sync_handler_lock.try_lock() {
+            Ok(sync_handler) => sync_handler.is_syncing(),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the verification diagnostics of a transaction, checked with the given mode.
+    /// All the checks are run, so that each reason the transaction is invalid for is reported.
+    pub fn validate_transaction(
+        &self,
+        transaction: &Tx,
+        mode: ValidationMode,
+    ) -> Result<TransactionValidation, RpcError> {
+        let mut dry_run = match mode {
+            ValidationMode::Stateless => self.consensus.dry_run_transaction_stateless(
+                &self.parameters,
+                transaction,
+                self.storage.parameters(),
+            )?,
+            ValidationMode::Stateful => {
+                self.storage.catch_up_secondary(false)?;
+                self.consensus
+                    .dry_run_transaction(&self.parameters, transaction, &self.storage)?
+            }
+        };
+
+        if mode == ValidationMode::Stateful {
+            let transaction_id = dry_run.transaction_id;
+            let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+            // The memory pool evicts the conflicting transactions with a lower fee rate
+            let entry = Entry {
+                size: to_bytes![transaction]?.len(),
+                transaction: transaction.clone(),
+            };
+            let mut conflicting_transaction_ids: Vec<_> = memory_pool
+                .get_conflicts(transaction)?
+                .iter()
+                .filter(|transaction_id| {
+                    memory_pool
+                        .transactions
+                        .get(*transaction_id)
+                        .map_or(false, |conflicting_entry| {
+                            entry.cmp_fee_rate(conflicting_entry) != Ordering::Greater
+                        })
+                })
+                .map(hex::encode)
+                .collect();
+            conflicting_transaction_ids.sort();
+
+            let reason = if memory_pool.transactions.contains_key(&transaction_id.to_vec()) {
+                "the transaction is already in the memory pool".to_string()
+            } else {
+                format!(
+                    "the transaction conflicts with the memory pool transactions {}",
+                    conflicting_transaction_ids.join(", ")
+                )
+            };
+            dry_run.check("memory_pool", conflicting_transaction_ids.is_empty(), || reason);
+        }
+
+        Ok(TransactionValidation {
+            txid: hex::encode(dry_run.transaction_id),
+            mode,
+            is_valid: dry_run.is_valid(),
+            checks: dry_run
+                .checks
+                .into_iter()
+                .map(|check| TransactionCheck {
+                    name: check.name.to_string(),
+                    passed: check.passed,
+                    reason: check.reason,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Returns information about a block from a block hash.
+fn block_info(storage: &MerkleTreeLedger, block_header_hash: &BlockHeaderHash) -> Result<BlockInfo, RpcError> {
+    let height = match storage.get_block_number(block_header_hash) {
+        Ok(block_num) => match storage.is_canon(block_header_hash) {
+            true => Some(block_num),
+            false => None,
+        },
+        Err(_) => None,
+    };
+
+    let confirmations = match height {
+        Some(block_height) => storage.get_latest_block_height() - block_height,
+        None => 0,
+    };
+
+    if let Ok(block) = storage.get_block(block_header_hash) {
+        let mut transactions = Vec::with_capacity(block.transactions.len());
+
+        for transaction in block.transactions.iter() {
+            transactions.push(hex::encode(&transaction.transaction_id()?));
+        }
+
+        Ok(BlockInfo {
+            hash: hex::encode(&block_header_hash.0),
+            height,
+            confirmations,
+            size: block.serialize()?.len(),
+            previous_block_hash: block.header.previous_block_hash.to_string(),
+            merkle_root: block.header.merkle_root_hash.to_string(),
+            pedersen_merkle_root_hash: block.header.pedersen_merkle_root_hash.to_string(),
+            proof: block.header.proof.to_string(),
+            time: block.header.time,
+            difficulty_target: block.header.difficulty_target,
+            nonce: block.header.nonce,
+            transactions,
+        })
+    } else {
+        Err(RpcError::InvalidBlockHash(hex::encode(&block_header_hash.0)))
+    }
+}
+
+/// Returns the height of the canon block created with the given ledger digest,
+/// or `None` if it is not the ledger digest of a canon block.
+fn canon_digest_height(storage: &MerkleTreeLedger, ledger_digest: &[u8]) -> Result<Option<u32>, RpcError> {
+    match storage.get_digest_block_number(ledger_digest)? {
+        Some(block_number) if block_number <= storage.get_latest_block_height() => Ok(storage
+            .get_ledger_checkpoint(block_number)
+            .ok()
+            .filter(|checkpoint| checkpoint.digest == ledger_digest)
+            .map(|_| block_number)),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the structured breakdown of a transaction, given its encoded serial numbers and commitments.
+fn transaction_details(
+    transaction: &Tx,
+    old_serial_numbers: &[String],
+    new_commitments: &[String],
+) -> Result<TransactionDetails, RpcError> {
+    let mut inputs = Vec::with_capacity(old_serial_numbers.len());
+    for (serial_number, signature) in old_serial_numbers.iter().zip(&transaction.signatures) {
+        inputs.push(TransactionInputInfo {
+            serial_number: serial_number.clone(),
+            signature: SignatureInfo {
+                prover_response: hex::encode(to_bytes![signature.prover_response]?),
+                verifier_challenge: hex::encode(to_bytes![signature.verifier_challenge]?),
+            },
+        });
+    }
+
+    let mut outputs = Vec::with_capacity(new_commitments.len());
+    for (commitment, encrypted_record) in new_commitments.iter().zip(&transaction.encrypted_records) {
+        let mut ciphertext = Vec::with_capacity(encrypted_record.encrypted_record.len());
+        for element in &encrypted_record.encrypted_record {
+            ciphertext.push(hex::encode(to_bytes![element]?));
+        }
+
+        outputs.push(TransactionOutputInfo {
+            commitment: commitment.clone(),
+            encrypted_record: EncryptedRecordInfo {
+                ciphertext,
+                final_fq_high_selector: encrypted_record.final_fq_high_selector,
+            },
+        });
+    }
+
+    // The memo is shown as text if it is padded UTF-8 text
+    let memo = transaction.memorandum();
+    let text_length = memo.iter().rposition(|byte| *byte != 0).map_or(0, |index| index + 1);
+    let text = match std::str::from_utf8(&memo[..text_length]) {
+        Ok(text) if !text.is_empty() => Some(text.to_string()),
+        _ => None,
+    };
+
+    let value_balance = transaction.value_balance.0;
+
+    // Only the coinbase transactions, which mint value, carry a miner tag
+    let miner_tag = if value_balance < 0 { miner_tag(memo) } else { None };
+
+    Ok(TransactionDetails {
+        inputs,
+        outputs,
+        memo: MemoInfo {
+            bytes: hex::encode(memo),
+            text,
+            miner_tag,
+        },
+        value_balance: ValueBalanceInfo {
+            value_balance,
+            fee: value_balance.max(0),
+            minted: (-value_balance).max(0),
+        },
+        program_commitment: hex::encode(to_bytes![transaction.program_commitment]?),
+    })
+}
+
+/// Returns the connection statistics of a connected peer, which are unknown if it has no channel yet.
+fn peer_details(address: SocketAddr, last_seen: i64, stats: Option<&PeerStats>) -> PeerDetails {
+    let state = stats.map(PeerStats::state).unwrap_or_default();
+
+    PeerDetails {
+        address,
+        peer_id: state.peer_id.map(|peer_id| peer_id.to_string()),
+        version: state.version,
+        height: state.height,
+        last_block_hash: state.last_block_hash.map(hex::encode),
+        last_block_time: state.last_block_time,
+        last_seen,
+        rtt: state.rtt.map(|rtt| rtt.as_millis() as u64),
+        bytes_sent: stats.map_or(0, PeerStats::bytes_sent),
+        bytes_received: stats.map_or(0, PeerStats::bytes_received),
+        misbehavior_score: state.misbehavior_score,
+    }
+}
+
+/// Returns the page of items at the given offset, holding at most `limit` items.
+/// The limit defaults to `MAX_PAGE_SIZE`, and can not exceed it.
+pub(crate) fn paginate<I: Iterator>(
+    items: I,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<I::Item>, RpcError> {
+    let limit = limit.unwrap_or(MAX_PAGE_SIZE);
+    if limit > MAX_PAGE_SIZE {
+        return Err(RpcError::InvalidRange(format!(
+            "at most {} items can be requested",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    Ok(items.skip(offset.unwrap_or(0)).take(limit).collect())
 }
 
 impl RpcFunctions for RpcImpl {
     /// Returns information about a block from a block hash.
     fn get_block(&self, block_hash_string: String) -> Result<BlockInfo, RpcError> {
         let block_hash = hex::decode(&block_hash_string)?;
-        assert_eq!(block_hash.len(), 32);
+
+        if block_hash.len() != 32 {
+            return Err(RpcError::InvalidBlockHash(block_hash_string));
+        }
 
         self.storage.catch_up_secondary(false)?;
+        let storage = self.storage.view();
 
-        let block_header_hash = BlockHeaderHash::new(block_hash);
-        let height = match self.storage.get_block_number(&block_header_hash) {
-            Ok(block_num) => match self.storage.is_canon(&block_header_hash) {
-                true => Some(block_num),
-                false => None,
-            },
-            Err(_) => None,
-        };
+        block_info(&storage, &BlockHeaderHash::new(block_hash))
+    }
 
-        let confirmations = match height {
-            Some(block_height) => self.storage.get_latest_block_height() - block_height,
-            None => 0,
-        };
+    /// Returns information about the canonical blocks in a range of block heights.
+    fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<BlockInfo>, RpcError> {
+        if end_block_height < start_block_height {
+            return Err(RpcError::InvalidRange(format!(
+                "the end height {} is below the start height {}",
+                end_block_height, start_block_height
+            )));
+        }
 
-        if let Ok(block) = self.storage.get_block(&block_header_hash) {
-            let mut transactions = Vec::with_capacity(block.transactions.len());
+        if end_block_height - start_block_height >= MAX_BLOCKS_RANGE {
+            return Err(RpcError::InvalidRange(format!(
+                "at most {} blocks can be requested",
+                MAX_BLOCKS_RANGE
+            )));
+        }
 
-            for transaction in block.transactions.iter() {
-                transactions.push(hex::encode(&transaction.transaction_id()?));
-            }
+        self.storage.catch_up_secondary(false)?;
+        let storage = self.storage.view();
 
-            Ok(BlockInfo {
-                hash: block_hash_string,
-                height,
-                confirmations,
-                size: block.serialize()?.len(),
-                previous_block_hash: block.header.previous_block_hash.to_string(),
-                merkle_root: block.header.merkle_root_hash.to_string(),
-                pedersen_merkle_root_hash: block.header.pedersen_merkle_root_hash.to_string(),
-                proof: block.header.proof.to_string(),
-                time: block.header.time,
-                difficulty_target: block.header.difficulty_target,
-                nonce: block.header.nonce,
-                transactions,
-            })
-        } else {
-            Err(RpcError::InvalidBlockHash(block_hash_string))
+        // The range is cut at the latest block
+        let end_block_height = end_block_height.min(storage.get_latest_block_height());
+
+        let mut blocks = vec![];
+        for block_height in start_block_height..=end_block_height {
+            blocks.push(block_info(&storage, &storage.get_block_hash(block_height)?)?);
         }
+
+        Ok(blocks)
     }
 
     /// Returns the number of blocks in the canonical chain.
@@ -180,13 +516,18 @@ impl RpcFunctions for RpcImpl {
     }
 
     /// Returns information about a transaction from a transaction id.
-    fn get_transaction_info(&self, transaction_id: String) -> Result<TransactionInfo, RpcError> {
+    fn get_transaction_info(&self, transaction_id: String, verbose: Option<bool>) -> Result<TransactionInfo, RpcError> {
         let transaction_bytes = self.get_raw_transaction(transaction_id)?;
-        self.decode_raw_transaction(transaction_bytes)
+        self.decode_raw_transaction(transaction_bytes, verbose)
     }
 
     /// Returns information about a transaction from serialized transaction bytes.
-    fn decode_raw_transaction(&self, transaction_bytes: String) -> Result<TransactionInfo, RpcError> {
+    /// Verbose requests also return a structured breakdown of the transaction.
+    fn decode_raw_transaction(
+        &self,
+        transaction_bytes: String,
+        verbose: Option<bool>,
+    ) -> Result<TransactionInfo, RpcError> {
         self.storage.catch_up_secondary(false)?;
         let transaction_bytes = hex::decode(transaction_bytes)?;
         let transaction = Tx::read(&transaction_bytes[..])?;
@@ -229,6 +570,15 @@ impl RpcFunctions for RpcImpl {
 
         let transaction_metadata = TransactionMetadata { block_number };
 
+        let details = match verbose {
+            Some(true) => Some(transaction_details(
+                &transaction,
+                &old_serial_numbers,
+                &new_commitments,
+            )?),
+            _ => None,
+        };
+
         Ok(TransactionInfo {
             txid: hex::encode(&transaction_id),
             size: transaction_bytes.len(),
@@ -244,12 +594,13 @@ impl RpcFunctions for RpcImpl {
             signatures,
             encrypted_records,
             transaction_metadata,
+            details,
         })
     }
 
     /// Send raw transaction bytes to this node to be added into the mempool.
     /// If valid, the transaction will be stored and propagated to all peers.
-    /// Returns the transaction id if valid.
+    /// Returns the transaction id if the transaction is in the mempool.
     fn send_raw_transaction(&self, transaction_bytes: String) -> Result<String, RpcError> {
         let transaction_bytes = hex::decode(transaction_bytes)?;
         let transaction = Tx::read(&transaction_bytes[..])?;
@@ -259,37 +610,109 @@ impl RpcFunctions for RpcImpl {
             .consensus
             .verify_transaction(&self.parameters, &transaction, &self.storage)?
         {
-            // TODO (raychu86) Add more descriptive message. (e.g. tx already exists)
-            return Ok("Transaction did not verify".into());
+            return Err(RpcError::InvalidTransaction("the transaction did not verify".into()));
         }
 
-        match !self.storage.transcation_conflicts(&transaction) {
-            true => {
-                Runtime::new()?.block_on(process_transaction_internal(
-                    self.server_context.clone(),
-                    &self.consensus,
-                    &self.parameters,
-                    self.storage.clone(),
-                    self.memory_pool_lock.clone(),
-                    to_bytes![transaction]?.to_vec(),
-                    *Runtime::new()?.block_on(self.server_context.local_address.read()),
-                ))?;
+        if transaction.value_balance.is_negative() {
+            return Err(RpcError::TransactionRejected(
+                "coinbase transactions are not relayed".into(),
+            ));
+        }
 
-                Ok(hex::encode(transaction.transaction_id()?))
-            }
-            false => Ok("Transaction contains spent records".into()),
+        if self.storage.transcation_conflicts(&transaction) {
+            return Err(RpcError::TransactionRejected(
+                "the transaction contains spent records".into(),
+            ));
+        }
+
+        Runtime::new()?.block_on(process_transaction_internal(
+            self.server_context.clone(),
+            &self.consensus,
+            &self.parameters,
+            self.storage.clone(),
+            self.memory_pool_lock.clone(),
+            to_bytes![transaction]?.to_vec(),
+            *Runtime::new()?.block_on(self.server_context.local_address.read()),
+        ))?;
+
+        // The memory pool does not explain its rejections, which are conflicts with its transactions
+        let transaction_id = transaction.transaction_id()?;
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        if !memory_pool.transactions.contains_key(&transaction_id.to_vec()) {
+            return Err(RpcError::TransactionRejected(
+                "the transaction conflicts with the memory pool".into(),
+            ));
         }
+
+        // The transaction is broadcast again by the node until it is confirmed
+        SentTransactions::new(&self.primary_storage).track(&transaction, Utc::now().timestamp())?;
+
+        Ok(hex::encode(transaction_id))
     }
 
     /// Validate and return if the transaction is valid.
-    fn validate_raw_transaction(&self, transaction_bytes: String) -> Result<bool, RpcError> {
+    fn validate_raw_transaction(
+        &self,
+        transaction_bytes: String,
+        mode: Option<ValidationMode>,
+    ) -> Result<TransactionValidity, RpcError> {
         let transaction_bytes = hex::decode(transaction_bytes)?;
         let transaction = Tx::read(&transaction_bytes[..])?;
+
+        if let Some(mode) = mode {
+            return Ok(TransactionValidity::Diagnostics(
+                self.validate_transaction(&transaction, mode)?,
+            ));
+        }
+
         self.storage.catch_up_secondary(false)?;
 
-        Ok(self
-            .consensus
-            .verify_transaction(&self.parameters, &transaction, &self.storage)?)
+        Ok(TransactionValidity::IsValid(self.consensus.verify_transaction(
+            &self.parameters,
+            &transaction,
+            &self.storage,
+        )?))
+    }
+
+    /// Audit the value balance of a transaction against its signatures and proof, and open the
+    /// output records encrypted to the given view keys.
+    fn audit_transaction(
+        &self,
+        transaction_bytes: String,
+        view_keys: Option<Vec<String>>,
+    ) -> Result<TransactionAuditInfo, RpcError> {
+        let transaction_bytes = hex::decode(transaction_bytes)?;
+        let transaction = Tx::read(&transaction_bytes[..])?;
+
+        let view_keys = view_keys
+            .unwrap_or_default()
+            .iter()
+            .map(|view_key| AccountViewKey::<Components>::from_str(view_key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let audit = TransactionAudit::new(&self.parameters, self.storage.parameters(), &transaction, &view_keys)?;
+
+        let mut disclosed_records = Vec::with_capacity(audit.disclosed_records.len());
+        for record in &audit.disclosed_records {
+            disclosed_records.push(DisclosedRecordInfo {
+                commitment: hex::encode(to_bytes![record.commitment()]?),
+                owner: record.owner().to_string(),
+                value: record.value(),
+            });
+        }
+
+        Ok(TransactionAuditInfo {
+            txid: hex::encode(audit.transaction_id),
+            value_balance: audit.value_balance.0,
+            fee: audit.fee(),
+            is_coinbase: audit.is_coinbase(),
+            signatures: audit.valid_signatures,
+            proof: audit.valid_proof,
+            is_valid: audit.is_valid(),
+            disclosed_records,
+            disclosed_value: audit.disclosed_value(),
+        })
     }
 
     /// Fetch the number of connected peers this node has.
@@ -300,41 +723,317 @@ impl RpcFunctions for RpcImpl {
         Ok(peer_book.connected_total() as usize)
     }
 
-    /// Returns this nodes connected peers.
-    fn get_peer_info(&self) -> Result<PeerInfo, RpcError> {
+    /// Returns a page of this nodes connected peers, and their connection statistics if verbose.
+    fn get_peer_info(
+        &self,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        verbose: Option<bool>,
+    ) -> Result<PeerInfo, RpcError> {
         // Create a temporary tokio runtime to make an asynchronous function call
         let peer_book = Runtime::new()?.block_on(self.server_context.peer_book.read());
 
-        let peers = peer_book.get_connected().keys().cloned().collect();
+        // The peers are sorted, so the pages are consistent
+        let connected_peers: BTreeMap<_, _> = peer_book.get_connected().into_iter().collect();
+        let total = connected_peers.len();
+        let page = paginate(connected_peers.into_iter(), limit, offset)?;
+
+        let details = if verbose == Some(true) {
+            let connections = Runtime::new()?.block_on(self.server_context.connections.read());
 
-        Ok(PeerInfo { peers })
+            Some(
+                page.iter()
+                    .map(|(address, last_seen)| {
+                        let stats = connections.get(address).map(|channel| channel.stats.clone());
+                        peer_details(*address, last_seen.timestamp(), stats.as_deref())
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        Ok(PeerInfo {
+            peers: page.into_iter().map(|(address, _)| address).collect(),
+            total,
+            details,
+        })
     }
 
-    /// Returns data about the node.
-    fn get_node_info(&self) -> Result<NodeInfo, RpcError> {
-        let mut is_syncing = false;
+    /// Returns the connectivity of this node, aggregated over its connected peers.
+    fn get_network_info(&self) -> Result<NetworkInfo, RpcError> {
+        let runtime = Runtime::new()?;
+        let peer_book = runtime.block_on(self.server_context.peer_book.read());
+        let connections = runtime.block_on(self.server_context.connections.read());
+
+        let connected_peers = peer_book.get_connected();
+
+        let mut versions = BTreeMap::new();
+        let mut rtts = vec![];
+        let mut bytes_sent = 0;
+        let mut bytes_received = 0;
+        let mut misbehaving_peers = 0;
+
+        for address in connected_peers.keys() {
+            let stats = match connections.get(address) {
+                Some(channel) => channel.stats.clone(),
+                None => continue,
+            };
+            let state = stats.state();
 
-        if let Ok(sync_handler) = self.sync_handler_lock.try_lock() {
-            is_syncing = sync_handler.is_syncing();
+            if let Some(version) = state.version {
+                *versions.entry(version).or_insert(0) += 1;
+            }
+            if let Some(rtt) = state.rtt {
+                rtts.push(rtt.as_secs_f64() * 1000.0);
+            }
+            if state.misbehavior_score > 0 {
+                misbehaving_peers += 1;
+            }
+
+            bytes_sent += stats.bytes_sent();
+            bytes_received += stats.bytes_received();
         }
 
+        let average_rtt = if rtts.is_empty() {
+            None
+        } else {
+            Some(rtts.iter().sum::<f64>() / rtts.len() as f64)
+        };
+
+        Ok(NetworkInfo {
+            local_address: *runtime.block_on(self.server_context.local_address.read()),
+            connected_peers: connected_peers.len(),
+            gossiped_peers: peer_book.get_gossiped().len(),
+            min_peers: self.server_context.min_peers(),
+            max_peers: self.server_context.max_peers(),
+            versions,
+            average_rtt,
+            bytes_sent,
+            bytes_received,
+            misbehaving_peers,
+        })
+    }
+
+    /// Returns a page of the ids of the transactions in the memory pool.
+    fn get_memory_pool(&self, limit: Option<usize>, offset: Option<usize>) -> Result<MemoryPoolInfo, RpcError> {
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        // The transaction ids are sorted, so the pages are consistent
+        let transaction_ids: BTreeSet<_> = memory_pool.transactions.keys().map(hex::encode).collect();
+
+        Ok(MemoryPoolInfo {
+            total: transaction_ids.len(),
+            transactions: paginate(transaction_ids.into_iter(), limit, offset)?,
+        })
+    }
+
+    /// Returns the recommended fee rate for a transaction to be included within the target number of blocks.
+    fn estimate_fee(&self, target_blocks: u32) -> Result<FeeEstimateInfo, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        let fee_estimate = self
+            .consensus
+            .estimate_fee(&self.storage, &memory_pool, target_blocks)?;
+
+        Ok(FeeEstimateInfo {
+            target_blocks: fee_estimate.target_blocks,
+            fee_rate: fee_estimate.fee_rate,
+            confidence: fee_estimate.confidence,
+            memory_pool_fee_rate: fee_estimate.memory_pool_fee_rate,
+            sampled_blocks: fee_estimate.sampled_blocks,
+        })
+    }
+
+    /// Returns the fee of a transaction paying the given fee rate.
+    fn calculate_fee(&self, fee_rate: f64) -> Result<FeeCalculationInfo, RpcError> {
+        let fee_calculation = calculate_fee(fee_rate)?;
+
+        Ok(FeeCalculationInfo {
+            transaction_size: fee_calculation.transaction_size,
+            fee_rate: fee_calculation.fee_rate,
+            fee: fee_calculation.fee,
+        })
+    }
+
+    /// Returns the URI of a request for a payment to the given address, on the network of the node.
+    fn create_payment_request(&self, request_input: PaymentRequestInputs) -> Result<PaymentRequestInfo, RpcError> {
+        let address = AccountAddress::<Components>::from_network_str(&request_input.address, self.consensus.network)?;
+
+        if let Some(expires) = request_input.expires {
+            if expires <= Utc::now().timestamp() {
+                return Err(PaymentRequestError::InvalidExpiry(format!("the request expired at {}", expires)).into());
+            }
+        }
+
+        let mut request = PaymentRequest::new(address, self.consensus.network);
+        request.amount = request_input.amount;
+        request.memo = request_input.memo;
+        request.expires = request_input.expires;
+
+        Ok(PaymentRequestInfo {
+            uri: request.to_uri()?,
+            address: request.address.to_network_string(request.network),
+        })
+    }
+
+    /// Returns the size and fee rates of the memory pool.
+    fn get_memory_pool_info(&self) -> Result<MemoryPoolSummary, RpcError> {
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        let mut total_fee = 0;
+        let mut min_fee_rate: Option<f64> = None;
+        let mut max_fee_rate: Option<f64> = None;
+
+        for entry in memory_pool.transactions.values() {
+            let fee: i64 = entry.transaction.value_balance().into();
+            let fee_rate = fee as f64 / entry.size as f64;
+
+            total_fee += fee;
+            min_fee_rate = Some(min_fee_rate.map_or(fee_rate, |min_fee_rate| min_fee_rate.min(fee_rate)));
+            max_fee_rate = Some(max_fee_rate.map_or(fee_rate, |max_fee_rate| max_fee_rate.max(fee_rate)));
+        }
+
+        Ok(MemoryPoolSummary {
+            size: memory_pool.transactions.len(),
+            bytes: memory_pool.total_size,
+            total_fee,
+            min_fee_rate,
+            max_fee_rate,
+            oldest_time: memory_pool.insertion_times.values().min().copied(),
+        })
+    }
+
+    /// Returns the ids of the transactions in the memory pool, or their fees, sizes and ages if verbose.
+    fn get_raw_memory_pool(&self, verbose: Option<bool>) -> Result<RawMemoryPool, RpcError> {
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        if verbose != Some(true) {
+            let mut transaction_ids: Vec<_> = memory_pool.transactions.keys().map(hex::encode).collect();
+            transaction_ids.sort();
+
+            return Ok(RawMemoryPool::TransactionIds(transaction_ids));
+        }
+
+        let now = Utc::now().timestamp();
+
+        let mut entries = BTreeMap::new();
+        for (transaction_id, entry) in &memory_pool.transactions {
+            let fee: i64 = entry.transaction.value_balance().into();
+            let time = memory_pool.insertion_times.get(transaction_id).copied().unwrap_or(now);
+
+            entries.insert(hex::encode(transaction_id), MemoryPoolEntryInfo {
+                size: entry.size,
+                fee,
+                fee_rate: fee as f64 / entry.size as f64,
+                time,
+                age: now.saturating_sub(time),
+            });
+        }
+
+        Ok(RawMemoryPool::Entries(entries))
+    }
+
+    /// Returns data about the node.
+    fn get_node_info(&self) -> Result<NodeInfo, RpcError> {
         Ok(NodeInfo {
+            peer_id: self.server_context.identity.peer_id().to_string(),
+            is_miner: self.server_context.is_miner,
+            is_syncing: self.is_syncing(),
+        })
+    }
+
+    /// Returns the hashrate, attempts, mined blocks, stale shares and template latency of the miner of the node.
+    fn get_mining_info(&self) -> Result<MiningInfo, RpcError> {
+        let stats = &self.server_context.mining_stats;
+
+        Ok(MiningInfo {
             is_miner: self.server_context.is_miner,
+            hashrate: stats.hashrate(),
+            attempts: stats.attempts(),
+            accepted_blocks: stats.accepted_blocks(),
+            stale_blocks: stats.stale_blocks(),
+            stale_shares: stats.stale_shares(),
+            templates: stats.templates(),
+            average_template_latency: stats.average_template_latency().as_secs_f64(),
+        })
+    }
+
+    /// Returns the sync state, peers, memory pool, uptime and storage health of the node.
+    fn get_node_state(&self) -> Result<NodeState, RpcError> {
+        let storage = self.storage_health();
+        let block_height = self.storage.get_latest_block_height();
+
+        let (is_syncing, queued_block_headers, pending_blocks) = {
+            let sync_handler = Runtime::new()?.block_on(self.sync_handler_lock.lock());
+            (
+                sync_handler.is_syncing(),
+                sync_handler.block_headers.len(),
+                sync_handler.pending_blocks.len(),
+            )
+        };
+
+        let (connected_peers, gossiped_peers) = {
+            let peer_book = Runtime::new()?.block_on(self.server_context.peer_book.read());
+            (peer_book.get_connected().len(), peer_book.get_gossiped().len())
+        };
+
+        let memory_pool_size = Runtime::new()?
+            .block_on(self.memory_pool_lock.lock())
+            .transactions
+            .len();
+
+        Ok(NodeState {
+            block_height,
+            best_known_block_height: block_height + (queued_block_headers + pending_blocks) as u32,
             is_syncing,
+            queued_block_headers,
+            pending_blocks,
+            connected_peers,
+            gossiped_peers,
+            memory_pool_size,
+            uptime: self.start_time.elapsed().as_secs(),
+            storage,
         })
     }
 
     /// Returns the current mempool and consensus information known by this node.
-    fn get_block_template(&self) -> Result<BlockTemplate, RpcError> {
+    fn get_block_template(&self, coinbase_address: Option<String>) -> Result<BlockTemplate, RpcError> {
+        // A template built on a stale chain would only produce orphan blocks
+        if self.is_syncing() {
+            return Err(RpcError::NodeSyncing);
+        }
+
+        let coinbase_address = match coinbase_address {
+            Some(address) => Some(AccountAddress::<Components>::from_network_str(
+                &address,
+                self.consensus.network,
+            )?),
+            None => None,
+        };
+
         self.storage.catch_up_secondary(false)?;
 
-        let block_height = self.storage.get_latest_block_height();
-        let block = self.storage.get_block_from_block_number(block_height)?;
+        let (block_height, block, time, full_transactions) = {
+            // The memory pool is locked before the ledger view, like when blocks are connected
+            let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+            let storage = self.storage.view();
 
-        let time = Utc::now().timestamp();
+            let block_height = storage.get_latest_block_height();
+            let block = storage.get_block_from_block_number(block_height)?;
 
-        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
-        let full_transactions = memory_pool.get_candidates(&self.storage, self.consensus.max_block_size)?;
+            let time = Utc::now().timestamp();
+
+            let full_transactions = memory_pool.get_candidates(
+                &storage,
+                self.consensus.template_size(),
+                self.consensus.template_weight(),
+            )?;
+
+            (block_height, block, time, full_transactions)
+        };
 
         let transaction_strings = full_transactions.serialize_as_str()?;
 
@@ -343,6 +1042,24 @@ impl RpcFunctions for RpcImpl {
             coinbase_value = coinbase_value.add(transaction.value_balance())
         }
 
+        // The coinbase transaction is built once the view is dropped, as its proof takes a while
+        let coinbase_transaction = match coinbase_address {
+            Some(address) => {
+                let miner = Miner::verify_only(address, self.consensus.clone())?;
+                let mut transactions = full_transactions;
+                miner.add_coinbase_transaction(
+                    &self.parameters,
+                    &self.storage,
+                    &mut transactions,
+                    &mut thread_rng(),
+                )?;
+
+                let coinbase_transaction = transactions.0.pop().expect("the coinbase transaction was added");
+                Some(hex::encode(to_bytes![coinbase_transaction]?))
+            }
+            None => None,
+        };
+
         Ok(BlockTemplate {
             previous_block_hash: hex::encode(&block.header.get_hash().0),
             block_height: block_height + 1,
@@ -350,6 +1067,212 @@ impl RpcFunctions for RpcImpl {
             difficulty_target: self.consensus.get_block_difficulty(&block.header, time),
             transactions: transaction_strings,
             coinbase_value: coinbase_value.0 as u64,
+            block_reward: get_block_reward(block_height + 1).0 as u64,
+            network_id: self.consensus.network.id(),
+            max_block_size: self.consensus.max_block_size,
+            coinbase_transaction,
         })
     }
+
+    /// Submit a block found by an external miner, to be added to the chain and propagated to all peers.
+    /// Returns the reason the block is rejected, if it is invalid.
+    fn submit_block(&self, block_bytes: String) -> Result<SubmitBlockResult, RpcError> {
+        let block_bytes = hex::decode(block_bytes)?;
+        let block = Block::<Tx>::deserialize(&block_bytes)?;
+        let block_hash = block.header.get_hash();
+
+        let rejected = |reason: String| SubmitBlockResult {
+            block_hash: block_hash.to_string(),
+            accepted: false,
+            reject_reason: Some(reason),
+        };
+
+        if block_bytes.len() > self.consensus.max_block_size {
+            let error = ConsensusError::BlockTooLarge(block_bytes.len(), self.consensus.max_block_size);
+            return Ok(rejected(error.to_string()));
+        }
+
+        // The block is connected by the node, so it is checked against the primary storage
+        let storage = &self.primary_storage;
+
+        if storage.block_hash_exists(&block_hash) {
+            return Ok(rejected("duplicate block".to_string()));
+        }
+
+        {
+            let mut memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+            match self.consensus.check_block(&self.parameters, &block, storage) {
+                Ok(()) => {}
+                Err(ConsensusError::StorageError(error)) => return Err(error.into()),
+                Err(error) => return Ok(rejected(error.to_string())),
+            }
+
+            if let Err(error) = self
+                .consensus
+                .receive_block(&self.parameters, storage, &mut memory_pool, &block)
+            {
+                return Ok(rejected(error.to_string()));
+            }
+        }
+
+        let local_address = *Runtime::new()?.block_on(self.server_context.local_address.read());
+        Runtime::new()?.block_on(propagate_block(self.server_context.clone(), block_bytes, local_address))?;
+
+        Ok(SubmitBlockResult {
+            block_hash: block_hash.to_string(),
+            accepted: true,
+            reject_reason: None,
+        })
+    }
+
+    /// Returns a proof that a serial number is or is not spent, against the root of the serial number tree.
+    fn get_serial_number_proof(&self, serial_number: String) -> Result<SerialNumberProofInfo, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+
+        let serial_number_bytes = hex::decode(&serial_number)?;
+        let sn: <Tx as Transaction>::SerialNumber = CanonicalDeserialize::deserialize(&mut &serial_number_bytes[..])
+            .map_err(|_| RpcError::InvalidSerialNumber(serial_number))?;
+
+        let serial_number_proof = self.storage.prove_sn(&sn)?;
+
+        Ok(SerialNumberProofInfo {
+            block_height: serial_number_proof.block_height,
+            block_hash: hex::encode(&serial_number_proof.block_hash.0),
+            root: hex::encode(serial_number_proof.root),
+            is_spent: serial_number_proof.is_spent,
+            leaf_key: hex::encode(sparse_merkle_leaf_key(&to_bytes![sn]?)),
+            siblings: serial_number_proof
+                .proof
+                .siblings
+                .iter()
+                .map(|(depth, hash)| (*depth, hex::encode(hash)))
+                .collect(),
+        })
+    }
+
+    /// Returns the Merkle path of a record commitment to the ledger digest of a canon block,
+    /// or to the latest ledger digest if none is given.
+    fn get_ledger_proof(&self, commitment: String, ledger_digest: Option<String>) -> Result<LedgerProofInfo, RpcError> {
+        let commitment_bytes = hex::decode(&commitment)?;
+        let cm: <Tx as Transaction>::Commitment =
+            FromBytes::read(&commitment_bytes[..]).map_err(|_| RpcError::InvalidCommitment(commitment.clone()))?;
+
+        // The digest and the path are read from the same ledger state
+        self.storage.catch_up_secondary(false)?;
+        let storage = self.storage.view();
+
+        let block_height = match ledger_digest {
+            Some(ledger_digest) => match canon_digest_height(&storage, &hex::decode(&ledger_digest)?)? {
+                Some(block_height) => block_height,
+                None => return Err(RpcError::UnknownLedgerDigest(ledger_digest)),
+            },
+            None => storage.block_height(),
+        };
+
+        let witness = storage.prove_cm_at_height(&cm, block_height)?;
+
+        let mut merkle_path = Vec::with_capacity(witness.path.len());
+        for (left, right) in &witness.path {
+            merkle_path.push((hex::encode(to_bytes![left]?), hex::encode(to_bytes![right]?)));
+        }
+
+        Ok(LedgerProofInfo {
+            block_height,
+            ledger_digest: hex::encode(to_bytes![storage.get_digest_at_height(block_height)?]?),
+            commitment,
+            merkle_path,
+        })
+    }
+
+    /// Returns whether the transactions built against a ledger digest are accepted by the node.
+    fn validate_ledger_digest(&self, ledger_digest: String) -> Result<LedgerDigestInfo, RpcError> {
+        let digest_bytes = hex::decode(&ledger_digest)?;
+
+        self.storage.catch_up_secondary(false)?;
+        let storage = self.storage.view();
+
+        let block_height = storage.get_digest_block_number(&digest_bytes)?;
+        let is_canon = canon_digest_height(&storage, &digest_bytes)?.is_some();
+
+        Ok(LedgerDigestInfo {
+            ledger_digest,
+            is_valid: block_height.is_some(),
+            is_canon,
+            block_height,
+        })
+    }
+
+    /// Returns a page of the canon transactions whose memo starts with the given prefix, in memo order.
+    fn search_transactions(
+        &self,
+        memo_prefix: String,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<TransactionSearchResult>, RpcError> {
+        let prefix = hex::decode(&memo_prefix)?;
+
+        self.storage.catch_up_secondary(false)?;
+
+        let transactions = paginate(self.storage.search_memos(&prefix)?, limit, offset)?;
+
+        Ok(transactions
+            .into_iter()
+            .map(|transaction| TransactionSearchResult {
+                memo: hex::encode(&transaction.key),
+                txid: hex::encode(&transaction.transaction_id),
+                block_height: transaction.block_number,
+            })
+            .collect())
+    }
+
+    /// Returns the canon transaction that created a record commitment, and the index of the commitment.
+    fn get_commitment_info(&self, commitment: String) -> Result<CommitmentInfo, RpcError> {
+        let commitment_bytes = hex::decode(&commitment)?;
+
+        self.storage.catch_up_secondary(false)?;
+
+        let (transaction, index) = match (
+            self.storage.get_commitment_transaction(&commitment_bytes)?,
+            self.storage.get_cm_index(&commitment_bytes)?,
+        ) {
+            (Some(transaction), Some(index)) => (transaction, index),
+            _ => return Err(RpcError::UnknownCommitment(commitment)),
+        };
+
+        Ok(CommitmentInfo {
+            commitment,
+            index,
+            txid: hex::encode(&transaction.transaction_id),
+            block_height: transaction.block_number,
+            confirmations: self
+                .storage
+                .get_latest_block_height()
+                .saturating_sub(transaction.block_number),
+        })
+    }
+
+    /// Returns the size of each storage column and the activity of the storage.
+    fn get_storage_metrics(&self) -> Result<StorageMetrics, RpcError> {
+        Ok(self.primary_storage.get_storage_metrics()?)
+    }
+
+    /// Returns the progress of the parameter files downloaded by the node.
+    fn get_parameter_downloads(&self) -> Result<Vec<ParameterDownloadInfo>, RpcError> {
+        Ok(download_progress()
+            .into_iter()
+            .map(|progress| ParameterDownloadInfo {
+                percent: progress.percent(),
+                state: progress.state.to_string(),
+                error: match progress.state {
+                    DownloadState::Failed(error) => Some(error),
+                    _ => None,
+                },
+                filename: progress.filename,
+                mirror: progress.mirror,
+                downloaded: progress.downloaded,
+                size: progress.size,
+            })
+            .collect())
+    }
 }
@@ -18,190 +18,833 @@
 //!
 //! See [ProtectedRpcFunctions](../trait.ProtectedRpcFunctions.html) for documentation of private endpoints.
 
-use crate::{rpc_trait::ProtectedRpcFunctions, rpc_types::*, RpcImpl};
+use crate::{rpc_trait::ProtectedRpcFunctions, rpc_types::*, token_hash, RpcImpl};
+use snarkos_consensus::{ConsensusParameters, TransactionBuilder, Wallet};
 use snarkos_dpc::base_dpc::{
     encrypted_record::EncryptedRecord,
     instantiated::{Components, InstantiatedDPC},
+    program::ProgramRegistry,
     record::DPCRecord,
     record_encryption::RecordEncryption,
     record_payload::RecordPayload,
+    TransactionAuthorization,
+};
+use snarkos_errors::{
+    consensus::ConsensusError,
+    rpc::{RpcError, RpcErrorCode},
 };
-use snarkos_errors::rpc::RpcError;
 use snarkos_models::{
     algorithms::CRH,
     dpc::{DPCComponents, Record},
-    objects::AccountScheme,
+    objects::{AccountScheme, LedgerScheme},
 };
-use snarkos_objects::{Account, AccountAddress, AccountPrivateKey, AccountViewKey};
+use snarkos_objects::{Account, AccountAddress, AccountPrivateKey, AccountProofKey, AccountViewKey};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
     to_bytes,
 };
 
-use jsonrpc_http_server::jsonrpc_core::{IoDelegate, MetaIoHandler, Params, Value};
-use rand::{thread_rng, Rng};
-use std::{str::FromStr, sync::Arc};
+use chrono::{Duration, Utc};
+use jsonrpc_http_server::jsonrpc_core::{IoDelegate, MetaIoHandler, Middleware, Params, Value};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use sha2::{Digest, Sha256};
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::runtime::Runtime;
 
 type JsonRPCError = jsonrpc_core::Error;
 
+/// The prefix of an API token in the authentication header
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The number of seconds a peer is banned for by default, one day
+pub const DEFAULT_BAN_DURATION: u32 = 86400;
+
+/// The number of confirmations of the records counted in the confirmed balance by default
+pub const DEFAULT_MIN_CONFIRMATIONS: u32 = 1;
+
+/// Returns the error of a call with invalid parameters.
+fn invalid_params<T: Into<String>>(message: T) -> JsonRPCError {
+    RpcError::InvalidParams(message.into()).into()
+}
+
+/// Returns the error of a call whose parameters are not an array.
+fn invalid_request() -> JsonRPCError {
+    RpcErrorCode::InvalidRequest.error("Invalid request")
+}
+
 /// The following `*_protected` functions wrap an authentication check around sensitive functions
 /// before being exposed as an RPC endpoint
 impl RpcImpl {
-    /// Validate the authentication header in the request metadata
-    pub fn validate_auth(&self, meta: Meta) -> Result<(), JsonRPCError> {
-        if let Some(credentials) = &self.credentials {
-            let auth = meta.auth.unwrap_or_else(String::new);
-            let basic_auth_encoding = format!(
-                "Basic {}",
-                base64::encode(format!("{}:{}", credentials.username, credentials.password))
-            );
+    /// Validate that the authentication header in the request metadata grants the given scope.
+    /// The endpoints are open if no credentials or API tokens are set, and to the clients of the Unix domain socket.
+    pub fn validate_auth(&self, meta: Meta, scope: RpcScope) -> Result<(), JsonRPCError> {
+        if meta.is_local || (self.credentials.is_none() && self.tokens.read().is_empty()) {
+            return Ok(());
+        }
+
+        match self.granted_scope(&meta.auth.unwrap_or_else(String::new)) {
+            Some(granted_scope) if granted_scope >= scope => Ok(()),
+            Some(_) => Err(RpcError::PermissionDenied.into()),
+            None => Err(RpcError::AuthenticationFailed.into()),
+        }
+    }
+
+    /// Returns the scope granted by an authentication header, if it holds an API token or the node credentials.
+    pub fn granted_scope(&self, auth: &str) -> Option<RpcScope> {
+        if auth.starts_with(BEARER_PREFIX) {
+            return self
+                .tokens
+                .read()
+                .get(&token_hash(&auth[BEARER_PREFIX.len()..]))
+                .copied();
+        }
 
-            if basic_auth_encoding != auth {
-                return Err(JsonRPCError::invalid_params("Authentication Error"));
+        match &self.credentials {
+            Some(credentials) => {
+                let basic_auth_encoding = format!(
+                    "Basic {}",
+                    base64::encode(format!("{}:{}", credentials.username, credentials.password))
+                );
+
+                // The node credentials grant every scope
+                if basic_auth_encoding == auth {
+                    Some(RpcScope::Admin)
+                } else {
+                    None
+                }
             }
+            None => None,
+        }
+    }
+
+    /// Returns the identity of the client authenticated by the request metadata, a hash of its authentication
+    /// header, or `None` if the endpoints are open to the client.
+    pub fn client_identity(&self, meta: &Meta) -> Option<String> {
+        if meta.is_local || (self.credentials.is_none() && self.tokens.read().is_empty()) {
+            return None;
         }
 
-        Ok(())
+        meta.auth
+            .as_ref()
+            .map(|auth| hex::encode(&Sha256::digest(auth.as_bytes())[..]))
     }
 
     /// Wrap authentication around `create_raw_transaction`
     pub fn create_raw_transaction_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::Wallet)?;
 
         let value = match params {
             Params::Array(arr) => arr,
-            _ => return Err(JsonRPCError::invalid_request()),
+            _ => return Err(invalid_request()),
         };
 
-        let val: TransactionInputs = serde_json::from_value(value[0].clone())
-            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+        let val: TransactionInputs =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
 
         match self.create_raw_transaction(val) {
             Ok(result) => Ok(serde_json::to_value(result).expect("transaction output serialization failed")),
-            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `create_transaction_skeleton`
+    pub fn create_transaction_skeleton_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let transaction_input: TransactionSkeletonInputs =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.create_transaction_skeleton(transaction_input) {
+            Ok(skeleton) => Ok(serde_json::to_value(skeleton).expect("transaction skeleton serialization failed")),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `prove_transaction`
+    pub fn prove_transaction_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let authorization: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.prove_transaction(authorization) {
+            Ok(result) => Ok(serde_json::to_value(result).expect("transaction output serialization failed")),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `get_record_commitment_count`
     pub fn get_record_commitment_count_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::ReadOnly)?;
 
-        params.expect_no_params()?;
+        params.expect_no_params().map_err(|e| invalid_params(e.message))?;
 
         match self.get_record_commitment_count() {
             Ok(num_record_commitments) => Ok(Value::from(num_record_commitments)),
-            Err(_) => Err(JsonRPCError::invalid_request()),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `get_record_commitments`
     pub fn get_record_commitments_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::ReadOnly)?;
 
-        params.expect_no_params()?;
+        params.expect_no_params().map_err(|e| invalid_params(e.message))?;
 
         match self.get_record_commitments() {
             Ok(record_commitments) => Ok(Value::from(record_commitments)),
-            Err(_) => Err(JsonRPCError::invalid_request()),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `get_raw_record`
     pub fn get_raw_record_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::ReadOnly)?;
 
         let value = match params {
             Params::Array(arr) => arr,
-            _ => return Err(JsonRPCError::invalid_request()),
+            _ => return Err(invalid_request()),
         };
 
         if value.len() != 1 {
-            return Err(JsonRPCError::invalid_params(format!(
+            return Err(invalid_params(format!(
                 "invalid length {}, expected 1 element",
                 value.len()
             )));
         }
 
-        let record_commitment: String = serde_json::from_value(value[0].clone())
-            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+        let record_commitment: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
 
         match self.get_raw_record(record_commitment) {
             Ok(record) => Ok(Value::from(record)),
-            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `decode_record`
     pub fn decode_record_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::Wallet)?;
 
         let value = match params {
             Params::Array(arr) => arr,
-            _ => return Err(JsonRPCError::invalid_request()),
+            _ => return Err(invalid_request()),
         };
 
         if value.len() != 1 {
-            return Err(JsonRPCError::invalid_params(format!(
+            return Err(invalid_params(format!(
                 "invalid length {}, expected 1 element",
                 value.len()
             )));
         }
 
-        let record_bytes: String = serde_json::from_value(value[0].clone())
-            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+        let record_bytes: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
 
         match self.decode_record(record_bytes) {
             Ok(record) => Ok(serde_json::to_value(record).expect("record deserialization failed")),
-            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `decrypt_record`
     pub fn decrypt_record_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::Wallet)?;
 
         let value = match params {
             Params::Array(arr) => arr,
-            _ => return Err(JsonRPCError::invalid_request()),
+            _ => return Err(invalid_request()),
         };
 
-        let decrypt_record_input: DecryptRecordInput = serde_json::from_value(value[0].clone())
-            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+        let decrypt_record_input: DecryptRecordInput =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
 
         match self.decrypt_record(decrypt_record_input) {
             Ok(result) => Ok(serde_json::to_value(result).expect("record serialization failed")),
-            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Wrap authentication around `create_account`
     pub fn create_account_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
-        self.validate_auth(meta)?;
+        self.validate_auth(meta, RpcScope::Wallet)?;
 
-        params.expect_no_params()?;
+        params.expect_no_params().map_err(|e| invalid_params(e.message))?;
 
         match self.create_account() {
             Ok(account) => Ok(serde_json::to_value(account).expect("account serialization failed")),
-            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `add_peer`
+    pub fn add_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.add_peer(address) {
+            Ok(is_requested) => Ok(Value::from(is_requested)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `remove_peer`
+    pub fn remove_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.remove_peer(address) {
+            Ok(is_connected) => Ok(Value::from(is_connected)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `ban_peer`
+    pub fn ban_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.is_empty() || value.len() > 2 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 or 2 elements",
+                value.len()
+            )));
+        }
+
+        let address: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        let duration: Option<u32> = match value.get(1) {
+            Some(duration) => serde_json::from_value(duration.clone())
+                .map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?,
+            None => None,
+        };
+
+        match self.ban_peer(address, duration) {
+            Ok(banned_until) => Ok(Value::from(banned_until)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `unban_peer`
+    pub fn unban_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.unban_peer(address) {
+            Ok(is_banned) => Ok(Value::from(is_banned)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `set_log_level`
+    pub fn set_log_level_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let filter: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.set_log_level(filter) {
+            Ok(is_set) => Ok(Value::from(is_set)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `generate_blocks`
+    pub fn generate_blocks_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let count: u32 =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.generate_blocks(count) {
+            Ok(num_pending_blocks) => Ok(Value::from(num_pending_blocks)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `reload_config`
+    pub fn reload_config_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        params.expect_no_params().map_err(|e| invalid_params(e.message))?;
+
+        match self.reload_config() {
+            Ok(settings) => Ok(Value::from(settings)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `revalidate_memory_pool`
+    pub fn revalidate_memory_pool_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        params.expect_no_params().map_err(|e| invalid_params(e.message))?;
+
+        match self.revalidate_memory_pool() {
+            Ok(num_evicted_transactions) => Ok(Value::from(num_evicted_transactions)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `remove_memory_pool_transaction`
+    pub fn remove_memory_pool_transaction_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let transaction_id: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.remove_memory_pool_transaction(transaction_id) {
+            Ok(is_removed) => Ok(Value::from(is_removed)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `rotate_token`
+    pub fn rotate_token_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Admin)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let token: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.rotate_token(token) {
+            Ok(new_token) => Ok(Value::from(new_token)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `submit_job`, recording the authenticated client as the owner of the job
+    pub fn submit_job_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        let owner = self.client_identity(&meta);
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 2 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 2 elements",
+                value.len()
+            )));
+        }
+
+        let method: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+        let job_params: Vec<Value> =
+            serde_json::from_value(value[1].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.submit_owned_job(method, job_params, owner) {
+            Ok(job_id) => Ok(Value::from(job_id)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `get_job_status`
+    pub fn get_job_status_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let job_id: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.get_job_status(job_id) {
+            Ok(job_info) => Ok(serde_json::to_value(job_info).expect("job info serialization failed")),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `get_job_result`
+    pub fn get_job_result_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let job_id: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.get_job_result(job_id) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `cancel_job`
+    pub fn cancel_job_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let job_id: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.cancel_job(job_id) {
+            Ok(is_cancelled) => Ok(Value::from(is_cancelled)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `add_wallet_account`
+    pub fn add_wallet_account_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let wallet_account_input: WalletAccountInput =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.add_wallet_account(wallet_account_input) {
+            Ok(is_new) => Ok(Value::from(is_new)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `remove_wallet_account`
+    pub fn remove_wallet_account_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let account_view_key: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.remove_wallet_account(account_view_key) {
+            Ok(is_removed) => Ok(Value::from(is_removed)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `get_balance`
+    pub fn get_balance_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.is_empty() || value.len() > 2 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 or 2 elements",
+                value.len()
+            )));
+        }
+
+        let account_view_key: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        let min_confirmations: Option<u32> = match value.get(1) {
+            Some(min_confirmations) => serde_json::from_value(min_confirmations.clone())
+                .map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?,
+            None => None,
+        };
+
+        match self.get_balance(account_view_key, min_confirmations) {
+            Ok(balance) => Ok(serde_json::to_value(balance).expect("balance serialization failed")),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Wrap authentication around `list_transactions`
+    pub fn list_transactions_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta, RpcScope::Wallet)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let account_view_key: String =
+            serde_json::from_value(value[0].clone()).map_err(|e| invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.list_transactions(account_view_key) {
+            Ok(transactions) => Ok(serde_json::to_value(transactions).expect("transactions serialization failed")),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Expose the protected functions as RPC enpoints
-    pub fn add_protected(&self, io: &mut MetaIoHandler<Meta>) {
+    pub fn add_protected<S: Middleware<Meta>>(&self, io: &mut MetaIoHandler<Meta, S>) {
         let mut d = IoDelegate::<Self, Meta>::new(Arc::new(self.clone()));
 
         d.add_method_with_meta("createrawtransaction", Self::create_raw_transaction_protected);
+        d.add_method_with_meta("createtransactionskeleton", Self::create_transaction_skeleton_protected);
+        d.add_method_with_meta("provetransaction", Self::prove_transaction_protected);
         d.add_method_with_meta("decoderecord", Self::decode_record_protected);
         d.add_method_with_meta("decryptrecord", Self::decrypt_record_protected);
         d.add_method_with_meta("getrecordcommitmentcount", Self::get_record_commitment_count_protected);
         d.add_method_with_meta("getrecordcommitments", Self::get_record_commitments_protected);
         d.add_method_with_meta("getrawrecord", Self::get_raw_record_protected);
         d.add_method_with_meta("createaccount", Self::create_account_protected);
+        d.add_method_with_meta(
+            "removemempooltransaction",
+            Self::remove_memory_pool_transaction_protected,
+        );
+        d.add_method_with_meta("rotatetoken", Self::rotate_token_protected);
+        d.add_method_with_meta("addpeer", Self::add_peer_protected);
+        d.add_method_with_meta("removepeer", Self::remove_peer_protected);
+        d.add_method_with_meta("banpeer", Self::ban_peer_protected);
+        d.add_method_with_meta("unbanpeer", Self::unban_peer_protected);
+        d.add_method_with_meta("setloglevel", Self::set_log_level_protected);
+        d.add_method_with_meta("generateblocks", Self::generate_blocks_protected);
+        d.add_method_with_meta("reloadconfig", Self::reload_config_protected);
+        d.add_method_with_meta("revalidatemempool", Self::revalidate_memory_pool_protected);
+        d.add_method_with_meta("submitjob", Self::submit_job_protected);
+        d.add_method_with_meta("getjobstatus", Self::get_job_status_protected);
+        d.add_method_with_meta("getjobresult", Self::get_job_result_protected);
+        d.add_method_with_meta("canceljob", Self::cancel_job_protected);
+        d.add_method_with_meta("addwalletaccount", Self::add_wallet_account_protected);
+        d.add_method_with_meta("removewalletaccount", Self::remove_wallet_account_protected);
+        d.add_method_with_meta("getbalance", Self::get_balance_protected);
+        d.add_method_with_meta("listtransactions", Self::list_transactions_protected);
 
         io.extend_with(d)
     }
 }
 
+impl RpcImpl {
+    /// Returns the id of the noop program, which is the birth and death program of the records.
+    fn noop_program_id(&self) -> Result<Vec<u8>, RpcError> {
+        let program_vk_hash = self
+            .parameters
+            .system_parameters
+            .program_verification_key_crh
+            .hash(&to_bytes![
+                self.parameters.noop_program_snark_parameters.verification_key
+            ]?)?;
+
+        Ok(to_bytes![program_vk_hash]?)
+    }
+
+    /// Close the connections to the given peers.
+    async fn close_connections(&self, addresses: &[SocketAddr]) {
+        let mut connections = self.server_context.connections.write().await;
+
+        for address in addresses {
+            if let Some(channel) = connections.remove(address) {
+                // The peer may have closed the connection already
+                let _ = channel.shutdown().await;
+            }
+        }
+    }
+}
+
+/// Parse the socket address of a peer.
+fn parse_peer_address(address: &str) -> Result<SocketAddr, RpcError> {
+    address
+        .parse()
+        .map_err(|_| RpcError::InvalidPeerAddress(address.to_string()))
+}
+
+/// Parse the ip address of a peer, given with or without its port.
+fn parse_peer_ip(address: &str) -> Result<IpAddr, RpcError> {
+    match address.parse::<IpAddr>() {
+        Ok(ip) => Ok(ip),
+        Err(_) => Ok(parse_peer_address(address)?.ip()),
+    }
+}
+
+/// Decode the payload of the record sent to a recipient, which is the default payload if none is given.
+fn decode_payload(recipient: &TransactionRecipient) -> Result<RecordPayload, RpcError> {
+    let payload = match &recipient.payload {
+        Some(payload) => hex::decode(payload)?,
+        None => return Ok(RecordPayload::default()),
+    };
+
+    if payload.len() != RecordPayload::default().size() {
+        return Err(RpcError::InvalidTransactionInputs(format!(
+            "the payload of the recipient {} is {} bytes instead of {}",
+            recipient.address,
+            payload.len(),
+            RecordPayload::default().size()
+        )));
+    }
+
+    Ok(RecordPayload::from_bytes(&payload))
+}
+
 /// Functions that are sensitive and need to be protected with authentication.
 /// The authentication logic is defined in `validate_auth`
 impl ProtectedRpcFunctions for RpcImpl {
@@ -236,60 +879,145 @@ impl ProtectedRpcFunctions for RpcImpl {
     ) -> Result<CreateRawTransactionOuput, RpcError> {
         let rng = &mut thread_rng();
 
-        assert!(!transaction_input.old_records.is_empty());
-        assert!(transaction_input.old_records.len() <= Components::NUM_INPUT_RECORDS);
-        assert!(!transaction_input.old_account_private_keys.is_empty());
-        assert!(transaction_input.old_account_private_keys.len() <= Components::NUM_OUTPUT_RECORDS);
-        assert!(!transaction_input.recipients.is_empty());
-        assert!(transaction_input.recipients.len() <= Components::NUM_OUTPUT_RECORDS);
+        if transaction_input.recipients.is_empty() || transaction_input.old_records.is_empty() {
+            return Err(RpcError::InvalidTransactionInputs(
+                "expected at least one old record and one recipient".into(),
+            ));
+        }
+        if transaction_input.old_records.len() != transaction_input.old_account_private_keys.len() {
+            return Err(RpcError::InvalidTransactionInputs(
+                "expected a private key for each old record".into(),
+            ));
+        }
+
+        // The invalid records are errors of the request, rather than failed verifications
+        let invalid_inputs = |error: ConsensusError| RpcError::InvalidTransactionInputs(error.to_string());
 
-        // Fetch birth/death programs
-        let program_vk_hash = self
-            .parameters
-            .system_parameters
-            .program_verification_key_crh
-            .hash(&to_bytes![
-                self.parameters.noop_program_snark_parameters.verification_key
-            ]?)?;
-        let program_vk_hash_bytes = to_bytes![program_vk_hash]?;
+        let mut builder = TransactionBuilder::new();
+
+        // Decode old records, each spent with the private key of its owner
+        for (record_string, private_key_string) in transaction_input
+            .old_records
+            .iter()
+            .zip(&transaction_input.old_account_private_keys)
+        {
+            let record_bytes = hex::decode(record_string)?;
+            let record = DPCRecord::<Components>::read(&record_bytes[..])?;
+            let private_key = AccountPrivateKey::<Components>::from_str(private_key_string)?;
+
+            builder = builder.add_input(record, private_key).map_err(invalid_inputs)?;
+        }
+
+        // Decode new recipient data
+        for recipient in &transaction_input.recipients {
+            let address = AccountAddress::<Components>::from_network_str(&recipient.address, self.consensus.network)?;
+            let payload = decode_payload(recipient)?;
+
+            builder = builder
+                .add_output_with_payload(address, recipient.amount, payload)
+                .map_err(invalid_inputs)?;
+        }
+
+        // Decode memo
+        let mut memo = [0u8; 32];
+        if let Some(memo_string) = transaction_input.memo {
+            if let Ok(bytes) = hex::decode(memo_string) {
+                bytes.write(&mut memo[..])?;
+            }
+        }
+
+        // If the request did not specify a valid memo, the builder generates one from random
+        if memo != [0u8; 32] {
+            builder = builder.memo(memo);
+        }
+
+        builder.fee().map_err(invalid_inputs)?;
+
+        // Because this is a computationally heavy endpoint, we open a
+        // new secondary storage instance to prevent storage bottle-necking.
+        let storage = self.new_secondary_storage_instance()?;
+
+        // Generate transaction
+        let (records, transaction) = builder.build(
+            &self.consensus,
+            &self.parameters,
+            self.noop_program_id()?,
+            &storage,
+            rng,
+        )?;
+
+        let encoded_transaction = hex::encode(to_bytes![transaction]?);
+        let mut encoded_records = Vec::with_capacity(records.len());
+        for record in records {
+            encoded_records.push(hex::encode(to_bytes![record]?));
+        }
+
+        Ok(CreateRawTransactionOuput {
+            encoded_transaction,
+            encoded_records,
+        })
+    }
+
+    /// Create the skeleton of a transaction, along with the ledger membership witnesses of its
+    /// old records, so the transaction can be signed and proven offline.
+    fn create_transaction_skeleton(
+        &self,
+        transaction_input: TransactionSkeletonInputs,
+    ) -> Result<TransactionSkeleton, RpcError> {
+        if transaction_input.old_records.is_empty()
+            || transaction_input.old_records.len() > Components::NUM_INPUT_RECORDS
+        {
+            return Err(RpcError::InvalidTransactionInputs(format!(
+                "expected 1 to {} old records",
+                Components::NUM_INPUT_RECORDS
+            )));
+        }
 
-        let program_id = program_vk_hash_bytes;
-        let new_birth_program_ids = vec![program_id.clone(); Components::NUM_OUTPUT_RECORDS];
-        let new_death_program_ids = vec![program_id.clone(); Components::NUM_OUTPUT_RECORDS];
+        if transaction_input.recipients.is_empty()
+            || transaction_input.recipients.len() > Components::NUM_OUTPUT_RECORDS
+        {
+            return Err(RpcError::InvalidTransactionInputs(format!(
+                "expected 1 to {} recipients",
+                Components::NUM_OUTPUT_RECORDS
+            )));
+        }
+
+        // The skeleton is generated from the given randomness, so it can be reproduced offline
+        let mut seed = [0u8; 32];
+        let randomness = hex::decode(&transaction_input.randomness)?;
+        if randomness.len() != seed.len() {
+            return Err(RpcError::InvalidTransactionInputs(format!(
+                "expected {} bytes of randomness",
+                seed.len()
+            )));
+        }
+        seed.copy_from_slice(&randomness);
+        let rng = &mut ChaChaRng::from_seed(seed);
+
+        let program_id = self.noop_program_id()?;
+        let sender_address =
+            AccountAddress::<Components>::from_network_str(&transaction_input.sender_address, self.consensus.network)?;
 
         // Decode old records
-        let mut old_records = Vec::with_capacity(transaction_input.old_records.len());
+        let mut old_records = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
         for record_string in transaction_input.old_records {
             let record_bytes = hex::decode(record_string)?;
             old_records.push(DPCRecord::<Components>::read(&record_bytes[..])?);
         }
 
-        let mut old_account_private_keys = Vec::with_capacity(transaction_input.old_account_private_keys.len());
-        for private_key_string in transaction_input.old_account_private_keys {
-            old_account_private_keys.push(AccountPrivateKey::<Components>::from_str(&private_key_string)?);
-        }
-
-        let sn_randomness: [u8; 32] = rng.gen();
         // Fill any unused old_record indices with dummy records
-        while old_records.len() < Components::NUM_OUTPUT_RECORDS {
+        while old_records.len() < Components::NUM_INPUT_RECORDS {
+            let sn_randomness: [u8; 32] = rng.gen();
             let old_sn_nonce = self
                 .parameters
                 .system_parameters
                 .serial_number_nonce
                 .hash(&sn_randomness)?;
 
-            let private_key = old_account_private_keys[0].clone();
-            let address = AccountAddress::<Components>::from_private_key(
-                self.parameters.account_signature_parameters(),
-                self.parameters.account_commitment_parameters(),
-                self.parameters.account_encryption_parameters(),
-                &private_key,
-            )?;
-
             let dummy_record = InstantiatedDPC::generate_record(
                 self.parameters.system_parameters.clone(),
                 old_sn_nonce,
-                address,
+                sender_address.clone(),
                 true, // The input record is dummy
                 0,
                 RecordPayload::default(),
@@ -299,36 +1027,31 @@ impl ProtectedRpcFunctions for RpcImpl {
             )?;
 
             old_records.push(dummy_record);
-            old_account_private_keys.push(private_key);
         }
 
-        assert_eq!(old_records.len(), Components::NUM_INPUT_RECORDS);
-        assert_eq!(old_account_private_keys.len(), Components::NUM_INPUT_RECORDS);
-
         // Decode new recipient data
         let mut new_record_owners = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
         let mut new_is_dummy_flags = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
         let mut new_values = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        let mut new_payloads = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
         for recipient in transaction_input.recipients {
-            new_record_owners.push(AccountAddress::<Components>::from_str(&recipient.address)?);
+            new_record_owners.push(
+                AccountAddress::<Components>::from_network_str(&recipient.address, self.consensus.network)?.to_string(),
+            );
             new_is_dummy_flags.push(false);
             new_values.push(recipient.amount);
+            new_payloads.push(hex::encode(to_bytes![decode_payload(&recipient)?]?));
         }
 
-        // Fill any unused new_record indices with dummy output values
+        // Fill any unused new_record indices with dummy output values and the default payload
+        let default_payload = hex::encode(to_bytes![RecordPayload::default()]?);
         while new_record_owners.len() < Components::NUM_OUTPUT_RECORDS {
             new_record_owners.push(new_record_owners[0].clone());
             new_is_dummy_flags.push(true);
             new_values.push(0);
+            new_payloads.push(default_payload.clone());
         }
 
-        assert_eq!(new_record_owners.len(), Components::NUM_OUTPUT_RECORDS);
-        assert_eq!(new_is_dummy_flags.len(), Components::NUM_OUTPUT_RECORDS);
-        assert_eq!(new_values.len(), Components::NUM_OUTPUT_RECORDS);
-
-        // Default record payload
-        let new_payloads = vec![RecordPayload::default(); Components::NUM_OUTPUT_RECORDS];
-
         // Decode memo
         let mut memo = [0u8; 32];
         if let Some(memo_string) = transaction_input.memo {
@@ -337,28 +1060,88 @@ impl ProtectedRpcFunctions for RpcImpl {
             }
         }
 
-        // If the request did not specify a valid memo, generate one from random
+        // If the request did not specify a valid memo, generate one from the randomness
         if memo == [0u8; 32] {
             memo = rng.gen();
         }
 
-        // Because this is a computationally heavy endpoint, we open a
-        // new secondary storage instance to prevent storage bottle-necking.
-        let storage = self.new_secondary_storage_instance()?;
+        // The digest and the witnesses are read from the same ledger state
+        self.storage.catch_up_secondary(false)?;
+        let storage = self.storage.view();
+
+        let mut old_witnesses = Vec::with_capacity(Components::NUM_INPUT_RECORDS);
+        for record in &old_records {
+            let witness = if record.is_dummy() {
+                Default::default()
+            } else {
+                storage
+                    .prove_cm(&record.commitment())
+                    .map_err(|error| RpcError::InvalidTransactionInputs(error.to_string()))?
+            };
+
+            let mut path = Vec::with_capacity(witness.path.len());
+            for (left, right) in &witness.path {
+                path.push((hex::encode(to_bytes![left]?), hex::encode(to_bytes![right]?)));
+            }
 
-        // Generate transaction
-        let (records, transaction) = self.consensus.create_transaction(
-            &self.parameters,
-            old_records,
-            old_account_private_keys,
+            old_witnesses.push(path);
+        }
+
+        let mut encoded_old_records = Vec::with_capacity(old_records.len());
+        for record in old_records {
+            encoded_old_records.push(hex::encode(to_bytes![record]?));
+        }
+
+        Ok(TransactionSkeleton {
+            old_records: encoded_old_records,
             new_record_owners,
-            new_birth_program_ids,
-            new_death_program_ids,
             new_is_dummy_flags,
             new_values,
             new_payloads,
-            memo,
-            &storage,
+            new_birth_program_ids: vec![hex::encode(&program_id); Components::NUM_OUTPUT_RECORDS],
+            new_death_program_ids: vec![hex::encode(&program_id); Components::NUM_OUTPUT_RECORDS],
+            memo: hex::encode(memo),
+            network_id: self.consensus.network.id(),
+            block_height: storage.block_height(),
+            ledger_digest: hex::encode(to_bytes![storage.digest()]?),
+            old_witnesses,
+        })
+    }
+
+    /// Returns the number of record commitments that are stored on the full node.
+    /// Prove a transaction authorized offline, returning the encoded transaction and the new records.
+    /// The authorization holds no private keys, so the node proves the transaction without
+    /// the spending authority of the accounts.
+    fn prove_transaction(&self, authorization: String) -> Result<CreateRawTransactionOuput, RpcError> {
+        let rng = &mut thread_rng();
+
+        let authorization_bytes = hex::decode(authorization)?;
+        let authorization = TransactionAuthorization::<Components>::read(&authorization_bytes[..])?;
+
+        if authorization.network_id != self.consensus.network.id() {
+            return Err(RpcError::InvalidTransactionInputs(format!(
+                "expected an authorization for network {}",
+                self.consensus.network.id()
+            )));
+        }
+
+        // Because this is a computationally heavy endpoint, we open a
+        // new secondary storage instance to prevent storage bottle-necking.
+        let storage = self.new_secondary_storage_instance()?;
+
+        // The authorization is signed against a ledger digest, which must still be valid once proven
+        if !storage.validate_digest(&authorization.ledger_digest) {
+            return Err(RpcError::InvalidTransactionInputs(
+                "the ledger digest of the authorization is unknown".into(),
+            ));
+        }
+
+        let programs = ProgramRegistry::new(&self.parameters)?;
+        let (records, transaction) = ConsensusParameters::prove_transaction(
+            &self.parameters,
+            &programs,
+            authorization,
+            storage.parameters(),
             rng,
         )?;
 
@@ -374,7 +1157,6 @@ impl ProtectedRpcFunctions for RpcImpl {
         })
     }
 
-    /// Returns the number of record commitments that are stored on the full node.
     fn get_record_commitment_count(&self) -> Result<usize, RpcError> {
         self.storage.catch_up_secondary(false)?;
         let record_commitments = self.storage.get_record_commitments(None)?;
@@ -449,4 +1231,235 @@ impl ProtectedRpcFunctions for RpcImpl {
             commitment_randomness,
         })
     }
+
+    /// Remove a transaction from the memory pool, returning true if it was in the memory pool.
+    fn remove_memory_pool_transaction(&self, transaction_id: String) -> Result<bool, RpcError> {
+        let transaction_id = hex::decode(transaction_id)?;
+
+        let mut memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        Ok(memory_pool.remove_by_hash(&transaction_id)?.is_some())
+    }
+
+    /// Replace an API token with a new random token of the same scope, returning the new token.
+    /// The rotation is stored, so the replaced token stays revoked after a reload or a restart.
+    fn rotate_token(&self, token: String) -> Result<String, RpcError> {
+        let mut tokens = self.tokens.write();
+
+        let hash = token_hash(&token);
+        let scope = *tokens.get(&hash).ok_or(RpcError::UnknownToken)?;
+        let new_token = self
+            .token_rotations
+            .write()
+            .rotate(&self.primary_storage, hash.clone(), scope)?;
+
+        tokens.remove(&hash);
+        tokens.insert(token_hash(&new_token), scope);
+
+        Ok(new_token)
+    }
+
+    /// Request a handshake with a peer. Returns false if the peer is connected or already requested.
+    fn add_peer(&self, address: String) -> Result<bool, RpcError> {
+        let address = parse_peer_address(&address)?;
+
+        let mut peer_book = Runtime::new()?.block_on(self.server_context.peer_book.write());
+
+        if peer_book.is_banned(&address.ip()) {
+            return Err(RpcError::BannedPeer(address.to_string()));
+        }
+
+        Ok(!peer_book.connected_contains(&address) && peer_book.request_peer(address))
+    }
+
+    /// Disconnect from a peer. Returns true if the peer was connected.
+    fn remove_peer(&self, address: String) -> Result<bool, RpcError> {
+        let address = parse_peer_address(&address)?;
+
+        Ok(Runtime::new()?.block_on(async {
+            let mut peer_book = self.server_context.peer_book.write().await;
+
+            let is_connected = peer_book.connected_contains(&address);
+            if is_connected {
+                peer_book.disconnect_peer(address);
+                self.close_connections(&[address]).await;
+            }
+
+            is_connected
+        }))
+    }
+
+    /// Ban a peer ip address and disconnect from its peers. Returns the time the ban expires.
+    fn ban_peer(&self, address: String, duration: Option<u32>) -> Result<i64, RpcError> {
+        let ip = parse_peer_ip(&address)?;
+        let banned_until = Utc::now() + Duration::seconds(i64::from(duration.unwrap_or(DEFAULT_BAN_DURATION)));
+
+        Runtime::new()?.block_on(async {
+            let disconnected_peers = self.server_context.peer_book.write().await.ban_peer(ip, banned_until);
+
+            self.close_connections(&disconnected_peers).await;
+        });
+
+        Ok(banned_until.timestamp())
+    }
+
+    /// Lift the ban of a peer ip address. Returns true if the ip address was banned.
+    fn unban_peer(&self, address: String) -> Result<bool, RpcError> {
+        let ip = parse_peer_ip(&address)?;
+
+        let mut peer_book = Runtime::new()?.block_on(self.server_context.peer_book.write());
+
+        Ok(peer_book.unban_peer(&ip))
+    }
+
+    /// Replace the log filter of the node.
+    fn set_log_level(&self, filter: String) -> Result<bool, RpcError> {
+        let log_filter = self.log_filter.as_ref().ok_or(RpcError::LoggingDisabled)?;
+
+        log_filter(&filter).map_err(RpcError::InvalidLogFilter)?;
+
+        Ok(true)
+    }
+
+    /// Request blocks from the miner of a development network. Returns the number of blocks pending.
+    fn generate_blocks(&self, count: u32) -> Result<u32, RpcError> {
+        let block_requests = self.block_requests.as_ref().ok_or(RpcError::DevModeDisabled)?;
+
+        Ok(block_requests.request(count))
+    }
+
+    /// Reload the configuration of the node. Returns the names of the replaced settings.
+    fn reload_config(&self) -> Result<Vec<String>, RpcError> {
+        let config_reloader = self.config_reloader.as_ref().ok_or(RpcError::ConfigReloadDisabled)?;
+
+        config_reloader().map_err(RpcError::ConfigReloadFailed)
+    }
+
+    /// Revalidate the memory pool transactions against the ledger. Returns the number of evicted transactions.
+    fn revalidate_memory_pool(&self) -> Result<usize, RpcError> {
+        let mut memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        let num_transactions = memory_pool.transactions.len();
+
+        memory_pool.cleanse(&self.primary_storage)?;
+        memory_pool.store(&self.primary_storage)?;
+
+        Ok(num_transactions.saturating_sub(memory_pool.transactions.len()))
+    }
+
+    /// Run a call as an asynchronous job. Returns the id of the job.
+    fn submit_job(&self, method: String, params: Vec<Value>) -> Result<String, RpcError> {
+        self.submit_owned_job(method, params, None)
+    }
+
+    /// Returns the state of a job.
+    fn get_job_status(&self, job_id: String) -> Result<JobInfo, RpcError> {
+        match self.jobs.get(&job_id) {
+            Some(job) => Ok(job.info),
+            None => Err(RpcError::UnknownJob(job_id)),
+        }
+    }
+
+    /// Returns the result of a completed job.
+    fn get_job_result(&self, job_id: String) -> Result<Value, RpcError> {
+        let job = self
+            .jobs
+            .get(&job_id)
+            .ok_or_else(|| RpcError::UnknownJob(job_id.clone()))?;
+
+        match (job.info.status, job.result) {
+            (JobStatus::Completed, Some(result)) => Ok(result),
+            (JobStatus::Failed, _) => Err(RpcError::JobFailed(job.info.error.unwrap_or_default())),
+            _ => Err(RpcError::JobNotCompleted(job_id)),
+        }
+    }
+
+    /// Cancel a job. Returns false if the job is already finished.
+    fn cancel_job(&self, job_id: String) -> Result<bool, RpcError> {
+        let is_cancelled = self.update_job(&job_id, |job| {
+            if job.info.status.is_finished() {
+                return false;
+            }
+
+            job.info.status = JobStatus::Cancelled;
+            job.info.finished = Some(Utc::now().timestamp());
+            true
+        });
+
+        is_cancelled.ok_or_else(|| RpcError::UnknownJob(job_id))
+    }
+
+    /// Register an account view key with the wallet of the node, which tracks the spends of its records
+    /// if the account private key is given. Returns false if the view key was already registered.
+    fn add_wallet_account(&self, wallet_account_input: WalletAccountInput) -> Result<bool, RpcError> {
+        let view_key = AccountViewKey::<Components>::from_str(&wallet_account_input.account_view_key)?;
+
+        let proof_key = match &wallet_account_input.account_private_key {
+            Some(private_key) => Some(AccountProofKey::<Components>::from_private_key(
+                self.parameters.account_signature_parameters(),
+                &AccountPrivateKey::<Components>::from_str(private_key)?,
+            )?),
+            None => None,
+        };
+
+        // The wallet accounts are updated by the node, so they are written to the primary storage
+        let storage = &self.primary_storage;
+        let is_new = storage.get_wallet_account(&to_bytes![view_key]?)?.is_none();
+
+        Wallet::new(&self.parameters, storage).register_account(&view_key, proof_key.as_ref())?;
+
+        Ok(is_new)
+    }
+
+    /// Remove an account view key from the wallet of the node. Returns false if it was not registered.
+    fn remove_wallet_account(&self, account_view_key: String) -> Result<bool, RpcError> {
+        let view_key = AccountViewKey::<Components>::from_str(&account_view_key)?;
+
+        Ok(Wallet::new(&self.parameters, &self.primary_storage).unregister_account(&view_key)?)
+    }
+
+    /// Returns the balance of an account view key registered with the wallet.
+    fn get_balance(
+        &self,
+        account_view_key: String,
+        min_confirmations: Option<u32>,
+    ) -> Result<WalletBalanceInfo, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+
+        let view_key = AccountViewKey::<Components>::from_str(&account_view_key)?;
+        let min_confirmations = min_confirmations.unwrap_or(DEFAULT_MIN_CONFIRMATIONS);
+
+        let balance = Wallet::new(&self.parameters, &self.storage)
+            .balance(&view_key, min_confirmations)?
+            .ok_or(RpcError::UnknownWalletAccount(account_view_key))?;
+
+        Ok(WalletBalanceInfo {
+            confirmed: balance.confirmed,
+            pending: balance.pending,
+            tracks_spends: balance.tracks_spends,
+        })
+    }
+
+    /// Returns the canon transactions paying or spending the records of an account view key registered
+    /// with the wallet, ordered by block.
+    fn list_transactions(&self, account_view_key: String) -> Result<Vec<WalletTransactionInfo>, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+
+        let view_key = AccountViewKey::<Components>::from_str(&account_view_key)?;
+
+        let transactions = Wallet::new(&self.parameters, &self.storage)
+            .transactions(&view_key)?
+            .ok_or(RpcError::UnknownWalletAccount(account_view_key))?;
+
+        Ok(transactions
+            .into_iter()
+            .map(|transaction| WalletTransactionInfo {
+                txid: hex::encode(&transaction.transaction_id),
+                block_height: transaction.block_number,
+                confirmations: transaction.confirmations,
+                received: transaction.received,
+                spent: transaction.spent,
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,194 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for serving the JSON-RPC endpoints over a Unix domain socket.
+//!
+//! The socket can only be reached by the local users allowed by its filesystem permissions,
+//! so its clients call the private endpoints without credentials.
+
+use crate::rpc_types::Meta;
+use snarkos_errors::rpc::RpcError;
+
+use jsonrpc_core::{MetaIoHandler, Middleware};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    sync::Arc,
+    thread,
+};
+
+/// The default filesystem permissions of the socket, which only allow the user running the node.
+pub const DEFAULT_IPC_SOCKET_MODE: u32 = 0o600;
+
+/// The maximum size in bytes of a request line, unless the request size is limited by the RPC server limits.
+pub const DEFAULT_IPC_MAX_REQUEST_SIZE: usize = 5 * 1024 * 1024;
+
+fn default_socket_mode() -> u32 {
+    DEFAULT_IPC_SOCKET_MODE
+}
+
+/// Defines the Unix domain socket settings of the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcIpcConfig {
+    /// The path of the socket
+    pub path: PathBuf,
+    /// The filesystem permissions of the socket
+    #[serde(default = "default_socket_mode")]
+    pub mode: u32,
+    /// If set, the RPC and WebSocket ports are not opened, and the RPC server is only reachable through the socket
+    #[serde(default)]
+    pub ipc_only: bool,
+}
+
+/// Starts a thread accepting the connections to the Unix domain socket of the given config,
+/// and answering the JSON-RPC requests of each connection in its own thread.
+/// A socket left at the path by a previous run of the node is replaced.
+/// A connection is closed if it sends a request larger than `max_request_size`, or `DEFAULT_IPC_MAX_REQUEST_SIZE`.
+#[cfg(unix)]
+pub fn start_ipc_server<S: Middleware<Meta> + 'static>(
+    config: &RpcIpcConfig,
+    io: MetaIoHandler<Meta, S>,
+    max_request_size: Option<usize>,
+) -> Result<(), RpcError> {
+    if let Ok(metadata) = fs::symlink_metadata(&config.path) {
+        if !metadata.file_type().is_socket() {
+            return Err(RpcError::InvalidIpcConfig(format!(
+                "{} exists and is not a socket",
+                config.path.display()
+            )));
+        }
+
+        fs::remove_file(&config.path)?;
+    }
+
+    let listener = bind_socket(&config.path, config.mode)?;
+    let max_request_size = max_request_size.unwrap_or(DEFAULT_IPC_MAX_REQUEST_SIZE);
+
+    let io = Arc::new(io);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                let io = io.clone();
+                thread::spawn(move || serve_ipc_connection(stream, &io, max_request_size));
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Binds a socket at the given path with the given filesystem permissions.
+/// The socket is bound in a directory only the user running the node can enter, and moved to its path once its
+/// permissions are set, so it cannot be reached with the permissions of the umask in between.
+#[cfg(unix)]
+fn bind_socket(path: &Path, mode: u32) -> Result<UnixListener, RpcError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| RpcError::InvalidIpcConfig(format!("{} is not a file path", path.display())))?;
+
+    let mut private_dir_name = file_name.to_os_string();
+    private_dir_name.push(".bind");
+    let private_dir = path.with_file_name(private_dir_name);
+    let private_path = private_dir.join("socket");
+
+    fs::DirBuilder::new().mode(0o700).create(&private_dir)?;
+
+    let result = UnixListener::bind(&private_path).and_then(|listener| {
+        fs::set_permissions(&private_path, fs::Permissions::from_mode(mode))?;
+        fs::rename(&private_path, path)?;
+        Ok(listener)
+    });
+
+    // The socket is left in the directory if it could not be moved to its path
+    let _ = fs::remove_file(&private_path);
+    let _ = fs::remove_dir(&private_dir);
+
+    Ok(result?)
+}
+
+/// Unix domain sockets are not available on this platform.
+#[cfg(not(unix))]
+pub fn start_ipc_server<S: Middleware<Meta> + 'static>(
+    _config: &RpcIpcConfig,
+    _io: MetaIoHandler<Meta, S>,
+    _max_request_size: Option<usize>,
+) -> Result<(), RpcError> {
+    Err(RpcError::InvalidIpcConfig(
+        "Unix domain sockets are not supported on this platform".to_string(),
+    ))
+}
+
+/// Answers the requests of a connection until it is closed, or until it sends a request larger than
+/// `max_request_size`. Each line holds a JSON-RPC request or batch, and is answered with a line holding its response.
+#[cfg(unix)]
+fn serve_ipc_connection<S: Middleware<Meta>>(stream: UnixStream, io: &MetaIoHandler<Meta, S>, max_request_size: usize) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        // A byte past the maximum size is read, to tell a request of the maximum size from a larger request
+        let mut line = Vec::new();
+        match (&mut reader)
+            .take(max_request_size as u64 + 1)
+            .read_until(b'\n', &mut line)
+        {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if line.len() > max_request_size {
+            return;
+        }
+
+        let request = match String::from_utf8(line) {
+            Ok(request) => request,
+            Err(_) => return,
+        };
+
+        if request.trim().is_empty() {
+            continue;
+        }
+
+        // The clients of the socket are authorized by its filesystem permissions
+        let meta = Meta {
+            auth: None,
+            is_local: true,
+            api_version: None,
+        };
+
+        // Notifications are not answered
+        if let Some(response) = io.handle_request_sync(&request, meta) {
+            if writeln!(writer, "{}", response).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for running long RPC calls, such as `createrawtransaction` and `provetransaction`, as asynchronous jobs.
+//!
+//! The jobs run one at a time in the background, and their state is stored by the node,
+//! so their results can be fetched once the connection submitting them is closed.
+
+use crate::{rpc_trait::ProtectedRpcFunctions, rpc_types::*, RpcImpl};
+use snarkos_consensus::MerkleTreeLedger;
+use snarkos_errors::rpc::RpcError;
+
+use chrono::Utc;
+use jsonrpc_http_server::jsonrpc_core::Value;
+use parking_lot::{Mutex, RwLock};
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, thread};
+
+/// The maximum number of finished jobs whose state is kept.
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// The error of the jobs interrupted by a restart of the node.
+const INTERRUPTED_JOB_ERROR: &str = "interrupted by a restart of the node";
+
+/// An asynchronous job, with its result once completed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    /// The state of the job
+    pub info: JobInfo,
+    /// The result of the completed job
+    pub result: Option<Value>,
+    /// The identity of the client that submitted the job, if the endpoints are protected
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+/// A call run by a job, with its parsed parameters.
+/// The parameters are not stored with the job, as they may hold account private keys.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobCall {
+    /// A `createrawtransaction` call
+    CreateRawTransaction(TransactionInputs),
+    /// A `provetransaction` call
+    ProveTransaction(String),
+}
+
+impl JobCall {
+    /// Parses a call of the given method, which must be one of the methods run as jobs.
+    pub fn parse(method: &str, params: Vec<Value>) -> Result<Self, RpcError> {
+        match method {
+            "createrawtransaction" => {
+                if params.len() != 1 {
+                    return Err(RpcError::InvalidParams(format!(
+                        "invalid length {}, expected 1 element",
+                        params.len()
+                    )));
+                }
+
+                let transaction_inputs = serde_json::from_value(params[0].clone())
+                    .map_err(|error| RpcError::InvalidParams(format!("Invalid params: {}.", error)))?;
+
+                Ok(JobCall::CreateRawTransaction(transaction_inputs))
+            }
+            "provetransaction" => {
+                if params.len() != 1 {
+                    return Err(RpcError::InvalidParams(format!(
+                        "invalid length {}, expected 1 element",
+                        params.len()
+                    )));
+                }
+
+                let authorization = serde_json::from_value(params[0].clone())
+                    .map_err(|error| RpcError::InvalidParams(format!("Invalid params: {}.", error)))?;
+
+                Ok(JobCall::ProveTransaction(authorization))
+            }
+            _ => Err(RpcError::UnsupportedJobMethod(method.to_string())),
+        }
+    }
+}
+
+impl JobStatus {
+    /// Returns true if the job will not run anymore.
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Pending | JobStatus::Running)
+    }
+}
+
+/// The jobs of the RPC server, by id.
+#[derive(Default)]
+pub struct JobQueue {
+    jobs: RwLock<HashMap<String, Job>>,
+    /// Held by the running job, as proving already uses every core
+    runner: Mutex<()>,
+}
+
+impl JobQueue {
+    /// Loads the jobs stored by the node.
+    /// The jobs interrupted by a restart are failed, as their parameters are not stored.
+    pub fn load(storage: &MerkleTreeLedger) -> Self {
+        let mut jobs: HashMap<String, Job> = storage
+            .get_rpc_jobs()
+            .ok()
+            .and_then(|jobs_serialized| serde_json::from_slice(&jobs_serialized).ok())
+            .unwrap_or_default();
+
+        for job in jobs.values_mut() {
+            if !job.info.status.is_finished() {
+                job.info.status = JobStatus::Failed;
+                job.info.finished = Some(Utc::now().timestamp());
+                job.info.error = Some(INTERRUPTED_JOB_ERROR.to_string());
+            }
+        }
+
+        Self {
+            jobs: RwLock::new(jobs),
+            runner: Mutex::new(()),
+        }
+    }
+
+    /// Returns the job with the given id.
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.jobs.read().get(id).cloned()
+    }
+
+    /// Returns the state of every job.
+    pub fn infos(&self) -> Vec<JobInfo> {
+        self.jobs.read().values().map(|job| job.info.clone()).collect()
+    }
+
+    /// Returns every job.
+    pub fn jobs(&self) -> Vec<Job> {
+        self.jobs.read().values().cloned().collect()
+    }
+}
+
+/// Removes the oldest finished jobs beyond `MAX_FINISHED_JOBS`.
+fn prune_jobs(jobs: &mut HashMap<String, Job>) {
+    let mut finished_jobs: Vec<(i64, String)> = jobs
+        .values()
+        .filter_map(|job| job.info.finished.map(|finished| (finished, job.info.id.clone())))
+        .collect();
+
+    if finished_jobs.len() > MAX_FINISHED_JOBS {
+        finished_jobs.sort();
+
+        for (_, id) in &finished_jobs[..finished_jobs.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(id);
+        }
+    }
+}
+
+impl RpcImpl {
+    /// Parses a call of one of the job methods and starts it as a job of the given client, returning its id.
+    pub(crate) fn submit_owned_job(
+        &self,
+        method: String,
+        params: Vec<Value>,
+        owner: Option<String>,
+    ) -> Result<String, RpcError> {
+        let call = JobCall::parse(&method, params)?;
+
+        Ok(self.start_job(method, call, owner))
+    }
+
+    /// Starts a job of the given client running the given call in the background, and returns its id.
+    pub(crate) fn start_job(&self, method: String, call: JobCall, owner: Option<String>) -> String {
+        let id = hex::encode(thread_rng().gen::<[u8; 16]>());
+
+        let job = Job {
+            info: JobInfo {
+                id: id.clone(),
+                method,
+                status: JobStatus::Pending,
+                submitted: Utc::now().timestamp(),
+                started: None,
+                finished: None,
+                error: None,
+            },
+            result: None,
+            owner,
+        };
+
+        {
+            let mut jobs = self.jobs.jobs.write();
+            jobs.insert(id.clone(), job);
+            self.store_jobs(&jobs);
+        }
+
+        let rpc = self.clone();
+        let job_id = id.clone();
+        thread::spawn(move || rpc.run_job(&job_id, call));
+
+        id
+    }
+
+    /// Runs a job once the previous jobs are finished, unless it was cancelled in the meantime.
+    fn run_job(&self, id: &str, call: JobCall) {
+        let _runner = self.jobs.runner.lock();
+
+        let is_started = self.update_job(id, |job| {
+            if job.info.status != JobStatus::Pending {
+                return false;
+            }
+
+            job.info.status = JobStatus::Running;
+            job.info.started = Some(Utc::now().timestamp());
+            true
+        });
+
+        if is_started != Some(true) {
+            return;
+        }
+
+        let result = match call {
+            JobCall::CreateRawTransaction(transaction_inputs) => {
+                self.create_raw_transaction(transaction_inputs).and_then(|output| {
+                    serde_json::to_value(output).map_err(|error| RpcError::Crate("serde_json", format!("{:?}", error)))
+                })
+            }
+            JobCall::ProveTransaction(authorization) => self.prove_transaction(authorization).and_then(|output| {
+                serde_json::to_value(output).map_err(|error| RpcError::Crate("serde_json", format!("{:?}", error)))
+            }),
+        };
+
+        // The result of a job cancelled while running is discarded
+        self.update_job(id, |job| {
+            if job.info.status != JobStatus::Running {
+                return;
+            }
+
+            job.info.finished = Some(Utc::now().timestamp());
+
+            match result {
+                Ok(value) => {
+                    job.info.status = JobStatus::Completed;
+                    job.result = Some(value);
+                }
+                Err(error) => {
+                    job.info.status = JobStatus::Failed;
+                    job.info.error = Some(error.to_string());
+                }
+            }
+        });
+    }
+
+    /// Applies a change to a job, then stores the jobs.
+    /// Returns the value of the change, or `None` if the job is unknown.
+    pub(crate) fn update_job<T, F: FnOnce(&mut Job) -> T>(&self, id: &str, change: F) -> Option<T> {
+        let mut jobs = self.jobs.jobs.write();
+
+        let value = change(jobs.get_mut(id)?);
+
+        prune_jobs(&mut jobs);
+        self.store_jobs(&jobs);
+
+        Some(value)
+    }
+
+    /// Store the jobs, so they are known after a restart of the node.
+    /// A failure to store the jobs does not affect the running jobs, so it is ignored.
+    fn store_jobs(&self, jobs: &HashMap<String, Job>) {
+        if let Ok(jobs_serialized) = serde_json::to_vec(jobs) {
+            let _ = self.primary_storage.store_rpc_jobs(jobs_serialized);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for limiting the rate, the concurrency and the size of the requests to the RPC server.
+//!
+//! The HTTP server does not expose the address of the clients, so the limits per IP address and
+//! the concurrency limit are enforced by a listener accepting the connections in front of it.
+//! The limit per API token only counts the requests with valid credentials, so the requests without
+//! them are only limited per IP address.
+
+use crate::rpc_tls::forward;
+use snarkos_errors::rpc::{RpcError, RpcErrorCode};
+use snarkos_profiler::METRICS;
+
+use jsonrpc_core::{
+    futures::future::{self, Either},
+    Call,
+    FutureOutput,
+    FutureResponse,
+    Metadata,
+    Middleware,
+    Output,
+    Version,
+};
+use jsonrpc_http_server::{
+    hyper::{
+        self,
+        header::{HeaderValue, AUTHORIZATION, CONTENT_LENGTH},
+        Body,
+        StatusCode,
+    },
+    RequestMiddleware,
+    RequestMiddlewareAction,
+    Response,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+};
+
+/// The maximum number of clients whose request rate is tracked at the same time.
+pub const MAX_RATE_LIMITED_CLIENTS: usize = 10_000;
+
+/// The JSON-RPC error code of the calls rejected by a method rate limit.
+pub const RATE_LIMITED_ERROR_CODE: i64 = RpcErrorCode::RateLimited as i64;
+
+/// The response sent on the connections rejected by the limits, before closing them.
+pub const TOO_MANY_REQUESTS_RESPONSE: &[u8] =
+    b"HTTP/1.1 429 Too Many Requests\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+
+/// The content type of the responses of the limits.
+const LIMITS_CONTENT_TYPE: &str = "text/plain";
+
+/// Defines the request limits of the RPC server
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcLimitsConfig {
+    /// The number of requests per second accepted from each IP address
+    pub requests_per_ip: Option<u32>,
+    /// The number of requests per second accepted with each API token or credentials
+    pub requests_per_token: Option<u32>,
+    /// The number of calls per second accepted for each of the given methods, from all the clients
+    pub requests_per_method: HashMap<String, u32>,
+    /// The maximum number of requests handled at the same time
+    pub max_concurrent_requests: Option<usize>,
+    /// The maximum size of a request body in bytes
+    pub max_request_size: Option<usize>,
+}
+
+/// Limits the rate of the requests of each client, allowing bursts of up to a second of requests.
+pub struct RateLimiter<K> {
+    rate: u32,
+    buckets: Mutex<Buckets<K>>,
+}
+
+/// The request buckets of the clients, in order of their last update.
+struct Buckets<K> {
+    /// The requests each client can still send, the time they were last updated, and the number of the update
+    buckets: HashMap<K, (f64, Instant, u64)>,
+    /// The clients by the number of their last update, the least recently updated first
+    updates: BTreeMap<u64, K>,
+    next_update: u64,
+}
+
+impl<K: Clone + Eq + Hash> RateLimiter<K> {
+    /// Creates a rate limiter accepting the given number of requests per second from each client.
+    pub fn new(rate: u32) -> Self {
+        Self {
+            rate,
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                updates: BTreeMap::new(),
+                next_update: 0,
+            }),
+        }
+    }
+
+    /// Returns true if a request of the client is accepted, and counts it.
+    /// Beyond `MAX_RATE_LIMITED_CLIENTS`, the least recently updated clients are forgotten.
+    pub fn check(&self, client: K) -> bool {
+        let now = Instant::now();
+        let rate = f64::from(self.rate);
+
+        let mut buckets = self.buckets.lock();
+        let Buckets {
+            buckets,
+            updates,
+            next_update,
+        } = &mut *buckets;
+
+        let mut tokens = match buckets.remove(&client) {
+            Some((tokens, updated, update)) => {
+                updates.remove(&update);
+                (tokens + now.duration_since(updated).as_secs_f64() * rate).min(rate)
+            }
+            None => {
+                while buckets.len() >= MAX_RATE_LIMITED_CLIENTS {
+                    let oldest_update = match updates.keys().next() {
+                        Some(update) => *update,
+                        None => break,
+                    };
+                    if let Some(oldest_client) = updates.remove(&oldest_update) {
+                        buckets.remove(&oldest_client);
+                    }
+                }
+                rate
+            }
+        };
+
+        let is_accepted = tokens >= 1.0;
+        if is_accepted {
+            tokens -= 1.0;
+        }
+
+        buckets.insert(client.clone(), (tokens, now, *next_update));
+        updates.insert(*next_update, client);
+        *next_update += 1;
+
+        is_accepted
+    }
+
+    /// Returns the number of clients whose request rate is tracked.
+    pub fn num_clients(&self) -> usize {
+        self.buckets.lock().buckets.len()
+    }
+}
+
+/// The rate limiters of a limits config.
+struct RateLimiters {
+    config: RpcLimitsConfig,
+    ip_limiter: Option<RateLimiter<IpAddr>>,
+    token_limiter: Option<RateLimiter<String>>,
+    method_limiters: HashMap<String, RateLimiter<()>>,
+}
+
+impl RateLimiters {
+    fn new(config: RpcLimitsConfig) -> Self {
+        Self {
+            ip_limiter: config.requests_per_ip.map(RateLimiter::new),
+            token_limiter: config.requests_per_token.map(RateLimiter::new),
+            method_limiters: config
+                .requests_per_method
+                .iter()
+                .map(|(method, rate)| (method.clone(), RateLimiter::new(*rate)))
+                .collect(),
+            config,
+        }
+    }
+}
+
+/// The request limits of the RPC server, shared by its listeners and middlewares.
+pub struct RpcLimits {
+    /// The limits of the config, replaced by a configuration reload
+    limiters: RwLock<RateLimiters>,
+    /// Whether the limits are enforced on the connections, which is fixed once the server is started
+    limits_connections: bool,
+    concurrent_requests: AtomicUsize,
+}
+
+impl RpcLimits {
+    /// Creates the request limits defined by the given config.
+    pub fn new(config: RpcLimitsConfig) -> Self {
+        Self {
+            limits_connections: config.requests_per_ip.is_some() || config.max_concurrent_requests.is_some(),
+            limiters: RwLock::new(RateLimiters::new(config)),
+            concurrent_requests: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the config of the limits.
+    pub fn config(&self) -> RpcLimitsConfig {
+        self.limiters.read().config.clone()
+    }
+
+    /// Replaces the limits with the limits of the given config, resetting the request rates of the clients.
+    /// The limits per IP address and the concurrency limit are only enforced if the server started with one of
+    /// them, and the size limits set on the servers themselves are kept until they restart.
+    pub fn reload(&self, config: RpcLimitsConfig) {
+        *self.limiters.write() = RateLimiters::new(config);
+    }
+
+    /// Returns true if the limits are enforced on the connections, which then carry a single request.
+    pub fn limits_connections(&self) -> bool {
+        self.limits_connections
+    }
+
+    /// Returns the number of requests currently handled.
+    pub fn concurrent_requests(&self) -> usize {
+        self.concurrent_requests.load(Ordering::SeqCst)
+    }
+
+    /// Accepts a connection from the given IP address, unless it exceeds the limits.
+    /// The returned guard counts the connection as a request being handled until it is dropped.
+    pub fn accept_connection(limits: &Arc<Self>, ip: IpAddr) -> Option<RequestGuard> {
+        let concurrent_requests = limits.concurrent_requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let guard = RequestGuard { limits: limits.clone() };
+
+        let limiters = limits.limiters.read();
+        let is_accepted = match limiters.config.max_concurrent_requests {
+            Some(max_concurrent_requests) if concurrent_requests > max_concurrent_requests => false,
+            _ => limiters.ip_limiter.as_ref().map_or(true, |limiter| limiter.check(ip)),
+        };
+
+        if is_accepted {
+            Some(guard)
+        } else {
+            record_rejected_request();
+            None
+        }
+    }
+
+    /// Returns true if a request with the given valid `Authorization` header is accepted, and counts it.
+    pub fn check_token(&self, authorization: &str) -> bool {
+        let is_accepted = self
+            .limiters
+            .read()
+            .token_limiter
+            .as_ref()
+            .map_or(true, |limiter| limiter.check(authorization.to_string()));
+
+        if !is_accepted {
+            record_rejected_request();
+        }
+
+        is_accepted
+    }
+
+    /// Returns true if a call of the given method is accepted, and counts it.
+    pub fn check_method(&self, method: &str) -> bool {
+        let is_accepted = self
+            .limiters
+            .read()
+            .method_limiters
+            .get(method)
+            .map_or(true, |limiter| limiter.check(()));
+
+        if !is_accepted {
+            record_rejected_request();
+        }
+
+        is_accepted
+    }
+}
+
+fn record_rejected_request() {
+    METRICS.increment_counter(
+        "snarkos_rpc_rejected_requests",
+        "The number of RPC requests rejected by the request limits",
+        1,
+    );
+}
+
+/// Counts a request as being handled, until it is dropped.
+pub struct RequestGuard {
+    limits: Arc<RpcLimits>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.limits.concurrent_requests.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Starts a task accepting the connections within the limits at the given address, and
+/// forwarding them to the server listening at the upstream address.
+/// The rejected connections are answered with a `429 Too Many Requests` response.
+pub async fn start_limited_listener(
+    address: SocketAddr,
+    upstream_address: SocketAddr,
+    limits: Arc<RpcLimits>,
+) -> Result<(), RpcError> {
+    let mut listener = TcpListener::bind(address).await?;
+
+    tokio::task::spawn(async move {
+        loop {
+            let (mut stream, client_address) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(_) => continue,
+            };
+
+            let guard = match RpcLimits::accept_connection(&limits, client_address.ip()) {
+                Some(guard) => guard,
+                None => {
+                    tokio::task::spawn(async move {
+                        let _ = stream.write_all(TOO_MANY_REQUESTS_RESPONSE).await;
+                    });
+                    continue;
+                }
+            };
+
+            tokio::task::spawn(async move {
+                if let Ok(upstream) = TcpStream::connect(upstream_address).await {
+                    forward(stream, upstream).await;
+                }
+                drop(guard);
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns true if an `Authorization` header holds an API token or the credentials of the node.
+pub type CredentialsValidator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Rejects the HTTP requests exceeding the size limit or the rate limit of their API token or credentials.
+pub struct LimitsEndpoint {
+    limits: Arc<RpcLimits>,
+    validator: CredentialsValidator,
+}
+
+impl LimitsEndpoint {
+    /// Creates the middleware enforcing the given limits, with the credentials checked by the given validator.
+    pub fn new(limits: Arc<RpcLimits>, validator: CredentialsValidator) -> Self {
+        Self { limits, validator }
+    }
+}
+
+impl RequestMiddleware for LimitsEndpoint {
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        let content_length = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|content_length| content_length.to_str().ok())
+            .and_then(|content_length| content_length.parse::<usize>().ok());
+
+        if let (Some(content_length), Some(max_request_size)) = (content_length, self.limits.config().max_request_size)
+        {
+            if content_length > max_request_size {
+                record_rejected_request();
+
+                return Response {
+                    code: StatusCode::PAYLOAD_TOO_LARGE,
+                    content_type: HeaderValue::from_static(LIMITS_CONTENT_TYPE),
+                    content: format!("Requests are limited to {} bytes", max_request_size),
+                }
+                .into();
+            }
+        }
+
+        let authorization = request.headers().get(AUTHORIZATION).and_then(|auth| auth.to_str().ok());
+
+        // The invalid credentials are not tracked, as any number of them can be sent
+        if let Some(authorization) = authorization.filter(|authorization| (self.validator)(*authorization)) {
+            if !self.limits.check_token(authorization) {
+                return Response {
+                    code: StatusCode::TOO_MANY_REQUESTS,
+                    content_type: HeaderValue::from_static(LIMITS_CONTENT_TYPE),
+                    content: "Too many requests".to_string(),
+                }
+                .into();
+            }
+        }
+
+        request.into()
+    }
+}
+
+/// Rejects the calls exceeding the rate limit of their method.
+#[derive(Clone)]
+pub struct MethodLimits {
+    limits: Arc<RpcLimits>,
+}
+
+impl MethodLimits {
+    /// Creates the middleware enforcing the given limits.
+    pub fn new(limits: Arc<RpcLimits>) -> Self {
+        Self { limits }
+    }
+}
+
+impl<M: Metadata> Middleware<M> for MethodLimits {
+    type CallFuture = FutureOutput;
+    type Future = FutureResponse;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: future::Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        match call {
+            Call::MethodCall(ref method_call) if !self.limits.check_method(&method_call.method) => {
+                let error =
+                    RpcErrorCode::RateLimited.error(format!("Calls to {} are rate limited", method_call.method));
+
+                Either::A(Box::new(future::ok(Some(Output::from(
+                    Err(error),
+                    method_call.id.clone(),
+                    Some(Version::V2),
+                )))))
+            }
+            call => Either::B(next(call, meta)),
+        }
+    }
+}
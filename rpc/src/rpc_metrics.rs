@@ -0,0 +1,134 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for serving the node metrics in the Prometheus text format.
+
+use crate::{rpc_trait::RpcFunctions, RpcImpl};
+use snarkos_errors::rpc::RpcError;
+use snarkos_profiler::METRICS;
+
+use jsonrpc_http_server::{
+    hyper::{self, header::HeaderValue, Body, Method, StatusCode},
+    RequestMiddleware,
+    RequestMiddlewareAction,
+    Response,
+};
+use tokio::runtime::Runtime;
+
+/// The path of the metrics endpoint of the RPC server.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// The content type of the Prometheus text format.
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+impl RpcImpl {
+    /// Update the metrics registry with the current node state, and return the encoded metrics.
+    pub fn metrics(&self) -> Result<String, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+
+        METRICS.set_gauge(
+            "snarkos_block_height",
+            "The height of the latest canon block",
+            f64::from(self.storage.get_latest_block_height()),
+        );
+        METRICS.set_gauge(
+            "snarkos_peers",
+            "The number of connected peers",
+            self.get_connection_count()? as f64,
+        );
+        METRICS.set_gauge(
+            "snarkos_syncing",
+            "Whether the node is syncing blocks",
+            if self.get_node_info()?.is_syncing { 1.0 } else { 0.0 },
+        );
+
+        {
+            let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+            METRICS.set_gauge(
+                "snarkos_memory_pool_transactions",
+                "The number of transactions in the memory pool",
+                memory_pool.transactions.len() as f64,
+            );
+            METRICS.set_gauge(
+                "snarkos_memory_pool_size_bytes",
+                "The size of the transactions in the memory pool",
+                memory_pool.total_size as f64,
+            );
+        }
+
+        let storage_metrics = self.get_storage_metrics()?;
+
+        METRICS.set_gauge(
+            "snarkos_storage_disk_size_bytes",
+            "The size of the storage columns on disk",
+            storage_metrics.disk_size as f64,
+        );
+        METRICS.set_gauge(
+            "snarkos_storage_archive_size_bytes",
+            "The size of the block archive files",
+            storage_metrics.archive_size as f64,
+        );
+        METRICS.set_gauge(
+            "snarkos_storage_reclaimed_size_bytes",
+            "The size of the data removed by garbage collections",
+            storage_metrics.garbage_collection.reclaimed_size as f64,
+        );
+        if let Some(cache_hit_rate) = storage_metrics.cache_hit_rate {
+            METRICS.set_gauge(
+                "snarkos_storage_cache_hit_rate",
+                "The ratio of block cache hits to block cache lookups",
+                cache_hit_rate,
+            );
+        }
+
+        Ok(METRICS.encode())
+    }
+}
+
+/// Serves the node metrics at `METRICS_PATH`, and passes the other requests to the RPC server.
+pub struct MetricsEndpoint {
+    rpc: RpcImpl,
+}
+
+impl MetricsEndpoint {
+    /// Creates the metrics endpoint of the node served by the given RPC endpoints.
+    pub fn new(rpc: RpcImpl) -> Self {
+        Self { rpc }
+    }
+}
+
+impl RequestMiddleware for MetricsEndpoint {
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+            return request.into();
+        }
+
+        match self.rpc.metrics() {
+            Ok(metrics) => Response {
+                code: StatusCode::OK,
+                content_type: HeaderValue::from_static(METRICS_CONTENT_TYPE),
+                content: metrics,
+            },
+            Err(error) => Response {
+                code: StatusCode::INTERNAL_SERVER_ERROR,
+                content_type: HeaderValue::from_static(METRICS_CONTENT_TYPE),
+                content: error.to_string(),
+            },
+        }
+        .into()
+    }
+}
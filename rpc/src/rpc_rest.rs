@@ -0,0 +1,304 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for serving common read queries over a REST API, backed by the public RPC endpoints.
+
+use crate::{rpc_trait::RpcFunctions, rpc_types::*, RpcImpl};
+use snarkos_errors::rpc::{RpcError, RpcErrorCode};
+
+use jsonrpc_http_server::{
+    hyper::{
+        self,
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        Body,
+        Method,
+        StatusCode,
+    },
+    jsonrpc_core::futures::future,
+    RequestMiddleware,
+    RequestMiddlewareAction,
+};
+use serde::Serialize;
+
+/// The path of the REST endpoint returning a canon block, followed by the block height.
+pub const REST_BLOCK_PATH: &str = "/block/";
+
+/// The path of the REST endpoint returning a transaction, followed by the transaction id.
+pub const REST_TRANSACTION_PATH: &str = "/transaction/";
+
+/// The path of the REST endpoint returning a page of the memory pool transaction ids.
+pub const REST_MEMORY_POOL_PATH: &str = "/mempool";
+
+/// The path of the REST endpoint returning the status of the node.
+pub const REST_STATUS_PATH: &str = "/status";
+
+/// The path of the REST endpoint returning the health of the node, for load balancers and probes.
+pub const REST_HEALTH_PATH: &str = "/health";
+
+/// The number of confirmations after which a block is not expected to leave the canon chain.
+pub const STABLE_BLOCK_CONFIRMATIONS: u32 = 100;
+
+/// The caching policy of the blocks and transactions with at least `STABLE_BLOCK_CONFIRMATIONS`.
+const STABLE_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// The caching policy of the recent blocks and transactions, which may still be reorganized.
+const RECENT_CACHE_CONTROL: &str = "public, max-age=10";
+
+/// The caching policy of the responses describing the current state of the node.
+const NO_CACHE_CONTROL: &str = "no-cache";
+
+/// The caching policy of the error responses.
+const NO_STORE_CONTROL: &str = "no-store";
+
+/// A response of the REST API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestResponse {
+    /// The status code of the response
+    pub status: StatusCode,
+    /// The JSON body of the response
+    pub body: String,
+    /// The weak entity tag identifying the resource, if it is immutable
+    pub etag: Option<String>,
+    /// The value of the `Cache-Control` header
+    pub cache_control: &'static str,
+}
+
+impl RestResponse {
+    fn ok<T: Serialize>(value: &T, etag: Option<String>, cache_control: &'static str) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self {
+                status: StatusCode::OK,
+                body,
+                etag,
+                cache_control,
+            },
+            Err(error) => Self::error(RpcErrorCode::InternalError, error.to_string()),
+        }
+    }
+
+    fn error(code: RpcErrorCode, message: String) -> Self {
+        let status = match code {
+            RpcErrorCode::InvalidParams => StatusCode::BAD_REQUEST,
+            RpcErrorCode::NotFound => StatusCode::NOT_FOUND,
+            RpcErrorCode::NodeSyncing | RpcErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self {
+            status,
+            body: serde_json::json!({ "error": message, "kind": code.kind() }).to_string(),
+            etag: None,
+            cache_control: NO_STORE_CONTROL,
+        }
+    }
+}
+
+impl From<RpcError> for RestResponse {
+    fn from(error: RpcError) -> Self {
+        Self::error(error.code(), error.to_string())
+    }
+}
+
+/// Returns the caching policy of a block or transaction with the given number of confirmations.
+fn block_cache_control(confirmations: u32) -> &'static str {
+    if confirmations >= STABLE_BLOCK_CONFIRMATIONS {
+        STABLE_CACHE_CONTROL
+    } else {
+        RECENT_CACHE_CONTROL
+    }
+}
+
+/// Returns the value of a parameter in a URI query string.
+fn query_parameter<'a>(query: Option<&'a str>, name: &str) -> Option<&'a str> {
+    query?.split('&').find_map(|pair| {
+        let mut pair = pair.splitn(2, '=');
+
+        match (pair.next(), pair.next()) {
+            (Some(key), Some(value)) if key == name => Some(value),
+            _ => None,
+        }
+    })
+}
+
+/// Parses an optional numeric parameter of a URI query string.
+fn parse_query_parameter(query: Option<&str>, name: &str) -> Result<Option<usize>, RestResponse> {
+    match query_parameter(query, name) {
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            RestResponse::error(
+                RpcErrorCode::InvalidParams,
+                format!("invalid {} parameter: {}", name, value),
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+impl RpcImpl {
+    /// Returns the response of the REST API to a GET request at the given path and query,
+    /// or `None` if the path is not a REST endpoint.
+    pub fn rest(&self, path: &str, query: Option<&str>) -> Option<RestResponse> {
+        let response = if path.starts_with(REST_BLOCK_PATH) {
+            self.rest_block(&path[REST_BLOCK_PATH.len()..])
+        } else if path.starts_with(REST_TRANSACTION_PATH) {
+            self.rest_transaction(&path[REST_TRANSACTION_PATH.len()..])
+        } else if path == REST_MEMORY_POOL_PATH {
+            self.rest_memory_pool(query)
+        } else if path == REST_STATUS_PATH {
+            self.rest_status()
+        } else if path == REST_HEALTH_PATH {
+            Ok(self.rest_health())
+        } else {
+            return None;
+        };
+
+        Some(response.unwrap_or_else(|response| response))
+    }
+
+    fn rest_block(&self, block_height: &str) -> Result<RestResponse, RestResponse> {
+        let block_height: u32 = block_height.parse().map_err(|_| {
+            RestResponse::error(
+                RpcErrorCode::InvalidParams,
+                format!("invalid block height: {}", block_height),
+            )
+        })?;
+
+        let block = self.get_blocks(block_height, block_height)?.pop().ok_or_else(|| {
+            RestResponse::error(RpcErrorCode::NotFound, format!("no block at height {}", block_height))
+        })?;
+
+        let etag = format!("W/\"{}\"", block.hash);
+        let cache_control = block_cache_control(block.confirmations);
+
+        Ok(RestResponse::ok(&block, Some(etag), cache_control))
+    }
+
+    fn rest_transaction(&self, transaction_id: &str) -> Result<RestResponse, RestResponse> {
+        let transaction = self.get_transaction_info(transaction_id.to_string(), None)?;
+
+        // Transactions without a block number are not cached for long
+        let confirmations = match transaction.transaction_metadata.block_number {
+            Some(block_number) => self.storage.get_latest_block_height().saturating_sub(block_number),
+            None => 0,
+        };
+
+        let etag = format!("W/\"{}\"", transaction.txid);
+
+        Ok(RestResponse::ok(
+            &transaction,
+            Some(etag),
+            block_cache_control(confirmations),
+        ))
+    }
+
+    fn rest_memory_pool(&self, query: Option<&str>) -> Result<RestResponse, RestResponse> {
+        let limit = parse_query_parameter(query, "limit")?;
+        let offset = parse_query_parameter(query, "offset")?;
+
+        let memory_pool = self.get_memory_pool(limit, offset)?;
+
+        Ok(RestResponse::ok(&memory_pool, None, NO_CACHE_CONTROL))
+    }
+
+    fn rest_status(&self) -> Result<RestResponse, RestResponse> {
+        let node_info = self.get_node_info()?;
+        let memory_pool_info = self.get_memory_pool_info()?;
+
+        let status = NodeStatus {
+            block_height: self.get_block_count()?,
+            best_block_hash: self.get_best_block_hash()?,
+            peers: self.get_connection_count()?,
+            memory_pool_size: memory_pool_info.size,
+            is_miner: node_info.is_miner,
+            is_syncing: node_info.is_syncing,
+        };
+
+        Ok(RestResponse::ok(&status, None, NO_CACHE_CONTROL))
+    }
+
+    /// Returns a `200 OK` response if the node is synced and its storage is healthy,
+    /// or a `503 Service Unavailable` response otherwise.
+    fn rest_health(&self) -> RestResponse {
+        let storage = self.storage_health();
+        let is_syncing = self.is_syncing();
+
+        let health = NodeHealth {
+            is_healthy: storage.is_healthy && !is_syncing,
+            is_syncing,
+            storage,
+        };
+
+        let mut response = RestResponse::ok(&health, None, NO_STORE_CONTROL);
+        if !health.is_healthy {
+            response.status = StatusCode::SERVICE_UNAVAILABLE;
+        }
+
+        response
+    }
+}
+
+/// Serves the REST API, and passes the other requests to the RPC server.
+pub struct RestEndpoint {
+    rpc: RpcImpl,
+}
+
+impl RestEndpoint {
+    /// Creates the REST API of the node served by the given RPC endpoints.
+    pub fn new(rpc: RpcImpl) -> Self {
+        Self { rpc }
+    }
+}
+
+impl RequestMiddleware for RestEndpoint {
+    fn on_request(&self, request: hyper::Request<Body>) -> RequestMiddlewareAction {
+        if request.method() != Method::GET {
+            return request.into();
+        }
+
+        let response = match self.rpc.rest(request.uri().path(), request.uri().query()) {
+            Some(response) => response,
+            None => return request.into(),
+        };
+
+        // Clients holding the current version of the resource do not need it sent again
+        let is_not_modified = match (&response.etag, request.headers().get(IF_NONE_MATCH)) {
+            (Some(etag), Some(if_none_match)) => if_none_match.as_bytes() == etag.as_bytes(),
+            _ => false,
+        };
+
+        let mut builder = hyper::Response::builder();
+        builder
+            .header(CONTENT_TYPE, "application/json")
+            .header(CACHE_CONTROL, response.cache_control);
+
+        if let Some(etag) = &response.etag {
+            builder.header(ETAG, etag.as_str());
+        }
+
+        let http_response = if is_not_modified {
+            builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+        } else {
+            builder.status(response.status).body(Body::from(response.body))
+        };
+
+        match http_response {
+            Ok(http_response) => RequestMiddlewareAction::Respond {
+                should_validate_hosts: true,
+                response: Box::new(future::ok(http_response)),
+            },
+            Err(_) => request.into(),
+        }
+    }
+}
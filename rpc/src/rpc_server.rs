@@ -17,29 +17,146 @@
 //! Logic for instantiating the RPC server.
 
 use crate::{
+    rpc_compression::{CompressionEndpoint, RpcCompressionConfig},
+    rpc_cors::{CorsEndpoint, RpcCorsConfig},
+    rpc_graphql::GraphQLEndpoint,
+    rpc_ipc::{start_ipc_server, RpcIpcConfig},
+    rpc_limits::{start_limited_listener, LimitsEndpoint, MethodLimits, RpcLimits, RpcLimitsConfig},
+    rpc_metrics::MetricsEndpoint,
+    rpc_rest::RestEndpoint,
+    rpc_subscriptions::{RpcSubscriptions, RpcSubscriptionsImpl},
+    rpc_tls::{start_tls_listener, RpcTlsConfig},
+    rpc_tokens::TokenRotations,
     rpc_trait::RpcFunctions,
-    rpc_types::{Meta, RpcCredentials},
+    rpc_types::{Meta, RpcCredentials, RpcScope, RpcToken, SubscriptionMeta},
+    rpc_versions::{RpcVersion, RpcVersions, RpcVersionsConfig},
+    rpc_webhooks::{RpcWebhook, RpcWebhookConfig},
+    ConfigReloader,
+    LogFilterReloader,
     RpcImpl,
 };
-use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
+use snarkos_consensus::{BlockRequests, ConsensusParameters, MemoryPool, MerkleTreeLedger};
 use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
 };
+use snarkos_errors::rpc::RpcErrorCode;
 use snarkos_network::{external::SyncHandler, internal::context::Context};
 
-use jsonrpc_http_server::{cors::AccessControlAllowHeaders, hyper, ServerBuilder};
-use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use jsonrpc_core::{
+    futures::future::{self, Either},
+    FutureOutput,
+    FutureResponse,
+    MetaIoHandler,
+    Metadata,
+    Middleware,
+    Request,
+    Response,
+    Version,
+};
+use jsonrpc_http_server::{
+    cors::AccessControlAllowHeaders,
+    hyper,
+    RequestMiddleware,
+    RequestMiddlewareAction,
+    ServerBuilder,
+};
+use jsonrpc_pubsub::{PubSubHandler, Session};
+use jsonrpc_ws_server::RequestContext;
+use parking_lot::RwLock;
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::sync::Mutex;
 
-/// Starts a local JSON-RPC HTTP server at rpc_port in a new thread.
+/// The maximum number of calls in a batch request.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Rejects the batch requests of more than `MAX_BATCH_SIZE` calls.
+/// The calls of a smaller batch are handled independently, and answered in a batch response.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BatchLimit;
+
+impl<M: Metadata> Middleware<M> for BatchLimit {
+    type CallFuture = FutureOutput;
+    type Future = FutureResponse;
+
+    fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+    where
+        F: Fn(Request, M) -> X + Send + Sync,
+        X: future::Future<Item = Option<Response>, Error = ()> + Send + 'static,
+    {
+        match request {
+            Request::Batch(ref calls) if calls.len() > MAX_BATCH_SIZE => {
+                let error = RpcErrorCode::InvalidRequest
+                    .error(format!("Batch requests are limited to {} calls", MAX_BATCH_SIZE));
+
+                Either::A(Box::new(future::ok(Some(Response::from(error, Some(Version::V2))))))
+            }
+            request => Either::B(next(request, meta)),
+        }
+    }
+}
+
+/// The settings of a running RPC server replaced by a configuration reload.
+#[derive(Clone)]
+pub struct RpcReloadHandle {
+    tokens: Arc<RwLock<HashMap<String, RpcScope>>>,
+    token_rotations: Arc<RwLock<TokenRotations>>,
+    limits: Arc<RpcLimits>,
+}
+
+impl RpcReloadHandle {
+    /// Replaces the API tokens of the server, keeping the rotated tokens revoked and the tokens they were rotated to.
+    pub fn reload_tokens(&self, tokens: Vec<RpcToken>) {
+        *self.tokens.write() = self.token_rotations.read().tokens(tokens);
+    }
+
+    /// Replaces the request limits of the server.
+    pub fn reload_limits(&self, limits: RpcLimitsConfig) {
+        self.limits.reload(limits);
+    }
+}
+
+/// Returns the handler of the JSON-RPC calls to the public and private endpoints.
+fn rpc_io_handler(
+    rpc_impl: &RpcImpl,
+    versions: &RpcVersions,
+    limits: &Arc<RpcLimits>,
+) -> MetaIoHandler<Meta, (BatchLimit, RpcVersions, MethodLimits)> {
+    let mut io = MetaIoHandler::with_middleware((BatchLimit, versions.clone(), MethodLimits::new(limits.clone())));
+
+    rpc_impl.add_protected(&mut io);
+    io.extend_with(rpc_impl.clone().to_delegate());
+
+    io
+}
+
+/// Starts a local JSON-RPC HTTP server at rpc_port in a new thread, serving the node metrics
+/// at `METRICS_PATH` and the REST API, and a JSON-RPC WebSocket
+/// server serving the public endpoints and the subscriptions at ws_port, if any.
+/// If a TLS config is given, the servers listen on the loopback interface and the connections
+/// to the given ports are accepted by TLS listeners forwarding the decrypted streams to them.
+/// If a CORS config is given, the HTTP server answers the cross-origin requests of the allowed origins.
+/// If a compression config is given, the HTTP responses are compressed for the clients accepting
+/// one of its encodings, and streamed in chunks.
+/// If the limits apply to the IP addresses or the concurrency of the requests, the connections are
+/// likewise accepted by listeners enforcing them, and the HTTP connections carry a single request.
+/// If graphql is set, the HTTP server also serves the chain data over GraphQL at `GRAPHQL_PATH`.
+/// The given webhooks are notified of the chain events they are configured for.
+/// The calls are served in the API version given by their method prefix or their HTTP path, or in the
+/// default version of the versions config.
+/// If an IPC config is given, the JSON-RPC endpoints are also served over a Unix domain socket,
+/// or only over the socket if the config disables the ports.
+/// The returned handle replaces the API tokens and the request limits of the running server, and the
+/// `reloadconfig` endpoint reloads the configuration of the node with the given reloader, if any.
 /// Rpc failures will error on the thread level but not affect the main network server.
 /// This may be changed in the future to give the node more control of the rpc server.
 #[allow(clippy::too_many_arguments)]
 pub async fn start_rpc_server(
     rpc_port: u16,
+    ws_port: Option<u16>,
     secondary_storage: Arc<MerkleTreeLedger>,
     storage_path: PathBuf,
+    primary_storage: Arc<MerkleTreeLedger>,
     parameters: PublicParameters<Components>,
     server_context: Arc<Context>,
     consensus: ConsensusParameters,
@@ -47,45 +164,209 @@ pub async fn start_rpc_server(
     sync_handler_lock: Arc<Mutex<SyncHandler>>,
     username: Option<String>,
     password: Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
+    tokens: Vec<RpcToken>,
+    tls: Option<RpcTlsConfig>,
+    cors: Option<RpcCorsConfig>,
+    compression: Option<RpcCompressionConfig>,
+    ipc: Option<RpcIpcConfig>,
+    graphql: bool,
+    webhooks: Vec<RpcWebhookConfig>,
+    versions: RpcVersionsConfig,
+    limits: RpcLimitsConfig,
+    log_filter: Option<LogFilterReloader>,
+    block_requests: Option<Arc<BlockRequests>>,
+    config_reloader: Option<ConfigReloader>,
+) -> Result<RpcReloadHandle, Box<dyn std::error::Error>> {
     let rpc_server: SocketAddr = format!("0.0.0.0:{}", rpc_port).parse()?;
 
+    let tls_config = match tls {
+        Some(tls) => Some(tls.server_config()?),
+        None => None,
+    };
+
+    let limits = Arc::new(RpcLimits::new(limits));
+    let versions = RpcVersions::new(versions);
+    let connection_limits = if limits.limits_connections() {
+        Some(limits.clone())
+    } else {
+        None
+    };
+
+    // The servers behind a TLS listener or a limited listener are only reachable through it
+    let server_address = |address: SocketAddr| match (&tls_config, &connection_limits) {
+        (None, None) => address,
+        _ => SocketAddr::from(([127, 0, 0, 1], 0)),
+    };
+
+    // Starts the listener forwarding the connections to the server, if any
+    let start_listener = |address: SocketAddr, upstream_address: SocketAddr| {
+        let tls_config = tls_config.clone();
+        let connection_limits = connection_limits.clone();
+
+        async move {
+            match (tls_config, connection_limits) {
+                (Some(tls_config), connection_limits) => {
+                    start_tls_listener(address, upstream_address, tls_config, connection_limits).await
+                }
+                (None, Some(connection_limits)) => {
+                    start_limited_listener(address, upstream_address, connection_limits).await
+                }
+                (None, None) => Ok(()),
+            }
+        }
+    };
+
     let credentials = match (username, password) {
         (Some(username), Some(password)) => Some(RpcCredentials { username, password }),
         _ => None,
     };
 
-    let rpc_impl = RpcImpl::new(
+    let mut rpc_impl = RpcImpl::new(
         secondary_storage,
         storage_path,
+        primary_storage,
         parameters,
         server_context,
         consensus,
         memory_pool_lock,
         sync_handler_lock,
         credentials,
+        tokens,
+        log_filter,
+    )?;
+    if let Some(block_requests) = block_requests {
+        rpc_impl = rpc_impl.with_block_requests(block_requests);
+    }
+    if let Some(config_reloader) = config_reloader {
+        rpc_impl = rpc_impl.with_config_reloader(config_reloader);
+    }
+
+    let reload_handle = RpcReloadHandle {
+        tokens: rpc_impl.tokens.clone(),
+        token_rotations: rpc_impl.token_rotations.clone(),
+        limits: limits.clone(),
+    };
+
+    for webhook in webhooks {
+        RpcWebhook::new(rpc_impl.clone(), webhook)?.start()?;
+    }
+
+    if let Some(ipc) = &ipc {
+        start_ipc_server(
+            ipc,
+            rpc_io_handler(&rpc_impl, &versions, &limits),
+            limits.config().max_request_size,
+        )?;
+
+        if ipc.ipc_only {
+            return Ok(reload_handle);
+        }
+    }
+
+    if let Some(ws_port) = ws_port {
+        let ws_server: SocketAddr = format!("0.0.0.0:{}", ws_port).parse()?;
+
+        let subscriptions = RpcSubscriptionsImpl::new(rpc_impl.clone());
+        subscriptions.start_notifications()?;
+
+        let mut ws_io = PubSubHandler::new(jsonrpc_core::MetaIoHandler::with_middleware((
+            BatchLimit,
+            versions.clone(),
+            MethodLimits::new(limits.clone()),
+        )));
+
+        ws_io.extend_with(rpc_impl.clone().to_delegate());
+        ws_io.extend_with(subscriptions.to_delegate());
+
+        let mut ws_builder =
+            jsonrpc_ws_server::ServerBuilder::with_meta_extractor(ws_io, |context: &RequestContext| SubscriptionMeta {
+                session: Some(Arc::new(Session::new(context.sender()))),
+            });
+
+        if let Some(max_request_size) = limits.config().max_request_size {
+            ws_builder = ws_builder.max_payload(max_request_size);
+        }
+
+        let server = ws_builder.start(&server_address(ws_server))?;
+
+        start_listener(ws_server, *server.addr()).await?;
+
+        tokio::task::spawn(async move {
+            let _ = server.wait();
+        });
+    }
+
+    let io = rpc_io_handler(&rpc_impl, &versions, &limits);
+    let compression_io = rpc_io_handler(&rpc_impl, &versions, &limits);
+
+    let credentials_rpc = rpc_impl.clone();
+    let limits_endpoint = LimitsEndpoint::new(
+        limits.clone(),
+        Arc::new(move |authorization: &str| credentials_rpc.granted_scope(authorization).is_some()),
     );
-    let mut io = jsonrpc_core::MetaIoHandler::default();
+    let metrics_endpoint = MetricsEndpoint::new(rpc_impl.clone());
+    let graphql_endpoint = if graphql {
+        Some(GraphQLEndpoint::new(rpc_impl.clone()))
+    } else {
+        None
+    };
+    let rest_endpoint = RestEndpoint::new(rpc_impl);
 
-    rpc_impl.add_protected(&mut io);
-    io.extend_with(rpc_impl.to_delegate());
+    let endpoints = move |request: hyper::Request<hyper::Body>| match limits_endpoint.on_request(request) {
+        RequestMiddlewareAction::Proceed { request, .. } => match metrics_endpoint.on_request(request) {
+            RequestMiddlewareAction::Proceed { request, .. } => match rest_endpoint.on_request(request) {
+                RequestMiddlewareAction::Proceed { request, .. } => match &graphql_endpoint {
+                    Some(graphql_endpoint) => graphql_endpoint.on_request(request),
+                    None => request.into(),
+                },
+                action => action,
+            },
+            action => action,
+        },
+        action => action,
+    };
 
-    let server = ServerBuilder::new(io)
-        .cors_allow_headers(AccessControlAllowHeaders::Any)
+    let endpoints = CompressionEndpoint::new(compression, limits.config().max_request_size, compression_io, endpoints);
+
+    let builder = match cors {
+        Some(cors) => ServerBuilder::new(io)
+            .cors(cors.server_origins())
+            .cors_allow_headers(cors.server_headers())
+            .cors_max_age(cors.max_age)
+            .request_middleware(CorsEndpoint::new(cors, endpoints)),
+        None => ServerBuilder::new(io)
+            .cors_allow_headers(AccessControlAllowHeaders::Any)
+            .request_middleware(endpoints),
+    };
+
+    let mut builder = builder
         .meta_extractor(|req: &hyper::Request<hyper::Body>| {
             let auth = req
                 .headers()
                 .get(hyper::header::AUTHORIZATION)
                 .map(|h| h.to_str().unwrap_or("").to_owned());
 
-            Meta { auth }
+            Meta {
+                auth,
+                is_local: false,
+                api_version: RpcVersion::from_path(req.uri().path()),
+            }
         })
         .threads(1)
-        .start_http(&rpc_server)?;
+        // The limited listener counts the connections as requests
+        .keep_alive(connection_limits.is_none());
+
+    if let Some(max_request_size) = limits.config().max_request_size {
+        builder = builder.max_request_body_size(max_request_size);
+    }
+
+    let server = builder.start_http(&server_address(rpc_server))?;
+
+    start_listener(rpc_server, *server.address()).await?;
 
     tokio::task::spawn(async move {
         server.wait();
     });
 
-    Ok(())
+    Ok(reload_handle)
 }
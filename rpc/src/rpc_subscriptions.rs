@@ -0,0 +1,421 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Definition and implementation of the WebSocket subscriptions.
+//!
+//! The subscribers are notified of new canon blocks, of new memory pool transactions,
+//! of the records of new canon blocks that their account view key can decrypt,
+//! and of the state changes of the asynchronous jobs submitted by the subscriber.
+
+use crate::{rpc_trait::RpcFunctions, rpc_types::*, RpcImpl};
+use snarkos_consensus::ConsensusParameters;
+use snarkos_dpc::base_dpc::instantiated::Components;
+use snarkos_errors::rpc::RpcError;
+use snarkos_objects::{AccountViewKey, BlockHeaderHash};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use jsonrpc_core::{futures::Future, Result as JsonRPCResult};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{
+    typed::{Sink, Subscriber},
+    SubscriptionId,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tokio::runtime::Runtime;
+
+/// The interval between two checks of the node state for notifications.
+pub(crate) const NOTIFICATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of notified canon blocks whose hashes are kept to detect forks.
+pub(crate) const NOTIFIED_BLOCK_HASHES: u32 = 100;
+
+/// Definition of the WebSocket subscriptions.
+#[rpc(server)]
+pub trait RpcSubscriptions {
+    type Metadata;
+
+    /// Subscribe to the new canon blocks, which are notified as `getblock` responses.
+    /// The blocks replacing the blocks reverted by a fork are notified again.
+    #[pubsub(subscription = "blocks", subscribe, name = "subscribeBlocks")]
+    fn subscribe_blocks(&self, meta: Self::Metadata, subscriber: Subscriber<BlockInfo>);
+
+    /// Cancel a subscription to the new canon blocks.
+    #[pubsub(subscription = "blocks", unsubscribe, name = "unsubscribeBlocks")]
+    fn unsubscribe_blocks(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool>;
+
+    /// Subscribe to the transactions entering the memory pool, which are notified as
+    /// `decoderawtransaction` responses.
+    #[pubsub(subscription = "transactions", subscribe, name = "subscribeTransactions")]
+    fn subscribe_transactions(&self, meta: Self::Metadata, subscriber: Subscriber<TransactionInfo>);
+
+    /// Cancel a subscription to the memory pool transactions.
+    #[pubsub(subscription = "transactions", unsubscribe, name = "unsubscribeTransactions")]
+    fn unsubscribe_transactions(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool>;
+
+    /// Subscribe to the records of the new canon blocks that the account view key can decrypt.
+    #[pubsub(subscription = "records", subscribe, name = "subscribeRecords")]
+    fn subscribe_records(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RecordNotification>,
+        account_view_key: String,
+    );
+
+    /// Cancel a subscription to the records of an account.
+    #[pubsub(subscription = "records", unsubscribe, name = "unsubscribeRecords")]
+    fn unsubscribe_records(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool>;
+
+    /// Subscribe to the state changes of the asynchronous jobs submitted by the client authenticated by the given
+    /// authentication header, with the scope required by `getjobstatus`, which are notified as `getjobstatus` responses.
+    /// The results of the completed jobs are fetched with `getjobresult`.
+    #[pubsub(subscription = "jobs", subscribe, name = "subscribeJobs")]
+    fn subscribe_jobs(&self, meta: Self::Metadata, subscriber: Subscriber<JobInfo>, authorization: String);
+
+    /// Cancel a subscription to the asynchronous jobs.
+    #[pubsub(subscription = "jobs", unsubscribe, name = "unsubscribeJobs")]
+    fn unsubscribe_jobs(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool>;
+}
+
+/// The sinks of the active subscriptions.
+#[derive(Default)]
+struct Subscribers {
+    blocks: HashMap<SubscriptionId, Sink<BlockInfo>>,
+    transactions: HashMap<SubscriptionId, Sink<TransactionInfo>>,
+    records: HashMap<SubscriptionId, (AccountViewKey<Components>, Sink<RecordNotification>)>,
+    jobs: HashMap<SubscriptionId, (Option<String>, Sink<JobInfo>)>,
+}
+
+/// The node state already notified to the subscribers.
+pub struct NotificationState {
+    /// The hashes of the latest notified canon blocks, by height
+    block_hashes: BTreeMap<u32, String>,
+    /// The ids of the notified transactions still in the memory pool
+    transaction_ids: HashSet<Vec<u8>>,
+    /// The notified states of the jobs, by id
+    job_statuses: HashMap<String, JobStatus>,
+    /// The runtime locking the memory pool, created once and reused by every check
+    runtime: Runtime,
+}
+
+/// Implements the WebSocket subscriptions of a node.
+/// The node state is read through the public RPC endpoints.
+#[derive(Clone)]
+pub struct RpcSubscriptionsImpl {
+    rpc: RpcImpl,
+    subscribers: Arc<Mutex<Subscribers>>,
+    next_subscription_id: Arc<AtomicU64>,
+}
+
+impl RpcSubscriptionsImpl {
+    /// Creates the subscriptions of the node served by the given RPC endpoints.
+    pub fn new(rpc: RpcImpl) -> Self {
+        Self {
+            rpc,
+            subscribers: Default::default(),
+            next_subscription_id: Default::default(),
+        }
+    }
+
+    /// Starts a thread that notifies the subscribers of the changes to the node state.
+    pub fn start_notifications(&self) -> Result<(), RpcError> {
+        let subscriptions = self.clone();
+        let mut state = subscriptions.notification_state()?;
+
+        thread::spawn(move || loop {
+            // A failed check is retried with the next one, as the notified state is only updated on success.
+            let _ = subscriptions.notify(&mut state);
+
+            thread::sleep(NOTIFICATION_INTERVAL);
+        });
+
+        Ok(())
+    }
+
+    /// Returns the current node state, so only the later changes are notified.
+    pub fn notification_state(&self) -> Result<NotificationState, RpcError> {
+        let latest_block_height = self.rpc.get_block_count()? - 1;
+
+        let mut block_hashes = BTreeMap::new();
+        block_hashes.insert(latest_block_height, self.rpc.get_block_hash(latest_block_height)?);
+
+        let mut runtime = Runtime::new()?;
+        let transaction_ids = runtime
+            .block_on(self.rpc.memory_pool_lock.lock())
+            .transactions
+            .keys()
+            .cloned()
+            .collect();
+
+        let job_statuses = self
+            .rpc
+            .jobs
+            .infos()
+            .into_iter()
+            .map(|job| (job.id, job.status))
+            .collect();
+
+        Ok(NotificationState {
+            block_hashes,
+            transaction_ids,
+            job_statuses,
+            runtime,
+        })
+    }
+
+    /// Notify the subscribers of the changes to the node state since the given state.
+    pub fn notify(&self, state: &mut NotificationState) -> Result<(), RpcError> {
+        self.notify_blocks(&mut state.block_hashes)?;
+        self.notify_transactions(&mut state.transaction_ids, &mut state.runtime)?;
+        self.notify_jobs(&mut state.job_statuses);
+
+        Ok(())
+    }
+
+    fn notify_blocks(&self, block_hashes: &mut BTreeMap<u32, String>) -> Result<(), RpcError> {
+        let latest_block_height = self.rpc.get_block_count()? - 1;
+        let block_height = latest_canon_block_height(&self.rpc, block_hashes, latest_block_height)?;
+
+        block_hashes.split_off(&(block_height + 1));
+
+        for block_height in (block_height + 1)..=latest_block_height {
+            let block_hash = self.rpc.get_block_hash(block_height)?;
+
+            let mut subscribers = self.subscribers.lock();
+
+            if !subscribers.blocks.is_empty() {
+                let block_info = self.rpc.get_block(block_hash.clone())?;
+                notify_all(&mut subscribers.blocks, &block_info);
+            }
+
+            if !subscribers.records.is_empty() {
+                let block = self
+                    .rpc
+                    .storage
+                    .get_block(&BlockHeaderHash::new(hex::decode(&block_hash)?))?;
+
+                let mut closed_subscriptions = vec![];
+                for (subscription_id, (view_key, sink)) in subscribers.records.iter() {
+                    let records = ConsensusParameters::decrypt_block_records(&self.rpc.parameters, view_key, &block)?;
+                    if records.is_empty() {
+                        continue;
+                    }
+
+                    let notification = RecordNotification {
+                        block_height,
+                        block_hash: block_hash.clone(),
+                        records: records
+                            .iter()
+                            .map(|record| Ok(hex::encode(to_bytes![record]?)))
+                            .collect::<Result<_, RpcError>>()?,
+                    };
+
+                    if sink.notify(Ok(notification)).wait().is_err() {
+                        closed_subscriptions.push(subscription_id.clone());
+                    }
+                }
+
+                for subscription_id in closed_subscriptions {
+                    subscribers.records.remove(&subscription_id);
+                }
+            }
+
+            block_hashes.insert(block_height, block_hash);
+        }
+
+        // Keep the hashes of the latest blocks only
+        *block_hashes = block_hashes.split_off(&latest_block_height.saturating_sub(NOTIFIED_BLOCK_HASHES));
+
+        Ok(())
+    }
+
+    fn notify_transactions(
+        &self,
+        transaction_ids: &mut HashSet<Vec<u8>>,
+        runtime: &mut Runtime,
+    ) -> Result<(), RpcError> {
+        let new_transactions = {
+            let memory_pool = runtime.block_on(self.rpc.memory_pool_lock.lock());
+
+            // Forget the transactions that left the memory pool
+            transaction_ids.retain(|transaction_id| memory_pool.transactions.contains_key(transaction_id));
+
+            memory_pool
+                .transactions
+                .iter()
+                .filter(|(transaction_id, _)| !transaction_ids.contains(*transaction_id))
+                .map(|(transaction_id, entry)| (transaction_id.clone(), entry.transaction.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        for (transaction_id, transaction) in new_transactions {
+            let mut subscribers = self.subscribers.lock();
+
+            if !subscribers.transactions.is_empty() {
+                let transaction_info = self
+                    .rpc
+                    .decode_raw_transaction(hex::encode(to_bytes![transaction]?), None)?;
+                notify_all(&mut subscribers.transactions, &transaction_info);
+            }
+
+            transaction_ids.insert(transaction_id);
+        }
+
+        Ok(())
+    }
+
+    fn notify_jobs(&self, job_statuses: &mut HashMap<String, JobStatus>) {
+        let jobs = self.rpc.jobs.jobs();
+
+        {
+            let mut subscribers = self.subscribers.lock();
+
+            for job in jobs.iter() {
+                if job_statuses.get(&job.info.id) == Some(&job.info.status) {
+                    continue;
+                }
+
+                // The subscribers are only notified of their own jobs
+                subscribers
+                    .jobs
+                    .retain(|_, (owner, sink)| *owner != job.owner || sink.notify(Ok(job.info.clone())).wait().is_ok());
+            }
+        }
+
+        // Forget the pruned jobs
+        *job_statuses = jobs.into_iter().map(|job| (job.info.id, job.info.status)).collect();
+    }
+
+    fn next_subscription_id(&self) -> SubscriptionId {
+        SubscriptionId::Number(self.next_subscription_id.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+impl RpcSubscriptions for RpcSubscriptionsImpl {
+    type Metadata = SubscriptionMeta;
+
+    fn subscribe_blocks(&self, _meta: Self::Metadata, subscriber: Subscriber<BlockInfo>) {
+        let subscription_id = self.next_subscription_id();
+
+        if let Ok(sink) = subscriber.assign_id(subscription_id.clone()) {
+            self.subscribers.lock().blocks.insert(subscription_id, sink);
+        }
+    }
+
+    fn unsubscribe_blocks(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool> {
+        Ok(self.subscribers.lock().blocks.remove(&id).is_some())
+    }
+
+    fn subscribe_transactions(&self, _meta: Self::Metadata, subscriber: Subscriber<TransactionInfo>) {
+        let subscription_id = self.next_subscription_id();
+
+        if let Ok(sink) = subscriber.assign_id(subscription_id.clone()) {
+            self.subscribers.lock().transactions.insert(subscription_id, sink);
+        }
+    }
+
+    fn unsubscribe_transactions(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool> {
+        Ok(self.subscribers.lock().transactions.remove(&id).is_some())
+    }
+
+    fn subscribe_records(
+        &self,
+        _meta: Self::Metadata,
+        subscriber: Subscriber<RecordNotification>,
+        account_view_key: String,
+    ) {
+        let view_key = match AccountViewKey::<Components>::from_str(&account_view_key) {
+            Ok(view_key) => view_key,
+            Err(error) => {
+                let _ = subscriber.reject(RpcError::from(error).into());
+                return;
+            }
+        };
+
+        let subscription_id = self.next_subscription_id();
+
+        if let Ok(sink) = subscriber.assign_id(subscription_id.clone()) {
+            self.subscribers
+                .lock()
+                .records
+                .insert(subscription_id, (view_key, sink));
+        }
+    }
+
+    fn unsubscribe_records(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool> {
+        Ok(self.subscribers.lock().records.remove(&id).is_some())
+    }
+
+    fn subscribe_jobs(&self, _meta: Self::Metadata, subscriber: Subscriber<JobInfo>, authorization: String) {
+        let meta = Meta {
+            auth: Some(authorization),
+            is_local: false,
+            api_version: None,
+        };
+
+        if let Err(error) = self.rpc.validate_auth(meta.clone(), RpcScope::Wallet) {
+            let _ = subscriber.reject(error);
+            return;
+        }
+
+        let owner = self.rpc.client_identity(&meta);
+        let subscription_id = self.next_subscription_id();
+
+        if let Ok(sink) = subscriber.assign_id(subscription_id.clone()) {
+            self.subscribers.lock().jobs.insert(subscription_id, (owner, sink));
+        }
+    }
+
+    fn unsubscribe_jobs(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> JsonRPCResult<bool> {
+        Ok(self.subscribers.lock().jobs.remove(&id).is_some())
+    }
+}
+
+/// Returns the height of the latest notified block that is still canon, as a fork may have reverted the later ones.
+pub(crate) fn latest_canon_block_height(
+    rpc: &RpcImpl,
+    block_hashes: &BTreeMap<u32, String>,
+    latest_block_height: u32,
+) -> Result<u32, RpcError> {
+    let mut block_height = match block_hashes.keys().next_back() {
+        Some(notified_block_height) => latest_block_height.min(*notified_block_height),
+        None => latest_block_height,
+    };
+
+    while block_height > 0 {
+        match block_hashes.get(&block_height) {
+            Some(block_hash) if *block_hash != rpc.get_block_hash(block_height)? => block_height -= 1,
+            _ => break,
+        }
+    }
+
+    Ok(block_height)
+}
+
+/// Notify a value to every sink, and remove the sinks of the closed connections.
+fn notify_all<T: Clone + Serialize>(sinks: &mut HashMap<SubscriptionId, Sink<T>>, value: &T) {
+    sinks.retain(|_, sink| sink.notify(Ok(value.clone())).wait().is_ok());
+}
@@ -0,0 +1,170 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for terminating TLS connections to the RPC server.
+
+use crate::rpc_limits::{RpcLimits, TOO_MANY_REQUESTS_RESPONSE};
+use snarkos_errors::rpc::RpcError;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    io::{copy, split, AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::{
+    rustls::{
+        internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys},
+        AllowAnyAuthenticatedClient,
+        Certificate,
+        NoClientAuth,
+        PrivateKey,
+        RootCertStore,
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+/// Defines the TLS settings of the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcTlsConfig {
+    /// The path of the PEM encoded certificate chain of the server
+    pub cert_path: PathBuf,
+    /// The path of the PEM encoded PKCS #8 or RSA private key of the server
+    pub key_path: PathBuf,
+    /// The path of the PEM encoded certificates of the authorities issuing client certificates.
+    /// If set, the clients must authenticate with a certificate issued by one of these authorities.
+    pub client_ca_path: Option<PathBuf>,
+}
+
+impl RpcTlsConfig {
+    /// Loads the certificates and keys into a TLS server configuration.
+    pub fn server_config(&self) -> Result<ServerConfig, RpcError> {
+        let client_verifier = match &self.client_ca_path {
+            Some(client_ca_path) => {
+                let mut client_roots = RootCertStore::empty();
+                for certificate in read_certificates(client_ca_path)? {
+                    client_roots
+                        .add(&certificate)
+                        .map_err(|error| RpcError::InvalidTlsConfig(format!("{:?}", error)))?;
+                }
+
+                AllowAnyAuthenticatedClient::new(client_roots)
+            }
+            None => NoClientAuth::new(),
+        };
+
+        let mut config = ServerConfig::new(client_verifier);
+        config
+            .set_single_cert(read_certificates(&self.cert_path)?, read_private_key(&self.key_path)?)
+            .map_err(|error| RpcError::InvalidTlsConfig(error.to_string()))?;
+
+        Ok(config)
+    }
+}
+
+/// Starts a task accepting TLS connections at the given address, and forwarding the
+/// decrypted streams to the server listening at the upstream address.
+/// If limits are given, the connections exceeding them are answered with a `429 Too Many Requests` response.
+pub async fn start_tls_listener(
+    address: SocketAddr,
+    upstream_address: SocketAddr,
+    config: ServerConfig,
+    limits: Option<Arc<RpcLimits>>,
+) -> Result<(), RpcError> {
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let mut listener = TcpListener::bind(address).await?;
+
+    tokio::task::spawn(async move {
+        loop {
+            let (stream, client_address) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(_) => continue,
+            };
+
+            let guard = match &limits {
+                Some(limits) => RpcLimits::accept_connection(limits, client_address.ip())
+                    .ok_or(())
+                    .map(Some),
+                None => Ok(None),
+            };
+
+            let acceptor = acceptor.clone();
+
+            tokio::task::spawn(async move {
+                // Connections failing the handshake, such as unauthenticated clients, are dropped
+                if let Ok(mut tls_stream) = acceptor.accept(stream).await {
+                    match guard {
+                        Ok(guard) => {
+                            if let Ok(upstream) = TcpStream::connect(upstream_address).await {
+                                forward(tls_stream, upstream).await;
+                            }
+                            drop(guard);
+                        }
+                        Err(()) => {
+                            let _ = tls_stream.write_all(TOO_MANY_REQUESTS_RESPONSE).await;
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Copy the bytes between two streams, until either is closed.
+pub(crate) async fn forward<A, B>(a: A, b: B)
+where
+    A: AsyncRead + AsyncWrite,
+    B: AsyncRead + AsyncWrite,
+{
+    let (mut a_reader, mut a_writer) = split(a);
+    let (mut b_reader, mut b_writer) = split(b);
+
+    let _ = tokio::try_join!(copy(&mut a_reader, &mut b_writer), copy(&mut b_reader, &mut a_writer));
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>, RpcError> {
+    let certificates = certs(&mut BufReader::new(File::open(path)?))
+        .map_err(|_| RpcError::InvalidTlsConfig(format!("invalid certificates in {}", path.display())))?;
+
+    if certificates.is_empty() {
+        return Err(RpcError::InvalidTlsConfig(format!(
+            "no certificates in {}",
+            path.display()
+        )));
+    }
+
+    Ok(certificates)
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKey, RpcError> {
+    let invalid_key = || RpcError::InvalidTlsConfig(format!("invalid private key in {}", path.display()));
+
+    let mut keys = pkcs8_private_keys(&mut BufReader::new(File::open(path)?)).map_err(|_| invalid_key())?;
+    if keys.is_empty() {
+        keys = rsa_private_keys(&mut BufReader::new(File::open(path)?)).map_err(|_| invalid_key())?;
+    }
+
+    keys.into_iter().next().ok_or_else(invalid_key)
+}
@@ -0,0 +1,88 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! The rotations of the API tokens of the RPC server.
+//!
+//! The rotations are stored by the node and applied to the configured tokens, so a rotated token
+//! stays revoked when the configuration is reloaded or the node restarts. Only the hashes of the
+//! tokens are stored, so the issued tokens cannot be read from the storage of the node.
+
+use crate::rpc_types::{RpcScope, RpcToken};
+use snarkos_consensus::MerkleTreeLedger;
+use snarkos_errors::{rpc::RpcError, storage::StorageError};
+
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Returns the hash of an API token, which identifies the token without revealing it.
+pub fn token_hash(token: &str) -> String {
+    hex::encode(&Sha256::digest(token.as_bytes())[..])
+}
+
+/// The hashes of the API tokens replaced by a rotation, and of the tokens issued in their place.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenRotations {
+    /// The hashes of the rotated tokens, revoked whether or not they are configured
+    revoked_hashes: HashSet<String>,
+    /// The hashes of the tokens issued by a rotation and not rotated since, with their permission scopes
+    issued_hashes: HashMap<String, RpcScope>,
+}
+
+impl TokenRotations {
+    /// Loads the token rotations stored by the node.
+    /// Rotations that cannot be read are an error, as the tokens they revoked would be valid again.
+    pub fn load(storage: &MerkleTreeLedger) -> Result<Self, RpcError> {
+        match storage.get_rpc_token_rotations() {
+            Ok(rotations_serialized) => serde_json::from_slice(&rotations_serialized)
+                .map_err(|error| RpcError::Message(format!("invalid API token rotations: {}", error))),
+            Err(StorageError::MissingValue(_)) => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Returns the hashes of the configured tokens without the revoked tokens, and of the issued tokens,
+    /// with their scopes.
+    pub fn tokens(&self, tokens: Vec<RpcToken>) -> HashMap<String, RpcScope> {
+        let mut tokens: HashMap<String, RpcScope> = tokens
+            .into_iter()
+            .map(|token| (token_hash(&token.token), token.scope))
+            .filter(|(hash, _)| !self.revoked_hashes.contains(hash))
+            .collect();
+        tokens.extend(self.issued_hashes.iter().map(|(hash, scope)| (hash.clone(), *scope)));
+
+        tokens
+    }
+
+    /// Revokes the token with the given hash and scope and issues a new random token of the same scope,
+    /// storing the rotation before the new token is returned.
+    pub fn rotate(&mut self, storage: &MerkleTreeLedger, hash: String, scope: RpcScope) -> Result<String, RpcError> {
+        let mut rotations = self.clone();
+
+        let new_token = hex::encode(thread_rng().gen::<[u8; 32]>());
+        rotations.issued_hashes.remove(&hash);
+        rotations.revoked_hashes.insert(hash);
+        rotations.issued_hashes.insert(token_hash(&new_token), scope);
+
+        let rotations_serialized =
+            serde_json::to_vec(&rotations).map_err(|error| RpcError::Message(error.to_string()))?;
+        storage.store_rpc_token_rotations(rotations_serialized)?;
+        *self = rotations;
+
+        Ok(new_token)
+    }
+}
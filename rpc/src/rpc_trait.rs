@@ -18,8 +18,10 @@
 
 use crate::rpc_types::*;
 use snarkos_errors::rpc::RpcError;
+use snarkos_storage::StorageMetrics;
 
 use jsonrpc_derive::rpc;
+use jsonrpc_http_server::jsonrpc_core::Value;
 
 /// Definition of public RPC endpoints.
 #[rpc]
@@ -28,6 +30,10 @@ pub trait RpcFunctions {
     #[rpc(name = "getblock")]
     fn get_block(&self, block_hash_string: String) -> Result<BlockInfo, RpcError>;
 
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getblocks.md"))]
+    #[rpc(name = "getblocks")]
+    fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<BlockInfo>, RpcError>;
+
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getblockcount.md"))]
     #[rpc(name = "getblockcount")]
     fn get_block_count(&self) -> Result<u32, RpcError>;
@@ -46,11 +52,15 @@ pub trait RpcFunctions {
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/gettransactioninfo.md"))]
     #[rpc(name = "gettransactioninfo")]
-    fn get_transaction_info(&self, transaction_id: String) -> Result<TransactionInfo, RpcError>;
+    fn get_transaction_info(&self, transaction_id: String, verbose: Option<bool>) -> Result<TransactionInfo, RpcError>;
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/decoderawtransaction.md"))]
     #[rpc(name = "decoderawtransaction")]
-    fn decode_raw_transaction(&self, transaction_bytes: String) -> Result<TransactionInfo, RpcError>;
+    fn decode_raw_transaction(
+        &self,
+        transaction_bytes: String,
+        verbose: Option<bool>,
+    ) -> Result<TransactionInfo, RpcError>;
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/sendtransaction.md"))]
     #[rpc(name = "sendtransaction")]
@@ -61,7 +71,19 @@ pub trait RpcFunctions {
         doc(include = "../documentation/public_endpoints/validaterawtransaction.md")
     )]
     #[rpc(name = "validaterawtransaction")]
-    fn validate_raw_transaction(&self, transaction_bytes: String) -> Result<bool, RpcError>;
+    fn validate_raw_transaction(
+        &self,
+        transaction_bytes: String,
+        mode: Option<ValidationMode>,
+    ) -> Result<TransactionValidity, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/audittransaction.md"))]
+    #[rpc(name = "audittransaction")]
+    fn audit_transaction(
+        &self,
+        transaction_bytes: String,
+        view_keys: Option<Vec<String>>,
+    ) -> Result<TransactionAuditInfo, RpcError>;
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getconnectioncount.md"))]
     #[rpc(name = "getconnectioncount")]
@@ -69,15 +91,93 @@ pub trait RpcFunctions {
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getpeerinfo.md"))]
     #[rpc(name = "getpeerinfo")]
-    fn get_peer_info(&self) -> Result<PeerInfo, RpcError>;
+    fn get_peer_info(
+        &self,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        verbose: Option<bool>,
+    ) -> Result<PeerInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getnetworkinfo.md"))]
+    #[rpc(name = "getnetworkinfo")]
+    fn get_network_info(&self) -> Result<NetworkInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getmemorypool.md"))]
+    #[rpc(name = "getmemorypool")]
+    fn get_memory_pool(&self, limit: Option<usize>, offset: Option<usize>) -> Result<MemoryPoolInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/estimatefee.md"))]
+    #[rpc(name = "estimatefee")]
+    fn estimate_fee(&self, target_blocks: u32) -> Result<FeeEstimateInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/calculatefee.md"))]
+    #[rpc(name = "calculatefee")]
+    fn calculate_fee(&self, fee_rate: f64) -> Result<FeeCalculationInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/createpaymentrequest.md"))]
+    #[rpc(name = "createpaymentrequest")]
+    fn create_payment_request(&self, request_input: PaymentRequestInputs) -> Result<PaymentRequestInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getmempoolinfo.md"))]
+    #[rpc(name = "getmempoolinfo")]
+    fn get_memory_pool_info(&self) -> Result<MemoryPoolSummary, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getrawmempool.md"))]
+    #[rpc(name = "getrawmempool")]
+    fn get_raw_memory_pool(&self, verbose: Option<bool>) -> Result<RawMemoryPool, RpcError>;
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getnodeinfo.md"))]
     #[rpc(name = "getnodeinfo")]
     fn get_node_info(&self) -> Result<NodeInfo, RpcError>;
 
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getmininginfo.md"))]
+    #[rpc(name = "getmininginfo")]
+    fn get_mining_info(&self) -> Result<MiningInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getnodestate.md"))]
+    #[rpc(name = "getnodestate")]
+    fn get_node_state(&self) -> Result<NodeState, RpcError>;
+
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getblocktemplate.md"))]
     #[rpc(name = "getblocktemplate")]
-    fn get_block_template(&self) -> Result<BlockTemplate, RpcError>;
+    fn get_block_template(&self, coinbase_address: Option<String>) -> Result<BlockTemplate, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/submitblock.md"))]
+    #[rpc(name = "submitblock")]
+    fn submit_block(&self, block_bytes: String) -> Result<SubmitBlockResult, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getserialnumberproof.md"))]
+    #[rpc(name = "getserialnumberproof")]
+    fn get_serial_number_proof(&self, serial_number: String) -> Result<SerialNumberProofInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getledgerproof.md"))]
+    #[rpc(name = "getledgerproof")]
+    fn get_ledger_proof(&self, commitment: String, ledger_digest: Option<String>) -> Result<LedgerProofInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/validateledgerdigest.md"))]
+    #[rpc(name = "validateledgerdigest")]
+    fn validate_ledger_digest(&self, ledger_digest: String) -> Result<LedgerDigestInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/searchtransactions.md"))]
+    #[rpc(name = "searchtransactions")]
+    fn search_transactions(
+        &self,
+        memo_prefix: String,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<TransactionSearchResult>, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getcommitmentinfo.md"))]
+    #[rpc(name = "getcommitmentinfo")]
+    fn get_commitment_info(&self, commitment: String) -> Result<CommitmentInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getstoragemetrics.md"))]
+    #[rpc(name = "getstoragemetrics")]
+    fn get_storage_metrics(&self) -> Result<StorageMetrics, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getparameterdownloads.md"))]
+    #[rpc(name = "getparameterdownloads")]
+    fn get_parameter_downloads(&self) -> Result<Vec<ParameterDownloadInfo>, RpcError>;
 }
 
 /// Definition of private RPC endpoints that require authentication.
@@ -91,6 +191,18 @@ pub trait ProtectedRpcFunctions {
         transaction_input: TransactionInputs,
     ) -> Result<CreateRawTransactionOuput, RpcError>;
 
+    #[cfg_attr(
+        nightly,
+        doc(include = "../documentation/private_endpoints/createtransactionskeleton.md")
+    )]
+    fn create_transaction_skeleton(
+        &self,
+        transaction_input: TransactionSkeletonInputs,
+    ) -> Result<TransactionSkeleton, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/provetransaction.md"))]
+    fn prove_transaction(&self, authorization: String) -> Result<CreateRawTransactionOuput, RpcError>;
+
     #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getrecordcommitments.md"))]
     fn get_record_commitments(&self) -> Result<Vec<String>, RpcError>;
 
@@ -108,4 +220,65 @@ pub trait ProtectedRpcFunctions {
 
     #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/decryptrecord.md"))]
     fn decrypt_record(&self, decryption_input: DecryptRecordInput) -> Result<String, RpcError>;
+
+    #[cfg_attr(
+        nightly,
+        doc(include = "../documentation/private_endpoints/removemempooltransaction.md")
+    )]
+    fn remove_memory_pool_transaction(&self, transaction_id: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/rotatetoken.md"))]
+    fn rotate_token(&self, token: String) -> Result<String, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/addpeer.md"))]
+    fn add_peer(&self, address: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/removepeer.md"))]
+    fn remove_peer(&self, address: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/banpeer.md"))]
+    fn ban_peer(&self, address: String, duration: Option<u32>) -> Result<i64, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/unbanpeer.md"))]
+    fn unban_peer(&self, address: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/setloglevel.md"))]
+    fn set_log_level(&self, filter: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/generateblocks.md"))]
+    fn generate_blocks(&self, count: u32) -> Result<u32, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/reloadconfig.md"))]
+    fn reload_config(&self) -> Result<Vec<String>, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/revalidatemempool.md"))]
+    fn revalidate_memory_pool(&self) -> Result<usize, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/submitjob.md"))]
+    fn submit_job(&self, method: String, params: Vec<Value>) -> Result<String, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getjobstatus.md"))]
+    fn get_job_status(&self, job_id: String) -> Result<JobInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getjobresult.md"))]
+    fn get_job_result(&self, job_id: String) -> Result<Value, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/canceljob.md"))]
+    fn cancel_job(&self, job_id: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/addwalletaccount.md"))]
+    fn add_wallet_account(&self, wallet_account_input: WalletAccountInput) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/removewalletaccount.md"))]
+    fn remove_wallet_account(&self, account_view_key: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getbalance.md"))]
+    fn get_balance(
+        &self,
+        account_view_key: String,
+        min_confirmations: Option<u32>,
+    ) -> Result<WalletBalanceInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/listtransactions.md"))]
+    fn list_transactions(&self, account_view_key: String) -> Result<Vec<WalletTransactionInfo>, RpcError>;
 }
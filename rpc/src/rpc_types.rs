@@ -16,9 +16,12 @@
 
 //! Structures for RPC endpoint requests and responses.
 
+use crate::rpc_versions::RpcVersion;
+
 use jsonrpc_http_server::jsonrpc_core::Metadata;
+use jsonrpc_pubsub::{PubSubMetadata, Session};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
 
 /// Defines the authentication format for accessing private endpoints on the RPC server
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -29,15 +32,55 @@ pub struct RpcCredentials {
     pub password: String,
 }
 
+/// The permission scopes of the API tokens, each scope granting the permissions of the previous ones
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcScope {
+    /// Read the records stored by the node
+    ReadOnly,
+    /// Create accounts and transactions, and decode and decrypt records
+    Wallet,
+    /// Manage the API tokens of the node
+    Admin,
+}
+
+/// Defines an API token for accessing private endpoints on the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcToken {
+    /// The secret token, sent as a bearer token in the authentication header
+    pub token: String,
+    /// The permission scope of the token
+    pub scope: RpcScope,
+}
+
 /// RPC metadata for encoding authentication
 #[derive(Default, Clone)]
 pub struct Meta {
     /// An optional authentication string for protected RPC functions
     pub auth: Option<String>,
+    /// Flag indicating if the request was received on the Unix domain socket, whose clients are trusted
+    pub is_local: bool,
+    /// The version of the API requested by the path of the HTTP request
+    pub api_version: Option<RpcVersion>,
 }
 
 impl Metadata for Meta {}
 
+/// RPC metadata of a WebSocket connection, holding the session of its subscriptions
+#[derive(Default, Clone)]
+pub struct SubscriptionMeta {
+    /// The session of the connection
+    pub session: Option<Arc<Session>>,
+}
+
+impl Metadata for SubscriptionMeta {}
+
+impl PubSubMetadata for SubscriptionMeta {
+    fn session(&self) -> Option<Arc<Session>> {
+        self.session.clone()
+    }
+}
+
 /// Returned value for the `getblock` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct BlockInfo {
@@ -98,6 +141,32 @@ pub struct BlockTemplate {
 
     /// Amount spendable by the coinbase transaction (block rewards + transaction fees)
     pub coinbase_value: u64,
+
+    /// Block reward of the block, excluding the transaction fees
+    pub block_reward: u64,
+
+    /// Network id of the coinbase transaction
+    pub network_id: u8,
+
+    /// Maximum size of the serialized block in bytes
+    pub max_block_size: usize,
+
+    /// Coinbase transaction sending the coinbase value to the requested address, to include in the block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coinbase_transaction: Option<String>,
+}
+
+/// Returned value for the `submitblock` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SubmitBlockResult {
+    /// The hash of the submitted block
+    pub block_hash: String,
+
+    /// True if the block was accepted by the node
+    pub accepted: bool,
+
+    /// The reason the block was rejected
+    pub reject_reason: Option<String>,
 }
 
 /// Output for the `createrawtransaction` rpc call
@@ -119,21 +188,615 @@ pub struct DecryptRecordInput {
     pub account_view_key: String,
 }
 
+/// Input for the `addwalletaccount` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletAccountInput {
+    /// The account view key whose records the wallet tracks
+    pub account_view_key: String,
+
+    /// The account private key the spends of the records are tracked with, if any.
+    /// Only the proof key derived from it is stored by the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_private_key: Option<String>,
+}
+
+/// Returned value for the `getbalance` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletBalanceInfo {
+    /// The value of the unspent records with at least the requested number of confirmations
+    pub confirmed: u64,
+
+    /// The value of the unspent records with fewer confirmations
+    pub pending: u64,
+
+    /// Whether the spends of the records are tracked; if not, every record counts as unspent
+    pub tracks_spends: bool,
+}
+
+/// An entry of the `listtransactions` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletTransactionInfo {
+    /// The transaction id
+    pub txid: String,
+
+    /// The height of the block holding the transaction
+    pub block_height: u32,
+
+    /// Number of blocks built on top of the block holding the transaction
+    pub confirmations: u32,
+
+    /// The value of the records of the account created by the transaction
+    pub received: u64,
+
+    /// The value of the records of the account spent by the transaction
+    pub spent: u64,
+}
+
 /// Returned value for the `getnodeinfo` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct NodeInfo {
+    /// The peer ID of the node, the public key of its identity
+    pub peer_id: String,
+
+    /// Flag indicating if the node is operating as a miner
+    pub is_miner: bool,
+
+    /// Flag indicating if the node is currently syncing
+    pub is_syncing: bool,
+}
+
+/// Returned value for the `getparameterdownloads` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterDownloadInfo {
+    /// The name of the parameter file
+    pub filename: String,
+    /// The mirror the file is downloaded from
+    pub mirror: String,
+    /// The number of bytes downloaded, including the bytes of a resumed download
+    pub downloaded: u64,
+    /// The size of the file
+    pub size: u64,
+    /// The percentage of the file downloaded
+    pub percent: f64,
+    /// The state of the download, `downloading`, `verifying`, `installed` or `failed`
+    pub state: String,
+    /// The error of a failed download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Returned value for the `getmininginfo` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MiningInfo {
+    /// Flag indicating if the node is operating as a miner
+    pub is_miner: bool,
+    /// The number of proofs of work attempted per second over the last 10 minutes
+    pub hashrate: f64,
+    /// The number of proofs of work attempted
+    pub attempts: u64,
+    /// The number of mined blocks committed to the canon chain
+    pub accepted_blocks: u64,
+    /// The number of mined blocks whose parent was no longer the tip of the canon chain
+    pub stale_blocks: u64,
+    /// The number of pool shares submitted for a superseded template
+    pub stale_shares: u64,
+    /// The number of block templates built
+    pub templates: u64,
+    /// The average time spent building a block template, in seconds
+    pub average_template_latency: f64,
+}
+
+/// Returned value for the `/status` REST endpoint
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    /// The height of the latest canon block
+    pub block_height: u32,
+    /// The hash of the latest canon block
+    pub best_block_hash: String,
+    /// The number of connected peers
+    pub peers: usize,
+    /// The number of transactions in the memory pool
+    pub memory_pool_size: usize,
     /// Flag indicating if the node is operating as a miner
     pub is_miner: bool,
+    /// Flag indicating if the node is currently syncing
+    pub is_syncing: bool,
+}
+
+/// The health of the storage of the node
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageHealth {
+    /// Flag indicating if the latest state of the ledger can be read
+    pub is_healthy: bool,
+    /// The storage error, if the storage is not healthy
+    pub error: Option<String>,
+}
 
+/// Returned value for the `getnodestate` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NodeState {
+    /// The height of the latest canon block
+    pub block_height: u32,
+    /// The height of the best block known to the node, including the announced blocks it has not yet received
+    pub best_known_block_height: u32,
     /// Flag indicating if the node is currently syncing
     pub is_syncing: bool,
+    /// The number of announced block headers whose blocks are not yet requested
+    pub queued_block_headers: usize,
+    /// The number of requested blocks not yet received
+    pub pending_blocks: usize,
+    /// The number of connected peers
+    pub connected_peers: usize,
+    /// The number of peers known from gossip, but not connected
+    pub gossiped_peers: usize,
+    /// The number of transactions in the memory pool
+    pub memory_pool_size: usize,
+    /// The number of seconds since the node started
+    pub uptime: u64,
+    /// The health of the storage
+    pub storage: StorageHealth,
+}
+
+/// Returned value for the `/health` REST endpoint
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NodeHealth {
+    /// Flag indicating if the node is synced and its storage is healthy
+    pub is_healthy: bool,
+    /// Flag indicating if the node is currently syncing
+    pub is_syncing: bool,
+    /// The health of the storage
+    pub storage: StorageHealth,
 }
 
 /// Returned value for the `getpeerinfo` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PeerInfo {
-    /// The peers connected to this node
+    /// The page of the peers connected to this node
     pub peers: Vec<SocketAddr>,
+    /// The number of peers connected to this node
+    pub total: usize,
+    /// The connection statistics of the page of peers, if verbose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<PeerDetails>>,
+}
+
+/// The connection statistics of a connected peer
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PeerDetails {
+    /// The address of the peer
+    pub address: SocketAddr,
+    /// The peer ID proven by the peer in its handshake
+    pub peer_id: Option<String>,
+    /// The protocol version of the peer
+    pub version: Option<u64>,
+    /// The block height advertised by the peer
+    pub height: Option<u32>,
+    /// The hash of the latest block announced by the peer
+    pub last_block_hash: Option<String>,
+    /// The time the latest block was announced by the peer
+    pub last_block_time: Option<i64>,
+    /// The time the peer was last seen
+    pub last_seen: i64,
+    /// The round trip time of the latest ping, in milliseconds
+    pub rtt: Option<u64>,
+    /// The number of bytes sent to the peer
+    pub bytes_sent: u64,
+    /// The number of bytes received from the peer
+    pub bytes_received: u64,
+    /// The accumulated score of the peer misbehaviors
+    pub misbehavior_score: u32,
+}
+
+/// Returned value for the `getnetworkinfo` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    /// The address of this node
+    pub local_address: SocketAddr,
+    /// The number of peers connected to this node
+    pub connected_peers: usize,
+    /// The number of gossiped peers this node is not connected to
+    pub gossiped_peers: usize,
+    /// The minimum number of peers this node connects to
+    pub min_peers: u16,
+    /// The maximum number of peers this node connects to
+    pub max_peers: u16,
+    /// The number of connected peers of each protocol version
+    pub versions: BTreeMap<u64, usize>,
+    /// The average round trip time of the connected peers, in milliseconds
+    pub average_rtt: Option<f64>,
+    /// The number of bytes sent to the connected peers
+    pub bytes_sent: u64,
+    /// The number of bytes received from the connected peers
+    pub bytes_received: u64,
+    /// The number of connected peers with a misbehavior score
+    pub misbehaving_peers: usize,
+}
+
+/// Returned value for the `getmemorypool` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoolInfo {
+    /// The page of the ids of the transactions in the memory pool
+    pub transactions: Vec<String>,
+    /// The number of transactions in the memory pool
+    pub total: usize,
+}
+
+/// Returned value for the `estimatefee` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimateInfo {
+    /// The number of blocks the transaction should be included within
+    pub target_blocks: u32,
+    /// The recommended fee rate, per byte of the transaction
+    pub fee_rate: f64,
+    /// The ratio of the sampled runs of `target_blocks` blocks that would have included the transaction
+    pub confidence: f64,
+    /// The fee rate outbidding the memory pool transactions filling the next `target_blocks` blocks
+    pub memory_pool_fee_rate: f64,
+    /// The number of sampled blocks
+    pub sampled_blocks: u32,
+}
+
+/// Returned value for the `calculatefee` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeCalculationInfo {
+    /// The size of the transaction, in bytes
+    pub transaction_size: usize,
+    /// The fee rate, per byte of the transaction
+    pub fee_rate: f64,
+    /// The fee paying the fee rate
+    pub fee: u64,
+}
+
+/// Input for the `createpaymentrequest` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequestInputs {
+    /// The address receiving the payment
+    pub address: String,
+    /// The requested amount, in bytes
+    pub amount: Option<u64>,
+    /// The memo to attach to the payment
+    pub memo: Option<String>,
+    /// The time after which the request is not honoured, as a Unix timestamp
+    pub expires: Option<i64>,
+}
+
+/// Returned value for the `createpaymentrequest` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequestInfo {
+    /// The payment request URI
+    pub uri: String,
+    /// The address receiving the payment, encoded for the network of the node
+    pub address: String,
+}
+
+/// The state of an asynchronous job
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job waits for the previous jobs to finish
+    Pending,
+    /// The job is running
+    Running,
+    /// The job completed, and its result is available
+    Completed,
+    /// The job failed with an error
+    Failed,
+    /// The job was cancelled, and its result is discarded
+    Cancelled,
+}
+
+/// Returned value for the `getjobstatus` rpc call, and notified value of the `subscribeJobs` subscription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JobInfo {
+    /// The id of the job
+    pub id: String,
+    /// The method run by the job
+    pub method: String,
+    /// The state of the job
+    pub status: JobStatus,
+    /// The time the job was submitted
+    pub submitted: i64,
+    /// The time the job started running
+    pub started: Option<i64>,
+    /// The time the job finished
+    pub finished: Option<i64>,
+    /// The error of a failed job
+    pub error: Option<String>,
+}
+
+/// Returned value for the `getmempoolinfo` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoolSummary {
+    /// The number of transactions in the memory pool
+    pub size: usize,
+    /// The size of the transactions in the memory pool, in bytes
+    pub bytes: usize,
+    /// The total fee paid by the transactions in the memory pool
+    pub total_fee: i64,
+    /// The lowest fee rate of the transactions in the memory pool, per byte
+    pub min_fee_rate: Option<f64>,
+    /// The highest fee rate of the transactions in the memory pool, per byte
+    pub max_fee_rate: Option<f64>,
+    /// The time the oldest transaction entered the memory pool
+    pub oldest_time: Option<i64>,
+}
+
+/// A transaction in the memory pool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoolEntryInfo {
+    /// The size of the transaction, in bytes
+    pub size: usize,
+    /// The fee paid by the transaction
+    pub fee: i64,
+    /// The fee paid by the transaction, per byte
+    pub fee_rate: f64,
+    /// The time the transaction entered the memory pool
+    pub time: i64,
+    /// The number of seconds the transaction has been in the memory pool
+    pub age: i64,
+}
+
+/// Returned value for the `getrawmempool` rpc call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RawMemoryPool {
+    /// The ids of the transactions in the memory pool
+    TransactionIds(Vec<String>),
+    /// The transactions in the memory pool, by id
+    Entries(BTreeMap<String, MemoryPoolEntryInfo>),
+}
+
+/// The checks a transaction is validated with by the `validaterawtransaction` rpc call
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationMode {
+    /// The consensus rules that do not depend on the ledger state
+    Stateless,
+    /// The stateless checks, and the checks against the ledger state and the memory pool
+    Stateful,
+}
+
+/// A check of a transaction validated by the `validaterawtransaction` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionCheck {
+    /// The name of the check
+    pub name: String,
+
+    /// Flag indicating if the transaction passed the check
+    pub passed: bool,
+
+    /// The reason the transaction failed the check
+    pub reason: Option<String>,
+}
+
+/// The verification diagnostics of a transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionValidation {
+    /// The id of the transaction
+    pub txid: String,
+
+    /// The checks the transaction was validated with
+    pub mode: ValidationMode,
+
+    /// Flag indicating if the transaction passed all the checks
+    pub is_valid: bool,
+
+    /// The result of each check, in order
+    pub checks: Vec<TransactionCheck>,
+}
+
+/// Returned value for the `validaterawtransaction` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TransactionValidity {
+    /// Flag indicating if the transaction is valid, if no mode is given
+    IsValid(bool),
+    /// The verification diagnostics of the transaction, if a mode is given
+    Diagnostics(TransactionValidation),
+}
+
+/// An output record disclosed by a view key in the `audittransaction` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DisclosedRecordInfo {
+    /// The encoded commitment of the record
+    pub commitment: String,
+
+    /// The owner of the record
+    pub owner: String,
+
+    /// The value of the record
+    pub value: u64,
+}
+
+/// Returned value for the `audittransaction` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionAuditInfo {
+    /// The id of the transaction
+    pub txid: String,
+
+    /// The value of the input records minus the value of the output records
+    pub value_balance: i64,
+
+    /// The fee paid by the transaction
+    pub fee: u64,
+
+    /// Flag indicating if the transaction mints new value
+    pub is_coinbase: bool,
+
+    /// Flag indicating if the signatures over the value balance verify
+    pub signatures: bool,
+
+    /// Flag indicating if the transaction proof verifies against the value balance
+    pub proof: bool,
+
+    /// Flag indicating if the value balance is bound by both the signatures and the proof
+    pub is_valid: bool,
+
+    /// The output records opened by the given view keys
+    pub disclosed_records: Vec<DisclosedRecordInfo>,
+
+    /// The total value of the disclosed records
+    pub disclosed_value: u64,
+}
+
+/// Returned value for the `getserialnumberproof` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerialNumberProofInfo {
+    /// The height of the latest block
+    pub block_height: u32,
+
+    /// The hash of the latest block
+    pub block_hash: String,
+
+    /// The root of the serial number tree
+    pub root: String,
+
+    /// Flag indicating if the serial number is in the ledger
+    pub is_spent: bool,
+
+    /// The path from the root to the leaf of the serial number
+    pub leaf_key: String,
+
+    /// The depths and hashes of the non-empty siblings from the leaf to the root
+    pub siblings: Vec<(u16, String)>,
+}
+
+/// Returned value for the `getledgerproof` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LedgerProofInfo {
+    /// The height of the block the ledger digest is the digest of
+    pub block_height: u32,
+
+    /// The encoded ledger digest the proof is valid for
+    pub ledger_digest: String,
+
+    /// The encoded record commitment
+    pub commitment: String,
+
+    /// The encoded left and right digests of each level of the merkle path of the commitment
+    pub merkle_path: Vec<(String, String)>,
+}
+
+/// Returned value for the `validateledgerdigest` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LedgerDigestInfo {
+    /// The encoded ledger digest
+    pub ledger_digest: String,
+
+    /// Flag indicating if transactions built against the digest are accepted
+    pub is_valid: bool,
+
+    /// Flag indicating if the digest is the ledger digest of a canon block
+    pub is_canon: bool,
+
+    /// The height of the block the digest was created at, if it is valid
+    pub block_height: Option<u32>,
+}
+
+/// Returned value for the `searchtransactions` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSearchResult {
+    /// The encoded memo of the transaction
+    pub memo: String,
+
+    /// Transaction id
+    pub txid: String,
+
+    /// The height of the block holding the transaction
+    pub block_height: u32,
+}
+
+/// Returned value for the `getcommitmentinfo` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CommitmentInfo {
+    /// The encoded record commitment
+    pub commitment: String,
+
+    /// The index of the commitment in the ledger
+    pub index: usize,
+
+    /// Id of the transaction that created the record
+    pub txid: String,
+
+    /// The height of the block holding the transaction
+    pub block_height: u32,
+
+    /// Number of blocks built on top of the block holding the transaction
+    pub confirmations: u32,
+}
+
+/// Notified value of the `subscribeRecords` subscription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecordNotification {
+    /// The height of the block holding the records
+    pub block_height: u32,
+
+    /// The hash of the block holding the records
+    pub block_hash: String,
+
+    /// The records of the block that the account view key can decrypt
+    pub records: Vec<String>,
+}
+
+/// The chain events notified to the webhooks
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A new canon block
+    Block,
+    /// A fork reverting canon blocks
+    Reorg,
+    /// A new canon transaction creating records that the account view key of the webhook can decrypt
+    Transaction,
+}
+
+/// Notified value of the `reorg` webhook event
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReorgNotification {
+    /// The height of the latest block kept in the canon chain
+    pub fork_block_height: u32,
+
+    /// The hashes of the reverted blocks, by increasing height
+    pub reverted_block_hashes: Vec<String>,
+}
+
+/// Notified value of the `transaction` webhook event
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionNotification {
+    /// The height of the block holding the transaction
+    pub block_height: u32,
+
+    /// The hash of the block holding the transaction
+    pub block_hash: String,
+
+    /// The transaction id
+    pub txid: String,
+
+    /// The records of the transaction that the account view key can decrypt
+    pub records: Vec<String>,
+}
+
+/// The body of the requests notifying an event to a webhook
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "payload", rename_all = "snake_case")]
+pub enum WebhookNotification {
+    /// A new canon block, as returned by `getblock`
+    Block(BlockInfo),
+    /// A fork reverting canon blocks, which is followed by the blocks replacing them
+    Reorg(ReorgNotification),
+    /// A new canon transaction creating records of the account
+    Transaction(TransactionNotification),
+}
+
+impl WebhookNotification {
+    /// Returns the event of the notification.
+    pub fn event(&self) -> WebhookEvent {
+        match self {
+            WebhookNotification::Block(_) => WebhookEvent::Block,
+            WebhookNotification::Reorg(_) => WebhookEvent::Reorg,
+            WebhookNotification::Transaction(_) => WebhookEvent::Transaction,
+        }
+    }
 }
 
 /// Record payload data
@@ -229,6 +892,96 @@ pub struct TransactionInfo {
 
     /// Block the transaction lives in
     pub transaction_metadata: TransactionMetadata,
+
+    /// Structured breakdown of the transaction, for verbose requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<TransactionDetails>,
+}
+
+/// Structured breakdown of a transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionDetails {
+    /// The spent records, by input index
+    pub inputs: Vec<TransactionInputInfo>,
+
+    /// The created records, by output index
+    pub outputs: Vec<TransactionOutputInfo>,
+
+    /// Transaction memo
+    pub memo: MemoInfo,
+
+    /// Transaction value balance
+    pub value_balance: ValueBalanceInfo,
+
+    /// Program verification key commitment
+    pub program_commitment: String,
+}
+
+/// A record spent by a transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionInputInfo {
+    /// The serial number of the spent record
+    pub serial_number: String,
+
+    /// The signature authorizing the spend
+    pub signature: SignatureInfo,
+}
+
+/// A Schnorr signature of a transaction input
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignatureInfo {
+    /// The prover response of the signature
+    pub prover_response: String,
+
+    /// The verifier challenge of the signature
+    pub verifier_challenge: String,
+}
+
+/// A record created by a transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionOutputInfo {
+    /// The commitment of the created record
+    pub commitment: String,
+
+    /// The created record, encrypted for its owner
+    pub encrypted_record: EncryptedRecordInfo,
+}
+
+/// A record encrypted for its owner
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedRecordInfo {
+    /// The ciphertext group elements
+    pub ciphertext: Vec<String>,
+
+    /// The selector of the high bits of the final field element
+    pub final_fq_high_selector: bool,
+}
+
+/// A transaction memo
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemoInfo {
+    /// The memo bytes
+    pub bytes: String,
+
+    /// The memo text, if the memo bytes are UTF-8 text followed by zero bytes
+    pub text: Option<String>,
+
+    /// The tag of the miner of the block, if the memo of a coinbase transaction carries one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub miner_tag: Option<String>,
+}
+
+/// The value balance of a transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ValueBalanceInfo {
+    /// The value of the spent records minus the value of the created records
+    pub value_balance: i64,
+
+    /// The fee paid to the miner, if the value balance is positive
+    pub fee: i64,
+
+    /// The value minted by a coinbase transaction, if the value balance is negative
+    pub minted: i64,
 }
 
 /// Input for the `createrawtransaction` rpc call
@@ -254,6 +1007,66 @@ pub struct TransactionInputs {
     //    pub new_payloads: Vec<String>,
 }
 
+/// Input for the `createtransactionskeleton` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSkeletonInputs {
+    /// Encoded records that are being spent
+    pub old_records: Vec<String>,
+
+    /// The address of the account spending the records, which owns the dummy records filling the unused inputs
+    pub sender_address: String,
+
+    /// Transaction recipent and amounts
+    pub recipients: Vec<TransactionRecipient>,
+
+    /// Transaction memo
+    pub memo: Option<String>,
+
+    /// The hex encoded 32 bytes seeding the generation of the dummy records and of the memo
+    pub randomness: String,
+}
+
+/// Returned value for the `createtransactionskeleton` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSkeleton {
+    /// The encoded records spent by the transaction, including the dummy records
+    pub old_records: Vec<String>,
+
+    /// The addresses of the owners of the new records
+    pub new_record_owners: Vec<String>,
+
+    /// The flags indicating which new records are dummy records
+    pub new_is_dummy_flags: Vec<bool>,
+
+    /// The values of the new records
+    pub new_values: Vec<u64>,
+
+    /// The encoded payloads of the new records
+    pub new_payloads: Vec<String>,
+
+    /// The encoded birth program ids of the new records
+    pub new_birth_program_ids: Vec<String>,
+
+    /// The encoded death program ids of the new records
+    pub new_death_program_ids: Vec<String>,
+
+    /// The encoded transaction memo
+    pub memo: String,
+
+    /// Network id of the transaction
+    pub network_id: u8,
+
+    /// The height of the latest block the witnesses are valid at
+    pub block_height: u32,
+
+    /// The encoded ledger digest the witnesses are valid for
+    pub ledger_digest: String,
+
+    /// The ledger membership witnesses of the old records, as the encoded left and right
+    /// digests of each level of their merkle paths
+    pub old_witnesses: Vec<Vec<(String, String)>>,
+}
+
 /// Additional metadata included with a transaction response
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TransactionMetadata {
@@ -268,4 +1081,7 @@ pub struct TransactionRecipient {
     pub address: String,
     /// The amount being sent
     pub amount: u64,
+    /// The hex encoded payload of the record sent, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
 }
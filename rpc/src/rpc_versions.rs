@@ -0,0 +1,307 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for serving several versions of the RPC API.
+//!
+//! The endpoints implement the shapes of the first version, and the calls to a later version are
+//! rewritten by the breaking changes it introduced, so the clients of each version keep working
+//! until their version is removed.
+
+use crate::rpc_types::{Meta, SubscriptionMeta};
+use snarkos_errors::rpc::RpcErrorCode;
+use snarkos_profiler::METRICS;
+
+use jsonrpc_core::{
+    futures::{
+        future::{self, Either},
+        Future,
+    },
+    Call,
+    FutureOutput,
+    FutureResponse,
+    Metadata,
+    Middleware,
+    Output,
+    Params,
+    Value,
+    Version,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The versions of the RPC API
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcVersion {
+    /// The first version of the API
+    V1,
+    /// The version defaulting to the verification diagnostics and peer statistics
+    V2,
+}
+
+impl RpcVersion {
+    /// All the versions, in order.
+    pub const ALL: [RpcVersion; 2] = [RpcVersion::V1, RpcVersion::V2];
+
+    /// Returns the name of the version, used as a path and a method prefix.
+    pub fn name(self) -> &'static str {
+        match self {
+            RpcVersion::V1 => "v1",
+            RpcVersion::V2 => "v2",
+        }
+    }
+
+    /// Returns the version served at the given HTTP path, if any.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = path.trim_matches('/');
+        Self::ALL.iter().copied().find(|version| version.name() == name)
+    }
+
+    /// Returns the version of a method with a version prefix, such as `v2.getpeerinfo`,
+    /// and the unprefixed method.
+    pub fn from_method(method: &str) -> Option<(Self, &str)> {
+        let mut parts = method.splitn(2, '.');
+        let prefix = parts.next()?;
+        let method = parts.next()?;
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|version| version.name() == prefix)
+            .map(|version| (version, method))
+    }
+}
+
+/// The RPC metadata carrying the version requested by the transport of the calls.
+pub trait VersionedMeta: Metadata {
+    /// Returns the version requested by the transport, if any.
+    fn api_version(&self) -> Option<RpcVersion>;
+}
+
+impl VersionedMeta for Meta {
+    fn api_version(&self) -> Option<RpcVersion> {
+        self.api_version
+    }
+}
+
+/// The WebSocket calls only request a version with a method prefix.
+impl VersionedMeta for SubscriptionMeta {
+    fn api_version(&self) -> Option<RpcVersion> {
+        None
+    }
+}
+
+fn default_version() -> RpcVersion {
+    RpcVersion::V1
+}
+
+/// Defines the API versions served by the RPC server
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcVersionsConfig {
+    /// The version of the calls that do not request one
+    #[serde(default = "default_version")]
+    pub default_version: RpcVersion,
+    /// The versions still served, whose calls are counted by the `snarkos_rpc_deprecated_calls` metric
+    #[serde(default)]
+    pub deprecated: Vec<RpcVersion>,
+    /// The versions no longer served, whose calls are rejected
+    #[serde(default)]
+    pub removed: Vec<RpcVersion>,
+}
+
+impl Default for RpcVersionsConfig {
+    fn default() -> Self {
+        Self {
+            default_version: default_version(),
+            deprecated: vec![],
+            removed: vec![],
+        }
+    }
+}
+
+/// A breaking change of a method, applied to the calls of the version it was introduced in and later.
+struct BreakingChange {
+    /// The version introducing the change
+    version: RpcVersion,
+    /// The method changed
+    method: &'static str,
+    /// Rewrites the params of a call into the params of the endpoint
+    params: fn(Params) -> Params,
+    /// Rewrites the result of the endpoint into the result of the version
+    result: fn(Value) -> Value,
+}
+
+/// The breaking changes of the API, by order of version.
+const BREAKING_CHANGES: [BreakingChange; 2] = [
+    BreakingChange {
+        version: RpcVersion::V2,
+        method: "getpeerinfo",
+        params: verbose_peer_info_params,
+        result: peer_details_result,
+    },
+    BreakingChange {
+        version: RpcVersion::V2,
+        method: "validaterawtransaction",
+        params: stateful_validation_params,
+        result: unchanged_result,
+    },
+];
+
+fn unchanged_result(result: Value) -> Value {
+    result
+}
+
+/// Returns the positional params of a call, padded with nulls to the given length.
+/// The named params are returned unchanged as an error.
+fn padded_params(params: Params, length: usize) -> Result<Vec<Value>, Params> {
+    let mut params = match params {
+        Params::None => vec![],
+        Params::Array(params) => params,
+        params => return Err(params),
+    };
+
+    if params.len() < length {
+        params.resize(length, Value::Null);
+    }
+
+    Ok(params)
+}
+
+/// The `getpeerinfo` calls always return the statistics of the peers.
+fn verbose_peer_info_params(params: Params) -> Params {
+    match padded_params(params, 3) {
+        Ok(mut params) => {
+            params[2] = Value::Bool(true);
+            Params::Array(params)
+        }
+        Err(params) => params,
+    }
+}
+
+/// The `getpeerinfo` results list the statistics of the peers in place of their addresses.
+fn peer_details_result(mut result: Value) -> Value {
+    if let Some(result) = result.as_object_mut() {
+        if let Some(details) = result.remove("details") {
+            result.insert("peers".to_string(), details);
+        }
+    }
+
+    result
+}
+
+/// The `validaterawtransaction` calls return the stateful verification diagnostics by default.
+fn stateful_validation_params(params: Params) -> Params {
+    match padded_params(params, 2) {
+        Ok(mut params) => {
+            if params[1].is_null() {
+                params[1] = Value::String("stateful".to_string());
+            }
+            Params::Array(params)
+        }
+        Err(params) => params,
+    }
+}
+
+/// Serves the versions of the API, given by the prefix of the called methods or by the transport.
+/// The calls to a later version than the first are rewritten by the breaking changes of the version.
+#[derive(Clone, Debug, Default)]
+pub struct RpcVersions {
+    config: Arc<RpcVersionsConfig>,
+}
+
+impl RpcVersions {
+    /// Creates the versioning middleware of the given config.
+    pub fn new(config: RpcVersionsConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    /// Returns the config of the served versions.
+    pub fn config(&self) -> &RpcVersionsConfig {
+        &self.config
+    }
+}
+
+impl<M: VersionedMeta> Middleware<M> for RpcVersions {
+    type CallFuture = FutureOutput;
+    type Future = FutureResponse;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: future::Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        let mut method_call = match call {
+            Call::MethodCall(method_call) => method_call,
+            call => return Either::B(next(call, meta)),
+        };
+
+        let version = match RpcVersion::from_method(&method_call.method) {
+            Some((version, method)) => {
+                method_call.method = method.to_string();
+                version
+            }
+            None => meta.api_version().unwrap_or(self.config.default_version),
+        };
+
+        if self.config.removed.contains(&version) {
+            let error =
+                RpcErrorCode::InvalidRequest.error(format!("Version {} of the API was removed", version.name()));
+
+            return Either::A(Box::new(future::ok(Some(Output::from(
+                Err(error),
+                method_call.id,
+                Some(Version::V2),
+            )))));
+        }
+
+        if self.config.deprecated.contains(&version) {
+            METRICS.increment_counter(
+                "snarkos_rpc_deprecated_calls",
+                "The number of RPC calls to a deprecated version of the API",
+                1,
+            );
+        }
+
+        let changes: Vec<&BreakingChange> = BREAKING_CHANGES
+            .iter()
+            .filter(|change| change.version <= version && change.method == method_call.method)
+            .collect();
+
+        if changes.is_empty() {
+            return Either::B(next(Call::MethodCall(method_call), meta));
+        }
+
+        for change in &changes {
+            method_call.params = (change.params)(method_call.params);
+        }
+
+        let results: Vec<fn(Value) -> Value> = changes.iter().map(|change| change.result).collect();
+
+        Either::A(Box::new(next(Call::MethodCall(method_call), meta).map(move |output| {
+            output.map(|output| match output {
+                Output::Success(mut success) => {
+                    for result in &results {
+                        success.result = result(success.result);
+                    }
+                    Output::Success(success)
+                }
+                output => output,
+            })
+        })))
+    }
+}
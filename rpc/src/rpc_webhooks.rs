@@ -0,0 +1,307 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logic for notifying the chain events to webhooks.
+//!
+//! Each webhook has a thread checking the node state for the events it is configured for, and
+//! posting them to its URL in order, retrying the failed requests with an exponential backoff.
+
+use crate::{
+    rpc_subscriptions::{latest_canon_block_height, NOTIFICATION_INTERVAL, NOTIFIED_BLOCK_HASHES},
+    rpc_trait::RpcFunctions,
+    rpc_types::*,
+    RpcImpl,
+};
+use snarkos_consensus::ConsensusParameters;
+use snarkos_dpc::base_dpc::instantiated::Components;
+use snarkos_errors::rpc::RpcError;
+use snarkos_models::objects::Transaction;
+use snarkos_objects::{AccountViewKey, BlockHeaderHash};
+use snarkos_profiler::METRICS;
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use reqwest::{blocking::Client, header::CONTENT_TYPE, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, str::FromStr, thread, time::Duration};
+
+/// The header holding the event of a webhook notification.
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Snarkos-Event";
+
+/// The header holding the HMAC-SHA256 signature of a webhook notification, if the webhook has a secret.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Snarkos-Signature";
+
+/// The time after which a webhook request is failed.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The delay before the first retry of a failed webhook request, which doubles with each retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximum delay between two retries of a failed webhook request.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The block size of SHA-256, used to pad the HMAC keys.
+const SHA256_BLOCK_SIZE: usize = 64;
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+/// Defines a webhook notified of chain events
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RpcWebhookConfig {
+    /// The URL the notifications are posted to
+    pub url: String,
+    /// The events notified to the webhook
+    pub events: Vec<WebhookEvent>,
+    /// The account view key decrypting the records of the notified transactions
+    #[serde(default)]
+    pub account_view_key: Option<String>,
+    /// The secret signing the notifications
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// The number of times a failed notification is retried before it is dropped
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+/// Returns the HMAC-SHA256 of the message with the given key.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut padded_key = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        padded_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        padded_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(padded_key.iter().map(|byte| byte ^ 0x36).collect::<Vec<u8>>());
+    inner.update(message);
+
+    let mut outer = Sha256::new();
+    outer.update(padded_key.iter().map(|byte| byte ^ 0x5c).collect::<Vec<u8>>());
+    outer.update(inner.finalize());
+
+    outer.finalize().to_vec()
+}
+
+/// Returns the value of the signature header of a notification body signed with the given secret.
+pub fn webhook_signature(secret: &str, body: &str) -> String {
+    format!(
+        "sha256={}",
+        hex::encode(hmac_sha256(secret.as_bytes(), body.as_bytes()))
+    )
+}
+
+fn event_name(event: WebhookEvent) -> &'static str {
+    match event {
+        WebhookEvent::Block => "block",
+        WebhookEvent::Reorg => "reorg",
+        WebhookEvent::Transaction => "transaction",
+    }
+}
+
+/// The chain state already notified to a webhook.
+pub struct WebhookState {
+    /// The hashes of the latest notified canon blocks, by height
+    block_hashes: BTreeMap<u32, String>,
+}
+
+/// Notifies the chain events to a webhook.
+/// The chain state is read through the public RPC endpoints.
+pub struct RpcWebhook {
+    rpc: RpcImpl,
+    config: RpcWebhookConfig,
+    view_key: Option<AccountViewKey<Components>>,
+}
+
+impl RpcWebhook {
+    /// Creates the webhook of the given config, for the node served by the given RPC endpoints.
+    pub fn new(rpc: RpcImpl, config: RpcWebhookConfig) -> Result<Self, RpcError> {
+        let url = Url::parse(&config.url).map_err(|error| RpcError::InvalidWebhookConfig(error.to_string()))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(RpcError::InvalidWebhookConfig(format!(
+                "{} is not an HTTP URL",
+                config.url
+            )));
+        }
+
+        let view_key = match (
+            config.events.contains(&WebhookEvent::Transaction),
+            &config.account_view_key,
+        ) {
+            (true, Some(view_key)) => Some(AccountViewKey::<Components>::from_str(view_key)?),
+            (true, None) => {
+                return Err(RpcError::InvalidWebhookConfig(
+                    "the transaction event requires an account view key".to_string(),
+                ));
+            }
+            (false, _) => None,
+        };
+
+        Ok(Self { rpc, config, view_key })
+    }
+
+    /// Starts a thread that notifies the webhook of the chain events.
+    pub fn start(self) -> Result<(), RpcError> {
+        let mut state = self.state()?;
+
+        thread::spawn(move || {
+            let client = match Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+                Ok(client) => client,
+                Err(_) => return,
+            };
+
+            loop {
+                // A failed check is retried with the next one, as the notified state is only updated on success.
+                if let Ok(notifications) = self.notifications(&mut state) {
+                    for notification in notifications {
+                        self.deliver(&client, &notification);
+                    }
+                }
+
+                thread::sleep(NOTIFICATION_INTERVAL);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Returns the current chain state, so only the later events are notified.
+    pub fn state(&self) -> Result<WebhookState, RpcError> {
+        let latest_block_height = self.rpc.get_block_count()? - 1;
+
+        let mut block_hashes = BTreeMap::new();
+        block_hashes.insert(latest_block_height, self.rpc.get_block_hash(latest_block_height)?);
+
+        Ok(WebhookState { block_hashes })
+    }
+
+    /// Returns the notifications of the chain events since the given state, in order.
+    pub fn notifications(&self, state: &mut WebhookState) -> Result<Vec<WebhookNotification>, RpcError> {
+        let mut block_hashes = state.block_hashes.clone();
+        let mut notifications = vec![];
+
+        let latest_block_height = self.rpc.get_block_count()? - 1;
+        let block_height = latest_canon_block_height(&self.rpc, &block_hashes, latest_block_height)?;
+
+        let reverted_block_hashes: Vec<String> = block_hashes
+            .split_off(&(block_height + 1))
+            .into_iter()
+            .map(|(_, block_hash)| block_hash)
+            .collect();
+
+        if !reverted_block_hashes.is_empty() && self.config.events.contains(&WebhookEvent::Reorg) {
+            notifications.push(WebhookNotification::Reorg(ReorgNotification {
+                fork_block_height: block_height,
+                reverted_block_hashes,
+            }));
+        }
+
+        for block_height in (block_height + 1)..=latest_block_height {
+            let block_hash = self.rpc.get_block_hash(block_height)?;
+
+            if self.config.events.contains(&WebhookEvent::Block) {
+                notifications.push(WebhookNotification::Block(self.rpc.get_block(block_hash.clone())?));
+            }
+
+            if let Some(view_key) = &self.view_key {
+                let block = self
+                    .rpc
+                    .storage
+                    .get_block(&BlockHeaderHash::new(hex::decode(&block_hash)?))?;
+
+                for transaction in block.transactions.iter() {
+                    let records =
+                        ConsensusParameters::decrypt_transaction_records(&self.rpc.parameters, view_key, transaction)?;
+                    if records.is_empty() {
+                        continue;
+                    }
+
+                    notifications.push(WebhookNotification::Transaction(TransactionNotification {
+                        block_height,
+                        block_hash: block_hash.clone(),
+                        txid: hex::encode(transaction.transaction_id()?),
+                        records: records
+                            .iter()
+                            .map(|record| Ok(hex::encode(to_bytes![record]?)))
+                            .collect::<Result<_, RpcError>>()?,
+                    }));
+                }
+            }
+
+            block_hashes.insert(block_height, block_hash);
+        }
+
+        // Keep the hashes of the latest blocks only
+        state.block_hashes = block_hashes.split_off(&latest_block_height.saturating_sub(NOTIFIED_BLOCK_HASHES));
+
+        Ok(notifications)
+    }
+
+    /// Posts a notification to the webhook, retrying the failed requests with an exponential backoff.
+    /// The notification is dropped once its retries failed.
+    pub fn deliver(&self, client: &Client, notification: &WebhookNotification) {
+        let body = match serde_json::to_string(notification) {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+
+        for retry in 0..=self.config.max_retries {
+            if retry > 0 {
+                thread::sleep(retry_delay);
+                retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+            }
+
+            if self.post(client, notification.event(), &body).is_ok() {
+                return;
+            }
+        }
+
+        METRICS.increment_counter(
+            "snarkos_rpc_failed_webhooks",
+            "The number of webhook notifications dropped after their retries failed",
+            1,
+        );
+    }
+
+    fn post(&self, client: &Client, event: WebhookEvent, body: &str) -> Result<(), RpcError> {
+        let mut request = client
+            .post(&self.config.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_EVENT_HEADER, event_name(event));
+
+        if let Some(secret) = &self.config.secret {
+            request = request.header(WEBHOOK_SIGNATURE_HEADER, webhook_signature(secret, body));
+        }
+
+        let response = request
+            .body(body.to_string())
+            .send()
+            .map_err(|error| RpcError::Message(error.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(RpcError::Message(format!(
+                "the webhook answered with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -16,15 +16,18 @@
 
 /// Tests for protected RPC endpoints
 mod protected_rpc_tests {
-    use snarkos_consensus::{memory_pool::MemoryPool, MerkleTreeLedger};
+    use snarkos_algorithms::merkle_tree::MerklePath;
+    use snarkos_consensus::{memory_pool::MemoryPool, BlockRequests, MerkleTreeLedger, TransactionMemo};
     use snarkos_dpc::base_dpc::{
         instantiated::{Components, Tx},
         parameters::PublicParameters,
         record::DPCRecord,
+        record_payload::RecordPayload,
     };
-    use snarkos_models::dpc::Record;
+    use snarkos_errors::rpc::RpcErrorCode;
+    use snarkos_models::dpc::{DPCComponents, Record};
     use snarkos_network::{external::SyncHandler, internal::context::Context};
-    use snarkos_objects::{AccountAddress, AccountPrivateKey, AccountViewKey};
+    use snarkos_objects::{AccountAddress, AccountPrivateKey, AccountViewKey, Network};
     use snarkos_rpc::*;
     use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, network::*, storage::*};
     use snarkos_utilities::{
@@ -32,13 +35,39 @@ mod protected_rpc_tests {
         to_bytes,
     };
 
-    use jsonrpc_core::MetaIoHandler;
+    use jsonrpc_core::{
+        futures::{sync::mpsc, Stream},
+        MetaIoHandler,
+    };
+    use jsonrpc_pubsub::{PubSubHandler, Session};
+    use rand::thread_rng;
     use serde_json::Value;
     use std::{str::FromStr, sync::Arc};
     use tokio::sync::Mutex;
 
     const TEST_USERNAME: &str = "TEST_USERNAME";
     const TEST_PASSWORD: &str = "TEST_PASSWORD";
+    const TEST_READ_ONLY_TOKEN: &str = "TEST_READ_ONLY_TOKEN";
+    const TEST_ADMIN_TOKEN: &str = "TEST_ADMIN_TOKEN";
+    const TEST_LOG_FILTER: &str = "debug";
+    const TEST_RELOADED_SETTING: &str = "log";
+
+    fn token_authentication(token: &str) -> Meta {
+        Meta {
+            auth: Some(format!("Bearer {}", token)),
+            is_local: false,
+            api_version: None,
+        }
+    }
+
+    fn make_admin_request(rpc: &MetaIoHandler<Meta>, method: &str, params: Value) -> Value {
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = rpc
+            .handle_request_sync(&request.to_string(), token_authentication(TEST_ADMIN_TOKEN))
+            .unwrap();
+
+        serde_json::from_str(&response).unwrap()
+    }
 
     fn invalid_authentication() -> Meta {
         let basic_auth_encoding = format!(
@@ -48,6 +77,8 @@ mod protected_rpc_tests {
 
         Meta {
             auth: Some(basic_auth_encoding),
+            is_local: false,
+            api_version: None,
         }
     }
 
@@ -59,6 +90,8 @@ mod protected_rpc_tests {
 
         Meta {
             auth: Some(basic_auth_encoding),
+            is_local: false,
+            api_version: None,
         }
     }
 
@@ -66,6 +99,14 @@ mod protected_rpc_tests {
         storage: &Arc<MerkleTreeLedger>,
         parameters: PublicParameters<Components>,
     ) -> MetaIoHandler<Meta> {
+        let mut io = jsonrpc_core::MetaIoHandler::default();
+
+        initialize_test_rpc_impl(storage, parameters).add_protected(&mut io);
+
+        io
+    }
+
+    fn initialize_test_rpc_impl(storage: &Arc<MerkleTreeLedger>, parameters: PublicParameters<Components>) -> RpcImpl {
         let server_address = random_socket_address();
         let consensus = TEST_CONSENSUS.clone();
 
@@ -82,24 +123,40 @@ mod protected_rpc_tests {
 
         let context = Context::new(server_address, 5, 1, 10, true, vec![], false);
 
+        let tokens = vec![
+            RpcToken {
+                token: TEST_READ_ONLY_TOKEN.to_string(),
+                scope: RpcScope::ReadOnly,
+            },
+            RpcToken {
+                token: TEST_ADMIN_TOKEN.to_string(),
+                scope: RpcScope::Admin,
+            },
+        ];
+
         let storage = storage.clone();
         let storage_path = storage.storage.db.path().to_path_buf();
+        let primary_storage = storage.clone();
 
-        let rpc_impl = RpcImpl::new(
+        RpcImpl::new(
             storage,
             storage_path,
+            primary_storage,
             parameters,
             Arc::new(context),
             consensus,
             memory_pool_lock,
             sync_handler_lock,
             Some(credentials),
-        );
-        let mut io = jsonrpc_core::MetaIoHandler::default();
-
-        rpc_impl.add_protected(&mut io);
-
-        io
+            tokens,
+            Some(Arc::new(|filter: &str| match filter {
+                TEST_LOG_FILTER => Ok(()),
+                _ => Err(format!("invalid log filter {}", filter)),
+            })),
+        )
+        .unwrap()
+        .with_block_requests(Arc::new(BlockRequests::new()))
+        .with_config_reloader(Arc::new(|| Ok(vec![TEST_RELOADED_SETTING.to_string()])))
     }
 
     #[test]
@@ -117,6 +174,339 @@ mod protected_rpc_tests {
 
         let expected_result = Value::String("Authentication Error".to_string());
         assert_eq!(extracted["error"]["message"], expected_result);
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::Unauthenticated.code());
+        assert_eq!(extracted["error"]["data"]["kind"], "unauthenticated");
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_local_authentication() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        // The clients of the Unix domain socket call the private endpoints without credentials
+        let meta = Meta {
+            auth: None,
+            is_local: true,
+            api_version: None,
+        };
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getrecordcommitmentcount\" }";
+        let response = rpc.handle_request_sync(request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rpc_ipc_server() {
+        use jsonrpc_core::futures::future;
+        use rand::Rng;
+        use std::{
+            io::{BufRead, BufReader, Write},
+            os::unix::{fs::PermissionsExt, net::UnixStream},
+        };
+
+        let mut io = MetaIoHandler::<Meta>::default();
+        io.add_method_with_meta("islocal", |_params, meta: Meta| future::ok(Value::Bool(meta.is_local)));
+
+        let path = std::env::temp_dir().join(format!("test_rpc_ipc_{}.sock", thread_rng().gen::<u64>()));
+        let config = RpcIpcConfig {
+            path: path.clone(),
+            mode: DEFAULT_IPC_SOCKET_MODE,
+            ipc_only: true,
+        };
+
+        start_ipc_server(&config, io, Some(128)).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, DEFAULT_IPC_SOCKET_MODE);
+
+        // Each line holds a request, answered by a line holding its response
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"islocal\" }}").unwrap();
+        writeln!(stream, "{{ \"jsonrpc\":\"2.0\", \"id\": 2, \"method\": \"islocal\" }}").unwrap();
+
+        let mut responses = BufReader::new(stream).lines();
+        for id in 1..=2 {
+            let extracted: Value = serde_json::from_str(&responses.next().unwrap().unwrap()).unwrap();
+
+            assert_eq!(extracted["id"], id);
+            assert_eq!(extracted["result"], true);
+        }
+
+        // A connection sending a request larger than the maximum request size is closed
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "{}", " ".repeat(256)).unwrap();
+
+        assert!(BufReader::new(stream).lines().next().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rpc_token_scopes() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getrecordcommitmentcount\" }";
+        let response = rpc
+            .handle_request_sync(request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        // The read-only scope does not grant the wallet endpoints
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"createaccount\" }";
+        let response = rpc
+            .handle_request_sync(request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Permission Error");
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::PermissionDenied.code());
+        assert_eq!(extracted["error"]["data"]["kind"], "permission_denied");
+
+        // An unknown token grants no scope
+        let response = rpc
+            .handle_request_sync(request, token_authentication("INVALID_TOKEN"))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Authentication Error");
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_remove_memory_pool_transaction() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"removemempooltransaction\", \"params\": [\"{}\"] }}",
+            hex::encode([0u8; 32])
+        );
+
+        // The read-only scope does not grant the admin endpoints
+        let response = rpc
+            .handle_request_sync(&request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Permission Error");
+
+        // The transaction is not in the memory pool
+        let response = rpc
+            .handle_request_sync(&request, token_authentication(TEST_ADMIN_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], false);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_peer_management() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let peer_address = "127.0.0.1:4131";
+
+        // The connection is only requested once
+        let extracted = make_admin_request(&rpc, "addpeer", serde_json::json!([peer_address]));
+        assert_eq!(extracted["result"], true);
+
+        let extracted = make_admin_request(&rpc, "addpeer", serde_json::json!([peer_address]));
+        assert_eq!(extracted["result"], false);
+
+        let extracted = make_admin_request(&rpc, "addpeer", serde_json::json!(["INVALID_ADDRESS"]));
+        assert!(extracted["error"].is_object());
+
+        // The peer is not connected
+        let extracted = make_admin_request(&rpc, "removepeer", serde_json::json!([peer_address]));
+        assert_eq!(extracted["result"], false);
+
+        // Banned peers can not be added
+        let extracted = make_admin_request(&rpc, "banpeer", serde_json::json!(["127.0.0.1", 3600]));
+        assert!(extracted["result"].as_i64().unwrap() > chrono::Utc::now().timestamp());
+
+        let extracted = make_admin_request(&rpc, "addpeer", serde_json::json!([peer_address]));
+        assert!(extracted["error"].is_object());
+
+        let extracted = make_admin_request(&rpc, "unbanpeer", serde_json::json!([peer_address]));
+        assert_eq!(extracted["result"], true);
+
+        let extracted = make_admin_request(&rpc, "unbanpeer", serde_json::json!(["127.0.0.1"]));
+        assert_eq!(extracted["result"], false);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_set_log_level() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let extracted = make_admin_request(&rpc, "setloglevel", serde_json::json!([TEST_LOG_FILTER]));
+        assert_eq!(extracted["result"], true);
+
+        let extracted = make_admin_request(&rpc, "setloglevel", serde_json::json!(["INVALID_FILTER"]));
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_generate_blocks() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        // No miner takes the requested blocks
+        let extracted = make_admin_request(&rpc, "generateblocks", serde_json::json!([2]));
+        assert_eq!(extracted["result"], 2);
+
+        let extracted = make_admin_request(&rpc, "generateblocks", serde_json::json!([3]));
+        assert_eq!(extracted["result"], 5);
+
+        let extracted = make_admin_request(&rpc, "generateblocks", serde_json::json!(["INVALID_COUNT"]));
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_reload_config() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let extracted = make_admin_request(&rpc, "reloadconfig", serde_json::json!([]));
+        assert_eq!(extracted["result"], serde_json::json!([TEST_RELOADED_SETTING]));
+
+        // The configuration is only reloaded with the admin scope
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"reloadconfig\", \"params\": [] }";
+        let response = rpc
+            .handle_request_sync(request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_revalidate_memory_pool() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let extracted = make_admin_request(&rpc, "revalidatemempool", serde_json::json!([]));
+        assert_eq!(extracted["result"], 0);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_rotate_token() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"rotatetoken\", \"params\": [\"{}\"] }}",
+            TEST_READ_ONLY_TOKEN
+        );
+
+        // The read-only scope does not grant the admin endpoints
+        let response = rpc
+            .handle_request_sync(&request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Permission Error");
+
+        let response = rpc
+            .handle_request_sync(&request, token_authentication(TEST_ADMIN_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let new_token = extracted["result"].as_str().unwrap().to_string();
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getrecordcommitmentcount\" }";
+
+        let response = rpc
+            .handle_request_sync(request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Authentication Error");
+
+        let response = rpc
+            .handle_request_sync(request, token_authentication(&new_token))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        // The rotation is stored, so the configured token stays revoked after a restart
+        drop(rpc);
+        let rpc = initialize_test_rpc(&storage, load_verifying_parameters());
+
+        let response = rpc
+            .handle_request_sync(request, token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Authentication Error");
+
+        let response = rpc
+            .handle_request_sync(request, token_authentication(&new_token))
+            .unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        // Only the hashes of the tokens are stored
+        let rotations = String::from_utf8(storage.get_rpc_token_rotations().unwrap()).unwrap();
+        assert!(!rotations.contains(&new_token));
+        assert!(rotations.contains(&token_hash(&new_token)));
+
+        // Rotations that cannot be read are not ignored, as the revoked tokens would be valid again
+        storage.store_rpc_token_rotations(b"invalid".to_vec()).unwrap();
+        assert!(TokenRotations::load(&storage).is_err());
 
         drop(rpc);
         kill_storage_sync(storage);
@@ -309,10 +699,19 @@ mod protected_rpc_tests {
         let old_records = vec![hex::encode(to_bytes![DATA.records_1[0]].unwrap())];
         let old_account_private_keys = vec![sender.private_key.to_string()];
 
-        let recipients = vec![TransactionRecipient {
-            address: receiver.address.to_string(),
-            amount: 100,
-        }];
+        // The recipients are sent records of their own values and payloads
+        let recipients = vec![
+            TransactionRecipient {
+                address: receiver.address.to_string(),
+                amount: 100,
+                payload: None,
+            },
+            TransactionRecipient {
+                address: sender.address.to_string(),
+                amount: 50,
+                payload: Some(hex::encode([7u8; 32])),
+            },
+        ];
 
         let network_id = 0;
 
@@ -324,6 +723,19 @@ mod protected_rpc_tests {
             network_id,
         };
 
+        // A payload of the wrong size is rejected
+        let mut invalid_params = params.clone();
+        invalid_params.recipients[1].payload = Some(hex::encode([7u8; 16]));
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": [invalid_params],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::InvalidParams.code());
+
         let params = serde_json::to_value(params).unwrap();
         let request = format!(
             "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"{}\", \"params\": [{}] }}",
@@ -335,11 +747,18 @@ mod protected_rpc_tests {
 
         let result = extracted["result"].clone();
 
+        let mut records = vec![];
         for record_value in result["encoded_records"].as_array().unwrap() {
             let record_bytes = hex::decode(record_value.as_str().unwrap()).unwrap();
-            let _record: DPCRecord<Components> = FromBytes::read(&record_bytes[..]).unwrap();
+            let record: DPCRecord<Components> = FromBytes::read(&record_bytes[..]).unwrap();
+            records.push(record);
         }
 
+        assert_eq!(records[0].value(), 100);
+        assert_eq!(records[0].payload(), &RecordPayload::default());
+        assert_eq!(records[1].value(), 50);
+        assert_eq!(records[1].payload(), &RecordPayload::from_bytes(&[7u8; 32]));
+
         let transaction_string = result["encoded_transaction"].as_str().unwrap();
         let transaction_bytes = hex::decode(transaction_string).unwrap();
         let _transaction: Tx = FromBytes::read(&transaction_bytes[..]).unwrap();
@@ -348,6 +767,348 @@ mod protected_rpc_tests {
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_create_transaction_skeleton() {
+        let storage = Arc::new(FIXTURE.ledger());
+        let parameters = FIXTURE.parameters.clone();
+        let meta = authentication();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        consensus
+            .receive_block(&parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        let io = initialize_test_rpc(&storage, parameters.clone());
+
+        let [sender, receiver, _] = &FIXTURE_VK.test_accounts;
+
+        let skeleton_inputs = TransactionSkeletonInputs {
+            old_records: vec![hex::encode(to_bytes![DATA.records_1[0]].unwrap())],
+            sender_address: sender.address.to_string(),
+            recipients: vec![TransactionRecipient {
+                address: receiver.address.to_string(),
+                amount: 100,
+                payload: None,
+            }],
+            memo: None,
+            randomness: hex::encode([1u8; 32]),
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "createtransactionskeleton",
+            "params": [skeleton_inputs],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        let skeleton: TransactionSkeleton = serde_json::from_value(extracted["result"].clone()).unwrap();
+
+        // The skeleton is reproduced from the same randomness
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], serde_json::to_value(&skeleton).unwrap());
+
+        // The addresses encoded for the network of the node are accepted, and those of another network rejected
+        let mut network_inputs = skeleton_inputs.clone();
+        network_inputs.recipients[0].address = receiver.address.to_network_string(consensus.network);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "createtransactionskeleton",
+            "params": [network_inputs],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], serde_json::to_value(&skeleton).unwrap());
+
+        network_inputs.recipients[0].address = receiver.address.to_network_string(Network::Custom(7));
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "createtransactionskeleton",
+            "params": [network_inputs],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::InvalidParams.code());
+
+        assert_eq!(skeleton.old_records.len(), Components::NUM_INPUT_RECORDS);
+        assert_eq!(skeleton.new_record_owners.len(), Components::NUM_OUTPUT_RECORDS);
+        assert_eq!(skeleton.new_is_dummy_flags, vec![false, true]);
+        assert_eq!(skeleton.new_values, vec![100, 0]);
+
+        // Sign the transaction without the ledger, and prove it on the node
+        let decode = |encoded: &String| hex::decode(encoded).unwrap();
+
+        let old_records: Vec<DPCRecord<Components>> = skeleton
+            .old_records
+            .iter()
+            .map(|record| FromBytes::read(&decode(record)[..]).unwrap())
+            .collect();
+        let old_account_private_keys = vec![sender.private_key.clone(); Components::NUM_INPUT_RECORDS];
+        let new_record_owners = skeleton
+            .new_record_owners
+            .iter()
+            .map(|address| AccountAddress::<Components>::from_str(address).unwrap())
+            .collect();
+        let new_payloads = skeleton
+            .new_payloads
+            .iter()
+            .map(|payload| FromBytes::read(&decode(payload)[..]).unwrap())
+            .collect();
+        let new_birth_program_ids = skeleton.new_birth_program_ids.iter().map(decode).collect();
+        let new_death_program_ids = skeleton.new_death_program_ids.iter().map(decode).collect();
+
+        let mut memo = [0u8; 32];
+        memo.copy_from_slice(&decode(&skeleton.memo));
+
+        let ledger_digest = FromBytes::read(&decode(&skeleton.ledger_digest)[..]).unwrap();
+        let old_witnesses = skeleton
+            .old_witnesses
+            .iter()
+            .map(|path| MerklePath {
+                parameters: storage.ledger_parameters.clone(),
+                path: path
+                    .iter()
+                    .map(|(left, right)| {
+                        (
+                            FromBytes::read(&decode(left)[..]).unwrap(),
+                            FromBytes::read(&decode(right)[..]).unwrap(),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let authorization = consensus
+            .authorize_transaction(
+                &parameters,
+                old_records,
+                old_account_private_keys,
+                new_record_owners,
+                new_birth_program_ids,
+                new_death_program_ids,
+                skeleton.new_is_dummy_flags,
+                skeleton.new_values,
+                new_payloads,
+                TransactionMemo::Plaintext(memo),
+                ledger_digest,
+                old_witnesses,
+                &mut thread_rng(),
+            )
+            .unwrap();
+
+        // The authorization holds no private keys
+        let authorization_bytes = to_bytes![authorization].unwrap();
+        let private_key_bytes = to_bytes![sender.private_key.sk_sig].unwrap();
+        assert!(!authorization_bytes
+            .windows(private_key_bytes.len())
+            .any(|window| window == &private_key_bytes[..]));
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "provetransaction",
+            "params": [hex::encode(&authorization_bytes)],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        let output: CreateRawTransactionOuput = serde_json::from_value(extracted["result"].clone()).unwrap();
+        let transaction: Tx = FromBytes::read(&decode(&output.encoded_transaction)[..]).unwrap();
+
+        assert_eq!(output.encoded_records.len(), Components::NUM_OUTPUT_RECORDS);
+        assert!(consensus
+            .verify_transaction(&parameters, &transaction, &storage)
+            .unwrap());
+
+        // A tampered authorization is rejected before proving
+        let mut tampered_authorization = authorization;
+        tampered_authorization.new_records.swap(0, 1);
+        tampered_authorization.new_commitments.swap(0, 1);
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "provetransaction",
+            "params": [hex::encode(to_bytes![tampered_authorization].unwrap())],
+        });
+        let response = io.handle_request_sync(&request.to_string(), meta).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_jobs() {
+        let storage = Arc::new(FIXTURE.ledger());
+        let parameters = FIXTURE.parameters.clone();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        consensus
+            .receive_block(&parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let [sender, receiver, _] = &FIXTURE_VK.test_accounts;
+
+        let transaction_inputs = TransactionInputs {
+            old_records: vec![hex::encode(to_bytes![DATA.records_1[0]].unwrap())],
+            old_account_private_keys: vec![sender.private_key.to_string()],
+            recipients: vec![TransactionRecipient {
+                address: receiver.address.to_string(),
+                amount: 100,
+                payload: None,
+            }],
+            memo: None,
+            network_id: 0,
+        };
+
+        let params = serde_json::json!(["createrawtransaction", [transaction_inputs]]);
+
+        // The read-only scope does not grant the job endpoints
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "submitjob", "params": params.clone() });
+        let response = rpc
+            .handle_request_sync(&request.to_string(), token_authentication(TEST_READ_ONLY_TOKEN))
+            .unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Permission Error");
+
+        // Only the long calls are run as jobs
+        let extracted = make_admin_request(&rpc, "submitjob", serde_json::json!(["getrecordcommitments", []]));
+
+        assert_eq!(
+            extracted["error"]["message"],
+            "unsupported job method: getrecordcommitments"
+        );
+
+        let extracted = make_admin_request(&rpc, "submitjob", params);
+        let job_id = extracted["result"].as_str().unwrap().to_string();
+
+        // The result is not available until the job completes
+        let extracted = make_admin_request(&rpc, "getjobresult", serde_json::json!([job_id]));
+
+        assert_eq!(
+            extracted["error"]["message"],
+            format!("job is not completed: {}", job_id)
+        );
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::JobNotCompleted.code());
+
+        let job_info: JobInfo = loop {
+            let extracted = make_admin_request(&rpc, "getjobstatus", serde_json::json!([job_id]));
+            let job_info: JobInfo = serde_json::from_value(extracted["result"].clone()).unwrap();
+
+            if job_info.status.is_finished() {
+                break job_info;
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        };
+
+        assert_eq!(job_info.status, JobStatus::Completed);
+        assert_eq!(job_info.method, "createrawtransaction");
+        assert!(job_info.finished.is_some());
+
+        let extracted = make_admin_request(&rpc, "getjobresult", serde_json::json!([job_id]));
+        let result = extracted["result"].clone();
+
+        for record_value in result["encoded_records"].as_array().unwrap() {
+            let record_bytes = hex::decode(record_value.as_str().unwrap()).unwrap();
+            let _record: DPCRecord<Components> = FromBytes::read(&record_bytes[..]).unwrap();
+        }
+
+        let transaction_bytes = hex::decode(result["encoded_transaction"].as_str().unwrap()).unwrap();
+        let _transaction: Tx = FromBytes::read(&transaction_bytes[..]).unwrap();
+
+        // A finished job cannot be cancelled
+        let extracted = make_admin_request(&rpc, "canceljob", serde_json::json!([job_id]));
+
+        assert_eq!(extracted["result"], false);
+
+        let extracted = make_admin_request(&rpc, "canceljob", serde_json::json!(["unknown"]));
+
+        assert_eq!(extracted["error"]["message"], "unknown job: unknown");
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::NotFound.code());
+
+        // The jobs are stored with their results
+        let stored_job = JobQueue::load(&storage).get(&job_id).unwrap();
+
+        assert_eq!(stored_job.info, job_info);
+        assert_eq!(stored_job.result, Some(result));
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_subscribe_jobs() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage, load_verifying_parameters());
+
+        let mut rpc = MetaIoHandler::default();
+        rpc_impl.add_protected(&mut rpc);
+
+        let subscriptions = RpcSubscriptionsImpl::new(rpc_impl);
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(subscriptions.to_delegate());
+
+        let (sender, receiver) = mpsc::channel(8);
+        let meta = SubscriptionMeta {
+            session: Some(Arc::new(Session::new(sender))),
+        };
+
+        // The subscription requires the scope of the job endpoints
+        let params = serde_json::json!([format!("Bearer {}", TEST_READ_ONLY_TOKEN)]);
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "subscribeJobs", "params": params });
+        let response = io.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["message"], "Permission Error");
+
+        let params = serde_json::json!([format!("Bearer {}", TEST_ADMIN_TOKEN)]);
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "subscribeJobs", "params": params });
+        let response = io.handle_request_sync(&request.to_string(), meta).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let subscription_id = extracted["result"].clone();
+
+        let mut state = subscriptions.notification_state().unwrap();
+
+        // The jobs of the other clients are not notified
+        let params = serde_json::json!(["provetransaction", ["00"]]);
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "submitjob", "params": params });
+        rpc.handle_request_sync(&request.to_string(), authentication()).unwrap();
+
+        subscriptions.notify(&mut state).unwrap();
+
+        let extracted = make_admin_request(&rpc, "submitjob", serde_json::json!(["provetransaction", ["00"]]));
+        let job_id = extracted["result"].clone();
+
+        subscriptions.notify(&mut state).unwrap();
+
+        let notification = receiver.wait().next().unwrap().unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+
+        assert_eq!(notification["method"], "jobs");
+        assert_eq!(notification["params"]["subscription"], subscription_id);
+        assert_eq!(notification["params"]["result"]["id"], job_id);
+
+        drop(rpc);
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_create_account() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -380,4 +1141,90 @@ mod protected_rpc_tests {
         drop(rpc);
         kill_storage_sync(storage);
     }
+
+    #[test]
+    fn test_rpc_wallet() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let system_parameters = &FIXTURE_VK.parameters.system_parameters;
+        let [miner_acc, other_acc, _] = FIXTURE_VK.test_accounts.clone();
+
+        storage.insert_and_commit(&DATA.block_1).unwrap();
+
+        let view_key = AccountViewKey::<Components>::from_private_key(
+            &system_parameters.account_signature,
+            &system_parameters.account_commitment,
+            &miner_acc.private_key,
+        )
+        .unwrap()
+        .to_string();
+
+        let request = |method: &str, params: Value| -> Value {
+            let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+            let response = rpc.handle_request_sync(&request.to_string(), meta.clone()).unwrap();
+
+            serde_json::from_str(&response).unwrap()
+        };
+
+        // An unregistered account has no balance
+        let extracted = request("getbalance", serde_json::json!([view_key]));
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::NotFound.code());
+
+        // The private key must be the key of the view key
+        let input = WalletAccountInput {
+            account_view_key: view_key.clone(),
+            account_private_key: Some(other_acc.private_key.to_string()),
+        };
+        let extracted = request("addwalletaccount", serde_json::json!([input]));
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::InvalidParams.code());
+
+        let input = WalletAccountInput {
+            account_view_key: view_key.clone(),
+            account_private_key: Some(miner_acc.private_key.to_string()),
+        };
+        let extracted = request("addwalletaccount", serde_json::json!([input]));
+        assert_eq!(extracted["result"], true);
+
+        // The records of the canon blocks are found when the account is registered
+        let records_value: u64 = DATA
+            .records_1
+            .iter()
+            .filter(|record| !record.is_dummy() && record.owner() == &miner_acc.address)
+            .map(|record| record.value())
+            .sum();
+        assert!(records_value > 0);
+
+        let extracted = request("getbalance", serde_json::json!([view_key, 0]));
+        let balance: WalletBalanceInfo = serde_json::from_value(extracted["result"].clone()).unwrap();
+        assert_eq!(balance, WalletBalanceInfo {
+            confirmed: records_value,
+            pending: 0,
+            tracks_spends: true,
+        });
+
+        // The records of the latest block have no confirmation yet
+        let extracted = request("getbalance", serde_json::json!([view_key]));
+        let balance: WalletBalanceInfo = serde_json::from_value(extracted["result"].clone()).unwrap();
+        assert_eq!((balance.confirmed, balance.pending), (0, records_value));
+
+        let extracted = request("listtransactions", serde_json::json!([view_key]));
+        let transactions: Vec<WalletTransactionInfo> = serde_json::from_value(extracted["result"].clone()).unwrap();
+        assert!(!transactions.is_empty());
+        assert!(transactions.iter().all(|transaction| transaction.block_height == 1));
+        assert_eq!(
+            transactions.iter().map(|transaction| transaction.received).sum::<u64>(),
+            records_value
+        );
+
+        let extracted = request("removewalletaccount", serde_json::json!([view_key]));
+        assert_eq!(extracted["result"], true);
+        let extracted = request("listtransactions", serde_json::json!([view_key]));
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::NotFound.code());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
 }
@@ -16,10 +16,15 @@
 
 /// Tests for public RPC endpoints
 mod rpc_tests {
-    use snarkos_consensus::{get_block_reward, MerkleTreeLedger};
-    use snarkos_dpc::base_dpc::instantiated::Tx;
-    use snarkos_models::objects::Transaction;
+    use snarkos_algorithms::merkle_tree::MerklePath;
+    use snarkos_consensus::{get_block_reward, memory_pool::MemoryPool, MerkleTreeLedger};
+    use snarkos_dpc::base_dpc::instantiated::{Components, Tx};
+    use snarkos_errors::rpc::RpcErrorCode;
+    use snarkos_models::{dpc::Record, objects::Transaction};
+    use snarkos_network::external::{PeerId, SyncHandler};
+    use snarkos_objects::{AccountViewKey, Block, BlockHeaderHash, PaymentRequest};
     use snarkos_rpc::*;
+    use snarkos_storage::StorageMetrics;
     use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, network::*, storage::*};
     use snarkos_utilities::{
         bytes::{FromBytes, ToBytes},
@@ -27,11 +32,34 @@ mod rpc_tests {
         to_bytes,
     };
 
+    use flate2::read::GzDecoder;
+    use jsonrpc_core::{
+        futures::{sync::mpsc, Future, Stream},
+        MetaIoHandler,
+    };
+    use jsonrpc_http_server::{
+        hyper::{
+            header::{ACCEPT_ENCODING, CONTENT_ENCODING, IF_NONE_MATCH},
+            Body,
+            Request,
+            StatusCode,
+        },
+        RequestMiddleware,
+        RequestMiddlewareAction,
+    };
+    use jsonrpc_pubsub::{PubSubHandler, Session};
     use jsonrpc_test::Rpc;
     use serde_json::Value;
-    use std::{net::SocketAddr, sync::Arc};
+    use std::{collections::HashMap, io::Read, net::SocketAddr, sync::Arc};
+    use tokio::sync::Mutex;
 
-    fn initialize_test_rpc(storage: &Arc<MerkleTreeLedger>) -> Rpc {
+    fn initialize_test_rpc_impl(storage: &Arc<MerkleTreeLedger>) -> RpcImpl {
+        initialize_test_rpc_impl_and_sync_handler(storage).0
+    }
+
+    fn initialize_test_rpc_impl_and_sync_handler(
+        storage: &Arc<MerkleTreeLedger>,
+    ) -> (RpcImpl, Arc<Mutex<SyncHandler>>) {
         let bootnode_address = random_socket_address();
         let server_address = random_socket_address();
 
@@ -49,20 +77,28 @@ mod rpc_tests {
 
         let storage = storage.clone();
         let storage_path = storage.storage.db.path().to_path_buf();
-
-        Rpc::new(
-            RpcImpl::new(
-                storage,
-                storage_path,
-                parameters,
-                server.context.clone(),
-                consensus,
-                server.memory_pool_lock,
-                server.sync_handler_lock,
-                None,
-            )
-            .to_delegate(),
+        let primary_storage = storage.clone();
+
+        let rpc = RpcImpl::new(
+            storage,
+            storage_path,
+            primary_storage,
+            parameters,
+            server.context.clone(),
+            consensus,
+            server.memory_pool_lock,
+            server.sync_handler_lock.clone(),
+            None,
+            vec![],
+            None,
         )
+        .unwrap();
+
+        (rpc, server.sync_handler_lock)
+    }
+
+    fn initialize_test_rpc(storage: &Arc<MerkleTreeLedger>) -> Rpc {
+        Rpc::new(initialize_test_rpc_impl(storage).to_delegate())
     }
 
     fn verify_transaction_info(transaction_bytes: Vec<u8>, transaction_info: Value) {
@@ -166,6 +202,35 @@ mod rpc_tests {
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_get_blocks() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        // The range is cut at the latest block
+        let response = rpc.request("getblocks", &[0, 10]);
+
+        let blocks: Vec<BlockInfo> = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].hash, hex::encode(GENESIS_BLOCK_HEADER_HASH.to_vec()));
+        assert_eq!(blocks[0].height, Some(0));
+
+        // The range is limited
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblocks\", \"params\": [0, {}] }}",
+            MAX_BLOCKS_RANGE
+        );
+        let response = rpc.io.handle_request_sync(&request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_rpc_get_block_count() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -267,6 +332,43 @@ mod rpc_tests {
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_decode_raw_transaction_verbose() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"decoderawtransaction\", \"params\": [\"{}\", true] }}",
+            hex::encode(TRANSACTION_1.to_vec())
+        );
+        let response = rpc.io.handle_request_sync(&request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let transaction_info: TransactionInfo = serde_json::from_value(extracted["result"].clone()).unwrap();
+
+        let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+        let details = transaction_info.details.unwrap();
+
+        assert_eq!(details.inputs.len(), transaction.old_serial_numbers().len());
+        assert_eq!(details.outputs.len(), transaction.new_commitments().len());
+        for (input, serial_number) in details.inputs.iter().zip(&transaction_info.old_serial_numbers) {
+            assert_eq!(&input.serial_number, serial_number);
+        }
+        for (output, commitment) in details.outputs.iter().zip(&transaction_info.new_commitments) {
+            assert_eq!(&output.commitment, commitment);
+        }
+
+        assert_eq!(details.memo.bytes, transaction_info.memo);
+        assert_eq!(details.value_balance.value_balance, transaction.value_balance.0);
+        assert_eq!(
+            details.value_balance.fee - details.value_balance.minted,
+            transaction.value_balance.0
+        );
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_rpc_send_raw_transaction() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -279,6 +381,10 @@ mod rpc_tests {
             format![r#""{}""#, hex::encode(transaction.transaction_id().unwrap())]
         );
 
+        // The sent transaction is tracked until it is confirmed
+        let transaction_id = transaction.transaction_id().unwrap();
+        assert!(storage.get_sent_transaction(&transaction_id).unwrap().is_some());
+
         drop(rpc);
         kill_storage_sync(storage);
     }
@@ -297,6 +403,112 @@ mod rpc_tests {
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_validate_transaction_diagnostics() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+        let stateless_checks = [
+            "inner_snark_id",
+            "size",
+            "unique_serial_numbers",
+            "unique_commitments",
+            "value_balance",
+            "signatures",
+            "proof",
+        ];
+        let stateful_checks = ["memo", "serial_numbers", "commitments", "ledger_digest", "memory_pool"];
+
+        for (mode, names) in [
+            ("stateless", stateless_checks.to_vec()),
+            ("stateful", [&stateless_checks[..], &stateful_checks[..]].concat()),
+        ]
+        .iter()
+        {
+            let response = rpc.request("validaterawtransaction", &[
+                hex::encode(TRANSACTION_1.to_vec()),
+                mode.to_string(),
+            ]);
+            let validation: TransactionValidation = serde_json::from_str(&response).unwrap();
+
+            assert_eq!(validation.txid, hex::encode(transaction.transaction_id().unwrap()));
+            assert_eq!(
+                validation
+                    .checks
+                    .iter()
+                    .map(|check| check.name.as_str())
+                    .collect::<Vec<_>>(),
+                *names
+            );
+            assert_eq!(validation.is_valid, validation.checks.iter().all(|check| check.passed));
+
+            // The fixture transaction is valid against the fresh ledger
+            for check in &validation.checks {
+                if check.name != "value_balance" {
+                    assert!(check.passed, "{} failed: {:?}", check.name, check.reason);
+                    assert_eq!(check.reason, None);
+                }
+            }
+        }
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_audit_transaction() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+        let [miner_acc, _, _] = FIXTURE_VK.test_accounts.clone();
+        let view_key = AccountViewKey::from_private_key(
+            &FIXTURE_VK.parameters.system_parameters.account_signature,
+            &FIXTURE_VK.parameters.system_parameters.account_commitment,
+            &miner_acc.private_key,
+        )
+        .unwrap();
+
+        // Without view keys, only the value balance is audited
+        let response = rpc.request("audittransaction", &[hex::encode(TRANSACTION_1.to_vec())]);
+        let audit: TransactionAuditInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(audit.txid, hex::encode(transaction.transaction_id().unwrap()));
+        assert_eq!(audit.value_balance, transaction.value_balance.0);
+        assert_eq!(audit.is_coinbase, transaction.value_balance.is_negative());
+        assert!(audit.signatures);
+        assert!(audit.proof);
+        assert!(audit.is_valid);
+        assert!(audit.disclosed_records.is_empty());
+        assert_eq!(audit.disclosed_value, 0);
+
+        // The view key of the owner discloses the output records
+        let response = rpc.request(
+            "audittransaction",
+            &(hex::encode(TRANSACTION_1.to_vec()), vec![view_key.to_string()]),
+        );
+        let audit: TransactionAuditInfo = serde_json::from_str(&response).unwrap();
+
+        let records: Vec<_> = DATA.records_1.iter().filter(|record| !record.is_dummy()).collect();
+        assert_eq!(audit.disclosed_records.len(), records.len());
+        for (disclosed_record, record) in audit.disclosed_records.iter().zip(records.iter()) {
+            assert_eq!(
+                disclosed_record.commitment,
+                hex::encode(to_bytes![record.commitment()].unwrap())
+            );
+            assert_eq!(disclosed_record.owner, miner_acc.address.to_string());
+            assert_eq!(disclosed_record.value, record.value());
+        }
+        assert_eq!(
+            audit.disclosed_value,
+            records.iter().map(|record| record.value()).sum::<u64>()
+        );
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_rpc_get_connection_count() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -326,6 +538,212 @@ mod rpc_tests {
         let expected_peers: Vec<SocketAddr> = vec![];
 
         assert_eq!(peer_info.peers, expected_peers);
+        assert_eq!(peer_info.total, 0);
+        assert_eq!(peer_info.details, None);
+
+        let response = rpc.request("getpeerinfo", &[Value::from(10), Value::from(0), Value::from(true)]);
+        let peer_info: PeerInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(peer_info.total, 0);
+        assert_eq!(peer_info.details, Some(vec![]));
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_network_info() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let result = make_request_no_params(&rpc, "getnetworkinfo".to_string());
+        let network_info: NetworkInfo = serde_json::from_value(result).unwrap();
+
+        assert_eq!(network_info.connected_peers, 0);
+        assert!(network_info.versions.is_empty());
+        assert_eq!(network_info.average_rtt, None);
+        assert_eq!(network_info.bytes_sent, 0);
+        assert_eq!(network_info.bytes_received, 0);
+        assert_eq!(network_info.misbehaving_peers, 0);
+        assert!(network_info.min_peers <= network_info.max_peers);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_memory_pool() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let response = rpc.request("getmemorypool", &[10, 0]);
+
+        let memory_pool_info: MemoryPoolInfo = serde_json::from_str(&response).unwrap();
+
+        let expected_transactions: Vec<String> = vec![];
+
+        assert_eq!(memory_pool_info.transactions, expected_transactions);
+        assert_eq!(memory_pool_info.total, 0);
+
+        // The page size is limited
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getmemorypool\", \"params\": [{}] }}",
+            MAX_PAGE_SIZE + 1
+        );
+        let response = rpc.io.handle_request_sync(&request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_estimate_fee() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let response = rpc.request("estimatefee", &[1]);
+
+        let fee_estimate: FeeEstimateInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(fee_estimate, FeeEstimateInfo {
+            target_blocks: 1,
+            fee_rate: 0.0,
+            confidence: 1.0,
+            memory_pool_fee_rate: 0.0,
+            sampled_blocks: 1,
+        });
+
+        // The target must be at least one block
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"estimatefee\", \"params\": [0] }";
+        let response = rpc.io.handle_request_sync(request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_calculate_fee() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let response = rpc.request("calculatefee", &[1.5]);
+
+        let fee_calculation: FeeCalculationInfo = serde_json::from_str(&response).unwrap();
+
+        // Every transaction is of the size of the proven transactions
+        assert_eq!(fee_calculation.transaction_size, TRANSACTION_1.len());
+        assert_eq!(fee_calculation.fee_rate, 1.5);
+        assert_eq!(fee_calculation.fee, (TRANSACTION_1.len() as f64 * 1.5).ceil() as u64);
+
+        // The fee rate must not be negative
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"calculatefee\", \"params\": [-1.0] }";
+        let response = rpc.io.handle_request_sync(request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::InvalidParams.code());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_create_payment_request() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let address = FIXTURE_VK.test_accounts[1].address.clone();
+        let network = TEST_CONSENSUS.network;
+
+        let request_input = PaymentRequestInputs {
+            address: address.to_string(),
+            amount: Some(100),
+            memo: Some("invoice 42".to_string()),
+            expires: None,
+        };
+
+        let response = rpc.request("createpaymentrequest", &[request_input.clone()]);
+
+        let payment_request: PaymentRequestInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(payment_request.address, address.to_network_string(network));
+        assert_eq!(
+            payment_request.uri,
+            format!("aleo:{}?amount=100&memo=invoice%2042", payment_request.address)
+        );
+
+        let request = PaymentRequest::<Components>::from_uri(&payment_request.uri, network).unwrap();
+        assert_eq!(request.address, address);
+        assert_eq!(request.amount, Some(100));
+        assert_eq!(request.memo, Some("invoice 42".to_string()));
+
+        // The expired requests are rejected
+        let mut expired_input = request_input;
+        expired_input.expires = Some(1);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "createpaymentrequest",
+            "params": [expired_input],
+        });
+        let response = rpc.io.handle_request_sync(&request.to_string()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["code"], RpcErrorCode::InvalidParams.code());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_memory_pool_info() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let method = "getmempoolinfo".to_string();
+
+        let result = make_request_no_params(&rpc, method);
+
+        let memory_pool_summary: MemoryPoolSummary = serde_json::from_value(result).unwrap();
+
+        assert_eq!(memory_pool_summary, MemoryPoolSummary {
+            size: 0,
+            bytes: 0,
+            total_fee: 0,
+            min_fee_rate: None,
+            max_fee_rate: None,
+            oldest_time: None,
+        });
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_raw_memory_pool() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let response = rpc.request("getrawmempool", &[false]);
+
+        let transaction_ids: Vec<String> = serde_json::from_str(&response).unwrap();
+
+        assert!(transaction_ids.is_empty());
+
+        let response = rpc.request("getrawmempool", &[true]);
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted, serde_json::json!({}));
 
         drop(rpc);
         kill_storage_sync(storage);
@@ -342,6 +760,7 @@ mod rpc_tests {
 
         let peer_info: NodeInfo = serde_json::from_value(result).unwrap();
 
+        assert!(peer_info.peer_id.parse::<PeerId>().is_ok());
         assert_eq!(peer_info.is_miner, false);
         assert_eq!(peer_info.is_syncing, false);
 
@@ -349,6 +768,191 @@ mod rpc_tests {
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_get_mining_info() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let method = "getmininginfo".to_string();
+
+        let result = make_request_no_params(&rpc, method);
+
+        let mining_info: MiningInfo = serde_json::from_value(result).unwrap();
+
+        assert_eq!(mining_info, MiningInfo {
+            is_miner: false,
+            hashrate: 0.0,
+            attempts: 0,
+            accepted_blocks: 0,
+            stale_blocks: 0,
+            stale_shares: 0,
+            templates: 0,
+            average_template_latency: 0.0,
+        });
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_serial_number_proof() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let genesis_block = genesis();
+        let sn = &genesis_block.transactions.0[0].old_serial_numbers()[0];
+
+        let mut serial_number = vec![];
+        CanonicalSerialize::serialize(sn, &mut serial_number).unwrap();
+
+        let response = rpc.request("getserialnumberproof", &[hex::encode(serial_number)]);
+
+        let proof_info: SerialNumberProofInfo = serde_json::from_str(&response).unwrap();
+
+        assert!(proof_info.is_spent);
+        assert_eq!(proof_info.block_height, 0);
+        assert_eq!(proof_info.block_hash, hex::encode(genesis_block.header.get_hash().0));
+        assert_eq!(proof_info.root, hex::encode(storage.get_serial_number_root().unwrap()));
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_ledger_proof() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let genesis_block = genesis();
+        let cm = &genesis_block.transactions.0[0].new_commitments()[0];
+        let commitment = hex::encode(to_bytes![cm].unwrap());
+        let digest = storage.get_digest_at_height(0).unwrap();
+        let ledger_digest = hex::encode(to_bytes![digest].unwrap());
+
+        let response = rpc.request("getledgerproof", &[commitment.clone()]);
+        let proof_info: LedgerProofInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(proof_info.block_height, 0);
+        assert_eq!(proof_info.ledger_digest, ledger_digest);
+        assert_eq!(proof_info.commitment, commitment);
+
+        let merkle_path = MerklePath {
+            parameters: storage.ledger_parameters.clone(),
+            path: proof_info
+                .merkle_path
+                .iter()
+                .map(|(left, right)| {
+                    (
+                        FromBytes::read(&hex::decode(left).unwrap()[..]).unwrap(),
+                        FromBytes::read(&hex::decode(right).unwrap()[..]).unwrap(),
+                    )
+                })
+                .collect(),
+        };
+
+        assert!(merkle_path.verify(&digest, cm).unwrap());
+
+        // The proofs are given against the ledger digests of canon blocks
+        let response = rpc.request("getledgerproof", &[commitment.clone(), ledger_digest.clone()]);
+        let past_proof_info: LedgerProofInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(past_proof_info, proof_info);
+
+        let response = rpc.request("validateledgerdigest", &[ledger_digest.clone()]);
+        let digest_info: LedgerDigestInfo = serde_json::from_str(&response).unwrap();
+
+        assert!(digest_info.is_valid);
+        assert!(digest_info.is_canon);
+        assert_eq!(digest_info.block_height, Some(0));
+
+        let unknown_digest = hex::encode([1u8; 32]);
+        let response = rpc.request("validateledgerdigest", &[unknown_digest.clone()]);
+        let digest_info: LedgerDigestInfo = serde_json::from_str(&response).unwrap();
+
+        assert!(!digest_info.is_valid);
+        assert!(!digest_info.is_canon);
+        assert_eq!(digest_info.block_height, None);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_search_transactions() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let genesis_block = genesis();
+        let transaction = &genesis_block.transactions.0[0];
+        let memo = hex::encode(to_bytes![transaction.memorandum()].unwrap());
+        let transaction_id = hex::encode(transaction.transaction_id().unwrap());
+
+        let memo_prefix = memo[..4].to_string();
+
+        let response = rpc.request("searchtransactions", &[memo_prefix.clone()]);
+        let results: Vec<TransactionSearchResult> = serde_json::from_str(&response).unwrap();
+
+        assert!(results.iter().all(|result| result.memo.starts_with(&memo_prefix)));
+        assert!(results.contains(&TransactionSearchResult {
+            memo,
+            txid: transaction_id.clone(),
+            block_height: 0,
+        }));
+
+        let cm = &transaction.new_commitments()[0];
+        let commitment = hex::encode(to_bytes![cm].unwrap());
+
+        let response = rpc.request("getcommitmentinfo", &[commitment.clone()]);
+        let commitment_info: CommitmentInfo = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(commitment_info.commitment, commitment);
+        assert_eq!(commitment_info.index, 0);
+        assert_eq!(commitment_info.txid, transaction_id);
+        assert_eq!(commitment_info.block_height, 0);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_storage_metrics() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let method = "getstoragemetrics".to_string();
+
+        let result = make_request_no_params(&rpc, method);
+
+        let storage_metrics: StorageMetrics = serde_json::from_value(result).unwrap();
+
+        assert_eq!(
+            storage_metrics.columns.len(),
+            storage.get_storage_metrics().unwrap().columns.len()
+        );
+        assert_eq!(storage_metrics.cache_hit_rate, None);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_parameter_downloads() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let method = "getparameterdownloads".to_string();
+
+        let result = make_request_no_params(&rpc, method);
+
+        let downloads: Vec<ParameterDownloadInfo> = serde_json::from_value(result).unwrap();
+
+        // The parameters of the tests are stored locally
+        assert!(downloads.is_empty());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_rpc_get_block_template() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -370,8 +974,824 @@ mod rpc_tests {
         assert_eq!(template.block_height, new_height);
         assert_eq!(template.transactions, expected_transactions);
         assert!(template.coinbase_value >= block_reward.0 as u64);
+        assert_eq!(template.block_reward, block_reward.0 as u64);
+        assert_eq!(template.network_id, TEST_CONSENSUS.network.id());
+        assert_eq!(template.max_block_size, TEST_CONSENSUS.max_block_size);
+        assert_eq!(template.coinbase_transaction, None);
+
+        // The coinbase address must be valid
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblocktemplate\", \"params\": [\"aleo1\"] }";
+        let response = rpc.io.handle_request_sync(request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_submit_block() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let submit_block = |block: &Block<Tx>| -> SubmitBlockResult {
+            let response = rpc.request("submitblock", &[hex::encode(block.serialize().unwrap())]);
+            serde_json::from_str(&response).unwrap()
+        };
+
+        // A block that does not extend the canon chain is rejected
+        let result = submit_block(&DATA.block_2);
+
+        assert!(!result.accepted);
+        assert!(result.reject_reason.is_some());
+        assert_eq!(storage.get_latest_block_height(), 0);
+
+        let result = submit_block(&DATA.block_1);
+
+        assert_eq!(result, SubmitBlockResult {
+            block_hash: DATA.block_1.header.get_hash().to_string(),
+            accepted: true,
+            reject_reason: None,
+        });
+        assert_eq!(storage.get_latest_block_height(), 1);
+
+        let result = submit_block(&DATA.block_1);
+
+        assert!(!result.accepted);
+        assert_eq!(result.reject_reason, Some("duplicate block".to_string()));
 
         drop(rpc);
         kill_storage_sync(storage);
     }
+
+    #[test]
+    fn test_rpc_subscribe_blocks() {
+        let storage = Arc::new(FIXTURE.ledger());
+        let subscriptions = RpcSubscriptionsImpl::new(initialize_test_rpc_impl(&storage));
+
+        let (sender, receiver) = mpsc::channel(8);
+        let meta = SubscriptionMeta {
+            session: Some(Arc::new(Session::new(sender))),
+        };
+
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(subscriptions.to_delegate());
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"subscribeBlocks\" }";
+        let response = io.handle_request_sync(request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let subscription_id = extracted["result"].clone();
+
+        let mut state = subscriptions.notification_state().unwrap();
+
+        TEST_CONSENSUS
+            .receive_block(&FIXTURE.parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        subscriptions.notify(&mut state).unwrap();
+
+        let notification = receiver.wait().next().unwrap().unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+
+        assert_eq!(notification["method"], "blocks");
+        assert_eq!(notification["params"]["subscription"], subscription_id);
+        assert_eq!(
+            hex::encode(DATA.block_1.header.get_hash().0),
+            notification["params"]["result"]["hash"]
+        );
+        assert_eq!(1, notification["params"]["result"]["height"]);
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_webhook_notifications() {
+        let storage = Arc::new(FIXTURE.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        let config = RpcWebhookConfig {
+            url: "http://127.0.0.1:8080/webhook".to_string(),
+            events: vec![WebhookEvent::Block, WebhookEvent::Reorg],
+            account_view_key: None,
+            secret: Some("TEST_SECRET".to_string()),
+            max_retries: 0,
+        };
+
+        // The transaction event requires an account view key
+        let invalid_config = RpcWebhookConfig {
+            events: vec![WebhookEvent::Transaction],
+            ..config.clone()
+        };
+        match RpcWebhook::new(rpc_impl.clone(), invalid_config) {
+            Err(error) => assert_eq!(error.code(), RpcErrorCode::InvalidParams),
+            Ok(_) => panic!("the webhook config is invalid"),
+        }
+
+        let webhook = RpcWebhook::new(rpc_impl, config).unwrap();
+        let mut state = webhook.state().unwrap();
+
+        TEST_CONSENSUS
+            .receive_block(&FIXTURE.parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        let notifications = webhook.notifications(&mut state).unwrap();
+        assert_eq!(notifications.len(), 1);
+
+        let notification: Value = serde_json::to_value(&notifications[0]).unwrap();
+        assert_eq!(notification["event"], "block");
+        assert_eq!(
+            hex::encode(DATA.block_1.header.get_hash().0),
+            notification["payload"]["hash"]
+        );
+
+        // The notified events are not notified again
+        assert!(webhook.notifications(&mut state).unwrap().is_empty());
+
+        drop(webhook);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_webhook_signature() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        assert_eq!(
+            webhook_signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_rpc_batch_request() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let request = "[
+            { \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblockcount\" },
+            { \"jsonrpc\":\"2.0\", \"id\": \"2\", \"method\": \"getblockhash\", \"params\": [0] },
+            { \"jsonrpc\":\"2.0\", \"id\": 3, \"method\": \"getblockhash\", \"params\": [100] },
+            { \"jsonrpc\":\"2.0\", \"method\": \"getblockcount\" }
+        ]";
+
+        let response = rpc.io.handle_request_sync(request).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let responses = extracted.as_array().unwrap();
+
+        // The notification is not answered
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], storage.get_block_count());
+
+        assert_eq!(responses[1]["id"], "2");
+        assert_eq!(responses[1]["result"], hex::encode(GENESIS_BLOCK_HEADER_HASH.to_vec()));
+
+        // A failed call does not fail the batch
+        assert_eq!(responses[2]["id"], 3);
+        assert!(responses[2]["error"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_batch_limit() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+
+        let mut io = MetaIoHandler::with_middleware(BatchLimit);
+        io.extend_with(initialize_test_rpc_impl(&storage).to_delegate());
+
+        let call = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblockcount\" }";
+        let request = format!("[{}]", vec![call; MAX_BATCH_SIZE + 1].join(","));
+
+        let response = io.handle_request_sync(&request, Meta::default()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["id"], Value::Null);
+        assert_eq!(extracted["error"]["code"], -32600);
+
+        let request = format!("[{}]", vec![call; MAX_BATCH_SIZE].join(","));
+
+        let response = io.handle_request_sync(&request, Meta::default()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted.as_array().unwrap().len(), MAX_BATCH_SIZE);
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_limits() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+
+        let mut requests_per_method = HashMap::new();
+        requests_per_method.insert("getblockcount".to_string(), 1);
+
+        let limits = Arc::new(RpcLimits::new(RpcLimitsConfig {
+            requests_per_ip: Some(1),
+            requests_per_token: Some(1),
+            requests_per_method,
+            max_concurrent_requests: Some(1),
+            max_request_size: Some(100),
+        }));
+
+        // Method limits
+        let mut io = MetaIoHandler::with_middleware((BatchLimit, MethodLimits::new(limits.clone())));
+        io.extend_with(initialize_test_rpc_impl(&storage).to_delegate());
+
+        let call = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblockcount\" }";
+
+        let response = io.handle_request_sync(call, Meta::default()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        let response = io.handle_request_sync(call, Meta::default()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["code"], RATE_LIMITED_ERROR_CODE);
+
+        // The other methods are not limited
+        let call = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getbestblockhash\" }";
+
+        for _ in 0..2 {
+            let response = io.handle_request_sync(call, Meta::default()).unwrap();
+            let extracted: Value = serde_json::from_str(&response).unwrap();
+
+            assert!(extracted["result"].is_string());
+        }
+
+        // Connection limits
+        let ip = "127.0.0.1".parse().unwrap();
+
+        let guard = RpcLimits::accept_connection(&limits, ip).unwrap();
+        assert_eq!(limits.concurrent_requests(), 1);
+        assert!(RpcLimits::accept_connection(&limits, "127.0.0.2".parse().unwrap()).is_none());
+
+        drop(guard);
+        assert_eq!(limits.concurrent_requests(), 0);
+        assert!(RpcLimits::accept_connection(&limits, ip).is_none());
+        assert!(RpcLimits::accept_connection(&limits, "127.0.0.2".parse().unwrap()).is_some());
+
+        // Request limits
+        let endpoint = LimitsEndpoint::new(
+            limits,
+            Arc::new(|authorization: &str| authorization != "Bearer INVALID_TOKEN"),
+        );
+
+        let request = |authorization: &str, body: &str| {
+            let request = Request::post("/")
+                .header("Authorization", authorization)
+                .header("Content-Length", body.len().to_string())
+                .body(Body::from(body.to_string()))
+                .unwrap();
+
+            match endpoint.on_request(request) {
+                RequestMiddlewareAction::Respond { response, .. } => Some(response.wait().unwrap().status()),
+                RequestMiddlewareAction::Proceed { .. } => None,
+            }
+        };
+
+        assert_eq!(
+            request("Bearer TOKEN", &"0".repeat(101)),
+            Some(StatusCode::PAYLOAD_TOO_LARGE)
+        );
+        assert_eq!(request("Bearer TOKEN", "{}"), None);
+        assert_eq!(request("Bearer TOKEN", "{}"), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(request("Bearer OTHER_TOKEN", "{}"), None);
+
+        // The requests with invalid credentials are only limited per IP address
+        assert_eq!(request("Bearer INVALID_TOKEN", "{}"), None);
+        assert_eq!(request("Bearer INVALID_TOKEN", "{}"), None);
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rate_limiter_clients() {
+        let limiter = RateLimiter::new(1);
+
+        for client in 0..MAX_RATE_LIMITED_CLIENTS {
+            assert!(limiter.check(client));
+        }
+        assert!(!limiter.check(0));
+
+        // The least recently updated client is forgotten to track a new client
+        assert!(limiter.check(MAX_RATE_LIMITED_CLIENTS));
+        assert_eq!(limiter.num_clients(), MAX_RATE_LIMITED_CLIENTS);
+
+        assert!(limiter.check(1));
+        assert!(!limiter.check(0));
+    }
+
+    #[test]
+    fn test_rpc_limits_reload() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+
+        let mut requests_per_method = HashMap::new();
+        requests_per_method.insert("getblockcount".to_string(), 1);
+
+        let limits = Arc::new(RpcLimits::new(RpcLimitsConfig {
+            requests_per_method,
+            ..RpcLimitsConfig::default()
+        }));
+
+        let mut io = MetaIoHandler::with_middleware((BatchLimit, MethodLimits::new(limits.clone())));
+        io.extend_with(initialize_test_rpc_impl(&storage).to_delegate());
+
+        let call = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblockcount\" }";
+
+        let response = io.handle_request_sync(call, Meta::default()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], 0);
+
+        let response = io.handle_request_sync(call, Meta::default()).unwrap();
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["error"]["code"], RATE_LIMITED_ERROR_CODE);
+
+        // The reloaded limits apply to the running middlewares
+        limits.reload(RpcLimitsConfig::default());
+        assert_eq!(limits.config(), RpcLimitsConfig::default());
+
+        for _ in 0..2 {
+            let response = io.handle_request_sync(call, Meta::default()).unwrap();
+            let extracted: Value = serde_json::from_str(&response).unwrap();
+
+            assert_eq!(extracted["result"], 0);
+        }
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_metrics() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        let metrics = rpc_impl.metrics().unwrap();
+
+        let block_height = format!("snarkos_block_height {}", storage.get_latest_block_height());
+        assert!(metrics.lines().any(|line| line == block_height));
+        assert!(metrics.lines().any(|line| line == "snarkos_peers 0"));
+        assert!(metrics.lines().any(|line| line == "snarkos_memory_pool_transactions 0"));
+        assert!(metrics.contains("# TYPE snarkos_storage_disk_size_bytes gauge"));
+
+        drop(rpc_impl);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_node_state() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let (rpc_impl, sync_handler_lock) = initialize_test_rpc_impl_and_sync_handler(&storage);
+
+        let node_state = rpc_impl.get_node_state().unwrap();
+
+        assert_eq!(node_state.block_height, storage.get_latest_block_height());
+        assert_eq!(node_state.best_known_block_height, node_state.block_height);
+        assert!(!node_state.is_syncing);
+        assert_eq!(node_state.connected_peers, 0);
+        assert_eq!(node_state.memory_pool_size, 0);
+        assert!(node_state.storage.is_healthy);
+        assert_eq!(node_state.storage.error, None);
+
+        let response = rpc_impl.rest("/health", None).unwrap();
+        let health: NodeHealth = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(health.is_healthy);
+
+        // The announced blocks are known before they are received
+        {
+            let mut sync_handler = sync_handler_lock.try_lock().unwrap();
+            sync_handler.receive_hashes(vec![BlockHeaderHash([1u8; 32]), BlockHeaderHash([2u8; 32])], 0);
+        }
+
+        let node_state = rpc_impl.get_node_state().unwrap();
+
+        assert!(node_state.is_syncing);
+        assert_eq!(node_state.queued_block_headers, 2);
+        assert_eq!(node_state.best_known_block_height, node_state.block_height + 2);
+
+        // Syncing nodes are not ready to serve requests
+        let response = rpc_impl.rest("/health", None).unwrap();
+        let health: NodeHealth = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!health.is_healthy);
+        assert!(health.is_syncing);
+        assert!(health.storage.is_healthy);
+
+        drop(rpc_impl);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_rest() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        let response = rpc_impl.rest("/block/0", None).unwrap();
+        let block_info: BlockInfo = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(block_info.height, Some(0));
+        assert_eq!(response.etag, Some(format!("W/\"{}\"", block_info.hash)));
+        assert_eq!(response.cache_control, "public, max-age=10");
+
+        let response = rpc_impl.rest("/block/INVALID_HEIGHT", None).unwrap();
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+        let response = rpc_impl
+            .rest(&format!("/transaction/{}", hex::encode([0u8; 32])), None)
+            .unwrap();
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(body["kind"], "not_found");
+
+        let response = rpc_impl.rest("/mempool", Some("limit=10&offset=0")).unwrap();
+        let memory_pool_info: MemoryPoolInfo = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(memory_pool_info.total, 0);
+
+        let response = rpc_impl.rest("/status", None).unwrap();
+        let status: NodeStatus = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.cache_control, "no-cache");
+        assert_eq!(status.block_height, storage.get_latest_block_height());
+        assert_eq!(status.peers, 0);
+
+        // The other paths are served by the RPC server
+        assert!(rpc_impl.rest("/", None).is_none());
+
+        // Clients holding the current version of a block do not receive it again
+        let request = Request::get("/block/0")
+            .header(IF_NONE_MATCH, format!("W/\"{}\"", block_info.hash))
+            .body(Body::empty())
+            .unwrap();
+
+        match RestEndpoint::new(rpc_impl).on_request(request) {
+            RequestMiddlewareAction::Respond { response, .. } => {
+                assert_eq!(response.wait().unwrap().status(), StatusCode::NOT_MODIFIED)
+            }
+            RequestMiddlewareAction::Proceed { .. } => panic!("the REST request was not served"),
+        }
+
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_compression() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        let config = RpcCompressionConfig {
+            min_size: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(config.negotiate("gzip, deflate, br"), Some(ContentEncoding::Gzip));
+        assert_eq!(config.negotiate("gzip, zstd"), Some(ContentEncoding::Zstd));
+        assert_eq!(config.negotiate("gzip, zstd;q=0"), Some(ContentEncoding::Gzip));
+        assert_eq!(config.negotiate("identity"), None);
+
+        let mut io = MetaIoHandler::<Meta>::default();
+        io.extend_with(rpc_impl.clone().to_delegate());
+
+        let endpoint = CompressionEndpoint::new(Some(config), None, io, RestEndpoint::new(rpc_impl.clone()));
+
+        let response_body = |request| match endpoint.on_request(request) {
+            RequestMiddlewareAction::Respond { response, .. } => {
+                let response = response.wait().unwrap();
+                let encoding = response.headers()[CONTENT_ENCODING].to_str().unwrap().to_string();
+                let body = response.into_body().concat2().wait().unwrap().to_vec();
+
+                (encoding, body)
+            }
+            RequestMiddlewareAction::Proceed { .. } => panic!("the request was not compressed"),
+        };
+
+        // The JSON-RPC responses are compressed
+        let request = Request::post("/")
+            .header(ACCEPT_ENCODING, "gzip")
+            .body(Body::from(
+                "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getblockcount\" }",
+            ))
+            .unwrap();
+
+        let (encoding, body) = response_body(request);
+        let mut response = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut response).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(encoding, "gzip");
+        assert_eq!(extracted["result"], rpc_impl.get_block_count().unwrap());
+
+        // The REST responses are compressed
+        let request = Request::get("/block/0")
+            .header(ACCEPT_ENCODING, "zstd")
+            .body(Body::empty())
+            .unwrap();
+
+        let (encoding, body) = response_body(request);
+        let block_info: BlockInfo = serde_json::from_slice(&zstd::stream::decode_all(&body[..]).unwrap()).unwrap();
+
+        assert_eq!(encoding, "zstd");
+        assert_eq!(block_info.height, Some(0));
+
+        drop(endpoint);
+        drop(rpc_impl);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_graphql() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+        let schema = graphql_schema();
+
+        let block_info = rpc_impl.get_blocks(0, 0).unwrap().pop().unwrap();
+        let transaction_info = rpc_impl
+            .get_transaction_info(block_info.transactions[0].clone(), None)
+            .unwrap();
+
+        // The block, its transactions and their records are fetched in a single request
+        let query =
+            "{ block(height: 0) { hash height transactions { txid blockHeight records { commitment index } } } }";
+        let request = serde_json::json!({ "query": query }).to_string();
+
+        let response = rpc_impl.graphql(&schema, request.as_bytes());
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.cache_control, "no-cache");
+
+        let block = &body["data"]["block"];
+        assert_eq!(block["hash"], block_info.hash);
+        assert_eq!(block["height"], 0);
+
+        let transaction = &block["transactions"][0];
+        assert_eq!(transaction["txid"], transaction_info.txid);
+        assert_eq!(transaction["blockHeight"], 0);
+
+        let records = transaction["records"].as_array().unwrap();
+        assert_eq!(records.len(), transaction_info.new_commitments.len());
+        assert_eq!(records[0]["commitment"], transaction_info.new_commitments[0]);
+        assert!(records[0]["index"].is_number());
+
+        // The records link back to their transaction
+        let query = format!(
+            "{{ record(commitment: \"{}\") {{ transaction {{ txid }} }} }}",
+            transaction_info.new_commitments[0]
+        );
+        let request = serde_json::json!({ "query": query }).to_string();
+
+        let response = rpc_impl.graphql(&schema, request.as_bytes());
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(body["data"]["record"]["transaction"]["txid"], transaction_info.txid);
+
+        // The failed fields carry the kind of their error
+        let request = serde_json::json!({ "query": "{ block(height: -1) { hash } }" }).to_string();
+
+        let response = rpc_impl.graphql(&schema, request.as_bytes());
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(body["errors"][0]["extensions"]["kind"], "invalid_params");
+
+        let response = rpc_impl.graphql(&schema, b"INVALID_REQUEST");
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+        // The queries are sent in the body of POST requests
+        let request = Request::post(GRAPHQL_PATH)
+            .body(Body::from(
+                serde_json::json!({ "query": "{ latestBlock { height } }" }).to_string(),
+            ))
+            .unwrap();
+
+        match GraphQLEndpoint::new(rpc_impl).on_request(request) {
+            RequestMiddlewareAction::Respond { response, .. } => {
+                let response = response.wait().unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+
+                let body = response.into_body().concat2().wait().unwrap();
+                let body: Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(body["data"]["latestBlock"]["height"], storage.get_latest_block_height());
+            }
+            RequestMiddlewareAction::Proceed { .. } => panic!("the GraphQL request was not served"),
+        }
+
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_cors() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        let cors = RpcCorsConfig {
+            allowed_origins: vec!["https://explorer.aleo.org".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: vec![],
+            max_age: Some(3600),
+        };
+        let endpoint = CorsEndpoint::new(cors, RestEndpoint::new(rpc_impl));
+
+        let respond = |request: Request<Body>| match endpoint.on_request(request) {
+            RequestMiddlewareAction::Respond { response, .. } => Some(response.wait().unwrap()),
+            RequestMiddlewareAction::Proceed { .. } => None,
+        };
+
+        // Preflight requests
+        let preflight = |origin: &str, method: &str| {
+            Request::options("/status")
+                .header("Origin", origin)
+                .header("Access-Control-Request-Method", method)
+                .header("Access-Control-Request-Headers", "content-type")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = respond(preflight("https://explorer.aleo.org", "GET")).unwrap();
+        let headers = response.headers();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(headers["Access-Control-Allow-Origin"], "https://explorer.aleo.org");
+        assert_eq!(headers["Access-Control-Allow-Methods"], "GET, POST");
+        assert_eq!(headers["Access-Control-Allow-Headers"], "content-type");
+        assert_eq!(headers["Access-Control-Max-Age"], "3600");
+
+        let response = respond(preflight("https://explorer.aleo.org", "DELETE")).unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = respond(preflight("https://other.org", "GET")).unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Cross-origin REST requests
+        let request = |origin: &str| {
+            Request::get("/status")
+                .header("Origin", origin)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = respond(request("https://explorer.aleo.org")).unwrap();
+        assert_eq!(
+            response.headers()["Access-Control-Allow-Origin"],
+            "https://explorer.aleo.org"
+        );
+
+        let response = respond(request("https://other.org")).unwrap();
+        assert!(!response.headers().contains_key("Access-Control-Allow-Origin"));
+
+        // The JSON-RPC requests are passed to the server
+        let request = Request::post("/")
+            .header("Origin", "https://explorer.aleo.org")
+            .body(Body::empty())
+            .unwrap();
+        assert!(respond(request).is_none());
+
+        drop(endpoint);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_error_codes() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let (rpc_impl, sync_handler_lock) = initialize_test_rpc_impl_and_sync_handler(&storage);
+
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_impl.to_delegate());
+
+        let request_error = |method: &str, params: Value| {
+            let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+            let response = io.handle_request_sync(&request.to_string(), Meta::default()).unwrap();
+            let extracted: Value = serde_json::from_str(&response).unwrap();
+
+            extracted["error"].clone()
+        };
+
+        // Missing blocks and transactions
+        let error = request_error("getblockhash", serde_json::json!([100]));
+
+        assert_eq!(error["code"], RpcErrorCode::NotFound.code());
+        assert_eq!(error["data"]["kind"], "not_found");
+
+        let error = request_error("getrawtransaction", serde_json::json!([hex::encode([0u8; 32])]));
+
+        assert_eq!(error["code"], RpcErrorCode::NotFound.code());
+
+        // Malformed parameters
+        let error = request_error("getblock", serde_json::json!(["00"]));
+
+        assert_eq!(error["code"], RpcErrorCode::InvalidParams.code());
+        assert_eq!(error["data"]["kind"], "invalid_params");
+
+        let error = request_error("sendtransaction", serde_json::json!(["not hex"]));
+
+        assert_eq!(error["code"], RpcErrorCode::InvalidParams.code());
+
+        // Block templates are not built while syncing
+        sync_handler_lock.try_lock().unwrap().update_syncing(1);
+
+        let error = request_error("getblocktemplate", serde_json::json!([]));
+
+        assert_eq!(error["code"], RpcErrorCode::NodeSyncing.code());
+        assert_eq!(error["data"]["kind"], "node_syncing");
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_versions() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc_impl = initialize_test_rpc_impl(&storage);
+
+        assert_eq!(RpcVersion::from_path("/v2"), Some(RpcVersion::V2));
+        assert_eq!(RpcVersion::from_path("/"), None);
+        assert_eq!(
+            RpcVersion::from_method("v1.getpeerinfo"),
+            Some((RpcVersion::V1, "getpeerinfo"))
+        );
+        assert_eq!(RpcVersion::from_method("getpeerinfo"), None);
+
+        let handler = |config: RpcVersionsConfig| {
+            let mut io = MetaIoHandler::with_middleware(RpcVersions::new(config));
+            io.extend_with(rpc_impl.clone().to_delegate());
+            io
+        };
+
+        let request_with_params =
+            |io: &MetaIoHandler<Meta, RpcVersions>, method: &str, params: Value, api_version: Option<RpcVersion>| {
+                let request =
+                    serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }).to_string();
+                let meta = Meta {
+                    auth: None,
+                    is_local: false,
+                    api_version,
+                };
+
+                serde_json::from_str::<Value>(&io.handle_request_sync(&request, meta).unwrap()).unwrap()
+            };
+        let request = |io: &MetaIoHandler<Meta, RpcVersions>, method: &str, api_version: Option<RpcVersion>| {
+            request_with_params(io, method, serde_json::json!([]), api_version)
+        };
+
+        let io = handler(RpcVersionsConfig::default());
+
+        // The first version lists the peer addresses
+        let response = request(&io, "getpeerinfo", None);
+        assert_eq!(response["result"], serde_json::json!({ "peers": [], "total": 0 }));
+        assert_eq!(request(&io, "v1.getpeerinfo", Some(RpcVersion::V2)), response);
+
+        // The second version lists the peer statistics, whether requested by the method or the path
+        let response = request(&io, "v2.getpeerinfo", None);
+        assert_eq!(response["result"], serde_json::json!({ "peers": [], "total": 0 }));
+        assert_eq!(request(&io, "getpeerinfo", Some(RpcVersion::V2)), response);
+
+        // The second version returns the stateful verification diagnostics by default
+        let transaction = serde_json::json!([hex::encode(TRANSACTION_1.to_vec())]);
+        let response = request_with_params(&io, "validaterawtransaction", transaction.clone(), None);
+        assert!(response["result"].is_boolean());
+
+        let response = request_with_params(&io, "v2.validaterawtransaction", transaction, None);
+        assert_eq!(response["result"]["mode"], "stateful");
+        assert!(response["result"]["checks"].is_array());
+
+        // The removed versions are no longer served
+        let io = handler(RpcVersionsConfig {
+            default_version: RpcVersion::V2,
+            deprecated: vec![],
+            removed: vec![RpcVersion::V1],
+        });
+
+        let response = request(&io, "getblockcount", Some(RpcVersion::V1));
+        assert_eq!(response["error"]["code"], RpcErrorCode::InvalidRequest.code());
+
+        let response = request(&io, "getblockcount", None);
+        assert_eq!(response["result"], rpc_impl.get_block_count().unwrap());
+
+        drop(io);
+        drop(rpc_impl);
+        kill_storage_sync(storage);
+    }
 }
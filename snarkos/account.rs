@@ -0,0 +1,140 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::{Config, ENV_VAR_PREFIX};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::SystemParameters};
+use snarkos_errors::node::CliError;
+use snarkos_models::objects::AccountScheme;
+use snarkos_objects::{Account, AccountAddress, AccountKeystore, AccountPrivateKey, AccountViewKey};
+
+use clap::ArgMatches;
+use rand::thread_rng;
+use std::{env, str::FromStr};
+
+pub struct AccountCLI;
+
+impl AccountCLI {
+    /// Create a new account, stored in a keystore file if one is given
+    fn create(config: &Config, arguments: &ArgMatches) -> Result<(), CliError> {
+        let parameters = SystemParameters::<Components>::load()?;
+        let account = Account::<Components>::new(
+            &parameters.account_signature,
+            &parameters.account_commitment,
+            &parameters.account_encryption,
+            &mut thread_rng(),
+        )?;
+
+        match arguments.value_of("keystore") {
+            Some(keystore) => Self::store(config, &account, keystore, Self::passphrase("new", arguments)?),
+            None => Self::print(config, &parameters, &account.private_key),
+        }
+    }
+
+    /// Import the account of a private key into a keystore file, or print the account if no keystore is given
+    fn import(config: &Config, arguments: &ArgMatches) -> Result<(), CliError> {
+        let private_key = arguments
+            .value_of("private-key")
+            .ok_or_else(|| CliError::MissingArgument("import".into(), "private-key".into()))?;
+        let private_key = AccountPrivateKey::<Components>::from_str(private_key)?;
+
+        let parameters = SystemParameters::<Components>::load()?;
+        let address = AccountAddress::<Components>::from_private_key(
+            &parameters.account_signature,
+            &parameters.account_commitment,
+            &parameters.account_encryption,
+            &private_key,
+        )?;
+
+        match arguments.value_of("keystore") {
+            Some(keystore) => {
+                let passphrase = Self::passphrase("import", arguments)?;
+                Self::store(config, &Account { private_key, address }, keystore, passphrase)
+            }
+            None => Self::print(config, &parameters, &private_key),
+        }
+    }
+
+    /// Decrypt the private key of a keystore file, and print the account
+    fn export(config: &Config, arguments: &ArgMatches) -> Result<(), CliError> {
+        let keystore = arguments
+            .value_of("keystore")
+            .ok_or_else(|| CliError::MissingArgument("export".into(), "keystore".into()))?;
+
+        let mut keystore = AccountKeystore::<Components>::open(keystore)?;
+        let private_key = keystore.unlock(&Self::passphrase("export", arguments)?)?.clone();
+
+        Self::print(config, &SystemParameters::<Components>::load()?, &private_key)
+    }
+
+    /// Encrypt the private key of an account in a new keystore file
+    fn store(
+        config: &Config,
+        account: &Account<Components>,
+        keystore: &str,
+        passphrase: String,
+    ) -> Result<(), CliError> {
+        let keystore = AccountKeystore::create(keystore, account, &passphrase, &mut thread_rng())?;
+
+        println!("Address: {}", account.address.to_network_string(config.network()));
+        println!("Keystore: {}", keystore.path().display());
+
+        Ok(())
+    }
+
+    /// Print the private key, the view key and the address of an account
+    fn print(
+        config: &Config,
+        parameters: &SystemParameters<Components>,
+        private_key: &AccountPrivateKey<Components>,
+    ) -> Result<(), CliError> {
+        let view_key = AccountViewKey::<Components>::from_private_key(
+            &parameters.account_signature,
+            &parameters.account_commitment,
+            private_key,
+        )?;
+        let address = AccountAddress::<Components>::from_private_key(
+            &parameters.account_signature,
+            &parameters.account_commitment,
+            &parameters.account_encryption,
+            private_key,
+        )?;
+
+        println!("Private Key: {}", private_key);
+        println!("View Key: {}", view_key);
+        println!("Address: {}", address.to_network_string(config.network()));
+
+        Ok(())
+    }
+
+    /// Returns the passphrase of the keystore file, given as an argument or by the `SNARKOS_PASSPHRASE`
+    /// environment variable
+    fn passphrase(action: &str, arguments: &ArgMatches) -> Result<String, CliError> {
+        match arguments.value_of("passphrase") {
+            Some(passphrase) => Ok(passphrase.to_string()),
+            None => env::var(format!("{}PASSPHRASE", ENV_VAR_PREFIX))
+                .map_err(|_| CliError::MissingArgument(action.into(), "passphrase".into())),
+        }
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        match arguments.value_of("action") {
+            Some("new") => Self::create(config, arguments),
+            Some("import") => Self::import(config, arguments),
+            Some("export") => Self::export(config, arguments),
+            _ => Ok(()),
+        }
+    }
+}
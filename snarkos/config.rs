@@ -15,18 +15,61 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    account::AccountCLI,
     cli::CLI,
+    data_dir::DataDir,
+    dev::{DevConfig, DEV_DIFFICULTY_TARGET},
+    limits::ResourceLimits,
+    logging::LogConfig,
+    miner::{benchmark::MinerCLI, failover::FailoverConfig, stratum::StratumConfig, worker::WorkerConfig},
+    networks::NetworkParameters,
     parameters::{flag, option, subcommand, types::*},
+    rpc_client::RpcClientCLI,
+    storage::StorageCLI,
+    test_parameters::TestParametersCLI,
     update::UpdateCLI,
 };
-use snarkos_errors::node::CliError;
+use snarkos_consensus::{
+    CoinbasePayout,
+    ConsensusParameters,
+    MerkleTreeLedger,
+    MiningThreadPolicy,
+    ProcessProverConfig,
+};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters, BaseDPCComponents};
+use snarkos_errors::{
+    consensus::ConsensusError,
+    network::IdentityError,
+    node::CliError,
+    objects::AccountError,
+    storage::StorageError,
+};
+use snarkos_models::algorithms::{CRH, SNARK};
+use snarkos_network::external::PeerId;
+use snarkos_objects::{AccountAddress, Network};
+use snarkos_posw::PoswMarlin;
+use snarkos_rpc::{
+    RpcCompressionConfig,
+    RpcCorsConfig,
+    RpcIpcConfig,
+    RpcLimitsConfig,
+    RpcTlsConfig,
+    RpcToken,
+    RpcVersionsConfig,
+    RpcWebhookConfig,
+};
+use snarkos_storage::StorageConfig;
+use snarkos_utilities::{to_bytes, ToBytes};
 
 use clap::ArgMatches;
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
 use std::{
+    env,
     fs,
+    io::Result as IoResult,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 /// Bootnodes maintained by Aleo.
@@ -45,6 +88,83 @@ pub const TESTNET_BOOTNODES: &[&str] = &[
     "50.18.83.123:4131",
 ]; // "192.168.0.1:4131"
 
+/// The configuration file read from the working directory, if it exists.
+pub const CONFIG_FILE_NAME: &str = "snarkos.toml";
+
+/// The file of the keys directory storing the identity key of the node.
+pub const IDENTITY_FILE: &str = "identity.key";
+
+/// The prefix of the environment variables overriding the configuration file.
+pub const ENV_VAR_PREFIX: &str = "SNARKOS_";
+
+/// The options of the configuration file that can be overridden, in the order they are applied.
+/// The development mode and the network go first, as they reset the network defaults.
+const CONFIG_OPTIONS: &[&str] = &[
+    "dev",
+    "network",
+    "no-jsonrpc",
+    "is-bootnode",
+    "is-miner",
+    "miner-low-priority",
+    "ip",
+    "port",
+    "data-dir",
+    "path",
+    "prune",
+    "archive",
+    "gc",
+    "readiness-threshold",
+    "connect",
+    "miner-address",
+    "miner-threads",
+    "miner-cores",
+    "miner-tag",
+    "miner-max-block-size",
+    "miner-max-block-weight",
+    "worker",
+    "miner-nodes",
+    "mempool-interval",
+    "min-peers",
+    "max-peers",
+    "rpc-port",
+    "rpc-ws-port",
+    "rpc-username",
+    "rpc-password",
+    "verbose",
+    "log-filter",
+    "log-json",
+    "log-file",
+    "max-upload",
+    "max-download",
+    "verification-threads",
+    "max-cache",
+    "parameters-mirrors",
+    "test-parameters",
+    "dev-block-interval",
+    "dev-accounts",
+];
+
+/// The options set by a flag, rather than by a value.
+const CONFIG_FLAGS: &[&str] = &[
+    "dev",
+    "no-jsonrpc",
+    "is-bootnode",
+    "is-miner",
+    "miner-low-priority",
+    "log-json",
+    "log-file",
+];
+
+/// Returns the environment variable of an option, such as `SNARKOS_MINER_ADDRESS` for `miner-address`.
+fn env_var_name(option: &str) -> String {
+    format!("{}{}", ENV_VAR_PREFIX, option.to_uppercase().replace('-', "_"))
+}
+
+/// Parses the value of an option, which is ignored if it is invalid.
+fn parse_value<T: FromStr>(value: Option<&str>) -> Option<T> {
+    value.and_then(|value| value.parse().ok())
+}
+
 /// Represents all configuration options for a node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -53,6 +173,16 @@ pub struct Config {
     pub miner: Miner,
     pub rpc: JsonRPC,
     pub p2p: P2P,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    #[serde(default)]
+    pub parameters: ParametersConfig,
+    #[serde(default)]
+    pub dev: Option<DevConfig>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -64,8 +194,23 @@ pub struct Aleo {
 pub struct JsonRPC {
     pub json_rpc: bool,
     pub port: u16,
+    pub ws_port: Option<u16>,
     pub username: Option<String>,
     pub password: Option<String>,
+    #[serde(default)]
+    pub tokens: Vec<RpcToken>,
+    pub tls: Option<RpcTlsConfig>,
+    pub cors: Option<RpcCorsConfig>,
+    pub compression: Option<RpcCompressionConfig>,
+    pub ipc: Option<RpcIpcConfig>,
+    #[serde(default)]
+    pub graphql: bool,
+    #[serde(default)]
+    pub webhooks: Vec<RpcWebhookConfig>,
+    #[serde(default)]
+    pub versions: RpcVersionsConfig,
+    #[serde(default)]
+    pub limits: RpcLimitsConfig,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -76,23 +221,107 @@ pub struct Node {
     pub ip: String,
     pub port: u16,
     pub verbose: u8,
+    pub prune: Option<u32>,
+    pub archive: Option<u32>,
+    pub gc: Option<u32>,
+    /// The number of blocks the node can be behind its peers and be reported ready to its service manager
+    #[serde(default = "default_readiness_threshold")]
+    pub readiness_threshold: u32,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Miner {
     pub is_miner: bool,
     pub miner_address: String,
+    #[serde(default = "default_miner_threads")]
+    pub threads: usize,
+    #[serde(default)]
+    pub thread_policy: MiningThreadPolicy,
+    #[serde(default)]
+    pub prover: Option<ProcessProverConfig>,
+    #[serde(default)]
+    pub stratum: Option<StratumConfig>,
+    #[serde(default)]
+    pub worker: Option<WorkerConfig>,
+    #[serde(default)]
+    pub failover: Option<FailoverConfig>,
+    #[serde(default)]
+    pub payout: Option<PayoutConfig>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// The maximum size in bytes of the block templates, or the maximum block size of the network if unset
+    #[serde(default)]
+    pub max_block_size: Option<usize>,
+    /// The maximum weight of the block templates, the number of ledger entries their transactions add, if any
+    #[serde(default)]
+    pub max_block_weight: Option<usize>,
+}
+
+impl P2P {
+    /// Returns the bootnodes and the peers the node connects to on startup.
+    pub fn startup_peers(&self) -> Vec<String> {
+        let mut peers = self.bootnodes.clone();
+        peers.extend(self.peers.iter().filter(|peer| !self.bootnodes.contains(peer)).cloned());
+        peers
+    }
+
+    /// Returns the peer IDs the node accepts handshakes from, or an empty list if any peer ID is accepted.
+    pub fn allowed_peer_ids(&self) -> Result<Vec<PeerId>, IdentityError> {
+        self.allowed_peers.iter().map(|peer_id| peer_id.parse()).collect()
+    }
+}
+
+fn default_readiness_threshold() -> u32 {
+    10
+}
+
+fn default_miner_threads() -> usize {
+    1
+}
+
+/// The recipients of the coinbase rewards, in place of the miner address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutConfig {
+    /// The rewards of each block are sent to one of the addresses, in turn by block height
+    Rotation(Vec<String>),
+    /// The rewards of each block are split between the addresses, by percentage
+    Split(Vec<PayoutShare>),
+}
+
+/// The share of the coinbase rewards of an address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PayoutShare {
+    pub address: String,
+    pub percent: u8,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct P2P {
     #[serde(skip_serializing, skip_deserializing)]
     pub bootnodes: Vec<String>,
+    /// The peers the node connects to on startup, next to the bootnodes
+    #[serde(default)]
+    pub peers: Vec<String>,
+    /// The peer IDs the node accepts handshakes from, or any peer ID if empty
+    #[serde(default)]
+    pub allowed_peers: Vec<String>,
     pub mempool_interval: u8,
     pub min_peers: u16,
     pub max_peers: u16,
 }
 
+/// The sources of the parameters downloaded by the node
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ParametersConfig {
+    /// The mirrors the missing parameters are downloaded from, in order of preference, or the default mirror if empty
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// The directory of the insecure test parameters the SNARK parameters are loaded from, if any
+    #[serde(default)]
+    pub test_dir: Option<PathBuf>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -104,32 +333,179 @@ impl Default for Config {
                 ip: "0.0.0.0".into(),
                 port: 4131,
                 verbose: 1,
+                prune: None,
+                archive: None,
+                gc: None,
+                readiness_threshold: default_readiness_threshold(),
             },
             miner: Miner {
                 is_miner: false,
                 miner_address: "".into(),
+                threads: default_miner_threads(),
+                thread_policy: MiningThreadPolicy::default(),
+                prover: None,
+                stratum: None,
+                worker: None,
+                failover: None,
+                payout: None,
+                tag: None,
+                max_block_size: None,
+                max_block_weight: None,
             },
             rpc: JsonRPC {
                 json_rpc: true,
                 port: 3030,
+                ws_port: None,
                 // TODO (raychu86) Establish a random username and password for the node operator by default
                 username: Some("Username".into()),
                 password: Some("Password".into()),
+                tokens: vec![],
+                tls: None,
+                cors: None,
+                compression: None,
+                ipc: None,
+                graphql: false,
+                webhooks: vec![],
+                versions: RpcVersionsConfig::default(),
+                limits: RpcLimitsConfig::default(),
             },
             p2p: P2P {
                 bootnodes: TESTNET_BOOTNODES
                     .iter()
                     .map(|node| (*node).to_string())
                     .collect::<Vec<String>>(),
+                peers: vec![],
+                allowed_peers: vec![],
                 mempool_interval: 5,
                 min_peers: 7,
                 max_peers: 25,
             },
+            storage: StorageConfig::default(),
+            log: LogConfig::default(),
+            limits: ResourceLimits::default(),
+            parameters: ParametersConfig::default(),
+            dev: None,
         }
     }
 }
 
 impl Config {
+    /// Returns the network the node runs on.
+    pub fn network(&self) -> Network {
+        Network::from_network_id(self.aleo.network_id)
+    }
+
+    /// Returns the parameters of the network the node runs on.
+    pub fn network_parameters(&self) -> NetworkParameters {
+        NetworkParameters::of(self.network())
+    }
+
+    /// Returns the public parameters of the configured network, with the proving parameters
+    /// unless verifying only.
+    pub fn public_parameters(&self, verify_only: bool) -> IoResult<PublicParameters<Components>> {
+        (self.network_parameters().public_parameters)(verify_only)
+    }
+
+    /// Returns the serialized genesis block of the configured network.
+    pub fn genesis_block(&self) -> Vec<u8> {
+        (self.network_parameters().genesis_block)()
+    }
+
+    /// Returns the data directory of the node.
+    pub fn data_dir(&self) -> DataDir {
+        DataDir::new(self.node.dir.clone())
+    }
+
+    /// Returns the path of the ledger of the configured network.
+    pub fn storage_path(&self) -> PathBuf {
+        self.data_dir().storage(&self.node.db)
+    }
+
+    /// Returns the path of the file storing the identity key of the node.
+    pub fn identity_path(&self) -> PathBuf {
+        self.data_dir().keys().join(IDENTITY_FILE)
+    }
+
+    /// Returns the tuning options of the storage, with block caches within the cache limit of the node.
+    pub fn storage_config(&self) -> StorageConfig {
+        let mut storage = self.storage.clone();
+        if let Some(max_cache_mb) = self.limits.max_cache_mb {
+            storage.max_cache_mb = Some(storage.max_cache_mb.map_or(max_cache_mb, |mb| mb.min(max_cache_mb)));
+        }
+
+        storage
+    }
+
+    /// Opens the ledger of the configured network at the given path. An existing ledger must start
+    /// from the genesis block of the network.
+    pub fn open_ledger(&self, path: PathBuf) -> Result<MerkleTreeLedger, StorageError> {
+        MerkleTreeLedger::open_at_path_with_genesis(path, &self.storage_config(), &self.genesis_block())
+    }
+
+    /// Returns the consensus parameters of the configured network, which authorize the
+    /// inner snark of the given parameters.
+    pub fn consensus_parameters(
+        &self,
+        parameters: &PublicParameters<Components>,
+    ) -> Result<ConsensusParameters, CliError> {
+        // Fetch the valid inner snark ids
+        let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
+            parameters.inner_snark_parameters.1.clone().into();
+        let inner_snark_id = parameters
+            .system_parameters
+            .inner_snark_verification_key_crh
+            .hash(&to_bytes![inner_snark_vk]?)?;
+
+        let authorized_inner_snark_ids = vec![to_bytes![inner_snark_id]?];
+        let network_parameters = self.network_parameters();
+
+        Ok(ConsensusParameters {
+            max_block_size: network_parameters.max_block_size,
+            max_nonce: u32::max_value(),
+            target_block_time: network_parameters.target_block_time,
+            network: network_parameters.network,
+            verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
+            authorized_inner_snark_ids,
+            fixed_difficulty_target: self.dev.as_ref().map(|_| DEV_DIFFICULTY_TARGET),
+            max_template_size: self.miner.max_block_size,
+            max_template_weight: self.miner.max_block_weight,
+        })
+    }
+
+    /// Returns the miner address, read in strictly for the configured network.
+    pub fn miner_address(&self) -> Result<AccountAddress<Components>, AccountError> {
+        AccountAddress::<Components>::from_network_str(&self.miner.miner_address, self.network())
+    }
+
+    /// Returns the configured recipients of the coinbase rewards, if any.
+    pub fn coinbase_payout(&self) -> Result<Option<CoinbasePayout>, CliError> {
+        let network = self.network();
+        let parse_address = |address: &str| {
+            AccountAddress::<Components>::from_network_str(address, network)
+                .map_err(|_| ConsensusError::InvalidCoinbasePayout(format!("invalid address {}", address)))
+        };
+
+        let payout = match &self.miner.payout {
+            Some(PayoutConfig::Rotation(addresses)) => CoinbasePayout::Rotation(
+                addresses
+                    .iter()
+                    .map(|address| parse_address(address))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some(PayoutConfig::Split(shares)) => CoinbasePayout::Split(
+                shares
+                    .iter()
+                    .map(|share| Ok((parse_address(&share.address)?, share.percent)))
+                    .collect::<Result<_, ConsensusError>>()?,
+            ),
+            None => return Ok(None),
+        };
+
+        payout.validate()?;
+
+        Ok(Some(payout))
+    }
+
     /// The directory that snarkOS system files will be stored
     fn snarkos_dir() -> PathBuf {
         let mut path = home_dir().unwrap_or_else(|| std::env::current_dir().unwrap());
@@ -138,95 +514,189 @@ impl Config {
         path
     }
 
-    /// Read the config from the `config.toml` file
-    fn read_config() -> Result<Self, CliError> {
-        let snarkos_path = Self::snarkos_dir();
-        let mut config_path = snarkos_path.clone();
-        config_path.push("config.toml");
+    /// Returns the path of the configuration file, which is the given file if any, or else the `snarkos.toml`
+    /// file of the working directory if it exists, or else the `config.toml` file of the snarkOS directory.
+    fn config_path(path: Option<&str>) -> PathBuf {
+        if let Some(path) = path {
+            return PathBuf::from(path);
+        }
 
-        if !Path::exists(&config_path) {
-            // Create a new default `config.toml` file if it doesn't already exist
-            fs::create_dir_all(&snarkos_path)?;
+        let working_config_path = PathBuf::from(CONFIG_FILE_NAME);
+        if working_config_path.exists() {
+            return working_config_path;
+        }
+
+        let mut config_path = Self::snarkos_dir();
+        config_path.push("config.toml");
+        config_path
+    }
 
-            let default_config_string = toml::to_string(&Config::default())?;
+    /// Read the config from the given file, or from the default configuration file.
+    /// The default `config.toml` file is created if it does not exist, but a given file must exist.
+    fn read_config(path: Option<&str>) -> Result<Self, CliError> {
+        let config_path = Self::config_path(path);
 
-            fs::write(&config_path, default_config_string)?;
-        }
+        let toml_string = if path.is_some() {
+            fs::read_to_string(&config_path)?
+        } else {
+            let snarkos_path = Self::snarkos_dir();
 
-        let toml_string = match fs::read_to_string(&config_path) {
-            Ok(toml) => toml,
-            Err(_) => {
+            if !Path::exists(&config_path) {
+                // Create a new default `config.toml` file if it doesn't already exist
                 fs::create_dir_all(&snarkos_path)?;
-                String::new()
+
+                let default_config_string = toml::to_string(&Config::default())?;
+
+                fs::write(&config_path, default_config_string)?;
+            }
+
+            match fs::read_to_string(&config_path) {
+                Ok(toml) => toml,
+                Err(_) => {
+                    fs::create_dir_all(&snarkos_path)?;
+                    String::new()
+                }
             }
         };
 
         // Parse the contents into the `Config` struct
         let mut config: Config = toml::from_str(&toml_string)?;
 
-        let bootnodes = match config.aleo.network_id {
-            0 => MAINNET_BOOTNODES,
-            _ => TESTNET_BOOTNODES,
-        };
-
-        config.p2p.bootnodes = bootnodes
+        config.p2p.bootnodes = config
+            .network_parameters()
+            .bootnodes
             .iter()
             .map(|node| (*node).to_string())
             .collect::<Vec<String>>();
 
+        if config.dev.is_some() {
+            config.dev(true);
+        }
+
         Ok(config)
     }
 
-    fn parse(&mut self, arguments: &ArgMatches, options: &[&str]) {
-        options.iter().for_each(|option| match *option {
-            // Flags
-            "is-bootnode" => self.is_bootnode(arguments.is_present(option)),
-            "is-miner" => self.is_miner(arguments.is_present(option)),
-            "no-jsonrpc" => self.no_jsonrpc(arguments.is_present(option)),
-            // Options
-            "connect" => self.connect(arguments.value_of(option)),
-            "ip" => self.ip(arguments.value_of(option)),
-            "miner-address" => self.miner_address(arguments.value_of(option)),
-            "mempool-interval" => self.mempool_interval(clap::value_t!(arguments.value_of(*option), u8).ok()),
-            "max-peers" => self.max_peers(clap::value_t!(arguments.value_of(*option), u16).ok()),
-            "min-peers" => self.min_peers(clap::value_t!(arguments.value_of(*option), u16).ok()),
-            "network" => self.network(clap::value_t!(arguments.value_of(*option), u8).ok()),
-            "path" => self.path(arguments.value_of(option)),
-            "port" => self.port(clap::value_t!(arguments.value_of(*option), u16).ok()),
-            "rpc-port" => self.rpc_port(clap::value_t!(arguments.value_of(*option), u16).ok()),
-            "rpc-username" => self.rpc_username(arguments.value_of(option)),
-            "rpc-password" => self.rpc_password(arguments.value_of(option)),
-            "verbose" => self.verbose(clap::value_t!(arguments.value_of(*option), u8).ok()),
-            _ => (),
-        });
-    }
-
-    /// Sets `network` to the specified network, overriding its previous state.
-    fn network(&mut self, argument: Option<u8>) {
-        if let Some(network_id) = argument {
-            match network_id {
-                0 => {
-                    self.node.db = "snarkos_mainnet".into();
-                    self.node.port = 4130;
-                    self.p2p.bootnodes = MAINNET_BOOTNODES
-                        .iter()
-                        .map(|node| (*node).to_string())
-                        .collect::<Vec<String>>();
-                    self.aleo.network_id = network_id;
+    /// Overrides the options with the environment variables named after them, such as `SNARKOS_MINER_ADDRESS`
+    /// for `--miner-address`. The flags are set by `true` or `1`, and unset by `false` or `0`.
+    fn parse_env(&mut self, options: &[&str]) {
+        for option in options {
+            let value = match env::var(env_var_name(option)) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+
+            if CONFIG_FLAGS.contains(option) {
+                match value.as_str() {
+                    "true" | "1" => self.set_flag(option, true),
+                    "false" | "0" => self.set_flag(option, false),
+                    _ => (),
                 }
-                _ => {
-                    self.node.db = format!("snarkos_testnet{}", network_id);
-                    self.node.port = 4130 + (network_id as u16);
-                    self.p2p.bootnodes = TESTNET_BOOTNODES
-                        .iter()
-                        .map(|node| (*node).to_string())
-                        .collect::<Vec<String>>();
-                    self.aleo.network_id = network_id;
+            } else {
+                self.set_option(option, Some(&value));
+            }
+        }
+    }
+
+    /// Overrides the options with the command line arguments. The flags are only set if they are present,
+    /// so an absent flag leaves the configured value.
+    fn parse(&mut self, arguments: &ArgMatches, options: &[&str]) {
+        for option in options {
+            if CONFIG_FLAGS.contains(option) {
+                if arguments.is_present(option) {
+                    self.set_flag(option, true);
                 }
+            } else {
+                self.set_option(option, arguments.value_of(option));
             }
         }
     }
 
+    fn set_flag(&mut self, flag: &str, value: bool) {
+        match flag {
+            "dev" => self.dev(value),
+            "is-bootnode" => self.is_bootnode(value),
+            "is-miner" => self.is_miner(value),
+            "miner-low-priority" => self.miner_low_priority(value),
+            "log-json" => self.log_json(value),
+            "log-file" => self.log_file(value),
+            "no-jsonrpc" => self.no_jsonrpc(value),
+            _ => (),
+        }
+    }
+
+    fn set_option(&mut self, option: &str, value: Option<&str>) {
+        match option {
+            "connect" => self.connect(value),
+            "ip" => self.ip(value),
+            "miner-address" => self.miner_address(value),
+            "miner-threads" => self.miner_threads(parse_value(value)),
+            "miner-cores" => self.miner_cores(value),
+            "miner-tag" => self.miner_tag(value),
+            "miner-max-block-size" => self.miner_max_block_size(parse_value(value)),
+            "miner-max-block-weight" => self.miner_max_block_weight(parse_value(value)),
+            "worker" => self.worker(value),
+            "miner-nodes" => self.miner_nodes(value),
+            "mempool-interval" => self.mempool_interval(parse_value(value)),
+            "max-peers" => self.max_peers(parse_value(value)),
+            "min-peers" => self.min_peers(parse_value(value)),
+            "network" => self.set_network(parse_value(value)),
+            "data-dir" => self.set_data_dir(value),
+            "path" => self.path(value),
+            "port" => self.port(parse_value(value)),
+            "prune" => self.prune(parse_value(value)),
+            "archive" => self.archive(parse_value(value)),
+            "gc" => self.gc(parse_value(value)),
+            "readiness-threshold" => self.readiness_threshold(parse_value(value)),
+            "rpc-port" => self.rpc_port(parse_value(value)),
+            "rpc-ws-port" => self.rpc_ws_port(parse_value(value)),
+            "rpc-username" => self.rpc_username(value),
+            "rpc-password" => self.rpc_password(value),
+            "verbose" => self.verbose(parse_value(value)),
+            "log-filter" => self.log_filter(value),
+            "max-upload" => self.max_upload(parse_value(value)),
+            "max-download" => self.max_download(parse_value(value)),
+            "verification-threads" => self.verification_threads(parse_value(value)),
+            "max-cache" => self.max_cache(parse_value(value)),
+            "parameters-mirrors" => self.parameters_mirrors(value),
+            "test-parameters" => self.test_parameters(value),
+            "dev-block-interval" => self.dev_block_interval(parse_value(value)),
+            "dev-accounts" => self.dev_accounts(parse_value(value)),
+            _ => (),
+        }
+    }
+
+    /// Sets `network` to the specified network, overriding its previous state along with the network defaults.
+    fn set_network(&mut self, argument: Option<Network>) {
+        if let Some(network) = argument {
+            let network_parameters = NetworkParameters::of(network);
+
+            self.node.db = network_parameters.db();
+            self.node.port = network_parameters.port;
+            self.rpc.port = network_parameters.rpc_port;
+            self.p2p.bootnodes = network_parameters
+                .bootnodes
+                .iter()
+                .map(|node| (*node).to_string())
+                .collect::<Vec<String>>();
+            self.aleo.network_id = network.id();
+        }
+    }
+
+    /// Runs a single node development network, which mines its own blocks at the trivial difficulty,
+    /// or disables the development mode.
+    fn dev(&mut self, argument: bool) {
+        if argument {
+            self.set_network(Some(Network::Devnet));
+            self.dev.get_or_insert_with(DevConfig::default);
+            self.node.is_bootnode = true;
+            self.miner.is_miner = true;
+            self.p2p.bootnodes = vec![];
+            self.p2p.min_peers = 0;
+        } else {
+            self.dev = None;
+        }
+    }
+
     fn no_jsonrpc(&mut self, argument: bool) {
         self.rpc.json_rpc = !argument;
     }
@@ -254,12 +724,42 @@ impl Config {
         }
     }
 
+    fn set_data_dir(&mut self, argument: Option<&str>) {
+        if let Some(dir) = argument {
+            self.node.dir = PathBuf::from(dir);
+        }
+    }
+
     fn path(&mut self, argument: Option<&str>) {
         if let Some(path) = argument {
             self.node.db = path.into();
         }
     }
 
+    fn prune(&mut self, argument: Option<u32>) {
+        if let Some(retention) = argument {
+            self.node.prune = Some(retention);
+        }
+    }
+
+    fn archive(&mut self, argument: Option<u32>) {
+        if let Some(retention) = argument {
+            self.node.archive = Some(retention);
+        }
+    }
+
+    fn gc(&mut self, argument: Option<u32>) {
+        if let Some(reorg_window) = argument {
+            self.node.gc = Some(reorg_window);
+        }
+    }
+
+    fn readiness_threshold(&mut self, argument: Option<u32>) {
+        if let Some(threshold) = argument {
+            self.node.readiness_threshold = threshold;
+        }
+    }
+
     fn connect(&mut self, argument: Option<&str>) {
         if let Some(bootnodes) = argument {
             let sanitize_bootnodes = bootnodes.replace(&['[', ']', ' '][..], "");
@@ -274,6 +774,71 @@ impl Config {
         }
     }
 
+    fn miner_threads(&mut self, argument: Option<usize>) {
+        if let Some(threads) = argument {
+            self.miner.threads = threads;
+        }
+    }
+
+    fn miner_cores(&mut self, argument: Option<&str>) {
+        if let Some(cores) = argument {
+            let sanitize_cores = cores.replace(&['[', ']', ' '][..], "");
+            self.miner.thread_policy.cores = sanitize_cores
+                .split(',')
+                .filter_map(|core| core.parse::<usize>().ok())
+                .collect();
+        }
+    }
+
+    fn miner_tag(&mut self, argument: Option<&str>) {
+        if let Some(tag) = argument {
+            self.miner.tag = Some(tag.to_string());
+        }
+    }
+
+    fn miner_max_block_size(&mut self, argument: Option<usize>) {
+        if let Some(max_block_size) = argument {
+            self.miner.max_block_size = Some(max_block_size);
+        }
+    }
+
+    fn miner_max_block_weight(&mut self, argument: Option<usize>) {
+        if let Some(max_block_weight) = argument {
+            self.miner.max_block_weight = Some(max_block_weight);
+        }
+    }
+
+    fn miner_low_priority(&mut self, argument: bool) {
+        self.miner.thread_policy.low_priority = argument;
+    }
+
+    fn worker(&mut self, argument: Option<&str>) {
+        if let Some(coordinator) = argument {
+            self.miner.worker.get_or_insert_with(WorkerConfig::default).coordinator = coordinator.to_string();
+        }
+    }
+
+    fn miner_nodes(&mut self, argument: Option<&str>) {
+        if let Some(nodes) = argument {
+            let sanitize_nodes = nodes.replace(&['[', ']', ' '][..], "");
+            let nodes: Vec<String> = sanitize_nodes.split(',').map(|s| s.to_string()).collect();
+            self.miner.failover.get_or_insert_with(FailoverConfig::default).nodes = nodes;
+        }
+    }
+
+    fn parameters_mirrors(&mut self, argument: Option<&str>) {
+        if let Some(mirrors) = argument {
+            let sanitize_mirrors = mirrors.replace(&['[', ']', ' '][..], "");
+            self.parameters.mirrors = sanitize_mirrors.split(',').map(|s| s.to_string()).collect();
+        }
+    }
+
+    fn test_parameters(&mut self, argument: Option<&str>) {
+        if let Some(dir) = argument {
+            self.parameters.test_dir = Some(dir.into());
+        }
+    }
+
     fn mempool_interval(&mut self, argument: Option<u8>) {
         if let Some(interval) = argument {
             self.p2p.mempool_interval = interval
@@ -298,6 +863,12 @@ impl Config {
         }
     }
 
+    fn rpc_ws_port(&mut self, argument: Option<u16>) {
+        if let Some(ws_port) = argument {
+            self.rpc.ws_port = Some(ws_port);
+        }
+    }
+
     fn rpc_username(&mut self, argument: Option<&str>) {
         if let Some(username) = argument {
             self.rpc.username = Some(username.to_string());
@@ -315,6 +886,56 @@ impl Config {
             self.node.verbose = verbose
         }
     }
+
+    fn log_filter(&mut self, argument: Option<&str>) {
+        if let Some(directives) = argument {
+            self.log.set_modules(directives);
+        }
+    }
+
+    fn log_json(&mut self, argument: bool) {
+        self.log.json = argument;
+    }
+
+    fn log_file(&mut self, argument: bool) {
+        self.log.file = argument;
+    }
+
+    fn max_upload(&mut self, argument: Option<u64>) {
+        if let Some(max_upload_kb) = argument {
+            self.limits.max_upload_kb = Some(max_upload_kb);
+        }
+    }
+
+    fn max_download(&mut self, argument: Option<u64>) {
+        if let Some(max_download_kb) = argument {
+            self.limits.max_download_kb = Some(max_download_kb);
+        }
+    }
+
+    fn verification_threads(&mut self, argument: Option<usize>) {
+        if let Some(threads) = argument {
+            self.limits.verification_threads = Some(threads);
+        }
+    }
+
+    fn max_cache(&mut self, argument: Option<u64>) {
+        if let Some(max_cache_mb) = argument {
+            self.limits.max_cache_mb = Some(max_cache_mb);
+        }
+    }
+
+    fn dev_block_interval(&mut self, argument: Option<u64>) {
+        if let (Some(dev), Some(block_interval)) = (&mut self.dev, argument) {
+            dev.block_interval = block_interval;
+        }
+    }
+
+    fn dev_accounts(&mut self, argument: Option<u32>) {
+        if let (Some(dev), Some(accounts)) = (&mut self.dev, argument) {
+            dev.accounts = accounts;
+        }
+    }
 }
 
 /// Parses command line arguments into node configuration parameters.
@@ -324,52 +945,72 @@ impl CLI for ConfigCli {
     type Config = Config;
 
     const ABOUT: AboutType = "Run an Aleo node (include -h for more options)";
-    const FLAGS: &'static [FlagType] = &[flag::NO_JSONRPC, flag::IS_BOOTNODE, flag::IS_MINER];
+    const FLAGS: &'static [FlagType] = flag::NODE;
     const NAME: NameType = "snarkOS";
-    const OPTIONS: &'static [OptionType] = &[
-        option::IP,
-        option::PORT,
-        option::PATH,
-        option::CONNECT,
-        option::MINER_ADDRESS,
-        option::MEMPOOL_INTERVAL,
-        option::MIN_PEERS,
-        option::MAX_PEERS,
-        option::NETWORK,
-        option::RPC_PORT,
-        option::RPC_USERNAME,
-        option::RPC_PASSWORD,
-        option::VERBOSE,
+    const OPTIONS: &'static [OptionType] = option::NODE;
+    const SUBCOMMANDS: &'static [SubCommandType] = &[
+        subcommand::START,
+        subcommand::ACCOUNT,
+        subcommand::STORAGE,
+        subcommand::RPC,
+        subcommand::MINER,
+        subcommand::PARAMETERS,
+        subcommand::UPDATE,
     ];
-    const SUBCOMMANDS: &'static [SubCommandType] = &[subcommand::UPDATE];
 
-    /// Handle all CLI arguments and flags for skeleton node
+    /// Handle all CLI arguments and flags for skeleton node.
+    /// The options are read from the configuration file, then overridden by the environment variables,
+    /// then by the command line arguments, given to the start subcommand or directly.
+    /// The other subcommands run with the configuration, and exit once they are done.
     fn parse(arguments: &ArgMatches) -> Result<Self::Config, CliError> {
-        let mut config = Config::read_config()?;
-        config.parse(arguments, &[
-            "network",
-            "no-jsonrpc",
-            "is-bootnode",
-            "is-miner",
-            "ip",
-            "port",
-            "path",
-            "connect",
-            "miner-address",
-            "mempool-interval",
-            "min-peers",
-            "max-peers",
-            "rpc-port",
-            "rpc-username",
-            "rpc-password",
-            "verbose",
-        ]);
+        let start_arguments = match arguments.subcommand() {
+            ("start", Some(start_arguments)) => Some(start_arguments),
+            _ => None,
+        };
+
+        let config_path = start_arguments
+            .and_then(|start_arguments| start_arguments.value_of("config"))
+            .or_else(|| arguments.value_of("config"))
+            .map(|path| path.to_string())
+            .or_else(|| env::var(env_var_name("config")).ok());
+
+        let mut config = Config::read_config(config_path.as_deref())?;
+        config.parse_env(CONFIG_OPTIONS);
+        config.parse(arguments, CONFIG_OPTIONS);
+        if let Some(start_arguments) = start_arguments {
+            config.parse(start_arguments, CONFIG_OPTIONS);
+        }
 
         if let ("update", Some(arguments)) = arguments.subcommand() {
             UpdateCLI::parse(arguments)?;
             std::process::exit(0x0100);
         }
 
+        if let ("account", Some(arguments)) = arguments.subcommand() {
+            AccountCLI::parse(arguments, &config)?;
+            std::process::exit(0x0100);
+        }
+
+        if let ("storage", Some(arguments)) = arguments.subcommand() {
+            StorageCLI::parse(arguments, &config)?;
+            std::process::exit(0x0100);
+        }
+
+        if let ("rpc", Some(arguments)) = arguments.subcommand() {
+            RpcClientCLI::parse(arguments, &config)?;
+            std::process::exit(0x0100);
+        }
+
+        if let ("miner", Some(arguments)) = arguments.subcommand() {
+            MinerCLI::parse(arguments, &config)?;
+            std::process::exit(0x0100);
+        }
+
+        if let ("parameters", Some(arguments)) = arguments.subcommand() {
+            TestParametersCLI::parse(arguments, &config)?;
+            std::process::exit(0x0100);
+        }
+
         Ok(config)
     }
 }
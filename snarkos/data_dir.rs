@@ -0,0 +1,136 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::node::CliError;
+
+use fs2::FileExt;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// The lock file of the data directory, holding the process ID of the node using the directory.
+const LOCK_FILE: &str = "LOCK";
+
+/// The data directory of a node, which holds:
+/// - `storage/`, the ledger of each network
+/// - `keys/`, the identity key of the node
+/// - `parameters/`, the parameters downloaded by the node
+/// - `test_parameters/`, the insecure test parameters generated by `snarkos parameters generate`, if any
+/// - `logs/`, the log files of the node, if enabled
+/// - `LOCK`, the lock file preventing two nodes from using the directory at once
+#[derive(Clone, Debug)]
+pub struct DataDir {
+    root: PathBuf,
+}
+
+/// The exclusive lock of a data directory, released when it is dropped or when the process exits.
+#[derive(Debug)]
+pub struct DataDirLock {
+    _file: File,
+}
+
+impl DataDir {
+    /// Returns the data directory at the given path.
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Returns the path of the data directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of the ledger of the given database name.
+    pub fn storage(&self, db: &str) -> PathBuf {
+        self.root.join("storage").join(db)
+    }
+
+    /// Returns the directory of the keys of the node.
+    pub fn keys(&self) -> PathBuf {
+        self.root.join("keys")
+    }
+
+    /// Returns the directory of the downloaded parameters.
+    pub fn parameters(&self) -> PathBuf {
+        self.root.join("parameters")
+    }
+
+    /// Returns the default directory of the generated insecure test parameters.
+    pub fn test_parameters(&self) -> PathBuf {
+        self.root.join("test_parameters")
+    }
+
+    /// Returns the directory of the log files.
+    pub fn logs(&self) -> PathBuf {
+        self.root.join("logs")
+    }
+
+    /// Locks the data directory for this process, and creates its layout.
+    /// Fails with the process ID of the node using the directory if it is already locked.
+    pub fn lock(&self) -> Result<DataDirLock, CliError> {
+        fs::create_dir_all(&self.root)?;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(self.root.join(LOCK_FILE))?;
+
+        if file.try_lock_exclusive().is_err() {
+            let mut holder = String::new();
+            let holder = match file.read_to_string(&mut holder) {
+                Ok(_) if !holder.trim().is_empty() => format!("the process {}", holder.trim()),
+                _ => "another process".to_string(),
+            };
+
+            return Err(CliError::DataDirLocked(self.root.display().to_string(), holder));
+        }
+
+        file.set_len(0)?;
+        file.write_all(process::id().to_string().as_bytes())?;
+
+        for dir in &[self.root.join("storage"), self.keys(), self.parameters(), self.logs()] {
+            fs::create_dir_all(dir)?;
+        }
+
+        Ok(DataDirLock { _file: file })
+    }
+
+    /// Moves the ledgers stored at the root of the directory by the previous versions into the storage directory.
+    /// The directory must be locked.
+    pub fn migrate_storage(&self) -> Result<(), CliError> {
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+
+            // A RocksDB database always holds a CURRENT file
+            if path.is_dir() && path.join("CURRENT").exists() {
+                if let Some(db) = path.file_name().and_then(|name| name.to_str()) {
+                    let storage = self.storage(db);
+
+                    if !storage.exists() {
+                        info!("Moving the ledger {} into {}", path.display(), storage.display());
+                        fs::rename(&path, &storage)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::SystemParameters};
+use snarkos_errors::objects::AccountError;
+use snarkos_objects::{Account, AccountDerivationPath, AccountSeed};
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The master seed of the accounts of the development network. The accounts are known to everyone,
+/// so they must never hold the coins of another network.
+const DEV_ACCOUNT_SEED: [u8; 32] = *b"snarkos development account seed";
+
+/// The difficulty target of the blocks of the development network, met by any proof of work.
+pub const DEV_DIFFICULTY_TARGET: u64 = u64::MAX;
+
+/// The settings of a single node development network, run with `--dev`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DevConfig {
+    /// The number of seconds between two blocks, or 0 to only mine the blocks requested with the
+    /// `generateblocks` RPC endpoint
+    #[serde(default = "default_block_interval")]
+    pub block_interval: u64,
+    /// The number of deterministic accounts the rewards of the first blocks are sent to
+    #[serde(default = "default_accounts")]
+    pub accounts: u32,
+}
+
+fn default_block_interval() -> u64 {
+    5
+}
+
+fn default_accounts() -> u32 {
+    10
+}
+
+impl Default for DevConfig {
+    fn default() -> Self {
+        Self {
+            block_interval: default_block_interval(),
+            accounts: default_accounts(),
+        }
+    }
+}
+
+impl DevConfig {
+    /// Returns the time between two blocks, or `None` if the blocks are only mined on request.
+    pub fn block_interval(&self) -> Option<Duration> {
+        match self.block_interval {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+
+    /// Returns the seed the development accounts are derived from.
+    pub fn account_seed() -> AccountSeed {
+        AccountSeed::from_bytes(DEV_ACCOUNT_SEED)
+    }
+
+    /// Derives the development accounts, at the paths `m/0`, `m/1` and so on of the development seed.
+    pub fn accounts(
+        &self,
+        parameters: &SystemParameters<Components>,
+    ) -> Result<Vec<Account<Components>>, AccountError> {
+        let seed = Self::account_seed();
+
+        (0..self.accounts)
+            .map(|index| {
+                seed.derive_account::<Components>(
+                    &parameters.account_signature,
+                    &parameters.account_commitment,
+                    &parameters.account_encryption,
+                    &AccountDerivationPath::default().child(index),
+                )
+            })
+            .collect()
+    }
+}
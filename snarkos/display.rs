@@ -14,12 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::config::Config;
+use crate::{config::Config, dev::DevConfig};
 use snarkos_dpc::base_dpc::instantiated::Components;
-use snarkos_objects::AccountAddress;
+use snarkos_objects::{Account, Network};
 
 use colored::*;
-use std::str::FromStr;
 
 pub fn render_init(config: &Config) -> String {
     let mut output = String::new();
@@ -45,12 +44,17 @@ pub fn render_init(config: &Config) -> String {
 
     let mut is_miner = config.miner.is_miner;
     if is_miner {
-        match AccountAddress::<Components>::from_str(&config.miner.miner_address) {
+        match config.miner_address() {
             Ok(miner_address) => {
                 output += &format!("Your Aleo address is {}.\n\n", miner_address)
                     .bold()
                     .to_string();
             }
+            Err(_) if config.dev.is_some() => {
+                output += &"The block rewards are sent to the development accounts.\n\n"
+                    .bold()
+                    .to_string();
+            }
             Err(_) => {
                 output +=
                     &"Miner not started. Please specify a valid miner address in your ~/.snarkOS/snarkOS.toml file or by using the --miner-address option in the CLI.\n\n"
@@ -61,11 +65,15 @@ pub fn render_init(config: &Config) -> String {
         }
     }
 
-    let network = match config.aleo.network_id {
-        0 => "mainnet".to_string(),
-        i => format!("testnet{}", i),
+    let network = match config.network() {
+        Network::Custom(id) => format!("testnet{}", id),
+        network => network.name(),
     };
-    if is_miner {
+    if config.dev.is_some() {
+        output += &format!("Starting a development node on {}.\n\n", network)
+            .bold()
+            .to_string();
+    } else if is_miner {
         output += &format!("Starting a mining node on {}.\n\n", network).bold().to_string();
     } else {
         output += &format!("Starting a client node on {}.\n\n", network).bold().to_string();
@@ -77,3 +85,28 @@ pub fn render_init(config: &Config) -> String {
 
     output
 }
+
+/// Renders the pre-funded accounts of the development network, with their private keys.
+pub fn render_dev_accounts(config: &Config, accounts: &[Account<Components>]) -> String {
+    let mut output = String::new();
+
+    output += &"Development accounts (never send the coins of another network to them)\n\n"
+        .yellow()
+        .bold();
+
+    for (index, account) in accounts.iter().enumerate() {
+        output += &format!(
+            "({}) {}\n    Private Key: {}\n",
+            index,
+            account.address.to_network_string(config.network()),
+            account.private_key
+        );
+    }
+
+    output += &format!(
+        "\nMnemonic: {}\nDerivation path: m/<index>\n",
+        DevConfig::account_seed().to_mnemonic()
+    );
+
+    output
+}
@@ -17,9 +17,20 @@
 #[macro_use]
 extern crate tracing;
 
+pub mod account;
 pub mod cli;
 pub mod config;
+pub mod data_dir;
+pub mod dev;
 pub mod display;
+pub mod limits;
+pub mod logging;
 pub mod miner;
+pub mod networks;
 pub mod parameters;
+pub mod reload;
+pub mod rpc_client;
+pub mod service;
+pub mod storage;
+pub mod test_parameters;
 pub mod update;
@@ -0,0 +1,54 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::set_verification_threads;
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_network::external::set_bandwidth_limits;
+
+use serde::{Deserialize, Serialize};
+
+const KILOBYTE: u64 = 1024;
+
+/// The resources a node uses at most, so it can share its machine with other services.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Maximum rate of the messages sent to all the peers, in kilobytes per second
+    pub max_upload_kb: Option<u64>,
+    /// Maximum rate of the messages received from all the peers, in kilobytes per second
+    pub max_download_kb: Option<u64>,
+    /// Maximum number of threads verifying the transactions of blocks, one per core if unset
+    pub verification_threads: Option<usize>,
+    /// Maximum size of the block caches of the storage, in megabytes
+    pub max_cache_mb: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Limits the bandwidth of the peer connections.
+    pub fn apply_bandwidth(&self) {
+        set_bandwidth_limits(
+            self.max_upload_kb.map(|kb| kb * KILOBYTE),
+            self.max_download_kb.map(|kb| kb * KILOBYTE),
+        );
+    }
+
+    /// Limits the bandwidth of the peer connections and the verification threads.
+    /// The cache limit is applied when the storage is opened.
+    pub fn apply(&self) -> Result<(), ConsensusError> {
+        self.apply_bandwidth();
+        set_verification_threads(self.verification_threads)
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use snarkos_errors::node::NodeError;
+use snarkos_rpc::LogFilterReloader;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use tracing::Subscriber;
+use tracing_subscriber::{reload::Handle, EnvFilter};
+
+/// The directive disabling the undesirable logs, added to every log filter.
+const DISABLED_LOGS: &str = "tokio_reactor=off";
+
+/// The file of the logs directory the logs are written to, if enabled.
+const LOG_FILE: &str = "snarkos.log";
+
+/// The log settings of the node.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// The log levels of modules, such as `snarkos_network = "debug"`, over the level of the verbosity
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
+    /// Writes the logs as JSON objects, one per line, for log aggregation pipelines
+    #[serde(default)]
+    pub json: bool,
+    /// Writes the logs to the `snarkos.log` file of the logs directory, in place of the standard output
+    #[serde(default)]
+    pub file: bool,
+}
+
+impl LogConfig {
+    /// Returns the log filter directives of the given verbosity and of the module levels,
+    /// such as `info,snarkos_network=debug`.
+    pub fn directives(&self, verbose: u8) -> String {
+        let level = match verbose {
+            2 => "debug",
+            _ => "info",
+        };
+
+        let mut directives = vec![level.to_string()];
+        directives.extend(
+            self.modules
+                .iter()
+                .map(|(module, level)| format!("{}={}", module, level)),
+        );
+
+        directives.join(",")
+    }
+
+    /// Sets the levels of the modules of the given directives, such as `snarkos_network=debug,snarkos_rpc=warn`.
+    /// The directives without a module are ignored.
+    pub fn set_modules(&mut self, directives: &str) {
+        for directive in directives.split(',') {
+            let mut parts = directive.splitn(2, '=');
+
+            if let (Some(module), Some(level)) = (parts.next(), parts.next()) {
+                if !module.trim().is_empty() && !level.trim().is_empty() {
+                    self.modules.insert(module.trim().to_string(), level.trim().to_string());
+                }
+            }
+        }
+    }
+}
+
+/// The destination of the logs, shared by the log events.
+#[derive(Clone)]
+enum LogWriter {
+    Stdout,
+    File(Arc<Mutex<File>>),
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            LogWriter::Stdout => io::stdout().write(buf),
+            LogWriter::File(file) => file.lock().expect("log file lock poisoned").write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            LogWriter::Stdout => io::stdout().flush(),
+            LogWriter::File(file) => file.lock().expect("log file lock poisoned").flush(),
+        }
+    }
+}
+
+/// Returns the log filter of the given directives, with the undesirable logs disabled.
+fn log_filter(directives: &str) -> Result<EnvFilter, String> {
+    Ok(EnvFilter::try_new(directives)
+        .map_err(|error| error.to_string())?
+        .add_directive(DISABLED_LOGS.parse().unwrap()))
+}
+
+/// Returns a function replacing the log filter of the subscriber of the given handle.
+fn filter_reloader<S: Subscriber + 'static>(reload_handle: Handle<EnvFilter, S>) -> LogFilterReloader {
+    Arc::new(move |directives: &str| {
+        reload_handle
+            .reload(log_filter(directives)?)
+            .map_err(|error| error.to_string())
+    })
+}
+
+/// Initializes the structured logs of the node, as text or as JSON objects, with a log filter the rpc
+/// server and a configuration reload can replace. Returns `None` if the logs are disabled.
+pub fn init_logging(config: &Config) -> Result<Option<LogFilterReloader>, NodeError> {
+    if config.node.verbose == 0 {
+        return Ok(None);
+    }
+
+    let filter = log_filter(&config.log.directives(config.node.verbose)).map_err(NodeError::Message)?;
+
+    // The logs are appended to the log file of the data directory
+    let writer = if config.log.file {
+        let logs = config.data_dir().logs();
+        fs::create_dir_all(&logs)?;
+        let file = OpenOptions::new().create(true).append(true).open(logs.join(LOG_FILE))?;
+
+        LogWriter::File(Arc::new(Mutex::new(file)))
+    } else {
+        LogWriter::Stdout
+    };
+
+    let reloader = if config.log.json {
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .with_env_filter(filter)
+            .with_filter_reloading();
+        let reload_handle = subscriber.reload_handle();
+        subscriber.init();
+
+        filter_reloader(reload_handle)
+    } else {
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_env_filter(filter)
+            .with_target(false)
+            .with_filter_reloading();
+        let reload_handle = subscriber.reload_handle();
+        subscriber.init();
+
+        filter_reloader(reload_handle)
+    };
+
+    Ok(Some(reloader))
+}
@@ -20,39 +20,116 @@ extern crate tracing;
 use snarkos::{
     cli::CLI,
     config::{Config, ConfigCli},
-    display::render_init,
-    miner::MinerInstance,
+    display::{render_dev_accounts, render_init},
+    logging::init_logging,
+    miner::{failover::FailoverMiner, stratum::StratumServer, worker::ProofWorker, MinerInstance},
+    reload::NodeReloader,
+    service::{supervise, Readiness, ServiceNotifier},
 };
-use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
-use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters, BaseDPCComponents};
+use snarkos_consensus::{BlockRequests, CoinbasePayout, MemoryPool, MerkleTreeLedger, Miner, ProcessProver};
+use snarkos_dpc::base_dpc::instantiated::Tx;
 use snarkos_errors::node::NodeError;
-use snarkos_models::algorithms::{CRH, SNARK};
-use snarkos_network::{external::protocol::SyncHandler, internal::context::Context, Server};
-use snarkos_objects::{AccountAddress, Network};
-use snarkos_posw::PoswMarlin;
-use snarkos_rpc::start_rpc_server;
-use snarkos_utilities::{to_bytes, ToBytes};
-
-use std::{net::SocketAddr, str::FromStr, sync::Arc};
-use tokio::{runtime::Builder, sync::Mutex};
+use snarkos_network::{
+    external::{protocol::SyncHandler, NodeIdentity},
+    internal::{context::Context, disconnect_peers, rebroadcast_sent_transactions},
+    Server,
+};
+use snarkos_objects::Network;
+use snarkos_parameters::{
+    set_parameter_provider,
+    DefaultProvider,
+    InsecureProvider,
+    ProviderChain,
+    PARAMETERS_DIR_VAR,
+    PARAMETERS_MIRRORS_VAR,
+};
+use snarkos_rpc::{start_rpc_server, LogFilterReloader};
+use snarkos_storage::{check_prune_retention, StartupCheck};
+
+use clap::ArgMatches;
+use std::{
+    env,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{runtime::Builder, signal, sync::Mutex, task, time::delay_for};
 use tracing_futures::Instrument;
-use tracing_subscriber::EnvFilter;
 
 /// Builds a node from configuration parameters.
 /// 1. Creates new storage database or uses existing.
 /// 2. Creates new memory pool or uses existing from storage.
 /// 3. Creates consensus parameters.
-/// 4. Creates network server.
-/// 5. Starts rpc server thread.
-/// 6. Starts miner thread.
-/// 7. Starts network server listener.
-async fn start_server(config: Config) -> Result<(), NodeError> {
+/// 4. Loads the node identity and creates network server.
+/// 5. Starts pruning thread.
+/// 6. Starts archiving thread.
+/// 7. Starts garbage collection thread.
+/// 8. Starts compaction thread.
+/// 9. Starts rpc server thread.
+/// 10. Starts miner thread.
+/// 11. Starts Stratum server thread.
+/// 12. Starts network server listener.
+/// 13. Reloads the operational settings on a SIGHUP signal.
+/// 14. Notifies the service manager once the node is ready, and pings its watchdog.
+/// 15. Shuts down gracefully on a SIGINT or SIGTERM signal.
+/// If a proof worker is configured, only the worker is run.
+/// If the nodes of a solo miner are configured, only the solo miner is run.
+async fn start_server(
+    config: Config,
+    arguments: ArgMatches<'static>,
+    log_filter: Option<LogFilterReloader>,
+) -> Result<(), NodeError> {
+    if let Some(worker) = config.miner.worker {
+        let mut proof_worker =
+            ProofWorker::new(worker, config.miner.threads)?.with_thread_policy(config.miner.thread_policy)?;
+        if let Some(prover) = &config.miner.prover {
+            proof_worker = proof_worker.with_prover(ProcessProver::spawn(prover)?)?;
+        }
+        proof_worker.run().await;
+        return Ok(());
+    }
+
+    if let Some(failover) = config.miner.failover.clone() {
+        let miner_address = config.miner_address()?;
+
+        // The coinbase transactions are created by the nodes, so only the proof of work parameters are used
+        let parameters = config.public_parameters(true)?;
+        let consensus = config.consensus_parameters(&parameters)?;
+
+        let mut miner = Miner::with_threads(miner_address.clone(), consensus, config.miner.threads)
+            .with_thread_policy(config.miner.thread_policy)?;
+        if let Some(prover) = &config.miner.prover {
+            miner = miner.with_prover(ProcessProver::spawn(prover)?);
+        }
+
+        FailoverMiner::new(failover, miner_address, miner)?.run().await;
+        return Ok(());
+    }
+
+    config.limits.apply()?;
+
     let address = format! {"{}:{}", config.node.ip, config.node.port};
     let socket_address = address.parse::<SocketAddr>()?;
 
-    let mut path = config.node.dir;
-    path.push(&config.node.db);
-    let storage = Arc::new(MerkleTreeLedger::open_at_path(path.clone())?);
+    let path = config.storage_path();
+    let storage = Arc::new(config.open_ledger(path.clone())?);
+
+    match &storage.startup_check {
+        Some(StartupCheck::CleanShutdown) => debug!("The node shut down cleanly, the storage checks are skipped"),
+        Some(StartupCheck::Recovered {
+            from_block_height,
+            inconsistencies,
+        }) => {
+            warn!(
+                "The node did not shut down cleanly, checked the storage from block {}",
+                from_block_height
+            );
+            for inconsistency in inconsistencies {
+                warn!("Repaired the storage: {}", inconsistency);
+            }
+        }
+        _ => {}
+    }
 
     let memory_pool = MemoryPool::from_storage(&storage.clone())?;
     let memory_pool_lock = Arc::new(Mutex::new(memory_pool.clone()));
@@ -65,57 +142,199 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
     let sync_handler = SyncHandler::new(bootnode);
     let sync_handler_lock = Arc::new(Mutex::new(sync_handler));
 
+    // The coinbase transactions of the Stratum jobs are created by the node as well.
+    let is_block_producer = config.miner.is_miner || config.miner.stratum.is_some();
+
     info!("Loading Aleo parameters...");
-    let parameters = PublicParameters::<Components>::load(!config.miner.is_miner)?;
+    let parameters = config.public_parameters(!is_block_producer)?;
     info!("Loading complete.");
 
-    // Fetch the valid inner snark ids
-    let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
-        parameters.inner_snark_parameters.1.clone().into();
-    let inner_snark_id = parameters
-        .system_parameters
-        .inner_snark_verification_key_crh
-        .hash(&to_bytes![inner_snark_vk]?)?;
-
-    let authorized_inner_snark_ids = vec![to_bytes![inner_snark_id]?];
+    // The proving keys of the coinbase transactions are loaded in the background, rather than on the first block.
+    if is_block_producer {
+        parameters.load_proving_keys_in_background();
+    }
 
     // Set the initial consensus parameters.
-    let consensus = ConsensusParameters {
-        max_block_size: 1_000_000_000usize,
-        max_nonce: u32::max_value(),
-        target_block_time: 10i64,
-        network: Network::from_network_id(config.aleo.network_id),
-        verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
-        authorized_inner_snark_ids,
-    };
+    let consensus = config.consensus_parameters(&parameters)?;
+
+    // Derive the pre-funded accounts of the development network, and request the blocks funding them.
+    let mut dev_accounts = vec![];
+    let mut block_requests = None;
+    if let Some(dev) = &config.dev {
+        dev_accounts = dev.accounts(&parameters.system_parameters)?;
+
+        if config.log.json {
+            for (index, account) in dev_accounts.iter().enumerate() {
+                info!(
+                    "Development account {}: {}",
+                    index,
+                    account.address.to_network_string(config.network())
+                );
+            }
+        } else {
+            println!("{}", render_dev_accounts(&config, &dev_accounts));
+        }
 
-    let mut context = Arc::new(Context::new(
-        socket_address,
-        config.p2p.mempool_interval,
-        config.p2p.min_peers,
-        config.p2p.max_peers,
-        config.node.is_bootnode,
-        config.p2p.bootnodes.clone(),
-        false,
-    ));
+        let requests = Arc::new(BlockRequests::new());
+        requests.request(dev.accounts.saturating_sub(storage.get_latest_block_height()));
+        block_requests = Some(requests);
+    }
+
+    // Load the identity of the node, generated on its first start.
+    let identity = NodeIdentity::load_or_generate(&config.identity_path())?;
+    info!("Peer ID: {}", identity.peer_id());
+
+    let mut context = Arc::new(
+        Context::new(
+            socket_address,
+            config.p2p.mempool_interval,
+            config.p2p.min_peers,
+            config.p2p.max_peers,
+            config.node.is_bootnode,
+            config.p2p.startup_peers(),
+            false,
+        )
+        .with_identity(identity),
+    );
+    context
+        .handshakes
+        .write()
+        .await
+        .set_allowed_peers(config.p2p.allowed_peer_ids()?);
+
+    // Start the pruning task, if the pruning configuration is enabled.
+    // The transactions of the blocks a fork can decommit are always kept.
+    if let Some(retention) = config.node.prune {
+        check_prune_retention(retention)?;
+
+        let storage = storage.clone();
+
+        task::spawn(async move {
+            loop {
+                match storage.prune_blocks(retention) {
+                    Ok(0) => {}
+                    Ok(num_blocks) => debug!("Pruned the transactions of {} blocks", num_blocks),
+                    Err(error) => error!("Failed to prune blocks: {}", error),
+                }
+
+                delay_for(Duration::from_secs(60)).await;
+            }
+        });
+    }
+
+    // Start the archiving task, if the archiving configuration is enabled.
+    if let Some(retention) = config.node.archive {
+        let storage = storage.clone();
+
+        task::spawn(async move {
+            loop {
+                match storage.archive_blocks(retention) {
+                    Ok(0) => {}
+                    Ok(num_blocks) => debug!("Archived the transactions of {} blocks", num_blocks),
+                    Err(error) => error!("Failed to archive blocks: {}", error),
+                }
+
+                delay_for(Duration::from_secs(60)).await;
+            }
+        });
+    }
+
+    // Start the garbage collection task, if the garbage collection configuration is enabled.
+    if let Some(reorg_window) = config.node.gc {
+        let storage = storage.clone();
+
+        task::spawn(async move {
+            loop {
+                match storage.collect_garbage(reorg_window) {
+                    Ok(collection) if collection.is_empty() => {}
+                    Ok(collection) => debug!(
+                        "Collected {} blocks, {} index entries and {} memory pool transactions, reclaiming {} bytes",
+                        collection.removed_blocks,
+                        collection.removed_index_entries,
+                        collection.removed_memory_pool_transactions,
+                        collection.reclaimed_size
+                    ),
+                    Err(error) => error!("Failed to collect garbage: {}", error),
+                }
+
+                delay_for(Duration::from_secs(600)).await;
+            }
+        });
+    }
+
+    // Start the compaction task, if idle compactions are enabled.
+    // The storage is compacted once each time no block has been added for the idle period.
+    if let Some(idle_secs) = config.storage.compaction_idle_secs {
+        let storage = storage.clone();
+
+        task::spawn(async move {
+            let mut latest_block_height = storage.get_latest_block_height();
+            let mut last_block_time = Instant::now();
+            let mut is_compacted = false;
+
+            loop {
+                delay_for(Duration::from_secs(idle_secs.min(60))).await;
+
+                let block_height = storage.get_latest_block_height();
+                if block_height != latest_block_height {
+                    latest_block_height = block_height;
+                    last_block_time = Instant::now();
+                    is_compacted = false;
+                } else if !is_compacted && last_block_time.elapsed() >= Duration::from_secs(idle_secs) {
+                    let storage = storage.clone();
+                    let start = Instant::now();
+
+                    match task::spawn_blocking(move || storage.compact_storage()).await {
+                        Ok(()) => debug!("Compacted the storage in {:?}", start.elapsed()),
+                        Err(error) => error!("Failed to compact the storage: {}", error),
+                    }
+
+                    is_compacted = true;
+                }
+            }
+        });
+    }
+
+    // The recipients of the block rewards, in place of the miner address.
+    // The rewards of a development network are sent to its accounts in turn by default.
+    let payout = match config.coinbase_payout()? {
+        None if !dev_accounts.is_empty() => Some(CoinbasePayout::Rotation(
+            dev_accounts.iter().map(|account| account.address.clone()).collect(),
+        )),
+        payout => payout,
+    };
 
     // Start the miner task, if the mining configuration is enabled.
     if config.miner.is_miner {
-        match AccountAddress::<Components>::from_str(&config.miner.miner_address) {
+        let miner_address = config.miner_address().or_else(|error| match dev_accounts.first() {
+            Some(account) => Ok(account.address.clone()),
+            None => Err(error),
+        });
+
+        match miner_address {
             Ok(miner_address) => {
                 if let Some(mutable_context) = Arc::get_mut(&mut context) {
                     mutable_context.is_miner = true;
                 }
 
-                MinerInstance::new(
+                let mut miner = MinerInstance::new(
                     miner_address,
+                    payout.clone(),
+                    config.miner.tag.clone(),
+                    config.miner.threads,
+                    config.miner.thread_policy.clone(),
+                    config.miner.prover.clone(),
                     consensus.clone(),
                     parameters.clone(),
                     storage.clone(),
                     memory_pool_lock.clone(),
                     context.clone(),
-                )
-                .spawn();
+                );
+                if let (Some(dev), Some(block_requests)) = (&config.dev, &block_requests) {
+                    miner = miner.with_block_requests(block_requests.clone(), dev.block_interval());
+                }
+
+                miner.spawn();
             }
             Err(_) => info!(
                 "Miner not started. Please specify a valid miner address in your ~/.snarkOS/config.toml file or by using the --miner-address option in the CLI."
@@ -123,30 +342,93 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
         }
     }
 
+    // Start the Stratum server, if the pool mining configuration is enabled.
+    if let Some(stratum) = config.miner.stratum.clone() {
+        match config.miner_address() {
+            Ok(miner_address) => {
+                if let Some(mutable_context) = Arc::get_mut(&mut context) {
+                    mutable_context.is_miner = true;
+                }
+
+                StratumServer::new(
+                    stratum,
+                    miner_address,
+                    payout,
+                    config.miner.tag.clone(),
+                    consensus.clone(),
+                    parameters.clone(),
+                    storage.clone(),
+                    memory_pool_lock.clone(),
+                    context.clone(),
+                )?
+                .spawn();
+            }
+            Err(_) => info!(
+                "Stratum server not started. Please specify a valid miner address in your ~/.snarkOS/config.toml file or by using the --miner-address option in the CLI."
+            ),
+        }
+    }
+
     // Construct the server instance. Note this does not start the server.
     let server = Server::new(
         context,
         consensus.clone(),
         storage.clone(),
-        parameters,
+        parameters.clone(),
         memory_pool_lock.clone(),
         sync_handler_lock.clone(),
         15000, // 15 seconds
     );
 
+    // Start the task broadcasting the transactions sent by the node again, until they are confirmed or abandoned.
+    {
+        let context = server.context.clone();
+        let consensus = consensus.clone();
+        let storage = storage.clone();
+        let memory_pool_lock = memory_pool_lock.clone();
+
+        task::spawn(async move {
+            loop {
+                delay_for(Duration::from_secs(60)).await;
+
+                if let Err(error) = rebroadcast_sent_transactions(
+                    context.clone(),
+                    &consensus,
+                    &parameters,
+                    storage.clone(),
+                    memory_pool_lock.clone(),
+                )
+                .await
+                {
+                    error!("Failed to rebroadcast the sent transactions: {}", error);
+                }
+            }
+        });
+    }
+
+    // The operational settings are reloaded without restarting the node.
+    let reloader = Arc::new(NodeReloader::new(
+        arguments,
+        config.clone(),
+        server.context.clone(),
+        log_filter.clone(),
+    ));
+
     // Start RPC thread, if the RPC configuration is enabled.
     if config.rpc.json_rpc {
         info!("Loading Aleo parameters for RPC...");
-        let proving_parameters = PublicParameters::<Components>::load(!config.miner.is_miner)?;
+        let proving_parameters = config.public_parameters(!config.miner.is_miner)?;
         info!("Loading complete.");
 
         // Open a secondary storage instance to prevent resource sharing and bottle-necking.
         let secondary_storage = Arc::new(MerkleTreeLedger::open_secondary_at_path(path.clone())?);
 
-        start_rpc_server(
+        let rpc_reload_handle = start_rpc_server(
             config.rpc.port,
+            config.rpc.ws_port,
             secondary_storage.clone(),
             path,
+            storage.clone(),
             proving_parameters,
             server.context.clone(),
             consensus.clone(),
@@ -154,12 +436,94 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
             sync_handler_lock.clone(),
             config.rpc.username,
             config.rpc.password,
+            config.rpc.tokens,
+            config.rpc.tls,
+            config.rpc.cors,
+            config.rpc.compression,
+            config.rpc.ipc,
+            config.rpc.graphql,
+            config.rpc.webhooks,
+            config.rpc.versions,
+            config.rpc.limits,
+            log_filter,
+            block_requests,
+            Some(NodeReloader::config_reloader(reloader.clone())),
         )
         .await?;
+
+        reloader.set_rpc(rpc_reload_handle);
+    }
+
+    #[cfg(unix)]
+    task::spawn(snarkos::reload::reload_on_sighup(reloader));
+
+    // The service manager running the node, if any, is notified once the node serves its RPC clients
+    // and is synced with its peers.
+    let notifier = ServiceNotifier::from_env();
+    let readiness = Readiness {
+        threshold: config.node.readiness_threshold,
+        needs_peers: !config.node.is_bootnode && config.p2p.min_peers > 0,
+    };
+
+    // Start the main server thread, until the node is asked to shut down.
+    let context = server.context.clone();
+
+    tokio::select! {
+        result = server.listen().instrument(debug_span!("server")) => result?,
+        _ = supervise(notifier.clone(), readiness, context.clone(), storage.clone()) => {}
+        signal = shutdown_signal() => {
+            info!("Received {}, shutting down...", signal?);
+            if let Err(error) = notifier.stopping() {
+                warn!("Failed to notify the service manager: {}", error);
+            }
+            shutdown(context, storage, memory_pool_lock).await?;
+        }
     }
 
-    // Start the main server thread.
-    server.listen().instrument(debug_span!("server")).await?;
+    Ok(())
+}
+
+/// Waits for a SIGINT or SIGTERM signal, and returns its name.
+#[cfg(unix)]
+async fn shutdown_signal() -> Result<&'static str, NodeError> {
+    let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+
+    tokio::select! {
+        result = signal::ctrl_c() => {
+            result?;
+            Ok("SIGINT")
+        }
+        _ = sigterm.recv() => Ok("SIGTERM"),
+    }
+}
+
+/// Waits for a SIGINT signal, and returns its name.
+#[cfg(not(unix))]
+async fn shutdown_signal() -> Result<&'static str, NodeError> {
+    signal::ctrl_c().await?;
+
+    Ok("SIGINT")
+}
+
+/// Shuts the node down gracefully.
+/// 1. Stops accepting new connections, messages and mining work.
+/// 2. Stores the memory pool and the connected peers.
+/// 3. Disconnects from the peers with the reason of the disconnection.
+/// 4. Waits for the block being committed, if any, records the clean shutdown and flushes the storage to disk.
+async fn shutdown(
+    context: Arc<Context>,
+    storage: Arc<MerkleTreeLedger>,
+    memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
+) -> Result<(), NodeError> {
+    context.shut_down();
+
+    memory_pool_lock.lock().await.store(&storage)?;
+    context.peer_book.read().await.store(&storage)?;
+
+    disconnect_peers(context, "the node is shutting down").await;
+
+    storage.shut_down_storage()?;
+    info!("Shutdown complete");
 
     Ok(())
 }
@@ -169,37 +533,60 @@ fn main() -> Result<(), NodeError> {
 
     let config: Config = ConfigCli::parse(&arguments)?;
 
-    match config.node.verbose {
-        0 => {}
-        verbosity => {
-            match verbosity {
-                1 => std::env::set_var("RUST_LOG", "info"),
-                2 => std::env::set_var("RUST_LOG", "debug"),
-                _ => std::env::set_var("RUST_LOG", "info"),
-            };
-
-            // disable undesirable logs
-            let filter = EnvFilter::from_default_env().add_directive("tokio_reactor=off".parse().unwrap());
-
-            // initialize tracing
-            tracing_subscriber::fmt()
-                .with_env_filter(filter)
-                .with_target(false)
-                .init();
-
-            println!("{}", render_init(&config));
+    // lock the data directory for the lifetime of the node, before its logs, keys or storage are written,
+    // unless it only runs a proof worker or a solo miner, which store no data
+    let data_dir = config.data_dir();
+    let stores_data = config.miner.worker.is_none() && config.miner.failover.is_none();
+    let _data_dir_lock = if stores_data { Some(data_dir.lock()?) } else { None };
+
+    // the remote parameters are downloaded to the data directory, unless another directory is set
+    if env::var_os(PARAMETERS_DIR_VAR).is_none() {
+        env::set_var(PARAMETERS_DIR_VAR, data_dir.parameters());
+    }
+    if !config.parameters.mirrors.is_empty() {
+        env::set_var(PARAMETERS_MIRRORS_VAR, config.parameters.mirrors.join(","));
+    }
+
+    // initialize the structured logs, with a filter the rpc server and a configuration reload can replace
+    let log_filter = init_logging(&config)?;
+
+    // the insecure test parameters replace the SNARK parameters they provide, and never run on the main network
+    if let Some(test_dir) = &config.parameters.test_dir {
+        if config.network() == Network::Mainnet {
+            return Err(NodeError::Message(
+                "the insecure test parameters cannot be used on the main network".into(),
+            ));
         }
+
+        warn!(
+            "Loading the INSECURE test parameters of {:?}. The proofs of this node can be forged, use it for testing only",
+            test_dir
+        );
+        set_parameter_provider(Arc::new(ProviderChain::new(vec![
+            Arc::new(InsecureProvider::new(test_dir.clone())),
+            Arc::new(DefaultProvider),
+        ])));
+    }
+
+    if stores_data {
+        data_dir.migrate_storage()?;
+    }
+
+    // the banner would break the JSON logs
+    if log_filter.is_some() && !config.log.json {
+        println!("{}", render_init(&config));
     }
 
     // create a tracing span dedicated to the entire node
     let node_span = debug_span!("node");
 
-    Builder::new()
+    let mut runtime = Builder::new()
         .threaded_scheduler()
         .enable_all()
         .thread_stack_size(4 * 1024 * 1024)
-        .build()?
-        .block_on(start_server(config).instrument(node_span))?;
+        .build()?;
+
+    runtime.block_on(start_server(config, arguments, log_filter).instrument(node_span))?;
 
     Ok(())
 }
@@ -14,28 +14,60 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger, Miner};
+pub mod benchmark;
+
+pub mod failover;
+
+pub mod stratum;
+
+pub mod worker;
+
+use snarkos_consensus::{
+    BlockRequests,
+    CoinbasePayout,
+    ConsensusParameters,
+    MemoryPool,
+    MerkleTreeLedger,
+    Miner,
+    MiningThreadPolicy,
+    ProcessProver,
+    ProcessProverConfig,
+};
 use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters};
+use snarkos_errors::consensus::ConsensusError;
 use snarkos_network::{external::propagate_block, internal::context::Context};
 use snarkos_objects::{AccountAddress, Block};
 
-use std::sync::Arc;
-use tokio::{sync::Mutex, task};
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::Mutex, task, time::delay_for};
 
 /// Parameters for spawning a miner that runs proof of work to find a block.
 pub struct MinerInstance {
     miner_address: AccountAddress<Components>,
+    payout: Option<CoinbasePayout>,
+    tag: Option<String>,
+    threads: usize,
+    thread_policy: MiningThreadPolicy,
+    prover: Option<ProcessProverConfig>,
     consensus: ConsensusParameters,
     parameters: PublicParameters<Components>,
     storage: Arc<MerkleTreeLedger>,
     memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
     server_context: Arc<Context>,
+    block_requests: Option<Arc<BlockRequests>>,
+    block_interval: Option<Duration>,
 }
 
 impl MinerInstance {
     /// Creates a new MinerInstance for spawning miners.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         miner_address: AccountAddress<Components>,
+        payout: Option<CoinbasePayout>,
+        tag: Option<String>,
+        threads: usize,
+        thread_policy: MiningThreadPolicy,
+        prover: Option<ProcessProverConfig>,
         consensus: ConsensusParameters,
         parameters: PublicParameters<Components>,
         storage: Arc<MerkleTreeLedger>,
@@ -44,29 +76,144 @@ impl MinerInstance {
     ) -> Self {
         Self {
             miner_address,
+            payout,
+            tag,
+            threads,
+            thread_policy,
+            prover,
             consensus,
             parameters,
             storage,
             memory_pool_lock,
             server_context,
+            block_requests: None,
+            block_interval: None,
+        }
+    }
+
+    /// Mines the blocks of a development network on request, and once every block interval if any,
+    /// rather than continuously.
+    pub fn with_block_requests(mut self, block_requests: Arc<BlockRequests>, block_interval: Option<Duration>) -> Self {
+        self.block_requests = Some(block_requests);
+        self.block_interval = block_interval;
+        self
+    }
+
+    /// Waits for the next block request, or for the end of the block interval if there is one.
+    async fn wait_for_next_block(&self, block_requests: &BlockRequests) {
+        match self.block_interval {
+            Some(block_interval) => tokio::select! {
+                _ = block_requests.next() => (),
+                _ = delay_for(block_interval) => (),
+            },
+            None => block_requests.next().await,
         }
     }
 
-    /// Spawns a new miner on a new thread using MinerInstance parameters.
+    /// Spawns a new miner running the proof of work on the configured number of threads.
     /// Once a block is found, A block message is sent to all peers.
     /// Calling this function multiple times will spawn additional listeners on separate threads.
-    /// Miner threads are asynchronous so the only way to stop them is to kill the runtime they were started in. This may be changed in the future.
+    /// The miner stops once the node is shutting down, and waits while the mining is paused.
     pub fn spawn(self) {
         task::spawn(async move {
             let context = self.server_context.clone();
             let local_address = *self.server_context.local_address.read().await;
             info!("Initializing Aleo miner - Your miner address is {}", self.miner_address);
-            let miner = Miner::new(self.miner_address.clone(), self.consensus.clone());
+            let mut miner = match Miner::with_threads(self.miner_address.clone(), self.consensus.clone(), self.threads)
+                .with_stats(context.mining_stats.clone())
+                .with_thread_policy(self.thread_policy.clone())
+            {
+                Ok(miner) => miner,
+                Err(error) => {
+                    error!("Miner not started: {}", error);
+                    return;
+                }
+            };
+            if let Some(payout) = self.payout.clone() {
+                miner = match miner.with_payout(payout) {
+                    Ok(miner) => miner,
+                    Err(error) => {
+                        error!("Miner not started: {}", error);
+                        return;
+                    }
+                };
+            }
+            if let Some(tag) = self.tag.clone() {
+                miner = match miner.with_tag(tag) {
+                    Ok(miner) => miner,
+                    Err(error) => {
+                        error!("Miner not started: {}", error);
+                        return;
+                    }
+                };
+            }
+            if let Some(prover) = &self.prover {
+                miner = match ProcessProver::spawn(prover) {
+                    Ok(prover) => miner.with_prover(prover),
+                    Err(error) => {
+                        error!(
+                            "Miner not started: failed to start the {} prover: {}",
+                            prover.name, error
+                        );
+                        return;
+                    }
+                };
+            }
+            info!(
+                "Mining on {} thread(s) with the {} prover",
+                miner.threads(),
+                miner.prover_name()
+            );
+
+            // Restart the search for a block as soon as its template is stale
+            let event_miner = miner.clone();
+            let mut events = self.memory_pool_lock.lock().await.subscribe();
+            task::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    event_miner.handle_memory_pool_event(event);
+                }
+            });
+
+            // Stop the search for a block once the node is shutting down or the miner is paused
+            let shutdown_miner = miner.clone();
+            let shutdown_context = context.clone();
+            task::spawn(async move {
+                let mut was_paused = false;
+
+                while !shutdown_context.is_shutting_down() {
+                    let is_paused = shutdown_context.is_mining_paused();
+                    if is_paused && !was_paused {
+                        shutdown_miner.cancel();
+                    }
+                    was_paused = is_paused;
+
+                    delay_for(Duration::from_secs(1)).await;
+                }
+                shutdown_miner.cancel();
+            });
 
             let mut mining_failure_count = 0;
             let mining_failure_threshold = 10;
+            let mut should_wait = true;
 
             loop {
+                if context.is_shutting_down() {
+                    info!("Stopping the miner as the node is shutting down");
+                    break;
+                }
+
+                if context.is_mining_paused() {
+                    delay_for(Duration::from_secs(1)).await;
+                    continue;
+                }
+
+                if let Some(block_requests) = &self.block_requests {
+                    if should_wait {
+                        self.wait_for_next_block(block_requests).await;
+                        should_wait = false;
+                    }
+                }
+
                 info!("Starting to mine the next block");
 
                 let (block_serialized, _coinbase_records) = match miner
@@ -74,6 +221,10 @@ impl MinerInstance {
                     .await
                 {
                     Ok(mined_block) => mined_block,
+                    Err(ConsensusError::MiningCancelled) => {
+                        debug!("The block template is stale, restarting the mining of the next block");
+                        continue;
+                    }
                     Err(error) => {
                         warn!(
                             "Miner failed to mine a block {} time(s). (error message: {}).",
@@ -93,9 +244,12 @@ impl MinerInstance {
                     }
                 };
 
+                should_wait = true;
+
                 match Block::<Tx>::deserialize(&block_serialized) {
                     Ok(block) => {
                         info!("Mined a new block!\t{:?}", hex::encode(block.header.get_hash().0));
+                        debug!("Mining at {:.3} proofs per second", miner.hashrate());
 
                         if let Err(err) = propagate_block(context.clone(), block_serialized, local_address).await {
                             error!("Error propagating block to peers: {:?}", err);
@@ -0,0 +1,85 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use snarkos_consensus::{Miner, ProcessProver};
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_errors::node::CliError;
+use snarkos_objects::{AccountAddress, Block};
+use snarkos_utilities::bytes::FromBytes;
+
+use clap::ArgMatches;
+
+/// The default number of proofs of work generated by the benchmark.
+const DEFAULT_BENCHMARK_PROOFS: u64 = 10;
+
+pub struct MinerCLI;
+
+impl MinerCLI {
+    /// Generate proofs of work against the template of the genesis block, without building blocks,
+    /// and report the proofs per second and the time spent in each stage
+    fn benchmark(config: &Config, proofs: u64, threads: usize) -> Result<(), CliError> {
+        let parameters = config.public_parameters(true)?;
+        let consensus = config.consensus_parameters(&parameters)?;
+        let genesis_block = Block::<Tx>::read(config.genesis_block().as_slice())?;
+
+        // No block is built, so no rewards are sent to the miner address
+        let mut miner = Miner::with_threads(AccountAddress::default(), consensus, threads)
+            .with_thread_policy(config.miner.thread_policy.clone())?;
+        if let Some(prover) = &config.miner.prover {
+            miner = miner.with_prover(ProcessProver::spawn(prover)?);
+        }
+
+        println!(
+            "Generating {} proofs of work on {} thread(s) with the {} prover...",
+            proofs,
+            miner.threads(),
+            miner.prover_name()
+        );
+
+        let benchmark = miner.benchmark(&genesis_block.transactions, &genesis_block.header, proofs)?;
+
+        println!(
+            "Generated {} proofs of work in {:.3} seconds",
+            benchmark.proofs,
+            benchmark.elapsed.as_secs_f64()
+        );
+        println!("* Proofs per second: {:.3}", benchmark.proofs_per_second());
+        println!("* Template roots: {:?}", benchmark.template_time);
+        println!(
+            "* Average proving time per thread: {:?}",
+            benchmark.average_proving_time()
+        );
+        println!(
+            "* Average verification time: {:?}",
+            benchmark.average_verification_time()
+        );
+
+        Ok(())
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        match arguments.value_of("action") {
+            Some("benchmark") => {
+                let proofs = clap::value_t!(arguments.value_of("proofs"), u64).unwrap_or(DEFAULT_BENCHMARK_PROOFS);
+                let threads = clap::value_t!(arguments.value_of("threads"), usize).unwrap_or(config.miner.threads);
+
+                Self::benchmark(config, proofs, threads)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! A solo miner with failover between several nodes.
+//!
+//! The miner does not keep a ledger. It checks the block heights of the RPC servers of its nodes,
+//! pulls its block templates, with their coinbase transactions, from the first healthy node in order of
+//! preference, and submits the blocks it finds to that node. A node is failed over once it stops
+//! responding or falls behind the highest node, and the search for a block is restarted as soon as
+//! its template is stale.
+
+use snarkos_consensus::Miner;
+use snarkos_dpc::base_dpc::instantiated::{Components, Tx};
+use snarkos_errors::{consensus::ConsensusError, node::FailoverError};
+use snarkos_objects::{dpc::DPCTransactions, AccountAddress, Block, BlockHeaderHash};
+use snarkos_rpc::{BlockTemplate, SubmitBlockResult};
+use snarkos_utilities::bytes::FromBytes;
+
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::{task, time::delay_for};
+
+/// Defines the nodes a solo miner pulls its block templates from
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailoverConfig {
+    /// The URLs of the RPC servers of the nodes, in order of preference, the first being the primary node
+    pub nodes: Vec<String>,
+    /// The RPC username of the nodes, if any
+    pub username: Option<String>,
+    /// The RPC password of the nodes, if any
+    pub password: Option<String>,
+    /// The number of seconds between the health checks of the nodes
+    pub check_interval: u64,
+    /// The number of seconds after which a node that does not respond is failed over
+    pub timeout: u64,
+    /// The number of blocks a node can fall behind the highest node before it is failed over
+    pub max_height_lag: u32,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            nodes: vec!["http://127.0.0.1:3030".into()],
+            username: None,
+            password: None,
+            check_interval: 5,
+            timeout: 5,
+            max_height_lag: 1,
+        }
+    }
+}
+
+/// Returns the index of the first node within the allowed lag of the highest node,
+/// given the block height of each node, if it responded.
+pub fn select_node(heights: &[Option<u32>], max_height_lag: u32) -> Option<usize> {
+    let highest = heights.iter().filter_map(|height| *height).max()?;
+
+    heights
+        .iter()
+        .position(|height| matches!(height, Some(height) if height.saturating_add(max_height_lag) >= highest))
+}
+
+/// Mines the block templates of the healthiest of several nodes.
+pub struct FailoverMiner {
+    config: FailoverConfig,
+    miner_address: AccountAddress<Components>,
+    miner: Miner,
+    client: Client,
+    /// The node the templates are pulled from, if any
+    selected: Option<usize>,
+    next_id: u64,
+}
+
+impl FailoverMiner {
+    /// Creates a solo miner sending the block rewards to the given address, and running the proof of work with the given miner.
+    pub fn new(
+        config: FailoverConfig,
+        miner_address: AccountAddress<Components>,
+        miner: Miner,
+    ) -> Result<Self, FailoverError> {
+        if config.nodes.is_empty() {
+            return Err(FailoverError::NoHealthyNode);
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout.max(1)))
+            .build()
+            .map_err(reqwest_error)?;
+
+        Ok(Self {
+            config,
+            miner_address,
+            miner,
+            client,
+            selected: None,
+            next_id: 0,
+        })
+    }
+
+    /// Runs the miner, retrying once every node failed.
+    pub async fn run(mut self) {
+        info!(
+            "Mining on {} thread(s) for the nodes {}",
+            self.miner.threads(),
+            self.config.nodes.join(", ")
+        );
+
+        loop {
+            if let Err(error) = self.mine_block().await {
+                match error {
+                    FailoverError::ConsensusError(ConsensusError::MiningCancelled) => {
+                        info!("The block template is stale, restarting the search for a block")
+                    }
+                    error => {
+                        warn!("Failed to mine a block: {}", error);
+                        delay_for(self.check_interval()).await;
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval.max(1))
+    }
+
+    /// Pulls a block template from the healthiest node, runs the proof of work until a block is found
+    /// or the template is stale, and submits the block found.
+    async fn mine_block(&mut self) -> Result<(), FailoverError> {
+        let node = self.select().await?;
+
+        let template_start = Instant::now();
+        let template: BlockTemplate = self
+            .request(node, "getblocktemplate", json!([self.miner_address.to_string()]))
+            .await?;
+        self.miner.stats().record_template(template_start.elapsed());
+
+        let transactions = template_transactions(&template)?;
+        let previous_block_hash = hex::decode(&template.previous_block_hash)?;
+        if previous_block_hash.len() != 32 {
+            return Err(FailoverError::InvalidTemplate(
+                "the previous block hash is not 32 bytes".into(),
+            ));
+        }
+        let previous_block_hash = BlockHeaderHash::new(previous_block_hash);
+
+        info!(
+            "Mining the block {} of the node {}",
+            template.block_height, self.config.nodes[node]
+        );
+
+        let miner = self.miner.clone();
+        let search_transactions = transactions.clone();
+        let (time, difficulty_target) = (template.time, template.difficulty_target);
+        let mut search = task::spawn_blocking(move || {
+            miner.find_block_for_template(&search_transactions, previous_block_hash, time, difficulty_target)
+        });
+
+        // The search is cancelled once a block is found at the height of the template, or once the node fails
+        let header = loop {
+            tokio::select! {
+                result = &mut search => {
+                    break result.map_err(|error| FailoverError::Crate("tokio", error.to_string()))??;
+                }
+                _ = delay_for(self.check_interval()) => {
+                    if self.is_stale(node, template.block_height).await {
+                        self.miner.cancel();
+                    }
+                }
+            }
+        };
+
+        let block = Block { header, transactions };
+        self.submit(node, &block).await
+    }
+
+    /// Returns the block height of each node, if it responded.
+    async fn heights(&mut self) -> Vec<Option<u32>> {
+        let mut heights = Vec::with_capacity(self.config.nodes.len());
+        for node in 0..self.config.nodes.len() {
+            match self.request::<u32>(node, "getblockcount", json!([])).await {
+                Ok(block_count) => heights.push(Some(block_count.saturating_sub(1))),
+                Err(error) => {
+                    debug!(
+                        "The node {} failed the health check: {}",
+                        self.config.nodes[node], error
+                    );
+                    heights.push(None);
+                }
+            }
+        }
+
+        heights
+    }
+
+    /// Selects the healthiest node, and logs the failovers.
+    async fn select(&mut self) -> Result<usize, FailoverError> {
+        let heights = self.heights().await;
+        let node = select_node(&heights, self.config.max_height_lag).ok_or(FailoverError::NoHealthyNode)?;
+
+        match self.selected {
+            Some(selected) if selected == node => {}
+            Some(selected) => warn!(
+                "Failing over from the node {} to the node {}",
+                self.config.nodes[selected], self.config.nodes[node]
+            ),
+            None => info!("Pulling the block templates from the node {}", self.config.nodes[node]),
+        }
+        self.selected = Some(node);
+
+        Ok(node)
+    }
+
+    /// Returns `true` if a block is known at the height of the template, or if the node is no longer the healthiest.
+    async fn is_stale(&mut self, node: usize, block_height: u32) -> bool {
+        let heights = self.heights().await;
+
+        let has_new_block = heights
+            .iter()
+            .any(|height| matches!(height, Some(height) if *height >= block_height));
+        has_new_block || select_node(&heights, self.config.max_height_lag) != Some(node)
+    }
+
+    /// Submits a block to the node, or to the other nodes in order of preference if the node does not respond.
+    async fn submit(&mut self, node: usize, block: &Block<Tx>) -> Result<(), FailoverError> {
+        let block_bytes = hex::encode(block.serialize().map_err(ConsensusError::from)?);
+        let nodes = std::iter::once(node).chain((0..self.config.nodes.len()).filter(|index| *index != node));
+
+        let mut last_error = FailoverError::NoHealthyNode;
+        for node in nodes.collect::<Vec<_>>() {
+            match self
+                .request::<SubmitBlockResult>(node, "submitblock", json!([block_bytes]))
+                .await
+            {
+                Ok(result) if result.accepted => {
+                    info!(
+                        "The node {} accepted the block {}",
+                        self.config.nodes[node], result.block_hash
+                    );
+                    self.miner.stats().record_accepted_block();
+                    return Ok(());
+                }
+                // A rejected block does not extend the canon chain, so it is counted as stale
+                Ok(result) => {
+                    warn!(
+                        "The node {} rejected the block {}: {}",
+                        self.config.nodes[node],
+                        result.block_hash,
+                        result.reject_reason.unwrap_or_default()
+                    );
+                    self.miner.stats().record_stale_block();
+                    return Ok(());
+                }
+                Err(error) => {
+                    warn!(
+                        "Failed to submit the block to the node {}: {}",
+                        self.config.nodes[node], error
+                    );
+                    last_error = error;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Sends a JSON-RPC request to a node, and returns its result.
+    async fn request<T: DeserializeOwned>(
+        &mut self,
+        node: usize,
+        method: &str,
+        params: Value,
+    ) -> Result<T, FailoverError> {
+        self.next_id += 1;
+        let body = json!({ "jsonrpc": "2.0", "id": self.next_id, "method": method, "params": params });
+
+        let mut request = self.client.post(&self.config.nodes[node]).json(&body);
+        if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_ref());
+        }
+
+        let response: Value = request
+            .send()
+            .await
+            .map_err(reqwest_error)?
+            .json()
+            .await
+            .map_err(reqwest_error)?;
+
+        match &response["error"] {
+            Value::Null => {}
+            error => {
+                return Err(FailoverError::Rpc(
+                    error["code"].as_i64().unwrap_or_default(),
+                    error["message"].as_str().unwrap_or("unknown error").to_string(),
+                ));
+            }
+        }
+
+        serde_json::from_value(response["result"].clone())
+            .map_err(|error| FailoverError::Crate("serde_json", format!("{:?}", error)))
+    }
+}
+
+/// Returns the transactions of a template, ending with its coinbase transaction.
+fn template_transactions(template: &BlockTemplate) -> Result<DPCTransactions<Tx>, FailoverError> {
+    let coinbase_transaction = template
+        .coinbase_transaction
+        .as_ref()
+        .ok_or_else(|| FailoverError::InvalidTemplate("the coinbase transaction is missing".into()))?;
+
+    let mut transactions = DPCTransactions::new();
+    for transaction in template
+        .transactions
+        .iter()
+        .chain(std::iter::once(coinbase_transaction))
+    {
+        transactions.push(Tx::read(&hex::decode(transaction)?[..])?);
+    }
+
+    Ok(transactions)
+}
+
+fn reqwest_error(error: reqwest::Error) -> FailoverError {
+    FailoverError::Crate("reqwest", format!("{:?}", error))
+}
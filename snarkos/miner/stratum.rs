@@ -0,0 +1,690 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! A Stratum server for pool mining.
+//!
+//! The workers connected to the server are given work units derived from the block template of the
+//! node, and submit the proofs of work they find as shares. The shares meeting the difficulty target
+//! of the block are assembled into blocks, which are added to the chain and propagated to the peers.
+//!
+//! The messages are newline-delimited JSON-RPC requests and notifications, as in Stratum:
+//! the workers call `mining.subscribe`, `mining.authorize` and `mining.submit`, and are notified
+//! of the share target with `mining.set_target` and of the work units with `mining.notify`.
+//!
+//! Every job is stamped with the parent hash and the ledger digest of its block template, and the
+//! shares of a job whose stamp no longer matches the ledger are rejected as stale before their proof
+//! of work is verified.
+//!
+//! The accepted shares are weighted by their target, and the latest shares covering the PPLNS window
+//! give the contribution of each worker to the blocks of the pool.
+
+use snarkos_consensus::{
+    CoinbasePayout,
+    ConsensusParameters,
+    MemoryPool,
+    MerkleTreeLedger,
+    Miner,
+    PoolShare,
+    PoolShares,
+    ShareValidator,
+};
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters, record::DPCRecord};
+use snarkos_errors::{consensus::ConsensusError, node::StratumError};
+use snarkos_models::dpc::Record;
+use snarkos_network::{external::propagate_block, internal::context::Context};
+use snarkos_objects::{
+    dpc::DPCTransactions,
+    AccountAddress,
+    Block,
+    BlockHeader,
+    BlockHeaderHash,
+    MerkleRootHash,
+    PedersenMerkleRootHash,
+    ProofOfSuccinctWork,
+};
+use snarkos_posw::txids_to_roots;
+use snarkos_utilities::bytes::FromBytes;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex as StdMutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{watch, Mutex},
+    task,
+    time::delay_for,
+};
+
+/// The default share target, under which one in 16 proofs of work is a share.
+pub const DEFAULT_SHARE_TARGET: u64 = u64::MAX / 16;
+
+/// The default total weight of the latest shares giving the contributions of the workers,
+/// which is ten thousand shares of the default share target.
+pub const DEFAULT_PPLNS_WINDOW: u64 = 160_000;
+
+/// The number of latest jobs whose shares are accepted.
+const MAX_JOBS: usize = 8;
+
+/// The interval at which the server checks for a new chain tip.
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The jobs broadcast to the connections, with the flag telling the workers to drop the previous jobs.
+type JobUpdate = Option<(Arc<StratumJob>, bool)>;
+
+/// Defines the Stratum server of a node
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StratumConfig {
+    /// The IP address the server listens on
+    pub ip: String,
+    /// The port the server listens on
+    pub port: u16,
+    /// The password the workers authorize with, if any
+    pub password: Option<String>,
+    /// The target under which the hash of a proof of work is a share
+    pub share_target: u64,
+    /// The number of seconds after which a new job is built with the latest memory pool transactions
+    pub job_interval: u64,
+    /// The total weight of the latest shares giving the contributions of the workers
+    pub pplns_window: u64,
+}
+
+impl Default for StratumConfig {
+    fn default() -> Self {
+        Self {
+            ip: "0.0.0.0".into(),
+            port: 3333,
+            password: None,
+            share_target: DEFAULT_SHARE_TARGET,
+            job_interval: 30,
+            pplns_window: DEFAULT_PPLNS_WINDOW,
+        }
+    }
+}
+
+/// The ledger state a job extends, given by the parent hash and the ledger digest of its block template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobStamp {
+    /// The hash of the block the job extends
+    pub previous_block_hash: BlockHeaderHash,
+    /// The digest of the ledger the transactions of the job are valid against
+    pub ledger_digest: Vec<u8>,
+}
+
+/// A work unit derived from the block template of the node.
+pub struct StratumJob {
+    /// The identifier of the job, sent in hex
+    pub id: u64,
+    /// The ledger state the job extends
+    pub stamp: JobStamp,
+    /// The header of the block the job extends
+    pub previous_header: BlockHeader,
+    /// The transactions of the block, including the coinbase transaction
+    pub transactions: DPCTransactions<Tx>,
+    /// The records of the coinbase transaction
+    pub coinbase_records: Vec<DPCRecord<Components>>,
+    /// The merkle root of the transactions
+    pub merkle_root_hash: MerkleRootHash,
+    /// The pedersen merkle root of the transactions, committed to by the proofs of work
+    pub pedersen_merkle_root_hash: PedersenMerkleRootHash,
+    /// The subroots of the transactions, from which the workers build the proof of work circuit
+    pub subroots: Vec<[u8; 32]>,
+    /// The timestamp of the block
+    pub time: i64,
+    /// The difficulty target of the block
+    pub difficulty_target: u64,
+    /// The time the job was built
+    created: Instant,
+    /// The proofs of work submitted for the job
+    proofs: StdMutex<HashSet<Vec<u8>>>,
+}
+
+impl StratumJob {
+    /// Returns the header of the block solved by the given nonce and proof of work.
+    pub fn header(&self, nonce: u32, proof: ProofOfSuccinctWork) -> BlockHeader {
+        BlockHeader {
+            previous_block_hash: self.stamp.previous_block_hash.clone(),
+            merkle_root_hash: self.merkle_root_hash.clone(),
+            pedersen_merkle_root_hash: self.pedersen_merkle_root_hash.clone(),
+            time: self.time,
+            difficulty_target: self.difficulty_target,
+            nonce,
+            proof,
+        }
+    }
+}
+
+/// The shares submitted over a connection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StratumShares {
+    /// The number of valid shares
+    pub accepted: u64,
+    /// The number of invalid, stale or duplicate shares
+    pub rejected: u64,
+    /// The number of shares submitted for an unknown or superseded job
+    pub stale: u64,
+    /// The number of shares that solved a block
+    pub blocks: u64,
+}
+
+/// The state of a worker connection.
+struct StratumConnection {
+    id: u64,
+    address: SocketAddr,
+    worker: Option<String>,
+    is_subscribed: bool,
+    shares: StratumShares,
+}
+
+#[derive(Deserialize)]
+struct StratumRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// Serves work units to the workers of a mining pool, and assembles the blocks they solve.
+/// The block rewards are sent to the miner address, or to the recipients of the payout if any,
+/// and the memos of the coinbase transactions carry the miner tag if any.
+pub struct StratumServer {
+    config: StratumConfig,
+    miner: Miner,
+    validator: ShareValidator,
+    shares: StdMutex<PoolShares>,
+    parameters: PublicParameters<Components>,
+    storage: Arc<MerkleTreeLedger>,
+    memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
+    server_context: Arc<Context>,
+    jobs: StdMutex<VecDeque<Arc<StratumJob>>>,
+    next_job_id: AtomicU64,
+    next_connection_id: AtomicU64,
+}
+
+impl StratumServer {
+    /// Creates a new Stratum server. The proofs of work are verified, but not run, by the node.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        config: StratumConfig,
+        miner_address: AccountAddress<Components>,
+        payout: Option<CoinbasePayout>,
+        tag: Option<String>,
+        consensus: ConsensusParameters,
+        parameters: PublicParameters<Components>,
+        storage: Arc<MerkleTreeLedger>,
+        memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
+        server_context: Arc<Context>,
+    ) -> Result<Self, ConsensusError> {
+        let mut miner = Miner::verify_only(miner_address, consensus)?.with_stats(server_context.mining_stats.clone());
+        if let Some(payout) = payout {
+            miner = miner.with_payout(payout)?;
+        }
+        if let Some(tag) = tag {
+            miner = miner.with_tag(tag)?;
+        }
+
+        Ok(Self {
+            validator: ShareValidator::new(config.share_target),
+            shares: StdMutex::new(PoolShares::new(config.pplns_window)),
+            config,
+            miner,
+            parameters,
+            storage,
+            memory_pool_lock,
+            server_context,
+            jobs: StdMutex::new(VecDeque::new()),
+            next_job_id: AtomicU64::new(0),
+            next_connection_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Spawns the tasks building the jobs and serving the worker connections.
+    pub fn spawn(self) {
+        let server = Arc::new(self);
+        let (sender, receiver) = watch::channel(None);
+
+        task::spawn(update_jobs(server.clone(), sender));
+
+        task::spawn(async move {
+            let address = format!("{}:{}", server.config.ip, server.config.port);
+            let mut listener = match TcpListener::bind(address.as_str()).await {
+                Ok(listener) => listener,
+                Err(error) => {
+                    error!("Failed to start the Stratum server on {}: {}", address, error);
+                    return;
+                }
+            };
+
+            info!("Listening for Stratum workers on {}", address);
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, address)) => {
+                        task::spawn(handle_connection(server.clone(), stream, address, receiver.clone()));
+                    }
+                    Err(error) => warn!("Failed to accept a Stratum connection: {}", error),
+                }
+            }
+        });
+    }
+
+    /// Returns the target under which the hash of a proof of work for the given job is a share.
+    /// The proofs solving the block are always shares.
+    pub fn share_target(&self, job: &StratumJob) -> u64 {
+        self.validator.share_target(job.difficulty_target)
+    }
+
+    /// Returns the total weight of the latest shares of each worker, within the PPLNS window.
+    pub fn contributions(&self) -> BTreeMap<String, u64> {
+        self.shares
+            .lock()
+            .expect("the Stratum shares lock is poisoned")
+            .contributions()
+    }
+
+    /// Returns the latest job, if any.
+    fn latest_job(&self) -> Option<Arc<StratumJob>> {
+        self.jobs
+            .lock()
+            .expect("the Stratum jobs lock is poisoned")
+            .back()
+            .cloned()
+    }
+
+    /// Returns the job of the given identifier, if its shares are still accepted.
+    fn job(&self, id: &str) -> Option<Arc<StratumJob>> {
+        let id = u64::from_str_radix(id, 16).ok()?;
+        let jobs = self.jobs.lock().expect("the Stratum jobs lock is poisoned");
+        jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    /// Returns the stamp of the latest ledger state.
+    fn current_stamp(&self) -> Result<JobStamp, StratumError> {
+        Ok(JobStamp {
+            previous_block_hash: self.storage.get_block_hash(self.storage.get_latest_block_height())?,
+            ledger_digest: self.storage.current_digest()?,
+        })
+    }
+
+    /// Builds a job from the current block template.
+    async fn new_job(&self) -> Result<StratumJob, ConsensusError> {
+        // The digest is read before the template is built, so a block committed in between
+        // leaves the job stale instead of stamping it with a later ledger state
+        let ledger_digest = self.storage.current_digest()?;
+
+        let candidate_transactions = Miner::fetch_memory_pool_transactions(
+            &self.storage,
+            &self.memory_pool_lock,
+            self.miner.consensus.template_size(),
+            self.miner.consensus.template_weight(),
+        )
+        .await?;
+
+        let (previous_header, transactions, coinbase_records) =
+            self.miner
+                .establish_block(&self.parameters, &self.storage, &candidate_transactions)?;
+
+        let txids = transactions.to_transaction_ids()?;
+        let (merkle_root_hash, pedersen_merkle_root_hash, subroots) = txids_to_roots(&txids);
+
+        let time = Utc::now().timestamp();
+        let difficulty_target = self.miner.consensus.get_block_difficulty(&previous_header, time);
+
+        Ok(StratumJob {
+            id: self.next_job_id.fetch_add(1, Ordering::SeqCst),
+            stamp: JobStamp {
+                previous_block_hash: previous_header.get_hash(),
+                ledger_digest,
+            },
+            previous_header,
+            transactions,
+            coinbase_records,
+            merkle_root_hash,
+            pedersen_merkle_root_hash,
+            subroots,
+            time,
+            difficulty_target,
+            created: Instant::now(),
+            proofs: StdMutex::new(HashSet::new()),
+        })
+    }
+
+    /// Returns the notifications of a job to the subscribed workers.
+    fn job_notifications(&self, job: &StratumJob, clean_jobs: bool) -> Vec<String> {
+        let subroots: Vec<String> = job.subroots.iter().map(hex::encode).collect();
+
+        vec![
+            notification("mining.set_target", json!([self.share_target(job)])),
+            notification(
+                "mining.notify",
+                json!([
+                    format!("{:x}", job.id),
+                    hex::encode(job.stamp.previous_block_hash.0),
+                    hex::encode(job.pedersen_merkle_root_hash.0),
+                    subroots,
+                    job.time,
+                    job.difficulty_target,
+                    self.miner.consensus.max_nonce,
+                    clean_jobs,
+                ]),
+            ),
+        ]
+    }
+
+    /// Handles a line received from a worker, and returns the messages to send back.
+    async fn handle_line(&self, connection: &mut StratumConnection, line: &str) -> Vec<String> {
+        let request: StratumRequest = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(error) => {
+                let error = StratumError::InvalidParams(error.to_string());
+                return vec![response(Value::Null, Err(error))];
+            }
+        };
+
+        let result = match request.method.as_str() {
+            "mining.subscribe" => {
+                connection.is_subscribed = true;
+                Ok(json!(format!("{:x}", connection.id)))
+            }
+            "mining.authorize" => self.authorize(connection, &request.params),
+            "mining.submit" => {
+                let result = self.submit(connection, &request.params).await;
+                match &result {
+                    Ok(_) => connection.shares.accepted += 1,
+                    Err(StratumError::StaleJob(_)) => {
+                        connection.shares.rejected += 1;
+                        connection.shares.stale += 1;
+                        self.miner.stats().record_stale_share();
+                    }
+                    Err(_) => connection.shares.rejected += 1,
+                }
+                result
+            }
+            method => Err(StratumError::UnknownMethod(method.to_string())),
+        };
+
+        let is_subscription = request.method == "mining.subscribe" && result.is_ok();
+        let mut messages = vec![response(request.id, result)];
+
+        // The new subscribers are sent the latest job at once
+        if is_subscription {
+            if let Some(job) = self.latest_job() {
+                messages.extend(self.job_notifications(&job, true));
+            }
+        }
+
+        messages
+    }
+
+    fn authorize(&self, connection: &mut StratumConnection, params: &[Value]) -> Result<Value, StratumError> {
+        let worker = string_param(params, 0, "worker")?;
+
+        if let Some(password) = &self.config.password {
+            if params.get(1).and_then(Value::as_str) != Some(password.as_str()) {
+                return Err(StratumError::Unauthorized);
+            }
+        }
+
+        connection.worker = Some(worker.to_string());
+
+        Ok(Value::Bool(true))
+    }
+
+    /// Verifies a share, and adds the block it solves to the chain, if any.
+    /// The shares of the unknown jobs, or of the jobs whose stamp no longer matches the ledger, are stale.
+    async fn submit(&self, connection: &mut StratumConnection, params: &[Value]) -> Result<Value, StratumError> {
+        if !connection.is_subscribed {
+            return Err(StratumError::NotSubscribed);
+        }
+        if connection.worker.is_none() {
+            return Err(StratumError::Unauthorized);
+        }
+
+        let job_id = string_param(params, 1, "job_id")?;
+        let nonce = params
+            .get(2)
+            .and_then(Value::as_u64)
+            .filter(|nonce| *nonce < u64::from(self.miner.consensus.max_nonce))
+            .ok_or_else(|| StratumError::InvalidParams("the nonce is missing or above the maximum nonce".into()))?;
+        let proof = hex::decode(string_param(params, 3, "proof")?)?;
+
+        let job = self
+            .job(job_id)
+            .ok_or_else(|| StratumError::StaleJob(job_id.to_string()))?;
+
+        // The shares of a superseded job cannot solve a block, so their proofs are not verified
+        if job.stamp != self.current_stamp()? {
+            return Err(StratumError::StaleJob(job_id.to_string()));
+        }
+
+        if proof.len() != ProofOfSuccinctWork::size() {
+            return Err(StratumError::InvalidParams(format!(
+                "the proof is {} bytes instead of {}",
+                proof.len(),
+                ProofOfSuccinctWork::size()
+            )));
+        }
+
+        let header = job.header(nonce as u32, ProofOfSuccinctWork::read(&proof[..])?);
+
+        let share = self
+            .validator
+            .validate(&self.miner.consensus, &header)
+            .map_err(|error| match error {
+                ConsensusError::LowDifficultyShare(share_target, hash) => {
+                    StratumError::LowDifficultyShare(share_target, hash)
+                }
+                error => error.into(),
+            })?;
+
+        if !job
+            .proofs
+            .lock()
+            .expect("the Stratum job lock is poisoned")
+            .insert(proof)
+        {
+            return Err(StratumError::DuplicateShare);
+        }
+
+        if let Some(worker) = &connection.worker {
+            self.shares
+                .lock()
+                .expect("the Stratum shares lock is poisoned")
+                .record(PoolShare {
+                    worker: worker.clone(),
+                    weight: share.weight,
+                });
+        }
+
+        if share.solves_block {
+            self.add_block(&job, header).await?;
+            connection.shares.blocks += 1;
+        }
+
+        Ok(Value::Bool(true))
+    }
+
+    /// Adds the block solved for a job to the chain, and propagates it to the peers.
+    async fn add_block(&self, job: &StratumJob, header: BlockHeader) -> Result<(), StratumError> {
+        let block = Block {
+            header,
+            transactions: job.transactions.clone(),
+        };
+
+        {
+            let mut memory_pool = self.memory_pool_lock.lock().await;
+            self.miner
+                .consensus
+                .receive_block(&self.parameters, &self.storage, &mut memory_pool, &block)?;
+        }
+
+        // The shares of the previous jobs no longer extend the chain
+        self.jobs.lock().expect("the Stratum jobs lock is poisoned").clear();
+
+        // Store the non-dummy coinbase records.
+        let records_to_store: Vec<_> = job
+            .coinbase_records
+            .iter()
+            .filter(|record| !record.is_dummy())
+            .cloned()
+            .collect();
+        self.storage.store_records(&records_to_store)?;
+
+        info!("Pool mined a new block!\t{:?}", hex::encode(block.header.get_hash().0));
+        debug!(
+            "The contributions of the workers to the block are {:?}",
+            self.contributions()
+        );
+
+        let block_serialized = block.serialize().map_err(ConsensusError::from)?;
+        let local_address = *self.server_context.local_address.read().await;
+        propagate_block(self.server_context.clone(), block_serialized, local_address).await?;
+
+        Ok(())
+    }
+}
+
+/// Builds a new job whenever the chain tip or the ledger digest changes, or once the latest job is older than the job interval.
+async fn update_jobs(server: Arc<StratumServer>, sender: watch::Sender<JobUpdate>) {
+    let job_interval = Duration::from_secs(server.config.job_interval);
+
+    loop {
+        let current_stamp = server.current_stamp().ok();
+
+        let (is_new_tip, is_expired) = match server.latest_job() {
+            Some(job) => (
+                Some(&job.stamp) != current_stamp.as_ref(),
+                job.created.elapsed() >= job_interval,
+            ),
+            None => (true, true),
+        };
+
+        if is_new_tip || is_expired {
+            match server.new_job().await {
+                Ok(job) => {
+                    let job = Arc::new(job);
+
+                    {
+                        let mut jobs = server.jobs.lock().expect("the Stratum jobs lock is poisoned");
+                        if is_new_tip {
+                            jobs.clear();
+                        }
+                        if jobs.len() == MAX_JOBS {
+                            jobs.pop_front();
+                        }
+                        jobs.push_back(job.clone());
+                    }
+
+                    if sender.broadcast(Some((job, is_new_tip))).is_err() {
+                        return;
+                    }
+                }
+                Err(error) => warn!("Failed to build a Stratum job: {}", error),
+            }
+        }
+
+        delay_for(JOB_POLL_INTERVAL).await;
+    }
+}
+
+/// Serves the requests of a worker, and notifies it of the new jobs once it is subscribed.
+async fn handle_connection(
+    server: Arc<StratumServer>,
+    stream: TcpStream,
+    address: SocketAddr,
+    mut jobs: watch::Receiver<JobUpdate>,
+) {
+    let (reader, mut writer) = io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    let mut connection = StratumConnection {
+        id: server.next_connection_id.fetch_add(1, Ordering::SeqCst),
+        address,
+        worker: None,
+        is_subscribed: false,
+        shares: StratumShares::default(),
+    };
+
+    debug!("Stratum worker connected from {}", connection.address);
+
+    loop {
+        let messages = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => server.handle_line(&mut connection, &line).await,
+                _ => break,
+            },
+            update = jobs.recv() => match update {
+                Some(Some((job, clean_jobs))) if connection.is_subscribed => server.job_notifications(&job, clean_jobs),
+                Some(_) => vec![],
+                None => break,
+            },
+        };
+
+        let mut is_closed = false;
+        for message in messages {
+            if writer.write_all(message.as_bytes()).await.is_err() {
+                is_closed = true;
+                break;
+            }
+        }
+
+        if is_closed {
+            break;
+        }
+    }
+
+    info!(
+        "Stratum worker {} from {} disconnected with {} accepted shares, {} rejected shares ({} stale) and {} blocks",
+        connection.worker.as_deref().unwrap_or("(unauthorized)"),
+        connection.address,
+        connection.shares.accepted,
+        connection.shares.rejected,
+        connection.shares.stale,
+        connection.shares.blocks
+    );
+}
+
+pub(crate) fn string_param<'a>(params: &'a [Value], index: usize, name: &str) -> Result<&'a str, StratumError> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| StratumError::InvalidParams(format!("the {} is missing", name)))
+}
+
+fn response(id: Value, result: Result<Value, StratumError>) -> String {
+    let message = match result {
+        Ok(result) => json!({ "id": id, "result": result, "error": null }),
+        Err(error) => json!({ "id": id, "result": null, "error": [error.code(), error.to_string(), null] }),
+    };
+
+    format!("{}\n", message)
+}
+
+fn notification(method: &str, params: Value) -> String {
+    format!("{}\n", json!({ "id": null, "method": method, "params": params }))
+}
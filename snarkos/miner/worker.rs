@@ -0,0 +1,369 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! A proof worker for pool mining.
+//!
+//! The worker connects to the Stratum server of a node, which coordinates the workers, and runs the
+//! proof of work of the work units it is notified of. The proofs of work under the share target are
+//! submitted back to the coordinator, which assembles the blocks they solve. The worker does not
+//! keep a ledger, so any number of workers can drive a single full node.
+
+use crate::miner::stratum::{string_param, DEFAULT_SHARE_TARGET};
+use snarkos_consensus::{miner::nonce_ranges, CheckedProver, CpuProver, MiningThreadPolicy, PoswProver};
+use snarkos_errors::{consensus::ConsensusError, node::StratumError};
+use snarkos_posw::PoswMarlin;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tokio::{
+    io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc::{self, UnboundedSender},
+    time::delay_for,
+};
+
+/// The interval after which the worker reconnects to the coordinator once the connection is lost.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The id of the `mining.subscribe` request.
+const SUBSCRIBE_ID: u64 = 1;
+
+/// The id of the `mining.authorize` request.
+const AUTHORIZE_ID: u64 = 2;
+
+/// The code of the errors rejecting the shares of an unknown or superseded job.
+const STALE_JOB_CODE: i64 = 21;
+
+/// Defines the coordinator of a proof worker
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerConfig {
+    /// The address of the Stratum server of the coordinator
+    pub coordinator: String,
+    /// The name the worker authorizes with
+    pub name: String,
+    /// The password of the workers of the coordinator, if any
+    pub password: Option<String>,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            coordinator: "127.0.0.1:3333".into(),
+            name: "worker".into(),
+            password: None,
+        }
+    }
+}
+
+/// A work unit notified by the coordinator.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkUnit {
+    /// The identifier of the job of the coordinator
+    pub job_id: String,
+    /// The subroots of the transactions of the block, from which the proof of work circuit is built
+    pub subroots: Vec<[u8; 32]>,
+    /// The target under which the hash of a proof of work is a share
+    pub share_target: u64,
+    /// The nonces of the proofs of work are below the maximum nonce
+    pub max_nonce: u32,
+}
+
+impl WorkUnit {
+    /// Returns the work unit of the params of a `mining.notify` notification, under the given share target.
+    pub fn from_notify(params: &[Value], share_target: u64) -> Result<Self, StratumError> {
+        let job_id = string_param(params, 0, "job_id")?.to_string();
+
+        let subroots = params
+            .get(3)
+            .and_then(Value::as_array)
+            .ok_or_else(|| StratumError::InvalidParams("the subroots are missing".into()))?
+            .iter()
+            .map(|subroot| {
+                let bytes = hex::decode(subroot.as_str().unwrap_or_default())?;
+                if bytes.len() != 32 {
+                    return Err(StratumError::InvalidParams(format!(
+                        "a subroot is {} bytes instead of 32",
+                        bytes.len()
+                    )));
+                }
+
+                let mut subroot = [0u8; 32];
+                subroot.copy_from_slice(&bytes);
+                Ok(subroot)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let max_nonce = params
+            .get(6)
+            .and_then(Value::as_u64)
+            .filter(|max_nonce| *max_nonce > 0 && *max_nonce <= u64::from(u32::MAX))
+            .ok_or_else(|| StratumError::InvalidParams("the maximum nonce is missing or invalid".into()))?;
+
+        Ok(Self {
+            job_id,
+            subroots,
+            share_target,
+            max_nonce: max_nonce as u32,
+        })
+    }
+}
+
+/// A proof of work under the share target of a work unit.
+struct Share {
+    job_id: String,
+    nonce: u32,
+    proof: Vec<u8>,
+}
+
+/// The shares submitted by a worker to its coordinator.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct SubmittedShares {
+    accepted: u64,
+    rejected: u64,
+    stale: u64,
+}
+
+/// Runs the proof of work of the work units of a coordinator, and submits the shares it finds.
+pub struct ProofWorker {
+    config: WorkerConfig,
+    threads: usize,
+    thread_policy: MiningThreadPolicy,
+    prover: Arc<dyn PoswProver>,
+    /// The number of work units started, which stops the mining of the previous work unit when incremented.
+    generation: Arc<AtomicU64>,
+}
+
+impl ProofWorker {
+    /// Creates a new proof worker running the proof of work on the given number of threads.
+    pub fn new(config: WorkerConfig, threads: usize) -> Result<Self, ConsensusError> {
+        Ok(Self {
+            config,
+            threads: threads.max(1),
+            thread_policy: MiningThreadPolicy::default(),
+            prover: Arc::new(CpuProver::new(PoswMarlin::load()?)),
+            generation: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Places the worker threads on the cores and at the priority of the given policy.
+    pub fn with_thread_policy(mut self, thread_policy: MiningThreadPolicy) -> Result<Self, ConsensusError> {
+        thread_policy.validate()?;
+        self.thread_policy = thread_policy;
+        Ok(self)
+    }
+
+    /// Runs the proof of work with the given prover in place of the CPU prover.
+    /// The solutions of the prover are verified before they are submitted to the coordinator.
+    pub fn with_prover<P: PoswProver + 'static>(mut self, prover: P) -> Result<Self, ConsensusError> {
+        self.prover = Arc::new(CheckedProver::new(prover, PoswMarlin::verify_only()?));
+        Ok(self)
+    }
+
+    /// Runs the worker, reconnecting to the coordinator whenever the connection is lost.
+    pub async fn run(self) {
+        info!(
+            "Running a proof worker for the coordinator {} on {} thread(s) with the {} prover",
+            self.config.coordinator,
+            self.threads,
+            self.prover.name()
+        );
+
+        loop {
+            match self.run_session().await {
+                Ok(()) => info!("The coordinator {} closed the connection", self.config.coordinator),
+                Err(error) => warn!(
+                    "Lost the connection to the coordinator {}: {}",
+                    self.config.coordinator, error
+                ),
+            }
+
+            // The work units of a lost connection are no longer mined
+            self.generation.fetch_add(1, Ordering::SeqCst);
+
+            delay_for(RECONNECT_INTERVAL).await;
+        }
+    }
+
+    /// Subscribes to the work units of the coordinator, and submits the shares found until the connection is closed.
+    async fn run_session(&self) -> Result<(), StratumError> {
+        let stream = TcpStream::connect(self.config.coordinator.as_str()).await?;
+        let (reader, mut writer) = io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+
+        info!("Connected to the coordinator {}", self.config.coordinator);
+
+        let (share_sender, mut shares) = mpsc::unbounded_channel();
+
+        writer
+            .write_all(request(SUBSCRIBE_ID, "mining.subscribe", json!([])).as_bytes())
+            .await?;
+        writer
+            .write_all(
+                request(
+                    AUTHORIZE_ID,
+                    "mining.authorize",
+                    json!([self.config.name, self.config.password]),
+                )
+                .as_bytes(),
+            )
+            .await?;
+
+        let mut share_target = DEFAULT_SHARE_TARGET;
+        let mut next_id = AUTHORIZE_ID + 1;
+        let mut submitted = SubmittedShares::default();
+
+        let result = loop {
+            tokio::select! {
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => {
+                        if let Err(error) = self.handle_line(&line, &mut share_target, &share_sender, &mut submitted) {
+                            break Err(error);
+                        }
+                    }
+                    Ok(None) => break Ok(()),
+                    Err(error) => break Err(error.into()),
+                },
+                Some(share) = shares.recv() => {
+                    let params = json!([self.config.name, share.job_id, share.nonce, hex::encode(share.proof)]);
+                    let message = request(next_id, "mining.submit", params);
+                    next_id += 1;
+
+                    if let Err(error) = writer.write_all(message.as_bytes()).await {
+                        break Err(error.into());
+                    }
+                }
+            }
+        };
+
+        info!(
+            "Submitted {} accepted shares and {} rejected shares ({} stale) to the coordinator {}",
+            submitted.accepted, submitted.rejected, submitted.stale, self.config.coordinator
+        );
+
+        result
+    }
+
+    /// Handles a message of the coordinator. The refused subscriptions and authorizations end the session.
+    fn handle_line(
+        &self,
+        line: &str,
+        share_target: &mut u64,
+        share_sender: &UnboundedSender<Share>,
+        submitted: &mut SubmittedShares,
+    ) -> Result<(), StratumError> {
+        let message: Value =
+            serde_json::from_str(line).map_err(|error| StratumError::InvalidParams(error.to_string()))?;
+        let params = message["params"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+        match message["method"].as_str() {
+            Some("mining.set_target") => match params.get(0).and_then(Value::as_u64) {
+                Some(target) => *share_target = target,
+                None => warn!("Ignoring a share target without a target"),
+            },
+            Some("mining.notify") => match WorkUnit::from_notify(params, *share_target) {
+                Ok(work_unit) => self.start(work_unit, share_sender.clone()),
+                Err(error) => warn!("Ignoring an invalid work unit: {}", error),
+            },
+            Some(method) => debug!("Ignoring the unknown notification {}", method),
+            None => {
+                let (error, code) = match &message["error"] {
+                    Value::Null => (None, None),
+                    error => (
+                        Some(error[1].as_str().unwrap_or("unknown error").to_string()),
+                        error[0].as_i64(),
+                    ),
+                };
+
+                match (message["id"].as_u64(), error) {
+                    (Some(SUBSCRIBE_ID), Some(error)) | (Some(AUTHORIZE_ID), Some(error)) => {
+                        return Err(StratumError::Message(error));
+                    }
+                    (Some(SUBSCRIBE_ID), None) | (Some(AUTHORIZE_ID), None) => {}
+                    (_, None) => submitted.accepted += 1,
+                    (_, Some(error)) => {
+                        debug!("The coordinator rejected a share: {}", error);
+                        submitted.rejected += 1;
+                        if code == Some(STALE_JOB_CODE) {
+                            submitted.stale += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts mining a work unit on the worker threads, each trying the nonces of its own range,
+    /// which stops the mining of the previous work unit. The shares found are sent to the given channel.
+    fn start(&self, work_unit: WorkUnit, share_sender: UnboundedSender<Share>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        debug!("Mining the job {} of the coordinator", work_unit.job_id);
+
+        for (index, nonces) in nonce_ranges(work_unit.max_nonce, self.threads).into_iter().enumerate() {
+            let prover = self.prover.clone();
+            let thread_policy = self.thread_policy.clone();
+            let current_generation = self.generation.clone();
+            let work_unit = work_unit.clone();
+            let share_sender = share_sender.clone();
+
+            thread::spawn(move || {
+                thread_policy.apply(index);
+
+                // The work unit is mined until a later one is started, as every share is submitted
+                loop {
+                    let result =
+                        prover.mine_nonces(&work_unit.subroots, work_unit.share_target, nonces.clone(), &mut || {
+                            current_generation.load(Ordering::SeqCst) == generation
+                        });
+
+                    match result {
+                        Ok(Some((nonce, proof))) => {
+                            let share = Share {
+                                job_id: work_unit.job_id.clone(),
+                                nonce,
+                                proof,
+                            };
+
+                            if share_sender.send(share).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(error) => {
+                            error!("Failed to run the proof of work: {}", error);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn request(id: u64, method: &str, params: Value) -> String {
+    format!("{}\n", json!({ "id": id, "method": method, "params": params }))
+}
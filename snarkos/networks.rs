@@ -0,0 +1,133 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::{MAINNET_BOOTNODES, TESTNET_BOOTNODES};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters};
+use snarkos_models::genesis::Genesis;
+use snarkos_objects::{BlockHeader, Network};
+use snarkos_parameters::GenesisBlock;
+
+use std::io::Result as IoResult;
+
+/// The parameters of a network the node runs on, selected at runtime with `--network`.
+/// The address prefix of a network is given by its `Network`.
+#[derive(Clone, Copy)]
+pub struct NetworkParameters {
+    /// The network
+    pub network: Network,
+    /// The default port of the node
+    pub port: u16,
+    /// The default port of the json rpc server
+    pub rpc_port: u16,
+    /// The bootnodes the node connects to first
+    pub bootnodes: &'static [&'static str],
+    /// The maximum block size in bytes
+    pub max_block_size: usize,
+    /// The amount of time it should take to find a block
+    pub target_block_time: i64,
+    /// Returns the serialized genesis block of the network
+    pub genesis_block: fn() -> Vec<u8>,
+    /// Returns the public parameters of the network, with the proving parameters unless verifying only
+    pub public_parameters: fn(bool) -> IoResult<PublicParameters<Components>>,
+}
+
+pub const MAINNET: NetworkParameters = NetworkParameters {
+    network: Network::Mainnet,
+    port: 4130,
+    rpc_port: 3030,
+    bootnodes: MAINNET_BOOTNODES,
+    max_block_size: 2_000_000,
+    target_block_time: 20,
+    genesis_block: mainnet_genesis_block,
+    public_parameters: PublicParameters::<Components>::load,
+};
+
+pub const TESTNET1: NetworkParameters = NetworkParameters {
+    network: Network::Testnet1,
+    port: 4131,
+    rpc_port: 3030,
+    bootnodes: TESTNET_BOOTNODES,
+    max_block_size: 1_000_000_000,
+    target_block_time: 10,
+    genesis_block: GenesisBlock::load_bytes,
+    public_parameters: PublicParameters::<Components>::load,
+};
+
+/// A local development network, without bootnodes, whose ports do not collide with the main and test networks.
+pub const DEVNET: NetworkParameters = NetworkParameters {
+    network: Network::Devnet,
+    port: 4132,
+    rpc_port: 3032,
+    bootnodes: &[],
+    max_block_size: 1_000_000_000,
+    target_block_time: 2,
+    genesis_block: devnet_genesis_block,
+    public_parameters: PublicParameters::<Components>::load,
+};
+
+/// The registry of the networks the node can run on. Each network starts from its own genesis block, and the
+/// networks share the public parameters of the setup compiled into the node.
+pub const NETWORKS: &[NetworkParameters] = &[MAINNET, TESTNET1, DEVNET];
+
+/// Returns the genesis block of the test network with the id of the given network committed in the nonce of its
+/// header, so the network has its own genesis block hash. The header of a genesis block is not checked against
+/// the proof of work, and the transactions of the genesis block are kept.
+fn derived_genesis_block(network: Network) -> Vec<u8> {
+    let mut genesis_block = GenesisBlock::load_bytes();
+
+    let mut header_bytes = [0u8; BlockHeader::size()];
+    header_bytes.copy_from_slice(&genesis_block[..BlockHeader::size()]);
+
+    // The nonce of the test network genesis block is zero, the id of the main network
+    let mut header = BlockHeader::deserialize(&header_bytes);
+    header.nonce = u32::from(network.id()) + 1;
+    genesis_block[..BlockHeader::size()].copy_from_slice(&header.serialize());
+
+    genesis_block
+}
+
+/// Returns the genesis block of the main network.
+fn mainnet_genesis_block() -> Vec<u8> {
+    derived_genesis_block(Network::Mainnet)
+}
+
+/// Returns the genesis block of the development network.
+fn devnet_genesis_block() -> Vec<u8> {
+    derived_genesis_block(Network::Devnet)
+}
+
+impl NetworkParameters {
+    /// Returns the parameters of the given network. A custom network id runs with the parameters
+    /// and the genesis block of the test network, on its own port.
+    pub fn of(network: Network) -> Self {
+        match NETWORKS.iter().find(|parameters| parameters.network == network) {
+            Some(parameters) => *parameters,
+            None => Self {
+                network,
+                port: 4130 + u16::from(network.id()),
+                ..TESTNET1
+            },
+        }
+    }
+
+    /// Returns the default name of the storage of the network.
+    pub fn db(&self) -> String {
+        match self.network {
+            Network::Custom(id) => format!("snarkos_testnet{}", id),
+            network => format!("snarkos_{}", network.name()),
+        }
+    }
+}
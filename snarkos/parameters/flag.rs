@@ -23,4 +23,28 @@ pub const IS_BOOTNODE: &str =
 
 pub const IS_MINER: &str = "[is-miner] --is-miner 'Start mining blocks from this node'";
 
+pub const MINER_LOW_PRIORITY: &str =
+    "[miner-low-priority] --miner-low-priority 'Run the mining threads at the lowest priority of the operating system'";
+
+pub const LOG_JSON: &str = "[log-json] --log-json 'Write the logs as JSON objects, one per line'";
+
+pub const LOG_FILE: &str =
+    "[log-file] --log-file 'Write the logs to the logs directory of the data directory, in place of the standard output'";
+
+pub const DEV: &str =
+    "[dev] --dev 'Run a single node development network, mining blocks at a trivial difficulty for pre-funded accounts'";
+
+/// The flags of the node, given directly or to the start subcommand.
+pub const NODE: &[&str] = &[
+    NO_JSONRPC,
+    IS_BOOTNODE,
+    IS_MINER,
+    MINER_LOW_PRIORITY,
+    LOG_JSON,
+    LOG_FILE,
+    DEV,
+];
+
 pub const LIST: &str = "[list] -l --list 'List all available releases of snarkOS'";
+
+pub const REPAIR: &str = "[repair] --repair 'Repair the inconsistencies found in the storage'";
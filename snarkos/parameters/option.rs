@@ -21,6 +21,20 @@ use crate::parameters::types::*;
 
 // Global
 
+pub const CONFIG: OptionType = (
+    "[config] -c --config=[path] 'Specify the configuration file of the node, in place of the snarkos.toml file of the working directory or the config.toml file of ~/.snarkOS/'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const DATA_DIR: OptionType = (
+    "[data-dir] --data-dir=[path] 'Specify the data directory of the node, holding its storage, keys, parameters and logs (default = ~/.snarkOS/)'",
+    &[],
+    &[],
+    &[],
+);
+
 pub const PATH: OptionType = (
     "[path] -d --path=[path] 'Specify the node's storage path'",
     &[],
@@ -28,6 +42,34 @@ pub const PATH: OptionType = (
     &[],
 );
 
+pub const PRUNE: OptionType = (
+    "[prune] --prune=[num-blocks] 'Specify the number of recent blocks to keep transactions for, discarding older ones, at least 1024'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const ARCHIVE: OptionType = (
+    "[archive] --archive=[num-blocks] 'Specify the number of recent blocks to keep transactions for in the database, moving older ones to archive files'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const GC: OptionType = (
+    "[gc] --gc=[num-blocks] 'Specify the reorg window, removing abandoned forks, stale memory pool transactions and obsolete index entries older than the window'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const READINESS_THRESHOLD: OptionType = (
+    "[readiness-threshold] --readiness-threshold=[num-blocks] 'Specify the number of blocks the node can be behind its peers and be reported ready to its service manager (default = 10)'",
+    &[],
+    &[],
+    &[],
+);
+
 pub const IP: OptionType = ("[ip] -i --ip=[ip] 'Specify the ip of your node'", &[], &[], &[]);
 
 pub const PORT: OptionType = (
@@ -51,6 +93,55 @@ pub const MINER_ADDRESS: OptionType = (
     &[],
 );
 
+pub const MINER_THREADS: OptionType = (
+    "[miner-threads] --miner-threads=[miner-threads] 'Specify the number of threads the miner runs the proof of work on'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MINER_CORES: OptionType = (
+    "[miner-cores] --miner-cores=[cores] 'Specify the cores the mining threads are pinned to, separated by commas'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MINER_MAX_BLOCK_SIZE: OptionType = (
+    "[miner-max-block-size] --miner-max-block-size=[bytes] 'Specify the maximum size in bytes of the block templates, up to the maximum block size of the network'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MINER_MAX_BLOCK_WEIGHT: OptionType = (
+    "[miner-max-block-weight] --miner-max-block-weight=[weight] 'Specify the maximum weight of the block templates, the number of serial numbers, commitments and memos of their transactions'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MINER_TAG: OptionType = (
+    "[miner-tag] --miner-tag=[tag] 'Specify the tag, such as a pool name or a node id, embedded in the mined blocks'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const WORKER: OptionType = (
+    "[worker] --worker=[coordinator] 'Run a proof worker for the Stratum server at the given address instead of a node'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MINER_NODES: OptionType = (
+    "[miner-nodes] --miner-nodes=[urls] 'Mine the block templates of the given node RPC servers, in order of preference, instead of running a node'",
+    &[],
+    &[],
+    &[],
+);
+
 pub const MEMPOOL_INTERVAL: OptionType = (
     "[mempool-interval] --mempool-interval=[mempool-interval] 'Specify the frequency in seconds the node should fetch a sync node's mempool'",
     &[],
@@ -73,7 +164,7 @@ pub const MAX_PEERS: OptionType = (
 );
 
 pub const NETWORK: OptionType = (
-    "[network] --network=[network-id] 'Specify the network id (default = 1) of the node'",
+    "[network] --network=[network] 'Specify the network of the node, mainnet, testnet1, devnet or a network id (default = testnet1)'",
     &[],
     &[],
     &[],
@@ -86,6 +177,13 @@ pub const RPC_PORT: OptionType = (
     &[],
 );
 
+pub const RPC_WS_PORT: OptionType = (
+    "[rpc-ws-port] --rpc-ws-port=[rpc-ws-port] 'Specify the port the json rpc websocket server for subscriptions is run on'",
+    &["no-jsonrpc"],
+    &[],
+    &[],
+);
+
 pub const RPC_USERNAME: OptionType = (
     "[rpc-username] --rpc-username=[rpc-username] 'Specify a username for rpc authentication'",
     &["no-jsonrpc"],
@@ -106,3 +204,215 @@ pub const VERBOSE: OptionType = (
     &["0", "1", "2"],
     &[],
 );
+
+pub const LOG_FILTER: OptionType = (
+    "[log-filter] --log-filter=[directives] 'Specify the log levels of modules, such as snarkos_network=debug,snarkos_rpc=warn'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_UPLOAD: OptionType = (
+    "[max-upload] --max-upload=[kilobytes] 'Specify the maximum kilobytes per second sent to all the peers'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_DOWNLOAD: OptionType = (
+    "[max-download] --max-download=[kilobytes] 'Specify the maximum kilobytes per second received from all the peers'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const VERIFICATION_THREADS: OptionType = (
+    "[verification-threads] --verification-threads=[threads] 'Specify the maximum number of threads verifying transactions (default = one per core)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_CACHE: OptionType = (
+    "[max-cache] --max-cache=[megabytes] 'Specify the maximum megabytes of the storage caches'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const PARAMETERS_MIRRORS: OptionType = (
+    "[parameters-mirrors] --parameters-mirrors=[urls] 'Specify the mirrors the missing parameters are downloaded from, in order of preference'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const TEST_PARAMETERS: OptionType = (
+    "[test-parameters] --test-parameters=[dir] 'Load the SNARK parameters from the insecure test parameters of a directory, generated by the parameters subcommand'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const DEV_BLOCK_INTERVAL: OptionType = (
+    "[dev-block-interval] --dev-block-interval=[seconds] 'Specify the seconds between two blocks of the development network (default = 5), or 0 to only mine the requested blocks'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const DEV_ACCOUNTS: OptionType = (
+    "[dev-accounts] --dev-accounts=[dev-accounts] 'Specify the number of pre-funded accounts of the development network (default = 10)'",
+    &[],
+    &[],
+    &[],
+);
+
+// Storage
+
+pub const MINER_ACTION: OptionType = (
+    "<action> 'Specify the mining operation to run'",
+    &[],
+    &["benchmark"],
+    &[],
+);
+
+pub const BENCHMARK_PROOFS: OptionType = (
+    "[proofs] --proofs=[proofs] 'Specify the number of proofs of work the benchmark generates'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const BENCHMARK_THREADS: OptionType = (
+    "[threads] --threads=[threads] 'Specify the number of threads the benchmark runs the proof of work on'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const STORAGE_ACTION: OptionType = (
+    "<action> 'Specify the storage operation to run'",
+    &[],
+    &["verify", "export", "import", "export-blocks", "import-blocks"],
+    &[],
+);
+
+pub const STORAGE_FILE: OptionType = (
+    "[file] 'Specify the block export file to write to or to read from'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const ACCOUNT_ACTION: OptionType = (
+    "<action> 'Specify the account operation to run'",
+    &[],
+    &["new", "import", "export"],
+    &[],
+);
+
+pub const ACCOUNT_PRIVATE_KEY: OptionType = (
+    "[private-key] --private-key=[private-key] 'Specify the private key of the account to import'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const ACCOUNT_KEYSTORE: OptionType = (
+    "[keystore] --keystore=[path] 'Specify the keystore file the private key of the account is encrypted in'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const ACCOUNT_PASSPHRASE: OptionType = (
+    "[passphrase] --passphrase=[passphrase] 'Specify the passphrase of the keystore file, or set SNARKOS_PASSPHRASE'",
+    &[],
+    &[],
+    &["keystore"],
+);
+
+pub const RPC_METHOD: OptionType = ("<method> 'Specify the RPC method to call'", &[], &[], &[]);
+
+pub const RPC_PARAMS: OptionType = (
+    "[params]... 'Specify the parameters of the RPC method, as JSON values or as strings'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const RPC_URL: OptionType = (
+    "[url] --url=[url] 'Specify the URL of the RPC server (default = the RPC server of the configured node)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const TEST_PARAMETERS_ACTION: OptionType = (
+    "<action> 'Specify the test parameters operation to run'",
+    &[],
+    &["generate"],
+    &[],
+);
+
+pub const TEST_PARAMETERS_OUTPUT: OptionType = (
+    "[output] --output=[dir] 'Specify the directory the test parameters are generated in (default = test_parameters in the data directory)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const TEST_PARAMETERS_SEED: OptionType = (
+    "[seed] --seed=[seed] 'Specify the seed the test parameters are derived from (default = 0)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const TEST_PARAMETERS_SRS_SIZE: OptionType = (
+    "[srs-size] --srs-size=[num-constraints] 'Specify the number of constraints the universal SRS of the proof of work supports (default = the size of the PoSW circuit)'",
+    &[],
+    &[],
+    &[],
+);
+
+/// The options of the node, given directly or to the start subcommand.
+pub const NODE: &[OptionType] = &[
+    CONFIG,
+    IP,
+    PORT,
+    DATA_DIR,
+    PATH,
+    PRUNE,
+    ARCHIVE,
+    GC,
+    READINESS_THRESHOLD,
+    CONNECT,
+    MINER_ADDRESS,
+    MINER_THREADS,
+    MINER_CORES,
+    MINER_TAG,
+    MINER_MAX_BLOCK_SIZE,
+    MINER_MAX_BLOCK_WEIGHT,
+    WORKER,
+    MINER_NODES,
+    MEMPOOL_INTERVAL,
+    MIN_PEERS,
+    MAX_PEERS,
+    NETWORK,
+    RPC_PORT,
+    RPC_WS_PORT,
+    RPC_USERNAME,
+    RPC_PASSWORD,
+    VERBOSE,
+    LOG_FILTER,
+    MAX_UPLOAD,
+    MAX_DOWNLOAD,
+    VERIFICATION_THREADS,
+    MAX_CACHE,
+    PARAMETERS_MIRRORS,
+    TEST_PARAMETERS,
+    DEV_BLOCK_INTERVAL,
+    DEV_ACCOUNTS,
+];
@@ -14,12 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::parameters::{flag, types::*};
+use crate::parameters::{flag, option, types::*};
 
 use clap::AppSettings;
 // Format
 // (name, about, options, flags, settings)
 
+pub const START: SubCommandType = (
+    "start",
+    "Start the node, as when no subcommand is given (include -h for more options)",
+    option::NODE,
+    flag::NODE,
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
 pub const UPDATE: SubCommandType = (
     "update",
     "Update the snarkOS to the latest version (include -h for more options)",
@@ -31,3 +43,77 @@ pub const UPDATE: SubCommandType = (
         AppSettings::DisableVersion,
     ],
 );
+
+pub const STORAGE: SubCommandType = (
+    "storage",
+    "Run an operation on the node storage (include -h for more options)",
+    &[option::STORAGE_ACTION, option::STORAGE_FILE],
+    &[flag::REPAIR],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
+pub const MINER: SubCommandType = (
+    "miner",
+    "Run a mining operation without running the node (include -h for more options)",
+    &[
+        option::MINER_ACTION,
+        option::BENCHMARK_PROOFS,
+        option::BENCHMARK_THREADS,
+    ],
+    &[],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
+pub const ACCOUNT: SubCommandType = (
+    "account",
+    "Create, import or export an account (include -h for more options)",
+    &[
+        option::ACCOUNT_ACTION,
+        option::ACCOUNT_PRIVATE_KEY,
+        option::ACCOUNT_KEYSTORE,
+        option::ACCOUNT_PASSPHRASE,
+    ],
+    &[],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
+pub const RPC: SubCommandType = (
+    "rpc",
+    "Call a method of the RPC server of a node (include -h for more options)",
+    &[option::RPC_METHOD, option::RPC_PARAMS, option::RPC_URL],
+    &[],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
+pub const PARAMETERS: SubCommandType = (
+    "parameters",
+    "Generate insecure, reduced-size parameters for test networks (include -h for more options)",
+    &[
+        option::TEST_PARAMETERS_ACTION,
+        option::TEST_PARAMETERS_OUTPUT,
+        option::TEST_PARAMETERS_SEED,
+        option::TEST_PARAMETERS_SRS_SIZE,
+    ],
+    &[],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
@@ -0,0 +1,191 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    cli::CLI,
+    config::{Config, ConfigCli},
+};
+use snarkos_consensus::set_verification_threads;
+use snarkos_errors::node::NodeError;
+use snarkos_network::internal::context::Context;
+use snarkos_rpc::{ConfigReloader, LogFilterReloader, RpcReloadHandle};
+
+use clap::ArgMatches;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex, RwLock},
+};
+use tokio::runtime::Runtime;
+
+/// Reloads the operational settings of a running node from its configuration, read again from the configuration
+/// file, the environment variables and the command line arguments, on a SIGHUP signal or a `reloadconfig` RPC call.
+///
+/// The reloaded settings are the peers to connect to, the peer limits and the allowed peer IDs, the log levels, the
+/// bandwidth limits and the verification threads, whether the miner is paused, and the API tokens and request limits
+/// of the RPC server. The other settings take effect on a restart.
+pub struct NodeReloader {
+    /// The command line arguments of the node
+    arguments: ArgMatches<'static>,
+    /// The configuration the node currently runs with
+    config: Mutex<Config>,
+    context: Arc<Context>,
+    log_filter: Option<LogFilterReloader>,
+    /// The handle of the RPC server, once it is started
+    rpc: RwLock<Option<RpcReloadHandle>>,
+}
+
+impl NodeReloader {
+    /// Creates the reloader of a node started with the given arguments and configuration.
+    pub fn new(
+        arguments: ArgMatches<'static>,
+        config: Config,
+        context: Arc<Context>,
+        log_filter: Option<LogFilterReloader>,
+    ) -> Self {
+        Self {
+            arguments,
+            config: Mutex::new(config),
+            context,
+            log_filter,
+            rpc: RwLock::new(None),
+        }
+    }
+
+    /// Reloads the settings of the given RPC server as well.
+    pub fn set_rpc(&self, rpc: RpcReloadHandle) {
+        *self.rpc.write().expect("reloader lock poisoned") = Some(rpc);
+    }
+
+    /// Reads the configuration again, and applies the operational settings that changed.
+    /// Returns the names of the replaced settings.
+    pub async fn reload(&self) -> Result<Vec<String>, NodeError> {
+        let config = ConfigCli::parse(&self.arguments)?;
+        let current = self.config.lock().expect("reloader lock poisoned").clone();
+
+        // Check the settings that can be invalid before any setting is replaced
+        let allowed_peers = config.p2p.allowed_peer_ids()?;
+
+        let mut reloaded = vec![];
+
+        let (peers, current_peers) = (config.p2p.startup_peers(), current.p2p.startup_peers());
+        if peers != current_peers {
+            // Connect to the peers added to the list, and keep the connected peers removed from it
+            let mut peer_book = self.context.peer_book.write().await;
+            for peer in &peers {
+                if let Ok(address) = peer.parse::<SocketAddr>() {
+                    if !current_peers.contains(peer) && !peer_book.connected_contains(&address) {
+                        peer_book.request_peer(address);
+                    }
+                }
+            }
+            *self.context.bootnodes.write().await = peers;
+
+            reloaded.push("peers".to_string());
+        }
+
+        if (config.p2p.min_peers, config.p2p.max_peers) != (current.p2p.min_peers, current.p2p.max_peers) {
+            self.context.set_peer_limits(config.p2p.min_peers, config.p2p.max_peers);
+
+            reloaded.push("peer_limits".to_string());
+        }
+
+        if config.p2p.allowed_peers != current.p2p.allowed_peers {
+            self.context.handshakes.write().await.set_allowed_peers(allowed_peers);
+
+            reloaded.push("allowed_peers".to_string());
+        }
+
+        let directives = config.log.directives(config.node.verbose);
+        if let Some(log_filter) = &self.log_filter {
+            if directives != current.log.directives(current.node.verbose) {
+                log_filter(&directives).map_err(NodeError::Message)?;
+
+                reloaded.push("log".to_string());
+            }
+        }
+
+        let (limits, current_limits) = (&config.limits, &current.limits);
+        if (limits.max_upload_kb, limits.max_download_kb)
+            != (current_limits.max_upload_kb, current_limits.max_download_kb)
+        {
+            limits.apply_bandwidth();
+
+            reloaded.push("bandwidth".to_string());
+        }
+
+        if limits.verification_threads != current_limits.verification_threads {
+            set_verification_threads(limits.verification_threads)?;
+
+            reloaded.push("verification_threads".to_string());
+        }
+
+        if config.miner.is_miner != current.miner.is_miner {
+            if self.context.is_miner {
+                self.context.pause_mining(!config.miner.is_miner);
+
+                reloaded.push("miner".to_string());
+            } else {
+                warn!("The miner is only started on a restart of the node");
+            }
+        }
+
+        if let Some(rpc) = &*self.rpc.read().expect("reloader lock poisoned") {
+            if config.rpc.tokens != current.rpc.tokens {
+                rpc.reload_tokens(config.rpc.tokens.clone());
+
+                reloaded.push("rpc_tokens".to_string());
+            }
+
+            if config.rpc.limits != current.rpc.limits {
+                rpc.reload_limits(config.rpc.limits.clone());
+
+                reloaded.push("rpc_limits".to_string());
+            }
+        }
+
+        *self.config.lock().expect("reloader lock poisoned") = config;
+
+        Ok(reloaded)
+    }
+
+    /// Returns the reloader of the `reloadconfig` RPC endpoint.
+    pub fn config_reloader(reloader: Arc<Self>) -> ConfigReloader {
+        Arc::new(move || {
+            Runtime::new()
+                .map_err(|error| error.to_string())?
+                .block_on(reloader.reload())
+                .map_err(|error| error.to_string())
+        })
+    }
+}
+
+/// Reloads the configuration of the node on each SIGHUP signal.
+#[cfg(unix)]
+pub async fn reload_on_sighup(reloader: Arc<NodeReloader>) -> Result<(), NodeError> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sighup = signal(SignalKind::hangup())?;
+
+    while sighup.recv().await.is_some() {
+        match reloader.reload().await {
+            Ok(reloaded) if reloaded.is_empty() => info!("Reloaded the configuration, without changed settings"),
+            Ok(reloaded) => info!("Reloaded the configuration settings {}", reloaded.join(", ")),
+            Err(error) => error!("Failed to reload the configuration: {}", error),
+        }
+    }
+
+    Ok(())
+}
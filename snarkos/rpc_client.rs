@@ -0,0 +1,100 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use snarkos_errors::node::CliError;
+
+use clap::ArgMatches;
+use reqwest::Client;
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+pub struct RpcClientCLI;
+
+impl RpcClientCLI {
+    /// Returns the URL of the RPC server of the configured node.
+    fn default_url(config: &Config) -> String {
+        let scheme = match config.rpc.tls {
+            Some(_) => "https",
+            None => "http",
+        };
+
+        format!("{}://127.0.0.1:{}", scheme, config.rpc.port)
+    }
+
+    /// Returns the parameter of a method, read as a JSON value, or as a string if it is not JSON.
+    fn param(param: &str) -> Value {
+        serde_json::from_str(param).unwrap_or_else(|_| Value::String(param.to_string()))
+    }
+
+    /// Call a method of the RPC server with the configured credentials, and return its result.
+    async fn call(config: &Config, url: &str, method: &str, params: Vec<Value>) -> Result<Value, CliError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+
+        let mut request = Client::new().post(url).json(&body);
+        if let Some(username) = &config.rpc.username {
+            request = request.basic_auth(username, config.rpc.password.as_ref());
+        }
+
+        let response: Value = request
+            .send()
+            .await
+            .map_err(reqwest_error)?
+            .json()
+            .await
+            .map_err(reqwest_error)?;
+
+        match &response["error"] {
+            Value::Null => Ok(response["result"].clone()),
+            error => Err(CliError::RpcError(
+                error["code"].as_i64().unwrap_or_default(),
+                error["message"].as_str().unwrap_or("unknown error").to_string(),
+            )),
+        }
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        let method = match arguments.value_of("method") {
+            Some(method) => method,
+            None => return Ok(()),
+        };
+        let params = arguments
+            .values_of("params")
+            .map(|params| params.map(Self::param).collect())
+            .unwrap_or_default();
+        let url = arguments
+            .value_of("url")
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| Self::default_url(config));
+
+        let result = Runtime::new()?.block_on(Self::call(config, &url, method, params))?;
+
+        match result {
+            Value::String(result) => println!("{}", result),
+            result => println!(
+                "{}",
+                serde_json::to_string_pretty(&result)
+                    .map_err(|error| CliError::Crate("serde_json", format!("{:?}", error)))?
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+fn reqwest_error(error: reqwest::Error) -> CliError {
+    CliError::Crate("reqwest", format!("{:?}", error))
+}
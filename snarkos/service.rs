@@ -0,0 +1,202 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::MerkleTreeLedger;
+use snarkos_network::internal::context::Context;
+
+use std::{
+    env,
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::delay_for;
+
+/// The environment variable of the socket the service manager receives the notifications on.
+const NOTIFY_SOCKET_VAR: &str = "NOTIFY_SOCKET";
+
+/// The environment variable of the microseconds the service manager waits for a watchdog ping.
+const WATCHDOG_USEC_VAR: &str = "WATCHDOG_USEC";
+
+/// The environment variable of the process the watchdog pings are expected from.
+const WATCHDOG_PID_VAR: &str = "WATCHDOG_PID";
+
+/// The time between two checks of the readiness of the node.
+const READINESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sends the readiness, status and watchdog notifications of the node to its service manager, such as systemd,
+/// on the socket of the `NOTIFY_SOCKET` environment variable. The notifications are dropped if the node is not
+/// run by a service manager.
+#[derive(Clone, Debug, Default)]
+pub struct ServiceNotifier {
+    /// The socket of the service manager, if the node is run by one
+    socket: Option<PathBuf>,
+    /// The time between two watchdog pings, if the service manager expects them
+    watchdog_interval: Option<Duration>,
+}
+
+impl ServiceNotifier {
+    /// Creates the notifier of the service manager set in the environment variables of the node.
+    pub fn from_env() -> Self {
+        // The sockets of the abstract namespace are named with a leading `@`, and are not supported
+        let socket = env::var_os(NOTIFY_SOCKET_VAR).map(PathBuf::from).filter(|socket| {
+            let is_abstract = socket.to_string_lossy().starts_with('@');
+            if is_abstract {
+                warn!("The abstract notification socket {} is not supported", socket.display());
+            }
+            !is_abstract
+        });
+
+        // The watchdog pings are expected from this process, unless another process is set
+        let is_watched = env::var(WATCHDOG_PID_VAR).map_or(true, |pid| pid == process::id().to_string());
+        let watchdog_interval = env::var(WATCHDOG_USEC_VAR)
+            .ok()
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .filter(|usec| is_watched && *usec > 0)
+            .map(|usec| Duration::from_micros(usec / 2));
+
+        Self {
+            socket,
+            watchdog_interval,
+        }
+    }
+
+    /// Returns true if the node is run by a service manager.
+    pub fn is_enabled(&self) -> bool {
+        self.socket.is_some()
+    }
+
+    /// Returns the time between two watchdog pings, half the timeout of the service manager, if it expects them.
+    pub fn watchdog_interval(&self) -> Option<Duration> {
+        self.watchdog_interval
+    }
+
+    /// Sends the given newline separated `KEY=VALUE` assignments to the service manager, if the node is run by one.
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        match &self.socket {
+            Some(socket) => send(socket, state),
+            None => Ok(()),
+        }
+    }
+
+    /// Notifies the service manager that the node is ready, with the given status.
+    pub fn ready(&self, status: &str) -> io::Result<()> {
+        self.notify(&format!("READY=1\nSTATUS={}", status))
+    }
+
+    /// Notifies the service manager of the status of the node.
+    pub fn status(&self, status: &str) -> io::Result<()> {
+        self.notify(&format!("STATUS={}", status))
+    }
+
+    /// Pings the watchdog of the service manager, if it expects the pings.
+    pub fn watchdog(&self) -> io::Result<()> {
+        match self.watchdog_interval {
+            Some(_) => self.notify("WATCHDOG=1"),
+            None => Ok(()),
+        }
+    }
+
+    /// Notifies the service manager that the node is shutting down.
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notify("STOPPING=1\nSTATUS=Shutting down")
+    }
+}
+
+/// Sends a notification to the datagram socket of the service manager.
+#[cfg(unix)]
+fn send(socket: &Path, state: &str) -> io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    UnixDatagram::unbound()?.send_to(state.as_bytes(), socket)?;
+
+    Ok(())
+}
+
+/// The service managers are only supported on unix.
+#[cfg(not(unix))]
+fn send(_socket: &Path, _state: &str) -> io::Result<()> {
+    Ok(())
+}
+
+/// The conditions of the readiness of a node: the node is ready once its RPC server serves the clients and its
+/// chain is within the readiness threshold of the highest block height advertised by its peers.
+#[derive(Clone, Copy, Debug)]
+pub struct Readiness {
+    /// The number of blocks the node can be behind its peers and be ready
+    pub threshold: u32,
+    /// Whether the node waits for the height of a peer, unless it runs without peers, as a bootnode does
+    pub needs_peers: bool,
+}
+
+impl Readiness {
+    /// Returns true if the chain of the given height is synced enough with the highest height of the peers.
+    pub fn is_synced(&self, height: u32, highest_peer_height: Option<u32>) -> bool {
+        match highest_peer_height {
+            Some(peer_height) => peer_height.saturating_sub(height) <= self.threshold,
+            None => !self.needs_peers,
+        }
+    }
+}
+
+/// Reports the status of the node to its service manager, and notifies it once the node is ready. The watchdog of
+/// the service manager is pinged as long as the node runs, so the future is polled by the main loop of the node,
+/// with the RPC server started.
+pub async fn supervise(
+    notifier: ServiceNotifier,
+    readiness: Readiness,
+    context: Arc<Context>,
+    storage: Arc<MerkleTreeLedger>,
+) {
+    let interval = notifier
+        .watchdog_interval()
+        .map_or(READINESS_INTERVAL, |interval| interval.min(READINESS_INTERVAL));
+    let mut is_ready = false;
+
+    loop {
+        if !is_ready {
+            let highest_peer_height = {
+                let peer_book = context.peer_book.read().await;
+                let connections = context.connections.read().await;
+                connections.highest_peer_height(peer_book.get_connected().keys())
+            };
+            let height = storage.get_latest_block_height();
+
+            let result = if readiness.is_synced(height, highest_peer_height) {
+                is_ready = true;
+                info!("The node is ready at block {}", height);
+                notifier.ready(&format!("Ready at block {}", height))
+            } else {
+                match highest_peer_height {
+                    Some(peer_height) => notifier.status(&format!("Syncing block {} of {}", height, peer_height)),
+                    None => notifier.status(&format!("Waiting for peers at block {}", height)),
+                }
+            };
+
+            if let Err(error) = result {
+                warn!("Failed to notify the service manager: {}", error);
+            }
+        }
+
+        if let Err(error) = notifier.watchdog() {
+            warn!("Failed to ping the watchdog of the service manager: {}", error);
+        }
+
+        delay_for(interval).await;
+    }
+}
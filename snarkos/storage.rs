@@ -0,0 +1,132 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use snarkos_consensus::MemoryPool;
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_errors::{node::CliError, storage::StorageError};
+use snarkos_storage::BlockExportReader;
+
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+pub struct StorageCLI;
+
+impl StorageCLI {
+    /// Verify the integrity of the node storage, and optionally repair it
+    fn verify(path: PathBuf, config: &Config, repair: bool) -> Result<(), CliError> {
+        let storage = config.open_ledger(path)?;
+        let inconsistencies = storage.verify_integrity(repair)?;
+        storage.shut_down_storage()?;
+
+        if inconsistencies.is_empty() {
+            println!("No inconsistencies found in the storage");
+            return Ok(());
+        }
+
+        println!("Found {} inconsistencies in the storage", inconsistencies.len());
+        for inconsistency in &inconsistencies {
+            println!("* {}", inconsistency);
+        }
+
+        if repair {
+            println!("The inconsistencies have been repaired");
+        } else {
+            println!("Run the command again with --repair to repair them");
+        }
+
+        Ok(())
+    }
+
+    /// Export the canon chain of the node storage to a block export file
+    fn export_blocks(path: PathBuf, config: &Config, file: &Path) -> Result<(), CliError> {
+        let storage = config.open_ledger(path)?;
+        let num_blocks = storage.export_blocks(BufWriter::new(File::create(file)?))?;
+        storage.shut_down_storage()?;
+
+        println!("Exported {} blocks to {}", num_blocks, file.display());
+
+        Ok(())
+    }
+
+    /// Import the blocks of a block export file into the node storage.
+    /// The blocks that are not in the storage yet are verified with the consensus rules.
+    fn import_blocks(path: PathBuf, config: &Config, file: &Path) -> Result<(), CliError> {
+        let storage = config.open_ledger(path)?;
+        let blocks = BlockExportReader::<_, Tx>::new(BufReader::new(File::open(file)?))?;
+
+        let genesis_hash = storage.get_block_hash(0)?;
+        if blocks.header().genesis_hash != genesis_hash {
+            return Err(StorageError::InvalidBlockExport("the genesis block differs from the storage".into()).into());
+        }
+
+        let parameters = config.public_parameters(true)?;
+        let consensus = config.consensus_parameters(&parameters)?;
+        let mut memory_pool = MemoryPool::new();
+
+        let mut num_imported_blocks = 0;
+        for block in blocks {
+            let block = block?;
+            if storage.is_canon(&block.header.get_hash()) {
+                continue;
+            }
+
+            consensus.receive_block(&parameters, &storage, &mut memory_pool, &block)?;
+            num_imported_blocks += 1;
+        }
+        storage.shut_down_storage()?;
+
+        println!(
+            "Imported {} blocks, the latest block height is {}",
+            num_imported_blocks,
+            storage.get_latest_block_height()
+        );
+
+        Ok(())
+    }
+
+    /// Returns the file argument that an action requires
+    fn file<'a>(arguments: &'a ArgMatches, action: &str) -> Result<&'a Path, CliError> {
+        match arguments.value_of("file") {
+            Some(file) => Ok(Path::new(file)),
+            None => Err(CliError::MissingStorageFile(action.into())),
+        }
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        // The storage can not be used by a running node
+        let data_dir = config.data_dir();
+        let _data_dir_lock = data_dir.lock()?;
+        data_dir.migrate_storage()?;
+
+        let path = config.storage_path();
+
+        match arguments.value_of("action") {
+            Some("verify") => Self::verify(path, config, arguments.is_present("repair")),
+            Some(action @ "export") | Some(action @ "export-blocks") => {
+                Self::export_blocks(path, config, Self::file(arguments, action)?)
+            }
+            Some(action @ "import") | Some(action @ "import-blocks") => {
+                Self::import_blocks(path, config, Self::file(arguments, action)?)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use snarkos_curves::bls12_377::Bls12_377;
+use snarkos_dpc::base_dpc::{
+    inner_circuit::InnerCircuit,
+    instantiated::Components,
+    outer_circuit::OuterCircuit,
+    parameters::SystemParameters,
+    program::{NoopCircuit, PrivateProgramInput},
+    BaseDPCComponents,
+    DPC,
+};
+use snarkos_errors::{dpc::DPCError, node::CliError};
+use snarkos_marlin::snark::Marlin;
+use snarkos_models::{
+    algorithms::{MerkleParameters, SNARK},
+    parameters::Parameters,
+};
+use snarkos_parameters::{
+    mark_insecure,
+    InnerSNARKPKParameters,
+    InnerSNARKVKParameters,
+    LedgerMerkleTreeParameters,
+    NoopProgramSNARKPKParameters,
+    NoopProgramSNARKVKParameters,
+    OuterSNARKPKParameters,
+    OuterSNARKVKParameters,
+    ParameterFile,
+    PoswSNARKPKParameters,
+    PoswSNARKVKParameters,
+};
+use snarkos_posw::PoswMarlin;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use clap::ArgMatches;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{fs, io, path::Path};
+
+/// The seed of the test parameters, unless another one is given.
+const DEFAULT_SEED: u64 = 0;
+
+pub struct TestParametersCLI;
+
+impl TestParametersCLI {
+    /// Generate the insecure test parameters of the proof of work and of the DPC in the given directory.
+    ///
+    /// The parameters are derived from the given seed, so the same seed generates the same parameters on every node
+    /// of a test network. The universal SRS of the proof of work supports the given number of constraints, or the
+    /// size of the PoSW circuit by default, rather than the size of the mainnet SRS. The system parameters and the
+    /// ledger parameters are not generated, so the accounts remain compatible with the other networks.
+    fn generate(output: &Path, seed: u64, srs_size: Option<usize>) -> Result<(), CliError> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+
+        fs::create_dir_all(output)?;
+
+        // The proof of work
+        let (num_constraints, num_variables, num_non_zero) = PoswMarlin::circuit_size()?;
+        let (num_constraints, num_variables, num_non_zero) = match srs_size {
+            Some(size) if size < num_constraints.max(num_variables) || size * 10 < num_non_zero => {
+                return Err(CliError::SrsTooSmall(num_constraints.max(num_variables), size));
+            }
+            Some(size) => (size, size, size * 10),
+            None => (num_constraints, num_variables, num_non_zero),
+        };
+        println!(
+            "Generating a universal SRS of {} constraints, {} variables and {} non-zero entries",
+            num_constraints, num_variables, num_non_zero
+        );
+
+        let srs = Marlin::<Bls12_377>::universal_setup(num_constraints, num_variables, num_non_zero, rng)
+            .map_err(|error| CliError::Crate("marlin", format!("{:?}", error)))?;
+        let posw = PoswMarlin::index(srs)?;
+        let posw_pk = posw.pk.expect("the indexed PoSW has a proving key");
+        Self::store(output, PoswSNARKPKParameters::FILE, &to_bytes![posw_pk]?)?;
+        Self::store(output, PoswSNARKVKParameters::FILE, &to_bytes![posw.vk]?)?;
+
+        Self::generate_dpc::<Components, _>(output, rng)?;
+
+        println!(
+            "\nWARNING - the parameters in {:?} are INSECURE and for testing only. Start the nodes of the test network with --test-parameters={}",
+            output,
+            output.display()
+        );
+
+        Ok(())
+    }
+
+    /// Generate the keys of the noop program SNARK and of the inner and outer SNARKs of the DPC, with the system
+    /// parameters and the ledger parameters of this node.
+    fn generate_dpc<C: BaseDPCComponents, R: Rng>(output: &Path, rng: &mut R) -> Result<(), DPCError> {
+        let system_parameters = SystemParameters::<C>::load()?;
+
+        let merkle_tree_hash_parameters: <C::MerkleParameters as MerkleParameters>::H =
+            From::from(FromBytes::read(&LedgerMerkleTreeParameters::load_bytes()?[..])?);
+        let ledger_parameters = From::from(merkle_tree_hash_parameters);
+
+        let noop_program_snark_parameters = DPC::<C>::generate_noop_program_snark_parameters(&system_parameters, rng)?;
+        let noop_program_snark_pk = to_bytes![noop_program_snark_parameters.proving_key]?;
+        let noop_program_snark_vk = to_bytes![noop_program_snark_parameters.verification_key]?;
+        Self::store(output, NoopProgramSNARKPKParameters::FILE, &noop_program_snark_pk)?;
+        Self::store(output, NoopProgramSNARKVKParameters::FILE, &noop_program_snark_vk)?;
+
+        let inner_circuit = InnerCircuit::blank(&system_parameters, &ledger_parameters);
+        let (inner_snark_pk, inner_snark_pvk) = C::InnerSNARK::setup(&inner_circuit, rng)?;
+        let inner_snark_vk: <C::InnerSNARK as SNARK>::VerificationParameters = inner_snark_pvk.into();
+        Self::store(output, InnerSNARKPKParameters::FILE, &to_bytes![inner_snark_pk]?)?;
+        Self::store(output, InnerSNARKVKParameters::FILE, &to_bytes![inner_snark_vk]?)?;
+
+        // The outer circuit verifies the proofs of the inner SNARK and of the noop program
+        let inner_snark_proof = C::InnerSNARK::prove(&inner_snark_pk, &inner_circuit, rng)?;
+        let program_snark_proof = C::NoopProgramSNARK::prove(
+            &noop_program_snark_parameters.proving_key,
+            &NoopCircuit::blank(&system_parameters),
+            rng,
+        )?;
+        let private_program_input = PrivateProgramInput {
+            verification_key: noop_program_snark_vk,
+            proof: to_bytes![program_snark_proof]?,
+        };
+
+        let (outer_snark_pk, outer_snark_pvk) = C::OuterSNARK::setup(
+            &OuterCircuit::blank(
+                system_parameters,
+                ledger_parameters,
+                inner_snark_vk,
+                inner_snark_proof,
+                private_program_input,
+            ),
+            rng,
+        )?;
+        let outer_snark_vk: <C::OuterSNARK as SNARK>::VerificationParameters = outer_snark_pvk.into();
+        Self::store(output, OuterSNARKPKParameters::FILE, &to_bytes![outer_snark_pk]?)?;
+        Self::store(output, OuterSNARKVKParameters::FILE, &to_bytes![outer_snark_vk]?)?;
+
+        Ok(())
+    }
+
+    /// Store the given parameters in the output directory, marked as insecure test parameters.
+    fn store(output: &Path, file: ParameterFile, bytes: &[u8]) -> io::Result<()> {
+        let path = output.join(file.filename());
+        fs::write(&path, mark_insecure(bytes))?;
+
+        println!("{}\n\tsize - {}", path.display(), bytes.len());
+
+        Ok(())
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        match arguments.value_of("action") {
+            Some("generate") => {
+                let output = arguments
+                    .value_of("output")
+                    .map(|output| output.into())
+                    .unwrap_or_else(|| config.data_dir().test_parameters());
+                // The invalid values exit with an error, and the defaults only apply to the absent options
+                let seed = match arguments.value_of("seed") {
+                    Some(_) => clap::value_t!(arguments.value_of("seed"), u64).unwrap_or_else(|error| error.exit()),
+                    None => DEFAULT_SEED,
+                };
+                let srs_size = match arguments.value_of("srs-size") {
+                    Some(_) => {
+                        Some(clap::value_t!(arguments.value_of("srs-size"), usize).unwrap_or_else(|error| error.exit()))
+                    }
+                    None => None,
+                };
+
+                Self::generate(&output, seed, srs_size)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{DatabaseTransaction, StorageMetrics};
+use snarkos_errors::storage::StorageError;
+
+use std::path::Path;
+
+/// An iterator over the key value pairs of a column, in key order.
+pub type StorageIterator<'a> = Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+
+/// The key-value operations the ledger requires from its storage.
+pub trait StorageBackend: Send + Sync + Sized {
+    /// Opens the storage at the given path with the given number of columns.
+    fn open<P: AsRef<Path>>(path: P, num_cols: u32) -> Result<Self, StorageError>;
+
+    /// Returns the value from a given key and col.
+    fn get(&self, col: u32, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Returns the iterator from a given col.
+    fn get_iter(&self, col: u32) -> Result<StorageIterator, StorageError>;
+
+    /// Returns the iterator from a given col, starting at the first key greater than or equal to the given key.
+    fn get_iter_from(&self, col: u32, key: &[u8]) -> Result<StorageIterator, StorageError>;
+
+    /// Returns `Ok(())` after executing a database transaction atomically.
+    fn write(&self, transaction: DatabaseTransaction) -> Result<(), StorageError>;
+
+    /// Returns the size of each column and the activity of the storage.
+    fn metrics(&self) -> Result<StorageMetrics, StorageError>;
+
+    /// Returns true if a value exists for a key and col pair.
+    fn exists(&self, col: u32, key: &[u8]) -> bool {
+        matches!(self.get(col, key), Ok(Some(_)))
+    }
+
+    /// Returns true if the storage rejects writes.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Attempt to catch a read-only storage instance up with the instance writing to the storage.
+    fn try_catch_up_with_primary(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::storage::StorageError;
+use snarkos_utilities::bytes::{FromBytes, ToBytes};
+
+use parking_lot::Mutex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Result as IoResult, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// The size after which a new archive file is started.
+const MAX_ARCHIVE_FILE_SIZE: u64 = 128 * 1024 * 1024;
+
+/// The location of a block body in the archive files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArchiveLocation {
+    /// Number of the archive file
+    pub file_number: u32,
+    /// Offset of the block body in the archive file
+    pub offset: u64,
+    /// Size of the block body
+    pub length: u32,
+}
+
+impl ToBytes for ArchiveLocation {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.file_number.write(&mut writer)?;
+        self.offset.write(&mut writer)?;
+        self.length.write(&mut writer)
+    }
+}
+
+impl FromBytes for ArchiveLocation {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let file_number: u32 = FromBytes::read(&mut reader)?;
+        let offset: u64 = FromBytes::read(&mut reader)?;
+        let length: u32 = FromBytes::read(&mut reader)?;
+
+        Ok(Self {
+            file_number,
+            offset,
+            length,
+        })
+    }
+}
+
+/// Append-only flat files storing the bodies of old blocks outside of the key-value storage.
+///
+/// Bodies are only appended, and their locations are indexed in the key-value storage once they
+/// are synced to disk, so a crash leaves at most unindexed bytes at the end of an archive file.
+pub struct BlockArchive {
+    path: PathBuf,
+    append_lock: Mutex<()>,
+}
+
+impl BlockArchive {
+    /// Returns the archive kept in the given directory. The directory is created on the first append.
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            append_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the path of the archive directory of the storage at a given path.
+    pub fn archive_path<P: AsRef<Path>>(storage_path: P) -> PathBuf {
+        let mut archive_path_os_string = storage_path.as_ref().to_path_buf().into_os_string();
+        archive_path_os_string.push("_archive");
+
+        PathBuf::from(archive_path_os_string)
+    }
+
+    /// Returns the path of an archive file.
+    fn file_path(&self, file_number: u32) -> PathBuf {
+        self.path.join(format!("blocks{:05}.dat", file_number))
+    }
+
+    /// Returns the number of the latest archive file, or 0 if there are none.
+    fn latest_file_number(&self) -> u32 {
+        let mut file_number = 0;
+        while self.file_path(file_number + 1).exists() {
+            file_number += 1;
+        }
+
+        file_number
+    }
+
+    /// Append block bodies to the archive and return their locations.
+    pub fn append(&self, bodies: &[Vec<u8>]) -> Result<Vec<ArchiveLocation>, StorageError> {
+        let _append_lock = self.append_lock.lock();
+
+        fs::create_dir_all(&self.path)?;
+
+        let mut file_number = self.latest_file_number();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path(file_number))?;
+        let mut offset = file.metadata()?.len();
+
+        let mut locations = Vec::with_capacity(bodies.len());
+
+        for body in bodies {
+            if offset > 0 && offset + body.len() as u64 > MAX_ARCHIVE_FILE_SIZE {
+                file.sync_data()?;
+
+                file_number += 1;
+                file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.file_path(file_number))?;
+                offset = 0;
+            }
+
+            file.write_all(body)?;
+
+            locations.push(ArchiveLocation {
+                file_number,
+                offset,
+                length: body.len() as u32,
+            });
+            offset += body.len() as u64;
+        }
+
+        file.sync_data()?;
+
+        Ok(locations)
+    }
+
+    /// Read a block body from the archive.
+    pub fn read(&self, location: &ArchiveLocation) -> Result<Vec<u8>, StorageError> {
+        let mut file = File::open(self.file_path(location.file_number))?;
+        file.seek(SeekFrom::Start(location.offset))?;
+
+        let mut body = vec![0u8; location.length as usize];
+        file.read_exact(&mut body)?;
+
+        Ok(body)
+    }
+
+    /// Returns the size of the archive files, in bytes.
+    pub fn size(&self) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for file_number in 0..=self.latest_file_number() {
+            if let Ok(metadata) = fs::metadata(self.file_path(file_number)) {
+                size += metadata.len();
+            }
+        }
+
+        Ok(size)
+    }
+
+    /// Remove the archive files.
+    pub fn destroy(&self) -> Result<(), StorageError> {
+        if self.path.exists() {
+            fs::remove_dir_all(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+
+/// The false positive rate of a bloom filter holding as many items as it was sized for.
+const FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// The FNV-1a offset basis and prime.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x100_0000_01b3;
+
+/// The constant separating the second item hash from the first one.
+const SECOND_HASH_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A bloom filter answering whether an item may have been inserted, without false negatives.
+///
+/// The item hashes are computed with a fixed hash function, so a persisted filter answers
+/// the same after a restart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    /// Bits of the filter
+    bits: Vec<u64>,
+    /// Number of bits set for each item
+    num_hashes: u32,
+    /// Number of items the filter was sized for
+    capacity: u64,
+    /// Number of items inserted in the filter
+    num_items: u64,
+}
+
+impl BloomFilter {
+    /// Returns an empty filter sized to hold `capacity` items at the target false positive rate.
+    pub fn new(capacity: u64) -> Self {
+        let capacity = capacity.max(1);
+        let ln_2 = std::f64::consts::LN_2;
+
+        let num_bits = (-(capacity as f64) * FALSE_POSITIVE_RATE.ln() / (ln_2 * ln_2)).ceil() as u64;
+        let num_words = ((num_bits + 63) / 64).max(1);
+        let num_hashes = ((num_words * 64) as f64 / capacity as f64 * ln_2).round().max(1.0) as u32;
+
+        Self {
+            bits: vec![0u64; num_words as usize],
+            num_hashes,
+            capacity,
+            num_items: 0,
+        }
+    }
+
+    /// Returns the number of items inserted in the filter.
+    pub fn len(&self) -> u64 {
+        self.num_items
+    }
+
+    /// Returns true if no item was inserted in the filter.
+    pub fn is_empty(&self) -> bool {
+        self.num_items == 0
+    }
+
+    /// Returns true if the filter holds more items than it was sized for,
+    /// so its false positive rate exceeds the target rate.
+    pub fn is_saturated(&self) -> bool {
+        self.num_items > self.capacity
+    }
+
+    /// Insert an item in the filter.
+    pub fn insert(&mut self, item: &[u8]) {
+        for bit in self.bit_indexes(item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.num_items += 1;
+    }
+
+    /// Returns false if the item was never inserted in the filter, and true if it may have been.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.bit_indexes(item)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Returns the indexes of the bits of an item, derived from two hashes of the item.
+    fn bit_indexes(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        let num_bits = self.bits.len() as u64 * 64;
+        let item_hash = fnv1a(item);
+        let first_hash = mix(item_hash);
+        let second_hash = mix(item_hash ^ SECOND_HASH_SEED) | 1;
+
+        (0..self.num_hashes as u64).map(move |i| first_hash.wrapping_add(i.wrapping_mul(second_hash)) % num_bits)
+    }
+}
+
+/// Returns the FNV-1a hash of the given bytes.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Returns the given hash with its bits mixed by the SplitMix64 finalizer.
+fn mix(hash: u64) -> u64 {
+    let hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
@@ -0,0 +1,126 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use rocksdb::{BlockBasedOptions, DBCompactionStyle, Options};
+use serde::{Deserialize, Serialize};
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+/// The compaction style of the storage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactionStyle {
+    Level,
+    Universal,
+    Fifo,
+}
+
+impl From<CompactionStyle> for DBCompactionStyle {
+    fn from(compaction_style: CompactionStyle) -> Self {
+        match compaction_style {
+            CompactionStyle::Level => DBCompactionStyle::Level,
+            CompactionStyle::Universal => DBCompactionStyle::Universal,
+            CompactionStyle::Fifo => DBCompactionStyle::Fifo,
+        }
+    }
+}
+
+/// The tuning options of the storage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Size of the block cache of each column, in megabytes
+    pub block_cache_mb: u64,
+    /// Maximum size of the block caches of all the columns together, in megabytes, which lowers
+    /// the block cache of each column if needed
+    pub max_cache_mb: Option<u64>,
+    /// Compaction style of each column
+    pub compaction_style: CompactionStyle,
+    /// Maximum number of write buffers of each column
+    pub max_write_buffer_number: i32,
+    /// Size of the write-ahead log that triggers a flush of the columns, in megabytes.
+    /// The size is chosen by RocksDB if zero.
+    pub max_wal_mb: u64,
+    /// Size of the write-ahead log writes after which the log is synced to disk, in megabytes.
+    /// The log is only synced by the operating system if zero.
+    pub wal_sync_mb: u64,
+    /// Maximum rate of the flush and compaction writes, in megabytes per second
+    pub rate_limit_mb: Option<u64>,
+    /// Number of seconds without a new block after which the node compacts the storage
+    pub compaction_idle_secs: Option<u64>,
+    /// Keep the statistics reporting the cache hit rate and write amplification of the storage
+    pub statistics: bool,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            block_cache_mb: 8,
+            max_cache_mb: None,
+            compaction_style: CompactionStyle::Level,
+            max_write_buffer_number: 16,
+            max_wal_mb: 0,
+            wal_sync_mb: 0,
+            rate_limit_mb: None,
+            compaction_idle_secs: Some(600),
+            statistics: false,
+        }
+    }
+}
+
+impl StorageConfig {
+    /// Returns the size of the block cache of each column of a storage with the given number of columns,
+    /// in megabytes.
+    pub fn column_cache_mb(&self, num_cfs: u32) -> u64 {
+        match self.max_cache_mb {
+            Some(max_cache_mb) => self.block_cache_mb.min(max_cache_mb / u64::from(num_cfs.max(1))),
+            None => self.block_cache_mb,
+        }
+    }
+
+    /// Returns the RocksDB options of a column of a storage with the given number of columns.
+    pub(crate) fn column_options(&self, num_cfs: u32) -> Options {
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_lru_cache((self.column_cache_mb(num_cfs) * MEGABYTE) as usize);
+
+        let mut cf_opts = Options::default();
+        cf_opts.set_max_write_buffer_number(self.max_write_buffer_number);
+        cf_opts.set_compaction_style(self.compaction_style.into());
+        cf_opts.set_block_based_table_factory(&block_opts);
+
+        cf_opts
+    }
+
+    /// Returns the RocksDB options of the storage.
+    pub(crate) fn storage_options(&self) -> Options {
+        let mut storage_opts = Options::default();
+        storage_opts.increase_parallelism(3);
+        storage_opts.create_missing_column_families(true);
+        storage_opts.create_if_missing(true);
+        storage_opts.set_max_total_wal_size(self.max_wal_mb * MEGABYTE);
+        storage_opts.set_wal_bytes_per_sync(self.wal_sync_mb * MEGABYTE);
+
+        if let Some(rate_limit_mb) = self.rate_limit_mb {
+            storage_opts.set_ratelimiter((rate_limit_mb * MEGABYTE) as i64, 100_000, 10);
+        }
+
+        if self.statistics {
+            storage_opts.enable_statistics();
+        }
+
+        storage_opts
+    }
+}
@@ -29,17 +29,68 @@ pub const COL_MEMO: u32 = 7; // Memo -> index
 pub const COL_DIGEST: u32 = 8; // Ledger digest -> index
 pub const COL_RECORDS: u32 = 9; // commitment -> record bytes
 pub const COL_CHILD_HASHES: u32 = 10; // block hash -> vector of potential child hashes
-pub const NUM_COLS: u32 = 11;
-
+pub const COL_RECORD_INDEX: u32 = 11; // account view key -> vector of (block number, record commitment)
+pub const COL_BLOCK_HEIGHT_INDEX: u32 = 12; // Block num (big-endian) -> block hash
+pub const COL_TRANSACTION_HEIGHT_INDEX: u32 = 13; // Block num and transaction index (big-endian) -> transaction id
+pub const COL_COMMITMENT_TREE: u32 = 14; // Level and node index (big-endian) -> commitment merkle tree node
+pub const COL_LEDGER_CHECKPOINT: u32 = 15; // Block num (big-endian) -> number of commitments and ledger digest
+pub const COL_BLOCK_UNDO: u32 = 16; // Block num (big-endian) -> changes reverted when decommitting the block
+pub const COL_BLOCK_ARCHIVE: u32 = 17; // Block hash -> location of the block transactions in the block archive
+pub const COL_SERIAL_NUMBER_TREE: u32 = 18; // Depth and path (big-endian) -> serial number sparse merkle tree node
+pub const COL_MEMO_SEARCH_INDEX: u32 = 19; // Memo -> block num (big-endian) and transaction id
+pub const COL_COMMITMENT_SEARCH_INDEX: u32 = 20; // Commitment -> block num (big-endian) and transaction id
+pub const COL_WALLET: u32 = 21; // Account view key -> wallet account (proof key, received records and their spends)
+pub const COL_SENT_TRANSACTIONS: u32 = 22; // Transaction id -> transaction sent by the node and not yet confirmed
+pub const NUM_COLS: u32 = 23;
+
+pub const KEY_ARCHIVED_BLOCK_NUMBER: &str = "ARCHIVED_BLOCK_NUMBER";
 pub const KEY_BEST_BLOCK_NUMBER: &str = "BEST_BLOCK_NUMBER";
+pub const KEY_CONSISTENCY_MARKER: &str = "CONSISTENCY_MARKER";
+pub const KEY_GARBAGE_COLLECTION: &str = "GARBAGE_COLLECTION";
+pub const KEY_INTERRUPTED_FORK: &str = "INTERRUPTED_FORK";
+pub const KEY_LEDGER_FILTERS: &str = "LEDGER_FILTERS";
 pub const KEY_MEMORY_POOL: &str = "MEMORY_POOL";
 pub const KEY_PEER_BOOK: &str = "PEER_BOOK";
+pub const KEY_PRUNED_BLOCK_NUMBER: &str = "PRUNED_BLOCK_NUMBER";
+pub const KEY_RPC_JOBS: &str = "RPC_JOBS";
+pub const KEY_RPC_TOKEN_ROTATIONS: &str = "RPC_TOKEN_ROTATIONS";
+pub const KEY_SCHEMA_VERSION: &str = "SCHEMA_VERSION";
 
 pub const KEY_CURR_CM_INDEX: &str = "CURRENT_CM_INDEX";
 pub const KEY_CURR_SN_INDEX: &str = "CURRENT_SN_INDEX";
 pub const KEY_CURR_MEMO_INDEX: &str = "CURRENT_MEMO_INDEX";
 pub const KEY_CURR_DIGEST: &str = "CURRENT_DIGEST";
 
+/// Returns the name of a column, as reported in the storage metrics.
+pub fn column_name(col: u32) -> &'static str {
+    match col {
+        COL_META => "meta",
+        COL_BLOCK_HEADER => "block_header",
+        COL_BLOCK_TRANSACTIONS => "block_transactions",
+        COL_BLOCK_LOCATOR => "block_locator",
+        COL_TRANSACTION_LOCATION => "transaction_location",
+        COL_COMMITMENT => "commitment",
+        COL_SERIAL_NUMBER => "serial_number",
+        COL_MEMO => "memo",
+        COL_DIGEST => "digest",
+        COL_RECORDS => "records",
+        COL_CHILD_HASHES => "child_hashes",
+        COL_RECORD_INDEX => "record_index",
+        COL_BLOCK_HEIGHT_INDEX => "block_height_index",
+        COL_TRANSACTION_HEIGHT_INDEX => "transaction_height_index",
+        COL_COMMITMENT_TREE => "commitment_tree",
+        COL_LEDGER_CHECKPOINT => "ledger_checkpoint",
+        COL_BLOCK_UNDO => "block_undo",
+        COL_BLOCK_ARCHIVE => "block_archive",
+        COL_SERIAL_NUMBER_TREE => "serial_number_tree",
+        COL_MEMO_SEARCH_INDEX => "memo_search_index",
+        COL_COMMITMENT_SEARCH_INDEX => "commitment_search_index",
+        COL_WALLET => "wallet",
+        COL_SENT_TRANSACTIONS => "sent_transactions",
+        _ => "unknown",
+    }
+}
+
 /// Represents address of certain transaction within block
 #[derive(Debug, PartialEq, Clone)]
 pub struct TransactionLocation {
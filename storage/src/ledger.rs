@@ -15,7 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::*;
-use snarkos_algorithms::merkle_tree::MerkleTree;
+use snarkos_algorithms::merkle_tree::IncrementalMerkleTree;
 use snarkos_errors::storage::StorageError;
 use snarkos_models::{
     algorithms::LoadableMerkleParameters,
@@ -27,7 +27,7 @@ use snarkos_objects::Block;
 use snarkos_parameters::{GenesisBlock, LedgerMerkleTreeParameters};
 use snarkos_utilities::bytes::FromBytes;
 
-use parking_lot::RwLock;
+use parking_lot::{ReentrantMutex, RwLock};
 use std::{
     fs,
     marker::PhantomData,
@@ -35,76 +35,130 @@ use std::{
     sync::Arc,
 };
 
-pub struct Ledger<T: Transaction, P: LoadableMerkleParameters> {
+pub struct Ledger<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend = Storage> {
     pub latest_block_height: RwLock<u32>,
     pub ledger_parameters: P,
-    pub cm_merkle_tree: RwLock<MerkleTree<P>>,
-    pub storage: Arc<Storage>,
+    pub cm_merkle_tree: RwLock<IncrementalMerkleTree<P>>,
+    pub block_lock: ReentrantMutex<()>,
+    pub filters: RwLock<Option<LedgerFilters>>,
+    pub storage: Arc<S>,
+    pub block_archive: Option<BlockArchive>,
+    /// The check of the storage run when the ledger was opened by a node, if any
+    pub startup_check: Option<StartupCheck>,
     pub _transaction: PhantomData<T>,
 }
 
 impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     /// Open the blockchain storage at a particular path.
     pub fn open_at_path<PATH: AsRef<Path>>(path: PATH) -> Result<Self, StorageError> {
+        Self::open_at_path_with_config(path, &StorageConfig::default())
+    }
+
+    /// Open the blockchain storage at a particular path with the given storage tuning options.
+    pub fn open_at_path_with_config<PATH: AsRef<Path>>(
+        path: PATH,
+        config: &StorageConfig,
+    ) -> Result<Self, StorageError> {
+        Self::open_at_path_with_genesis(path, config, &GenesisBlock::load_bytes())
+    }
+
+    /// Open the blockchain storage at a particular path with the given storage tuning options, starting
+    /// from the given serialized genesis block. An existing ledger must start from the same genesis block.
+    pub fn open_at_path_with_genesis<PATH: AsRef<Path>>(
+        path: PATH,
+        config: &StorageConfig,
+        genesis_block: &[u8],
+    ) -> Result<Self, StorageError> {
         fs::create_dir_all(path.as_ref()).map_err(|err| StorageError::Message(err.to_string()))?;
 
-        Self::load_ledger_state(path, true)
+        let ledger = Self::load_ledger_state(path, None, config, genesis_block)?;
+
+        let genesis_block: Block<T> = FromBytes::read(genesis_block)?;
+        let genesis_hash = genesis_block.header.get_hash();
+        let ledger_genesis_hash = ledger.get_block_hash(0)?;
+
+        if ledger_genesis_hash != genesis_hash {
+            return Err(StorageError::InvalidGenesisBlock(
+                hex::encode(ledger_genesis_hash.0),
+                hex::encode(genesis_hash.0),
+            ));
+        }
+
+        Ok(ledger)
     }
 
     /// Open the blockchain storage at a particular path as a secondary read-only instance.
     pub fn open_secondary_at_path<PATH: AsRef<Path>>(path: PATH) -> Result<Self, StorageError> {
-        fs::create_dir_all(path.as_ref()).map_err(|err| StorageError::Message(err.to_string()))?;
+        let mut secondary_path_os_string = path.as_ref().to_path_buf().into_os_string();
+        secondary_path_os_string.push("_secondary");
 
-        Self::load_ledger_state(path, false)
+        Self::open_secondary_at_paths(path.as_ref(), &PathBuf::from(secondary_path_os_string))
     }
 
-    /// Returns true if there are no blocks in the ledger.
-    pub fn is_empty(&self) -> bool {
-        self.get_latest_block().is_err()
-    }
+    /// Open the blockchain storage at a particular path as a secondary read-only instance
+    /// that keeps its own state at `secondary_path`.
+    ///
+    /// A secondary instance reads the storage concurrently with the primary instance and
+    /// follows it with `catch_up_secondary`. Each secondary instance that is open at the
+    /// same time, such as the one of an RPC server or of an indexing process, needs its
+    /// own secondary path.
+    pub fn open_secondary_at_paths<PATH: AsRef<Path>>(path: PATH, secondary_path: PATH) -> Result<Self, StorageError> {
+        fs::create_dir_all(path.as_ref()).map_err(|err| StorageError::Message(err.to_string()))?;
 
-    /// Get the latest block height of the chain.
-    pub fn get_latest_block_height(&self) -> u32 {
-        *self.latest_block_height.read()
+        Self::load_ledger_state(
+            path.as_ref(),
+            Some(secondary_path.as_ref()),
+            &StorageConfig::default(),
+            &GenesisBlock::load_bytes(),
+        )
     }
 
-    /// Get the latest number of blocks in the chain.
-    pub fn get_block_count(&self) -> u32 {
-        *self.latest_block_height.read() + 1
+    /// Compact the storage of the ledger. Compactions are expensive, so they are best run
+    /// while the node is idle.
+    pub fn compact_storage(&self) {
+        self.storage.compact()
     }
 
-    /// Get the stored old connected peers.
-    pub fn get_peer_book(&self) -> Result<Vec<u8>, StorageError> {
-        Ok(self.get(COL_META, &KEY_PEER_BOOK.as_bytes().to_vec())?)
+    /// Flush the storage of the ledger to disk, once the block being committed, if any, is committed.
+    pub fn flush_storage(&self) -> Result<(), StorageError> {
+        let _block_lock = self.lock_blocks();
+
+        self.storage.flush()
     }
 
-    /// Store the connected peers.
-    pub fn store_to_peer_book(&self, peers_serialized: Vec<u8>) -> Result<(), StorageError> {
-        let op = Op::Insert {
-            col: COL_META,
-            key: KEY_PEER_BOOK.as_bytes().to_vec(),
-            value: peers_serialized,
-        };
-        self.storage.write(DatabaseTransaction(vec![op]))
+    /// Record a clean shutdown at the latest block, and flush the storage to disk once the block being
+    /// committed, if any, is committed. The storage is not checked when it is opened again at the same block.
+    pub fn shut_down_storage(&self) -> Result<(), StorageError> {
+        let _block_lock = self.lock_blocks();
+
+        self.mark_consistent(true)?;
+        self.storage.flush()
     }
 
-    /// Destroy the storage given a path.
+    /// Destroy the storage and the block archive given a path.
     pub fn destroy_storage(path: PathBuf) -> Result<(), StorageError> {
+        BlockArchive::open(BlockArchive::archive_path(&path)).destroy()?;
         Storage::destroy_storage(path)
     }
 
     /// Returns a `Ledger` with the latest state loaded from storage at a given path as
-    /// a primary or secondary ledger. A secondary ledger runs as a read-only instance.
-    fn load_ledger_state<PATH: AsRef<Path>>(path: PATH, primary: bool) -> Result<Self, StorageError> {
-        let mut secondary_path_os_string = path.as_ref().to_path_buf().into_os_string();
-        secondary_path_os_string.push("_secondary");
-
-        let secondary_path = PathBuf::from(secondary_path_os_string);
+    /// a primary ledger, or as a secondary ledger if a secondary path is given.
+    /// A secondary ledger runs as a read-only instance.
+    /// A new ledger starts from the given serialized genesis block.
+    fn load_ledger_state<PATH: AsRef<Path>>(
+        path: PATH,
+        secondary_path: Option<&Path>,
+        config: &StorageConfig,
+        genesis_block: &[u8],
+    ) -> Result<Self, StorageError> {
+        if secondary_path.is_none() {
+            migrate_storage(path.as_ref())?;
+        }
 
         let latest_block_number = {
-            let storage = match primary {
-                true => Storage::open_cf(path.as_ref(), NUM_COLS)?,
-                false => Storage::open_secondary_cf(path.as_ref(), &secondary_path, NUM_COLS)?,
+            let storage = match secondary_path {
+                None => Storage::open_cf_with_config(path.as_ref(), NUM_COLS, config)?,
+                Some(secondary_path) => Storage::open_secondary_cf(path.as_ref(), secondary_path, NUM_COLS)?,
             };
             storage.get(COL_META, KEY_BEST_BLOCK_NUMBER.as_bytes())?
         };
@@ -114,74 +168,172 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
 
         match latest_block_number {
             Some(val) => {
-                let storage = match primary {
-                    true => Storage::open_cf(path.as_ref(), NUM_COLS)?,
-                    false => Storage::open_secondary_cf(path.as_ref(), &secondary_path, NUM_COLS)?,
+                let storage = match secondary_path {
+                    None => Storage::open_cf_with_config(path.as_ref(), NUM_COLS, config)?,
+                    Some(secondary_path) => Storage::open_secondary_cf(path.as_ref(), secondary_path, NUM_COLS)?,
                 };
 
-                // Build commitment merkle tree
-
-                let mut cm_and_indices = vec![];
-
-                for (commitment_key, index_value) in storage.get_iter(COL_COMMITMENT)? {
-                    let commitment: T::Commitment = FromBytes::read(&commitment_key[..])?;
-                    let index = bytes_to_u32(index_value.to_vec()) as usize;
-
-                    cm_and_indices.push((commitment, index));
-                }
-
-                cm_and_indices.sort_by(|&(_, i), &(_, j)| i.cmp(&j));
-                let commitments = cm_and_indices.into_iter().map(|(cm, _)| cm).collect::<Vec<_>>();
+                // Load commitment merkle tree
 
-                let merkle_tree = MerkleTree::new(ledger_parameters.clone(), &commitments)?;
+                let merkle_tree = Self::load_commitment_tree(&storage, &ledger_parameters)?;
 
-                Ok(Self {
+                let mut ledger = Self {
                     latest_block_height: RwLock::new(bytes_to_u32(val)),
                     storage: Arc::new(storage),
+                    block_archive: Some(BlockArchive::open(BlockArchive::archive_path(path.as_ref()))),
+                    startup_check: None,
                     cm_merkle_tree: RwLock::new(merkle_tree),
+                    block_lock: ReentrantMutex::new(()),
+                    filters: RwLock::new(None),
                     ledger_parameters,
                     _transaction: PhantomData,
-                })
+                };
+
+                if secondary_path.is_none() {
+                    ledger.backfill_ledger_checkpoints()?;
+                    ledger.backfill_search_indexes()?;
+                    ledger.recover_interrupted_fork()?;
+                    ledger.startup_check = Some(ledger.check_startup_consistency()?);
+                    ledger.load_filters()?;
+                }
+
+                Ok(ledger)
             }
             None => {
                 // Add genesis block to database
 
-                let genesis_block: Block<T> = FromBytes::read(GenesisBlock::load_bytes().as_slice())?;
+                let block: Block<T> = FromBytes::read(genesis_block)?;
 
-                let ledger_storage = Self::new(&path.as_ref().to_path_buf(), ledger_parameters, genesis_block)
+                let ledger_storage = Self::new(&path.as_ref().to_path_buf(), ledger_parameters, block)
                     .expect("Ledger could not be instantiated");
+                drop(ledger_storage);
 
-                // If there did not exist a primary ledger at the path,
-                // then create one and then open the secondary instance.
-                if !primary {
-                    return Self::load_ledger_state(path, primary);
-                }
-
-                Ok(ledger_storage)
+                // If there did not exist a primary ledger at the path, then create one and
+                // reopen it with the storage configuration, or open the secondary instance.
+                Self::load_ledger_state(path, secondary_path, config, genesis_block)
             }
         }
     }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns true if the ledger is a secondary read-only instance.
+    pub fn is_secondary(&self) -> bool {
+        self.storage.is_read_only()
+    }
+
+    /// Returns true if there are no blocks in the ledger.
+    pub fn is_empty(&self) -> bool {
+        self.get_latest_block().is_err()
+    }
+
+    /// Get the latest block height of the chain.
+    pub fn get_latest_block_height(&self) -> u32 {
+        *self.latest_block_height.read_recursive()
+    }
+
+    /// Get the latest number of blocks in the chain.
+    pub fn get_block_count(&self) -> u32 {
+        *self.latest_block_height.read_recursive() + 1
+    }
+
+    /// Get the size of each storage column and of the block archive, the activity of the storage,
+    /// and the data removed by garbage collections.
+    pub fn get_storage_metrics(&self) -> Result<StorageMetrics, StorageError> {
+        let mut metrics = self.storage.metrics()?;
+        metrics.garbage_collection = self.get_garbage_collection_metrics()?;
+
+        if let Some(block_archive) = &self.block_archive {
+            metrics.archive_size = block_archive.size()?;
+        }
+
+        Ok(metrics)
+    }
+
+    /// Get the stored old connected peers.
+    pub fn get_peer_book(&self) -> Result<Vec<u8>, StorageError> {
+        Ok(self.get(COL_META, &KEY_PEER_BOOK.as_bytes().to_vec())?)
+    }
+
+    /// Store the connected peers.
+    pub fn store_to_peer_book(&self, peers_serialized: Vec<u8>) -> Result<(), StorageError> {
+        let op = Op::Insert {
+            col: COL_META,
+            key: KEY_PEER_BOOK.as_bytes().to_vec(),
+            value: peers_serialized,
+        };
+        self.storage.write(DatabaseTransaction(vec![op]))
+    }
+
+    /// Get the stored jobs of the RPC server.
+    pub fn get_rpc_jobs(&self) -> Result<Vec<u8>, StorageError> {
+        Ok(self.get(COL_META, &KEY_RPC_JOBS.as_bytes().to_vec())?)
+    }
+
+    /// Store the jobs of the RPC server.
+    pub fn store_rpc_jobs(&self, jobs_serialized: Vec<u8>) -> Result<(), StorageError> {
+        let op = Op::Insert {
+            col: COL_META,
+            key: KEY_RPC_JOBS.as_bytes().to_vec(),
+            value: jobs_serialized,
+        };
+        self.storage.write(DatabaseTransaction(vec![op]))
+    }
+
+    /// Get the stored rotations of the API tokens of the RPC server.
+    pub fn get_rpc_token_rotations(&self) -> Result<Vec<u8>, StorageError> {
+        Ok(self.get(COL_META, &KEY_RPC_TOKEN_ROTATIONS.as_bytes().to_vec())?)
+    }
+
+    /// Store the rotations of the API tokens of the RPC server.
+    pub fn store_rpc_token_rotations(&self, rotations_serialized: Vec<u8>) -> Result<(), StorageError> {
+        let op = Op::Insert {
+            col: COL_META,
+            key: KEY_RPC_TOKEN_ROTATIONS.as_bytes().to_vec(),
+            value: rotations_serialized,
+        };
+        self.storage.write(DatabaseTransaction(vec![op]))
+    }
+
+    /// Get the schema version of the storage.
+    pub fn get_schema_version(&self) -> Result<u32, StorageError> {
+        Ok(get_schema_version(&*self.storage)?.unwrap_or(SCHEMA_VERSION))
+    }
 
     /// Attempt to catch the secondary read-only storage instance with the primary instance.
+    ///
+    /// A single thread catches up at a time, and the others keep reading the current state of the
+    /// secondary instance instead of waiting. The merkle tree is loaded before the new state is
+    /// published, so the readers are only blocked while it is swapped in.
     pub fn catch_up_secondary(&self, update_merkle_tree: bool) -> Result<(), StorageError> {
+        let _block_lock = match self.block_lock.try_lock() {
+            Some(block_lock) => block_lock,
+            None => return Ok(()),
+        };
+
         // Sync the secondary and primary instances
-        if self.storage.db.try_catch_up_with_primary().is_ok() {
+        if self.storage.try_catch_up_with_primary().is_ok() {
             let latest_block_height_bytes = self.get(COL_META, &KEY_BEST_BLOCK_NUMBER.as_bytes().to_vec())?;
             let new_latest_block_height = bytes_to_u32(latest_block_height_bytes);
-            let mut latest_block_height = self.latest_block_height.write();
-
-            // If the new block height is greater than the stored block height,
-            // update the block height and merkle tree.
-            if new_latest_block_height > *latest_block_height {
-                // Update the latest block height of the secondary instance.
-                *latest_block_height = new_latest_block_height;
 
+            // If the new block height differs from the stored block height, update the block
+            // height and merkle tree. The height decreases when the primary instance reverts
+            // blocks for a fork.
+            if new_latest_block_height != self.get_latest_block_height() {
                 // Optional `cm_merkle_tree` regeneration because not all usages of
                 // the secondary instance requires it.
-                if update_merkle_tree {
-                    // Update the Merkle tree of the secondary instance.
-                    let mut merkle_tree = self.cm_merkle_tree.write();
-                    *merkle_tree = self.build_merkle_tree(vec![])?;
+                let new_merkle_tree = if update_merkle_tree {
+                    Some(Self::load_commitment_tree(&self.storage, &self.ledger_parameters)?)
+                } else {
+                    None
+                };
+
+                // Update the latest block height and the Merkle tree of the secondary instance.
+                let mut latest_block_height = self.latest_block_height.write();
+                *latest_block_height = new_latest_block_height;
+
+                if let Some(new_merkle_tree) = new_merkle_tree {
+                    *self.cm_merkle_tree.write() = new_merkle_tree;
                 }
             }
         }
@@ -14,14 +14,38 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod backend;
+pub use backend::*;
+
+pub mod block_archive;
+pub use block_archive::*;
+
+pub mod bloom_filter;
+pub use bloom_filter::*;
+
+pub mod config;
+pub use config::*;
+
 pub mod ledger;
 pub use ledger::*;
 
 pub mod key_value;
 pub use key_value::*;
 
+pub mod memory;
+pub use memory::*;
+
+pub mod metrics;
+pub use metrics::*;
+
+pub mod migration;
+pub use migration::*;
+
 pub mod objects;
 pub use objects::*;
 
+pub mod sparse_merkle_tree;
+pub use sparse_merkle_tree::*;
+
 pub mod storage;
 pub use storage::*;
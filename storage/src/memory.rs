@@ -0,0 +1,119 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{column_name, ColumnMetrics, DatabaseTransaction, Op, StorageBackend, StorageIterator, StorageMetrics};
+use snarkos_errors::storage::StorageError;
+
+use parking_lot::RwLock;
+use std::{collections::BTreeMap, path::Path};
+
+/// A storage kept in memory, for tests and applications that do not persist the ledger.
+pub struct MemoryStorage {
+    cols: RwLock<Vec<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    /// Returns a new empty storage with the given number of columns.
+    pub fn new(num_cols: u32) -> Self {
+        Self {
+            cols: RwLock::new(vec![BTreeMap::new(); num_cols as usize]),
+        }
+    }
+
+    /// Returns the entries of a given col, starting at the first key greater than or equal to the given key.
+    /// The entries are copied, so the iterator is not affected by later writes.
+    fn iter_from(&self, col: u32, key: &[u8]) -> Result<StorageIterator, StorageError> {
+        let cols = self.cols.read();
+        let entries = cols
+            .get(col as usize)
+            .ok_or(StorageError::InvalidColumnFamily(col))?
+            .range(key.to_vec()..)
+            .map(|(key, value)| (key.clone().into_boxed_slice(), value.clone().into_boxed_slice()))
+            .collect::<Vec<_>>();
+
+        Ok(Box::new(entries.into_iter()))
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    /// Returns a new empty storage, as nothing is stored at the given path.
+    fn open<P: AsRef<Path>>(_path: P, num_cols: u32) -> Result<Self, StorageError> {
+        Ok(Self::new(num_cols))
+    }
+
+    fn get(&self, col: u32, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        match self.cols.read().get(col as usize) {
+            Some(entries) => Ok(entries.get(key).cloned()),
+            None => Err(StorageError::InvalidColumnFamily(col)),
+        }
+    }
+
+    fn get_iter(&self, col: u32) -> Result<StorageIterator, StorageError> {
+        self.iter_from(col, &[])
+    }
+
+    fn get_iter_from(&self, col: u32, key: &[u8]) -> Result<StorageIterator, StorageError> {
+        self.iter_from(col, key)
+    }
+
+    fn write(&self, transaction: DatabaseTransaction) -> Result<(), StorageError> {
+        let mut cols = self.cols.write();
+
+        // Check every column before applying any operation, so the transaction is atomic
+        if let Some(operation) = transaction
+            .0
+            .iter()
+            .find(|operation| operation.col() as usize >= cols.len())
+        {
+            return Err(StorageError::InvalidColumnFamily(operation.col()));
+        }
+
+        for operation in transaction.0 {
+            match operation {
+                Op::Insert { col, key, value } => {
+                    cols[col as usize].insert(key, value);
+                }
+                Op::Delete { col, key } => {
+                    cols[col as usize].remove(&key);
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Returns the size of each column in memory. Nothing is stored on disk, and no statistics are kept.
+    fn metrics(&self) -> Result<StorageMetrics, StorageError> {
+        let columns = self
+            .cols
+            .read()
+            .iter()
+            .enumerate()
+            .map(|(col, entries)| ColumnMetrics {
+                col: col as u32,
+                name: column_name(col as u32).to_string(),
+                memory_size: entries
+                    .iter()
+                    .map(|(key, value)| (key.len() + value.len()) as u64)
+                    .sum(),
+                estimated_keys: entries.len() as u64,
+                ..Default::default()
+            })
+            .collect();
+
+        Ok(StorageMetrics::new(columns, None))
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The statistics counter of the bytes written to the storage.
+const BYTES_WRITTEN: &str = "rocksdb.bytes.written";
+/// The statistics counter of the bytes written to disk by flushes.
+const FLUSH_WRITE_BYTES: &str = "rocksdb.flush.write.bytes";
+/// The statistics counter of the bytes written to disk by compactions.
+const COMPACT_WRITE_BYTES: &str = "rocksdb.compact.write.bytes";
+/// The statistics counter of the block reads served by the block caches.
+const BLOCK_CACHE_HIT: &str = "rocksdb.block.cache.hit";
+/// The statistics counter of the block reads not served by the block caches.
+const BLOCK_CACHE_MISS: &str = "rocksdb.block.cache.miss";
+
+/// The size of a column of the storage.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnMetrics {
+    /// Index of the column
+    pub col: u32,
+    /// Name of the column
+    pub name: String,
+    /// Size of the column on disk, in bytes
+    pub disk_size: u64,
+    /// Size of the column values not written to disk yet, in bytes
+    pub memory_size: u64,
+    /// Size of the column values held in the block cache, in bytes
+    pub cache_size: u64,
+    /// Estimated number of keys in the column
+    pub estimated_keys: u64,
+}
+
+/// The data removed by the garbage collections of the storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GarbageCollectionMetrics {
+    /// Number of abandoned side chain and orphan blocks removed
+    pub removed_blocks: u64,
+    /// Number of obsolete transaction location and ledger digest entries removed
+    pub removed_index_entries: u64,
+    /// Number of stored memory pool transactions removed as they conflict with the ledger
+    pub removed_memory_pool_transactions: u64,
+    /// Size of the keys and values removed, in bytes
+    pub reclaimed_size: u64,
+}
+
+impl GarbageCollectionMetrics {
+    /// Returns true if no data was removed.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Add the data removed by another garbage collection.
+    pub fn add(&mut self, other: &Self) {
+        self.removed_blocks += other.removed_blocks;
+        self.removed_index_entries += other.removed_index_entries;
+        self.removed_memory_pool_transactions += other.removed_memory_pool_transactions;
+        self.reclaimed_size += other.reclaimed_size;
+    }
+}
+
+/// The size and the activity of the storage.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageMetrics {
+    /// Size of each column
+    pub columns: Vec<ColumnMetrics>,
+    /// Size of the columns on disk, in bytes
+    pub disk_size: u64,
+    /// Size of the block archive files, in bytes
+    pub archive_size: u64,
+    /// Totals of the data removed by garbage collections
+    pub garbage_collection: GarbageCollectionMetrics,
+    /// Ratio of the block reads served by the block caches, if the storage keeps statistics
+    pub cache_hit_rate: Option<f64>,
+    /// Ratio of the bytes written to disk by flushes and compactions to the bytes written to
+    /// the storage, if the storage keeps statistics
+    pub write_amplification: Option<f64>,
+}
+
+impl StorageMetrics {
+    /// Returns the metrics of the given columns, with the rates computed from the
+    /// statistics dump of the storage, if any.
+    pub fn new(columns: Vec<ColumnMetrics>, statistics: Option<&str>) -> Self {
+        let disk_size = columns.iter().map(|column| column.disk_size).sum();
+        let counters = statistics.map(parse_statistics_counters).unwrap_or_default();
+
+        let ratio = |numerator: u64, denominator: u64| match denominator {
+            0 => None,
+            _ => Some(numerator as f64 / denominator as f64),
+        };
+        let counter = |name: &str| counters.get(name).copied().unwrap_or(0);
+
+        let cache_hits = counter(BLOCK_CACHE_HIT);
+        let cache_hit_rate = ratio(cache_hits, cache_hits + counter(BLOCK_CACHE_MISS));
+        let write_amplification = ratio(
+            counter(FLUSH_WRITE_BYTES) + counter(COMPACT_WRITE_BYTES),
+            counter(BYTES_WRITTEN),
+        );
+
+        Self {
+            columns,
+            disk_size,
+            archive_size: 0,
+            garbage_collection: GarbageCollectionMetrics::default(),
+            cache_hit_rate,
+            write_amplification,
+        }
+    }
+}
+
+/// Returns the counters of a RocksDB statistics dump, written as `<name> COUNT : <value>` lines.
+pub fn parse_statistics_counters(statistics: &str) -> HashMap<&str, u64> {
+    statistics
+        .lines()
+        .filter_map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [name, "COUNT", ":", value] => value.parse().ok().map(|value| (name, value)),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,237 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_utilities::bytes::FromBytes;
+
+use rocksdb::checkpoint::Checkpoint;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The current version of the storage schema.
+pub const SCHEMA_VERSION: u32 = 10;
+
+/// A migration upgrading the storage from one schema version to the next.
+/// The migration returns its writes, which are committed together with the next schema version.
+pub type Migration = fn(&Storage) -> Result<DatabaseTransaction, StorageError>;
+
+/// The migrations to the current schema version, indexed by the schema version they upgrade from.
+pub const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+    migrate_v9_to_v10,
+];
+
+/// Version 0 is the unversioned storage layout, which version 1 only extends with the schema version.
+fn migrate_v0_to_v1(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 2 adds the block and transaction height indexes, which are built from the canon chain.
+fn migrate_v1_to_v2(storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    let mut database_transaction = DatabaseTransaction::new();
+    let mut canon_block_numbers = HashMap::new();
+
+    // The block locator maps canon block numbers to block hashes and canon block hashes to block numbers
+
+    for (key, value) in storage.get_iter(COL_BLOCK_LOCATOR)? {
+        if key.len() == 4 {
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_HEIGHT_INDEX,
+                key: block_height_index_key(bytes_to_u32(key.to_vec())),
+                value: value.to_vec(),
+            });
+        } else {
+            canon_block_numbers.insert(key.to_vec(), bytes_to_u32(value.to_vec()));
+        }
+    }
+
+    // Transaction locations also cover the transactions of side chain blocks, which are skipped
+
+    for (transaction_id, transaction_location_bytes) in storage.get_iter(COL_TRANSACTION_LOCATION)? {
+        let transaction_location = TransactionLocation::read(&transaction_location_bytes[..])?;
+
+        if let Some(block_number) = canon_block_numbers.get(&transaction_location.block_hash[..]) {
+            database_transaction.push(Op::Insert {
+                col: COL_TRANSACTION_HEIGHT_INDEX,
+                key: transaction_height_index_key(*block_number, transaction_location.index),
+                value: transaction_id.to_vec(),
+            });
+        }
+    }
+
+    Ok(database_transaction)
+}
+
+/// Version 3 adds the commitment merkle tree nodes, which are built from the commitments when the ledger is loaded.
+fn migrate_v2_to_v3(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 4 adds the ledger checkpoints, which are built from the canon blocks when the ledger is loaded.
+fn migrate_v3_to_v4(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 5 adds the block undo records, which are stored as blocks are committed.
+/// Blocks committed before are decommitted from their transactions.
+fn migrate_v4_to_v5(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 6 adds the block archive index, which is filled as block transactions are archived.
+fn migrate_v5_to_v6(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 7 adds the serial number tree, which is built from the serial numbers.
+fn migrate_v6_to_v7(storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    let mut update = SparseMerkleTreeUpdate::new(storage, COL_SERIAL_NUMBER_TREE);
+    for (sn, _) in storage.get_iter(COL_SERIAL_NUMBER)? {
+        update.insert(&sn)?;
+    }
+
+    Ok(DatabaseTransaction(update.into_ops()))
+}
+
+/// Version 8 adds the memo and commitment search indexes, which are built from the canon blocks when the ledger is loaded.
+fn migrate_v7_to_v8(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 9 adds the wallet accounts, which are empty until account view keys are registered with the wallet.
+fn migrate_v8_to_v9(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Version 10 adds the transactions sent by the node, which are empty until a transaction is sent.
+fn migrate_v9_to_v10(_storage: &Storage) -> Result<DatabaseTransaction, StorageError> {
+    Ok(DatabaseTransaction::new())
+}
+
+/// Returns the schema version of the storage, or `None` if the storage is new.
+pub fn get_schema_version<S: StorageBackend>(storage: &S) -> Result<Option<u32>, StorageError> {
+    match storage.get(COL_META, KEY_SCHEMA_VERSION.as_bytes())? {
+        Some(schema_version_bytes) => Ok(Some(bytes_to_u32(schema_version_bytes))),
+        None => match storage.get(COL_META, KEY_BEST_BLOCK_NUMBER.as_bytes())? {
+            Some(_) => Ok(Some(0)),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Returns the database operation that sets the schema version of the storage.
+pub fn set_schema_version(schema_version: u32) -> Op {
+    Op::Insert {
+        col: COL_META,
+        key: KEY_SCHEMA_VERSION.as_bytes().to_vec(),
+        value: schema_version.to_le_bytes().to_vec(),
+    }
+}
+
+/// Returns the path of the backup made before migrating the storage at a given path.
+pub fn migration_backup_path<PATH: AsRef<Path>>(path: PATH) -> PathBuf {
+    let mut backup_path_os_string = path.as_ref().to_path_buf().into_os_string();
+    backup_path_os_string.push("_backup");
+
+    PathBuf::from(backup_path_os_string)
+}
+
+/// Recover the storage at a given path from the backup left by an interrupted migration.
+///
+/// The backup is only stale if the migration completed before the backup was removed,
+/// otherwise the storage may be partially migrated and is replaced by the backup.
+fn recover_interrupted_migration(path: &Path, backup_path: &Path) -> Result<(), StorageError> {
+    let migrated = path.exists()
+        && Storage::open_cf(path, NUM_COLS)
+            .and_then(|storage| get_schema_version(&storage))
+            .map(|schema_version| schema_version == Some(SCHEMA_VERSION))
+            .unwrap_or(false);
+
+    if migrated {
+        fs::remove_dir_all(backup_path)?;
+    } else {
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+        fs::rename(backup_path, path)?;
+    }
+
+    Ok(())
+}
+
+/// Upgrade the storage at a given path to the current schema version.
+///
+/// A checkpoint of the storage is taken before migrating. If a migration fails, the storage
+/// is rolled back to the checkpoint, otherwise the checkpoint is removed. If the node stopped
+/// during a previous migration, the storage is first recovered from the checkpoint it left.
+pub fn migrate_storage<PATH: AsRef<Path>>(path: PATH) -> Result<(), StorageError> {
+    let path = path.as_ref();
+
+    let backup_path = migration_backup_path(path);
+    if backup_path.exists() {
+        recover_interrupted_migration(path, &backup_path)?;
+    }
+
+    let storage = Storage::open_cf(path, NUM_COLS)?;
+
+    let schema_version = match get_schema_version(&storage)? {
+        Some(schema_version) => schema_version,
+        None => return Ok(()),
+    };
+
+    if schema_version > SCHEMA_VERSION {
+        return Err(StorageError::InvalidSchemaVersion(schema_version, SCHEMA_VERSION));
+    }
+
+    if schema_version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    Checkpoint::new(&storage.db)?.create_checkpoint(&backup_path)?;
+
+    let result = (schema_version..SCHEMA_VERSION).try_for_each(|version| {
+        let mut database_transaction = MIGRATIONS[version as usize](&storage)?;
+        database_transaction.push(set_schema_version(version + 1));
+
+        storage.write(database_transaction)
+    });
+
+    drop(storage);
+
+    match result {
+        Ok(()) => {
+            fs::remove_dir_all(&backup_path)?;
+            Ok(())
+        }
+        Err(error) => {
+            fs::remove_dir_all(path)?;
+            fs::rename(&backup_path, path)?;
+            Err(error)
+        }
+    }
+}
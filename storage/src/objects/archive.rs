@@ -0,0 +1,116 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::BlockHeaderHash;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the height of the latest block whose transactions have been moved to the block archive.
+    /// Returns 0 if no blocks have been archived, as the genesis block is never archived.
+    pub fn get_archived_block_height(&self) -> Result<u32, StorageError> {
+        match self.storage.get(COL_META, KEY_ARCHIVED_BLOCK_NUMBER.as_bytes())? {
+            Some(block_num_bytes) => Ok(bytes_to_u32(block_num_bytes)),
+            None => Ok(0),
+        }
+    }
+
+    /// Get the location of the archived transactions of a block, if they have been archived.
+    pub fn get_archive_location(&self, block_hash: &BlockHeaderHash) -> Result<Option<ArchiveLocation>, StorageError> {
+        match self.storage.get(COL_BLOCK_ARCHIVE, &block_hash.0)? {
+            Some(location_bytes) => Ok(Some(ArchiveLocation::read(&location_bytes[..])?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the serialized transactions of a block from the block archive, if they have been archived.
+    pub(crate) fn get_archived_block_transactions(
+        &self,
+        block_hash: &BlockHeaderHash,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        match (&self.block_archive, self.get_archive_location(block_hash)?) {
+            (Some(block_archive), Some(location)) => Ok(Some(block_archive.read(&location)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Move the transactions of canon blocks older than the latest `retention` blocks from the
+    /// key-value storage to the block archive, and return the number of blocks archived.
+    ///
+    /// Archived transactions are read back transparently, but are no longer rewritten by the
+    /// compactions of the key-value storage. Blocks are not archived if the ledger has no archive.
+    pub fn archive_blocks(&self, retention: u32) -> Result<u32, StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let block_archive = match &self.block_archive {
+            Some(block_archive) => block_archive,
+            None => return Ok(0),
+        };
+
+        let latest_block_height = self.get_latest_block_height();
+        let archived_block_height = self.get_archived_block_height()?.max(self.get_pruned_block_height()?);
+
+        if latest_block_height <= retention || latest_block_height - retention <= archived_block_height {
+            return Ok(0);
+        }
+
+        let new_archived_block_height = latest_block_height - retention;
+
+        let mut block_hashes = vec![];
+        let mut block_transactions = vec![];
+
+        for block_number in (archived_block_height + 1)..=new_archived_block_height {
+            let block_hash = self.get_block_hash(block_number)?;
+
+            if let Some(transactions_bytes) = self.storage.get(COL_BLOCK_TRANSACTIONS, &block_hash.0)? {
+                block_hashes.push(block_hash);
+                block_transactions.push(transactions_bytes);
+            }
+        }
+
+        // The transactions are synced to the archive before they are indexed and removed from the storage
+        let locations = block_archive.append(&block_transactions)?;
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for (block_hash, location) in block_hashes.iter().zip(locations) {
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_ARCHIVE,
+                key: block_hash.0.to_vec(),
+                value: to_bytes![location]?.to_vec(),
+            });
+            database_transaction.push(Op::Delete {
+                col: COL_BLOCK_TRANSACTIONS,
+                key: block_hash.0.to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_ARCHIVED_BLOCK_NUMBER.as_bytes().to_vec(),
+            value: new_archived_block_height.to_le_bytes().to_vec(),
+        });
+
+        self.storage.write(database_transaction)?;
+
+        Ok(new_archived_block_height - archived_block_height)
+    }
+}
@@ -20,7 +20,7 @@ use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction}
 use snarkos_objects::{Block, BlockHeaderHash, DPCTransactions};
 use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Get the latest block in the chain.
     pub fn get_latest_block(&self) -> Result<Block<T>, StorageError> {
         self.get_block_from_block_number(self.get_latest_block_height())
@@ -62,11 +62,23 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     }
 
     /// Get the list of transaction ids given a block hash.
+    /// The transactions are read from the block archive if they have been archived,
+    /// and are missing with a `PrunedBlockTransactions` error if they have been pruned.
     pub fn get_block_transactions(&self, block_hash: &BlockHeaderHash) -> Result<DPCTransactions<T>, StorageError> {
-        match self.storage.get(COL_BLOCK_TRANSACTIONS, &block_hash.0)? {
-            Some(encoded_block_transactions) => Ok(DPCTransactions::read(&encoded_block_transactions[..])?),
-            None => Err(StorageError::MissingBlockTransactions(block_hash.to_string())),
-        }
+        let encoded_block_transactions = match self.storage.get(COL_BLOCK_TRANSACTIONS, &block_hash.0)? {
+            Some(encoded_block_transactions) => encoded_block_transactions,
+            None => match self.get_archived_block_transactions(block_hash)? {
+                Some(encoded_block_transactions) => encoded_block_transactions,
+                None => match self.get_block_number(block_hash) {
+                    Ok(block_number) if block_number != 0 && block_number <= self.get_pruned_block_height()? => {
+                        return Err(StorageError::PrunedBlockTransactions(block_hash.to_string()));
+                    }
+                    _ => return Err(StorageError::MissingBlockTransactions(block_hash.to_string())),
+                },
+            },
+        };
+
+        Ok(DPCTransactions::read(&encoded_block_transactions[..])?)
     }
 
     /// Find the potential child block hashes given a parent block header.
@@ -99,6 +111,8 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
 
     /// Remove a block and it's related data from the storage.
     pub fn remove_block(&self, block_hash: BlockHeaderHash) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         if self.is_canon(&block_hash) {
             return Err(StorageError::InvalidBlockRemovalCanon(block_hash.to_string()));
         }
@@ -111,6 +125,10 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             col: COL_BLOCK_TRANSACTIONS,
             key: block_hash.0.to_vec(),
         });
+        database_transaction.push(Op::Delete {
+            col: COL_BLOCK_ARCHIVE,
+            key: block_hash.0.to_vec(),
+        });
 
         for transaction in self.get_block_transactions(&block_hash)?.0 {
             database_transaction.push(Op::Delete {
@@ -145,7 +163,10 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     }
 
     /// De-commit the latest block and return its header hash.
+    /// The ledger state is reverted from the undo record of the block, in a single batch.
     pub fn decommit_latest_block(&self) -> Result<BlockHeaderHash, StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         let latest_block_height = self.get_latest_block_height();
         if latest_block_height == 0 {
             return Err(StorageError::InvalidBlockDecommit);
@@ -167,52 +188,84 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             key: self.current_digest()?,
         });
 
-        let mut sn_index = self.current_sn_index()?;
-        let mut cm_index = self.current_cm_index()?;
-        let mut memo_index = self.current_memo_index()?;
+        // Revert the changes recorded in the undo record of the block
 
-        let mut database_transaction = DatabaseTransaction::new();
+        let block_undo = self.latest_block_undo(&block_hash)?;
 
-        for transaction in self.get_block_transactions(&block_hash)?.0 {
-            for sn in transaction.old_serial_numbers() {
-                database_transaction.push(Op::Delete {
-                    col: COL_SERIAL_NUMBER,
-                    key: to_bytes![sn]?.to_vec(),
-                });
-                sn_index -= 1;
-            }
+        for sn in &block_undo.serial_numbers {
+            database_transaction.push(Op::Delete {
+                col: COL_SERIAL_NUMBER,
+                key: sn.clone(),
+            });
+        }
 
-            for cm in transaction.new_commitments() {
-                database_transaction.push(Op::Delete {
-                    col: COL_COMMITMENT,
-                    key: to_bytes![cm]?.to_vec(),
-                });
-                cm_index -= 1;
-            }
+        database_transaction.push_vec(self.serial_number_tree_ops(&block_undo.serial_numbers, false)?);
 
+        let mut removed_commitments = Vec::with_capacity(block_undo.commitments.len());
+        for cm in &block_undo.commitments {
+            database_transaction.push(Op::Delete {
+                col: COL_COMMITMENT,
+                key: cm.clone(),
+            });
+            removed_commitments.push(T::Commitment::read(&cm[..])?);
+        }
+
+        for memo in &block_undo.memos {
             database_transaction.push(Op::Delete {
                 col: COL_MEMO,
-                key: to_bytes![transaction.memorandum()]?.to_vec(),
+                key: memo.clone(),
             });
-            memo_index -= 1;
         }
 
+        database_transaction.push(Op::Delete {
+            col: COL_BLOCK_UNDO,
+            key: block_undo_key(latest_block_height),
+        });
+
+        // Remove the block from the height indexes
+
+        database_transaction.push(Op::Delete {
+            col: COL_BLOCK_HEIGHT_INDEX,
+            key: block_height_index_key(latest_block_height),
+        });
+        database_transaction
+            .push_vec(self.unindex_transaction_height_ops(latest_block_height, block_undo.num_transactions as usize));
+
+        // Remove the memos and commitments of the block from the search indexes
+
+        database_transaction.push_vec(Self::unindex_transaction_search_ops(&block_undo));
+
+        // Remove the ledger checkpoint of the block
+
+        database_transaction.push(Op::Delete {
+            col: COL_LEDGER_CHECKPOINT,
+            key: ledger_checkpoint_key(latest_block_height),
+        });
+
+        // Remove the records indexed for the block
+
+        database_transaction.push_vec(self.unindex_records_ops(latest_block_height)?);
+
+        // Remove the wallet records and spends found in the block
+
+        database_transaction.push_vec(self.unwind_wallet_accounts_ops(latest_block_height)?);
+
         // Update the database state for current indexes
 
         database_transaction.push(Op::Insert {
             col: COL_META,
             key: KEY_CURR_SN_INDEX.as_bytes().to_vec(),
-            value: (sn_index as u32).to_le_bytes().to_vec(),
+            value: block_undo.sn_index.to_le_bytes().to_vec(),
         });
         database_transaction.push(Op::Insert {
             col: COL_META,
             key: KEY_CURR_CM_INDEX.as_bytes().to_vec(),
-            value: (cm_index as u32).to_le_bytes().to_vec(),
+            value: block_undo.cm_index.to_le_bytes().to_vec(),
         });
         database_transaction.push(Op::Insert {
             col: COL_META,
             key: KEY_CURR_MEMO_INDEX.as_bytes().to_vec(),
-            value: (memo_index as u32).to_le_bytes().to_vec(),
+            value: block_undo.memo_index.to_le_bytes().to_vec(),
         });
 
         database_transaction.push(Op::Delete {
@@ -225,12 +278,47 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             key: block_hash.0.to_vec(),
         });
 
-        self.storage.write(database_transaction)?;
+        // Move the consistency marker back to the new latest block if it marks the decommitted block
+
+        if let Some(marker) = self.get_consistency_marker()? {
+            if marker.block_height > update_best_block_num {
+                database_transaction.push(self.consistency_marker_op(update_best_block_num, false)?);
+            }
+        }
+
+        // Remove the commitments from the commitment merkle tree
 
-        let mut latest_block_height = self.latest_block_height.write();
-        *latest_block_height -= 1;
+        let mut height = self.latest_block_height.write();
+        let mut cm_merkle_tree = self.cm_merkle_tree.write();
+        let previous_level_sizes = cm_merkle_tree.levels().iter().map(Vec::len).collect::<Vec<_>>();
 
-        self.update_merkle_tree()?;
+        let updated_nodes = cm_merkle_tree.truncate(block_undo.cm_index as usize)?;
+        database_transaction.push_vec(Self::commitment_tree_ops(
+            &cm_merkle_tree,
+            &previous_level_sizes,
+            &updated_nodes,
+        )?);
+
+        // Restore the digest of the new latest block, as it is removed above when the block left it unchanged
+        let digest = to_bytes![cm_merkle_tree.root()]?.to_vec();
+        database_transaction.push(Op::Insert {
+            col: COL_DIGEST,
+            key: digest.clone(),
+            value: update_best_block_num.to_le_bytes().to_vec(),
+        });
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_CURR_DIGEST.as_bytes().to_vec(),
+            value: digest,
+        });
+
+        // Restore the removed commitments if the block could not be decommitted
+        if let Err(error) = self.storage.write(database_transaction) {
+            cm_merkle_tree.append(&removed_commitments)?;
+            return Err(error);
+        }
+
+        *height -= 1;
 
         Ok(block_hash)
     }
@@ -243,6 +331,8 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
 
     /// Remove the latest `num_blocks` blocks.
     pub fn remove_latest_blocks(&self, num_blocks: u32) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         let latest_block_height = self.get_latest_block_height();
         if num_blocks > latest_block_height {
             return Err(StorageError::InvalidBlockRemovalNum(num_blocks, latest_block_height));
@@ -0,0 +1,222 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::crh::double_sha256;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::{Block, BlockHeaderHash};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::{
+    io::{Read, Write},
+    marker::PhantomData,
+};
+
+/// The magic bytes at the start of a block export.
+pub const BLOCK_EXPORT_MAGIC: [u8; 8] = *b"ALEOBLKS";
+
+/// The current version of the block export format.
+pub const BLOCK_EXPORT_VERSION: u32 = 1;
+
+/// The header of a block export.
+///
+/// A block export is a flat file of the canon chain that starts with the magic bytes and the
+/// header, followed by one record per block from the genesis block onwards. Each record holds
+/// the length of the serialized block, a checksum of the serialized block, and the serialized block.
+/// All integers are little-endian, so exporting the same chain always gives the same bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockExportHeader {
+    /// The version of the block export format
+    pub version: u32,
+    /// The number of blocks in the export
+    pub block_count: u32,
+    /// The hash of the genesis block
+    pub genesis_hash: BlockHeaderHash,
+}
+
+impl BlockExportHeader {
+    fn write<W: Write>(&self, mut writer: W) -> Result<(), StorageError> {
+        writer.write_all(&BLOCK_EXPORT_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&self.block_count.to_le_bytes())?;
+        writer.write_all(&self.genesis_hash.0)?;
+
+        Ok(())
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self, StorageError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != BLOCK_EXPORT_MAGIC {
+            return Err(StorageError::InvalidBlockExport("invalid magic bytes".into()));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != BLOCK_EXPORT_VERSION {
+            return Err(StorageError::InvalidBlockExportVersion(version));
+        }
+
+        let block_count = read_u32(&mut reader)?;
+
+        let mut genesis_hash = [0u8; 32];
+        reader.read_exact(&mut genesis_hash)?;
+
+        Ok(Self {
+            version,
+            block_count,
+            genesis_hash: BlockHeaderHash(genesis_hash),
+        })
+    }
+}
+
+/// Reads the blocks of a block export in order.
+///
+/// The reader verifies the checksum of each block and that the blocks form a chain from the
+/// genesis block of the header. It does not verify the contents of the blocks, which is left
+/// to the consensus rules of the importing node.
+pub struct BlockExportReader<R: Read, T: Transaction> {
+    reader: R,
+    header: BlockExportHeader,
+    block_number: u32,
+    previous_block_hash: Option<BlockHeaderHash>,
+    _transaction: PhantomData<T>,
+}
+
+impl<R: Read, T: Transaction> BlockExportReader<R, T> {
+    /// Read the header of a block export.
+    pub fn new(mut reader: R) -> Result<Self, StorageError> {
+        let header = BlockExportHeader::read(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            header,
+            block_number: 0,
+            previous_block_hash: None,
+            _transaction: PhantomData,
+        })
+    }
+
+    /// Returns the header of the block export.
+    pub fn header(&self) -> &BlockExportHeader {
+        &self.header
+    }
+
+    fn read_block(&mut self) -> Result<Block<T>, StorageError> {
+        let block_length = read_u32(&mut self.reader)?;
+
+        let mut checksum = [0u8; 32];
+        self.reader.read_exact(&mut checksum)?;
+
+        // The block is read incrementally, so a corrupted length does not allocate the full length upfront.
+        let mut block_bytes = vec![];
+        (&mut self.reader)
+            .take(block_length as u64)
+            .read_to_end(&mut block_bytes)?;
+        if block_bytes.len() != block_length as usize {
+            return Err(StorageError::InvalidBlockExport(format!(
+                "truncated block {}",
+                self.block_number
+            )));
+        }
+
+        if checksum != double_sha256(&block_bytes) {
+            return Err(StorageError::InvalidBlockExport(format!(
+                "invalid checksum of block {}",
+                self.block_number
+            )));
+        }
+
+        let block = Block::<T>::read(&block_bytes[..])?;
+        let block_hash = block.header.get_hash();
+
+        let is_chained = match &self.previous_block_hash {
+            None => block_hash == self.header.genesis_hash,
+            Some(previous_block_hash) => &block.header.previous_block_hash == previous_block_hash,
+        };
+        if !is_chained {
+            return Err(StorageError::InvalidBlockExport(format!(
+                "block {} does not extend the exported chain",
+                self.block_number
+            )));
+        }
+
+        self.block_number += 1;
+        self.previous_block_hash = Some(block_hash);
+
+        Ok(block)
+    }
+}
+
+impl<R: Read, T: Transaction> Iterator for BlockExportReader<R, T> {
+    type Item = Result<Block<T>, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.block_number >= self.header.block_count {
+            return None;
+        }
+
+        let result = self.read_block();
+
+        // Stop at the first invalid block, as the following blocks can not be chained.
+        if result.is_err() {
+            self.block_number = self.header.block_count;
+        }
+
+        Some(result)
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Write the canon chain to a block export, and return the number of exported blocks.
+    ///
+    /// The transactions of every block are needed, so a pruned ledger can not be exported.
+    pub fn export_blocks<W: Write>(&self, mut writer: W) -> Result<u32, StorageError> {
+        // The canon chain can not change while it is exported.
+        let _block_lock = self.block_lock.lock();
+        let block_count = self.get_block_count();
+
+        let header = BlockExportHeader {
+            version: BLOCK_EXPORT_VERSION,
+            block_count,
+            genesis_hash: self.get_block_hash(0)?,
+        };
+        header.write(&mut writer)?;
+
+        for block_number in 0..block_count {
+            let block = self.get_block(&self.get_block_hash(block_number)?)?;
+            let block_bytes = to_bytes![block]?;
+
+            writer.write_all(&(block_bytes.len() as u32).to_le_bytes())?;
+            writer.write_all(&double_sha256(&block_bytes))?;
+            writer.write_all(&block_bytes)?;
+        }
+
+        writer.flush()?;
+
+        Ok(block_count)
+    }
+}
+
+fn read_u32<R: Read>(mut reader: R) -> Result<u32, StorageError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
@@ -14,12 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::Ledger;
+use crate::{Ledger, StorageBackend};
 use snarkos_errors::{objects::BlockError, storage::StorageError};
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
 use snarkos_objects::{BlockHeader, BlockHeaderHash};
 
-const OLDEST_FORK_THRESHOLD: u32 = 1024;
+/// The maximum number of side chain blocks the node walks back to find a fork, the deepest fork it can switch to.
+pub const OLDEST_FORK_THRESHOLD: u32 = 1024;
 
 #[derive(Clone, Debug)]
 pub enum BlockPath {
@@ -40,7 +41,7 @@ pub struct SideChainPath {
     pub path: Vec<BlockHeaderHash>,
 }
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Get the block's path/origin.
     pub fn get_block_path(&self, block_header: &BlockHeader) -> Result<BlockPath, StorageError> {
         let block_hash = block_header.get_hash();
@@ -0,0 +1,188 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::{BlockHeaderHash, DPCTransactions};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use serde::{Deserialize, Serialize};
+
+/// The changes made to the ledger state by committing a canon block,
+/// which are reverted when the block is decommitted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockUndo {
+    /// Number of transactions in the block
+    pub num_transactions: u32,
+    /// Serial numbers spent by the block
+    pub serial_numbers: Vec<Vec<u8>>,
+    /// Commitments created by the block, in commitment index order
+    pub commitments: Vec<Vec<u8>>,
+    /// Memos of the block transactions
+    pub memos: Vec<Vec<u8>>,
+    /// Serial number index before the block
+    pub sn_index: u32,
+    /// Commitment index before the block
+    pub cm_index: u32,
+    /// Memo index before the block
+    pub memo_index: u32,
+}
+
+impl BlockUndo {
+    /// Returns the undo record of the given block transactions committed at the given indexes.
+    pub fn new<T: Transaction>(
+        transactions: &DPCTransactions<T>,
+        sn_index: usize,
+        cm_index: usize,
+        memo_index: usize,
+    ) -> Result<Self, StorageError> {
+        let mut serial_numbers = vec![];
+        let mut commitments = vec![];
+        let mut memos = Vec::with_capacity(transactions.len());
+
+        for transaction in transactions.iter() {
+            for sn in transaction.old_serial_numbers() {
+                serial_numbers.push(to_bytes![sn]?);
+            }
+            for cm in transaction.new_commitments() {
+                commitments.push(to_bytes![cm]?);
+            }
+            memos.push(to_bytes![transaction.memorandum()]?);
+        }
+
+        Ok(Self {
+            num_transactions: transactions.len() as u32,
+            serial_numbers,
+            commitments,
+            memos,
+            sn_index: sn_index as u32,
+            cm_index: cm_index as u32,
+            memo_index: memo_index as u32,
+        })
+    }
+}
+
+/// A fork to a side chain that has reverted canon blocks, and has not been completed yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterruptedFork {
+    /// Latest block number shared by the canon chain and the side chain
+    pub shared_block_number: u32,
+    /// Hashes of the canon blocks reverted by the fork (oldest first)
+    pub canon_path: Vec<BlockHeaderHash>,
+}
+
+/// Returns the key of a block number in the block undo column.
+pub fn block_undo_key(block_number: u32) -> Vec<u8> {
+    block_number.to_be_bytes().to_vec()
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the undo record of a canon block.
+    /// Returns `None` for blocks committed before undo records were stored.
+    pub fn get_block_undo(&self, block_number: u32) -> Result<Option<BlockUndo>, StorageError> {
+        match self.storage.get(COL_BLOCK_UNDO, &block_undo_key(block_number))? {
+            Some(block_undo_bytes) => Ok(Some(bincode::deserialize(&block_undo_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the operation that stores the undo record of a canon block.
+    pub(crate) fn block_undo_op(block_number: u32, block_undo: &BlockUndo) -> Result<Op, StorageError> {
+        Ok(Op::Insert {
+            col: COL_BLOCK_UNDO,
+            key: block_undo_key(block_number),
+            value: bincode::serialize(block_undo)?,
+        })
+    }
+
+    /// Returns the undo record of the latest canon block, rebuilt from its transactions
+    /// if it was committed before undo records were stored.
+    pub(crate) fn latest_block_undo(&self, block_hash: &BlockHeaderHash) -> Result<BlockUndo, StorageError> {
+        if let Some(block_undo) = self.get_block_undo(self.get_latest_block_height())? {
+            return Ok(block_undo);
+        }
+
+        let transactions = self.get_block_transactions(block_hash)?;
+        let mut block_undo = BlockUndo::new(&transactions, 0, 0, 0)?;
+
+        block_undo.sn_index = (self.current_sn_index()? - block_undo.serial_numbers.len()) as u32;
+        block_undo.cm_index = (self.current_cm_index()? - block_undo.commitments.len()) as u32;
+        block_undo.memo_index = (self.current_memo_index()? - block_undo.memos.len()) as u32;
+
+        Ok(block_undo)
+    }
+
+    /// Get the fork that has reverted canon blocks and has not been completed yet, if any.
+    pub fn get_interrupted_fork(&self) -> Result<Option<InterruptedFork>, StorageError> {
+        match self.storage.get(COL_META, KEY_INTERRUPTED_FORK.as_bytes())? {
+            Some(fork_bytes) => Ok(Some(bincode::deserialize(&fork_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Mark the fork started by `revert_for_fork` as completed, once the side chain blocks
+    /// have been committed.
+    pub fn complete_fork(&self) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        self.storage.write(DatabaseTransaction(vec![Op::Delete {
+            col: COL_META,
+            key: KEY_INTERRUPTED_FORK.as_bytes().to_vec(),
+        }]))
+    }
+
+    /// Roll back a fork that has not been completed, by decommitting the side chain blocks
+    /// and recommitting the canon blocks the fork reverted.
+    ///
+    /// The reverted canon blocks are kept in the storage and were validated when they were
+    /// first committed, so the ledger returns to its state before the fork.
+    pub fn rollback_fork(&self) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let fork = match self.get_interrupted_fork()? {
+            Some(fork) => fork,
+            None => return Ok(()),
+        };
+
+        while self.get_latest_block_height() > fork.shared_block_number {
+            self.decommit_latest_block()?;
+        }
+
+        for block_hash in &fork.canon_path {
+            self.commit(block_hash)?;
+        }
+
+        self.complete_fork()
+    }
+
+    /// Recover from a fork that was interrupted by a crash.
+    ///
+    /// If the side chain already outgrew the canon chain it replaced, the fork is completed,
+    /// otherwise it is rolled back and the side chain can be processed again.
+    pub(crate) fn recover_interrupted_fork(&self) -> Result<(), StorageError> {
+        let fork = match self.get_interrupted_fork()? {
+            Some(fork) => fork,
+            None => return Ok(()),
+        };
+
+        if self.get_latest_block_height() > fork.shared_block_number + fork.canon_path.len() as u32 {
+            self.complete_fork()
+        } else {
+            self.rollback_fork()
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::merkle_tree::{IncrementalMerkleTree, MerkleTreeDigest};
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::convert::TryInto;
+
+/// Returns the key of a node in the commitment tree column.
+/// Keys are big-endian so the column iterates in level and index order.
+pub fn commitment_tree_node_key(level: usize, index: usize) -> Vec<u8> {
+    [&[level as u8][..], &(index as u32).to_be_bytes()[..]].concat()
+}
+
+/// Returns the level and the index of a key in the commitment tree column.
+fn commitment_tree_node_position(key: &[u8]) -> (usize, usize) {
+    let index = u32::from_be_bytes(
+        key[1..5]
+            .try_into()
+            .expect("commitment tree keys end with a node index"),
+    );
+
+    (key[0] as usize, index as usize)
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Load the commitment merkle tree from its stored nodes.
+    ///
+    /// If the stored nodes do not cover the stored commitments, the tree is rebuilt from the
+    /// commitments, and its nodes are stored unless the storage is read-only.
+    pub(crate) fn load_commitment_tree(storage: &S, parameters: &P) -> Result<IncrementalMerkleTree<P>, StorageError> {
+        let num_commitments = match storage.get(COL_META, KEY_CURR_CM_INDEX.as_bytes())? {
+            Some(cm_index_bytes) => bytes_to_u32(cm_index_bytes) as usize,
+            None => 0,
+        };
+
+        if let Some(levels) = Self::read_commitment_tree_levels(storage)? {
+            if levels[0].len() == num_commitments {
+                if let Ok(merkle_tree) = IncrementalMerkleTree::from_levels(parameters.clone(), levels) {
+                    return Ok(merkle_tree);
+                }
+            }
+        }
+
+        let merkle_tree = Self::build_commitment_tree(storage, parameters)?;
+
+        if !storage.is_read_only() {
+            storage.write(DatabaseTransaction(Self::rebuild_commitment_tree_ops(
+                storage,
+                &merkle_tree,
+            )?))?;
+        }
+
+        Ok(merkle_tree)
+    }
+
+    /// Build the commitment merkle tree from the stored commitments.
+    pub(crate) fn build_commitment_tree(storage: &S, parameters: &P) -> Result<IncrementalMerkleTree<P>, StorageError> {
+        let mut cm_and_indices = vec![];
+
+        for (commitment_key, index_value) in storage.get_iter(COL_COMMITMENT)? {
+            let commitment: T::Commitment = FromBytes::read(&commitment_key[..])?;
+            let index = bytes_to_u32(index_value.to_vec()) as usize;
+
+            cm_and_indices.push((commitment, index));
+        }
+
+        cm_and_indices.sort_by(|&(_, i), &(_, j)| i.cmp(&j));
+        let commitments = cm_and_indices.into_iter().map(|(cm, _)| cm).collect::<Vec<_>>();
+
+        Ok(IncrementalMerkleTree::new(parameters.clone(), &commitments)?)
+    }
+
+    /// Read the levels of the commitment merkle tree from its stored nodes.
+    /// Returns `None` if the stored nodes do not form contiguous levels.
+    pub(crate) fn read_commitment_tree_levels(
+        storage: &S,
+    ) -> Result<Option<Vec<Vec<MerkleTreeDigest<P>>>>, StorageError> {
+        let mut levels: Vec<Vec<MerkleTreeDigest<P>>> = vec![vec![]];
+
+        for (key, value) in storage.get_iter(COL_COMMITMENT_TREE)? {
+            let (level, index) = commitment_tree_node_position(&key);
+
+            if level > levels.len() || (level == levels.len() && index != 0) {
+                return Ok(None);
+            }
+            if level == levels.len() {
+                levels.push(vec![]);
+            }
+            if index != levels[level].len() {
+                return Ok(None);
+            }
+
+            levels[level].push(FromBytes::read(&value[..])?);
+        }
+
+        Ok(Some(levels))
+    }
+
+    /// Returns the operations that store the given nodes of the commitment merkle tree, and remove
+    /// the nodes past the end of each level, given the number of nodes of each level before the update.
+    pub(crate) fn commitment_tree_ops(
+        merkle_tree: &IncrementalMerkleTree<P>,
+        previous_level_sizes: &[usize],
+        updated_nodes: &[(usize, usize)],
+    ) -> Result<Vec<Op>, StorageError> {
+        let mut ops = Vec::with_capacity(updated_nodes.len());
+
+        for (level, &previous_level_size) in previous_level_sizes.iter().enumerate() {
+            let level_size = merkle_tree.levels().get(level).map(|nodes| nodes.len()).unwrap_or(0);
+
+            for index in level_size..previous_level_size {
+                ops.push(Op::Delete {
+                    col: COL_COMMITMENT_TREE,
+                    key: commitment_tree_node_key(level, index),
+                });
+            }
+        }
+
+        for &(level, index) in updated_nodes {
+            if let Some(node) = merkle_tree.node(level, index) {
+                ops.push(Op::Insert {
+                    col: COL_COMMITMENT_TREE,
+                    key: commitment_tree_node_key(level, index),
+                    value: to_bytes![node]?.to_vec(),
+                });
+            }
+        }
+
+        Ok(ops)
+    }
+
+    /// Returns the operations that replace every stored node of the commitment merkle tree.
+    pub(crate) fn rebuild_commitment_tree_ops(
+        storage: &S,
+        merkle_tree: &IncrementalMerkleTree<P>,
+    ) -> Result<Vec<Op>, StorageError> {
+        let mut ops = vec![];
+
+        for (key, _value) in storage.get_iter(COL_COMMITMENT_TREE)? {
+            ops.push(Op::Delete {
+                col: COL_COMMITMENT_TREE,
+                key: key.to_vec(),
+            });
+        }
+
+        for (level, nodes) in merkle_tree.levels().iter().enumerate() {
+            for (index, node) in nodes.iter().enumerate() {
+                ops.push(Op::Insert {
+                    col: COL_COMMITMENT_TREE,
+                    key: commitment_tree_node_key(level, index),
+                    value: to_bytes![node]?.to_vec(),
+                });
+            }
+        }
+
+        Ok(ops)
+    }
+}
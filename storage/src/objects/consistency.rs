@@ -0,0 +1,136 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of blocks committed before the consistency marker moves to the latest block, which bounds the
+/// number of blocks checked after a crash.
+pub const CONSISTENCY_MARKER_INTERVAL: u32 = 100;
+
+/// The latest block the storage is known to be consistent at. The marker is written when the ledger is opened,
+/// every `CONSISTENCY_MARKER_INTERVAL` committed blocks, and when the node shuts down cleanly.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyMarker {
+    /// The height of the consistent block
+    pub block_height: u32,
+    /// The hash of the consistent block
+    pub block_hash: Vec<u8>,
+    /// Flag indicating if the node shut down cleanly after the block was committed
+    pub clean_shutdown: bool,
+}
+
+/// The check of the storage run when the ledger of a node is opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartupCheck {
+    /// The node shut down cleanly at the latest block, so the storage was not checked
+    CleanShutdown,
+    /// The node did not shut down cleanly, so the blocks from the last consistent block were checked,
+    /// and the inconsistencies found were repaired
+    Recovered {
+        from_block_height: u32,
+        inconsistencies: Vec<Inconsistency>,
+    },
+    /// The storage has no consistency marker, such as the storage of a previous version, so it was not checked
+    Unmarked,
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the latest block the storage is known to be consistent at, if any.
+    pub fn get_consistency_marker(&self) -> Result<Option<ConsistencyMarker>, StorageError> {
+        match self.storage.get(COL_META, KEY_CONSISTENCY_MARKER.as_bytes())? {
+            Some(marker_bytes) => Ok(Some(bincode::deserialize(&marker_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the operation that records the canon block of the given height as consistent.
+    pub(crate) fn consistency_marker_op(&self, block_height: u32, clean_shutdown: bool) -> Result<Op, StorageError> {
+        let marker = ConsistencyMarker {
+            block_height,
+            block_hash: self.get_block_hash(block_height)?.0.to_vec(),
+            clean_shutdown,
+        };
+
+        Ok(Op::Insert {
+            col: COL_META,
+            key: KEY_CONSISTENCY_MARKER.as_bytes().to_vec(),
+            value: bincode::serialize(&marker)?,
+        })
+    }
+
+    /// Record the latest canon block as consistent, and whether the node is shutting down cleanly.
+    pub fn mark_consistent(&self, clean_shutdown: bool) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let marker_op = self.consistency_marker_op(self.get_latest_block_height(), clean_shutdown)?;
+        self.storage.write(DatabaseTransaction(vec![marker_op]))
+    }
+
+    /// Move the consistency marker to the latest canon block once `CONSISTENCY_MARKER_INTERVAL` blocks
+    /// were committed after it.
+    ///
+    /// The storage writes are applied in order, so the marker is only persisted after the blocks it follows.
+    pub(crate) fn advance_consistency_marker(&self) -> Result<(), StorageError> {
+        let latest_block_height = self.get_latest_block_height();
+
+        match self.get_consistency_marker()? {
+            Some(marker)
+                if latest_block_height >= marker.block_height
+                    && latest_block_height - marker.block_height < CONSISTENCY_MARKER_INTERVAL =>
+            {
+                Ok(())
+            }
+            _ => self.mark_consistent(false),
+        }
+    }
+
+    /// Check the storage of a node that was opened.
+    ///
+    /// The storage of a node that shut down cleanly at its latest block is not checked. Otherwise, the blocks
+    /// from the last consistent block are checked with `verify_integrity_from` and repaired, rather than the
+    /// whole chain. The latest block is then marked as consistent until the node shuts down cleanly.
+    pub(crate) fn check_startup_consistency(&self) -> Result<StartupCheck, StorageError> {
+        let latest_block_height = self.get_latest_block_height();
+        let latest_block_hash = self.get_block_hash(latest_block_height)?.0.to_vec();
+
+        let startup_check = match self.get_consistency_marker()? {
+            Some(marker)
+                if marker.clean_shutdown
+                    && marker.block_height == latest_block_height
+                    && marker.block_hash == latest_block_hash =>
+            {
+                StartupCheck::CleanShutdown
+            }
+            Some(marker) => {
+                let from_block_height = marker.block_height.min(latest_block_height);
+
+                StartupCheck::Recovered {
+                    from_block_height,
+                    inconsistencies: self.verify_integrity_from(from_block_height, true)?,
+                }
+            }
+            None => StartupCheck::Unmarked,
+        };
+
+        self.mark_consistent(false)?;
+
+        Ok(startup_check)
+    }
+}
@@ -15,17 +15,14 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::*;
-use snarkos_algorithms::merkle_tree::MerkleTree;
+use snarkos_algorithms::merkle_tree::IncrementalMerkleTree;
 use snarkos_errors::storage::StorageError;
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
-use snarkos_utilities::{
-    bytes::{FromBytes, ToBytes},
-    to_bytes,
-};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
 
 use std::collections::HashSet;
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Get the current commitment index
     pub fn current_cm_index(&self) -> Result<usize, StorageError> {
         match self.storage.get(COL_META, KEY_CURR_CM_INDEX.as_bytes())? {
@@ -54,7 +51,7 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     pub fn current_digest(&self) -> Result<Vec<u8>, StorageError> {
         match self.storage.get(COL_META, KEY_CURR_DIGEST.as_bytes())? {
             Some(current_digest) => Ok(current_digest),
-            None => Ok(to_bytes![self.cm_merkle_tree.read().root()].unwrap()),
+            None => Ok(to_bytes![self.cm_merkle_tree.read_recursive().root()].unwrap()),
         }
     }
 
@@ -108,37 +105,26 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     }
 
     /// Build a new commitment merkle tree from the stored commitments
-    pub fn build_merkle_tree(
-        &self,
-        additional_cms: Vec<(T::Commitment, usize)>,
-    ) -> Result<MerkleTree<P>, StorageError> {
-        // TODO (raychu86) make this more efficient
-        let mut cm_and_indices = additional_cms;
-
-        for (commitment_key, index_value) in self.storage.get_iter(COL_COMMITMENT)? {
-            let commitment: T::Commitment = FromBytes::read(&commitment_key[..])?;
-            let index = bytes_to_u32(index_value.to_vec()) as usize;
-
-            cm_and_indices.push((commitment, index));
-        }
-
-        cm_and_indices.sort_by(|&(_, i), &(_, j)| i.cmp(&j));
-        let commitments = cm_and_indices.into_iter().map(|(cm, _)| cm).collect::<Vec<_>>();
-
-        Ok(MerkleTree::new(self.ledger_parameters.clone(), &commitments)?)
+    pub fn build_merkle_tree(&self) -> Result<IncrementalMerkleTree<P>, StorageError> {
+        Self::build_commitment_tree(&self.storage, &self.ledger_parameters)
     }
 
-    /// Rebuild the stored merkle tree with the current stored commitments
+    /// Rebuild the stored merkle tree and its stored nodes with the current stored commitments
     pub fn update_merkle_tree(&self) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let new_merkle_tree = self.build_merkle_tree()?;
+
         let mut merkle_tree = self.cm_merkle_tree.write();
-        *merkle_tree = self.build_merkle_tree(vec![])?;
+        *merkle_tree = new_merkle_tree;
 
-        let update_current_digest = DatabaseTransaction(vec![Op::Insert {
+        let mut update_merkle_tree = DatabaseTransaction(Self::rebuild_commitment_tree_ops(&self.storage, &merkle_tree)?);
+        update_merkle_tree.push(Op::Insert {
             col: COL_META,
             key: KEY_CURR_DIGEST.as_bytes().to_vec(),
             value: to_bytes![merkle_tree.root()]?.to_vec(),
-        }]);
+        });
 
-        self.storage.write(update_current_digest)
+        self.storage.write(update_merkle_tree)
     }
 }
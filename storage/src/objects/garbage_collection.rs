@@ -0,0 +1,287 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::{BlockHeader, BlockHeaderHash, DPCTransactions};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::collections::{HashMap, HashSet};
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the totals of the data removed by the garbage collections of the storage.
+    pub fn get_garbage_collection_metrics(&self) -> Result<GarbageCollectionMetrics, StorageError> {
+        match self.storage.get(COL_META, KEY_GARBAGE_COLLECTION.as_bytes())? {
+            Some(metrics_bytes) => Ok(bincode::deserialize(&metrics_bytes)?),
+            None => Ok(GarbageCollectionMetrics::default()),
+        }
+    }
+
+    /// Remove the data that can no longer be used by the ledger, and return what was removed.
+    ///
+    /// The removed data is:
+    /// - the side chain blocks that fork from a canon block older than the latest `reorg_window` blocks,
+    ///   and the orphan blocks older than these canon blocks,
+    /// - the past ledger digests of decommitted blocks older than the latest `reorg_window` blocks,
+    /// - the stored memory pool transactions that conflict with the ledger.
+    ///
+    /// Nothing is removed while a fork is interrupted, as the blocks it reverted may be committed again.
+    pub fn collect_garbage(&self, reorg_window: u32) -> Result<GarbageCollectionMetrics, StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let latest_block_height = self.get_latest_block_height();
+        if latest_block_height <= reorg_window || self.get_interrupted_fork()?.is_some() {
+            return Ok(GarbageCollectionMetrics::default());
+        }
+
+        let window_block_height = latest_block_height - reorg_window;
+
+        let mut collection = GarbageCollectionMetrics::default();
+        let mut database_transaction = DatabaseTransaction::new();
+
+        self.collect_abandoned_blocks(window_block_height, &mut collection, &mut database_transaction)?;
+        self.collect_past_digests(window_block_height, &mut collection, &mut database_transaction)?;
+        self.collect_memory_pool(&mut collection, &mut database_transaction)?;
+
+        if collection.is_empty() {
+            return Ok(collection);
+        }
+
+        let mut totals = self.get_garbage_collection_metrics()?;
+        totals.add(&collection);
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_GARBAGE_COLLECTION.as_bytes().to_vec(),
+            value: bincode::serialize(&totals)?,
+        });
+
+        self.storage.write(database_transaction)?;
+
+        Ok(collection)
+    }
+
+    /// Remove the side chain and orphan blocks that can no longer become canon.
+    fn collect_abandoned_blocks(
+        &self,
+        window_block_height: u32,
+        collection: &mut GarbageCollectionMetrics,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        let window_block_time = self.get_block_header(&self.get_block_hash(window_block_height)?)?.time;
+
+        let mut side_blocks = HashMap::new();
+        for (block_hash, block_header_bytes) in self.storage.get_iter(COL_BLOCK_HEADER)? {
+            if !self.storage.exists(COL_BLOCK_LOCATOR, &block_hash) {
+                side_blocks.insert(
+                    BlockHeaderHash::new(block_hash.to_vec()),
+                    BlockHeader::read(&block_header_bytes[..])?,
+                );
+            }
+        }
+
+        let mut abandoned_blocks = HashSet::new();
+        for (block_hash, block_header) in &side_blocks {
+            // Find the first block of the side chain
+            let mut first_block_header = block_header;
+            while let Some(parent_header) = side_blocks.get(&first_block_header.previous_block_hash) {
+                first_block_header = parent_header;
+            }
+
+            let is_abandoned = match self
+                .storage
+                .get(COL_BLOCK_LOCATOR, &first_block_header.previous_block_hash.0)?
+            {
+                Some(fork_block_number) => bytes_to_u32(fork_block_number) < window_block_height,
+                // The parent of an orphan chain is unknown, so the orphan blocks are aged by time instead.
+                None => block_header.time < window_block_time,
+            };
+
+            if is_abandoned {
+                abandoned_blocks.insert(block_hash.clone());
+            }
+        }
+
+        // The potential children of the parents of removed blocks, and their previous size
+        let mut child_hashes = HashMap::new();
+
+        for block_hash in &abandoned_blocks {
+            let key = block_hash.0.to_vec();
+
+            if let Ok(transactions) = self.get_block_transactions(block_hash) {
+                for transaction in transactions.0 {
+                    let transaction_id = transaction.transaction_id()?.to_vec();
+
+                    // The location may point to another block holding the same transaction
+                    match self.get_transaction_location(&transaction_id)? {
+                        Some(location) if location.block_hash == block_hash.0 => {
+                            if self.collect_key(
+                                COL_TRANSACTION_LOCATION,
+                                transaction_id,
+                                collection,
+                                database_transaction,
+                            )? {
+                                collection.removed_index_entries += 1;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            self.collect_key(COL_BLOCK_HEADER, key.clone(), collection, database_transaction)?;
+            self.collect_key(COL_BLOCK_TRANSACTIONS, key.clone(), collection, database_transaction)?;
+            self.collect_key(COL_BLOCK_ARCHIVE, key.clone(), collection, database_transaction)?;
+            self.collect_key(COL_CHILD_HASHES, key, collection, database_transaction)?;
+
+            // Remove the block from the potential children of its parent, unless the parent is removed too
+            let parent_hash = &side_blocks[block_hash].previous_block_hash;
+            if !abandoned_blocks.contains(parent_hash) {
+                if !child_hashes.contains_key(parent_hash) {
+                    let children = self.get_child_block_hashes(parent_hash)?;
+                    let size = bincode::serialized_size(&children)?;
+                    child_hashes.insert(parent_hash.clone(), (size, children));
+                }
+
+                if let Some((_, children)) = child_hashes.get_mut(parent_hash) {
+                    children.retain(|child| child != block_hash);
+                }
+            }
+
+            collection.removed_blocks += 1;
+        }
+
+        for (parent_hash, (previous_size, children)) in child_hashes {
+            let key = parent_hash.0.to_vec();
+
+            if children.is_empty() {
+                self.collect_key(COL_CHILD_HASHES, key, collection, database_transaction)?;
+            } else {
+                let value = bincode::serialize(&children)?;
+                collection.reclaimed_size += previous_size.saturating_sub(value.len() as u64);
+
+                database_transaction.push(Op::Insert {
+                    col: COL_CHILD_HASHES,
+                    key,
+                    value,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the past ledger digests of blocks that were decommitted before the reorg window.
+    fn collect_past_digests(
+        &self,
+        window_block_height: u32,
+        collection: &mut GarbageCollectionMetrics,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        let mut num_checkpoints = 0;
+        let mut canon_digests = HashSet::new();
+        for (_, checkpoint_bytes) in self.storage.get_iter(COL_LEDGER_CHECKPOINT)? {
+            canon_digests.insert(LedgerCheckpoint::read(&checkpoint_bytes[..])?.digest);
+            num_checkpoints += 1;
+        }
+
+        // A canon digest without a checkpoint can not be told apart from the digest of a decommitted block.
+        if num_checkpoints != self.get_block_count() {
+            return Ok(());
+        }
+
+        for (digest, block_number_bytes) in self.storage.get_iter(COL_DIGEST)? {
+            let block_number = bytes_to_u32(block_number_bytes.to_vec());
+
+            if block_number < window_block_height && !canon_digests.contains(&digest[..]) {
+                collection.removed_index_entries += 1;
+                collection.reclaimed_size += (digest.len() + block_number_bytes.len()) as u64;
+
+                database_transaction.push(Op::Delete {
+                    col: COL_DIGEST,
+                    key: digest.to_vec(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the stored memory pool transactions that conflict with the ledger,
+    /// as they can no longer be included in a block.
+    fn collect_memory_pool(
+        &self,
+        collection: &mut GarbageCollectionMetrics,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        let memory_pool_bytes = match self.storage.get(COL_META, KEY_MEMORY_POOL.as_bytes())? {
+            Some(memory_pool_bytes) => memory_pool_bytes,
+            None => return Ok(()),
+        };
+
+        let transactions = DPCTransactions::<T>::read(&memory_pool_bytes[..])?;
+        let num_transactions = transactions.0.len();
+
+        let valid_transactions = DPCTransactions(
+            transactions
+                .0
+                .into_iter()
+                .filter(|transaction| !self.transcation_conflicts(transaction))
+                .collect(),
+        );
+
+        if valid_transactions.0.len() == num_transactions {
+            return Ok(());
+        }
+
+        let value = to_bytes![valid_transactions]?.to_vec();
+
+        collection.removed_memory_pool_transactions += (num_transactions - valid_transactions.0.len()) as u64;
+        collection.reclaimed_size += memory_pool_bytes.len().saturating_sub(value.len()) as u64;
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_MEMORY_POOL.as_bytes().to_vec(),
+            value,
+        });
+
+        Ok(())
+    }
+
+    /// Push the operation that removes a key, if it exists, and count the size of the key and its value.
+    /// Returns true if the key exists.
+    fn collect_key(
+        &self,
+        col: u32,
+        key: Vec<u8>,
+        collection: &mut GarbageCollectionMetrics,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<bool, StorageError> {
+        match self.storage.get(col, &key)? {
+            Some(value) => {
+                collection.reclaimed_size += (key.len() + value.len()) as u64;
+                database_transaction.push(Op::Delete { col, key });
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
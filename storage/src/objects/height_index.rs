@@ -0,0 +1,149 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::{Block, BlockHeaderHash};
+
+use std::convert::TryInto;
+
+/// Returns the key of a block number in the block height index.
+/// Keys are big-endian so the index iterates in block number order.
+pub fn block_height_index_key(block_number: u32) -> Vec<u8> {
+    block_number.to_be_bytes().to_vec()
+}
+
+/// Returns the key of a transaction in the transaction height index.
+/// Keys are big-endian so the index iterates in block number and transaction index order.
+pub fn transaction_height_index_key(block_number: u32, index: u32) -> Vec<u8> {
+    [block_number.to_be_bytes(), index.to_be_bytes()].concat()
+}
+
+/// Returns the block number of a key in the block or transaction height index.
+fn height_index_block_number(key: &[u8]) -> u32 {
+    u32::from_be_bytes(
+        key[0..4]
+            .try_into()
+            .expect("height index keys start with a block number"),
+    )
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns the operations that add a canon block and its transactions to the height indexes.
+    pub(crate) fn index_block_height_ops(&self, block_number: u32, block: &Block<T>) -> Result<Vec<Op>, StorageError> {
+        let mut ops = vec![Op::Insert {
+            col: COL_BLOCK_HEIGHT_INDEX,
+            key: block_height_index_key(block_number),
+            value: block.header.get_hash().0.to_vec(),
+        }];
+
+        for (index, transaction) in block.transactions.iter().enumerate() {
+            ops.push(Op::Insert {
+                col: COL_TRANSACTION_HEIGHT_INDEX,
+                key: transaction_height_index_key(block_number, index as u32),
+                value: transaction.transaction_id()?.to_vec(),
+            });
+        }
+
+        Ok(ops)
+    }
+
+    /// Returns the operations that remove the transactions of a block from the transaction height index.
+    pub(crate) fn unindex_transaction_height_ops(&self, block_number: u32, num_transactions: usize) -> Vec<Op> {
+        (0..num_transactions)
+            .map(|index| Op::Delete {
+                col: COL_TRANSACTION_HEIGHT_INDEX,
+                key: transaction_height_index_key(block_number, index as u32),
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the numbers and hashes of the canon blocks
+    /// from `start_block_number` to `end_block_number` inclusive, in ascending order.
+    pub fn get_block_hashes_in_range(
+        &self,
+        start_block_number: u32,
+        end_block_number: u32,
+    ) -> Result<impl Iterator<Item = (u32, BlockHeaderHash)> + '_, StorageError> {
+        Ok(self
+            .storage
+            .get_iter_from(COL_BLOCK_HEIGHT_INDEX, &block_height_index_key(start_block_number))?
+            .map(|(key, block_hash)| {
+                (
+                    height_index_block_number(&key),
+                    BlockHeaderHash::new(block_hash.to_vec()),
+                )
+            })
+            .take_while(move |(block_number, _)| *block_number <= end_block_number))
+    }
+
+    /// Returns an iterator over the canon blocks from `start_block_number` to `end_block_number`
+    /// inclusive, in ascending order.
+    pub fn get_blocks_in_range(
+        &self,
+        start_block_number: u32,
+        end_block_number: u32,
+    ) -> Result<impl Iterator<Item = Result<(u32, Block<T>), StorageError>> + '_, StorageError> {
+        Ok(self
+            .get_block_hashes_in_range(start_block_number, end_block_number)?
+            .map(move |(block_number, block_hash)| Ok((block_number, self.get_block(&block_hash)?))))
+    }
+
+    /// Returns an iterator over the block numbers and ids of the canon transactions in the blocks
+    /// from `start_block_number` to `end_block_number` inclusive, in chain order.
+    /// The transactions of pruned blocks are not included.
+    pub fn get_transaction_ids_in_range(
+        &self,
+        start_block_number: u32,
+        end_block_number: u32,
+    ) -> Result<impl Iterator<Item = (u32, Vec<u8>)> + '_, StorageError> {
+        Ok(self
+            .storage
+            .get_iter_from(
+                COL_TRANSACTION_HEIGHT_INDEX,
+                &transaction_height_index_key(start_block_number, 0),
+            )?
+            .map(|(key, transaction_id)| (height_index_block_number(&key), transaction_id.to_vec()))
+            .take_while(move |(block_number, _)| *block_number <= end_block_number))
+    }
+
+    /// Returns an iterator over the block numbers and canon transactions in the blocks
+    /// from `start_block_number` to `end_block_number` inclusive, in chain order.
+    /// The transactions of pruned blocks are not included.
+    pub fn get_transactions_in_range(
+        &self,
+        start_block_number: u32,
+        end_block_number: u32,
+    ) -> Result<impl Iterator<Item = Result<(u32, T), StorageError>> + '_, StorageError> {
+        let pruned_block_height = self.get_pruned_block_height()?;
+
+        Ok(self
+            .get_block_hashes_in_range(start_block_number, end_block_number)?
+            .filter(move |(block_number, _)| *block_number == 0 || *block_number > pruned_block_height)
+            .flat_map(move |(block_number, block_hash)| {
+                let transactions: Vec<Result<(u32, T), StorageError>> = match self.get_block_transactions(&block_hash) {
+                    Ok(transactions) => transactions
+                        .0
+                        .into_iter()
+                        .map(|transaction| Ok((block_number, transaction)))
+                        .collect(),
+                    Err(error) => vec![Err(error)],
+                };
+                transactions
+            }))
+    }
+}
@@ -20,7 +20,7 @@ use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction}
 use snarkos_objects::{Block, BlockHeader, BlockHeaderHash};
 use snarkos_utilities::{bytes::ToBytes, has_duplicates, to_bytes};
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Commit a transaction to the canon chain
     #[allow(clippy::type_complexity)]
     pub(crate) fn commit_transaction(
@@ -83,6 +83,15 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
 
     /// Insert a block into storage without canonizing/committing it.
     pub fn insert_only(&self, block: &Block<T>) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let database_transaction = self.insert_block_ops(block)?;
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Returns the database operations that insert a block into storage without canonizing/committing it.
+    fn insert_block_ops(&self, block: &Block<T>) -> Result<DatabaseTransaction, StorageError> {
         let block_hash = block.header.get_hash();
 
         // Check that the block does not already exist.
@@ -156,28 +165,31 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             });
         }
 
-        database_transaction.push(Op::Insert {
-            col: COL_BLOCK_TRANSACTIONS,
-            key: block.header.get_hash().0.to_vec(),
-            value: to_bytes![block.transactions]?.to_vec(),
-        });
-
-        self.storage.write(database_transaction)?;
-
-        Ok(())
+        Ok(database_transaction)
     }
 
     /// Commit/canonize a particular block.
     pub fn commit(&self, block_header_hash: &BlockHeaderHash) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         let block = self.get_block(block_header_hash)?;
 
+        self.commit_block(&block, DatabaseTransaction::new())
+    }
+
+    /// Commit/canonize a block, writing the given pending database operations in the same batch.
+    fn commit_block(
+        &self,
+        block: &Block<T>,
+        mut database_transaction: DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        let block_header_hash = block.header.get_hash();
+
         // Check if the block is already in the canon chain
-        if self.is_canon(block_header_hash) {
+        if self.is_canon(&block_header_hash) {
             return Err(StorageError::ExistingCanonBlock(block_header_hash.to_string()));
         }
 
-        let mut database_transaction = DatabaseTransaction::new();
-
         let mut transaction_serial_numbers = Vec::with_capacity(block.transactions.0.len());
         let mut transaction_commitments = Vec::with_capacity(block.transactions.0.len());
         let mut transaction_memos = Vec::with_capacity(block.transactions.0.len());
@@ -209,6 +221,8 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         let mut cm_index = self.current_cm_index()?;
         let mut memo_index = self.current_memo_index()?;
 
+        let block_undo = BlockUndo::new(&block.transactions, sn_index, cm_index, memo_index)?;
+
         // Process the individual transactions
 
         let mut transaction_cms = vec![];
@@ -243,10 +257,9 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             && self.get_latest_block_height() == 0
             && self.is_empty();
 
-        let mut height = self.latest_block_height.write();
         let mut new_best_block_number = 0;
         if !is_genesis {
-            new_best_block_number = *height + 1;
+            new_best_block_number = self.get_latest_block_height() + 1;
         }
 
         database_transaction.push(Op::Insert {
@@ -268,9 +281,45 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             value: block.header.get_hash().0.to_vec(),
         });
 
-        // Rebuild the new commitment merkle tree
-        let new_merkle_tree = self.build_merkle_tree(transaction_cms)?;
-        let new_digest = new_merkle_tree.root();
+        // Update the block and transaction height indexes
+
+        database_transaction.push_vec(self.index_block_height_ops(new_best_block_number, block)?);
+
+        // Add the memos and commitments of the block to the search indexes
+
+        database_transaction.push_vec(Self::index_transaction_search_ops(
+            new_best_block_number,
+            &block.transactions,
+        )?);
+
+        // Store the undo record of the block
+
+        database_transaction.push(Self::block_undo_op(new_best_block_number, &block_undo)?);
+
+        // Add the serial numbers and memos of the block to the ledger filters
+
+        database_transaction.push_vec(self.update_filters_ops(
+            new_best_block_number,
+            &block_header_hash,
+            &block_undo,
+        )?);
+
+        // Add the serial numbers of the block to the serial number tree
+
+        database_transaction.push_vec(self.serial_number_tree_ops(&block_undo.serial_numbers, true)?);
+
+        // Append the new commitments to the commitment merkle tree. The block is published once the
+        // ledger views are dropped, so they do not observe the tree ahead of the block height.
+
+        let mut height = self.latest_block_height.write();
+        let mut cm_merkle_tree = self.cm_merkle_tree.write();
+        let num_commitments = cm_merkle_tree.len();
+
+        let new_commitments = transaction_cms.into_iter().map(|(cm, _)| cm).collect::<Vec<_>>();
+        let updated_nodes = cm_merkle_tree.append(&new_commitments)?;
+        database_transaction.push_vec(Self::commitment_tree_ops(&cm_merkle_tree, &[], &updated_nodes)?);
+
+        let new_digest = cm_merkle_tree.root();
 
         database_transaction.push(Op::Insert {
             col: COL_DIGEST,
@@ -282,27 +331,45 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             key: KEY_CURR_DIGEST.as_bytes().to_vec(),
             value: to_bytes![new_digest]?.to_vec(),
         });
-
-        let mut cm_merkle_tree = self.cm_merkle_tree.write();
-        *cm_merkle_tree = new_merkle_tree;
-
-        self.storage.write(database_transaction)?;
+        database_transaction.push(Self::ledger_checkpoint_op(
+            new_best_block_number,
+            cm_merkle_tree.len(),
+            &new_digest,
+        )?);
+
+        // Remove the appended commitments if the block could not be written
+        if let Err(error) = self.storage.write(database_transaction) {
+            cm_merkle_tree.truncate(num_commitments)?;
+            return Err(error);
+        }
 
         if !is_genesis {
             *height += 1;
         }
 
-        Ok(())
+        drop(cm_merkle_tree);
+        drop(height);
+
+        // Resize the ledger filters once they hold more items than they were sized for
+        if self.filters.read().as_ref().map_or(false, LedgerFilters::is_saturated) {
+            self.rebuild_filters()?;
+        }
+
+        self.advance_consistency_marker()
     }
 
     /// Insert a block into the storage and commit as part of the longest chain.
+    /// The insertion and the commitment are written atomically in a single batch.
     pub fn insert_and_commit(&self, block: &Block<T>) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         let block_hash = block.header.get_hash();
 
         // If the block does not exist in the storage
         if !self.block_hash_exists(&block_hash) {
-            // Insert it first
-            self.insert_only(&block)?;
+            // Insert and commit it together
+            let database_transaction = self.insert_block_ops(block)?;
+            return self.commit_block(block, database_transaction);
         }
         // Commit it
         self.commit(&block_hash)
@@ -319,10 +386,31 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     }
 
     /// Revert the chain to the state before the fork.
+    ///
+    /// The reverted canon blocks are recorded until `complete_fork` is called, so a fork that fails
+    /// or is interrupted by a crash can be rolled back with `rollback_fork`.
     pub fn revert_for_fork(&self, side_chain_path: &SideChainPath) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
         let latest_block_height = self.get_latest_block_height();
 
         if side_chain_path.new_block_number > latest_block_height {
+            let mut canon_path = vec![];
+            for block_number in (side_chain_path.shared_block_number + 1)..=latest_block_height {
+                canon_path.push(self.get_block_hash(block_number)?);
+            }
+
+            let fork = InterruptedFork {
+                shared_block_number: side_chain_path.shared_block_number,
+                canon_path,
+            };
+
+            self.storage.write(DatabaseTransaction(vec![Op::Insert {
+                col: COL_META,
+                key: KEY_INTERRUPTED_FORK.as_bytes().to_vec(),
+                value: bincode::serialize(&fork)?,
+            }]))?;
+
             // Decommit all blocks on canon chain up to the shared block number with the side chain.
             for _ in (side_chain_path.shared_block_number)..latest_block_height {
                 self.decommit_latest_block()?;
@@ -0,0 +1,460 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::merkle_tree::IncrementalMerkleTree;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::{collections::HashSet, fmt};
+
+/// An inconsistency found when verifying the storage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// A canon block number without a block hash
+    MissingBlockHash(u32),
+    /// A canon block hash without a block number
+    MissingBlockNumber(u32),
+    /// A canon block without a block header
+    MissingBlockHeader(u32),
+    /// A canon block with a missing or invalid entry in the block height index
+    InvalidBlockHeightIndex(u32),
+    /// A canon block header that does not extend the previous canon block
+    InvalidPreviousBlockHash(u32),
+    /// A canon block without transactions
+    MissingBlockTransactions(u32),
+    /// A canon transaction with a missing or invalid location
+    InvalidTransactionLocation(Vec<u8>),
+    /// A canon transaction with a missing or invalid entry in the transaction height index
+    InvalidTransactionHeightIndex(Vec<u8>),
+    /// A canon memo or commitment with a missing or invalid entry in the given search index column
+    InvalidSearchIndex(u32, Vec<u8>),
+    /// A canon serial number, commitment, or memo with a missing or invalid index in the given column
+    InvalidIndex(u32, Vec<u8>),
+    /// An indexed serial number, commitment, or memo in the given column that is not in the canon chain
+    UnexpectedIndex(u32, Vec<u8>),
+    /// A current index that does not match the number of canon serial numbers, commitments, or memos
+    InvalidCurrentIndex(&'static str, usize),
+    /// A current ledger digest that does not match the canon commitments
+    InvalidCurrentDigest,
+    /// Stored commitment merkle tree nodes that do not match the canon commitments
+    InvalidCommitmentTree,
+    /// A canon block with a missing or invalid ledger checkpoint
+    InvalidLedgerCheckpoint(u32),
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inconsistency::MissingBlockHash(block_number) => write!(f, "missing block hash for block {}", block_number),
+            Inconsistency::MissingBlockNumber(block_number) => {
+                write!(f, "missing block number for block {}", block_number)
+            }
+            Inconsistency::MissingBlockHeader(block_number) => {
+                write!(f, "missing block header for block {}", block_number)
+            }
+            Inconsistency::InvalidBlockHeightIndex(block_number) => {
+                write!(f, "invalid block height index for block {}", block_number)
+            }
+            Inconsistency::InvalidPreviousBlockHash(block_number) => {
+                write!(f, "invalid previous block hash for block {}", block_number)
+            }
+            Inconsistency::MissingBlockTransactions(block_number) => {
+                write!(f, "missing block transactions for block {}", block_number)
+            }
+            Inconsistency::InvalidTransactionLocation(transaction_id) => {
+                write!(f, "invalid location for transaction {}", hex::encode(transaction_id))
+            }
+            Inconsistency::InvalidTransactionHeightIndex(transaction_id) => {
+                write!(
+                    f,
+                    "invalid height index for transaction {}",
+                    hex::encode(transaction_id)
+                )
+            }
+            Inconsistency::InvalidSearchIndex(col, key) => {
+                write!(f, "invalid search index for key {} in column {}", hex::encode(key), col)
+            }
+            Inconsistency::InvalidIndex(col, key) => {
+                write!(f, "invalid index for key {} in column {}", hex::encode(key), col)
+            }
+            Inconsistency::UnexpectedIndex(col, key) => {
+                write!(f, "unexpected index for key {} in column {}", hex::encode(key), col)
+            }
+            Inconsistency::InvalidCurrentIndex(current_index_key, index) => {
+                write!(f, "invalid {}, expected {}", current_index_key, index)
+            }
+            Inconsistency::InvalidCurrentDigest => write!(f, "invalid current ledger digest"),
+            Inconsistency::InvalidCommitmentTree => write!(f, "invalid commitment merkle tree nodes"),
+            Inconsistency::InvalidLedgerCheckpoint(block_number) => {
+                write!(f, "invalid ledger checkpoint for block {}", block_number)
+            }
+        }
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Walk the canon chain and return the inconsistencies found in the storage.
+    ///
+    /// The block locators, transaction locations, and height and search indexes are checked against the
+    /// canon blocks.
+    /// Unless blocks have been pruned, the serial number, commitment, and memo indexes and the
+    /// ledger digest, commitment merkle tree, and ledger checkpoints are also recomputed from the canon
+    /// blocks and checked against the storage.
+    /// If `repair` is set, the inconsistent indexes are rewritten from the canon blocks.
+    pub fn verify_integrity(&self, repair: bool) -> Result<Vec<Inconsistency>, StorageError> {
+        self.verify_integrity_from(0, repair)
+    }
+
+    /// Walk the canon chain from the given block number and return the inconsistencies found in the storage,
+    /// trusting the storage state before that block.
+    ///
+    /// The checks of `verify_integrity` are run on the blocks from the given block number, except that the
+    /// serial number, commitment, and memo indexes are only checked from the genesis block, and that the
+    /// ledger checkpoints and the ledger digest are checked against the stored commitment merkle tree.
+    /// If `repair` is set, the inconsistent indexes are rewritten from the canon blocks.
+    pub fn verify_integrity_from(
+        &self,
+        start_block_number: u32,
+        repair: bool,
+    ) -> Result<Vec<Inconsistency>, StorageError> {
+        let mut inconsistencies = vec![];
+        let mut database_transaction = DatabaseTransaction::new();
+
+        let latest_block_height = self.get_latest_block_height();
+        let pruned_block_height = self.get_pruned_block_height()?;
+        let mut has_all_transactions = pruned_block_height == 0 || pruned_block_height < start_block_number;
+
+        let mut serial_numbers = vec![];
+        let mut commitments = vec![];
+        let mut memos = vec![];
+        let mut num_commitments = vec![];
+
+        let mut previous_block_hash = start_block_number
+            .checked_sub(1)
+            .and_then(|block_number| self.get_block_hash(block_number).ok());
+
+        for block_number in start_block_number..=latest_block_height {
+            let block_hash = match self.get_block_hash(block_number) {
+                Ok(block_hash) => block_hash,
+                Err(_) => {
+                    inconsistencies.push(Inconsistency::MissingBlockHash(block_number));
+                    has_all_transactions = false;
+                    previous_block_hash = None;
+                    continue;
+                }
+            };
+
+            if self.get_block_number(&block_hash).ok() != Some(block_number) {
+                inconsistencies.push(Inconsistency::MissingBlockNumber(block_number));
+                database_transaction.push(Op::Insert {
+                    col: COL_BLOCK_LOCATOR,
+                    key: block_hash.0.to_vec(),
+                    value: block_number.to_le_bytes().to_vec(),
+                });
+            }
+
+            let block_height_key = block_height_index_key(block_number);
+            if self.storage.get(COL_BLOCK_HEIGHT_INDEX, &block_height_key)?.as_deref() != Some(&block_hash.0[..]) {
+                inconsistencies.push(Inconsistency::InvalidBlockHeightIndex(block_number));
+                database_transaction.push(Op::Insert {
+                    col: COL_BLOCK_HEIGHT_INDEX,
+                    key: block_height_key,
+                    value: block_hash.0.to_vec(),
+                });
+            }
+
+            match self.get_block_header(&block_hash) {
+                Ok(block_header) => {
+                    if let Some(previous_block_hash) = &previous_block_hash {
+                        if &block_header.previous_block_hash != previous_block_hash {
+                            inconsistencies.push(Inconsistency::InvalidPreviousBlockHash(block_number));
+                        }
+                    }
+                }
+                Err(_) => inconsistencies.push(Inconsistency::MissingBlockHeader(block_number)),
+            }
+
+            previous_block_hash = Some(block_hash.clone());
+
+            // Pruned blocks no longer have transactions
+            if block_number > 0 && block_number <= pruned_block_height {
+                continue;
+            }
+
+            let transactions = match self.get_block_transactions(&block_hash) {
+                Ok(transactions) => transactions,
+                Err(_) => {
+                    inconsistencies.push(Inconsistency::MissingBlockTransactions(block_number));
+                    has_all_transactions = false;
+                    continue;
+                }
+            };
+
+            for (index, transaction) in transactions.0.iter().enumerate() {
+                let transaction_id = transaction.transaction_id()?.to_vec();
+                let transaction_location = TransactionLocation {
+                    index: index as u32,
+                    block_hash: block_hash.0,
+                };
+
+                if self.get_transaction_location(&transaction_id)? != Some(transaction_location.clone()) {
+                    inconsistencies.push(Inconsistency::InvalidTransactionLocation(transaction_id.clone()));
+                    database_transaction.push(Op::Insert {
+                        col: COL_TRANSACTION_LOCATION,
+                        key: transaction_id.clone(),
+                        value: to_bytes![transaction_location]?.to_vec(),
+                    });
+                }
+
+                let transaction_height_key = transaction_height_index_key(block_number, index as u32);
+                if self
+                    .storage
+                    .get(COL_TRANSACTION_HEIGHT_INDEX, &transaction_height_key)?
+                    .as_deref()
+                    != Some(&transaction_id[..])
+                {
+                    inconsistencies.push(Inconsistency::InvalidTransactionHeightIndex(transaction_id.clone()));
+                    database_transaction.push(Op::Insert {
+                        col: COL_TRANSACTION_HEIGHT_INDEX,
+                        key: transaction_height_key,
+                        value: transaction_id.clone(),
+                    });
+                }
+
+                let search_value = search_index_value(block_number, &transaction_id);
+                let mut search_index_keys = vec![(COL_MEMO_SEARCH_INDEX, to_bytes![transaction.memorandum()]?)];
+
+                for sn in transaction.old_serial_numbers() {
+                    serial_numbers.push(to_bytes![sn]?);
+                }
+
+                for cm in transaction.new_commitments() {
+                    commitments.push(to_bytes![cm]?);
+                    search_index_keys.push((COL_COMMITMENT_SEARCH_INDEX, to_bytes![cm]?));
+                }
+
+                memos.push(to_bytes![transaction.memorandum()]?);
+
+                for (col, key) in search_index_keys {
+                    if self.storage.get(col, &key)?.as_deref() != Some(&search_value[..]) {
+                        inconsistencies.push(Inconsistency::InvalidSearchIndex(col, key.clone()));
+                        database_transaction.push(Op::Insert {
+                            col,
+                            key,
+                            value: search_value.clone(),
+                        });
+                    }
+                }
+            }
+
+            num_commitments.push((block_number, commitments.len()));
+        }
+
+        // The ledger state can only be recomputed from the transactions of every canon block
+
+        if has_all_transactions && start_block_number == 0 {
+            self.verify_index(
+                COL_SERIAL_NUMBER,
+                KEY_CURR_SN_INDEX,
+                &serial_numbers,
+                &mut inconsistencies,
+                &mut database_transaction,
+            )?;
+            self.verify_index(
+                COL_COMMITMENT,
+                KEY_CURR_CM_INDEX,
+                &commitments,
+                &mut inconsistencies,
+                &mut database_transaction,
+            )?;
+            self.verify_index(
+                COL_MEMO,
+                KEY_CURR_MEMO_INDEX,
+                &memos,
+                &mut inconsistencies,
+                &mut database_transaction,
+            )?;
+
+            let commitments = commitments
+                .iter()
+                .map(|cm| T::Commitment::read(&cm[..]))
+                .collect::<Result<Vec<_>, _>>()?;
+            let merkle_tree = IncrementalMerkleTree::new(self.ledger_parameters.clone(), &commitments)?;
+            let digest = to_bytes![merkle_tree.root()]?;
+
+            // The stored nodes are rewritten from the commitments when the merkle tree is updated
+            if Self::read_commitment_tree_levels(&self.storage)?.as_deref() != Some(merkle_tree.levels()) {
+                inconsistencies.push(Inconsistency::InvalidCommitmentTree);
+            }
+
+            for (block_number, num_commitments) in num_commitments {
+                let checkpoint = LedgerCheckpoint {
+                    num_commitments: num_commitments as u32,
+                    digest: to_bytes![merkle_tree.historical_root(num_commitments)?]?,
+                };
+
+                if self.get_ledger_checkpoint(block_number).ok() != Some(checkpoint.clone()) {
+                    inconsistencies.push(Inconsistency::InvalidLedgerCheckpoint(block_number));
+                    database_transaction.push(Op::Insert {
+                        col: COL_LEDGER_CHECKPOINT,
+                        key: ledger_checkpoint_key(block_number),
+                        value: to_bytes![checkpoint]?,
+                    });
+                }
+            }
+
+            if self.current_digest()? != digest || !self.storage.exists(COL_DIGEST, &digest) {
+                inconsistencies.push(Inconsistency::InvalidCurrentDigest);
+                database_transaction.push(Op::Insert {
+                    col: COL_DIGEST,
+                    key: digest.clone(),
+                    value: latest_block_height.to_le_bytes().to_vec(),
+                });
+                database_transaction.push(Op::Insert {
+                    col: COL_META,
+                    key: KEY_CURR_DIGEST.as_bytes().to_vec(),
+                    value: digest,
+                });
+            }
+        } else if has_all_transactions {
+            self.verify_ledger_state_from(
+                start_block_number,
+                &num_commitments,
+                &mut inconsistencies,
+                &mut database_transaction,
+            )?;
+        }
+
+        if repair && !inconsistencies.is_empty() {
+            self.storage.write(database_transaction)?;
+            self.update_merkle_tree()?;
+        }
+
+        Ok(inconsistencies)
+    }
+
+    /// Check the ledger checkpoints of the canon blocks from the given block number, given the number of commitments
+    /// of the checked blocks up to each block, and the current ledger digest against the stored commitment merkle tree.
+    fn verify_ledger_state_from(
+        &self,
+        start_block_number: u32,
+        num_commitments: &[(u32, usize)],
+        inconsistencies: &mut Vec<Inconsistency>,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        // The ledger state before the checked blocks is trusted
+        let initial_num_commitments = match start_block_number.checked_sub(1) {
+            Some(block_number) => match self.get_ledger_checkpoint(block_number) {
+                Ok(checkpoint) => checkpoint.num_commitments as usize,
+                Err(_) => return Ok(()),
+            },
+            None => 0,
+        };
+
+        let merkle_tree = self.cm_merkle_tree.read();
+
+        for (block_number, block_num_commitments) in num_commitments {
+            let num_commitments = initial_num_commitments + block_num_commitments;
+            if num_commitments > merkle_tree.len() {
+                inconsistencies.push(Inconsistency::InvalidLedgerCheckpoint(*block_number));
+                continue;
+            }
+
+            let checkpoint = LedgerCheckpoint {
+                num_commitments: num_commitments as u32,
+                digest: to_bytes![merkle_tree.historical_root(num_commitments)?]?,
+            };
+
+            if self.get_ledger_checkpoint(*block_number).ok() != Some(checkpoint.clone()) {
+                inconsistencies.push(Inconsistency::InvalidLedgerCheckpoint(*block_number));
+                database_transaction.push(Op::Insert {
+                    col: COL_LEDGER_CHECKPOINT,
+                    key: ledger_checkpoint_key(*block_number),
+                    value: to_bytes![checkpoint]?,
+                });
+            }
+        }
+
+        let digest = to_bytes![merkle_tree.root()]?;
+        if self.current_digest()? != digest || !self.storage.exists(COL_DIGEST, &digest) {
+            inconsistencies.push(Inconsistency::InvalidCurrentDigest);
+            database_transaction.push(Op::Insert {
+                col: COL_DIGEST,
+                key: digest.clone(),
+                value: self.get_latest_block_height().to_le_bytes().to_vec(),
+            });
+            database_transaction.push(Op::Insert {
+                col: COL_META,
+                key: KEY_CURR_DIGEST.as_bytes().to_vec(),
+                value: digest,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check the index of a column against the keys expected in order of insertion.
+    fn verify_index(
+        &self,
+        col: u32,
+        current_index_key: &'static str,
+        expected_keys: &[Vec<u8>],
+        inconsistencies: &mut Vec<Inconsistency>,
+        database_transaction: &mut DatabaseTransaction,
+    ) -> Result<(), StorageError> {
+        for (index, key) in expected_keys.iter().enumerate() {
+            if self.storage.get(col, key)?.map(bytes_to_u32) != Some(index as u32) {
+                inconsistencies.push(Inconsistency::InvalidIndex(col, key.clone()));
+                database_transaction.push(Op::Insert {
+                    col,
+                    key: key.clone(),
+                    value: (index as u32).to_le_bytes().to_vec(),
+                });
+            }
+        }
+
+        let expected_count = expected_keys.len();
+        let expected_keys: HashSet<&[u8]> = expected_keys.iter().map(|key| &key[..]).collect();
+
+        for (key, _index) in self.storage.get_iter(col)? {
+            if !expected_keys.contains(&key[..]) {
+                inconsistencies.push(Inconsistency::UnexpectedIndex(col, key.to_vec()));
+                database_transaction.push(Op::Delete { col, key: key.to_vec() });
+            }
+        }
+
+        let current_index = self
+            .storage
+            .get(COL_META, current_index_key.as_bytes())?
+            .map(bytes_to_u32);
+
+        if current_index != Some(expected_count as u32) {
+            inconsistencies.push(Inconsistency::InvalidCurrentIndex(current_index_key, expected_count));
+            database_transaction.push(Op::Insert {
+                col: COL_META,
+                key: current_index_key.as_bytes().to_vec(),
+                value: (expected_count as u32).to_le_bytes().to_vec(),
+            });
+        }
+
+        Ok(())
+    }
+}
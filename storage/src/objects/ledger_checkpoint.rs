@@ -0,0 +1,179 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::merkle_tree::{MerklePath, MerkleTreeDigest};
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::{
+    collections::HashMap,
+    io::{Read, Result as IoResult, Write},
+};
+
+/// The state of the commitment merkle tree after a canon block
+#[derive(Debug, PartialEq, Clone)]
+pub struct LedgerCheckpoint {
+    /// Number of commitments in the ledger
+    pub num_commitments: u32,
+    /// Ledger digest
+    pub digest: Vec<u8>,
+}
+
+impl ToBytes for LedgerCheckpoint {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.num_commitments.write(&mut writer)?;
+        writer.write_all(&self.digest)
+    }
+}
+
+impl FromBytes for LedgerCheckpoint {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let num_commitments: u32 = FromBytes::read(&mut reader)?;
+        let mut digest = vec![];
+        reader.read_to_end(&mut digest)?;
+
+        Ok(Self { num_commitments, digest })
+    }
+}
+
+/// Returns the key of a block number in the ledger checkpoint column.
+pub fn ledger_checkpoint_key(block_number: u32) -> Vec<u8> {
+    block_number.to_be_bytes().to_vec()
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the ledger checkpoint of a canon block.
+    pub fn get_ledger_checkpoint(&self, block_number: u32) -> Result<LedgerCheckpoint, StorageError> {
+        match self.storage.get(COL_LEDGER_CHECKPOINT, &ledger_checkpoint_key(block_number))? {
+            Some(checkpoint_bytes) => Ok(LedgerCheckpoint::read(&checkpoint_bytes[..])?),
+            None => Err(StorageError::MissingLedgerCheckpoint(block_number)),
+        }
+    }
+
+    /// Get the ledger digest as of a canon block.
+    pub fn get_digest_at_height(&self, block_number: u32) -> Result<MerkleTreeDigest<P>, StorageError> {
+        Ok(FromBytes::read(&self.get_ledger_checkpoint(block_number)?.digest[..])?)
+    }
+
+    /// Get the number of the block a past ledger digest was created at,
+    /// or `None` if the digest is not a valid ledger digest.
+    pub fn get_digest_block_number(&self, digest: &[u8]) -> Result<Option<u32>, StorageError> {
+        Ok(self.storage.get(COL_DIGEST, digest)?.map(bytes_to_u32))
+    }
+
+    /// Returns the Merkle path of a commitment to the ledger digest as of a canon block,
+    /// if the commitment was in the ledger at that block.
+    pub fn prove_cm_at_height(&self, cm: &T::Commitment, block_number: u32) -> Result<MerklePath<P>, StorageError> {
+        let checkpoint = self.get_ledger_checkpoint(block_number)?;
+        let cm_bytes = to_bytes![cm]?;
+
+        let cm_index = match self.get_cm_index(&cm_bytes)? {
+            Some(cm_index) if cm_index < checkpoint.num_commitments as usize => cm_index,
+            _ => return Err(StorageError::MissingCommitment(hex::encode(cm_bytes), block_number)),
+        };
+
+        Ok(self
+            .cm_merkle_tree
+            .read_recursive()
+            .generate_historical_proof(checkpoint.num_commitments as usize, cm_index, cm)?)
+    }
+
+    /// Returns the operation that stores the ledger checkpoint of a canon block.
+    pub(crate) fn ledger_checkpoint_op(
+        block_number: u32,
+        num_commitments: usize,
+        digest: &MerkleTreeDigest<P>,
+    ) -> Result<Op, StorageError> {
+        let checkpoint = LedgerCheckpoint {
+            num_commitments: num_commitments as u32,
+            digest: to_bytes![digest]?,
+        };
+
+        Ok(Op::Insert {
+            col: COL_LEDGER_CHECKPOINT,
+            key: ledger_checkpoint_key(block_number),
+            value: to_bytes![checkpoint]?,
+        })
+    }
+
+    /// Store the missing ledger checkpoints of the canon blocks whose transactions are still stored.
+    ///
+    /// The number of commitments is counted back from the current commitment index, and the digest is
+    /// the past ledger digest created at the block, or computed from the commitment merkle tree.
+    pub(crate) fn backfill_ledger_checkpoints(&self) -> Result<(), StorageError> {
+        let latest_block_height = self.get_latest_block_height();
+        if self.storage.exists(COL_LEDGER_CHECKPOINT, &ledger_checkpoint_key(latest_block_height)) {
+            return Ok(());
+        }
+
+        let pruned_block_height = self.get_pruned_block_height()?;
+
+        // Past ledger digests are not removed with their blocks, so a block number can have several digests
+        let mut past_digests: HashMap<u32, Vec<Vec<u8>>> = HashMap::new();
+        for (digest, block_number_bytes) in self.storage.get_iter(COL_DIGEST)? {
+            past_digests
+                .entry(bytes_to_u32(block_number_bytes.to_vec()))
+                .or_default()
+                .push(digest.to_vec());
+        }
+
+        let merkle_tree = self.cm_merkle_tree.read();
+        let mut num_commitments = merkle_tree.len();
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for block_number in (1..=latest_block_height).rev() {
+            if !self.storage.exists(COL_LEDGER_CHECKPOINT, &ledger_checkpoint_key(block_number)) {
+                let digest = match past_digests.get(&block_number).map(Vec::as_slice) {
+                    Some([digest]) => FromBytes::read(&digest[..])?,
+                    _ => merkle_tree.historical_root(num_commitments)?,
+                };
+
+                database_transaction.push(Self::ledger_checkpoint_op(block_number, num_commitments, &digest)?);
+            }
+
+            // The number of commitments before a pruned block is unknown
+            if block_number <= pruned_block_height {
+                break;
+            }
+
+            for transaction in self.get_block_transactions(&self.get_block_hash(block_number)?)?.iter() {
+                num_commitments -= transaction.new_commitments().len();
+            }
+        }
+
+        // The genesis block transactions are never pruned
+        if pruned_block_height > 0 {
+            num_commitments = 0;
+            for transaction in self.get_block_transactions(&self.get_block_hash(0)?)?.iter() {
+                num_commitments += transaction.new_commitments().len();
+            }
+        }
+
+        if !self.storage.exists(COL_LEDGER_CHECKPOINT, &ledger_checkpoint_key(0)) {
+            let digest = merkle_tree.historical_root(num_commitments)?;
+            database_transaction.push(Self::ledger_checkpoint_op(0, num_commitments, &digest)?);
+        }
+
+        self.storage.write(database_transaction)
+    }
+}
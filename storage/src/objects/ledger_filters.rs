@@ -0,0 +1,192 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::BlockHeaderHash;
+
+use serde::{Deserialize, Serialize};
+
+/// The version of the persisted ledger filters. Filters of another version are rebuilt.
+pub const LEDGER_FILTERS_VERSION: u32 = 1;
+
+/// The number of blocks after which the ledger filters are persisted.
+pub const LEDGER_FILTERS_PERSIST_INTERVAL: u32 = 1000;
+
+/// The minimum number of items the ledger filters are sized for.
+const MIN_FILTER_CAPACITY: u64 = 1 << 16;
+
+/// The bloom filters of the serial numbers and memos of the ledger, which answer most of the
+/// existence checks of new transactions without reading the storage.
+///
+/// The filters may hold the items of decommitted blocks, which only causes false positives.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerFilters {
+    /// Version of the filters
+    pub version: u32,
+    /// Latest block whose serial numbers and memos are in the filters
+    pub block_height: u32,
+    /// Hash of the latest block whose serial numbers and memos are in the filters
+    pub block_hash: BlockHeaderHash,
+    /// Filter of the serial numbers
+    pub serial_numbers: BloomFilter,
+    /// Filter of the memos
+    pub memos: BloomFilter,
+}
+
+impl LedgerFilters {
+    /// Insert the serial numbers and memos of a block in the filters.
+    pub fn insert_block(&mut self, block_height: u32, block_hash: BlockHeaderHash, block_undo: &BlockUndo) {
+        for sn in &block_undo.serial_numbers {
+            self.serial_numbers.insert(sn);
+        }
+        for memo in &block_undo.memos {
+            self.memos.insert(memo);
+        }
+
+        self.block_height = block_height;
+        self.block_hash = block_hash;
+    }
+
+    /// Returns true if a filter holds more items than it was sized for.
+    pub fn is_saturated(&self) -> bool {
+        self.serial_numbers.is_saturated() || self.memos.is_saturated()
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns false if the serial number is not in the ledger, and true if it may be.
+    pub fn may_contain_sn(&self, sn_bytes: &[u8]) -> bool {
+        match &*self.filters.read_recursive() {
+            Some(filters) => filters.serial_numbers.contains(sn_bytes),
+            None => true,
+        }
+    }
+
+    /// Returns false if the memo is not in the ledger, and true if it may be.
+    pub fn may_contain_memo(&self, memo_bytes: &[u8]) -> bool {
+        match &*self.filters.read_recursive() {
+            Some(filters) => filters.memos.contains(memo_bytes),
+            None => true,
+        }
+    }
+
+    /// Insert the serial numbers and memos of a block that is being committed in the ledger filters,
+    /// and return the operation persisting the filters at every persist interval.
+    ///
+    /// The items are inserted before the block is written, so the filters never miss an item
+    /// of the storage, even if the block fails to be written.
+    pub(crate) fn update_filters_ops(
+        &self,
+        block_height: u32,
+        block_hash: &BlockHeaderHash,
+        block_undo: &BlockUndo,
+    ) -> Result<Vec<Op>, StorageError> {
+        let mut filters = self.filters.write();
+
+        let filters = match &mut *filters {
+            Some(filters) => filters,
+            None => return Ok(vec![]),
+        };
+
+        filters.insert_block(block_height, block_hash.clone(), block_undo);
+
+        if block_height % LEDGER_FILTERS_PERSIST_INTERVAL != 0 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Op::Insert {
+            col: COL_META,
+            key: KEY_LEDGER_FILTERS.as_bytes().to_vec(),
+            value: bincode::serialize(filters)?,
+        }])
+    }
+
+    /// Load the persisted ledger filters and insert the items of the blocks committed since they
+    /// were persisted. The filters are rebuilt from the storage if they are missing, of another
+    /// version, saturated, or were persisted on a chain that has since been reverted.
+    pub(crate) fn load_filters(&self) -> Result<(), StorageError> {
+        let persisted_filters = match self.storage.get(COL_META, KEY_LEDGER_FILTERS.as_bytes())? {
+            Some(filters_bytes) => bincode::deserialize::<LedgerFilters>(&filters_bytes).ok(),
+            None => None,
+        };
+
+        if let Some(mut filters) = persisted_filters {
+            if filters.version == LEDGER_FILTERS_VERSION && self.catch_up_filters(&mut filters)? {
+                *self.filters.write() = Some(filters);
+                return Ok(());
+            }
+        }
+
+        self.rebuild_filters()
+    }
+
+    /// Insert the items of the canon blocks following the latest block of the filters, and return
+    /// false if the filters do not match the canon chain.
+    fn catch_up_filters(&self, filters: &mut LedgerFilters) -> Result<bool, StorageError> {
+        let latest_block_height = self.get_latest_block_height();
+
+        if filters.block_height > latest_block_height
+            || self.get_block_hash(filters.block_height).ok().as_ref() != Some(&filters.block_hash)
+        {
+            return Ok(false);
+        }
+
+        for block_height in (filters.block_height + 1)..=latest_block_height {
+            match self.get_block_undo(block_height)? {
+                Some(block_undo) => filters.insert_block(block_height, self.get_block_hash(block_height)?, &block_undo),
+                None => return Ok(false),
+            }
+        }
+
+        Ok(!filters.is_saturated())
+    }
+
+    /// Rebuild the ledger filters from the serial numbers and memos of the storage, and persist them.
+    pub fn rebuild_filters(&self) -> Result<(), StorageError> {
+        let _block_lock = self.block_lock.lock();
+
+        let num_serial_numbers = self.storage.get_iter(COL_SERIAL_NUMBER)?.count() as u64;
+        let num_memos = self.storage.get_iter(COL_MEMO)?.count() as u64;
+
+        let latest_block_height = self.get_latest_block_height();
+        let mut filters = LedgerFilters {
+            version: LEDGER_FILTERS_VERSION,
+            block_height: latest_block_height,
+            block_hash: self.get_block_hash(latest_block_height)?,
+            serial_numbers: BloomFilter::new((2 * num_serial_numbers).max(MIN_FILTER_CAPACITY)),
+            memos: BloomFilter::new((2 * num_memos).max(MIN_FILTER_CAPACITY)),
+        };
+
+        for (sn, _) in self.storage.get_iter(COL_SERIAL_NUMBER)? {
+            filters.serial_numbers.insert(&sn);
+        }
+        for (memo, _) in self.storage.get_iter(COL_MEMO)? {
+            filters.memos.insert(&memo);
+        }
+
+        self.storage.write(DatabaseTransaction(vec![Op::Insert {
+            col: COL_META,
+            key: KEY_LEDGER_FILTERS.as_bytes().to_vec(),
+            value: bincode::serialize(&filters)?,
+        }]))?;
+
+        *self.filters.write() = Some(filters);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{
+    algorithms::LoadableMerkleParameters,
+    objects::{LedgerScheme, Transaction},
+};
+use snarkos_objects::DPCTransactions;
+use snarkos_utilities::{has_duplicates, to_bytes, ToBytes};
+
+use std::collections::HashSet;
+
+/// Pending transactions staged on top of a consistent view of the ledger.
+///
+/// The memory pool and the block producer stage the transactions selected for a block, so each
+/// candidate is validated against the ledger and the transactions staged before it, without
+/// writing to the storage. The staged transactions are then committed to the block as a batch,
+/// or discarded together. An overlay holds a ledger view, so it should be short-lived.
+pub struct LedgerOverlay<'a, T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> {
+    view: LedgerView<'a, T, P, S>,
+    network_id: Option<u8>,
+    serial_numbers: HashSet<Vec<u8>>,
+    commitments: HashSet<Vec<u8>>,
+    memos: HashSet<Vec<u8>>,
+    transactions: DPCTransactions<T>,
+}
+
+impl<'a, T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> LedgerOverlay<'a, T, P, S> {
+    /// Returns the ledger view the transactions are staged on.
+    pub fn view(&self) -> &LedgerView<'a, T, P, S> {
+        &self.view
+    }
+
+    /// Returns the staged transactions, in the order they were staged.
+    pub fn transactions(&self) -> &DPCTransactions<T> {
+        &self.transactions
+    }
+
+    /// Returns true if the serial number exists in the ledger or in a staged transaction.
+    pub fn contains_sn(&self, sn: &T::SerialNumber) -> Result<bool, StorageError> {
+        Ok(self.serial_numbers.contains(&to_bytes![sn]?) || self.view.contains_sn(sn))
+    }
+
+    /// Returns true if the commitment exists in the ledger or in a staged transaction.
+    pub fn contains_cm(&self, cm: &T::Commitment) -> Result<bool, StorageError> {
+        Ok(self.commitments.contains(&to_bytes![cm]?) || self.view.contains_cm(cm))
+    }
+
+    /// Returns true if the memo exists in the ledger or in a staged transaction.
+    pub fn contains_memo(&self, memo: &T::Memorandum) -> Result<bool, StorageError> {
+        Ok(self.memos.contains(&to_bytes![memo]?) || self.view.contains_memo(memo))
+    }
+
+    /// Returns true if the transaction can not be staged, as it spends a serial number or creates
+    /// a commitment or memo that already exists in the ledger or in a staged transaction.
+    pub fn conflicts(&self, transaction: &T) -> Result<bool, StorageError> {
+        // The transactions of a block belong to a single network
+        if self
+            .network_id
+            .map_or(false, |network_id| network_id != transaction.network_id())
+        {
+            return Ok(true);
+        }
+
+        let transaction_serial_numbers = transaction.old_serial_numbers();
+        let transaction_commitments = transaction.new_commitments();
+
+        if has_duplicates(transaction_serial_numbers) || has_duplicates(transaction_commitments) {
+            return Ok(true);
+        }
+
+        for sn in transaction_serial_numbers {
+            if self.contains_sn(sn)? {
+                return Ok(true);
+            }
+        }
+
+        for cm in transaction_commitments {
+            if self.contains_cm(cm)? {
+                return Ok(true);
+            }
+        }
+
+        self.contains_memo(transaction.memorandum())
+    }
+
+    /// Stage the transaction if it does not conflict, and return true if it was staged.
+    pub fn stage(&mut self, transaction: T) -> Result<bool, StorageError> {
+        if self.conflicts(&transaction)? {
+            return Ok(false);
+        }
+
+        for sn in transaction.old_serial_numbers() {
+            self.serial_numbers.insert(to_bytes![sn]?);
+        }
+
+        for cm in transaction.new_commitments() {
+            self.commitments.insert(to_bytes![cm]?);
+        }
+
+        self.memos.insert(to_bytes![transaction.memorandum()]?);
+        self.network_id = Some(transaction.network_id());
+        self.transactions.push(transaction);
+
+        Ok(true)
+    }
+
+    /// Discard the staged transactions, keeping the ledger view.
+    pub fn discard(&mut self) {
+        self.network_id = None;
+        self.serial_numbers.clear();
+        self.commitments.clear();
+        self.memos.clear();
+        self.transactions = DPCTransactions::new();
+    }
+
+    /// Release the ledger view and return the staged transactions, to be committed as a batch.
+    pub fn commit(self) -> DPCTransactions<T> {
+        self.transactions
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns an empty overlay on a consistent view of the ledger state, to validate
+    /// transactions against the ledger and each other before they are written in a block.
+    pub fn overlay(&self) -> LedgerOverlay<T, P, S> {
+        LedgerOverlay {
+            view: self.view(),
+            network_id: None,
+            serial_numbers: HashSet::new(),
+            commitments: HashSet::new(),
+            memos: HashSet::new(),
+            transactions: DPCTransactions::new(),
+        }
+    }
+}
@@ -27,10 +27,10 @@ use snarkos_utilities::{
     to_bytes,
 };
 
-use parking_lot::RwLock;
-use std::{fs, marker::PhantomData, path::PathBuf, sync::Arc};
+use parking_lot::{ReentrantMutex, RwLock};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc};
 
-impl<T: Transaction, P: LoadableMerkleParameters> LedgerScheme for Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> LedgerScheme for Ledger<T, P, S> {
     type Block = Block<Self::Transaction>;
     type Commitment = T::Commitment;
     type MerkleParameters = P;
@@ -45,8 +45,7 @@ impl<T: Transaction, P: LoadableMerkleParameters> LedgerScheme for Ledger<T, P>
         parameters: Self::MerkleParameters,
         genesis_block: Self::Block,
     ) -> Result<Self, LedgerError> {
-        fs::create_dir_all(&path).map_err(|err| LedgerError::Message(err.to_string()))?;
-        let storage = match Storage::open_cf(path, NUM_COLS) {
+        let storage = match S::open(path, NUM_COLS) {
             Ok(storage) => storage,
             Err(err) => return Err(LedgerError::StorageError(err)),
         };
@@ -58,17 +57,25 @@ impl<T: Transaction, P: LoadableMerkleParameters> LedgerScheme for Ledger<T, P>
         }
 
         let leaves: Vec<[u8; 32]> = vec![];
-        let empty_cm_merkle_tree = MerkleTree::<Self::MerkleParameters>::new(parameters.clone(), &leaves)?;
+        let empty_cm_merkle_tree = IncrementalMerkleTree::<Self::MerkleParameters>::new(parameters.clone(), &leaves)?;
 
         let ledger_storage = Self {
             latest_block_height: RwLock::new(0),
             storage: Arc::new(storage),
+            block_archive: None,
+            startup_check: None,
             cm_merkle_tree: RwLock::new(empty_cm_merkle_tree),
+            block_lock: ReentrantMutex::new(()),
+            filters: RwLock::new(None),
             ledger_parameters: parameters,
             _transaction: PhantomData,
         };
 
         ledger_storage.insert_and_commit(&genesis_block)?;
+        ledger_storage
+            .storage
+            .write(DatabaseTransaction(vec![set_schema_version(SCHEMA_VERSION)]))?;
+        ledger_storage.load_filters()?;
 
         Ok(ledger_storage)
     }
@@ -100,20 +107,24 @@ impl<T: Transaction, P: LoadableMerkleParameters> LedgerScheme for Ledger<T, P>
     }
 
     /// Returns true if the given serial number exists in the ledger.
+    /// The ledger filters answer without reading the storage if it does not exist.
     fn contains_sn(&self, sn: &Self::SerialNumber) -> bool {
-        self.storage.exists(COL_SERIAL_NUMBER, &to_bytes![sn].unwrap())
+        let sn_bytes = to_bytes![sn].unwrap();
+        self.may_contain_sn(&sn_bytes) && self.storage.exists(COL_SERIAL_NUMBER, &sn_bytes)
     }
 
     /// Returns true if the given memo exists in the ledger.
+    /// The ledger filters answer without reading the storage if it does not exist.
     fn contains_memo(&self, memo: &<Self::Transaction as Transaction>::Memorandum) -> bool {
-        self.storage.exists(COL_MEMO, &to_bytes![memo].unwrap())
+        let memo_bytes = to_bytes![memo].unwrap();
+        self.may_contain_memo(&memo_bytes) && self.storage.exists(COL_MEMO, &memo_bytes)
     }
 
     /// Returns the Merkle path to the latest ledger digest
     /// for a given commitment, if it exists in the ledger.
     fn prove_cm(&self, cm: &Self::Commitment) -> Result<Self::MerklePath, LedgerError> {
         let cm_index = self.get_cm_index(&to_bytes![cm]?)?.ok_or(LedgerError::InvalidCmIndex)?;
-        let result = self.cm_merkle_tree.read().generate_proof(cm_index, cm)?;
+        let result = self.cm_merkle_tree.read_recursive().generate_proof(cm_index, cm)?;
 
         Ok(result)
     }
@@ -0,0 +1,78 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::merkle_tree::{IncrementalMerkleTree, MerkleTreeDigest};
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+
+use parking_lot::{ReentrantMutexGuard, RwLockReadGuard};
+use std::ops::Deref;
+
+/// A consistent view of the ledger state at its latest block height.
+///
+/// Blocks are not committed or decommitted while a view is held, so the reads made through the
+/// view observe a single canon chain. Other reads and the connection of the next block proceed
+/// concurrently, and the block is published once the views are dropped, so views should be short-lived.
+/// The ledger must not be written to by the thread holding a view.
+pub struct LedgerView<'a, T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> {
+    ledger: &'a Ledger<T, P, S>,
+    block_height: RwLockReadGuard<'a, u32>,
+    cm_merkle_tree: RwLockReadGuard<'a, IncrementalMerkleTree<P>>,
+}
+
+impl<'a, T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> LedgerView<'a, T, P, S> {
+    /// Returns the latest block height of the view.
+    pub fn block_height(&self) -> u32 {
+        *self.block_height
+    }
+
+    /// Returns the ledger digest of the view.
+    pub fn digest(&self) -> MerkleTreeDigest<P> {
+        self.cm_merkle_tree.root()
+    }
+}
+
+impl<'a, T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Deref for LedgerView<'a, T, P, S> {
+    type Target = Ledger<T, P, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.ledger
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns a consistent view of the ledger state, for the readers that make several reads
+    /// such as the RPC endpoints and the memory pool.
+    pub fn view(&self) -> LedgerView<T, P, S> {
+        // Recursive reads, so the views can be nested and used by the ledger methods
+        let block_height = self.latest_block_height.read_recursive();
+        let cm_merkle_tree = self.cm_merkle_tree.read_recursive();
+
+        LedgerView {
+            ledger: self,
+            block_height,
+            cm_merkle_tree,
+        }
+    }
+
+    /// Acquire the exclusive right to connect and disconnect blocks until the guard is dropped.
+    ///
+    /// The ledger methods writing blocks acquire it themselves, so it is only needed to make a
+    /// sequence of writes exclusive, such as the blocks of a fork.
+    pub fn lock_blocks(&self) -> ReentrantMutexGuard<()> {
+        self.block_lock.lock()
+    }
+}
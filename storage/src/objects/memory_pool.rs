@@ -14,11 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{DatabaseTransaction, Ledger, Op, COL_META, KEY_MEMORY_POOL};
+use crate::{DatabaseTransaction, Ledger, Op, StorageBackend, COL_META, KEY_MEMORY_POOL};
 use snarkos_errors::storage::StorageError;
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Get the stored memory pool transactions.
     pub fn get_memory_pool(&self) -> Result<Vec<u8>, StorageError> {
         Ok(self.get(COL_META, &KEY_MEMORY_POOL.as_bytes().to_vec())?)
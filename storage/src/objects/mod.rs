@@ -15,29 +15,86 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 /// The methods defined in this module require direct access to the storage module.
+pub mod archive;
+pub use archive::*;
+
 pub mod block;
 pub use block::*;
 
+pub mod block_export;
+pub use block_export::*;
+
 pub mod block_header;
 pub use block_header::*;
 
 pub mod block_path;
 pub use block_path::*;
 
+pub mod block_undo;
+pub use block_undo::*;
+
+pub mod commitment_tree;
+pub use commitment_tree::*;
+
+pub mod consistency;
+pub use consistency::*;
+
 pub mod dpc_state;
 pub use dpc_state::*;
 
+pub mod garbage_collection;
+pub use garbage_collection::*;
+
+pub mod height_index;
+pub use height_index::*;
+
 pub mod insert_commit;
 pub use insert_commit::*;
 
+pub mod integrity;
+pub use integrity::*;
+
+pub mod ledger_checkpoint;
+pub use ledger_checkpoint::*;
+
+pub mod ledger_filters;
+pub use ledger_filters::*;
+
+pub mod ledger_overlay;
+pub use ledger_overlay::*;
+
 pub mod ledger_scheme;
 pub use ledger_scheme::*;
 
+pub mod ledger_view;
+pub use ledger_view::*;
+
 pub mod memory_pool;
 pub use memory_pool::*;
 
+pub mod prune;
+pub use prune::*;
+
+pub mod record_index;
+pub use record_index::*;
+
 pub mod records;
 pub use records::*;
 
+pub mod search_index;
+pub use search_index::*;
+
+pub mod sent_transactions;
+pub use sent_transactions::*;
+
+pub mod serial_number_tree;
+pub use serial_number_tree::*;
+
+pub mod snapshot;
+pub use snapshot::*;
+
 pub mod transaction;
 pub use transaction::*;
+
+pub mod wallet;
+pub use wallet::*;
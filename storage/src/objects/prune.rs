@@ -0,0 +1,97 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+
+/// Checks that a pruning retention keeps the transactions of the blocks the deepest fork can decommit.
+pub fn check_prune_retention(retention: u32) -> Result<(), StorageError> {
+    if retention < OLDEST_FORK_THRESHOLD {
+        return Err(StorageError::InvalidPruneRetention(retention, OLDEST_FORK_THRESHOLD));
+    }
+
+    Ok(())
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the height of the latest block whose transactions have been pruned.
+    /// Returns 0 if no blocks have been pruned, as the genesis block is never pruned.
+    pub fn get_pruned_block_height(&self) -> Result<u32, StorageError> {
+        match self.storage.get(COL_META, KEY_PRUNED_BLOCK_NUMBER.as_bytes())? {
+            Some(block_num_bytes) => Ok(bytes_to_u32(block_num_bytes)),
+            None => Ok(0),
+        }
+    }
+
+    /// Discard the transactions of canon blocks older than the latest `retention` blocks
+    /// and return the number of blocks pruned.
+    ///
+    /// Block headers, the block locators, the block height index, and the commitment, serial number,
+    /// memo, and digest indexes are kept, so new blocks can still be validated. The memo and commitment
+    /// search indexes are also kept, so pruned transactions can still be found. The undo records of
+    /// pruned blocks are kept, so they can still be decommitted, but they can no longer be committed
+    /// again, so `retention` must be at least `OLDEST_FORK_THRESHOLD`, the deepest fork.
+    pub fn prune_blocks(&self, retention: u32) -> Result<u32, StorageError> {
+        check_prune_retention(retention)?;
+
+        let _block_lock = self.block_lock.lock();
+
+        let latest_block_height = self.get_latest_block_height();
+        let pruned_block_height = self.get_pruned_block_height()?;
+
+        if latest_block_height <= retention || latest_block_height - retention <= pruned_block_height {
+            return Ok(0);
+        }
+
+        let new_pruned_block_height = latest_block_height - retention;
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for block_number in (pruned_block_height + 1)..=new_pruned_block_height {
+            let block_hash = self.get_block_hash(block_number)?;
+
+            let block_transactions = self.get_block_transactions(&block_hash)?;
+
+            for transaction in block_transactions.iter() {
+                database_transaction.push(Op::Delete {
+                    col: COL_TRANSACTION_LOCATION,
+                    key: transaction.transaction_id()?.to_vec(),
+                });
+            }
+            database_transaction.push_vec(self.unindex_transaction_height_ops(block_number, block_transactions.len()));
+
+            database_transaction.push(Op::Delete {
+                col: COL_BLOCK_TRANSACTIONS,
+                key: block_hash.0.to_vec(),
+            });
+            database_transaction.push(Op::Delete {
+                col: COL_BLOCK_ARCHIVE,
+                key: block_hash.0.to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_PRUNED_BLOCK_NUMBER.as_bytes().to_vec(),
+            value: new_pruned_block_height.to_le_bytes().to_vec(),
+        });
+
+        self.storage.write(database_transaction)?;
+
+        Ok(new_pruned_block_height - pruned_block_height)
+    }
+}
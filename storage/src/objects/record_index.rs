@@ -0,0 +1,146 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, dpc::Record, objects::Transaction};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+/// The block number and commitment of a record indexed for an account view key.
+pub type IndexedRecord = (u32, Vec<u8>);
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Register an account view key with the record index.
+    /// Records decryptable by the view key are indexed as blocks are committed.
+    pub fn register_view_key(&self, view_key: &[u8]) -> Result<(), StorageError> {
+        if self.storage.exists(COL_RECORD_INDEX, view_key) {
+            return Ok(());
+        }
+
+        let indexed_records: Vec<IndexedRecord> = vec![];
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        database_transaction.push(Op::Insert {
+            col: COL_RECORD_INDEX,
+            key: view_key.to_vec(),
+            value: bincode::serialize(&indexed_records)?,
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Remove an account view key and its indexed records from the record index.
+    pub fn unregister_view_key(&self, view_key: &[u8]) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        database_transaction.push(Op::Delete {
+            col: COL_RECORD_INDEX,
+            key: view_key.to_vec(),
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Get the account view keys registered with the record index.
+    pub fn get_registered_view_keys(&self) -> Result<Vec<Vec<u8>>, StorageError> {
+        Ok(self
+            .storage
+            .get_iter(COL_RECORD_INDEX)?
+            .map(|(view_key, _indexed_records)| view_key.to_vec())
+            .collect())
+    }
+
+    /// Get the records indexed for a registered account view key, ordered by block number.
+    /// Returns `None` if the view key is not registered.
+    pub fn get_indexed_records(&self, view_key: &[u8]) -> Result<Option<Vec<IndexedRecord>>, StorageError> {
+        match self.storage.get(COL_RECORD_INDEX, view_key)? {
+            Some(indexed_records_bytes) => Ok(Some(bincode::deserialize(&indexed_records_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store the records found in the block at `block_number` and add them to the index
+    /// of a registered account view key.
+    pub fn index_records<R: Record>(
+        &self,
+        view_key: &[u8],
+        block_number: u32,
+        records: &[R],
+    ) -> Result<(), StorageError> {
+        let mut indexed_records = match self.get_indexed_records(view_key)? {
+            Some(indexed_records) => indexed_records,
+            None => return Err(StorageError::UnregisteredViewKey(hex::encode(view_key))),
+        };
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for record in records {
+            let commitment = to_bytes![record.commitment()]?.to_vec();
+
+            if indexed_records
+                .iter()
+                .any(|(_, indexed_commitment)| indexed_commitment == &commitment)
+            {
+                continue;
+            }
+
+            database_transaction.push(Op::Insert {
+                col: COL_RECORDS,
+                key: commitment.clone(),
+                value: to_bytes![record]?.to_vec(),
+            });
+
+            indexed_records.push((block_number, commitment));
+        }
+
+        indexed_records.sort_by_key(|(indexed_block_number, _)| *indexed_block_number);
+
+        database_transaction.push(Op::Insert {
+            col: COL_RECORD_INDEX,
+            key: view_key.to_vec(),
+            value: bincode::serialize(&indexed_records)?,
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Returns the operations that remove the records indexed at or above `block_number`
+    /// from the index of every registered account view key.
+    pub(crate) fn unindex_records_ops(&self, block_number: u32) -> Result<Vec<Op>, StorageError> {
+        let mut ops = vec![];
+
+        for (view_key, indexed_records_bytes) in self.storage.get_iter(COL_RECORD_INDEX)? {
+            let indexed_records: Vec<IndexedRecord> = bincode::deserialize(&indexed_records_bytes)?;
+            let num_indexed_records = indexed_records.len();
+
+            let indexed_records: Vec<IndexedRecord> = indexed_records
+                .into_iter()
+                .filter(|(indexed_block_number, _)| *indexed_block_number < block_number)
+                .collect();
+
+            if indexed_records.len() != num_indexed_records {
+                ops.push(Op::Insert {
+                    col: COL_RECORD_INDEX,
+                    key: view_key.to_vec(),
+                    value: bincode::serialize(&indexed_records)?,
+                });
+            }
+        }
+
+        Ok(ops)
+    }
+}
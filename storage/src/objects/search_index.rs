@@ -0,0 +1,175 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::DPCTransactions;
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use std::convert::TryInto;
+
+/// A canon transaction found in the memo or commitment search index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedTransaction {
+    /// The memo or commitment of the transaction the search matched
+    pub key: Vec<u8>,
+    /// Number of the block holding the transaction
+    pub block_number: u32,
+    /// Id of the transaction
+    pub transaction_id: Vec<u8>,
+}
+
+/// Returns the value of a transaction in the memo and commitment search indexes.
+/// The block number is kept with the transaction id, so it is still known once the block is pruned.
+pub fn search_index_value(block_number: u32, transaction_id: &[u8]) -> Vec<u8> {
+    [&block_number.to_be_bytes()[..], transaction_id].concat()
+}
+
+/// Returns the transaction of an entry in the memo or commitment search index.
+fn indexed_transaction(key: &[u8], value: &[u8]) -> IndexedTransaction {
+    IndexedTransaction {
+        key: key.to_vec(),
+        block_number: u32::from_be_bytes(
+            value[0..4]
+                .try_into()
+                .expect("search index values start with a block number"),
+        ),
+        transaction_id: value[4..].to_vec(),
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns the operations that add the memos and commitments of the transactions of a canon block
+    /// to the search indexes.
+    pub(crate) fn index_transaction_search_ops(
+        block_number: u32,
+        transactions: &DPCTransactions<T>,
+    ) -> Result<Vec<Op>, StorageError> {
+        let mut ops = vec![];
+
+        for transaction in transactions.iter() {
+            let value = search_index_value(block_number, &transaction.transaction_id()?);
+
+            ops.push(Op::Insert {
+                col: COL_MEMO_SEARCH_INDEX,
+                key: to_bytes![transaction.memorandum()]?.to_vec(),
+                value: value.clone(),
+            });
+
+            for cm in transaction.new_commitments() {
+                ops.push(Op::Insert {
+                    col: COL_COMMITMENT_SEARCH_INDEX,
+                    key: to_bytes![cm]?.to_vec(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        Ok(ops)
+    }
+
+    /// Returns the operations that remove the memos and commitments of a decommitted block from the search indexes.
+    pub(crate) fn unindex_transaction_search_ops(block_undo: &BlockUndo) -> Vec<Op> {
+        let memo_ops = block_undo.memos.iter().map(|memo| Op::Delete {
+            col: COL_MEMO_SEARCH_INDEX,
+            key: memo.clone(),
+        });
+        let commitment_ops = block_undo.commitments.iter().map(|cm| Op::Delete {
+            col: COL_COMMITMENT_SEARCH_INDEX,
+            key: cm.clone(),
+        });
+
+        memo_ops.chain(commitment_ops).collect()
+    }
+
+    /// Get the canon transaction with the given memo, if it is indexed.
+    pub fn get_memo_transaction(&self, memo: &[u8]) -> Result<Option<IndexedTransaction>, StorageError> {
+        Ok(self
+            .storage
+            .get(COL_MEMO_SEARCH_INDEX, memo)?
+            .map(|value| indexed_transaction(memo, &value)))
+    }
+
+    /// Get the canon transaction that created the given record commitment, if it is indexed.
+    pub fn get_commitment_transaction(&self, cm: &[u8]) -> Result<Option<IndexedTransaction>, StorageError> {
+        Ok(self
+            .storage
+            .get(COL_COMMITMENT_SEARCH_INDEX, cm)?
+            .map(|value| indexed_transaction(cm, &value)))
+    }
+
+    /// Returns an iterator over the canon transactions whose memo starts with the given prefix,
+    /// in memo order.
+    pub fn search_memos(&self, prefix: &[u8]) -> Result<impl Iterator<Item = IndexedTransaction> + '_, StorageError> {
+        self.search_index(COL_MEMO_SEARCH_INDEX, prefix)
+    }
+
+    /// Returns an iterator over the canon transactions that created a record commitment starting with
+    /// the given prefix, in commitment order.
+    pub fn search_commitments(
+        &self,
+        prefix: &[u8],
+    ) -> Result<impl Iterator<Item = IndexedTransaction> + '_, StorageError> {
+        self.search_index(COL_COMMITMENT_SEARCH_INDEX, prefix)
+    }
+
+    fn search_index(
+        &self,
+        col: u32,
+        prefix: &[u8],
+    ) -> Result<impl Iterator<Item = IndexedTransaction> + '_, StorageError> {
+        let prefix = prefix.to_vec();
+
+        Ok(self
+            .storage
+            .get_iter_from(col, &prefix)?
+            .take_while(move |(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| indexed_transaction(&key, &value)))
+    }
+
+    /// Index the memos and commitments of the canon blocks whose transactions are still stored,
+    /// if the search indexes were not built yet.
+    ///
+    /// The genesis block transactions are never pruned, so the indexes are built if the memo of the
+    /// first genesis transaction is missing.
+    pub(crate) fn backfill_search_indexes(&self) -> Result<(), StorageError> {
+        let genesis_transactions = self.get_block_transactions(&self.get_block_hash(0)?)?;
+        let genesis_memo = match genesis_transactions.iter().next() {
+            Some(transaction) => to_bytes![transaction.memorandum()]?,
+            None => return Ok(()),
+        };
+
+        if self.storage.exists(COL_MEMO_SEARCH_INDEX, &genesis_memo) {
+            return Ok(());
+        }
+
+        let pruned_block_height = self.get_pruned_block_height()?;
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for block_number in 0..=self.get_latest_block_height() {
+            // Pruned blocks no longer have transactions
+            if block_number > 0 && block_number <= pruned_block_height {
+                continue;
+            }
+
+            let transactions = self.get_block_transactions(&self.get_block_hash(block_number)?)?;
+            database_transaction.push_vec(Self::index_transaction_search_ops(block_number, &transactions)?);
+        }
+
+        self.storage.write(database_transaction)
+    }
+}
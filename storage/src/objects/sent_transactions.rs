@@ -0,0 +1,85 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+
+use serde::{Deserialize, Serialize};
+
+/// A transaction sent by the node that is not yet in a canon block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SentTransaction {
+    /// The serialized transaction
+    pub transaction: Vec<u8>,
+    /// The time the transaction was sent, in seconds since the epoch
+    pub sent_at: i64,
+    /// The time the transaction was last broadcast to the peers, in seconds since the epoch
+    pub broadcast_at: i64,
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get a transaction sent by the node, given its transaction id.
+    pub fn get_sent_transaction(&self, transaction_id: &[u8]) -> Result<Option<SentTransaction>, StorageError> {
+        match self.storage.get(COL_SENT_TRANSACTIONS, transaction_id)? {
+            Some(sent_transaction_bytes) => Ok(Some(bincode::deserialize(&sent_transaction_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the transactions sent by the node that are not yet confirmed, by transaction id.
+    pub fn get_sent_transactions(&self) -> Result<Vec<(Vec<u8>, SentTransaction)>, StorageError> {
+        let mut sent_transactions = vec![];
+
+        for (transaction_id, sent_transaction_bytes) in self.storage.get_iter(COL_SENT_TRANSACTIONS)? {
+            sent_transactions.push((transaction_id.to_vec(), bincode::deserialize(&sent_transaction_bytes)?));
+        }
+
+        Ok(sent_transactions)
+    }
+
+    /// Store the transactions sent by the node, replacing the stored transactions of the same ids.
+    pub fn store_sent_transactions(
+        &self,
+        sent_transactions: &[(Vec<u8>, SentTransaction)],
+    ) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for (transaction_id, sent_transaction) in sent_transactions {
+            database_transaction.push(Op::Insert {
+                col: COL_SENT_TRANSACTIONS,
+                key: transaction_id.clone(),
+                value: bincode::serialize(sent_transaction)?,
+            });
+        }
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Remove the transactions sent by the node of the given transaction ids.
+    pub fn remove_sent_transactions(&self, transaction_ids: &[Vec<u8>]) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for transaction_id in transaction_ids {
+            database_transaction.push(Op::Delete {
+                col: COL_SENT_TRANSACTIONS,
+                key: transaction_id.clone(),
+            });
+        }
+
+        self.storage.write(database_transaction)
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::BlockHeaderHash;
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of serial numbers inserted in each write when the serial number tree is built.
+const SERIAL_NUMBER_TREE_BATCH_SIZE: usize = 1000;
+
+/// A proof that a serial number is or is not spent in the ledger as of a canon block.
+///
+/// The proof is checked against the root of the serial number tree, so a light client that
+/// trusts the root can check whether a record is unspent without the serial number set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialNumberProof {
+    /// The height of the latest canon block when the proof was generated
+    pub block_height: u32,
+    /// The hash of the latest canon block when the proof was generated
+    pub block_hash: BlockHeaderHash,
+    /// The root of the serial number tree
+    pub root: [u8; 32],
+    /// True if the serial number is in the ledger
+    pub is_spent: bool,
+    /// The path from the serial number leaf to the root
+    pub proof: SparseMerkleProof,
+}
+
+impl SerialNumberProof {
+    /// Returns true if the proof shows whether the given serialized serial number is spent.
+    pub fn verify(&self, sn_bytes: &[u8]) -> bool {
+        if self.is_spent {
+            self.proof.verify_membership(&self.root, sn_bytes)
+        } else {
+            self.proof.verify_non_membership(&self.root, sn_bytes)
+        }
+    }
+}
+
+/// Insert serialized serial numbers into the serial number tree of a storage.
+/// The tree nodes are written in batches, so the pending nodes stay small.
+pub fn insert_serial_numbers<S: StorageBackend>(storage: &S, serial_numbers: &[Vec<u8>]) -> Result<(), StorageError> {
+    for batch in serial_numbers.chunks(SERIAL_NUMBER_TREE_BATCH_SIZE) {
+        let mut update = SparseMerkleTreeUpdate::new(storage, COL_SERIAL_NUMBER_TREE);
+        for sn in batch {
+            update.insert(sn)?;
+        }
+
+        storage.write(DatabaseTransaction(update.into_ops()))?;
+    }
+
+    Ok(())
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the root of the serial number tree.
+    pub fn get_serial_number_root(&self) -> Result<[u8; 32], StorageError> {
+        sparse_merkle_root(&*self.storage, COL_SERIAL_NUMBER_TREE)
+    }
+
+    /// Returns the proof that a serial number is or is not spent as of the latest canon block.
+    pub fn prove_sn(&self, sn: &T::SerialNumber) -> Result<SerialNumberProof, StorageError> {
+        // The serial number tree is written before the block height is published
+        let _block_lock = self.block_lock.lock();
+
+        let block_height = self.get_latest_block_height();
+        let (is_spent, proof) = sparse_merkle_proof(&*self.storage, COL_SERIAL_NUMBER_TREE, &to_bytes![sn]?)?;
+
+        Ok(SerialNumberProof {
+            block_height,
+            block_hash: self.get_block_hash(block_height)?,
+            root: self.get_serial_number_root()?,
+            is_spent,
+            proof,
+        })
+    }
+
+    /// Returns the operations that insert the given serialized serial numbers into the serial number tree,
+    /// or remove them from it.
+    pub(crate) fn serial_number_tree_ops(
+        &self,
+        serial_numbers: &[Vec<u8>],
+        is_spent: bool,
+    ) -> Result<Vec<Op>, StorageError> {
+        let mut update = SparseMerkleTreeUpdate::new(&*self.storage, COL_SERIAL_NUMBER_TREE);
+
+        for sn in serial_numbers {
+            if is_spent {
+                update.insert(sn)?;
+            } else {
+                update.remove(sn)?;
+            }
+        }
+
+        Ok(update.into_ops())
+    }
+}
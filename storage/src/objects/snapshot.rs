@@ -0,0 +1,477 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::{crh::double_sha256, merkle_tree::IncrementalMerkleTree};
+use snarkos_errors::{objects::BlockError, storage::StorageError};
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction, parameters::Parameters};
+use snarkos_objects::{Block, BlockHeader, BlockHeaderHash, DPCTransactions};
+use snarkos_parameters::LedgerMerkleTreeParameters;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use parking_lot::{ReentrantMutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{fs, marker::PhantomData, path::Path, sync::Arc};
+
+/// The current version of the ledger snapshot format.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The ledger state at a given block height.
+///
+/// A snapshot holds every block header, but only the transactions of the genesis block,
+/// so a ledger imported from a snapshot behaves as if it were pruned up to the snapshot height.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerSnapshot {
+    /// The version of the snapshot format
+    pub version: u32,
+    /// The block height of the snapshot
+    pub block_height: u32,
+    /// The serialized block headers from the genesis block up to the block height
+    pub block_headers: Vec<Vec<u8>>,
+    /// The serialized transactions of the genesis block
+    pub genesis_transactions: Vec<u8>,
+    /// The commitments in the ledger, in order of insertion
+    pub commitments: Vec<Vec<u8>>,
+    /// The serial numbers in the ledger, in order of insertion
+    pub serial_numbers: Vec<Vec<u8>>,
+    /// The memos in the ledger, in order of insertion
+    pub memos: Vec<Vec<u8>>,
+    /// The past ledger digests and the block numbers they were created at
+    pub past_digests: Vec<(Vec<u8>, u32)>,
+    /// The ledger digest at the block height
+    pub digest: Vec<u8>,
+}
+
+impl LedgerSnapshot {
+    /// Returns the hash of the ledger state of the snapshot: its commitments, serial numbers, memos and digests.
+    ///
+    /// The block headers do not commit to the ledger state, so the state hash is the only anchor of the state
+    /// of a snapshot. It must be taken from a trusted source, such as the `snapshot_checkpoint` of a trusted node.
+    pub fn state_hash(&self) -> Result<[u8; 32], StorageError> {
+        let state = bincode::serialize(&(
+            self.block_height,
+            &self.commitments,
+            &self.serial_numbers,
+            &self.memos,
+            &self.past_digests,
+            &self.digest,
+        ))?;
+
+        Ok(double_sha256(&state))
+    }
+}
+
+/// The block and the ledger state a snapshot is trusted to hold, given by the user from a trusted source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotCheckpoint {
+    /// The height of the trusted block, the height of the snapshot
+    pub block_height: u32,
+    /// The hash of the trusted block
+    pub block_hash: BlockHeaderHash,
+    /// The hash of the trusted ledger state at the block height, as returned by `LedgerSnapshot::state_hash`
+    pub state_hash: [u8; 32],
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Returns the checkpoint of the ledger state at a given block height, which the nodes importing
+    /// a snapshot of this height from an untrusted source check the snapshot against.
+    pub fn snapshot_checkpoint(&self, block_height: u32) -> Result<SnapshotCheckpoint, StorageError> {
+        let snapshot = self.snapshot(block_height)?;
+
+        Ok(SnapshotCheckpoint {
+            block_height,
+            block_hash: self.get_block_hash(block_height)?,
+            state_hash: snapshot.state_hash()?,
+        })
+    }
+
+    /// Write a snapshot of the ledger state at a given block height to a file.
+    ///
+    /// The file holds a checksum of the snapshot followed by the serialized snapshot.
+    pub fn export_snapshot<PATH: AsRef<Path>>(&self, path: PATH, block_height: u32) -> Result<(), StorageError> {
+        let snapshot = self.snapshot(block_height)?;
+
+        let serialized_snapshot = bincode::serialize(&snapshot)?;
+
+        let mut snapshot_bytes = double_sha256(&serialized_snapshot).to_vec();
+        snapshot_bytes.extend(serialized_snapshot);
+
+        fs::write(path, snapshot_bytes)?;
+
+        Ok(())
+    }
+
+    /// Returns the snapshot of the ledger state at a given block height.
+    ///
+    /// The ledger state at the block height is found by discounting the transactions of the later blocks,
+    /// so the block height must not be below the pruned block height.
+    fn snapshot(&self, block_height: u32) -> Result<LedgerSnapshot, StorageError> {
+        let latest_block_height = self.get_latest_block_height();
+        if block_height > latest_block_height {
+            return Err(StorageError::BlockError(BlockError::InvalidBlockNumber(block_height)));
+        }
+
+        let pruned_block_height = self.get_pruned_block_height()?;
+        if block_height < pruned_block_height {
+            return Err(StorageError::InvalidSnapshot(format!(
+                "the transactions after block {} are pruned up to block {}, which is the lowest snapshot height",
+                block_height, pruned_block_height
+            )));
+        }
+
+        // Count the serial numbers, commitments, and memos up to the block height
+
+        let mut sn_count = self.current_sn_index()?;
+        let mut cm_count = self.current_cm_index()?;
+        let mut memo_count = self.current_memo_index()?;
+
+        for block_number in (block_height + 1)..=latest_block_height {
+            for transaction in self.get_block_transactions(&self.get_block_hash(block_number)?)?.0 {
+                sn_count -= transaction.old_serial_numbers().len();
+                cm_count -= transaction.new_commitments().len();
+                memo_count -= 1;
+            }
+        }
+
+        let serial_numbers = self.get_indexed_keys(COL_SERIAL_NUMBER, sn_count)?;
+        let commitments = self.get_indexed_keys(COL_COMMITMENT, cm_count)?;
+        let memos = self.get_indexed_keys(COL_MEMO, memo_count)?;
+
+        let mut past_digests = vec![];
+        for (digest, block_number_bytes) in self.storage.get_iter(COL_DIGEST)? {
+            let block_number = bytes_to_u32(block_number_bytes.to_vec());
+            if block_number < block_height {
+                past_digests.push((digest.to_vec(), block_number));
+            }
+        }
+        past_digests.sort_by(|(_, i), (_, j)| i.cmp(j));
+
+        let digest = to_bytes![Self::build_snapshot_merkle_tree(&self.ledger_parameters, &commitments)?.root()]?;
+
+        let mut block_headers = Vec::with_capacity(block_height as usize + 1);
+        for block_number in 0..=block_height {
+            let block_header = self.get_block_header(&self.get_block_hash(block_number)?)?;
+            block_headers.push(to_bytes![block_header]?);
+        }
+
+        let genesis_transactions = to_bytes![self.get_block_transactions(&self.get_block_hash(0)?)?]?;
+
+        Ok(LedgerSnapshot {
+            version: SNAPSHOT_VERSION,
+            block_height,
+            block_headers,
+            genesis_transactions,
+            commitments,
+            serial_numbers,
+            memos,
+            past_digests,
+            digest,
+        })
+    }
+
+    /// Create a new ledger at a given path from a snapshot file, after verifying the
+    /// snapshot checksum, the block header chain, and the ledger state against a trusted checkpoint.
+    ///
+    /// The block header chain must start from the given serialized genesis block, the genesis block of the
+    /// network, and end at the block of the checkpoint. Every other block header is checked by `verify_header`
+    /// against its parent block header, which is expected to verify the proof of work and the difficulty of the block.
+    ///
+    /// The block headers do not commit to the ledger state, so the commitments, serial numbers, memos and
+    /// digests of the snapshot are only as trusted as the state hash of the checkpoint.
+    pub fn import_snapshot<PATH: AsRef<Path>, SNAPSHOT: AsRef<Path>, F: Fn(&BlockHeader, &BlockHeader) -> bool>(
+        path: PATH,
+        snapshot_path: SNAPSHOT,
+        genesis_block: &[u8],
+        checkpoint: &SnapshotCheckpoint,
+        verify_header: F,
+    ) -> Result<Self, StorageError> {
+        let snapshot_bytes = fs::read(snapshot_path)?;
+        if snapshot_bytes.len() < 32 {
+            return Err(StorageError::InvalidSnapshot("missing checksum".into()));
+        }
+
+        let (checksum, serialized_snapshot) = snapshot_bytes.split_at(32);
+        if checksum != double_sha256(serialized_snapshot) {
+            return Err(StorageError::InvalidSnapshot("invalid checksum".into()));
+        }
+
+        let snapshot: LedgerSnapshot = bincode::deserialize(serialized_snapshot)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(StorageError::InvalidSnapshotVersion(snapshot.version));
+        }
+
+        if snapshot.block_headers.len() != snapshot.block_height as usize + 1 {
+            return Err(StorageError::InvalidSnapshot("invalid number of block headers".into()));
+        }
+
+        if checkpoint.block_height != snapshot.block_height {
+            return Err(StorageError::InvalidSnapshot(format!(
+                "the snapshot height {} differs from the checkpoint height {}",
+                snapshot.block_height, checkpoint.block_height
+            )));
+        }
+
+        // Verify the ledger state
+
+        if snapshot.state_hash()? != checkpoint.state_hash {
+            return Err(StorageError::InvalidSnapshot(
+                "the ledger state differs from the checkpoint".into(),
+            ));
+        }
+
+        // Verify the genesis block
+
+        let genesis_block: Block<T> = FromBytes::read(genesis_block)?;
+        if snapshot.block_headers[0] != to_bytes![genesis_block.header]?
+            || snapshot.genesis_transactions != to_bytes![genesis_block.transactions]?
+        {
+            return Err(StorageError::InvalidSnapshot("the genesis block differs".into()));
+        }
+
+        // Verify the block header chain
+
+        let mut block_headers: Vec<BlockHeader> = Vec::with_capacity(snapshot.block_headers.len());
+        for (block_number, block_header_bytes) in snapshot.block_headers.iter().enumerate() {
+            let block_header = BlockHeader::read(&block_header_bytes[..])?;
+
+            if let Some(previous_block_header) = block_headers.last() {
+                if block_header.previous_block_hash != previous_block_header.get_hash() {
+                    return Err(StorageError::InvalidSnapshot(
+                        "block headers do not form a chain".into(),
+                    ));
+                }
+
+                if !verify_header(&block_header, previous_block_header) {
+                    return Err(StorageError::InvalidSnapshot(format!(
+                        "invalid proof of work for block {}",
+                        block_number
+                    )));
+                }
+            }
+
+            block_headers.push(block_header);
+        }
+
+        if block_headers[checkpoint.block_height as usize].get_hash() != checkpoint.block_hash {
+            return Err(StorageError::InvalidSnapshot(format!(
+                "block {} differs from the checkpoint",
+                checkpoint.block_height
+            )));
+        }
+
+        // Check the ledger digest is the root of the commitments
+
+        let crh = P::H::from(FromBytes::read(&LedgerMerkleTreeParameters::load_bytes()?[..])?);
+        let ledger_parameters = P::from(crh);
+
+        let merkle_tree = Self::build_snapshot_merkle_tree(&ledger_parameters, &snapshot.commitments)?;
+        if to_bytes![merkle_tree.root()]? != snapshot.digest {
+            return Err(StorageError::InvalidSnapshot("invalid ledger digest".into()));
+        }
+
+        let storage = S::open(path.as_ref(), NUM_COLS)?;
+
+        if storage.get(COL_META, KEY_BEST_BLOCK_NUMBER.as_bytes())?.is_some() {
+            return Err(StorageError::ExistingLedger(path.as_ref().display().to_string()));
+        }
+
+        // Build the serial number tree before the ledger state is written, so an interrupted
+        // import does not leave a ledger without it.
+
+        insert_serial_numbers(&storage, &snapshot.serial_numbers)?;
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        // Insert the block headers and the genesis block transactions
+
+        for (block_number, block_header) in block_headers.iter().enumerate() {
+            let block_hash = block_header.get_hash();
+
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_HEADER,
+                key: block_hash.0.to_vec(),
+                value: to_bytes![block_header]?.to_vec(),
+            });
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_LOCATOR,
+                key: block_hash.0.to_vec(),
+                value: (block_number as u32).to_le_bytes().to_vec(),
+            });
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_LOCATOR,
+                key: (block_number as u32).to_le_bytes().to_vec(),
+                value: block_hash.0.to_vec(),
+            });
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_HEIGHT_INDEX,
+                key: block_height_index_key(block_number as u32),
+                value: block_hash.0.to_vec(),
+            });
+
+            if block_number > 0 {
+                database_transaction.push(Op::Insert {
+                    col: COL_CHILD_HASHES,
+                    key: block_header.previous_block_hash.0.to_vec(),
+                    value: bincode::serialize(&vec![block_hash])?,
+                });
+            }
+        }
+
+        let genesis_block_hash: BlockHeaderHash = block_headers[0].get_hash();
+        let genesis_transactions = DPCTransactions::<T>::read(&snapshot.genesis_transactions[..])?;
+
+        for (index, transaction) in genesis_transactions.iter().enumerate() {
+            let transaction_location = TransactionLocation {
+                index: index as u32,
+                block_hash: genesis_block_hash.0,
+            };
+            database_transaction.push(Op::Insert {
+                col: COL_TRANSACTION_LOCATION,
+                key: transaction.transaction_id()?.to_vec(),
+                value: to_bytes![transaction_location]?.to_vec(),
+            });
+            database_transaction.push(Op::Insert {
+                col: COL_TRANSACTION_HEIGHT_INDEX,
+                key: transaction_height_index_key(0, index as u32),
+                value: transaction.transaction_id()?.to_vec(),
+            });
+        }
+        database_transaction.push_vec(Self::index_transaction_search_ops(0, &genesis_transactions)?);
+
+        database_transaction.push(Op::Insert {
+            col: COL_BLOCK_TRANSACTIONS,
+            key: genesis_block_hash.0.to_vec(),
+            value: snapshot.genesis_transactions.clone(),
+        });
+
+        // Insert the ledger state
+
+        for (col, keys) in &[
+            (COL_SERIAL_NUMBER, &snapshot.serial_numbers),
+            (COL_COMMITMENT, &snapshot.commitments),
+            (COL_MEMO, &snapshot.memos),
+        ] {
+            for (index, key) in keys.iter().enumerate() {
+                database_transaction.push(Op::Insert {
+                    col: *col,
+                    key: key.clone(),
+                    value: (index as u32).to_le_bytes().to_vec(),
+                });
+            }
+        }
+
+        database_transaction.push_vec(Self::rebuild_commitment_tree_ops(&storage, &merkle_tree)?);
+        database_transaction.push(Self::ledger_checkpoint_op(
+            snapshot.block_height,
+            merkle_tree.len(),
+            &merkle_tree.root(),
+        )?);
+
+        for (digest, block_number) in &snapshot.past_digests {
+            database_transaction.push(Op::Insert {
+                col: COL_DIGEST,
+                key: digest.clone(),
+                value: block_number.to_le_bytes().to_vec(),
+            });
+        }
+        database_transaction.push(Op::Insert {
+            col: COL_DIGEST,
+            key: snapshot.digest.clone(),
+            value: snapshot.block_height.to_le_bytes().to_vec(),
+        });
+
+        // Update the database state for current indexes
+
+        for (key, index) in &[
+            (KEY_CURR_SN_INDEX, snapshot.serial_numbers.len()),
+            (KEY_CURR_CM_INDEX, snapshot.commitments.len()),
+            (KEY_CURR_MEMO_INDEX, snapshot.memos.len()),
+        ] {
+            database_transaction.push(Op::Insert {
+                col: COL_META,
+                key: key.as_bytes().to_vec(),
+                value: (*index as u32).to_le_bytes().to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_CURR_DIGEST.as_bytes().to_vec(),
+            value: snapshot.digest.clone(),
+        });
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_PRUNED_BLOCK_NUMBER.as_bytes().to_vec(),
+            value: snapshot.block_height.to_le_bytes().to_vec(),
+        });
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_BEST_BLOCK_NUMBER.as_bytes().to_vec(),
+            value: snapshot.block_height.to_le_bytes().to_vec(),
+        });
+        database_transaction.push(set_schema_version(SCHEMA_VERSION));
+
+        storage.write(database_transaction)?;
+
+        let ledger = Self {
+            latest_block_height: RwLock::new(snapshot.block_height),
+            storage: Arc::new(storage),
+            block_archive: Some(BlockArchive::open(BlockArchive::archive_path(path.as_ref()))),
+            startup_check: None,
+            cm_merkle_tree: RwLock::new(merkle_tree),
+            block_lock: ReentrantMutex::new(()),
+            filters: RwLock::new(None),
+            ledger_parameters,
+            _transaction: PhantomData,
+        };
+
+        ledger.load_filters()?;
+
+        Ok(ledger)
+    }
+
+    /// Get the keys of a column indexed by insertion order, up to a given count.
+    fn get_indexed_keys(&self, col: u32, count: usize) -> Result<Vec<Vec<u8>>, StorageError> {
+        let mut keys_and_indices = vec![];
+
+        for (key, index_value) in self.storage.get_iter(col)? {
+            let index = bytes_to_u32(index_value.to_vec()) as usize;
+            if index < count {
+                keys_and_indices.push((key.to_vec(), index));
+            }
+        }
+
+        keys_and_indices.sort_by(|(_, i), (_, j)| i.cmp(j));
+
+        Ok(keys_and_indices.into_iter().map(|(key, _)| key).collect())
+    }
+
+    /// Build the commitment merkle tree of serialized snapshot commitments.
+    fn build_snapshot_merkle_tree(
+        parameters: &P,
+        commitments: &[Vec<u8>],
+    ) -> Result<IncrementalMerkleTree<P>, StorageError> {
+        let commitments = commitments
+            .iter()
+            .map(|cm| T::Commitment::read(&cm[..]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(IncrementalMerkleTree::new(parameters.clone(), &commitments)?)
+    }
+}
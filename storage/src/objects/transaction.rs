@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Ledger, TransactionLocation, COL_TRANSACTION_LOCATION};
+use crate::{Ledger, StorageBackend, TransactionLocation, COL_TRANSACTION_LOCATION};
 use snarkos_errors::storage::StorageError;
 use snarkos_models::{
     algorithms::LoadableMerkleParameters,
@@ -27,7 +27,7 @@ use snarkos_utilities::{
     to_bytes,
 };
 
-impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
     /// Returns a transaction location given the transaction ID if it exists. Returns `None` otherwise.
     pub fn get_transaction_location(&self, transaction_id: &[u8]) -> Result<Option<TransactionLocation>, StorageError> {
         match self.storage.get(COL_TRANSACTION_LOCATION, &transaction_id)? {
@@ -0,0 +1,169 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, dpc::Record, objects::Transaction};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use serde::{Deserialize, Serialize};
+
+/// The spend of a wallet record, found by its serial number in a canon block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletSpend {
+    /// The number of the block spending the record
+    pub block_number: u32,
+    /// The id of the transaction spending the record
+    pub transaction_id: Vec<u8>,
+}
+
+/// A record received by a wallet account in a canon block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletRecord {
+    /// The number of the block of the record
+    pub block_number: u32,
+    /// The id of the transaction of the record
+    pub transaction_id: Vec<u8>,
+    /// The commitment of the record
+    pub commitment: Vec<u8>,
+    /// The value of the record
+    pub value: u64,
+    /// The serial number of the record, if the proof key of the account is known
+    pub serial_number: Option<Vec<u8>>,
+    /// The spend of the record, if its serial number is in a canon block
+    pub spend: Option<WalletSpend>,
+}
+
+/// The records received by an account view key registered with the wallet, and their spends.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletAccount {
+    /// The account proof key the serial numbers of the records are derived with, if it is known
+    pub proof_key: Option<Vec<u8>>,
+    /// The records of the account, ordered by block number
+    pub records: Vec<WalletRecord>,
+}
+
+impl WalletAccount {
+    /// Creates a wallet account without records.
+    pub fn new(proof_key: Option<Vec<u8>>) -> Self {
+        Self {
+            proof_key,
+            records: vec![],
+        }
+    }
+
+    /// Returns true if the spends of the records are tracked, which requires the proof key of the account.
+    pub fn tracks_spends(&self) -> bool {
+        self.proof_key.is_some()
+    }
+
+    /// Removes the records received and the spends found at or above `block_number`.
+    /// Returns true if the account changed.
+    pub fn unwind(&mut self, block_number: u32) -> bool {
+        let num_records = self.records.len();
+        self.records.retain(|record| record.block_number < block_number);
+
+        let mut changed = self.records.len() != num_records;
+
+        for record in self.records.iter_mut() {
+            if record.spend.as_ref().map(|spend| spend.block_number >= block_number) == Some(true) {
+                record.spend = None;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters, S: StorageBackend> Ledger<T, P, S> {
+    /// Get the wallet account of a registered account view key.
+    /// Returns `None` if the view key is not registered with the wallet.
+    pub fn get_wallet_account(&self, view_key: &[u8]) -> Result<Option<WalletAccount>, StorageError> {
+        match self.storage.get(COL_WALLET, view_key)? {
+            Some(wallet_account_bytes) => Ok(Some(bincode::deserialize(&wallet_account_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the account view keys registered with the wallet.
+    pub fn get_wallet_view_keys(&self) -> Result<Vec<Vec<u8>>, StorageError> {
+        Ok(self
+            .storage
+            .get_iter(COL_WALLET)?
+            .map(|(view_key, _wallet_account)| view_key.to_vec())
+            .collect())
+    }
+
+    /// Store the wallet account of an account view key, registering the view key if it is new,
+    /// along with the records it received since it was last stored.
+    pub fn store_wallet_account<R: Record>(
+        &self,
+        view_key: &[u8],
+        wallet_account: &WalletAccount,
+        new_records: &[R],
+    ) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for record in new_records {
+            database_transaction.push(Op::Insert {
+                col: COL_RECORDS,
+                key: to_bytes![record.commitment()]?.to_vec(),
+                value: to_bytes![record]?.to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Insert {
+            col: COL_WALLET,
+            key: view_key.to_vec(),
+            value: bincode::serialize(wallet_account)?,
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Remove an account view key and its records from the wallet.
+    pub fn remove_wallet_account(&self, view_key: &[u8]) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        database_transaction.push(Op::Delete {
+            col: COL_WALLET,
+            key: view_key.to_vec(),
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Returns the operations that remove the records received and the spends found at or above
+    /// `block_number` from every wallet account.
+    pub(crate) fn unwind_wallet_accounts_ops(&self, block_number: u32) -> Result<Vec<Op>, StorageError> {
+        let mut ops = vec![];
+
+        for (view_key, wallet_account_bytes) in self.storage.get_iter(COL_WALLET)? {
+            let mut wallet_account: WalletAccount = bincode::deserialize(&wallet_account_bytes)?;
+
+            if wallet_account.unwind(block_number) {
+                ops.push(Op::Insert {
+                    col: COL_WALLET,
+                    key: view_key.to_vec(),
+                    value: bincode::serialize(&wallet_account)?,
+                });
+            }
+        }
+
+        Ok(ops)
+    }
+}
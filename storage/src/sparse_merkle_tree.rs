@@ -0,0 +1,250 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_algorithms::crh::sha256;
+use snarkos_errors::storage::StorageError;
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::TryInto};
+
+/// The depth of a sparse merkle tree, which has a leaf for each 256-bit item hash.
+pub const SPARSE_MERKLE_TREE_DEPTH: usize = 256;
+
+/// The hash of an empty subtree. Empty subtrees are not stored.
+pub const EMPTY_SUBTREE_HASH: [u8; 32] = [0u8; 32];
+
+/// Returns the key of the leaf of an item, which is the path from the root to the leaf.
+pub fn sparse_merkle_leaf_key(item: &[u8]) -> [u8; 32] {
+    sha256(item)
+}
+
+/// Returns the key of the node at a given depth on the path to a leaf, in a sparse merkle tree column.
+/// The key holds the depth and the path from the root to the node.
+pub fn sparse_merkle_node_key(depth: usize, leaf_key: &[u8; 32]) -> Vec<u8> {
+    let mut key = (depth as u16).to_be_bytes().to_vec();
+    key.extend_from_slice(&leaf_key[..(depth + 7) / 8]);
+
+    if depth % 8 != 0 {
+        let last_byte = key.len() - 1;
+        key[last_byte] &= 0xffu8 << (8 - depth % 8);
+    }
+
+    key
+}
+
+/// Returns true if the path to a leaf goes right below the given depth.
+fn goes_right(leaf_key: &[u8; 32], depth: usize) -> bool {
+    (leaf_key[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+/// Returns the key of the sibling of the node at a given depth on the path to a leaf.
+fn sibling_node_key(depth: usize, leaf_key: &[u8; 32]) -> Vec<u8> {
+    let mut sibling_leaf_key = *leaf_key;
+    sibling_leaf_key[(depth - 1) / 8] ^= 1 << (7 - (depth - 1) % 8);
+
+    sparse_merkle_node_key(depth, &sibling_leaf_key)
+}
+
+/// Returns the hash of a leaf holding an item.
+fn leaf_hash(leaf_key: &[u8; 32]) -> [u8; 32] {
+    sha256(&[&[0u8][..], &leaf_key[..]].concat())
+}
+
+/// Returns the hash of an inner node. The parent of two empty subtrees is an empty subtree.
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    if left == &EMPTY_SUBTREE_HASH && right == &EMPTY_SUBTREE_HASH {
+        return EMPTY_SUBTREE_HASH;
+    }
+
+    sha256(&[&[1u8][..], &left[..], &right[..]].concat())
+}
+
+/// A proof that an item is or is not in a sparse merkle tree with a given root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseMerkleProof {
+    /// The depths and hashes of the non-empty siblings on the path from the leaf to the root.
+    /// The other siblings are empty subtrees.
+    pub siblings: Vec<(u16, [u8; 32])>,
+}
+
+impl SparseMerkleProof {
+    /// Returns the root of the tree the proof is for, given whether the item is in the tree,
+    /// or `None` if the proof is malformed.
+    pub fn root(&self, item: &[u8], is_member: bool) -> Option<[u8; 32]> {
+        let leaf_key = sparse_merkle_leaf_key(item);
+
+        let mut hash = if is_member {
+            leaf_hash(&leaf_key)
+        } else {
+            EMPTY_SUBTREE_HASH
+        };
+        let mut siblings = self.siblings.iter().peekable();
+
+        for depth in (1..=SPARSE_MERKLE_TREE_DEPTH).rev() {
+            let sibling = match siblings.peek() {
+                Some(&&(sibling_depth, sibling)) if sibling_depth as usize == depth => {
+                    siblings.next();
+                    sibling
+                }
+                _ => EMPTY_SUBTREE_HASH,
+            };
+
+            hash = if goes_right(&leaf_key, depth - 1) {
+                node_hash(&sibling, &hash)
+            } else {
+                node_hash(&hash, &sibling)
+            };
+        }
+
+        // The siblings must be ordered from the leaf to the root
+        match siblings.next() {
+            Some(_) => None,
+            None => Some(hash),
+        }
+    }
+
+    /// Returns true if the proof shows that the item is in the tree with the given root.
+    pub fn verify_membership(&self, root: &[u8; 32], item: &[u8]) -> bool {
+        self.root(item, true).as_ref() == Some(root)
+    }
+
+    /// Returns true if the proof shows that the item is not in the tree with the given root.
+    pub fn verify_non_membership(&self, root: &[u8; 32], item: &[u8]) -> bool {
+        self.root(item, false).as_ref() == Some(root)
+    }
+}
+
+/// Reads a node of a sparse merkle tree stored in a column.
+fn read_node<S: StorageBackend>(storage: &S, col: u32, key: &[u8]) -> Result<[u8; 32], StorageError> {
+    match storage.get(col, key)? {
+        Some(hash) => Ok(hash[..]
+            .try_into()
+            .map_err(|_| StorageError::Message("invalid sparse merkle tree node".into()))?),
+        None => Ok(EMPTY_SUBTREE_HASH),
+    }
+}
+
+/// Returns the root of a sparse merkle tree stored in a column.
+pub fn sparse_merkle_root<S: StorageBackend>(storage: &S, col: u32) -> Result<[u8; 32], StorageError> {
+    read_node(storage, col, &sparse_merkle_node_key(0, &[0u8; 32]))
+}
+
+/// Returns whether an item is in a sparse merkle tree stored in a column, and the proof of it.
+pub fn sparse_merkle_proof<S: StorageBackend>(
+    storage: &S,
+    col: u32,
+    item: &[u8],
+) -> Result<(bool, SparseMerkleProof), StorageError> {
+    let leaf_key = sparse_merkle_leaf_key(item);
+    let is_member = read_node(
+        storage,
+        col,
+        &sparse_merkle_node_key(SPARSE_MERKLE_TREE_DEPTH, &leaf_key),
+    )? != EMPTY_SUBTREE_HASH;
+
+    let mut siblings = vec![];
+    for depth in (1..=SPARSE_MERKLE_TREE_DEPTH).rev() {
+        let sibling = read_node(storage, col, &sibling_node_key(depth, &leaf_key))?;
+        if sibling != EMPTY_SUBTREE_HASH {
+            siblings.push((depth as u16, sibling));
+        }
+    }
+
+    Ok((is_member, SparseMerkleProof { siblings }))
+}
+
+/// Pending updates to a sparse merkle tree stored in a column, which are written as database operations.
+pub struct SparseMerkleTreeUpdate<'a, S: StorageBackend> {
+    storage: &'a S,
+    col: u32,
+    /// The updated nodes, by key
+    nodes: BTreeMap<Vec<u8>, [u8; 32]>,
+}
+
+impl<'a, S: StorageBackend> SparseMerkleTreeUpdate<'a, S> {
+    /// Returns an empty update of the sparse merkle tree stored in a column.
+    pub fn new(storage: &'a S, col: u32) -> Self {
+        Self {
+            storage,
+            col,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Insert an item into the tree.
+    pub fn insert(&mut self, item: &[u8]) -> Result<(), StorageError> {
+        let leaf_key = sparse_merkle_leaf_key(item);
+        self.update_path(&leaf_key, leaf_hash(&leaf_key))
+    }
+
+    /// Remove an item from the tree.
+    pub fn remove(&mut self, item: &[u8]) -> Result<(), StorageError> {
+        self.update_path(&sparse_merkle_leaf_key(item), EMPTY_SUBTREE_HASH)
+    }
+
+    /// Returns the root of the updated tree.
+    pub fn root(&self) -> Result<[u8; 32], StorageError> {
+        self.get_node(&sparse_merkle_node_key(0, &[0u8; 32]))
+    }
+
+    /// Returns the operations that write the updated nodes, and remove the nodes that became empty.
+    pub fn into_ops(self) -> Vec<Op> {
+        let col = self.col;
+
+        self.nodes
+            .into_iter()
+            .map(|(key, hash)| {
+                if hash == EMPTY_SUBTREE_HASH {
+                    Op::Delete { col, key }
+                } else {
+                    Op::Insert {
+                        col,
+                        key,
+                        value: hash.to_vec(),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Set the hash of a leaf, and recompute the hashes of the nodes on its path to the root.
+    fn update_path(&mut self, leaf_key: &[u8; 32], mut hash: [u8; 32]) -> Result<(), StorageError> {
+        self.nodes
+            .insert(sparse_merkle_node_key(SPARSE_MERKLE_TREE_DEPTH, leaf_key), hash);
+
+        for depth in (1..=SPARSE_MERKLE_TREE_DEPTH).rev() {
+            let sibling = self.get_node(&sibling_node_key(depth, leaf_key))?;
+
+            hash = if goes_right(leaf_key, depth - 1) {
+                node_hash(&sibling, &hash)
+            } else {
+                node_hash(&hash, &sibling)
+            };
+
+            self.nodes.insert(sparse_merkle_node_key(depth - 1, leaf_key), hash);
+        }
+
+        Ok(())
+    }
+
+    fn get_node(&self, key: &[u8]) -> Result<[u8; 32], StorageError> {
+        match self.nodes.get(key) {
+            Some(hash) => Ok(*hash),
+            None => read_node(self.storage, self.col, key),
+        }
+    }
+}
@@ -14,11 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{DatabaseTransaction, Op};
+use crate::{
+    column_name,
+    ColumnMetrics,
+    DatabaseTransaction,
+    Op,
+    StorageBackend,
+    StorageConfig,
+    StorageIterator,
+    StorageMetrics,
+};
 use snarkos_errors::storage::StorageError;
 
-use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, DBIterator, IteratorMode, Options, WriteBatch, DB};
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
 use std::{
+    fs,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -28,6 +38,7 @@ use std::{
 pub struct Storage {
     pub db: Arc<DB>,
     pub cf_names: Vec<String>,
+    pub read_only: bool,
 }
 
 impl Storage {
@@ -35,30 +46,37 @@ impl Storage {
     /// it creates a new storage file at the given path with its given names, and opens it.
     /// If RocksDB fails to open, returns [StorageError](snarkos_errors::storage::StorageError).
     pub fn open_cf<P: AsRef<Path>>(path: P, num_cfs: u32) -> Result<Self, StorageError> {
+        Self::open_cf_with_config(path, num_cfs, &StorageConfig::default())
+    }
+
+    /// Opens storage from the given path with its given names and tuning options.
+    /// If RocksDB fails to open, returns [StorageError](snarkos_errors::storage::StorageError).
+    pub fn open_cf_with_config<P: AsRef<Path>>(
+        path: P,
+        num_cfs: u32,
+        config: &StorageConfig,
+    ) -> Result<Self, StorageError> {
         let mut cfs = Vec::with_capacity(num_cfs as usize);
         let mut cf_names: Vec<String> = Vec::with_capacity(cfs.len());
 
         for column in 0..num_cfs {
             let column_name = format!("col{}", column.to_string());
 
-            let mut cf_opts = Options::default();
-            cf_opts.set_max_write_buffer_number(16);
-
-            cfs.push(ColumnFamilyDescriptor::new(&column_name, cf_opts));
+            cfs.push(ColumnFamilyDescriptor::new(&column_name, config.column_options(num_cfs)));
             cf_names.push(column_name);
         }
 
-        let mut storage_opts = Options::default();
-        storage_opts.increase_parallelism(3);
-        storage_opts.create_missing_column_families(true);
-        storage_opts.create_if_missing(true);
-
-        let storage = Arc::new(DB::open_cf_descriptors(&storage_opts, path, cfs)?);
+        let storage = Arc::new(DB::open_cf_descriptors(&config.storage_options(), path, cfs)?);
 
-        Ok(Self { db: storage, cf_names })
+        Ok(Self {
+            db: storage,
+            cf_names,
+            read_only: false,
+        })
     }
 
     /// Opens a secondary storage instance from the given path with its given names.
+    /// The secondary instance is read-only and keeps its own state at the secondary path.
     /// If RocksDB fails to open, returns [StorageError](snarkos_errors::storage::StorageError).
     pub fn open_secondary_cf<P: AsRef<Path> + Clone>(
         primary_path: P,
@@ -85,7 +103,11 @@ impl Storage {
 
         storage.try_catch_up_with_primary()?;
 
-        Ok(Self { db: storage, cf_names })
+        Ok(Self {
+            db: storage,
+            cf_names,
+            read_only: true,
+        })
     }
 
     /// Returns the column family reference from a given index.
@@ -96,21 +118,81 @@ impl Storage {
             .expect("the column family exists")
     }
 
+    /// Compact every column of the storage, to remove the deleted and overwritten values
+    /// from disk outside of the compactions triggered by writes.
+    pub fn compact(&self) {
+        for column in 0..self.cf_names.len() {
+            self.db
+                .compact_range_cf::<&[u8], &[u8]>(self.get_cf_ref(column as u32), None, None);
+        }
+    }
+
+    /// Flush the memory tables of every column to disk, so the storage is reopened
+    /// without replaying its write-ahead log.
+    pub fn flush(&self) -> Result<(), StorageError> {
+        for column in 0..self.cf_names.len() {
+            self.db.flush_cf(self.get_cf_ref(column as u32))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `Ok(())` after destroying the storage
+    /// If RocksDB fails to destroy storage, returns [StorageError](snarkos_errors::storage::StorageError).
+    pub fn destroy(&self) -> Result<(), StorageError> {
+        let path = self.db.path();
+        // drop(&self.db); FIXME: this didn't actually drop self.db
+        Self::destroy_storage(path.into())
+    }
+
+    /// Returns `Ok(())` after destroying the storage of the given path.
+    /// If RocksDB fails to destroy storage, returns [StorageError](snarkos_errors::storage::StorageError).
+    pub(crate) fn destroy_storage(path: PathBuf) -> Result<(), StorageError> {
+        let mut storage_opts = Options::default();
+        storage_opts.create_missing_column_families(true);
+        storage_opts.create_if_missing(true);
+
+        Ok(DB::destroy(&storage_opts, path)?)
+    }
+}
+
+impl StorageBackend for Storage {
+    /// Opens storage from the given path, creating its directory if it does not exist.
+    /// If RocksDB fails to open, returns [StorageError](snarkos_errors::storage::StorageError).
+    fn open<P: AsRef<Path>>(path: P, num_cols: u32) -> Result<Self, StorageError> {
+        fs::create_dir_all(path.as_ref()).map_err(|err| StorageError::Message(err.to_string()))?;
+
+        Self::open_cf(path, num_cols)
+    }
+
     /// Returns the value from a given key and col.
     /// If the given key does not exist, returns [StorageError](snarkos_errors::storage::StorageError).
-    pub(crate) fn get(&self, col: u32, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+    fn get(&self, col: u32, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
         Ok(self.db.get_cf(self.get_cf_ref(col), key)?)
     }
 
     /// Returns the iterator from a given col.
     /// If the given key does not exist, returns [StorageError](snarkos_errors::storage::StorageError).
-    pub(crate) fn get_iter(&self, col: u32) -> Result<DBIterator, StorageError> {
-        Ok(self.db.iterator_cf(self.get_cf_ref(col), IteratorMode::Start))
+    fn get_iter(&self, col: u32) -> Result<StorageIterator, StorageError> {
+        Ok(Box::new(self.db.iterator_cf(self.get_cf_ref(col), IteratorMode::Start)))
+    }
+
+    /// Returns the iterator from a given col, starting at the first key greater than or equal to the given key.
+    /// If the given col does not exist, returns [StorageError](snarkos_errors::storage::StorageError).
+    fn get_iter_from(&self, col: u32, key: &[u8]) -> Result<StorageIterator, StorageError> {
+        Ok(Box::new(self.db.iterator_cf(
+            self.get_cf_ref(col),
+            IteratorMode::From(key, Direction::Forward),
+        )))
     }
 
     /// Returns `Ok(())` after executing a database transaction
     /// If the any of the operations fail, returns [StorageError](snarkos_errors::storage::StorageError).
-    pub(crate) fn write(&self, transaction: DatabaseTransaction) -> Result<(), StorageError> {
+    fn write(&self, transaction: DatabaseTransaction) -> Result<(), StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnlyStorage);
+        }
+
         let mut batch = WriteBatch::default();
 
         for operation in transaction.0 {
@@ -131,29 +213,44 @@ impl Storage {
         Ok(())
     }
 
+    /// Returns the size of each column from the RocksDB properties, and the cache hit rate and
+    /// write amplification if the storage was opened with statistics.
+    fn metrics(&self) -> Result<StorageMetrics, StorageError> {
+        let mut columns = Vec::with_capacity(self.cf_names.len());
+
+        for col in 0..self.cf_names.len() as u32 {
+            let cf = self.get_cf_ref(col);
+            let property =
+                |name: &str| -> Result<u64, StorageError> { Ok(self.db.property_int_value_cf(cf, name)?.unwrap_or(0)) };
+
+            columns.push(ColumnMetrics {
+                col,
+                name: column_name(col).to_string(),
+                disk_size: property("rocksdb.total-sst-files-size")?,
+                memory_size: property("rocksdb.size-all-mem-tables")?,
+                cache_size: property("rocksdb.block-cache-usage")?,
+                estimated_keys: property("rocksdb.estimate-num-keys")?,
+            });
+        }
+
+        let statistics = self.db.property_value("rocksdb.options-statistics")?;
+
+        Ok(StorageMetrics::new(columns, statistics.as_deref()))
+    }
+
     /// Returns true if a value exists for a key and col pair.
-    pub fn exists(&self, col: u32, key: &[u8]) -> bool {
+    fn exists(&self, col: u32, key: &[u8]) -> bool {
         match self.db.get_cf(self.get_cf_ref(col), key) {
             Ok(val) => val.is_some(),
             Err(_) => false,
         }
     }
 
-    /// Returns `Ok(())` after destroying the storage
-    /// If RocksDB fails to destroy storage, returns [StorageError](snarkos_errors::storage::StorageError).
-    pub fn destroy(&self) -> Result<(), StorageError> {
-        let path = self.db.path();
-        // drop(&self.db); FIXME: this didn't actually drop self.db
-        Self::destroy_storage(path.into())
+    fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
-    /// Returns `Ok(())` after destroying the storage of the given path.
-    /// If RocksDB fails to destroy storage, returns [StorageError](snarkos_errors::storage::StorageError).
-    pub(crate) fn destroy_storage(path: PathBuf) -> Result<(), StorageError> {
-        let mut storage_opts = Options::default();
-        storage_opts.create_missing_column_families(true);
-        storage_opts.create_if_missing(true);
-
-        Ok(DB::destroy(&storage_opts, path)?)
+    fn try_catch_up_with_primary(&self) -> Result<(), StorageError> {
+        Ok(self.db.try_catch_up_with_primary()?)
     }
 }
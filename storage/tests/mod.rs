@@ -15,6 +15,11 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 mod test_storage {
+    use snarkos_errors::storage::StorageError;
+    use snarkos_models::{
+        genesis::Genesis,
+        objects::{LedgerScheme, Transaction},
+    };
     use snarkos_objects::{
         Block,
         BlockHeader,
@@ -24,7 +29,45 @@ mod test_storage {
         PedersenMerkleRootHash,
         ProofOfSuccinctWork,
     };
-    use snarkos_testing::storage::*;
+    use snarkos_parameters::GenesisBlock;
+    use snarkos_storage::{
+        block_height_index_key,
+        commitment_tree_node_key,
+        insert_serial_numbers,
+        ledger_checkpoint_key,
+        migration_backup_path,
+        parse_statistics_counters,
+        sparse_merkle_proof,
+        sparse_merkle_root,
+        transaction_height_index_key,
+        BlockExportReader,
+        BloomFilter,
+        CompactionStyle,
+        DatabaseTransaction,
+        Inconsistency,
+        IndexedTransaction,
+        MemoryStorage,
+        SideChainPath,
+        SnapshotCheckpoint,
+        SparseMerkleTreeUpdate,
+        StartupCheck,
+        StorageBackend,
+        StorageConfig,
+        BLOCK_EXPORT_VERSION,
+        COL_BLOCK_HEADER,
+        COL_COMMITMENT_SEARCH_INDEX,
+        COL_MEMO_SEARCH_INDEX,
+        COL_META,
+        COL_SERIAL_NUMBER,
+        COL_SERIAL_NUMBER_TREE,
+        EMPTY_SUBTREE_HASH,
+        KEY_LEDGER_FILTERS,
+        KEY_SCHEMA_VERSION,
+        NUM_COLS,
+        OLDEST_FORK_THRESHOLD,
+        SCHEMA_VERSION,
+    };
+    use snarkos_testing::{consensus::TestTx, storage::*};
 
     use std::sync::Arc;
 
@@ -46,25 +89,959 @@ mod test_storage {
         assert_eq!(blockchain.get_latest_block_height(), 0);
 
         // insert a block
+        let block = test_block(BlockHeaderHash([0; 32]), 99);
+
+        blockchain.insert_and_commit(&block).unwrap();
+        assert_eq!(blockchain.get_latest_block_height(), 1);
+
+        // removing it decrements the chain's height
+        blockchain.remove_latest_block().unwrap();
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn remove_persists_height() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        // insert a block
+        let block = test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 99);
+
+        blockchain.insert_and_commit(&block).unwrap();
+        blockchain.remove_latest_block().unwrap();
+
+        // reopening the storage restores the decremented height
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(path).unwrap());
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn failed_commit_does_not_insert() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        // the genesis block already holds the serial number of a test transaction
         let block = Block {
+            transactions: DPCTransactions(vec![TestTx]),
+            ..test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 99)
+        };
+
+        assert!(blockchain.insert_and_commit(&block).is_err());
+        assert!(!blockchain.block_hash_exists(&block.header.get_hash()));
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn prune_blocks() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut previous_block_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+
+        // insert two blocks more than the deepest fork
+        for nonce in 0..OLDEST_FORK_THRESHOLD + 2 {
+            let block = test_block(previous_block_hash, nonce);
+            previous_block_hash = block.header.get_hash();
+            blockchain.insert_and_commit(&block).unwrap();
+        }
+        assert_eq!(blockchain.get_latest_block_height(), OLDEST_FORK_THRESHOLD + 2);
+
+        // the blocks a fork can decommit cannot be pruned
+        assert!(blockchain.prune_blocks(OLDEST_FORK_THRESHOLD - 1).is_err());
+        assert_eq!(blockchain.get_pruned_block_height().unwrap(), 0);
+
+        // pruning discards the transactions of all but the blocks a fork can decommit
+        assert_eq!(blockchain.prune_blocks(OLDEST_FORK_THRESHOLD).unwrap(), 2);
+        assert_eq!(blockchain.get_pruned_block_height().unwrap(), 2);
+
+        assert!(blockchain.get_block_from_block_number(0).is_ok());
+        for block_number in 1..=2 {
+            match blockchain.get_block_from_block_number(block_number) {
+                Err(StorageError::PrunedBlockTransactions(_)) => {}
+                result => panic!("expected the block to be pruned, got {:?}", result.map(|_| ())),
+            }
+
+            let block_hash = blockchain.get_block_hash(block_number).unwrap();
+            assert!(blockchain.get_block_header(&block_hash).is_ok());
+        }
+        assert!(blockchain.get_block_from_block_number(3).is_ok());
+
+        // pruning again within the retention window is a no-op
+        assert_eq!(blockchain.prune_blocks(OLDEST_FORK_THRESHOLD).unwrap(), 0);
+
+        // the snapshots are only exported from the pruned block height
+        let mut snapshot_path = std::env::temp_dir();
+        snapshot_path.push(random_storage_path());
+
+        assert!(blockchain.export_snapshot(&snapshot_path, 1).is_err());
+        blockchain.export_snapshot(&snapshot_path, 2).unwrap();
+
+        std::fs::remove_file(snapshot_path).unwrap();
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn export_and_import_snapshot() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut previous_block_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+
+        // insert three blocks
+        for nonce in 0..3 {
+            let block = test_block(previous_block_hash, nonce);
+            previous_block_hash = block.header.get_hash();
+            blockchain.insert_and_commit(&block).unwrap();
+        }
+
+        let mut snapshot_path = std::env::temp_dir();
+        snapshot_path.push(random_storage_path());
+
+        blockchain.export_snapshot(&snapshot_path, 2).unwrap();
+
+        // the imported ledger holds the state at the snapshot height
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+
+        let genesis_block = GenesisBlock::load_bytes();
+        let import = |checkpoint: &SnapshotCheckpoint, verify_header: fn(&BlockHeader, &BlockHeader) -> bool| {
+            Store::import_snapshot(&path, &snapshot_path, &genesis_block, checkpoint, verify_header)
+        };
+
+        let checkpoint = blockchain.snapshot_checkpoint(2).unwrap();
+        assert_eq!(checkpoint.block_hash, blockchain.get_block_hash(2).unwrap());
+
+        // the block headers are verified
+        assert!(import(&checkpoint, |_, _| false).is_err());
+
+        // the snapshot must hold the block and the ledger state of the checkpoint
+        let wrong_block_checkpoint = SnapshotCheckpoint {
+            block_hash: blockchain.get_block_hash(1).unwrap(),
+            ..checkpoint.clone()
+        };
+        assert!(import(&wrong_block_checkpoint, |_, _| true).is_err());
+
+        let wrong_state_checkpoint = SnapshotCheckpoint {
+            state_hash: [0u8; 32],
+            ..checkpoint.clone()
+        };
+        assert!(import(&wrong_state_checkpoint, |_, _| true).is_err());
+
+        let other_height_checkpoint = blockchain.snapshot_checkpoint(1).unwrap();
+        assert!(import(&other_height_checkpoint, |_, _| true).is_err());
+
+        // the snapshot must start from the given genesis block
+        let other_genesis_block = test_block(BlockHeaderHash([0u8; 32]), 0).serialize().unwrap();
+        assert!(Store::import_snapshot(&path, &snapshot_path, &other_genesis_block, &checkpoint, |_, _| true).is_err());
+
+        let imported_blockchain = Arc::new(import(&checkpoint, |_, _| true).unwrap());
+
+        assert_eq!(imported_blockchain.get_latest_block_height(), 2);
+        assert_eq!(imported_blockchain.get_pruned_block_height().unwrap(), 2);
+        assert_eq!(
+            imported_blockchain.get_block_hash(2).unwrap(),
+            blockchain.get_block_hash(2).unwrap()
+        );
+        assert_eq!(
+            imported_blockchain.current_digest().unwrap(),
+            blockchain.current_digest().unwrap()
+        );
+        assert_eq!(
+            imported_blockchain.get_serial_number_root().unwrap(),
+            blockchain.get_serial_number_root().unwrap()
+        );
+        assert!(imported_blockchain.get_block_from_block_number(0).is_ok());
+
+        kill_storage_sync(imported_blockchain);
+
+        // a corrupted snapshot is rejected
+        let mut snapshot_bytes = std::fs::read(&snapshot_path).unwrap();
+        let last_byte = snapshot_bytes.len() - 1;
+        snapshot_bytes[last_byte] ^= 1;
+        std::fs::write(&snapshot_path, snapshot_bytes).unwrap();
+
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+
+        assert!(Store::import_snapshot(&path, &snapshot_path, &genesis_block, &checkpoint, |_, _| true).is_err());
+
+        std::fs::remove_file(snapshot_path).unwrap();
+        Store::destroy_storage(path).unwrap();
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn export_and_read_blocks() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut blocks = vec![blockchain.get_latest_block().unwrap()];
+        for nonce in 0..3 {
+            let block = test_block(blocks.last().unwrap().header.get_hash(), nonce);
+            blockchain.insert_and_commit(&block).unwrap();
+            blocks.push(block);
+        }
+
+        let mut export = vec![];
+        assert_eq!(blockchain.export_blocks(&mut export).unwrap(), 4);
+
+        // the export is deterministic
+        let mut second_export = vec![];
+        blockchain.export_blocks(&mut second_export).unwrap();
+        assert_eq!(export, second_export);
+
+        let reader = BlockExportReader::<_, TestTx>::new(&export[..]).unwrap();
+        assert_eq!(reader.header().version, BLOCK_EXPORT_VERSION);
+        assert_eq!(reader.header().block_count, 4);
+        assert_eq!(reader.header().genesis_hash, blocks[0].header.get_hash());
+
+        let read_blocks = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read_blocks, blocks);
+
+        // the blocks are loaded into a new ledger with the same genesis block
+        let (imported_blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+        for block in BlockExportReader::<_, TestTx>::new(&export[..]).unwrap() {
+            let block = block.unwrap();
+            if !imported_blockchain.is_canon(&block.header.get_hash()) {
+                imported_blockchain.insert_and_commit(&block).unwrap();
+            }
+        }
+        assert_eq!(imported_blockchain.get_latest_block().unwrap(), blocks[3]);
+
+        kill_storage_sync(imported_blockchain);
+
+        // a corrupted block is rejected, and the reader stops at it
+        let mut corrupted_export = export.clone();
+        let last_byte = corrupted_export.len() - 1;
+        corrupted_export[last_byte] ^= 1;
+
+        let results = BlockExportReader::<_, TestTx>::new(&corrupted_export[..])
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 4);
+        assert!(results[2].is_ok());
+        assert!(results[3].is_err());
+
+        // a truncated export is rejected
+        let results = BlockExportReader::<_, TestTx>::new(&export[..export.len() - 1])
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(results[3].is_err());
+
+        // an export of another format is rejected
+        assert!(BlockExportReader::<_, TestTx>::new(&export[1..]).is_err());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn migrate_unversioned_storage() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        assert_eq!(blockchain.get_schema_version().unwrap(), SCHEMA_VERSION);
+
+        // remove the schema version to simulate an unversioned storage
+        let meta = blockchain.storage.db.cf_handle("col0").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(meta, KEY_SCHEMA_VERSION.as_bytes())
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.get_schema_version().unwrap(), SCHEMA_VERSION);
+        assert!(!migration_backup_path(&path).exists());
+
+        // storage from a newer schema version is rejected
+        let meta = blockchain.storage.db.cf_handle("col0").unwrap();
+        blockchain
+            .storage
+            .db
+            .put_cf(meta, KEY_SCHEMA_VERSION.as_bytes(), (SCHEMA_VERSION + 1).to_le_bytes())
+            .unwrap();
+
+        drop(blockchain);
+        assert!(Store::open_at_path(&path).is_err());
+
+        Store::destroy_storage(path).unwrap();
+    }
+
+    #[test]
+    pub fn migrate_interrupted_storage() {
+        // the backup of an unversioned storage, marked to tell it apart from the migrated storage
+        let (backup, backup_source_path): (Arc<Store>, _) = open_test_blockchain();
+        let meta = backup.storage.db.cf_handle("col0").unwrap();
+        backup
+            .storage
+            .db
+            .delete_cf(meta, KEY_SCHEMA_VERSION.as_bytes())
+            .unwrap();
+        backup.storage.db.put_cf(meta, b"backup", b"").unwrap();
+        drop(backup);
+
+        // a storage left partially migrated
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+        let meta = blockchain.storage.db.cf_handle("col0").unwrap();
+        blockchain
+            .storage
+            .db
+            .put_cf(meta, KEY_SCHEMA_VERSION.as_bytes(), (SCHEMA_VERSION - 1).to_le_bytes())
+            .unwrap();
+        drop(blockchain);
+
+        std::fs::rename(&backup_source_path, migration_backup_path(&path)).unwrap();
+
+        // the storage is recovered from the backup before it is migrated
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+        assert_eq!(blockchain.get_schema_version().unwrap(), SCHEMA_VERSION);
+        assert!(blockchain.storage.get(COL_META, b"backup").unwrap().is_some());
+        assert!(!migration_backup_path(&path).exists());
+        drop(blockchain);
+
+        // a backup left after a completed migration is stale and removed
+        std::fs::create_dir(migration_backup_path(&path)).unwrap();
+
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+        assert!(blockchain.storage.get(COL_META, b"backup").unwrap().is_some());
+        assert!(!migration_backup_path(&path).exists());
+        drop(blockchain);
+
+        Store::destroy_storage(backup_source_path).unwrap();
+        Store::destroy_storage(path).unwrap();
+    }
+
+    #[test]
+    pub fn verify_and_repair_integrity() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        // remove the serial number of the genesis transaction
+        let serial_numbers = blockchain.storage.db.cf_handle("col6").unwrap();
+        blockchain.storage.db.delete_cf(serial_numbers, [0u8; 32]).unwrap();
+
+        let expected_inconsistencies = vec![Inconsistency::InvalidIndex(COL_SERIAL_NUMBER, vec![0u8; 32])];
+
+        assert_eq!(blockchain.verify_integrity(false).unwrap(), expected_inconsistencies);
+        assert_eq!(blockchain.verify_integrity(true).unwrap(), expected_inconsistencies);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn search_indexes() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        // the genesis transaction is found by its memo and its commitment
+        let genesis_transaction = IndexedTransaction {
+            key: vec![0u8; 32],
+            block_number: 0,
+            transaction_id: vec![0u8; 32],
+        };
+        assert_eq!(
+            blockchain.get_memo_transaction(&[0u8; 32]).unwrap(),
+            Some(genesis_transaction.clone())
+        );
+        assert_eq!(
+            blockchain.get_commitment_transaction(&[0u8; 32]).unwrap(),
+            Some(genesis_transaction.clone())
+        );
+        assert_eq!(blockchain.get_memo_transaction(&[1u8; 32]).unwrap(), None);
+
+        // searches return the transactions whose keys start with the prefix
+        let memo_results: Vec<IndexedTransaction> = blockchain.search_memos(&[0u8, 0u8]).unwrap().collect();
+        assert_eq!(memo_results, vec![genesis_transaction.clone()]);
+        assert_eq!(blockchain.search_commitments(&[]).unwrap().count(), 1);
+        assert_eq!(blockchain.search_commitments(&[1u8]).unwrap().count(), 0);
+
+        // missing entries are reported by the integrity check
+        let memo_search_index = blockchain.storage.db.cf_handle("col19").unwrap();
+        blockchain.storage.db.delete_cf(memo_search_index, [0u8; 32]).unwrap();
+        let commitment_search_index = blockchain.storage.db.cf_handle("col20").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(commitment_search_index, [0u8; 32])
+            .unwrap();
+
+        assert_eq!(blockchain.verify_integrity(false).unwrap(), vec![
+            Inconsistency::InvalidSearchIndex(COL_MEMO_SEARCH_INDEX, vec![0u8; 32]),
+            Inconsistency::InvalidSearchIndex(COL_COMMITMENT_SEARCH_INDEX, vec![0u8; 32]),
+        ]);
+
+        // the search indexes are rebuilt when the ledger is loaded
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        let commitment_results: Vec<IndexedTransaction> = blockchain.search_commitments(&[0u8; 32]).unwrap().collect();
+        assert_eq!(commitment_results, vec![genesis_transaction]);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn commitment_tree_nodes_are_persisted() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        let digest = blockchain.current_digest().unwrap();
+        let commitment_tree = blockchain.storage.db.cf_handle("col14").unwrap();
+
+        // the commitment of the genesis transaction is the only leaf
+        assert!(blockchain
+            .storage
+            .db
+            .get_cf(commitment_tree, commitment_tree_node_key(0, 0))
+            .unwrap()
+            .is_some());
+
+        // a missing node is reported and rewritten
+        blockchain
+            .storage
+            .db
+            .delete_cf(commitment_tree, commitment_tree_node_key(0, 0))
+            .unwrap();
+
+        let expected_inconsistencies = vec![Inconsistency::InvalidCommitmentTree];
+
+        assert_eq!(blockchain.verify_integrity(false).unwrap(), expected_inconsistencies);
+        assert_eq!(blockchain.verify_integrity(true).unwrap(), expected_inconsistencies);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        // missing nodes are rebuilt from the commitments when the ledger is loaded
+        let commitment_tree = blockchain.storage.db.cf_handle("col14").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(commitment_tree, commitment_tree_node_key(0, 0))
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.cm_merkle_tree.read().len(), 1);
+        assert_eq!(blockchain.current_digest().unwrap(), digest);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn prove_commitment_at_past_height() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        // the commitment of the genesis transaction is in the ledger as of the genesis block
+        let genesis_digest = blockchain.get_digest_at_height(0).unwrap();
+        let path_at_genesis = blockchain.prove_cm_at_height(&[0u8; 32], 0).unwrap();
+        assert!(path_at_genesis.verify(&genesis_digest, &[0u8; 32]).unwrap());
+        assert!(blockchain.prove_cm_at_height(&[1u8; 32], 0).is_err());
+
+        // insert a block
+        let block = test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 99);
+        blockchain.insert_and_commit(&block).unwrap();
+
+        assert_eq!(blockchain.get_ledger_checkpoint(1).unwrap().num_commitments, 1);
+        assert_eq!(blockchain.get_digest_at_height(1).unwrap(), genesis_digest);
+
+        // missing checkpoints are rebuilt when the ledger is loaded
+        let ledger_checkpoints = blockchain.storage.db.cf_handle("col15").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(ledger_checkpoints, ledger_checkpoint_key(1))
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.get_digest_at_height(1).unwrap(), genesis_digest);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        // decommitted blocks lose their checkpoint, and the digest returns to the new latest block
+        blockchain.decommit_latest_block().unwrap();
+        assert!(blockchain.get_ledger_checkpoint(1).is_err());
+        assert_eq!(
+            blockchain
+                .get_digest_block_number(&blockchain.current_digest().unwrap())
+                .unwrap(),
+            Some(0)
+        );
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    fn test_block(previous_block_hash: BlockHeaderHash, nonce: u32) -> Block<TestTx> {
+        Block {
             header: BlockHeader {
                 difficulty_target: 100,
-                nonce: 99,
+                nonce,
                 merkle_root_hash: MerkleRootHash([0; 32]),
-                previous_block_hash: BlockHeaderHash([0; 32]),
+                previous_block_hash,
                 time: 123,
                 proof: ProofOfSuccinctWork::default(),
                 pedersen_merkle_root_hash: PedersenMerkleRootHash([0; 32]),
             },
             transactions: DPCTransactions::new(),
+        }
+    }
+
+    #[test]
+    pub fn interrupted_fork_is_rolled_back() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+        let genesis_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+
+        // build a canon chain of two blocks
+        let block_1 = test_block(genesis_hash.clone(), 1);
+        let block_2 = test_block(block_1.header.get_hash(), 2);
+        blockchain.insert_and_commit(&block_1).unwrap();
+        blockchain.insert_and_commit(&block_2).unwrap();
+
+        // committed blocks have an undo record
+        assert_eq!(blockchain.get_block_undo(2).unwrap().unwrap().cm_index, 1);
+        let digest = blockchain.current_digest().unwrap();
+
+        // store a longer side chain and start a fork to it
+        let side_block_1 = test_block(genesis_hash, 3);
+        let side_block_2 = test_block(side_block_1.header.get_hash(), 4);
+        let side_block_3 = test_block(side_block_2.header.get_hash(), 5);
+        for block in &[&side_block_1, &side_block_2, &side_block_3] {
+            blockchain.insert_only(block).unwrap();
+        }
+
+        let side_chain_path = SideChainPath {
+            shared_block_number: 0,
+            new_block_number: 3,
+            path: vec![
+                side_block_1.header.get_hash(),
+                side_block_2.header.get_hash(),
+                side_block_3.header.get_hash(),
+            ],
         };
 
+        blockchain.revert_for_fork(&side_chain_path).unwrap();
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+        assert!(blockchain.get_block_undo(2).unwrap().is_none());
+
+        // the node crashes after committing the first side chain block
+        blockchain.commit(&side_block_1.header.get_hash()).unwrap();
+        assert!(blockchain.get_interrupted_fork().unwrap().is_some());
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        // the canon chain is restored when the ledger is loaded
+        assert!(blockchain.get_interrupted_fork().unwrap().is_none());
+        assert_eq!(blockchain.get_latest_block_height(), 2);
+        assert_eq!(blockchain.get_latest_block().unwrap(), block_2);
+        assert_eq!(blockchain.current_digest().unwrap(), digest);
+        assert!(!blockchain.is_canon(&side_block_1.header.get_hash()));
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn collect_garbage() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+        let genesis_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+
+        // build a canon chain of four blocks
+        let mut canon_hashes = vec![genesis_hash.clone()];
+        for nonce in 1..=4 {
+            let block = test_block(canon_hashes.last().unwrap().clone(), nonce);
+            blockchain.insert_and_commit(&block).unwrap();
+            canon_hashes.push(block.header.get_hash());
+        }
+
+        // store side chains forking from the genesis block and from the third block, and two orphan blocks
+        let old_side_block_1 = test_block(genesis_hash.clone(), 11);
+        let old_side_block_2 = test_block(old_side_block_1.header.get_hash(), 12);
+        let recent_side_block = test_block(canon_hashes[3].clone(), 13);
+
+        let mut old_orphan_block = test_block(BlockHeaderHash([1u8; 32]), 14);
+        old_orphan_block.header.time = 0;
+        let mut recent_orphan_block = test_block(BlockHeaderHash([2u8; 32]), 15);
+        recent_orphan_block.header.time = 1000;
+
+        for block in &[
+            &old_side_block_1,
+            &old_side_block_2,
+            &recent_side_block,
+            &old_orphan_block,
+            &recent_orphan_block,
+        ] {
+            blockchain.insert_only(block).unwrap();
+        }
+
+        // nothing is collected while every block is within the reorg window
+        assert!(blockchain.collect_garbage(4).unwrap().is_empty());
+
+        let collection = blockchain.collect_garbage(2).unwrap();
+
+        assert_eq!(collection.removed_blocks, 3);
+        assert_eq!(collection.removed_memory_pool_transactions, 0);
+        assert!(collection.reclaimed_size > 0);
+
+        assert!(!blockchain.block_hash_exists(&old_side_block_1.header.get_hash()));
+        assert!(!blockchain.block_hash_exists(&old_side_block_2.header.get_hash()));
+        assert!(!blockchain.block_hash_exists(&old_orphan_block.header.get_hash()));
+        assert!(blockchain.block_hash_exists(&recent_side_block.header.get_hash()));
+        assert!(blockchain.block_hash_exists(&recent_orphan_block.header.get_hash()));
+
+        // the parents no longer list the removed blocks as potential children
+        assert_eq!(blockchain.get_child_block_hashes(&genesis_hash).unwrap(), vec![
+            canon_hashes[1].clone()
+        ]);
+        assert!(blockchain
+            .get_child_block_hashes(&BlockHeaderHash([1u8; 32]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(blockchain.get_child_block_hashes(&canon_hashes[3]).unwrap().len(), 2);
+
+        // the removed data is added to the storage metrics
+        assert_eq!(blockchain.get_storage_metrics().unwrap().garbage_collection, collection);
+        assert!(blockchain.collect_garbage(2).unwrap().is_empty());
+
+        assert_eq!(blockchain.get_latest_block_height(), 4);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn archive_block_transactions() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut blocks = vec![];
+        let mut previous_block_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+        for nonce in 1..=3 {
+            let block = test_block(previous_block_hash, nonce);
+            blockchain.insert_and_commit(&block).unwrap();
+
+            previous_block_hash = block.header.get_hash();
+            blocks.push(block);
+        }
+
+        // the transactions of the blocks older than the latest block are archived
+        assert_eq!(blockchain.archive_blocks(1).unwrap(), 2);
+        assert_eq!(blockchain.archive_blocks(1).unwrap(), 0);
+        assert_eq!(blockchain.get_archived_block_height().unwrap(), 2);
+
+        assert!(blockchain
+            .get_archive_location(&blocks[0].header.get_hash())
+            .unwrap()
+            .is_some());
+        assert!(blockchain
+            .get_archive_location(&blocks[2].header.get_hash())
+            .unwrap()
+            .is_none());
+
+        // archived blocks are read back from the archive, including after a restart
+        assert_eq!(blockchain.get_block_from_block_number(1).unwrap(), blocks[0]);
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.get_block_from_block_number(2).unwrap(), blocks[1]);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        // archived blocks can be removed
+        blockchain.remove_latest_blocks(3).unwrap();
+        assert!(blockchain
+            .get_archive_location(&blocks[0].header.get_hash())
+            .unwrap()
+            .is_none());
+        assert!(!blockchain.block_hash_exists(&blocks[0].header.get_hash()));
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn memory_storage_ledger() {
+        let (persisted_blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+        let blockchain: Arc<MemoryStore> = open_test_memory_blockchain();
+
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+        assert_eq!(
+            blockchain.current_digest().unwrap(),
+            persisted_blockchain.current_digest().unwrap()
+        );
+
+        let block = test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 99);
+
         blockchain.insert_and_commit(&block).unwrap();
         assert_eq!(blockchain.get_latest_block_height(), 1);
+        assert_eq!(blockchain.get_block_from_block_number(1).unwrap(), block);
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
 
-        // removing it decrements the chain's height
         blockchain.remove_latest_block().unwrap();
         assert_eq!(blockchain.get_latest_block_height(), 0);
+        assert!(!blockchain.block_hash_exists(&block.header.get_hash()));
+
+        kill_storage_sync(persisted_blockchain);
+    }
+
+    #[test]
+    pub fn ledger_view_defers_commits() {
+        let blockchain: Arc<MemoryStore> = open_test_memory_blockchain();
+        let block = test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 1);
+        let block_hash = block.header.get_hash();
+
+        let view = blockchain.view();
+
+        let writer = {
+            let blockchain = blockchain.clone();
+            std::thread::spawn(move || blockchain.insert_and_commit(&block).unwrap())
+        };
+
+        // the block is not published while the view is held
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(view.block_height(), 0);
+        assert_eq!(view.get_latest_block_height(), 0);
+        assert!(!view.is_canon(&block_hash));
+
+        drop(view);
+        writer.join().unwrap();
+
+        assert_eq!(blockchain.get_latest_block_height(), 1);
+        assert!(blockchain.is_canon(&block_hash));
+    }
+
+    #[test]
+    pub fn ledger_overlay_stages_transactions() {
+        let blockchain: Arc<MemoryStore> = open_test_memory_blockchain();
+
+        let mut overlay = blockchain.overlay();
+
+        // a transaction conflicts with the staged transactions spending the same serial numbers
+        assert!(overlay.stage(TestTx).unwrap());
+        assert!(overlay.contains_sn(&[0u8; 32]).unwrap());
+        assert!(overlay.conflicts(&TestTx).unwrap());
+        assert!(!overlay.stage(TestTx).unwrap());
+        assert_eq!(overlay.transactions().len(), 1);
+
+        // the staged transactions are not written to the ledger
+        assert!(!overlay.view().contains_sn(&[0u8; 32]));
+
+        overlay.discard();
+        assert!(!overlay.conflicts(&TestTx).unwrap());
+        assert!(overlay.stage(TestTx).unwrap());
+
+        assert_eq!(overlay.commit(), DPCTransactions(vec![TestTx]));
+        assert!(!blockchain.contains_sn(&[0u8; 32]));
+    }
+
+    #[test]
+    pub fn serial_number_tree() {
+        let storage = MemoryStorage::open(std::env::temp_dir(), NUM_COLS).unwrap();
+        assert_eq!(
+            sparse_merkle_root(&storage, COL_SERIAL_NUMBER_TREE).unwrap(),
+            EMPTY_SUBTREE_HASH
+        );
+
+        insert_serial_numbers(&storage, &[b"sn_1".to_vec(), b"sn_2".to_vec()]).unwrap();
+        let root = sparse_merkle_root(&storage, COL_SERIAL_NUMBER_TREE).unwrap();
+
+        // a spent serial number has a membership proof
+        let (is_member, proof) = sparse_merkle_proof(&storage, COL_SERIAL_NUMBER_TREE, b"sn_1").unwrap();
+        assert!(is_member);
+        assert!(proof.verify_membership(&root, b"sn_1"));
+        assert!(!proof.verify_non_membership(&root, b"sn_1"));
+        assert!(!proof.verify_membership(&root, b"sn_2"));
+
+        // an unspent serial number has a non-membership proof
+        let (is_member, proof) = sparse_merkle_proof(&storage, COL_SERIAL_NUMBER_TREE, b"sn_3").unwrap();
+        assert!(!is_member);
+        assert!(proof.verify_non_membership(&root, b"sn_3"));
+        assert!(!proof.verify_membership(&root, b"sn_3"));
+
+        // removing the serial numbers empties the tree
+        let mut update = SparseMerkleTreeUpdate::new(&storage, COL_SERIAL_NUMBER_TREE);
+        update.remove(b"sn_2").unwrap();
+        update.remove(b"sn_1").unwrap();
+        assert_eq!(update.root().unwrap(), EMPTY_SUBTREE_HASH);
+
+        storage.write(DatabaseTransaction(update.into_ops())).unwrap();
+        assert_eq!(storage.get_iter(COL_SERIAL_NUMBER_TREE).unwrap().count(), 0);
+
+        // the ledger proves the serial numbers of the canon blocks
+        let blockchain: Arc<MemoryStore> = open_test_memory_blockchain();
+        let genesis_block = blockchain.get_block_from_block_number(0).unwrap();
+
+        for transaction in genesis_block.transactions.iter() {
+            for sn in transaction.old_serial_numbers() {
+                let sn_proof = blockchain.prove_sn(sn).unwrap();
+                assert!(sn_proof.is_spent);
+                assert!(sn_proof.verify(&sn[..]));
+            }
+        }
+
+        let sn_proof = blockchain.prove_sn(&[1u8; 32]).unwrap();
+        assert!(!sn_proof.is_spent);
+        assert!(sn_proof.verify(&[1u8; 32]));
+        assert_eq!(sn_proof.root, blockchain.get_serial_number_root().unwrap());
+    }
+
+    #[test]
+    pub fn bloom_filter() {
+        let mut filter = BloomFilter::new(100);
+        assert!(filter.is_empty());
+
+        for item in 0u32..100 {
+            filter.insert(&item.to_le_bytes());
+        }
+
+        assert_eq!(filter.len(), 100);
+        assert!(!filter.is_saturated());
+        assert!((0u32..100).all(|item| filter.contains(&item.to_le_bytes())));
+
+        // about 1% of the absent items are false positives
+        let false_positives = (100u32..10_100)
+            .filter(|item| filter.contains(&item.to_le_bytes()))
+            .count();
+        assert!(false_positives < 300);
+
+        filter.insert(b"item");
+        assert!(filter.is_saturated());
+    }
+
+    #[test]
+    pub fn ledger_filters_are_rebuilt_on_mismatch() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+        let genesis_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+
+        let filters = blockchain.filters.read().clone().unwrap();
+        assert_eq!(filters.block_hash, genesis_hash);
+        assert!(!blockchain.may_contain_memo(&[1u8; 32]));
+
+        let block = test_block(genesis_hash, 1);
+        blockchain.insert_and_commit(&block).unwrap();
+
+        // persist filters of a chain that has since been reverted
+        let mut stale_filters = filters.clone();
+        stale_filters.block_hash = BlockHeaderHash([1u8; 32]);
+        stale_filters.serial_numbers.insert(b"stale serial number");
+
+        let meta = blockchain.storage.db.cf_handle("col0").unwrap();
+        blockchain
+            .storage
+            .db
+            .put_cf(
+                meta,
+                KEY_LEDGER_FILTERS.as_bytes(),
+                bincode::serialize(&stale_filters).unwrap(),
+            )
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        let rebuilt_filters = blockchain.filters.read().clone().unwrap();
+        assert_eq!(rebuilt_filters.block_height, 1);
+        assert_eq!(rebuilt_filters.block_hash, block.header.get_hash());
+        assert_eq!(rebuilt_filters.serial_numbers.len(), filters.serial_numbers.len());
+        assert!(!rebuilt_filters.serial_numbers.contains(b"stale serial number"));
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn open_with_config_and_compact() {
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+
+        let config = StorageConfig {
+            block_cache_mb: 16,
+            compaction_style: CompactionStyle::Universal,
+            wal_sync_mb: 1,
+            rate_limit_mb: Some(64),
+            ..StorageConfig::default()
+        };
+
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path_with_config(&path, &config).unwrap());
+        let genesis_block = blockchain.get_block_from_block_number(0).unwrap();
+
+        blockchain.compact_storage();
+        assert_eq!(blockchain.get_block_from_block_number(0).unwrap(), genesis_block);
+
+        // the storage can be reopened with the default configuration
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+        assert_eq!(blockchain.get_block_from_block_number(0).unwrap(), genesis_block);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn open_with_cache_limit() {
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+
+        let config = StorageConfig {
+            block_cache_mb: 16,
+            max_cache_mb: Some(NUM_COLS as u64 * 4),
+            ..StorageConfig::default()
+        };
+
+        // the cache limit is shared by the columns
+        assert_eq!(config.column_cache_mb(NUM_COLS), 4);
+        assert_eq!(config.column_cache_mb(1), 16);
+        assert_eq!(StorageConfig::default().column_cache_mb(NUM_COLS), 8);
+
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path_with_config(&path, &config).unwrap());
+        assert_eq!(blockchain.get_latest_block_height(), 0);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn storage_metrics() {
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+
+        let config = StorageConfig {
+            statistics: true,
+            ..StorageConfig::default()
+        };
+
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path_with_config(&path, &config).unwrap());
+        blockchain.compact_storage();
+
+        let metrics = blockchain.get_storage_metrics().unwrap();
+
+        assert_eq!(metrics.columns.len(), NUM_COLS as usize);
+        assert_eq!(metrics.columns[COL_BLOCK_HEADER as usize].name, "block_header");
+        assert_eq!(metrics.columns[COL_BLOCK_HEADER as usize].estimated_keys, 1);
+        assert!(metrics.disk_size > 0);
+        assert_eq!(
+            metrics.disk_size,
+            metrics.columns.iter().map(|column| column.disk_size).sum::<u64>()
+        );
+        assert!(metrics.write_amplification.is_some());
+
+        // the rates are only reported by the storage keeping statistics
+        let memory_blockchain: Arc<MemoryStore> = open_test_memory_blockchain();
+        let memory_metrics = memory_blockchain.get_storage_metrics().unwrap();
+
+        assert_eq!(memory_metrics.columns[COL_BLOCK_HEADER as usize].estimated_keys, 1);
+        assert_eq!(memory_metrics.disk_size, 0);
+        assert_eq!(memory_metrics.cache_hit_rate, None);
+        assert_eq!(memory_metrics.write_amplification, None);
+
+        let counters = parse_statistics_counters(
+            "rocksdb.block.cache.miss COUNT : 7\nrocksdb.db.get.micros P50 : 1.0 P95 : 2.0 COUNT : 3 SUM : 4\n",
+        );
+        assert_eq!(counters.get("rocksdb.block.cache.miss"), Some(&7));
+        assert_eq!(counters.len(), 1);
 
         kill_storage_sync(blockchain);
     }
@@ -152,4 +1129,186 @@ mod test_storage {
             kill_storage_sync(blockchain);
         }
     }
+
+    #[test]
+    pub fn startup_check_after_crash() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        // a clean shutdown skips the checks on the next startup
+        blockchain.shut_down_storage().unwrap();
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(blockchain.startup_check, Some(StartupCheck::CleanShutdown));
+
+        // the storage of a crashed node is checked and repaired from the last consistent block
+        let block_height_index = blockchain.storage.db.cf_handle("col12").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(block_height_index, block_height_index_key(0))
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(
+            blockchain.startup_check,
+            Some(StartupCheck::Recovered {
+                from_block_height: 0,
+                inconsistencies: vec![Inconsistency::InvalidBlockHeightIndex(0)],
+            })
+        );
+        assert!(blockchain.verify_integrity(false).unwrap().is_empty());
+
+        // the blocks before the last consistent block are not checked
+        let mut previous_block_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+        for nonce in 0..2 {
+            let block = test_block(previous_block_hash, nonce);
+            previous_block_hash = block.header.get_hash();
+            blockchain.insert_and_commit(&block).unwrap();
+        }
+        blockchain.mark_consistent(false).unwrap();
+
+        let block_height_index = blockchain.storage.db.cf_handle("col12").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(block_height_index, block_height_index_key(1))
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        assert_eq!(
+            blockchain.startup_check,
+            Some(StartupCheck::Recovered {
+                from_block_height: 2,
+                inconsistencies: vec![],
+            })
+        );
+        assert_eq!(blockchain.get_consistency_marker().unwrap().unwrap().block_height, 2);
+        assert_eq!(blockchain.verify_integrity(false).unwrap(), vec![
+            Inconsistency::InvalidBlockHeightIndex(1)
+        ]);
+
+        // the marker follows the decommitted blocks
+        blockchain.decommit_latest_block().unwrap();
+        assert_eq!(blockchain.get_consistency_marker().unwrap().unwrap().block_height, 1);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn height_index_range_queries() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut previous_block_hash = blockchain.get_latest_block().unwrap().header.get_hash();
+        let mut block_hashes = vec![previous_block_hash.clone()];
+
+        // insert three blocks
+        for nonce in 0..3 {
+            let block = test_block(previous_block_hash, nonce);
+            previous_block_hash = block.header.get_hash();
+            block_hashes.push(previous_block_hash.clone());
+            blockchain.insert_and_commit(&block).unwrap();
+        }
+
+        // ranges are iterated in ascending order and end at the latest block
+        let expected_block_hashes: Vec<(u32, BlockHeaderHash)> =
+            (1..=3).map(|n| (n, block_hashes[n as usize].clone())).collect();
+        let block_hashes_in_range: Vec<(u32, BlockHeaderHash)> =
+            blockchain.get_block_hashes_in_range(1, 10).unwrap().collect();
+        assert_eq!(block_hashes_in_range, expected_block_hashes);
+
+        // pages are taken from the range iterators
+        let page: Vec<u32> = blockchain
+            .get_blocks_in_range(0, 3)
+            .unwrap()
+            .skip(1)
+            .take(2)
+            .map(|block| block.unwrap().0)
+            .collect();
+        assert_eq!(page, vec![1, 2]);
+
+        // only the genesis block has a transaction
+        let transaction_ids: Vec<(u32, Vec<u8>)> = blockchain.get_transaction_ids_in_range(0, 3).unwrap().collect();
+        assert_eq!(transaction_ids, vec![(0, vec![0u8; 32])]);
+        assert_eq!(blockchain.get_transactions_in_range(0, 3).unwrap().count(), 1);
+        assert_eq!(blockchain.get_transaction_ids_in_range(1, 3).unwrap().count(), 0);
+
+        // decommitted blocks are removed from the index
+        blockchain.decommit_latest_block().unwrap();
+        assert_eq!(blockchain.get_block_hashes_in_range(0, 10).unwrap().count(), 3);
+
+        // migrating from schema version 1 rebuilds the indexes
+        let meta = blockchain.storage.db.cf_handle("col0").unwrap();
+        blockchain
+            .storage
+            .db
+            .put_cf(meta, KEY_SCHEMA_VERSION.as_bytes(), 1u32.to_le_bytes())
+            .unwrap();
+        let block_height_index = blockchain.storage.db.cf_handle("col12").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(block_height_index, block_height_index_key(1))
+            .unwrap();
+        let transaction_height_index = blockchain.storage.db.cf_handle("col13").unwrap();
+        blockchain
+            .storage
+            .db
+            .delete_cf(transaction_height_index, transaction_height_index_key(0, 0))
+            .unwrap();
+
+        drop(blockchain);
+        let blockchain: Arc<Store> = Arc::new(Store::open_at_path(&path).unwrap());
+
+        let block_hashes_in_range: Vec<(u32, BlockHeaderHash)> =
+            blockchain.get_block_hashes_in_range(1, 10).unwrap().collect();
+        assert_eq!(block_hashes_in_range, expected_block_hashes[..2].to_vec());
+        assert_eq!(blockchain.get_transaction_ids_in_range(0, 3).unwrap().count(), 1);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn secondary_storage_catch_up() {
+        let (blockchain, path): (Arc<Store>, _) = open_test_blockchain();
+
+        let mut secondary_path = path.clone().into_os_string();
+        secondary_path.push("_reader");
+        let secondary_path = std::path::PathBuf::from(secondary_path);
+
+        let secondary_blockchain: Arc<Store> =
+            Arc::new(Store::open_secondary_at_paths(&path, &secondary_path).unwrap());
+
+        assert!(!blockchain.is_secondary());
+        assert!(secondary_blockchain.is_secondary());
+
+        // insert a block in the primary instance
+        let block = test_block(blockchain.get_latest_block().unwrap().header.get_hash(), 99);
+        blockchain.insert_and_commit(&block).unwrap();
+
+        // the secondary instance follows the primary instance once it catches up
+        assert_eq!(secondary_blockchain.get_latest_block_height(), 0);
+        secondary_blockchain.catch_up_secondary(false).unwrap();
+        assert_eq!(secondary_blockchain.get_latest_block_height(), 1);
+        assert_eq!(secondary_blockchain.get_block_hash(1).unwrap(), block.header.get_hash());
+
+        // the secondary instance rejects writes
+        match secondary_blockchain.store_to_peer_book(vec![]) {
+            Err(StorageError::ReadOnlyStorage) => {}
+            result => panic!("expected a read-only storage error, found {:?}", result),
+        }
+
+        // the secondary instance follows reverted blocks
+        blockchain.decommit_latest_block().unwrap();
+        secondary_blockchain.catch_up_secondary(false).unwrap();
+        assert_eq!(secondary_blockchain.get_latest_block_height(), 0);
+
+        drop(secondary_blockchain);
+        std::fs::remove_dir_all(secondary_path).unwrap();
+        kill_storage_sync(blockchain);
+    }
 }
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger, Miner};
+use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger, Miner, TransactionMemo};
 use snarkos_dpc::base_dpc::{instantiated::*, record::DPCRecord, record_payload::RecordPayload};
 use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::dpc::{DPCScheme, Program, Record};
@@ -149,7 +149,7 @@ fn send<R: Rng>(
         dummy_flags,
         values,
         output,
-        [0u8; 32],
+        TransactionMemo::Plaintext([0u8; 32]),
         &ledger,
         rng,
     )
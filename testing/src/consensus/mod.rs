@@ -38,11 +38,9 @@ pub static TEST_CONSENSUS: Lazy<ConsensusParameters> = Lazy::new(|| {
     let inner_snark_verification_key_crh: <Components as DPCComponents>::InnerSNARKVerificationKeyCRH =
         From::from(inner_snark_verification_key_crh_parameters);
 
-    let inner_snark_id = to_bytes![
-        inner_snark_verification_key_crh
-            .hash(&InnerSNARKVKParameters::load_bytes().unwrap())
-            .unwrap()
-    ]
+    let inner_snark_id = to_bytes![inner_snark_verification_key_crh
+        .hash(&InnerSNARKVKParameters::load_bytes().unwrap())
+        .unwrap()]
     .unwrap();
 
     ConsensusParameters {
@@ -52,6 +50,9 @@ pub static TEST_CONSENSUS: Lazy<ConsensusParameters> = Lazy::new(|| {
         network: Network::Mainnet,
         verifier: PoswMarlin::verify_only().unwrap(),
         authorized_inner_snark_ids: vec![inner_snark_id],
+        fixed_difficulty_target: None,
+        max_template_size: None,
+        max_template_weight: None,
     }
 });
 
@@ -18,16 +18,22 @@ use crate::consensus::TestTx;
 use snarkos_dpc::base_dpc::instantiated::CommitmentMerkleParameters;
 use snarkos_models::{
     algorithms::merkle_tree::LoadableMerkleParameters,
+    genesis::Genesis,
     objects::{LedgerScheme, Transaction},
+    parameters::Parameters,
 };
 use snarkos_objects::Block;
-pub use snarkos_storage::Ledger;
+use snarkos_parameters::{GenesisBlock, LedgerMerkleTreeParameters};
+pub use snarkos_storage::{Ledger, MemoryStorage};
+use snarkos_utilities::bytes::FromBytes;
 
 use rand::{thread_rng, Rng};
 use std::{path::PathBuf, sync::Arc};
 
 pub type Store = Ledger<TestTx, CommitmentMerkleParameters>;
 
+pub type MemoryStore = Ledger<TestTx, CommitmentMerkleParameters, MemoryStorage>;
+
 pub fn random_storage_path() -> String {
     let random_path: usize = thread_rng().gen();
     format!("./test_db-{}", random_path)
@@ -58,6 +64,14 @@ pub fn open_test_blockchain<T: Transaction, P: LoadableMerkleParameters>() -> (A
     (storage, path)
 }
 
+// Open a test blockchain kept in memory from stored genesis attributes
+pub fn open_test_memory_blockchain<T: Transaction, P: LoadableMerkleParameters>() -> Arc<Ledger<T, P, MemoryStorage>> {
+    let crh = P::H::from(FromBytes::read(&LedgerMerkleTreeParameters::load_bytes().unwrap()[..]).unwrap());
+    let genesis_block: Block<T> = FromBytes::read(GenesisBlock::load_bytes().as_slice()).unwrap();
+
+    Arc::new(Ledger::<T, P, MemoryStorage>::new(&PathBuf::new(), P::from(crh), genesis_block).unwrap())
+}
+
 pub fn kill_storage<T: Transaction, P: LoadableMerkleParameters>(ledger: Ledger<T, P>) {
     let path = ledger.storage.db.path().to_owned();
 